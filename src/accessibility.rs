@@ -0,0 +1,371 @@
+//! Offline accessibility audit
+//!
+//! A compact rule engine modelled on axe-core ships with the server and is
+//! injected into the page, so audits work without network access or a CDN.
+//! Rules reuse axe's ids and impact levels (`image-alt`, `label`,
+//! `color-contrast`, ...) so results read the same as an axe report.
+
+use std::collections::BTreeMap;
+
+use fantoccini::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    ClientManager,
+    error::{Result, WebDriverError},
+    page_scripts::selector_for_script,
+};
+
+/// Nodes reported per rule; the full count is still returned
+const MAX_NODES_PER_RULE: usize = 20;
+
+/// Audit engine. Takes an optional scope selector, an optional list of rule ids
+/// and the per-rule node limit; returns `{ url, rules, violations }` or `{ error }`.
+const AUDIT_SCRIPT: &str = concat!(selector_for_script!(), r#"
+    const [scopeSelector, onlyRules, maxNodes] = arguments;
+    try {
+        const root = scopeSelector ? document.querySelector(scopeSelector) : document.documentElement;
+        if (!root) {
+            return { error: 'no such element: ' + scopeSelector };
+        }
+        const pageLevel = !scopeSelector;
+
+        const all = sel => Array.from(root.querySelectorAll(sel)).concat(root.matches && root.matches(sel) ? [root] : []);
+        const isHidden = el => {
+            for (let node = el; node && node.nodeType === 1; node = node.parentElement) {
+                const style = getComputedStyle(node);
+                if (style.display === 'none' || style.visibility === 'hidden' || node.getAttribute('aria-hidden') === 'true') {
+                    return true;
+                }
+            }
+            return false;
+        };
+        const visible = els => els.filter(el => !isHidden(el));
+        const text = el => (el.innerText || el.textContent || '').trim();
+        const labelledBy = el => (el.getAttribute('aria-labelledby') || '').split(/\s+/)
+            .map(id => id && document.getElementById(id)).filter(Boolean).map(text).join(' ').trim();
+        const accessibleName = el => (el.getAttribute('aria-label') || '').trim()
+            || labelledBy(el)
+            || (el.getAttribute('title') || '').trim();
+        const snippet = el => {
+            const html = el.outerHTML || '';
+            const open = html.indexOf('>') + 1;
+            return (open > 0 && open < 200 ? html.slice(0, open) : html.slice(0, 200));
+        };
+
+        const parseColor = value => {
+            const m = (value || '').match(/rgba?\(([^)]+)\)/);
+            if (!m) return null;
+            const [r, g, b, a = 1] = m[1].split(',').map(s => parseFloat(s));
+            return { r, g, b, a };
+        };
+        const luminance = ({ r, g, b }) => {
+            const channel = v => { v /= 255; return v <= 0.03928 ? v / 12.92 : Math.pow((v + 0.055) / 1.055, 2.4); };
+            return 0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b);
+        };
+        const background = el => {
+            for (let node = el; node && node.nodeType === 1; node = node.parentElement) {
+                const style = getComputedStyle(node);
+                if (style.backgroundImage && style.backgroundImage !== 'none') return null;
+                const color = parseColor(style.backgroundColor);
+                if (color && color.a >= 1) return color;
+                if (color && color.a > 0) return null;
+            }
+            return { r: 255, g: 255, b: 255, a: 1 };
+        };
+
+        const rules = [
+            { id: 'html-has-lang', impact: 'serious', pageLevel: true,
+              description: 'The <html> element must have a lang attribute',
+              help: 'Set lang on <html> (e.g. lang="en") so screen readers pick the right pronunciation',
+              check: () => (document.documentElement.getAttribute('lang') || '').trim() ? [] : [document.documentElement] },
+            { id: 'document-title', impact: 'serious', pageLevel: true,
+              description: 'Documents must have a non-empty <title>',
+              help: 'Give the page a descriptive <title>',
+              check: () => document.title.trim() ? [] : [document.documentElement] },
+            { id: 'meta-viewport', impact: 'critical', pageLevel: true,
+              description: 'Zooming and scaling must not be disabled',
+              help: 'Remove user-scalable=no and keep maximum-scale at 2 or more',
+              check: () => Array.from(document.querySelectorAll('meta[name="viewport"]')).filter(meta => {
+                  const content = (meta.getAttribute('content') || '').toLowerCase().replace(/\s/g, '');
+                  const max = content.match(/maximum-scale=([\d.]+)/);
+                  return /user-scalable=(no|0)/.test(content) || (max && parseFloat(max[1]) < 2);
+              }) },
+            { id: 'image-alt', impact: 'critical',
+              description: 'Images must have alternate text',
+              help: 'Add an alt attribute (alt="" for decorative images) or an aria-label',
+              check: () => visible(all('img')).filter(img => !img.hasAttribute('alt') && !accessibleName(img)
+                  && !['presentation', 'none'].includes(img.getAttribute('role'))) },
+            { id: 'label', impact: 'critical',
+              description: 'Form elements must have labels',
+              help: 'Associate a <label for>, wrap the control in a <label>, or add aria-label / aria-labelledby',
+              check: () => visible(all('input, select, textarea')).filter(el => {
+                  const type = (el.getAttribute('type') || '').toLowerCase();
+                  if (['hidden', 'submit', 'button', 'reset', 'image'].includes(type)) return false;
+                  if (accessibleName(el) || el.closest('label')) return false;
+                  return !(el.id && document.querySelector('label[for="' + CSS.escape(el.id) + '"]'));
+              }) },
+            { id: 'button-name', impact: 'critical',
+              description: 'Buttons must have discernible text',
+              help: 'Give the button visible text, an aria-label, or alt text on its image',
+              check: () => visible(all('button, [role="button"], input[type="submit"], input[type="button"], input[type="reset"]')).filter(el => {
+                  if (el.tagName === 'INPUT') return !(el.value || '').trim() && !accessibleName(el);
+                  const imgAlt = Array.from(el.querySelectorAll('img[alt]')).some(img => img.alt.trim());
+                  return !text(el) && !accessibleName(el) && !imgAlt;
+              }) },
+            { id: 'link-name', impact: 'serious',
+              description: 'Links must have discernible text',
+              help: 'Give the link visible text, an aria-label, or alt text on its image',
+              check: () => visible(all('a[href]')).filter(el => {
+                  const imgAlt = Array.from(el.querySelectorAll('img[alt]')).some(img => img.alt.trim());
+                  return !text(el) && !accessibleName(el) && !imgAlt;
+              }) },
+            { id: 'frame-title', impact: 'serious',
+              description: 'Frames must have an accessible name',
+              help: 'Add a title attribute describing the frame content',
+              check: () => visible(all('iframe, frame')).filter(el => !accessibleName(el)) },
+            { id: 'color-contrast', impact: 'serious',
+              description: 'Text must have sufficient contrast against its background',
+              help: 'Reach a contrast ratio of at least 4.5:1 (3:1 for large text)',
+              check: () => visible(all('body *')).filter(el => {
+                  const ownText = Array.from(el.childNodes).some(n => n.nodeType === 3 && n.textContent.trim());
+                  if (!ownText) return false;
+                  const style = getComputedStyle(el);
+                  const fg = parseColor(style.color);
+                  const bg = background(el);
+                  if (!fg || !bg || fg.a < 1) return false;
+                  const [light, dark] = [luminance(fg), luminance(bg)].sort((a, b) => b - a);
+                  const ratio = (light + 0.05) / (dark + 0.05);
+                  const size = parseFloat(style.fontSize);
+                  const bold = parseInt(style.fontWeight, 10) >= 700;
+                  const large = size >= 24 || (bold && size >= 18.66);
+                  return ratio < (large ? 3 : 4.5);
+              }) },
+            { id: 'duplicate-id', impact: 'minor',
+              description: 'id attribute values must be unique',
+              help: 'Rename duplicated ids; labels and ARIA references resolve to the first match only',
+              check: () => {
+                  const seen = new Map();
+                  all('[id]').forEach(el => seen.set(el.id, (seen.get(el.id) || []).concat([el])));
+                  return Array.from(seen.values()).filter(els => els.length > 1).map(els => els[1]);
+              } },
+            { id: 'heading-order', impact: 'moderate',
+              description: 'Heading levels should only increase by one',
+              help: 'Do not skip heading levels (e.g. an <h4> directly after an <h2>)',
+              check: () => {
+                  let previous = 0;
+                  return visible(all('h1, h2, h3, h4, h5, h6')).filter(h => {
+                      const level = parseInt(h.tagName[1], 10);
+                      const skipped = previous && level > previous + 1;
+                      previous = level;
+                      return skipped;
+                  });
+              } },
+            { id: 'empty-heading', impact: 'minor',
+              description: 'Headings must not be empty',
+              help: 'Give the heading text or remove it',
+              check: () => visible(all('h1, h2, h3, h4, h5, h6')).filter(h => !text(h) && !accessibleName(h)) },
+            { id: 'list', impact: 'serious',
+              description: '<ul> and <ol> must only directly contain <li>, <script> or <template>',
+              help: 'Wrap list content in <li> elements',
+              check: () => all('ul, ol').filter(list => Array.from(list.children)
+                  .some(child => !['LI', 'SCRIPT', 'TEMPLATE'].includes(child.tagName))) },
+            { id: 'tabindex', impact: 'serious',
+              description: 'Elements should not have a tabindex greater than zero',
+              help: 'Use tabindex="0" or -1 and order focus through the DOM instead',
+              check: () => all('[tabindex]').filter(el => parseInt(el.getAttribute('tabindex'), 10) > 0) },
+            { id: 'aria-hidden-focus', impact: 'serious',
+              description: 'aria-hidden elements must not contain focusable elements',
+              help: 'Remove focusable descendants or make them unfocusable with tabindex="-1"',
+              check: () => all('[aria-hidden="true"]').filter(el => Array.from(
+                  el.querySelectorAll('a[href], button, input, select, textarea, [tabindex]:not([tabindex="-1"])')
+              ).some(focusable => !focusable.disabled && getComputedStyle(focusable).display !== 'none')) },
+        ];
+
+        const selected = rules.filter(rule => (!rule.pageLevel || pageLevel)
+            && (!onlyRules || onlyRules.length === 0 || onlyRules.includes(rule.id)));
+        const violations = [];
+        for (const rule of selected) {
+            const offending = rule.check();
+            if (offending.length === 0) continue;
+            violations.push({
+                id: rule.id,
+                impact: rule.impact,
+                description: rule.description,
+                help: rule.help,
+                node_count: offending.length,
+                nodes: offending.slice(0, maxNodes).map(el => ({ selector: selectorFor(el), html: snippet(el) })),
+            });
+        }
+        return { url: location.href, rules: selected.map(rule => rule.id), violations };
+    } catch (e) {
+        return { error: String(e) };
+    }
+"#);
+
+/// Severity of a violation, in axe's terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Impact {
+    Minor,
+    Moderate,
+    Serious,
+    Critical,
+}
+
+impl Impact {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "minor" => Some(Self::Minor),
+            "moderate" => Some(Self::Moderate),
+            "serious" => Some(Self::Serious),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Minor => "minor",
+            Self::Moderate => "moderate",
+            Self::Serious => "serious",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// An element that breaks a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViolationNode {
+    pub selector: String,
+    /// Opening tag of the element
+    pub html: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Violation {
+    /// Rule id, matching axe-core's
+    pub id: String,
+    pub impact: Impact,
+    pub description: String,
+    /// How to fix it
+    pub help: String,
+    /// Number of offending elements; `nodes` lists at most the first 20
+    pub node_count: usize,
+    pub nodes: Vec<ViolationNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditReport {
+    pub url: String,
+    /// Rules that were checked
+    pub rules: Vec<String>,
+    pub violations: Vec<Violation>,
+}
+
+impl AuditReport {
+    /// Violations grouped by impact, most severe first
+    pub fn by_impact(&self) -> Vec<(Impact, Vec<&Violation>)> {
+        let mut groups: BTreeMap<Impact, Vec<&Violation>> = BTreeMap::new();
+        for violation in &self.violations {
+            groups.entry(violation.impact).or_default().push(violation);
+        }
+        groups.into_iter().rev().collect()
+    }
+
+    /// Violations at or above `threshold`
+    pub fn failing(&self, threshold: Impact) -> Vec<&Violation> {
+        self.violations.iter().filter(|v| v.impact >= threshold).collect()
+    }
+
+    /// Human-readable report, grouped by impact
+    pub fn summary(&self) -> String {
+        if self.violations.is_empty() {
+            return format!("No accessibility violations found on {} ({} rules checked)", self.url, self.rules.len());
+        }
+
+        let mut text = format!(
+            "{} accessibility violation(s) on {} ({} rules checked):\n",
+            self.violations.len(),
+            self.url,
+            self.rules.len()
+        );
+        for (impact, violations) in self.by_impact() {
+            text.push_str(&format!("\n{}:\n", impact.as_str().to_uppercase()));
+            for violation in violations {
+                text.push_str(&format!(
+                    "- {} ({} element(s)): {}\n  Fix: {}\n",
+                    violation.id, violation.node_count, violation.description, violation.help
+                ));
+                for node in &violation.nodes {
+                    text.push_str(&format!("    {}\n", node.selector));
+                }
+            }
+        }
+        text
+    }
+
+    /// Counts per impact level, for the structured result
+    pub fn counts(&self) -> serde_json::Value {
+        let mut counts = json!({ "critical": 0, "serious": 0, "moderate": 0, "minor": 0 });
+        for violation in &self.violations {
+            counts[violation.impact.as_str()] = json!(counts[violation.impact.as_str()].as_u64().unwrap_or(0) + 1);
+        }
+        counts
+    }
+}
+
+/// Run the audit on the current page of `session`, optionally scoped to the first
+/// element matching `scope` and limited to the rule ids in `rules`
+pub async fn run_audit(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    scope: Option<&str>,
+    rules: &[String],
+) -> Result<AuditReport> {
+    let result = client_manager
+        .run_command(
+            session,
+            "audit_accessibility",
+            client.execute(AUDIT_SCRIPT, vec![json!(scope), json!(rules), json!(MAX_NODES_PER_RULE)]),
+        )
+        .await?;
+
+    if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+        return Err(WebDriverError::Execution(format!("Accessibility audit script failed: {error}")));
+    }
+    serde_json::from_value(result)
+        .map_err(|e| WebDriverError::Serialization(format!("Unexpected accessibility audit result: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_groups_and_thresholds_by_impact() {
+        let report: AuditReport = serde_json::from_value(json!({
+            "url": "https://example.com/",
+            "rules": ["image-alt", "heading-order", "duplicate-id"],
+            "violations": [
+                { "id": "duplicate-id", "impact": "minor", "description": "d", "help": "h", "node_count": 1,
+                  "nodes": [{ "selector": "#a", "html": "<div id=\"a\">" }] },
+                { "id": "image-alt", "impact": "critical", "description": "d", "help": "h", "node_count": 3,
+                  "nodes": [{ "selector": "img:nth-of-type(1)", "html": "<img>" }] },
+            ]
+        }))
+        .unwrap();
+
+        let groups = report.by_impact();
+        assert_eq!(groups[0].0, Impact::Critical);
+        assert_eq!(groups[1].0, Impact::Minor);
+
+        assert_eq!(report.failing(Impact::Serious).len(), 1);
+        assert_eq!(report.failing(Impact::Minor).len(), 2);
+        assert_eq!(report.counts()["critical"], 1);
+        assert!(report.summary().starts_with("2 accessibility violation(s)"));
+        assert_eq!(Impact::from_name("Serious"), Some(Impact::Serious));
+    }
+}
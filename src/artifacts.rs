@@ -0,0 +1,351 @@
+//! Files written on behalf of tool calls and recipe runs
+//!
+//! Screenshots, PDFs, HAR files, traces and recipe outputs all land under one
+//! artifacts root, in a subdirectory per server run (and per recipe run inside
+//! it). Callers name files relative to that directory instead of passing
+//! arbitrary filesystem paths. Files written for an authenticated principal go
+//! below `users/<principal>/` instead, and each principal lists only their own.
+//! Old files are pruned by age and by the total size of the root, every
+//! `PRUNE_INTERVAL` or once `PRUNE_AFTER_BYTES` were written since the last time.
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    config::Config,
+    error::{Result, WebDriverError},
+};
+
+/// Artifacts root used when `artifacts_dir` is not configured
+pub const DEFAULT_ARTIFACTS_DIR: &str = "./artifacts";
+
+/// Directory below the root holding each principal's artifacts
+const USERS_DIR: &str = "users";
+
+/// Longest time between two prunes while artifacts are being written
+const PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Bytes written since the last prune that trigger the next one early
+const PRUNE_AFTER_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A file stored under the artifacts root
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtifactInfo {
+    /// Run directory the file belongs to
+    pub run: String,
+    /// Path relative to the artifacts root
+    pub path: String,
+    pub size_bytes: u64,
+    /// Last modification time (RFC 3339)
+    pub modified: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+    run_id: String,
+    max_age: Option<Duration>,
+    max_bytes: Option<u64>,
+    /// Bytes written since the last prune, and when that was; shared by clones
+    written_since_prune: Arc<AtomicU64>,
+    last_prune: Arc<Mutex<Instant>>,
+}
+
+impl ArtifactStore {
+    /// Store rooted at `root` that never prunes
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            run_id: format!("{}-{}", Utc::now().format("%Y%m%d-%H%M%S"), std::process::id()),
+            max_age: None,
+            max_bytes: None,
+            written_since_prune: Arc::default(),
+            last_prune: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_age: config
+                .artifacts_retention_hours
+                .map(|hours| Duration::from_secs(hours * 3600)),
+            max_bytes: config.artifacts_max_bytes,
+            ..Self::new(config.artifacts_dir.as_deref().unwrap_or(DEFAULT_ARTIFACTS_DIR))
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Name of this server run's subdirectory
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Directory the current principal's runs are stored in: the root, or
+    /// `users/<principal>` below it. Artifact paths are relative to it.
+    pub fn base_dir(&self) -> PathBuf {
+        match crate::ClientManager::current_principal() {
+            Some(principal) => self.root.join(USERS_DIR).join(Self::principal_dir(&principal)),
+            None => self.root.clone(),
+        }
+    }
+
+    /// Write `data` to `name` inside this run's directory, below `scope` when given
+    /// (e.g. a recipe run), on the blocking thread pool. Prunes old artifacts when
+    /// a prune is due. Returns the written path.
+    pub async fn write(&self, scope: Option<&str>, name: &str, data: Vec<u8>) -> Result<PathBuf> {
+        let mut path = self.base_dir().join(&self.run_id);
+        if let Some(scope) = scope {
+            path.push(Self::relative_path(scope)?);
+        }
+        path.push(Self::relative_path(name)?);
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    WebDriverError::FileSystem(format!("Failed to create {}: {e}", parent.display()))
+                })?;
+            }
+            fs::write(&path, &data)
+                .map_err(|e| WebDriverError::FileSystem(format!("Failed to write {}: {e}", path.display())))?;
+
+            if store.prune_due(data.len() as u64)
+                && let Err(e) = store.prune()
+            {
+                tracing::warn!("Failed to prune artifacts: {}", e);
+            }
+            Ok(path)
+        })
+        .await
+        .map_err(|e| WebDriverError::FileSystem(format!("Failed to write artifact: {e}")))?
+    }
+
+    /// The current principal's artifacts, newest first, optionally limited to one run
+    pub async fn list(&self, run: Option<&str>) -> Result<Vec<ArtifactInfo>> {
+        let base = self.base_dir();
+        let run = run.map(str::to_string);
+        tokio::task::spawn_blocking(move || Self::list_in(&base, run.as_deref()))
+            .await
+            .map_err(|e| WebDriverError::FileSystem(format!("Failed to list artifacts: {e}")))?
+    }
+
+    fn list_in(base: &Path, run: Option<&str>) -> Result<Vec<ArtifactInfo>> {
+        let mut files = Self::files_in(base)?;
+        files.sort_by_key(|(_, modified, _)| std::cmp::Reverse(*modified));
+
+        Ok(files
+            .into_iter()
+            .filter_map(|(path, modified, size_bytes)| {
+                let relative = path.strip_prefix(base).ok()?;
+                let file_run = relative
+                    .components()
+                    .next()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())?;
+                // Other principals' artifacts live below the shared root
+                if file_run == USERS_DIR || run.is_some_and(|run| run != file_run) {
+                    return None;
+                }
+                Some(ArtifactInfo {
+                    run: file_run,
+                    path: relative.to_string_lossy().replace('\\', "/"),
+                    size_bytes,
+                    modified: DateTime::<Utc>::from(modified).to_rfc3339(),
+                })
+            })
+            .collect())
+    }
+
+    /// Count `written` bytes towards the next prune and tell whether it is due
+    fn prune_due(&self, written: u64) -> bool {
+        if self.max_age.is_none() && self.max_bytes.is_none() {
+            return false;
+        }
+        let since = self.written_since_prune.fetch_add(written, Ordering::Relaxed) + written;
+        let mut last_prune = self.last_prune.lock().unwrap_or_else(|e| e.into_inner());
+        if since < PRUNE_AFTER_BYTES && last_prune.elapsed() < PRUNE_INTERVAL {
+            return false;
+        }
+        *last_prune = Instant::now();
+        self.written_since_prune.store(0, Ordering::Relaxed);
+        true
+    }
+
+    /// Delete files older than the retention period, then the oldest files until
+    /// the root fits the size limit. Returns the number of files removed.
+    pub fn prune(&self) -> Result<usize> {
+        if self.max_age.is_none() && self.max_bytes.is_none() {
+            return Ok(0);
+        }
+
+        let mut files = Self::files_in(&self.root)?;
+        files.sort_by_key(|(_, modified, _)| *modified);
+
+        let now = SystemTime::now();
+        let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+        let mut removed = 0;
+        for (path, modified, size) in &files {
+            let expired = self
+                .max_age
+                .is_some_and(|max_age| now.duration_since(*modified).unwrap_or_default() > max_age);
+            let over_size = self.max_bytes.is_some_and(|max_bytes| total > max_bytes);
+            if !expired && !over_size {
+                continue;
+            }
+            if fs::remove_file(path).is_ok() {
+                total -= size;
+                removed += 1;
+            }
+        }
+
+        if removed > 0 {
+            Self::remove_empty_dirs(&self.root);
+        }
+        Ok(removed)
+    }
+
+    /// Every file under `dir` with its modification time and size
+    fn files_in(dir: &Path) -> Result<Vec<(PathBuf, SystemTime, u64)>> {
+        let mut files = Vec::new();
+        if dir.is_dir() {
+            Self::collect_files(dir, &mut files)?;
+        }
+        Ok(files)
+    }
+
+    /// Directory name of `principal`: letters, digits and `-` kept, every other
+    /// byte written as `_xx`, so no two principals share one and none escapes `users/`
+    fn principal_dir(principal: &str) -> String {
+        principal
+            .bytes()
+            .map(|byte| match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' => (byte as char).to_string(),
+                _ => format!("_{byte:02x}"),
+            })
+            .collect()
+    }
+
+    fn collect_files(dir: &Path, files: &mut Vec<(PathBuf, SystemTime, u64)>) -> Result<()> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| WebDriverError::FileSystem(format!("Failed to read {}: {e}", dir.display())))?;
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                Self::collect_files(&entry.path(), files)?;
+            } else if metadata.is_file() {
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                files.push((entry.path(), modified, metadata.len()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove directories left empty by pruning, keeping the root itself
+    fn remove_empty_dirs(dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                Self::remove_empty_dirs(&path);
+                // Fails while the directory still has files, which is what we want
+                let _ = fs::remove_dir(&path);
+            }
+        }
+    }
+
+    /// Validate a caller-supplied name: relative, without `..` or a drive prefix
+    fn relative_path(value: &str) -> Result<PathBuf> {
+        let path = Path::new(value);
+        let valid = !value.trim().is_empty()
+            && path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !valid {
+            return Err(WebDriverError::FileSystem(format!(
+                "Invalid artifact path '{value}': use a relative path inside the artifacts directory, without '..'"
+            )));
+        }
+        Ok(path.to_path_buf())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_list_and_prune_by_size() {
+        let root = std::env::temp_dir().join(format!("artifacts-test-{}", uuid::Uuid::new_v4().simple()));
+        let store = ArtifactStore::new(&root);
+
+        assert!(store.write(None, "/etc/passwd", b"x".to_vec()).await.is_err());
+        assert!(store.write(None, "../escape.png", b"x".to_vec()).await.is_err());
+        assert!(store.write(Some("recipes/../.."), "a.png", b"x".to_vec()).await.is_err());
+
+        let first = store.write(Some("recipes/login"), "first.png", vec![0; 100]).await.unwrap();
+        assert!(first.starts_with(root.join(store.run_id()).join("recipes/login")));
+        std::thread::sleep(Duration::from_millis(20));
+        store.write(None, "second.png", vec![0; 100]).await.unwrap();
+
+        let listed = store.list(None).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].path, format!("{}/second.png", store.run_id()));
+        assert_eq!(listed[0].run, store.run_id());
+        assert!(store.list(Some("other-run")).await.unwrap().is_empty());
+
+        // Over the size limit the oldest file goes first, and its empty directories with it
+        let limited = ArtifactStore { max_bytes: Some(150), ..store.clone() };
+        assert_eq!(limited.prune().unwrap(), 1);
+        assert!(!first.exists());
+        assert!(!root.join(store.run_id()).join("recipes").exists());
+        assert_eq!(store.list(None).await.unwrap().len(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_artifacts_are_private_to_their_principal() {
+        let root = std::env::temp_dir().join(format!("artifacts-test-{}", uuid::Uuid::new_v4().simple()));
+        let store = ArtifactStore::new(&root);
+        let as_user = |principal: &str| Some(principal.to_string());
+
+        store.write(None, "shared.png", vec![0; 10]).await.unwrap();
+        let written = crate::ClientManager::with_principal(as_user("../alice"), store.write(None, "a.png", vec![0; 10]))
+            .await
+            .unwrap();
+        assert!(written.starts_with(root.join("users/_2e_2e_2falice")));
+
+        let listed = crate::ClientManager::with_principal(as_user("../alice"), store.list(None)).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].path, format!("{}/a.png", store.run_id()));
+        assert!(crate::ClientManager::with_principal(as_user("bob"), store.list(None)).await.unwrap().is_empty());
+        assert_eq!(store.list(None).await.unwrap().len(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_prune_runs_after_enough_bytes() {
+        let store = ArtifactStore { max_bytes: Some(1), ..ArtifactStore::new("unused") };
+        assert!(!store.prune_due(PRUNE_AFTER_BYTES / 2));
+        assert!(store.prune_due(PRUNE_AFTER_BYTES / 2));
+        assert!(!store.prune_due(1));
+        assert!(!ArtifactStore::new("unused").prune_due(PRUNE_AFTER_BYTES));
+    }
+}
@@ -0,0 +1,251 @@
+//! Tool-call audit log
+//!
+//! Every `tools/call` is recorded with its (redacted) arguments, target session,
+//! duration, outcome and, in HTTP mode, the authenticated client. Entries are kept
+//! in a bounded in-memory buffer for `get_audit_log` and, when configured, appended
+//! to a JSONL file that is rotated once it grows past the configured size.
+
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{config::Config, rotating_file::RotatingFile};
+
+/// Number of entries kept in memory for `get_audit_log`
+const MEMORY_CAPACITY: usize = 1000;
+
+/// Argument keys whose values are never written to the audit log. The storage
+/// maps of `login_via_token` hold tokens under whatever names the app picked.
+const SENSITIVE_KEYS: &[&str] = &[
+    "password", "passwd", "secret", "token", "api_key", "apikey", "authorization", "cookie",
+    "credential", "otp", "totp", "local_storage", "session_storage",
+];
+
+/// Selector fragments marking a field whose typed text is secret, besides `SENSITIVE_KEYS`
+/// (which already cover e.g. `input[type=password]`)
+const SENSITIVE_FIELDS: &[&str] = &["cvv", "cvc", "cc-number", "card-number", "cardnumber", "ssn", "passcode"];
+
+/// Keys naming the element that `send_keys`, `fill_and_submit_form` fields and recipe steps type into
+const TARGET_KEYS: &[&str] = &["selector", "target", "field", "label"];
+
+/// Keys carrying the text typed into that element
+const TYPED_KEYS: &[&str] = &["text", "value", "keys"];
+
+const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Success,
+    Error,
+    /// Refused before dispatch, e.g. because the tool is disabled
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    pub tool: String,
+    pub arguments: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub duration_ms: u64,
+    pub outcome: AuditOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Authenticated user or MCP session in HTTP mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+}
+
+/// Shared audit recorder; clones write to the same buffer and file
+#[derive(Clone)]
+pub struct AuditLog {
+    recent: Arc<Mutex<VecDeque<AuditEntry>>>,
+    file: Option<Arc<Mutex<RotatingFile>>>,
+}
+
+impl AuditLog {
+    pub fn from_config(config: &Config) -> Self {
+        let file = config.audit_log_path.as_ref().map(|path| {
+            Arc::new(Mutex::new(RotatingFile::new(path, config.audit_log_max_bytes, config.audit_log_max_files)))
+        });
+
+        Self {
+            recent: Arc::new(Mutex::new(VecDeque::new())),
+            file,
+        }
+    }
+
+    pub fn record(&self, entry: AuditEntry) {
+        if let Some(file) = &self.file {
+            match serde_json::to_string(&entry) {
+                Ok(line) => {
+                    let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                    if let Err(e) = file.write_record(format!("{line}\n").as_bytes()) {
+                        tracing::warn!("Failed to write audit log entry to {}: {}", file.path.display(), e);
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to serialize audit log entry: {}", e),
+            }
+        }
+
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        recent.push_back(entry);
+        while recent.len() > MEMORY_CAPACITY {
+            recent.pop_front();
+        }
+    }
+
+    /// Most recent entries first, optionally restricted to one tool and/or session
+    pub fn recent(&self, limit: usize, tool: Option<&str>, session_id: Option<&str>) -> Vec<AuditEntry> {
+        self.recent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .rev()
+            .filter(|entry| tool.is_none_or(|tool| entry.tool == tool))
+            .filter(|entry| session_id.is_none_or(|sid| entry.session_id.as_deref() == Some(sid)))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    pub fn file_path(&self) -> Option<PathBuf> {
+        self.file
+            .as_ref()
+            .map(|file| file.lock().unwrap_or_else(|e| e.into_inner()).path.clone())
+    }
+
+    /// Copy of the tool arguments with credentials and secrets masked
+    pub fn redact_arguments(arguments: &Option<Map<String, Value>>) -> Value {
+        arguments
+            .as_ref()
+            .map(|args| Self::redact(&Value::Object(args.clone())))
+            .unwrap_or(Value::Null)
+    }
+
+    fn redact(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                // Text typed into a password or card field is as secret as a password argument
+                let secret_target = TARGET_KEYS.iter().any(|key| {
+                    map.get(*key).and_then(Value::as_str).is_some_and(|target| {
+                        let target = target.to_lowercase();
+                        Self::is_sensitive(&target) || SENSITIVE_FIELDS.iter().any(|field| target.contains(field))
+                    })
+                });
+                Value::Object(
+                    map.iter()
+                        .map(|(key, value)| {
+                            let typed = secret_target && TYPED_KEYS.contains(&key.as_str());
+                            let redacted = if typed || Self::is_sensitive(key) {
+                                Value::String(REDACTED.to_string())
+                            } else {
+                                Self::redact(value)
+                            };
+                            (key.clone(), redacted)
+                        })
+                        .collect(),
+                )
+            }
+            Value::Array(items) => Value::Array(items.iter().map(Self::redact).collect()),
+            other => other.clone(),
+        }
+    }
+
+    fn is_sensitive(key: &str) -> bool {
+        let key = key.to_lowercase();
+        SENSITIVE_KEYS.iter().any(|sensitive| key.contains(sensitive))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_redaction_and_rotation() {
+        let arguments = json!({
+            "username": "alice",
+            "password": "hunter2",
+            "fields": { "#password": "hunter2", "#email": "a@example.com" },
+            "headers": [{ "Authorization": "Bearer abc" }]
+        });
+        let redacted = AuditLog::redact_arguments(&arguments.as_object().cloned());
+        assert_eq!(redacted["username"], "alice");
+        assert_eq!(redacted["password"], REDACTED);
+        assert_eq!(redacted["fields"]["#password"], REDACTED);
+        assert_eq!(redacted["fields"]["#email"], "a@example.com");
+        assert_eq!(redacted["headers"][0]["Authorization"], REDACTED);
+
+        // Text typed into password and card fields, directly or in form fields and steps
+        let typed = json!({
+            "selector": "input[type='password']",
+            "text": "hunter2",
+            "fields": [
+                { "selector": "#cc-number", "value": "4111111111111111" },
+                { "selector": "#email", "value": "a@example.com" }
+            ],
+            "steps": [{ "action": "send_keys", "arguments": { "selector": "#password", "text": "x" } }]
+        });
+        let redacted = AuditLog::redact_arguments(&typed.as_object().cloned());
+        assert_eq!(redacted["text"], REDACTED);
+        assert_eq!(redacted["selector"], "input[type='password']");
+        assert_eq!(redacted["fields"][0]["value"], REDACTED);
+        assert_eq!(redacted["fields"][1]["value"], "a@example.com");
+        assert_eq!(redacted["steps"][0]["arguments"]["text"], REDACTED);
+
+        // login_via_token entries are secret whatever they are called
+        let login = json!({
+            "url": "https://app.test/",
+            "cookies": [{ "name": "sid", "value": "s3cr3t" }],
+            "local_storage": { "auth": "eyJhbGciOi", "theme": "dark" },
+            "session_storage": { "state": { "user": 7 } }
+        });
+        let redacted = AuditLog::redact_arguments(&login.as_object().cloned());
+        assert_eq!(redacted["url"], "https://app.test/");
+        assert_eq!(redacted["cookies"], REDACTED);
+        assert_eq!(redacted["local_storage"], REDACTED);
+        assert_eq!(redacted["session_storage"], REDACTED);
+        assert!(!redacted.to_string().contains("s3cr3t") && !redacted.to_string().contains("eyJ"));
+
+        let dir = std::env::temp_dir().join(format!("audit-test-{}", uuid::Uuid::new_v4().simple()));
+        let path = dir.join("audit.jsonl");
+        let mut config = Config::from_env();
+        config.audit_log_path = Some(path.to_string_lossy().into_owned());
+        config.audit_log_max_bytes = 200;
+        config.audit_log_max_files = 2;
+
+        let audit = AuditLog::from_config(&config);
+        for i in 0..10 {
+            audit.record(AuditEntry {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                tool: if i % 2 == 0 { "navigate" } else { "click" }.to_string(),
+                arguments: json!({ "i": i }),
+                session_id: Some("default".to_string()),
+                duration_ms: 1,
+                outcome: AuditOutcome::Success,
+                error: None,
+                client: None,
+            });
+        }
+
+        assert!(path.exists());
+        assert!(RotatingFile::rotated_path(&path, 1).exists());
+        assert!(!RotatingFile::rotated_path(&path, 3).exists());
+
+        let navigations = audit.recent(3, Some("navigate"), None);
+        assert_eq!(navigations.len(), 3);
+        assert_eq!(navigations[0].arguments["i"], 8);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
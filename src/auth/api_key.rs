@@ -0,0 +1,230 @@
+//! Static API key authentication for the HTTP and WebSocket transports
+//!
+//! A lighter alternative to the OAuth flow for homelab and CI setups. Keys come
+//! from the config (`WEBDRIVER_API_KEYS`) and/or a keys file that is re-read
+//! whenever it changes, so keys can be rotated without restarting the server.
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderMap, Request, StatusCode, header::AUTHORIZATION},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    auth::oauth::AccessToken,
+    config::Config,
+    error::{Result, WebDriverError},
+};
+
+/// A configured key and the id it is reported under
+#[derive(Clone)]
+struct ApiKey {
+    id: String,
+    key: String,
+}
+
+struct KeysFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    keys: Vec<ApiKey>,
+}
+
+impl KeysFile {
+    /// Re-read the file if its modification time changed since the last load
+    fn refresh(&mut self) -> Result<()> {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if self.modified.is_some() && modified == self.modified {
+            return Ok(());
+        }
+
+        let contents = fs::read_to_string(&self.path).map_err(|e| {
+            WebDriverError::FileSystem(format!("Failed to read API keys file {}: {e}", self.path.display()))
+        })?;
+        self.keys = ApiKeyStore::parse_keys(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        );
+        self.modified = modified;
+        tracing::info!("Loaded {} API keys from {}", self.keys.len(), self.path.display());
+        Ok(())
+    }
+}
+
+/// Validates API keys presented in a request header
+#[derive(Clone)]
+pub struct ApiKeyStore {
+    keys: Arc<Vec<ApiKey>>,
+    file: Option<Arc<Mutex<KeysFile>>>,
+    header: String,
+}
+
+impl ApiKeyStore {
+    /// Build the store from config; `Ok(None)` when no API keys are configured
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        if !config.api_key_auth_enabled() {
+            return Ok(None);
+        }
+
+        let file = match &config.api_keys_file {
+            Some(path) => {
+                let mut file = KeysFile {
+                    path: PathBuf::from(path),
+                    modified: None,
+                    keys: Vec::new(),
+                };
+                file.refresh()?;
+                Some(Arc::new(Mutex::new(file)))
+            }
+            None => None,
+        };
+
+        Ok(Some(Self {
+            keys: Arc::new(Self::parse_keys(config.api_keys.iter().map(String::as_str))),
+            file,
+            header: config.api_key_header.clone(),
+        }))
+    }
+
+    /// Check the configured header and return the caller as an access token whose
+    /// `user_id` is `apikey:<id>`
+    pub fn authenticate(&self, headers: &HeaderMap) -> Option<AccessToken> {
+        let value = headers.get(self.header.as_str())?.to_str().ok()?;
+        let presented = if self.header.eq_ignore_ascii_case(AUTHORIZATION.as_str()) {
+            value.strip_prefix("Bearer ")?
+        } else {
+            value
+        };
+
+        let id = self.find_key_id(presented.trim())?;
+        Some(AccessToken {
+            token: presented.to_string(),
+            token_type: "ApiKey".to_string(),
+            expires_in: None,
+            refresh_token: None,
+            scope: None,
+            user_id: format!("apikey:{id}"),
+        })
+    }
+
+    fn find_key_id(&self, presented: &str) -> Option<String> {
+        if let Some(key) = self.keys.iter().find(|k| constant_time_eq(&k.key, presented)) {
+            return Some(key.id.clone());
+        }
+
+        let file = self.file.as_ref()?;
+        let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = file.refresh() {
+            // Keep serving the last good set of keys
+            tracing::warn!("{}", e);
+        }
+        file.keys
+            .iter()
+            .find(|k| constant_time_eq(&k.key, presented))
+            .map(|k| k.id.clone())
+    }
+
+    /// Parse `id:key` entries; bare keys get a positional id such as `key-2`
+    fn parse_keys<'a>(entries: impl Iterator<Item = &'a str>) -> Vec<ApiKey> {
+        entries
+            .enumerate()
+            .map(|(index, entry)| match entry.split_once(':') {
+                Some((id, key)) => ApiKey {
+                    id: id.trim().to_string(),
+                    key: key.trim().to_string(),
+                },
+                None => ApiKey {
+                    id: format!("key-{}", index + 1),
+                    key: entry.to_string(),
+                },
+            })
+            .filter(|k| !k.key.is_empty())
+            .collect()
+    }
+}
+
+/// Compare without short-circuiting on the first differing byte
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Reject requests without a valid API key; the caller is attached as an
+/// [`AccessToken`] extension, like an OAuth-authenticated request
+pub async fn api_key_middleware(
+    State(store): State<ApiKeyStore>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    match store.authenticate(request.headers()) {
+        Some(token_info) => {
+            request.extensions_mut().insert(token_info);
+            next.run(request).await
+        }
+        None => (StatusCode::UNAUTHORIZED, "Missing or invalid API key").into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            value.parse().unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_static_keys_custom_header_and_file_rotation() {
+        let dir = std::env::temp_dir().join(format!("api-keys-test-{}", uuid::Uuid::new_v4().simple()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("keys.txt");
+        fs::write(&path, "# CI runners\nci:file-key-1\n").unwrap();
+
+        let mut config = Config::from_env();
+        config.api_keys = vec!["homelab:static-key".to_string(), "bare-key".to_string()];
+        config.api_keys_file = Some(path.to_string_lossy().into_owned());
+        config.api_key_header = "Authorization".to_string();
+
+        let store = ApiKeyStore::from_config(&config).unwrap().unwrap();
+        let token = store.authenticate(&headers("Authorization", "Bearer static-key")).unwrap();
+        assert_eq!(token.user_id, "apikey:homelab");
+        assert_eq!(
+            store.authenticate(&headers("Authorization", "Bearer bare-key")).unwrap().user_id,
+            "apikey:key-2"
+        );
+        assert!(store.authenticate(&headers("Authorization", "static-key")).is_none());
+        assert!(store.authenticate(&headers("Authorization", "Bearer wrong")).is_none());
+        assert!(store.authenticate(&headers("Authorization", "Bearer file-key-1")).is_some());
+
+        // Rotate: the old file key stops working, the new one is accepted
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(&path, "ci:file-key-2\n").unwrap();
+        let file = store.file.as_ref().unwrap();
+        file.lock().unwrap().modified = Some(SystemTime::UNIX_EPOCH);
+        assert_eq!(
+            store.authenticate(&headers("Authorization", "Bearer file-key-2")).unwrap().user_id,
+            "apikey:ci"
+        );
+        assert!(store.authenticate(&headers("Authorization", "Bearer file-key-1")).is_none());
+
+        config.api_key_header = "X-API-Key".to_string();
+        let store = ApiKeyStore::from_config(&config).unwrap().unwrap();
+        assert!(store.authenticate(&headers("X-API-Key", "static-key")).is_some());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,5 +1,11 @@
+pub mod api_key;
+pub mod jwt;
 pub mod oauth;
 pub mod keycloak;
+pub mod scopes;
 
+pub use api_key::*;
+pub use jwt::*;
 pub use oauth::*;
-pub use keycloak::*;
\ No newline at end of file
+pub use keycloak::*;
+pub use scopes::*;
\ No newline at end of file
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use askama::Template;
 use axum::{
     body::Body,
-    extract::{Form, Query, State},
+    extract::{Form, OriginalUri, Query, State},
     http::{Request, StatusCode, HeaderMap, header::{SET_COOKIE, COOKIE}},
     middleware::Next,
     response::{Html, IntoResponse, Json, Redirect, Response},
@@ -133,7 +133,7 @@ impl OAuthStore {
             <li>Execute WebDriver commands</li>
             <li>Manage browser sessions</li>
         </ul>
-        <form method="post" action="/oauth/callback">
+        <form method="post" action="callback">
             <input type="hidden" name="state" value="{{ state }}">
             <button type="submit" name="action" value="authorize" class="btn btn-primary">Authorize</button>
             <button type="submit" name="action" value="deny" class="btn btn-secondary">Deny</button>
@@ -432,7 +432,8 @@ pub async fn validate_token_middleware(
     }
 }
 
-/// Helper function to extract base URL from request
+/// Helper function to extract base URL from request, including the prefix the
+/// router is nested under when served at a base path
 fn get_base_url_from_request(request: &Request<Body>) -> String {
     let scheme = "http"; // TODO: Detect HTTPS
     let host = request
@@ -440,7 +441,12 @@ fn get_base_url_from_request(request: &Request<Body>) -> String {
         .get("host")
         .and_then(|h| h.to_str().ok())
         .unwrap_or("localhost:8080");
-    format!("{scheme}://{host}")
+    let prefix = request
+        .extensions()
+        .get::<OriginalUri>()
+        .and_then(|original| original.path().strip_suffix(request.uri().path()))
+        .unwrap_or_default();
+    format!("{scheme}://{host}{prefix}")
 }
 
 /// OAuth 2.0 Authorization Server Metadata endpoint (RFC 8414)
@@ -0,0 +1,181 @@
+//! OAuth scope based tool authorization
+//!
+//! Tokens carrying `browser:*` scopes are limited to the tools those scopes cover,
+//! so each agent can get a least-privilege token:
+//!
+//! - `browser:read` — read-only browser tools (page text, screenshots, element info, ...),
+//!   not the read-only driver management and admin tools
+//! - `browser:interact` — automation, scripting, performance and recipe tools
+//! - `browser:admin` — everything, including driver management and admin tools
+//! - `browser:<category>` — one tool category, e.g. `browser:scripting`
+
+use serde_json::{Value, json};
+
+use crate::{
+    Config, ErrorCode,
+    auth::oauth::AccessToken,
+    tools::{ToolCategory, ToolDefinitions},
+};
+
+pub const SCOPE_PREFIX: &str = "browser:";
+pub const SCOPE_READ: &str = "browser:read";
+pub const SCOPE_INTERACT: &str = "browser:interact";
+pub const SCOPE_ADMIN: &str = "browser:admin";
+
+tokio::task_local! {
+    /// Scopes of the tool call being served; `None` means unrestricted
+    static GRANTED: Option<GrantedScopes>;
+}
+
+/// The `browser:*` scopes granted to a token
+#[derive(Debug, Clone, Default)]
+pub struct GrantedScopes {
+    scopes: Vec<String>,
+}
+
+impl GrantedScopes {
+    /// Parse the token's space-separated `scope` claim, keeping only `browser:*` scopes
+    pub fn from_token(token: &AccessToken) -> Self {
+        let scopes = token
+            .scope
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .filter(|scope| scope.starts_with(SCOPE_PREFIX))
+            .map(str::to_string)
+            .collect();
+        Self { scopes }
+    }
+
+    /// Run `future` as part of a tool call limited to `scopes`
+    pub async fn with_scopes<F: std::future::Future>(scopes: Option<GrantedScopes>, future: F) -> F::Output {
+        GRANTED.scope(scopes, future).await
+    }
+
+    /// Scopes of the tool call being served, if it is limited
+    pub fn current() -> Option<GrantedScopes> {
+        GRANTED.try_with(|scopes| scopes.clone()).ok().flatten()
+    }
+
+    /// Whether the token carries any `browser:*` scope at all
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[String] {
+        &self.scopes
+    }
+
+    pub fn allows(&self, tool_name: &str) -> bool {
+        Self::accepted_scopes(tool_name)
+            .iter()
+            .any(|accepted| self.scopes.iter().any(|granted| granted == accepted))
+    }
+
+    /// Every scope that grants access to `tool_name`
+    pub fn accepted_scopes(tool_name: &str) -> Vec<String> {
+        let category = ToolCategory::of_tool(tool_name);
+        let mut scopes = Vec::new();
+        // Driver and admin tools need their own scope or admin, even the read-only ones
+        if !matches!(category, ToolCategory::DriverManagement | ToolCategory::Admin) {
+            if ToolDefinitions::is_read_only(tool_name) {
+                scopes.push(SCOPE_READ.to_string());
+            }
+            scopes.push(SCOPE_INTERACT.to_string());
+        }
+        scopes.push(format!("{SCOPE_PREFIX}{}", category.as_str()));
+        scopes.push(SCOPE_ADMIN.to_string());
+        scopes
+    }
+
+    /// Error data explaining a rejected call
+    pub fn denial(&self, tool_name: &str) -> Value {
+        json!({
+            "error": "insufficient_scope",
+            "code": ErrorCode::PolicyBlocked.as_str(),
+            "retryable": false,
+            "tool": tool_name,
+            "category": ToolCategory::of_tool(tool_name).as_str(),
+            "accepted_scopes": Self::accepted_scopes(tool_name),
+            "granted_scopes": self.scopes,
+        })
+    }
+}
+
+/// Refuse `tool_name` where another tool runs it on the caller's behalf, such as
+/// a recipe step or a hook script, when the server configuration disables it or
+/// the scopes of the tool call being served do not cover it
+pub fn check_tool_allowed(config: &Config, tool_name: &str) -> Result<(), String> {
+    if !config.is_tool_enabled(tool_name) {
+        return Err(format!("'{tool_name}' is disabled by server configuration"));
+    }
+    if GrantedScopes::current().is_some_and(|scopes| !scopes.allows(tool_name)) {
+        let accepted = GrantedScopes::accepted_scopes(tool_name).join(", ");
+        return Err(format!("'{tool_name}' requires one of the scopes: {accepted}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(scope: &str) -> AccessToken {
+        AccessToken {
+            token: "t".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_in: None,
+            refresh_token: None,
+            scope: Some(scope.to_string()),
+            user_id: "agent".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_scope_to_tool_mapping() {
+        let read = GrantedScopes::from_token(&token("openid browser:read"));
+        assert_eq!(read.as_slice(), ["browser:read"]);
+        assert!(read.allows("get_title"));
+        assert!(read.allows("get_page_source"));
+        assert!(!read.allows("click"));
+        assert!(!read.allows("execute_script"));
+        assert!(!read.allows("get_audit_log"));
+        assert!(!read.allows("list_managed_drivers"));
+
+        let interact = GrantedScopes::from_token(&token("browser:interact"));
+        assert!(interact.allows("click"));
+        assert!(interact.allows("execute_script"));
+        assert!(interact.allows("recipe_login"));
+        assert!(!interact.allows("stop_all_drivers"));
+        assert!(!interact.allows("get_audit_log"));
+
+        let scripting = GrantedScopes::from_token(&token("browser:scripting"));
+        assert!(scripting.allows("execute_script"));
+        assert!(!scripting.allows("click"));
+
+        let admin = GrantedScopes::from_token(&token("browser:admin"));
+        assert!(admin.allows("get_audit_log"));
+        assert!(admin.allows("stop_all_drivers"));
+
+        assert!(GrantedScopes::from_token(&token("openid profile")).is_empty());
+        assert_eq!(read.denial("click")["error"], "insufficient_scope");
+    }
+
+    #[tokio::test]
+    async fn test_nested_tools_need_the_callers_scopes() {
+        async fn check_as(config: &Config, scope: &str) -> Result<(), String> {
+            let scopes = Some(GrantedScopes::from_token(&token(scope)));
+            GrantedScopes::with_scopes(scopes, async { check_tool_allowed(config, "execute_script") }).await
+        }
+
+        let mut config = Config::default();
+        assert!(check_tool_allowed(&config, "execute_script").is_ok());
+        let refused = check_as(&config, "browser:read").await.unwrap_err();
+        assert!(refused.contains("requires one of the scopes: browser:interact, browser:scripting"), "{refused}");
+        assert!(check_as(&config, "browser:scripting").await.is_ok());
+
+        config.disabled_tools.push("execute_script".to_string());
+        let disabled = check_as(&config, "browser:scripting").await.unwrap_err();
+        assert!(disabled.contains("disabled by server configuration"), "{disabled}");
+    }
+}
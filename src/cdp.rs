@@ -0,0 +1,68 @@
+//! Chrome DevTools Protocol commands through the WebDriver session
+//!
+//! ChromeDriver and msedgedriver accept CDP commands at the vendor endpoint
+//! `POST /session/{id}/goog/cdp/execute`, applied to the session's current tab.
+//! Other drivers answer "unknown command", reported here as needing a
+//! Chromium-based session.
+
+use fantoccini::{Client, wd::WebDriverCompatibleCommand};
+use serde_json::{Value, json};
+
+use crate::{ClientManager, WebDriverError};
+
+#[derive(Debug)]
+struct CdpCommand {
+    cmd: String,
+    params: Value,
+}
+
+impl WebDriverCompatibleCommand for CdpCommand {
+    fn endpoint(&self, base_url: &url::Url, session_id: Option<&str>) -> Result<url::Url, url::ParseError> {
+        base_url.join(&format!("session/{}/goog/cdp/execute", session_id.unwrap_or_default()))
+    }
+
+    fn method_and_body(&self, _request_url: &url::Url) -> (http::Method, Option<String>) {
+        let body = json!({ "cmd": self.cmd, "params": self.params });
+        (http::Method::POST, Some(body.to_string()))
+    }
+}
+
+/// Run CDP command `cmd` in `session`'s current tab and return its result
+pub async fn execute(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    cmd: &str,
+    params: Value,
+) -> crate::Result<Value> {
+    let command = CdpCommand { cmd: cmd.to_string(), params };
+    match client_manager.run_command(session, cmd, client.issue_cmd(command)).await {
+        Err(WebDriverError::Client(e)) if e.is_unknown_command() => {
+            Err(WebDriverError::Execution(format!(
+                "{cmd} needs a Chrome or Edge session (Chrome DevTools Protocol is not available): {e}"
+            )))
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cdp_command_goes_to_the_vendor_endpoint() {
+        let command = CdpCommand {
+            cmd: "Network.setCacheDisabled".to_string(),
+            params: json!({ "cacheDisabled": true }),
+        };
+        let base = url::Url::parse("http://localhost:9515/").unwrap();
+        let endpoint = command.endpoint(&base, Some("abc123")).unwrap();
+        assert_eq!(endpoint.as_str(), "http://localhost:9515/session/abc123/goog/cdp/execute");
+
+        let (method, body) = command.method_and_body(&endpoint);
+        assert_eq!(method, http::Method::POST);
+        let body: Value = serde_json::from_str(&body.unwrap()).unwrap();
+        assert_eq!(body, json!({ "cmd": "Network.setCacheDisabled", "params": { "cacheDisabled": true } }));
+    }
+}
@@ -0,0 +1,87 @@
+//! Spotting bot challenges
+//!
+//! Sites behind bot protection answer automated browsers with a CAPTCHA widget
+//! or a "checking your browser" interstitial instead of the page. Clicking and
+//! typing cannot get past those, so tools that land on one report
+//! `BLOCKED_BY_CHALLENGE` rather than a success the next call will contradict.
+
+use fantoccini::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::ClientManager;
+
+/// Looks for visible CAPTCHA widgets and known interstitial pages. Returns
+/// `null` when there are none, otherwise `{provider, kind, evidence}`.
+const CHALLENGE_SCRIPT: &str = r#"
+    const visible = el => {
+        const rect = el.getBoundingClientRect();
+        const style = window.getComputedStyle(el);
+        return rect.width > 10 && rect.height > 10 && style.visibility !== 'hidden' && style.display !== 'none';
+    };
+    // Invisible reCAPTCHA keeps a visible badge on normal pages; only its challenge frame counts
+    const badge = el => el.closest('.grecaptcha-badge') || /[?&]size=invisible/.test(el.getAttribute('src') || '');
+    const widgets = [
+        ['reCAPTCHA', "iframe[src*='/recaptcha/'][src*='anchor'], iframe[src*='/recaptcha/'][src*='bframe'], iframe[title*='reCAPTCHA']"],
+        ['hCaptcha', "iframe[src*='hcaptcha.com']"],
+        ['Cloudflare Turnstile', "iframe[src*='challenges.cloudflare.com']"],
+        ['DataDome', "iframe[src*='captcha-delivery.com']"],
+        ['PerimeterX', '#px-captcha'],
+        ['AWS WAF', '#captcha-container, awswaf-captcha'],
+        ['Arkose Labs', "iframe[src*='arkoselabs.com'], iframe[src*='funcaptcha.com']"],
+    ];
+    for (const [provider, selector] of widgets) {
+        const el = Array.from(document.querySelectorAll(selector)).find(el => visible(el) && !badge(el));
+        if (el) {
+            return { provider, kind: 'captcha', evidence: `visible ${el.tagName.toLowerCase()} matching ${selector.split(',')[0]}` };
+        }
+    }
+
+    const title = document.title || '';
+    const interstitials = [
+        ['Cloudflare', () => /^just a moment|attention required! \| cloudflare/i.test(title) && 'page title "' + title + '"'],
+        ['Cloudflare', () => document.querySelector('#challenge-form, #challenge-running, #cf-challenge-running, .cf-browser-verification') && 'Cloudflare challenge form'],
+        ['Cloudflare', () => document.querySelector("script[src*='/cdn-cgi/challenge-platform/']") && !document.body?.innerText.trim().length && 'challenge platform script on an empty page'],
+        ['Akamai', () => /^access denied$/i.test(title) && /reference #[\d.a-f]+/i.test(document.body?.innerText || '') && 'Akamai access denied page'],
+        ['DDoS-Guard', () => /ddos-guard/i.test(title) && 'page title "' + title + '"'],
+        ['Sucuri', () => /sucuri website firewall/i.test(title) && 'page title "' + title + '"'],
+    ];
+    for (const [provider, test] of interstitials) {
+        const evidence = test();
+        if (evidence) {
+            return { provider, kind: 'interstitial', evidence };
+        }
+    }
+    return null;
+"#;
+
+/// A challenge found on the page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Challenge {
+    /// Vendor, e.g. "reCAPTCHA" or "Cloudflare"
+    pub provider: String,
+    /// "captcha" for a widget, "interstitial" for a page standing in for the site
+    pub kind: String,
+    /// What gave it away
+    pub evidence: String,
+}
+
+impl Challenge {
+    /// One-line description for tool messages
+    pub fn describe(&self) -> String {
+        let what = if self.kind == "captcha" { "CAPTCHA" } else { "bot-check page" };
+        format!(
+            "The page shows a {} {} ({}); automated clicks and typing cannot get past it",
+            self.provider, what, self.evidence
+        )
+    }
+}
+
+/// Check the current page for a challenge. Script failures count as none, so
+/// detection never turns a working call into an error.
+pub async fn detect(client_manager: &ClientManager, session: &str, client: &Client) -> Option<Challenge> {
+    let found = client_manager
+        .run_command(session, "detect_challenge", client.execute(CHALLENGE_SCRIPT, vec![]))
+        .await
+        .ok()?;
+    serde_json::from_value(found).ok()
+}
@@ -0,0 +1,194 @@
+//! Telling what a click landed on instead of its target
+//!
+//! WebDriver clicks the centre of an element; when another element covers that
+//! point (a consent overlay, a sticky header, a chat widget) the driver only
+//! answers "element click intercepted" with a driver-specific description.
+//! [`diagnose`] asks the page what sits at the target's centre and which fixed
+//! or sticky layer it belongs to, so the caller learns what to dismiss or
+//! scroll away. [`scroll_clear`] centres the target in the viewport, which
+//! moves it out from under headers and footers pinned to the edges.
+
+use fantoccini::{Client, elements::Element};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ClientManager, element_handles::element_reference, page_scripts::selector_for_script};
+
+/// Describes what covers the centre of element `arguments[0]`, or returns
+/// `{intercepted: false}` when the element itself would receive the click
+const INTERCEPT_SCRIPT: &str = concat!(selector_for_script!(), r#"
+    const [element] = arguments;
+    const describe = el => ({
+        selector: selectorFor(el),
+        tag: el.tagName.toLowerCase(),
+        text: (el.innerText || el.getAttribute('aria-label') || '').trim().replace(/\s+/g, ' ').slice(0, 80),
+        role: el.getAttribute('role'),
+        position: window.getComputedStyle(el).position,
+    });
+
+    const rect = element.getBoundingClientRect();
+    const x = rect.left + rect.width / 2;
+    const y = rect.top + rect.height / 2;
+    const hit = document.elementFromPoint(x, y);
+    if (!hit || hit === element || element.contains(hit)) {
+        return { intercepted: false };
+    }
+
+    // The layer covering the target: the nearest fixed or sticky ancestor of what was hit
+    let layer = null;
+    for (let node = hit; node && node !== document.documentElement; node = node.parentElement) {
+        const position = window.getComputedStyle(node).position;
+        if (position === 'fixed' || position === 'sticky') {
+            layer = node;
+            break;
+        }
+    }
+    let kind = 'element';
+    if (layer) {
+        const box = layer.getBoundingClientRect();
+        const band = box.height < window.innerHeight / 3;
+        kind = band && box.top <= 1 ? 'sticky_header'
+            : band && box.bottom >= window.innerHeight - 1 ? 'sticky_footer'
+            : 'overlay';
+    }
+    return {
+        intercepted: true,
+        kind,
+        point: { x: Math.round(x), y: Math.round(y) },
+        element: describe(hit),
+        layer: layer && layer !== hit ? describe(layer) : null,
+    };
+"#);
+
+/// Centres element `arguments[0]` in the viewport
+const SCROLL_CLEAR_SCRIPT: &str = r#"
+    arguments[0].scrollIntoView({ block: 'center', inline: 'center' });
+    return true;
+"#;
+
+/// An element as the page describes it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoveringElement {
+    /// Selector that matches the element, unique where the page allows
+    pub selector: String,
+    pub tag: String,
+    /// Start of its visible text or accessible label
+    #[serde(default)]
+    pub text: String,
+    pub role: Option<String>,
+    /// CSS `position`, e.g. "fixed" for overlays
+    pub position: String,
+}
+
+/// What would receive a click aimed at the target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interception {
+    /// "sticky_header", "sticky_footer", "overlay" or "element"
+    pub kind: String,
+    /// Viewport point the click was aimed at
+    pub point: Value,
+    /// Topmost element at that point
+    pub element: CoveringElement,
+    /// Fixed or sticky container of that element, when it is not the element itself
+    pub layer: Option<CoveringElement>,
+}
+
+impl Interception {
+    pub fn describe(&self) -> String {
+        let kind = match self.kind.as_str() {
+            "sticky_header" => "a sticky header",
+            "sticky_footer" => "a sticky footer",
+            "overlay" => "an overlay",
+            _ => "another element",
+        };
+        let covering = self.layer.as_ref().unwrap_or(&self.element);
+        match covering.text.as_str() {
+            "" => format!("{kind} ({})", covering.selector),
+            text => format!("{kind} ({}, \"{}\")", covering.selector, text),
+        }
+    }
+
+    /// Whether centring the target could move it out from under the cover
+    pub fn scrolling_may_help(&self) -> bool {
+        matches!(self.kind.as_str(), "sticky_header" | "sticky_footer")
+    }
+}
+
+/// Whether a WebDriver error is a click landing on another element
+pub fn is_click_intercepted(message: &str) -> bool {
+    message.to_lowercase().contains("element click intercepted")
+}
+
+/// What covers the centre of `element`, or `None` when nothing does any more
+pub async fn diagnose(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    element: &Element,
+) -> crate::Result<Option<Interception>> {
+    let arguments = vec![element_reference(&element.element_id())];
+    let result = client_manager
+        .run_command(session, "diagnose_click", client.execute(INTERCEPT_SCRIPT, arguments))
+        .await?;
+    Ok(parse_interception(result))
+}
+
+/// Scroll `element` to the middle of the viewport
+pub async fn scroll_clear(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    element: &Element,
+) -> crate::Result<()> {
+    let arguments = vec![element_reference(&element.element_id())];
+    client_manager
+        .run_command(session, "scroll_clear", client.execute(SCROLL_CLEAR_SCRIPT, arguments))
+        .await?;
+    Ok(())
+}
+
+fn parse_interception(result: Value) -> Option<Interception> {
+    if !result.get("intercepted").and_then(Value::as_bool).unwrap_or(false) {
+        return None;
+    }
+    serde_json::from_value(result).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_describe_interceptions() {
+        assert!(is_click_intercepted(
+            "element click intercepted: Element <button id=\"buy\"> is not clickable at point (640, 80). \
+             Other element would receive the click: <div class=\"nav\">"
+        ));
+        assert!(parse_interception(json!({ "intercepted": false })).is_none());
+
+        let header = parse_interception(json!({
+            "intercepted": true,
+            "kind": "sticky_header",
+            "point": { "x": 640, "y": 80 },
+            "element": {
+                "selector": "nav > a:nth-of-type(2)", "tag": "a", "text": "Deals", "role": null, "position": "static"
+            },
+            "layer": {
+                "selector": "header.site-nav", "tag": "header", "text": "", "role": "banner", "position": "sticky"
+            }
+        }))
+        .unwrap();
+        assert_eq!(header.describe(), "a sticky header (header.site-nav)");
+        assert!(header.scrolling_may_help());
+
+        let overlay = Interception {
+            kind: "overlay".to_string(),
+            layer: None,
+            element: CoveringElement { text: "Accept all".to_string(), ..header.layer.clone().unwrap() },
+            ..header
+        };
+        assert_eq!(overlay.describe(), "an overlay (header.site-nav, \"Accept all\")");
+        assert!(!overlay.scrolling_may_help());
+    }
+}
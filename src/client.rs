@@ -1,9 +1,17 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{collections::{BTreeMap, HashMap}, sync::Arc, time::Duration};
 
 use fantoccini::{Client, ClientBuilder, Locator, elements::Element};
 use futures::lock::Mutex;
 
-use crate::{config::Config, driver::DriverManager, error::Result, pool::ConnectionPool};
+use crate::{artifacts::ArtifactStore, config::Config, console::{ConsoleBuffer, DRAIN_CONSOLE_SCRIPT}, dom_snapshot::{DomSnapshot, DomSnapshotStore}, driver::{DriverManager, DriverStartupPolicy}, element_handles::ElementHandleStore, error::{CrashRecovery, ErrorCode, Result, WebDriverError}, page_events::{PageEvent, PageEventBuffer}, politeness::Politeness, pool::ConnectionPool, popups::{PopupEvent, PopupPolicy, PopupTracker}, quotas::{QuotaExceeded, QuotaTracker, SessionQuota}, resources::ResourceStore, robots::{RobotsBlock, RobotsTxt}, session_aliases::{SessionAliases, SessionBinding}, session_hooks::{HookEvent, SessionHook, SessionHooks}, session_locks::SessionLocks, site_pool::{self, SitePool}, stats::ServerStats};
+
+/// How long a session gets to answer a liveness check before it is treated as dead
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(5);
+
+tokio::task_local! {
+    /// Authenticated user on whose behalf the current tool call runs
+    static PRINCIPAL: Option<String>;
+}
 
 /// Metadata about a session for pool management
 #[derive(Clone, Debug)]
@@ -11,15 +19,118 @@ struct SessionMetadata {
     driver_type: crate::driver::DriverType,
 }
 
+/// Per-session overrides applied when creating a browser session
+#[derive(Clone, Debug, Default)]
+pub struct SessionOptions {
+    /// Overrides `Config::headless` when set
+    pub headless: Option<bool>,
+    /// Start the browser in a private/incognito window
+    pub private: bool,
+    /// Overrides `Config::auto_dismiss_banners` for this session when set
+    pub auto_dismiss_banners: Option<bool>,
+    /// Overrides `Config::popup_policy` for this session when set
+    pub popup_policy: Option<PopupPolicy>,
+    /// Limits on what the session may be used for, applied when it is created
+    pub quota: SessionQuota,
+    /// Hooks run when the session is created or closed, after `Config::session_hooks`
+    pub hooks: Vec<SessionHook>,
+    /// Size the window so the page's viewport is this width and height
+    pub viewport: Option<(u32, u32)>,
+    /// Proxy the browser's traffic goes through, e.g. `socks5://proxy:1080`
+    pub proxy: Option<String>,
+    /// Extra HTTP headers sent with every request of the session's first tab (Chrome and Edge)
+    pub headers: BTreeMap<String, String>,
+}
+
+impl SessionOptions {
+    /// Whether the session needs a connection of its own. Pooled connections are
+    /// opened with default settings, and must not carry these into other sessions.
+    fn needs_own_connection(&self) -> bool {
+        self.headless.is_some()
+            || self.private
+            || self.viewport.is_some()
+            || self.proxy.is_some()
+            || !self.headers.is_empty()
+    }
+}
+
+/// Sessions open on a driver the server is asked to stop, of every principal
+#[derive(Debug, Default)]
+pub struct ManagedSessions {
+    keys: Vec<String>,
+}
+
+impl ManagedSessions {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Ids of the current principal's sessions, sorted
+    pub fn own(&self) -> Vec<String> {
+        self.keys.iter().filter_map(|key| ClientManager::own_session_id(key)).collect()
+    }
+
+    /// Number of sessions other principals have open
+    pub fn others(&self) -> usize {
+        self.keys.len() - self.own().len()
+    }
+}
+
+/// An open session as reported by `list_sessions`
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct SessionInfo {
+    pub session_id: String,
+    /// Browser name when known
+    pub browser: Option<String>,
+    /// WebDriver endpoint the session was started at
+    pub endpoint: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct ClientManager {
+    /// Open sessions, locked only to read or change the table; sessions are
+    /// checked and opened under `session_locks`
     clients: Arc<Mutex<HashMap<String, Client>>>,
+    /// Held while a session is checked or opened, so other sessions are not kept waiting
+    session_locks: SessionLocks,
     /// Metadata for each session (driver type, etc.)
     session_metadata: Arc<Mutex<HashMap<String, SessionMetadata>>>,
     config: Config,
     driver_manager: DriverManager,
     /// Connection pool for reusing sessions
     pool: Arc<ConnectionPool>,
+    /// Screenshots, logs and artifacts captured across sessions
+    resources: ResourceStore,
+    /// Files written to disk by tools and recipes
+    artifacts: ArtifactStore,
+    /// Console output drained from each session's pages
+    console: ConsoleBuffer,
+    /// Named DOM snapshots taken with `snapshot_dom`
+    dom_snapshots: DomSnapshotStore,
+    /// Elements found by `find_element(s)`, addressable from scripts by handle
+    element_handles: ElementHandleStore,
+    /// Sessions that override `Config::auto_dismiss_banners`
+    banner_settings: Arc<std::sync::Mutex<HashMap<String, bool>>>,
+    /// Popup policies, known windows and popup events per session
+    popups: PopupTracker,
+    /// Lifecycle events drained from each session's pages
+    page_events: PageEventBuffer,
+    /// WebDriver endpoint each session was started at
+    session_endpoints: Arc<std::sync::Mutex<HashMap<String, String>>>,
+    /// Browser, endpoint and capabilities sessions were bound to with `browser`
+    aliases: SessionAliases,
+    /// Hooks `create_session` added to the sessions it opened
+    session_hooks: SessionHooks,
+    /// Quotas and usage of sessions created with limits
+    quotas: QuotaTracker,
+    /// Per-origin sessions used when `site_pool_enabled` is on
+    site_pool: SitePool,
+    /// Page load slots per origin, shared by all sessions
+    politeness: Politeness,
+    /// robots.txt rules per origin, checked before page loads when `respect_robots_txt` is on
+    robots: RobotsTxt,
+    /// Tool-call, recipe and driver counters reported by `get_server_stats`
+    stats: ServerStats,
 }
 
 impl ClientManager {
@@ -29,20 +140,200 @@ impl ClientManager {
             .map_err(|e| anyhow::anyhow!("Configuration error: {}", e))?;
 
         let pool = Arc::new(ConnectionPool::new(&config));
+        let artifacts = ArtifactStore::from_config(&config);
+        let driver_manager = DriverManager::with_startup_policy(DriverStartupPolicy::from_config(&config));
+        let site_pool = SitePool::from_config(&config);
+        let politeness = Politeness::from_config(&config);
+        let robots = RobotsTxt::from_config(&config);
 
         Ok(Self {
             clients: Arc::new(Mutex::new(HashMap::new())),
+            session_locks: SessionLocks::default(),
             session_metadata: Arc::new(Mutex::new(HashMap::new())),
             config,
-            driver_manager: DriverManager::new(),
+            driver_manager,
             pool,
+            resources: ResourceStore::default(),
+            console: ConsoleBuffer::default(),
+            dom_snapshots: DomSnapshotStore::default(),
+            element_handles: ElementHandleStore::default(),
+            banner_settings: Arc::default(),
+            popups: PopupTracker::default(),
+            page_events: PageEventBuffer::default(),
+            session_endpoints: Arc::default(),
+            aliases: SessionAliases::default(),
+            session_hooks: SessionHooks::default(),
+            quotas: QuotaTracker::default(),
+            site_pool,
+            politeness,
+            robots,
+            artifacts,
+            stats: ServerStats::new(),
         })
     }
 
+    /// Run `future` on behalf of `principal`. Session ids used inside it are private
+    /// to that principal, so authenticated HTTP users cannot reach each other's browsers
+    /// even when they pick the same session name. `None` keeps the shared namespace.
+    pub async fn with_principal<F: std::future::Future>(principal: Option<String>, future: F) -> F::Output {
+        PRINCIPAL.scope(principal, future).await
+    }
+
+    /// Principal of the tool call being served, if any
+    pub fn current_principal() -> Option<String> {
+        PRINCIPAL.try_with(|principal| principal.clone()).ok().flatten()
+    }
+
+    /// Internal key of a client-visible session id: the id with `%` and `@` escaped,
+    /// then `@` and the principal, if any, so the first `@` always starts the owner.
+    /// Keeps the session name as a prefix so browser selection by name (`firefox_...`)
+    /// still works.
+    pub(crate) fn session_key(session_id: &str) -> String {
+        let escaped = session_id.replace('%', "%25").replace('@', "%40");
+        match Self::current_principal() {
+            Some(principal) => format!("{escaped}@{principal}"),
+            None => escaped,
+        }
+    }
+
+    /// Client-visible session id and owning principal of the session under `key`
+    fn split_session_key(key: &str) -> (String, Option<&str>) {
+        let (escaped, owner) = match key.split_once('@') {
+            Some((escaped, owner)) => (escaped, Some(owner)),
+            None => (key, None),
+        };
+        (escaped.replace("%40", "@").replace("%25", "%"), owner)
+    }
+
+    /// Whether a pooled connection registered under `key` may be handed to the current principal
+    fn owned_by_current_principal(key: &str) -> bool {
+        Self::own_session_id(key).is_some()
+    }
+
+    /// Client for `session_id`, starting a browser when the session does not exist
+    /// yet, unless `auto_create_sessions` is off
     pub async fn get_or_create_client(
         &self,
         session_id: Option<String>,
     ) -> Result<(String, Client)> {
+        if !self.config.auto_create_sessions {
+            return self.get_existing_client(session_id).await;
+        }
+        self.create_or_reuse_client(session_id).await
+    }
+
+    /// Client of an already running session. Used by read-only tools, which
+    /// have nothing to read from a browser they would have to launch first.
+    pub async fn get_existing_client(&self, session_id: Option<String>) -> Result<(String, Client)> {
+        let session = session_id.unwrap_or_else(|| self.default_session_name());
+        let clients = self.clients.lock().await;
+        match clients.get(&Self::session_key(&session)) {
+            Some(client) => Ok((session, client.clone())),
+            None => Err(WebDriverError::NoActiveSession { session }),
+        }
+    }
+
+    /// Explicitly start a session (or reuse a running one), regardless of
+    /// `auto_create_sessions`. Returns the session id and whether it was created.
+    pub async fn create_session(&self, session_id: Option<String>, options: &SessionOptions) -> Result<(String, bool)> {
+        let session = session_id.unwrap_or_else(|| self.default_session_name());
+        if let Some(enabled) = options.auto_dismiss_banners {
+            self.banner_settings
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(Self::session_key(&session), enabled);
+        }
+        if let Some(policy) = options.popup_policy {
+            self.popups.set_policy(&Self::session_key(&session), policy);
+        }
+        if self.get_existing_client(Some(session.clone())).await.is_ok() {
+            return Ok((session, false));
+        }
+        if !options.hooks.is_empty() {
+            self.session_hooks.set(&Self::session_key(&session), options.hooks.clone());
+        }
+
+        if options.needs_own_connection() {
+            self.create_isolated_session(&session, options).await?;
+        } else {
+            self.create_or_reuse_client(Some(session.clone())).await?;
+        }
+        if !options.quota.is_unlimited() {
+            self.quotas.set(&Self::session_key(&session), options.quota);
+        }
+        Ok((session, true))
+    }
+
+    /// Open `session_id` with `binding` once it is created. A session that is
+    /// already open keeps its binding, and may not be asked for another browser.
+    pub async fn bind_session(&self, session_id: Option<String>, binding: SessionBinding) -> Result<String> {
+        self.config.check_session_binding(&binding).map_err(WebDriverError::Execution)?;
+        let session = session_id.unwrap_or_else(|| self.default_session_name());
+        let key = Self::session_key(&session);
+        if self.clients.lock().await.contains_key(&key) {
+            let running = self.session_browser(&session).unwrap_or(crate::driver::DriverType::Chrome);
+            if running != binding.browser {
+                return Err(WebDriverError::Session(format!(
+                    "Session '{session}' is already open in {}; close it or use another session_id to open {}",
+                    running.browser_name(),
+                    binding.browser.browser_name()
+                )));
+            }
+            return Ok(session);
+        }
+        self.aliases.bind(&key, binding);
+        Ok(session)
+    }
+
+    /// Browser of `session_id`: the one it was bound to, else the one its id
+    /// prefix names
+    fn session_browser(&self, session_id: &str) -> Option<crate::driver::DriverType> {
+        match self.aliases.get(&Self::session_key(session_id)) {
+            Some(binding) => Some(binding.browser),
+            None => self.extract_browser_preference_from_session(session_id),
+        }
+    }
+
+    /// Session used when a tool call does not name one: the site session
+    /// navigated last when the site pool is on
+    fn default_session_name(&self) -> String {
+        if self.config.site_pool_enabled
+            && let Some(session) = self.site_pool.current(&Self::current_principal())
+        {
+            return session;
+        }
+        if self.is_stdio_mode() { "stdio_default" } else { "default" }.to_string()
+    }
+
+    /// Session a navigation to `url` runs in. With the site pool on, a call that
+    /// names no session goes to the session of `url`'s site, closing the least
+    /// recently used site session when the pool is full. Waits until the origin's
+    /// politeness interval since its last navigation has passed.
+    pub async fn route_navigation(&self, session_id: Option<String>, url: &str) -> Option<String> {
+        if !self.config.site_pool_enabled {
+            return session_id;
+        }
+        let Some(origin) = site_pool::origin_of(url) else {
+            return session_id;
+        };
+        let site = session_id.is_none();
+        let session = session_id.or_else(|| site_pool::site_session_name(url))?;
+        let key = Self::session_key(&session);
+        let visit = self.site_pool.visit(Self::current_principal(), &session, &key, &origin, site);
+        if let Some(evicted) = visit.evict {
+            tracing::debug!("Site pool is full, closing least recently used session '{}'", evicted);
+            if let Err(e) = self.close_session(&evicted).await {
+                tracing::warn!("Failed to close site session '{}': {}", evicted, e);
+            }
+        }
+        if !visit.wait.is_zero() {
+            tracing::debug!("Waiting {:?} before navigating to {}", visit.wait, origin);
+            tokio::time::sleep(visit.wait).await;
+        }
+        Some(session)
+    }
+
+    async fn create_or_reuse_client(&self, session_id: Option<String>) -> Result<(String, Client)> {
         // For stdio mode, use simplified single-session approach
         if self.is_stdio_mode() {
             return self.get_or_create_client_stdio(session_id).await;
@@ -54,120 +345,297 @@ impl ClientManager {
 
     /// Simplified client creation for stdio mode - single session per browser type
     async fn get_or_create_client_stdio(&self, session_id: Option<String>) -> Result<(String, Client)> {
-        let session = session_id.unwrap_or_else(|| "stdio_default".to_string());
-        
-        let mut clients = self.clients.lock().await;
-        if let Some(client) = clients.get(&session) {
-            // Quick check if client is still alive
-            match client.current_url().await {
-                Ok(_) => return Ok((session, client.clone())),
-                Err(_) => {
-                    tracing::debug!("Existing stdio client is dead, removing");
-                    clients.remove(&session);
-                }
-            }
+        let session = session_id.unwrap_or_else(|| self.default_session_name());
+        let key = Self::session_key(&session);
+
+        let _opening = self.session_locks.lock(&key).await;
+        if let Some(client) = self.live_client(&key).await {
+            return Ok((session, client));
         }
-        drop(clients);
 
         // Use browser-aware endpoint resolution for stdio to support multi-browser recipes
-        let endpoint = self.resolve_webdriver_endpoint_for_session(&session).await?;
-        
-        let client = self.create_configured_client(&endpoint, &session).await?;
-        
-        let mut clients = self.clients.lock().await; 
-        clients.insert(session.clone(), client.clone());
-        
+        let client = self.connect_session(&session, &SessionOptions::default()).await?;
+
+        self.clients.lock().await.insert(key.clone(), client.clone());
+        self.run_session_hooks(&key, &client, HookEvent::Create).await;
+
         Ok((session, client))
     }
 
     /// Full multi-tenant client creation for HTTP mode
     async fn get_or_create_client_http(&self, session_id: Option<String>) -> Result<(String, Client)> {
-        let session = session_id.unwrap_or_else(|| "default".to_string());
+        let session = session_id.unwrap_or_else(|| self.default_session_name());
+        let key = Self::session_key(&session);
 
         // Check active clients first
-        {
-            let mut clients = self.clients.lock().await;
-            if let Some(client) = clients.get(&session) {
-                match client.current_url().await {
-                    Ok(_) => return Ok((session, client.clone())),
-                    Err(_) => {
-                        clients.remove(&session);
-                        // Also remove from metadata
-                        let mut metadata = self.session_metadata.lock().await;
-                        metadata.remove(&session);
-                    }
-                }
-            }
+        let _opening = self.session_locks.lock(&key).await;
+        if let Some(client) = self.live_client(&key).await {
+            return Ok((session, client));
         }
 
         // Determine driver type for this session
-        let driver_type = self.extract_browser_preference_from_session(&session)
-            .unwrap_or(crate::driver::DriverType::Chrome);
-
-        // Try to acquire from pool
-        if let Ok(Some((pooled_session, client))) = self.pool.acquire(&driver_type).await {
+        let driver_type = self.session_browser(&session).unwrap_or(crate::driver::DriverType::Chrome);
+        // Pooled connections were opened with default settings, so bound endpoints and capabilities skip the pool
+        let poolable = self.aliases.get(&key).is_none_or(|binding| binding.allows_pooling());
+
+        // Try to acquire from pool; connections never move between principals
+        if poolable
+            && let Ok(Some((pooled_session, client))) = self
+                .pool
+                .acquire_matching(&driver_type, Self::owned_by_current_principal)
+                .await
+        {
             tracing::debug!(
                 "Reusing pooled {} connection for session '{}'",
                 driver_type.browser_name(),
                 session
             );
 
+            // The connection keeps the endpoint it was started at
+            {
+                let mut endpoints = self.session_endpoints.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(endpoint) = endpoints.get(&pooled_session).cloned() {
+                    endpoints.insert(key.clone(), endpoint);
+                }
+            }
+
+            // Update pool to track with new session id
+            self.pool.release(&driver_type, &pooled_session).await;
+            self.pool.add(driver_type.clone(), client.clone(), key.clone()).await;
+
             // Store in active clients
-            let mut clients = self.clients.lock().await;
-            clients.insert(session.clone(), client.clone());
+            self.clients.lock().await.insert(key.clone(), client.clone());
 
             // Store metadata
-            let mut metadata = self.session_metadata.lock().await;
-            metadata.insert(session.clone(), SessionMetadata {
-                driver_type: driver_type.clone(),
+            self.session_metadata.lock().await.insert(key.clone(), SessionMetadata {
+                driver_type,
             });
-
-            // Update pool to track with new session id
-            self.pool.release(&driver_type, &pooled_session).await;
-            self.pool.add(driver_type, client.clone(), session.clone()).await;
+            self.run_session_hooks(&key, &client, HookEvent::Create).await;
 
             return Ok((session, client));
         }
 
         // No pooled connection available, create a new one
-        let endpoint = self.resolve_webdriver_endpoint_for_session(&session).await?;
-
         let client = self
-            .create_configured_client(&endpoint, &session)
+            .connect_session(&session, &SessionOptions::default())
             .await
-            .map_err(|e| {
-                anyhow::anyhow!(
-                    "Failed to connect to WebDriver at '{}': {}\n\n{}",
-                    endpoint,
-                    e,
-                    crate::config::Config::setup_guidance()
-                )
-            })?;
+            .map_err(|e| anyhow::anyhow!("{}\n\n{}", e, crate::config::Config::setup_guidance()))?;
 
         // Add to pool
-        let added_to_pool = self.pool.add(driver_type.clone(), client.clone(), session.clone()).await;
+        let added_to_pool = poolable && self.pool.add(driver_type.clone(), client.clone(), key.clone()).await;
         if added_to_pool {
-            tracing::debug!("Added new {} connection to pool: {}", driver_type.browser_name(), session);
+            tracing::debug!("Added new {} connection to pool: {}", driver_type.browser_name(), key);
         }
 
         // Store in active clients
-        let mut clients = self.clients.lock().await;
-        clients.insert(session.clone(), client.clone());
+        self.clients.lock().await.insert(key.clone(), client.clone());
 
         // Store metadata
-        let mut metadata = self.session_metadata.lock().await;
-        metadata.insert(session.clone(), SessionMetadata {
+        self.session_metadata.lock().await.insert(key.clone(), SessionMetadata {
             driver_type,
         });
+        self.run_session_hooks(&key, &client, HookEvent::Create).await;
 
         Ok((session, client))
     }
 
-    async fn create_configured_client(&self, endpoint: &str, session_id: &str) -> Result<Client> {
+    /// Open client of `key` if it still answers. A dead one is forgotten. The
+    /// session table is not locked while the browser is asked, which can take
+    /// up to `LIVENESS_TIMEOUT`.
+    async fn live_client(&self, key: &str) -> Option<Client> {
+        let client = self.clients.lock().await.get(key).cloned()?;
+        match tokio::time::timeout(LIVENESS_TIMEOUT, client.current_url()).await {
+            Ok(Ok(_)) => Some(client),
+            _ => {
+                tracing::debug!("Existing client of session '{}' is dead, removing", key);
+                self.clients.lock().await.remove(key);
+                self.session_metadata.lock().await.remove(key);
+                None
+            }
+        }
+    }
+
+    /// Create a dedicated session that bypasses the pool, e.g. for isolated recipe runs.
+    /// The session is registered under `session_id` until `close_session` is called.
+    pub async fn create_isolated_session(&self, session_id: &str, options: &SessionOptions) -> Result<Client> {
+        let key = Self::session_key(session_id);
+        let _opening = self.session_locks.lock(&key).await;
+        let client = self.connect_session(session_id, options).await?;
+
+        self.clients.lock().await.insert(key.clone(), client.clone());
+        tracing::debug!("Created isolated session '{}'", session_id);
+        if let Err(e) = self.apply_session_options(session_id, &client, options).await {
+            if let Err(close_error) = self.shut_session(session_id, false).await {
+                tracing::debug!("Failed to close session '{}': {}", session_id, close_error);
+            }
+            return Err(e);
+        }
+        self.run_session_hooks(&key, &client, HookEvent::Create).await;
+
+        Ok(client)
+    }
+
+    /// Close a single session and forget about it, after running its close hooks
+    pub async fn close_session(&self, session_id: &str) -> Result<()> {
+        self.shut_session(session_id, true).await
+    }
+
+    /// Close `session_id`. A session that stopped answering skips its close hooks,
+    /// which could only time out.
+    async fn shut_session(&self, session_id: &str, run_hooks: bool) -> Result<()> {
+        self.shut_key(&Self::session_key(session_id), run_hooks).await
+    }
+
+    /// Close the session stored under `key`, which already names its principal
+    async fn shut_key(&self, key: &str, run_hooks: bool) -> Result<()> {
+        let key = key.to_string();
+        if run_hooks {
+            let client = self.clients.lock().await.get(&key).cloned();
+            if let Some(client) = client {
+                self.run_session_hooks(&key, &client, HookEvent::Close).await;
+            }
+        }
+        let client = {
+            let mut clients = self.clients.lock().await;
+            clients.remove(&key)
+        };
+        let driver_type = {
+            let mut metadata = self.session_metadata.lock().await;
+            metadata.remove(&key).map(|m| m.driver_type)
+        };
+        if let Some(driver_type) = driver_type {
+            self.pool.remove(&driver_type, &key).await;
+        }
+        self.console.remove(&key);
+        self.dom_snapshots.remove(&key);
+        self.element_handles.remove(&key);
+        self.banner_settings.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+        self.popups.remove(&key);
+        self.page_events.remove(&key);
+        self.site_pool.remove(&key);
+        self.quotas.remove(&key);
+        self.session_endpoints.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+        self.aliases.remove(&key);
+        self.session_hooks.remove(&key);
+        self.session_locks.remove(&key);
+
+        let Some(client) = client else {
+            return Ok(());
+        };
+
+        let close_timeout = Duration::from_secs(2);
+        match tokio::time::timeout(close_timeout, client.close()).await {
+            Ok(Ok(())) => {
+                tracing::debug!("Closed session: {}", key);
+                Ok(())
+            }
+            Ok(Err(e)) => Err(e.into()),
+            Err(_) => Err(anyhow::anyhow!("Timeout closing session {} after {:?}", key, close_timeout).into()),
+        }
+    }
+
+    /// Size the viewport and send the extra headers `options` ask for
+    async fn apply_session_options(&self, session_id: &str, client: &Client, options: &SessionOptions) -> Result<()> {
+        if let Some((width, height)) = options.viewport {
+            crate::screenshots::set_viewport(self, session_id, client, width, height).await?;
+        }
+        if !options.headers.is_empty() {
+            crate::cdp::execute(self, session_id, client, "Network.enable", serde_json::json!({})).await?;
+            let headers = serde_json::json!({ "headers": options.headers });
+            crate::cdp::execute(self, session_id, client, "Network.setExtraHTTPHeaders", headers).await?;
+        }
+        Ok(())
+    }
+
+    /// Run the configured hooks for `event`, then the session's own, in the session
+    /// under `key`. A failed hook is logged and does not stop the others.
+    async fn run_session_hooks(&self, key: &str, client: &Client, event: HookEvent) {
+        let configured = self.config.session_hooks.iter().filter(|hook| hook.on == event).cloned();
+        // Configured hooks were checked at startup; the session's own run for the caller
+        let hooks: Vec<(SessionHook, bool)> = configured
+            .map(|hook| (hook, false))
+            .chain(self.session_hooks.for_event(key, event).into_iter().map(|hook| (hook, true)))
+            .collect();
+        for (index, (hook, own)) in hooks.iter().enumerate() {
+            if *own
+                && let Err(e) = hook.check_script_allowed(&self.config)
+            {
+                tracing::warn!("Skipping {:?} hook {} of session '{}': {}", event, index + 1, key, e);
+                continue;
+            }
+            if let Err(e) = hook.run(client, self.call_timeout()).await {
+                tracing::warn!("{:?} hook {} of session '{}' failed: {}", event, index + 1, key, e);
+            }
+        }
+    }
+
+    /// Start a browser for `session_id` at its resolved endpoint, failing over to
+    /// each `failover_endpoints` entry for its browser in turn, and remember
+    /// where the session landed
+    async fn connect_session(&self, session_id: &str, options: &SessionOptions) -> Result<Client> {
+        let binding = self.aliases.get(&Self::session_key(session_id));
+        if binding.is_none()
+            && let Some(driver_type) = self.extract_browser_preference_from_session(session_id)
+        {
+            tracing::warn!(
+                "Session '{}' picks {} by its id prefix, which is deprecated; pass browser: \"{}\" instead",
+                session_id,
+                driver_type.browser_name(),
+                driver_type.browser_name().to_lowercase()
+            );
+        }
+        let driver_type = self
+            .session_browser(session_id)
+            .or_else(|| self.config.preferred_driver.as_deref().and_then(crate::driver::DriverType::from_string))
+            .unwrap_or(crate::driver::DriverType::Chrome);
+        let failover = self.config.failover_endpoints_for(&driver_type);
+
+        let mut candidates = Vec::new();
+        let primary = match binding.and_then(|binding| binding.endpoint) {
+            Some(endpoint) => Ok(endpoint),
+            None => self.resolve_webdriver_endpoint_for_session(session_id).await,
+        };
+        match primary {
+            Ok(endpoint) => candidates.push(endpoint),
+            // Nothing else to try: report why the primary endpoint is unusable
+            Err(e) if failover.is_empty() => return Err(e),
+            Err(e) => tracing::warn!("No primary WebDriver for session '{}': {}", session_id, e),
+        }
+        for endpoint in failover {
+            if !candidates.contains(&endpoint) {
+                candidates.push(endpoint);
+            }
+        }
+
+        let mut failures = Vec::new();
+        for endpoint in candidates {
+            match self.create_client_with_options(&endpoint, session_id, options).await {
+                Ok(client) => {
+                    if !failures.is_empty() {
+                        tracing::warn!("Session '{}' failed over to WebDriver at {}", session_id, endpoint);
+                    }
+                    self.session_endpoints
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(Self::session_key(session_id), endpoint);
+                    return Ok(client);
+                }
+                Err(e) => {
+                    tracing::debug!("Could not start session '{}' at {}: {}", session_id, endpoint, e);
+                    failures.push(format!("'{endpoint}': {e}"));
+                }
+            }
+        }
+        Err(anyhow::anyhow!("Failed to connect to WebDriver at {}", failures.join(", then at ")).into())
+    }
+
+    async fn create_client_with_options(&self, endpoint: &str, session_id: &str, options: &SessionOptions) -> Result<Client> {
         use serde_json::json;
 
+        let headless = options.headless.unwrap_or(self.config.headless);
+
         // Determine which browser we're connecting to based on session ID first, then fallback to port/preference
-        let session_browser_preference = self.extract_browser_preference_from_session(session_id);
+        let session_browser_preference = self.session_browser(session_id);
         
         let is_firefox = if let Some(driver_type) = session_browser_preference {
             matches!(driver_type, crate::driver::DriverType::Firefox)
@@ -191,9 +659,17 @@ impl ClientManager {
             // Firefox capabilities
             caps.insert("browserName".to_string(), json!("firefox"));
 
-            if self.config.headless {
+            let mut firefox_args = Vec::new();
+            if headless {
+                firefox_args.push("--headless");
+            }
+            if options.private {
+                firefox_args.push("-private");
+            }
+
+            if !firefox_args.is_empty() {
                 let mut firefox_options = serde_json::Map::new();
-                firefox_options.insert("args".to_string(), json!(["--headless"]));
+                firefox_options.insert("args".to_string(), json!(firefox_args));
                 caps.insert("moz:firefoxOptions".to_string(), json!(firefox_options));
             }
         } else {
@@ -211,14 +687,42 @@ impl ClientManager {
                 "--remote-debugging-port=0"
             ].iter().map(|s| s.to_string()));
             
-            if self.config.headless {
+            if headless {
                 chrome_args.push("--headless".to_string());
             }
+
+            if options.private {
+                chrome_args.push("--incognito".to_string());
+            }
             
             chrome_options.insert("args".to_string(), json!(chrome_args));
             caps.insert("goog:chromeOptions".to_string(), json!(chrome_options));
         }
 
+        // W3C session timeouts; anything left unset keeps the driver's default
+        let mut timeouts = serde_json::Map::new();
+        if let Some(ms) = self.config.navigation_timeout_ms {
+            timeouts.insert("pageLoad".to_string(), json!(ms));
+        }
+        if let Some(ms) = self.config.script_timeout_ms {
+            timeouts.insert("script".to_string(), json!(ms));
+        }
+        if !timeouts.is_empty() {
+            caps.insert("timeouts".to_string(), json!(timeouts));
+        }
+        // Raw capabilities configured for the browser, then the session's own on top
+        let browser = self.session_browser(session_id).unwrap_or_else(|| driver_type.clone());
+        if let Some(extra) = self.config.extra_capabilities_for(&browser) {
+            crate::session_aliases::merge_capabilities(&mut caps, extra);
+        }
+        if let Some(proxy) = &options.proxy {
+            let proxy = crate::session_templates::proxy_capability(proxy).map_err(WebDriverError::Session)?;
+            caps.insert("proxy".to_string(), proxy);
+        }
+        if let Some(binding) = self.aliases.get(&Self::session_key(session_id)) {
+            binding.apply_capabilities(&mut caps);
+        }
+
         // Try to connect, if it fails due to session conflict, clean up and retry
         let client = ClientBuilder::native()
             .capabilities(caps.clone())
@@ -266,7 +770,7 @@ impl ClientManager {
             
             if !healthy_endpoints.is_empty() {
                 // Check if session ID specifies a browser preference (e.g., "firefox_session1", "chrome_default")
-                let preferred_driver = self.extract_browser_preference_from_session(session_id);
+                let preferred_driver = self.session_browser(session_id);
                 
                 // Try preferred driver from session ID first
                 if let Some(driver_type) = preferred_driver {
@@ -335,7 +839,35 @@ impl ClientManager {
         }
     }
 
-    /// Extract browser preference from session ID (e.g., "firefox_session1" -> Some(DriverType::Firefox))
+    /// Why sessions of `driver_type` cannot be opened, or `None` when they can: a
+    /// configured or failover endpoint serves it, its driver is running, or the
+    /// driver is installed and may be started on demand
+    pub async fn browser_unavailable_reason(&self, driver_type: &crate::driver::DriverType) -> Option<String> {
+        // A fixed endpoint is trusted to serve whatever browser is asked for
+        if self.config.webdriver_endpoint != "auto" || !self.config.failover_endpoints_for(driver_type).is_empty() {
+            return None;
+        }
+        if self.driver_manager.is_driver_healthy(driver_type).await {
+            return None;
+        }
+        let executable = driver_type.executable_name();
+        match self.driver_manager.find_driver_executable(driver_type) {
+            Some(_) if self.config.auto_start_driver => None,
+            Some(path) => Some(format!(
+                "{} is installed at {} but not running, and auto_start_driver is disabled",
+                executable,
+                path.display()
+            )),
+            None => Some(format!(
+                "{} was not found on PATH or in the usual install locations, and no {} driver is running",
+                executable,
+                driver_type.browser_name()
+            )),
+        }
+    }
+
+    /// Extract browser preference from session ID (e.g., "firefox_session1" -> Some(DriverType::Firefox)).
+    /// Deprecated: sessions bound to a `browser` never consult their id.
     fn extract_browser_preference_from_session(&self, session_id: &str) -> Option<crate::driver::DriverType> {
         let session_lower = session_id.to_lowercase();
         
@@ -408,6 +940,238 @@ impl ClientManager {
         &self.config
     }
 
+    pub fn get_resource_store(&self) -> &ResourceStore {
+        &self.resources
+    }
+
+    /// Move console output recorded by the current page of `session` into the
+    /// server-side buffer. Returns the number of new entries.
+    pub async fn drain_console_logs(&self, session: &str, client: &Client) -> Result<usize> {
+        let drained = self
+            .run_command(session, "drain_console_logs", client.execute(DRAIN_CONSOLE_SCRIPT, vec![]))
+            .await?;
+        let entries = match drained {
+            serde_json::Value::Array(entries) => entries,
+            _ => Vec::new(),
+        };
+        Ok(self.console.append(&Self::session_key(session), entries))
+    }
+
+    /// Console entries buffered for `session`, oldest first
+    pub fn console_logs(&self, session: &str) -> Vec<serde_json::Value> {
+        self.console.entries(&Self::session_key(session))
+    }
+
+    /// `seq` of the newest console entry `session` has had
+    pub fn console_high_watermark(&self, session: &str) -> u64 {
+        self.console.high_watermark(&Self::session_key(session))
+    }
+
+    /// Drop the buffered console entries of `session` up to and including `seq`
+    pub fn clear_console_logs(&self, session: &str, seq: u64) {
+        self.console.clear_through(&Self::session_key(session), seq);
+    }
+
+    /// Keep a DOM snapshot of `session` under its name
+    pub fn save_dom_snapshot(&self, session: &str, snapshot: DomSnapshot) {
+        self.dom_snapshots.insert(&Self::session_key(session), snapshot);
+    }
+
+    /// The DOM snapshot of `session` saved as `name`
+    pub fn dom_snapshot(&self, session: &str, name: &str) -> Option<DomSnapshot> {
+        self.dom_snapshots.get(&Self::session_key(session), name)
+    }
+
+    /// Names of the DOM snapshots kept for `session`, oldest first
+    pub fn dom_snapshot_names(&self, session: &str) -> Vec<String> {
+        self.dom_snapshots.names(&Self::session_key(session))
+    }
+
+    /// Register an element of `session` found by a tool and return its handle
+    pub fn remember_element(&self, session: &str, reference: serde_json::Value) -> String {
+        self.element_handles.insert(&Self::session_key(session), reference)
+    }
+
+    /// Script arguments of `session` with `{"element": "el-N"}` handles replaced
+    /// by the elements they name
+    pub fn resolve_script_arguments(
+        &self,
+        session: &str,
+        arguments: Vec<serde_json::Value>,
+    ) -> std::result::Result<Vec<serde_json::Value>, String> {
+        self.element_handles.resolve_arguments(&Self::session_key(session), arguments)
+    }
+
+    /// Whether `navigate` dismisses consent banners in `session`
+    pub fn auto_dismiss_banners(&self, session: &str) -> bool {
+        let settings = self.banner_settings.lock().unwrap_or_else(|e| e.into_inner());
+        settings
+            .get(&Self::session_key(session))
+            .copied()
+            .unwrap_or(self.config.auto_dismiss_banners)
+    }
+
+    /// Count a call to `tool` against the quota of the session it names (the
+    /// default session when `None`), or refuse it when a limit is used up
+    pub fn charge_quota(&self, tool: &str, session_id: Option<String>) -> std::result::Result<(), QuotaExceeded> {
+        if !crate::quotas::is_metered(tool) {
+            return Ok(());
+        }
+        let session = session_id.unwrap_or_else(|| self.default_session_name());
+        self.quotas.charge(&Self::session_key(&session), &session, tool)
+    }
+
+    /// Quota and usage so far of `session`, if it was created with limits
+    pub fn quota_usage(&self, session: &str) -> Option<serde_json::Value> {
+        self.quotas.usage(&Self::session_key(session))
+    }
+
+    /// What `session` does with windows its pages open
+    pub fn popup_policy(&self, session: &str) -> PopupPolicy {
+        self.popups
+            .policy(&Self::session_key(session))
+            .or_else(|| PopupPolicy::from_name(&self.config.popup_policy))
+            .unwrap_or_default()
+    }
+
+    /// Popup events of `session`, oldest first; `clear` forgets them afterwards
+    pub fn popup_events(&self, session: &str, clear: bool) -> Vec<PopupEvent> {
+        self.popups.events(&Self::session_key(session), clear)
+    }
+
+    pub(crate) fn popups(&self) -> &PopupTracker {
+        &self.popups
+    }
+
+    /// Page lifecycle events buffered for `session`, oldest first; `clear` forgets them
+    pub fn page_events(&self, session: &str, clear: bool) -> Vec<PageEvent> {
+        self.page_events.events(&Self::session_key(session), clear)
+    }
+
+    pub(crate) fn page_event_buffer(&self) -> &PageEventBuffer {
+        &self.page_events
+    }
+
+    pub fn get_artifact_store(&self) -> &ArtifactStore {
+        &self.artifacts
+    }
+
+    /// Get the runtime counters shared by all client connections
+    pub fn get_stats(&self) -> &ServerStats {
+        &self.stats
+    }
+
+    /// Number of open sessions per browser name. Sessions whose browser is not
+    /// known (e.g. the stdio default session) are counted under "default".
+    pub async fn get_active_sessions_by_browser(&self) -> HashMap<String, usize> {
+        let clients = self.clients.lock().await;
+        let metadata = self.session_metadata.lock().await;
+
+        let mut counts = HashMap::new();
+        for session in clients.keys() {
+            let browser = self
+                .aliases
+                .get(session)
+                .map(|binding| binding.browser)
+                .or_else(|| metadata.get(session).map(|m| m.driver_type.clone()))
+                .or_else(|| self.extract_browser_preference_from_session(session))
+                .map(|driver_type| driver_type.browser_name().to_lowercase())
+                .unwrap_or_else(|| "default".to_string());
+            *counts.entry(browser).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Sessions of every principal that run on a driver process this server
+    /// manages: one of `driver_type`, or of any type when `None`. A session counts
+    /// when it was started at the driver's endpoint, or, where its endpoint is not
+    /// known, when its browser is `driver_type`.
+    pub async fn sessions_on_managed_driver(&self, driver_type: Option<&crate::driver::DriverType>) -> ManagedSessions {
+        let driver_endpoints: Vec<String> = self
+            .driver_manager
+            .get_managed_processes_status()
+            .await
+            .into_iter()
+            .filter(|(running, _, _)| driver_type.is_none_or(|driver_type| running == driver_type))
+            .map(|(_, _, port)| format!("http://localhost:{port}"))
+            .collect();
+        if driver_endpoints.is_empty() {
+            return ManagedSessions::default();
+        }
+
+        let keys: Vec<String> = self.clients.lock().await.keys().cloned().collect();
+        let metadata = self.session_metadata.lock().await;
+        let endpoints = self.session_endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        let mut keys: Vec<String> = keys
+            .into_iter()
+            .filter(|key| match endpoints.get(key) {
+                Some(endpoint) => driver_endpoints.contains(endpoint),
+                None => driver_type.is_none_or(|driver_type| {
+                    self.aliases
+                        .get(key)
+                        .map(|binding| binding.browser)
+                        .or_else(|| metadata.get(key).map(|m| m.driver_type.clone()))
+                        .or_else(|| self.extract_browser_preference_from_session(key))
+                        .is_some_and(|browser| &browser == driver_type)
+                }),
+            })
+            .collect();
+        keys.sort();
+        ManagedSessions { keys }
+    }
+
+    /// Close `sessions` before their driver goes away. Returns the ids and errors
+    /// of the current principal's sessions whose browser did not close cleanly;
+    /// the server forgets every one of them either way.
+    pub async fn close_managed_sessions(&self, sessions: &ManagedSessions) -> Vec<(String, WebDriverError)> {
+        let mut failed = Vec::new();
+        for key in &sessions.keys {
+            if let Err(e) = self.shut_key(key, true).await {
+                tracing::warn!("Session '{}' did not close cleanly before stopping its driver: {}", key, e);
+                if let Some(session) = Self::own_session_id(key) {
+                    failed.push((session, e));
+                }
+            }
+        }
+        failed
+    }
+
+    /// Session id the current principal knows the session `key` by; `None` for
+    /// another principal's session. Callers without a principal own the sessions
+    /// opened without one.
+    fn own_session_id(key: &str) -> Option<String> {
+        let (session_id, owner) = Self::split_session_key(key);
+        (owner == Self::current_principal().as_deref()).then_some(session_id)
+    }
+
+    /// Open sessions of the current principal, sorted by id
+    pub async fn list_sessions(&self) -> Vec<SessionInfo> {
+        let keys: Vec<String> = self.clients.lock().await.keys().cloned().collect();
+        let metadata = self.session_metadata.lock().await;
+        let endpoints = self.session_endpoints.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut sessions: Vec<SessionInfo> = keys
+            .iter()
+            .filter_map(|key| {
+                let session_id = Self::own_session_id(key)?;
+                let browser = self
+                    .aliases
+                    .get(key)
+                    .map(|binding| binding.browser)
+                    .or_else(|| metadata.get(key).map(|m| m.driver_type.clone()))
+                    .or_else(|| self.extract_browser_preference_from_session(&session_id))
+                    .map(|driver_type| driver_type.browser_name().to_lowercase());
+                Some(SessionInfo {
+                    session_id,
+                    browser,
+                    endpoint: endpoints.get(key).cloned(),
+                })
+            })
+            .collect();
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        sessions
+    }
+
     /// Get access to the connection pool
     pub fn get_pool(&self) -> &ConnectionPool {
         &self.pool
@@ -415,14 +1179,16 @@ impl ClientManager {
 
     /// Release a session back to the pool (marks it as idle for reuse)
     pub async fn release_session(&self, session_id: &str) {
+        let key = Self::session_key(session_id);
+
         // Get the driver type for this session
         let driver_type = {
             let metadata = self.session_metadata.lock().await;
-            metadata.get(session_id).map(|m| m.driver_type.clone())
+            metadata.get(&key).map(|m| m.driver_type.clone())
         };
 
         if let Some(driver_type) = driver_type {
-            self.pool.release(&driver_type, session_id).await;
+            self.pool.release(&driver_type, &key).await;
             tracing::debug!("Released session '{}' back to pool", session_id);
         }
     }
@@ -436,6 +1202,15 @@ impl ClientManager {
     pub async fn close_all_sessions(&self) -> Result<()> {
         tracing::info!("Closing all active WebDriver sessions...");
 
+        // Close hooks need the browsers, which closing the pool shuts
+        let open: Vec<(String, Client)> = {
+            let clients = self.clients.lock().await;
+            clients.iter().map(|(key, client)| (key.clone(), client.clone())).collect()
+        };
+        for (key, client) in &open {
+            self.run_session_hooks(key, client, HookEvent::Close).await;
+        }
+
         // Close all pooled connections first
         if self.pool.is_enabled() {
             tracing::debug!("Closing pooled connections...");
@@ -450,9 +1225,9 @@ impl ClientManager {
             metadata.clear();
         }
 
-        let mut clients = self.clients.lock().await;
+        let clients: Vec<(String, Client)> = self.clients.lock().await.drain().collect();
 
-        for (session_id, client) in clients.drain() {
+        for (session_id, client) in clients {
             tracing::debug!("Closing session: {}", session_id);
 
             // Add timeout to individual session close operations
@@ -626,23 +1401,265 @@ impl ClientManager {
         wait_timeout: Option<f64>,
     ) -> Result<Element> {
         let locator = Locator::Css(selector);
+        let call_timeout = self.call_timeout();
 
         if let Some(timeout_seconds) = wait_timeout {
             if timeout_seconds > 0.0 {
-                let timeout = Duration::from_secs_f64(timeout_seconds);
-                return client
-                    .wait()
-                    .at_most(timeout)
-                    .for_element(locator)
-                    .await
-                    .map_err(Into::into);
+                let timeout = crate::waits::clamped_wait(timeout_seconds);
+                // The wait polls the driver, so allow it the full wait plus one call
+                return match tokio::time::timeout(
+                    timeout + call_timeout,
+                    client.wait().at_most(timeout).for_element(locator),
+                )
+                .await
+                {
+                    Ok(result) => result.map_err(Into::into),
+                    Err(_) => Err(self.command_timeout("find_element")),
+                };
+            }
+        }
+
+        tokio::time::timeout(call_timeout, client.find(locator))
+            .await
+            .map_err(|_| self.command_timeout("find_element"))?
+            .map_err(Into::into)
+    }
+
+    /// Hard limit on a single WebDriver command (`webdriver_call_timeout_secs`)
+    pub fn call_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.webdriver_call_timeout_secs)
+    }
+
+    fn command_timeout(&self, operation: &str) -> WebDriverError {
+        WebDriverError::CommandTimeout {
+            operation: operation.to_string(),
+            timeout_secs: self.config.webdriver_call_timeout_secs,
+        }
+    }
+
+    /// Why robots.txt forbids loading `url`; always `None` unless `respect_robots_txt` is on
+    pub async fn robots_block(&self, url: &str) -> Option<RobotsBlock> {
+        self.robots.check(url).await
+    }
+
+    /// Load `url` in `session` once its origin's politeness slot has come,
+    /// unless robots.txt disallows it
+    pub async fn navigate(&self, session: &str, client: &Client, url: &str) -> Result<()> {
+        if let Some(block) = self.robots_block(url).await {
+            return Err(WebDriverError::Execution(block.describe()));
+        }
+        let wait = self.politeness.reserve(url);
+        if !wait.is_zero() {
+            tracing::debug!("Waiting {:?} for a politeness slot before loading {}", wait, url);
+            tokio::time::sleep(wait).await;
+        }
+        self.run_command(session, "navigate", client.goto(url)).await
+    }
+
+    /// Run a WebDriver command for `session` under the call timeout. A wedged
+    /// driver can leave a command hanging forever; on expiry the session gets a
+    /// short liveness check and is closed when it no longer answers, so the next
+    /// call starts a fresh browser instead of hanging again.
+    pub async fn run_command<T, E>(
+        &self,
+        session: &str,
+        operation: &str,
+        command: impl std::future::Future<Output = std::result::Result<T, E>>,
+    ) -> Result<T>
+    where
+        WebDriverError: From<E>,
+    {
+        self.run_command_within(session, operation, self.call_timeout(), command).await
+    }
+
+    /// `run_command` for a command known to take longer than one driver call,
+    /// e.g. typing with pauses between the keys, given `timeout` instead
+    pub async fn run_command_within<T, E>(
+        &self,
+        session: &str,
+        operation: &str,
+        timeout: Duration,
+        command: impl std::future::Future<Output = std::result::Result<T, E>>,
+    ) -> Result<T>
+    where
+        WebDriverError: From<E>,
+    {
+        match tokio::time::timeout(timeout, command).await {
+            Ok(result) => match result.map_err(WebDriverError::from) {
+                Err(error) if error.code() == ErrorCode::SessionCrashed => {
+                    tracing::warn!("Page crashed during '{}' in session '{}': {}", operation, session, error);
+                    Err(self.recover_crashed_session(session).await)
+                }
+                other => other,
+            },
+            Err(_) => {
+                tracing::warn!(
+                    "WebDriver command '{}' for session '{}' timed out after {}s",
+                    operation,
+                    session,
+                    timeout.as_secs()
+                );
+                self.recheck_session(session).await;
+                Err(WebDriverError::CommandTimeout {
+                    operation: operation.to_string(),
+                    timeout_secs: timeout.as_secs(),
+                })
             }
         }
+    }
+
+    /// A renderer crash leaves the session's tab unusable. With `recover_crashed_tabs`
+    /// the session gets a fresh tab, or is closed when the driver went down with the
+    /// page, so the next call can carry on instead of failing the same way.
+    async fn recover_crashed_session(&self, session: &str) -> WebDriverError {
+        self.stats.record_page_crash();
+        // Handles pointed into the crashed page
+        self.element_handles.remove(&Self::session_key(session));
+        // The replacement tab is the server's own, not a popup
+        self.popups.forget_windows(&Self::session_key(session));
+
+        let recovery = if !self.config.recover_crashed_tabs {
+            CrashRecovery::Disabled
+        } else {
+            match self.open_replacement_tab(session).await {
+                Ok(()) => CrashRecovery::NewTab,
+                Err(e) => {
+                    tracing::debug!("Could not open a new tab in crashed session '{}': {}", session, e);
+                    if let Err(e) = self.shut_session(session, false).await {
+                        tracing::debug!("Failed to close crashed session '{}': {}", session, e);
+                    }
+                    CrashRecovery::SessionClosed
+                }
+            }
+        };
+        WebDriverError::SessionCrashed { session: session.to_string(), recovery }
+    }
+
+    /// Open a tab in `session`, switch to it and make sure it answers
+    async fn open_replacement_tab(&self, session: &str) -> Result<()> {
+        let client = {
+            let clients = self.clients.lock().await;
+            clients.get(&Self::session_key(session)).cloned()
+        };
+        let Some(client) = client else {
+            return Err(WebDriverError::NoActiveSession { session: session.to_string() });
+        };
+
+        let replaced = async {
+            let tab = client.new_window(true).await?;
+            client.switch_to_window(tab.handle).await?;
+            client.current_url().await.map(|_| ())
+        };
+        tokio::time::timeout(LIVENESS_TIMEOUT, replaced)
+            .await
+            .map_err(|_| self.command_timeout("new_window"))?
+            .map_err(Into::into)
+    }
+
+    /// Close `session` if it no longer answers a liveness check
+    async fn recheck_session(&self, session: &str) {
+        let client = {
+            let clients = self.clients.lock().await;
+            clients.get(&Self::session_key(session)).cloned()
+        };
+        let Some(client) = client else {
+            return;
+        };
 
-        client.find(locator).await.map_err(Into::into)
+        if let Ok(Ok(_)) = tokio::time::timeout(LIVENESS_TIMEOUT, client.current_url()).await {
+            return;
+        }
+        tracing::warn!("Session '{}' is unresponsive, closing it", session);
+        if let Err(e) = self.shut_session(session, false).await {
+            tracing::debug!("Failed to close unresponsive session '{}': {}", session, e);
+        }
     }
 }
 
 // Note: Default is intentionally not implemented for ClientManager
 // because ClientManager::new() can fail if configuration validation fails.
 // Use ClientManager::new(Config::from_env()) with proper error handling instead.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_session_keys_are_scoped_by_principal() {
+        assert_eq!(ClientManager::session_key("default"), "default");
+
+        let (alice, bob) = tokio::join!(
+            ClientManager::with_principal(Some("alice".to_string()), async {
+                (
+                    ClientManager::session_key("firefox_work"),
+                    ClientManager::owned_by_current_principal("default@alice"),
+                    ClientManager::owned_by_current_principal("default@bob"),
+                )
+            }),
+            ClientManager::with_principal(Some("bob".to_string()), async {
+                ClientManager::session_key("firefox_work")
+            }),
+        );
+
+        assert_eq!(alice.0, "firefox_work@alice");
+        assert!(alice.1);
+        assert!(!alice.2);
+        assert_eq!(bob, "firefox_work@bob");
+        assert_ne!(alice.0, bob);
+
+        // An `@` in the session id cannot pass one principal's session off as another's
+        let (key, owner) = ClientManager::with_principal(Some("corp.com".to_string()), async {
+            (
+                ClientManager::session_key("x@alice"),
+                ClientManager::owned_by_current_principal("default@alice@corp.com"),
+            )
+        })
+        .await;
+        assert_eq!(key, "x%40alice@corp.com");
+        assert!(!owner);
+        assert_eq!(ClientManager::split_session_key(&key), ("x@alice".to_string(), Some("corp.com")));
+        assert_eq!(ClientManager::split_session_key("100%25%40"), ("100%@".to_string(), None));
+
+        // Without a principal only the shared namespace is owned
+        assert_eq!(ClientManager::own_session_id("default"), Some("default".to_string()));
+        assert_eq!(ClientManager::own_session_id("default@alice"), None);
+    }
+
+    #[tokio::test]
+    async fn test_browser_availability_reasons() {
+        let remote = ClientManager::new(Config {
+            webdriver_endpoint: "http://grid:4444".to_string(),
+            ..Config::default()
+        })
+        .unwrap();
+        assert_eq!(remote.browser_unavailable_reason(&crate::driver::DriverType::Edge).await, None);
+
+        let local = ClientManager::new(Config { auto_start_driver: false, ..Config::default() }).unwrap();
+        let reason = local.browser_unavailable_reason(&crate::driver::DriverType::Edge).await;
+        // Whether msedgedriver is installed depends on the machine running the test
+        assert!(reason.is_some_and(|reason| reason.contains("msedgedriver")));
+
+        let failover = ClientManager::new(Config {
+            auto_start_driver: false,
+            failover_endpoints: vec!["edge=http://backup:9515".to_string()],
+            ..Config::default()
+        })
+        .unwrap();
+        assert_eq!(failover.browser_unavailable_reason(&crate::driver::DriverType::Edge).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_bound_sessions_ignore_id_prefix() {
+        use crate::driver::DriverType;
+
+        let manager = ClientManager::new(Config::default()).unwrap();
+        assert_eq!(manager.session_browser("firefox_work"), Some(DriverType::Firefox));
+        assert_eq!(manager.session_browser("work"), None);
+
+        let session = manager.bind_session(Some("firefox_work".to_string()), SessionBinding::new(DriverType::Edge));
+        assert_eq!(session.await.unwrap(), "firefox_work");
+        assert_eq!(manager.session_browser("firefox_work"), Some(DriverType::Edge));
+        manager.bind_session(Some("work".to_string()), SessionBinding::new(DriverType::Firefox)).await.unwrap();
+        assert_eq!(manager.session_browser("work"), Some(DriverType::Firefox));
+    }
+}
@@ -0,0 +1,67 @@
+//! Cold page loads for first-visit performance
+//!
+//! A cold load clears the browser's HTTP cache, unregisters the target
+//! origin's service workers and deletes its Cache Storage, then loads the page
+//! with the HTTP cache disabled and service workers bypassed. Cookies and other
+//! storage stay, so a logged-in session stays logged in, and the same session
+//! can measure a first visit again and again. Needs Chrome or Edge.
+
+use fantoccini::Client;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{ClientManager, Result, cdp, site_pool};
+
+/// What was cleared before a cold load
+#[derive(Debug, Clone, Serialize)]
+pub struct ColdLoad {
+    pub url: String,
+    /// Caches emptied: `http_cache`, plus `service_workers` and `cache_storage` of the origin
+    pub cleared: Vec<&'static str>,
+}
+
+/// Load `url` in `session` as a first visit would. The HTTP cache and service
+/// workers are used again once the page has loaded, whether or not it did.
+pub async fn navigate_cold(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    url: &str,
+) -> Result<ColdLoad> {
+    let cleared = clear_caches(client_manager, session, client, url).await?;
+    let loaded = client_manager.navigate(session, client, url).await;
+    set_cache_bypass(client_manager, session, client, false).await.ok();
+    loaded.map(|()| ColdLoad { url: url.to_string(), cleared })
+}
+
+/// Load the session's current page again as a first visit
+pub async fn reload_cold(client_manager: &ClientManager, session: &str, client: &Client) -> Result<ColdLoad> {
+    let url = client_manager.run_command(session, "current_url", client.current_url()).await?;
+    navigate_cold(client_manager, session, client, url.as_str()).await
+}
+
+async fn clear_caches(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    url: &str,
+) -> Result<Vec<&'static str>> {
+    cdp::execute(client_manager, session, client, "Network.enable", json!({})).await?;
+    cdp::execute(client_manager, session, client, "Network.clearBrowserCache", json!({})).await?;
+    let mut cleared = vec!["http_cache"];
+    if let Some(origin) = site_pool::origin_of(url) {
+        let storage = json!({ "origin": origin, "storageTypes": "service_workers,cache_storage" });
+        cdp::execute(client_manager, session, client, "Storage.clearDataForOrigin", storage).await?;
+        cleared.extend(["service_workers", "cache_storage"]);
+    }
+    set_cache_bypass(client_manager, session, client, true).await?;
+    Ok(cleared)
+}
+
+async fn set_cache_bypass(client_manager: &ClientManager, session: &str, client: &Client, bypass: bool) -> Result<()> {
+    let disabled = json!({ "cacheDisabled": bypass });
+    cdp::execute(client_manager, session, client, "Network.setCacheDisabled", disabled).await?;
+    let bypass = json!({ "bypass": bypass });
+    cdp::execute(client_manager, session, client, "Network.setBypassServiceWorker", bypass).await?;
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use rust_browser_mcp::DriverManager;
+
+/// WebDriver executables the server starts
+const DRIVER_PROGRAMS: &[&str] = &["chromedriver", "geckodriver", "msedgedriver"];
+
+/// Browser executables a driver starts
+const BROWSER_PROGRAMS: &[&str] = &["chrome", "chromium", "chromium-browser", "google-chrome", "firefox", "msedge"];
+
+/// Arguments drivers start browsers with, which hand-started browsers lack
+const AUTOMATION_FLAGS: &[&str] = &["--enable-automation", "-marionette", "--remote-debugging-port"];
+
+/// Processes that adopt orphans: init, or a service manager acting as subreaper
+const REAPERS: &[&str] = &["init", "systemd"];
+
+/// Kill WebDriver and automated browser processes left behind by a crashed or
+/// force-killed server, without starting a new one. Only orphans are killed:
+/// drivers, and browsers started by a driver, whose parent is gone. Drivers
+/// and browsers another program still runs, e.g. a Selenium suite or a
+/// running server, are left alone unless `force` is set.
+pub async fn run_cleanup(force: bool) -> Result<()> {
+    if force {
+        println!("Stopping every WebDriver and automated browser process (--force)...");
+        DriverManager::new().force_cleanup_all_processes().await?;
+        println!("Cleanup complete");
+        return Ok(());
+    }
+
+    println!("Stopping orphaned WebDriver and automated browser processes...");
+    let output = tokio::process::Command::new("ps")
+        .args(["-eo", "pid=,ppid=,args="])
+        .output()
+        .await
+        .context("Failed to list processes with ps")?;
+    let orphans = orphaned_processes(&String::from_utf8_lossy(&output.stdout));
+    if orphans.is_empty() {
+        println!("No orphaned processes found");
+        return Ok(());
+    }
+
+    for (pid, program) in &orphans {
+        let killed = tokio::process::Command::new("kill")
+            .args(["-9", &pid.to_string()])
+            .status()
+            .await
+            .is_ok_and(|status| status.success());
+        if killed {
+            println!("  Killed {program} (PID {pid})");
+        } else {
+            println!("  Could not kill {program} (PID {pid})");
+        }
+    }
+    println!("Cleanup complete");
+    Ok(())
+}
+
+/// Orphaned drivers and automated browsers in `ps -eo pid=,ppid=,args=` output,
+/// with every process they started, as PID and program name
+fn orphaned_processes(ps_output: &str) -> Vec<(u32, String)> {
+    let processes: HashMap<u32, (u32, String, String)> = ps_output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let args: Vec<&str> = fields.collect();
+            let program = args.first()?.rsplit('/').next()?.to_string();
+            Some((pid, (ppid, program, args.join(" "))))
+        })
+        .collect();
+
+    let adopted = |ppid: u32| {
+        ppid == 1 || processes.get(&ppid).is_some_and(|(_, program, _)| REAPERS.contains(&program.as_str()))
+    };
+    let mut orphans: Vec<u32> = processes
+        .iter()
+        .filter(|(_, (ppid, program, args))| {
+            let driver = DRIVER_PROGRAMS.contains(&program.as_str());
+            let browser = BROWSER_PROGRAMS.contains(&program.as_str())
+                && AUTOMATION_FLAGS.iter().any(|flag| args.contains(flag));
+            (driver || browser) && adopted(*ppid)
+        })
+        .map(|(pid, _)| *pid)
+        .collect();
+
+    // Then the browsers and helpers they started, at any depth
+    let mut index = 0;
+    while index < orphans.len() {
+        let parent = orphans[index];
+        let children = processes.iter().filter(|(_, (ppid, _, _))| *ppid == parent).map(|(pid, _)| *pid);
+        let children: Vec<u32> = children.filter(|pid| !orphans.contains(pid)).collect();
+        orphans.extend(children);
+        index += 1;
+    }
+
+    orphans
+        .into_iter()
+        .map(|pid| (pid, processes[&pid].1.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_orphans_are_selected() {
+        let ps = "
+              1     0 /sbin/init
+            900     1 /usr/lib/systemd/systemd --user
+            100     1 /usr/bin/chromedriver --port=9515
+            101   100 /opt/google/chrome/chrome --headless --enable-automation
+            102   101 /opt/google/chrome/chrome --type=renderer
+            200   900 /usr/bin/firefox -marionette -headless
+            300   299 /usr/bin/java -jar selenium-tests.jar
+            301   300 /usr/bin/geckodriver --port 4444
+            400     1 /usr/bin/firefox
+            500   499 rust-browser-mcp serve
+            501   500 chromedriver --port=9516
+        ";
+        let mut orphans = orphaned_processes(ps);
+        orphans.sort();
+        assert_eq!(
+            orphans,
+            vec![
+                (100, "chromedriver".to_string()),
+                (101, "chrome".to_string()),
+                (102, "chrome".to_string()),
+                (200, "firefox".to_string()),
+            ]
+        );
+        assert!(orphaned_processes("").is_empty());
+    }
+}
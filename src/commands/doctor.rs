@@ -0,0 +1,189 @@
+use std::{
+    net::TcpListener,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Result;
+use rust_browser_mcp::{Config, DriverManager, DriverType};
+
+/// Diagnose the local setup: configuration, driver executables, browsers and
+/// driver ports. Every failed check prints how to fix it; the command exits
+/// with an error when anything needs attention.
+pub async fn run_doctor(config: &Config) -> Result<()> {
+    let mut report = Report::default();
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .unwrap_or_default();
+
+    println!("Configuration");
+    match config.validate() {
+        Ok(()) => report.ok("Configuration is valid"),
+        Err(e) => report.fail(&e, "Fix the setting in your config file, environment or command line flags"),
+    }
+
+    if config.webdriver_endpoint != "auto" {
+        println!("\nWebDriver endpoint");
+        let status_url = format!("{}/status", config.webdriver_endpoint.trim_end_matches('/'));
+        match http.get(&status_url).send().await {
+            Ok(response) if response.status().is_success() => {
+                report.ok(&format!("{} is reachable", config.webdriver_endpoint))
+            }
+            Ok(response) => report.fail(
+                &format!("{} answered {}", config.webdriver_endpoint, response.status()),
+                "Check that the URL points at a WebDriver server, not another service",
+            ),
+            Err(e) => report.fail(
+                &format!("{} is unreachable: {e}", config.webdriver_endpoint),
+                "Start the driver there, or set WEBDRIVER_ENDPOINT=auto to let the server start one",
+            ),
+        }
+    }
+
+    let detected: Vec<(DriverType, PathBuf)> = DriverManager::new().detect_available_drivers();
+    for driver_type in [DriverType::Chrome, DriverType::Firefox, DriverType::Edge] {
+        let name = driver_type.browser_name().to_lowercase();
+        let wanted = config
+            .concurrent_drivers
+            .iter()
+            .any(|driver| DriverType::from_string(driver).as_ref() == Some(&driver_type));
+        println!("\n{}{}", driver_type.browser_name(), if wanted { "" } else { " (not configured)" });
+
+        match detected.iter().find(|(found, _)| *found == driver_type) {
+            Some((_, path)) => report.ok(&format!(
+                "{} found at {}{}",
+                driver_type.executable_name(),
+                path.display(),
+                executable_version(path).map(|v| format!(" ({v})")).unwrap_or_default()
+            )),
+            None if wanted => report.fail(
+                &format!("{} not found", driver_type.executable_name()),
+                driver_install_hint(&driver_type),
+            ),
+            None => report.skip(&format!("{} not installed", driver_type.executable_name())),
+        }
+
+        match find_browser(&driver_type) {
+            Some(path) => report.ok(&format!("Browser found at {}", path.display())),
+            None if wanted => report.fail(
+                &format!("No {name} browser found on PATH"),
+                "Install the browser, or remove it from concurrent_drivers / WEBDRIVER_CONCURRENT_DRIVERS",
+            ),
+            None => report.skip("Browser not installed"),
+        }
+
+        if wanted && config.webdriver_endpoint == "auto" {
+            let port = driver_type.default_port();
+            if TcpListener::bind(("127.0.0.1", port)).is_ok() {
+                report.ok(&format!("Port {port} is free"));
+            } else if http
+                .get(format!("http://127.0.0.1:{port}/status"))
+                .send()
+                .await
+                .is_ok_and(|response| response.status().is_success())
+            {
+                report.warn(
+                    &format!("A WebDriver is already listening on port {port}"),
+                    "It will be reused; if it is left over from a crashed server, `rust-browser-mcp cleanup` stops it",
+                );
+            } else {
+                report.fail(
+                    &format!("Port {port} is used by another program"),
+                    "Stop that program, or run the driver elsewhere and set WEBDRIVER_ENDPOINT",
+                );
+            }
+        }
+    }
+
+    println!();
+    match report.failures {
+        0 if report.warnings == 0 => {
+            println!("Everything looks good");
+            Ok(())
+        }
+        0 => {
+            println!("{} warning(s); the server should still start", report.warnings);
+            Ok(())
+        }
+        failures => Err(anyhow::anyhow!("{failures} problem(s) found")),
+    }
+}
+
+#[derive(Default)]
+struct Report {
+    failures: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn ok(&mut self, message: &str) {
+        println!("  ✅ {message}");
+    }
+
+    fn skip(&mut self, message: &str) {
+        println!("  ➖ {message}");
+    }
+
+    fn warn(&mut self, message: &str, fix: &str) {
+        self.warnings += 1;
+        println!("  ⚠️  {message}\n     → {fix}");
+    }
+
+    fn fail(&mut self, message: &str, fix: &str) {
+        self.failures += 1;
+        println!("  ❌ {message}\n     → {fix}");
+    }
+}
+
+fn driver_install_hint(driver_type: &DriverType) -> &'static str {
+    match driver_type {
+        DriverType::Chrome => {
+            "Install ChromeDriver: `brew install chromedriver`, `sudo apt-get install chromium-chromedriver`, \
+             or download from https://chromedriver.chromium.org/"
+        }
+        DriverType::Firefox => {
+            "Install GeckoDriver: `brew install geckodriver`, `sudo apt-get install firefox-geckodriver`, \
+             or download from https://github.com/mozilla/geckodriver/releases"
+        }
+        DriverType::Edge => {
+            "Install EdgeDriver from https://developer.microsoft.com/microsoft-edge/tools/webdriver/"
+        }
+    }
+}
+
+/// First line of `<executable> --version`, e.g. `ChromeDriver 126.0.6478.126`
+fn executable_version(path: &Path) -> Option<String> {
+    let output = std::process::Command::new(path).arg("--version").output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next().map(|line| line.trim().to_string()).filter(|line| !line.is_empty())
+}
+
+/// Locate the browser binary a driver launches
+fn find_browser(driver_type: &DriverType) -> Option<PathBuf> {
+    let (names, app_bundle): (&[&str], &str) = match driver_type {
+        DriverType::Chrome => (
+            &["google-chrome", "google-chrome-stable", "chromium", "chromium-browser", "chrome"],
+            "/Applications/Google Chrome.app",
+        ),
+        DriverType::Firefox => (&["firefox"], "/Applications/Firefox.app"),
+        DriverType::Edge => (
+            &["microsoft-edge", "microsoft-edge-stable", "msedge"],
+            "/Applications/Microsoft Edge.app",
+        ),
+    };
+
+    if cfg!(target_os = "macos") && Path::new(app_bundle).exists() {
+        return Some(PathBuf::from(app_bundle));
+    }
+
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .flat_map(|dir| {
+            names.iter().flat_map(move |name| {
+                let candidate = dir.join(name);
+                [candidate.with_extension("exe"), candidate]
+            })
+        })
+        .find(|candidate| candidate.is_file())
+}
@@ -0,0 +1,7 @@
+pub mod cleanup;
+pub mod doctor;
+pub mod recipes;
+
+pub use cleanup::run_cleanup;
+pub use doctor::run_doctor;
+pub use recipes::{RecipesCommand, run_recipes_command};
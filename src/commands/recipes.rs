@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use clap::Subcommand;
+use rust_browser_mcp::{Config, ExecutionContext, Recipe, RecipeExecutor, RecipeManager, ReportFormat, WebDriverServer};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Subcommand)]
+pub enum RecipesCommand {
+    /// List saved recipes
+    List,
+    /// Check saved recipes for errors without running them (all recipes unless a name is given)
+    Validate {
+        /// Recipe to validate
+        name: Option<String>,
+    },
+    /// Run a saved recipe in a local browser and print the result
+    Run {
+        /// Recipe to run
+        name: String,
+        /// Recipe parameter as `name=value` (repeatable)
+        #[arg(short, long = "param", value_name = "NAME=VALUE", value_parser = parse_parameter)]
+        params: Vec<(String, String)>,
+        /// Run-time variable referenced as `{{name}}`, as `name=value` (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_parameter)]
+        variables: Vec<(String, String)>,
+        /// Keep going after a failed step
+        #[arg(long)]
+        continue_on_error: bool,
+        /// Write an `html` or `markdown` report of the run to the artifacts directory
+        #[arg(long, value_name = "FORMAT", value_parser = parse_report)]
+        report: Option<ReportFormat>,
+    },
+}
+
+fn parse_report(raw: &str) -> Result<ReportFormat, String> {
+    ReportFormat::from_name(raw).ok_or_else(|| format!("expected html or markdown, got '{raw}'"))
+}
+
+fn parse_parameter(raw: &str) -> Result<(String, String), String> {
+    raw.split_once('=')
+        .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{raw}'"))
+}
+
+/// Work with saved recipes offline, without an MCP client
+pub async fn run_recipes_command(config: Config, command: RecipesCommand) -> Result<()> {
+    let recipe_manager = RecipeManager::from_config(&config);
+
+    match command {
+        RecipesCommand::List => {
+            let recipes = recipe_manager.list_recipes().await?;
+            if recipes.is_empty() {
+                println!("No recipes in {}", recipe_manager.location());
+            }
+            for info in recipes {
+                println!(
+                    "{} ({} steps){}",
+                    info.name,
+                    info.step_count,
+                    info.description.map(|d| format!(" - {d}")).unwrap_or_default()
+                );
+            }
+            Ok(())
+        }
+        RecipesCommand::Validate { name: Some(name) } => {
+            recipe_manager.load_recipe(&name).await?;
+            println!("✅ {name}");
+            Ok(())
+        }
+        RecipesCommand::Validate { name: None } => validate_all(&recipe_manager).await,
+        RecipesCommand::Run { name, params, variables, continue_on_error, report } => {
+            let recipe = recipe_manager.load_recipe(&name).await?;
+            let context = ExecutionContext {
+                session_id: None,
+                variables: variables.into_iter().collect(),
+                continue_on_error,
+            };
+            let report = report.or_else(|| config.recipe_report.as_deref().and_then(ReportFormat::from_name));
+            run_recipe(config, &recipe, params.into_iter().collect(), context, report).await
+        }
+    }
+}
+
+/// Validate every saved recipe, reporting each broken one
+async fn validate_all(recipe_manager: &RecipeManager) -> Result<()> {
+    let sources = recipe_manager.load_recipe_sources().await?;
+    if sources.is_empty() {
+        println!("No recipes in {}", recipe_manager.location());
+        return Ok(());
+    }
+
+    let mut invalid = 0;
+    for (location, source) in sources {
+        let outcome = source
+            .map_err(|e| e.to_string())
+            .and_then(|json| Recipe::from_json(&json).map_err(|e| e.to_string()))
+            .and_then(|recipe| recipe.validate().map(|()| recipe.name));
+        match outcome {
+            Ok(name) => println!("✅ {name}"),
+            Err(e) => {
+                invalid += 1;
+                println!("❌ {location}: {e}");
+            }
+        }
+    }
+
+    if invalid > 0 {
+        return Err(anyhow!("{invalid} invalid recipe(s)"));
+    }
+    Ok(())
+}
+
+async fn run_recipe(
+    config: Config,
+    recipe: &Recipe,
+    parameters: HashMap<String, String>,
+    context: ExecutionContext,
+    report: Option<ReportFormat>,
+) -> Result<()> {
+    let server = WebDriverServer::with_config(config)?;
+
+    // Ctrl+C stops the run at the next step boundary so browsers are still closed
+    let cancellation = CancellationToken::new();
+    tokio::spawn({
+        let cancellation = cancellation.clone();
+        async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancellation.cancel();
+            }
+        }
+    });
+
+    let outcome = RecipeExecutor::new(&server)
+        .with_cancellation(cancellation)
+        .with_report(report)
+        .execute_recipe(recipe, Some(parameters), context)
+        .await;
+
+    if let Err(e) = server.cleanup().await {
+        tracing::warn!("Error during cleanup: {}", e);
+    }
+
+    let result = outcome?;
+    if result.success {
+        println!("{}", result.to_summary_string());
+        Ok(())
+    } else {
+        println!("{}", result.to_detailed_string());
+        Err(anyhow!("Recipe '{}' failed", recipe.name))
+    }
+}
@@ -0,0 +1,211 @@
+//! Server-side buffer of browser console output
+//!
+//! Pages record console calls and uncaught errors into `window.__mcpConsoleLogs`,
+//! which is lost on every navigation. Each read drains that page buffer into a
+//! per-session buffer here, so entries survive page loads and repeated reads do
+//! not have to wait for the page to produce them again.
+//!
+//! Levels are normalized on the way in, so filters work the same whichever
+//! browser or log source produced an entry. Each entry also gets a `seq`
+//! number, increasing per session, that callers use as a cursor: the highest
+//! one handed out is the session's high watermark.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use serde_json::{Value, json};
+
+/// Entries kept per session before the oldest are dropped
+pub const MAX_CONSOLE_ENTRIES: usize = 5000;
+
+/// Removes and returns everything the page recorded since the last drain
+pub const DRAIN_CONSOLE_SCRIPT: &str = r#"
+    try {
+        const logs = window.__mcpConsoleLogs;
+        return logs ? logs.splice(0, logs.length) : [];
+    } catch (e) {
+        return [];
+    }
+"#;
+
+/// The level filters use for a browser or driver level name: `error`, `warn`,
+/// `info` or `debug`. `console.log` output counts as `info`.
+pub fn normalize_level(level: &str) -> &'static str {
+    match level.to_ascii_lowercase().as_str() {
+        "error" | "severe" | "assert" | "exception" => "error",
+        "warn" | "warning" => "warn",
+        "debug" | "verbose" | "trace" | "fine" | "finer" | "finest" => "debug",
+        _ => "info",
+    }
+}
+
+/// Collapse repeats of the same message from the same place into their first
+/// occurrence, with a `count` and the `last_timestamp` of the repeats
+pub fn dedup_entries(entries: Vec<Value>) -> Vec<Value> {
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut unique: Vec<Value> = Vec::new();
+    for mut entry in entries {
+        let key = json!([entry["level"], entry["message"], entry["source"], entry["line"]]).to_string();
+        match positions.get(&key) {
+            Some(&position) => {
+                let first = &mut unique[position];
+                first["count"] = json!(first["count"].as_u64().unwrap_or(1) + 1);
+                first["last_timestamp"] = entry["timestamp"].clone();
+            }
+            None => {
+                if let Some(fields) = entry.as_object_mut() {
+                    fields.insert("count".to_string(), json!(1));
+                }
+                positions.insert(key, unique.len());
+                unique.push(entry);
+            }
+        }
+    }
+    unique
+}
+
+/// Sequence number of a buffered entry; 0 for entries without one
+pub fn entry_seq(entry: &Value) -> u64 {
+    entry["seq"].as_u64().unwrap_or(0)
+}
+
+#[derive(Debug, Default)]
+struct SessionLog {
+    entries: VecDeque<Value>,
+    /// `seq` of the newest entry ever appended, cleared or not
+    high_watermark: u64,
+}
+
+/// Console entries collected per session
+#[derive(Debug, Clone)]
+pub struct ConsoleBuffer {
+    sessions: Arc<Mutex<HashMap<String, SessionLog>>>,
+    max_entries: usize,
+}
+
+impl Default for ConsoleBuffer {
+    fn default() -> Self {
+        Self::new(MAX_CONSOLE_ENTRIES)
+    }
+}
+
+impl ConsoleBuffer {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    /// Append entries drained from the page. Returns how many were added.
+    pub fn append(&self, session: &str, entries: Vec<Value>) -> usize {
+        if entries.is_empty() {
+            return 0;
+        }
+        let added = entries.len();
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let log = sessions.entry(session.to_string()).or_default();
+        for mut entry in entries {
+            if let Some(fields) = entry.as_object_mut() {
+                let level = normalize_level(fields.get("level").and_then(Value::as_str).unwrap_or_default());
+                fields.insert("level".to_string(), json!(level));
+                log.high_watermark += 1;
+                fields.insert("seq".to_string(), json!(log.high_watermark));
+            }
+            log.entries.push_back(entry);
+        }
+        while log.entries.len() > self.max_entries {
+            log.entries.pop_front();
+        }
+        added
+    }
+
+    /// Every buffered entry of `session`, oldest first
+    pub fn entries(&self, session: &str) -> Vec<Value> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions
+            .get(session)
+            .map(|log| log.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// `seq` of the newest entry `session` ever had, 0 before the first one
+    pub fn high_watermark(&self, session: &str) -> u64 {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.get(session).map_or(0, |log| log.high_watermark)
+    }
+
+    /// Drop the entries of `session` up to and including `seq`; newer ones stay
+    pub fn clear_through(&self, session: &str, seq: u64) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(log) = sessions.get_mut(session) {
+            log.entries.retain(|entry| entry_seq(entry) > seq);
+        }
+    }
+
+    /// Forget a closed session's entries
+    pub fn remove(&self, session: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(session);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_keeps_newest_entries_per_session() {
+        let buffer = ConsoleBuffer::new(2);
+        assert_eq!(buffer.append("a", vec![json!(1), json!(2)]), 2);
+        assert_eq!(buffer.append("a", vec![json!(3)]), 1);
+        assert_eq!(buffer.append("b", vec![]), 0);
+
+        assert_eq!(buffer.entries("a"), vec![json!(2), json!(3)]);
+        assert!(buffer.entries("b").is_empty());
+
+        buffer.remove("a");
+        assert!(buffer.entries("a").is_empty());
+    }
+
+    #[test]
+    fn test_levels_are_normalized_and_repeats_collapsed() {
+        assert_eq!(normalize_level("SEVERE"), "error");
+        assert_eq!(normalize_level("warning"), "warn");
+        assert_eq!(normalize_level("log"), "info");
+        assert_eq!(normalize_level("verbose"), "debug");
+
+        let buffer = ConsoleBuffer::default();
+        let tick = |timestamp| {
+            json!({ "level": "log", "message": "tick", "source": "app.js", "line": 3, "timestamp": timestamp })
+        };
+        buffer.append("a", vec![tick(1), json!({ "level": "warning", "message": "slow" }), tick(2), tick(3)]);
+        let logs = dedup_entries(buffer.entries("a"));
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0]["level"], "info");
+        assert_eq!(logs[0]["count"], 3);
+        assert_eq!(logs[0]["timestamp"], 1);
+        assert_eq!(logs[0]["last_timestamp"], 3);
+        assert_eq!(logs[1]["level"], "warn");
+        assert_eq!(logs[1]["count"], 1);
+    }
+
+    #[test]
+    fn test_watermark_survives_clearing() {
+        let buffer = ConsoleBuffer::default();
+        assert_eq!(buffer.high_watermark("a"), 0);
+        buffer.append("a", vec![json!({ "message": "one" }), json!({ "message": "two" })]);
+        assert_eq!(buffer.high_watermark("a"), 2);
+        assert_eq!(entry_seq(&buffer.entries("a")[1]), 2);
+
+        buffer.clear_through("a", 1);
+        buffer.append("a", vec![json!({ "message": "three" })]);
+        let seqs: Vec<u64> = buffer.entries("a").iter().map(entry_seq).collect();
+        assert_eq!(seqs, vec![2, 3]);
+        buffer.clear_through("a", buffer.high_watermark("a"));
+        assert!(buffer.entries("a").is_empty());
+        assert_eq!(buffer.high_watermark("a"), 3);
+    }
+}
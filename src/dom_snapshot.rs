@@ -0,0 +1,448 @@
+//! Normalized DOM snapshots and the differences between two of them
+//!
+//! A snapshot is the element tree of the page, or of one subtree, with each
+//! element's attributes and its own whitespace-collapsed text. Scripts, styles
+//! and the server's highlight overlay are left out, and live form state is
+//! recorded as `(value)` / `(checked)` pseudo-attributes. When two snapshots are
+//! compared, children are matched by tag, id and class rather than by position,
+//! so one inserted list item shows up as one added node instead of every later
+//! sibling appearing changed.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::text;
+
+/// Elements captured per snapshot before the tree is cut off
+pub const MAX_SNAPSHOT_NODES: u64 = 20000;
+
+/// Named snapshots kept per session; the oldest is dropped first
+pub const MAX_SNAPSHOTS_PER_SESSION: usize = 20;
+
+/// Sibling lists longer than this product are aligned by position instead of
+/// by longest common subsequence
+const MAX_ALIGNMENT_CELLS: usize = 1_000_000;
+
+/// Serializes the tree under `arguments[0]` (default: the whole document)
+pub const SNAPSHOT_SCRIPT: &str = r#"
+    const [rootSelector, maxNodes] = arguments;
+    let root;
+    try {
+        root = rootSelector ? document.querySelector(rootSelector) : document.documentElement;
+    } catch (e) {
+        return { error: String(e) };
+    }
+    if (!root) {
+        return { error: `no element matches '${rootSelector}'` };
+    }
+
+    const skipped = new Set(['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE', 'LINK', 'META']);
+    let count = 0;
+    let truncated = false;
+    const ownText = el => Array.from(el.childNodes)
+        .filter(node => node.nodeType === Node.TEXT_NODE)
+        .map(node => node.textContent)
+        .join(' ')
+        .replace(/\s+/g, ' ')
+        .trim();
+    const capture = el => {
+        if (count >= maxNodes) {
+            truncated = true;
+            return null;
+        }
+        count += 1;
+        const attributes = {};
+        for (const attr of el.attributes) {
+            attributes[attr.name] = attr.value;
+        }
+        // Live form state is not reflected in attributes
+        if (['INPUT', 'TEXTAREA', 'SELECT'].includes(el.tagName) && el.type !== 'password') {
+            attributes['(value)'] = el.value;
+        }
+        if (el.type === 'checkbox' || el.type === 'radio') {
+            attributes['(checked)'] = String(el.checked);
+        }
+        const children = [];
+        for (const child of el.children) {
+            if (skipped.has(child.tagName) || child.id === '__mcp_highlights') continue;
+            const node = capture(child);
+            if (node) children.push(node);
+        }
+        const node = { tag: el.tagName.toLowerCase(), attributes, children };
+        const text = ownText(el);
+        if (text) node.text = text;
+        return node;
+    };
+
+    const tree = capture(root);
+    return { url: location.href, node_count: count, truncated, tree };
+"#;
+
+/// One element of a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DomNode {
+    pub tag: String,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub children: Vec<DomNode>,
+}
+
+impl DomNode {
+    /// Number of elements in this subtree, including this one
+    pub fn size(&self) -> usize {
+        1 + self.children.iter().map(DomNode::size).sum::<usize>()
+    }
+
+    /// What matches children across snapshots
+    fn signature(&self) -> (&str, Option<&String>, Option<&String>) {
+        (&self.tag, self.attributes.get("id"), self.attributes.get("class"))
+    }
+
+    /// Opening tag with id and class, followed by a preview of its text
+    pub fn describe(&self) -> String {
+        let mut description = format!("<{}", self.tag);
+        for name in ["id", "class", "name", "href"] {
+            if let Some(value) = self.attributes.get(name) {
+                description.push_str(&format!(" {}=\"{}\"", name, text::preview(value, 60)));
+            }
+        }
+        description.push('>');
+        if let Some(content) = &self.text {
+            description.push_str(&text::preview(content, 60));
+        }
+        description
+    }
+}
+
+/// A captured snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct DomSnapshot {
+    pub name: String,
+    pub url: String,
+    /// Selector the snapshot was taken under, `None` for the whole document
+    pub root: Option<String>,
+    pub taken_at: String,
+    pub node_count: u64,
+    pub truncated: bool,
+    pub tree: DomNode,
+}
+
+/// Before and after value of an attribute or text
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueChange {
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// One difference between two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DomChange {
+    Added { path: String, node: String, descendants: usize },
+    Removed { path: String, node: String, descendants: usize },
+    Changed {
+        path: String,
+        node: String,
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        attributes: BTreeMap<String, ValueChange>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        text: Option<ValueChange>,
+    },
+}
+
+impl DomChange {
+    /// One-line rendering: `+` added, `-` removed, `~` changed
+    pub fn summary(&self) -> String {
+        let quoted = |value: &Option<String>| match value {
+            Some(value) => format!("\"{}\"", text::preview(value, 60)),
+            None => "(none)".to_string(),
+        };
+        match self {
+            Self::Added { path, node, descendants } => format!("+ {path} {node}{}", nested(*descendants)),
+            Self::Removed { path, node, descendants } => format!("- {path} {node}{}", nested(*descendants)),
+            Self::Changed { path, attributes, text, .. } => {
+                let mut parts: Vec<String> = attributes
+                    .iter()
+                    .map(|(name, change)| format!("{name}: {} → {}", quoted(&change.before), quoted(&change.after)))
+                    .collect();
+                if let Some(change) = text {
+                    parts.push(format!("text: {} → {}", quoted(&change.before), quoted(&change.after)));
+                }
+                format!("~ {path} {}", parts.join("; "))
+            }
+        }
+    }
+}
+
+fn nested(descendants: usize) -> String {
+    if descendants > 0 {
+        format!(" (+{descendants} nested)")
+    } else {
+        String::new()
+    }
+}
+
+/// Every difference between `before` and `after`, parents before children
+pub fn diff(before: &DomNode, after: &DomNode, root_path: &str) -> Vec<DomChange> {
+    let mut changes = Vec::new();
+    if before.tag != after.tag {
+        changes.push(removed(root_path, before));
+        changes.push(added(root_path, after));
+    } else {
+        diff_nodes(before, after, root_path, &mut changes);
+    }
+    changes
+}
+
+fn added(path: &str, node: &DomNode) -> DomChange {
+    DomChange::Added { path: path.to_string(), node: node.describe(), descendants: node.size() - 1 }
+}
+
+fn removed(path: &str, node: &DomNode) -> DomChange {
+    DomChange::Removed { path: path.to_string(), node: node.describe(), descendants: node.size() - 1 }
+}
+
+fn diff_nodes(before: &DomNode, after: &DomNode, path: &str, changes: &mut Vec<DomChange>) {
+    let mut attributes = BTreeMap::new();
+    for name in before.attributes.keys().chain(after.attributes.keys()) {
+        let (old, new) = (before.attributes.get(name), after.attributes.get(name));
+        if old != new {
+            attributes.insert(name.clone(), ValueChange { before: old.cloned(), after: new.cloned() });
+        }
+    }
+    let text = (before.text != after.text).then(|| ValueChange { before: before.text.clone(), after: after.text.clone() });
+    if !attributes.is_empty() || text.is_some() {
+        changes.push(DomChange::Changed { path: path.to_string(), node: after.describe(), attributes, text });
+    }
+
+    let before_paths = child_paths(path, &before.children);
+    let after_paths = child_paths(path, &after.children);
+    for pair in align(&before.children, &after.children) {
+        match pair {
+            (Some(i), Some(j)) => diff_nodes(&before.children[i], &after.children[j], &after_paths[j], changes),
+            (Some(i), None) => changes.push(removed(&before_paths[i], &before.children[i])),
+            (None, Some(j)) => changes.push(added(&after_paths[j], &after.children[j])),
+            (None, None) => {}
+        }
+    }
+}
+
+/// Selector-like path of each child: `#id` when it has one, otherwise the tag,
+/// with `:nth-of-type` when siblings share it
+fn child_paths(parent: &str, children: &[DomNode]) -> Vec<String> {
+    let mut totals: HashMap<&str, usize> = HashMap::new();
+    for child in children {
+        *totals.entry(child.tag.as_str()).or_default() += 1;
+    }
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    children
+        .iter()
+        .map(|child| {
+            let index = seen.entry(child.tag.as_str()).or_default();
+            *index += 1;
+            match child.attributes.get("id").filter(|id| !id.is_empty()) {
+                Some(id) => format!("{}#{}", child.tag, id),
+                None if totals[child.tag.as_str()] > 1 => {
+                    format!("{parent} > {}:nth-of-type({index})", child.tag)
+                }
+                None => format!("{parent} > {}", child.tag),
+            }
+        })
+        .collect()
+}
+
+/// Pair children of two snapshots: matching signatures along their longest
+/// common subsequence, then leftovers with the same tag between those anchors
+/// (the same element with a changed class, say). Unpaired entries are additions
+/// or removals.
+fn align(before: &[DomNode], after: &[DomNode]) -> Vec<(Option<usize>, Option<usize>)> {
+    let anchors = if before.len() * after.len() <= MAX_ALIGNMENT_CELLS {
+        common_subsequence(before, after)
+    } else {
+        (0..before.len().min(after.len()))
+            .filter(|&i| before[i].signature() == after[i].signature())
+            .map(|i| (i, i))
+            .collect()
+    };
+
+    let mut pairs = Vec::with_capacity(before.len().max(after.len()));
+    let (mut i, mut j) = (0, 0);
+    for (anchor_i, anchor_j) in anchors.into_iter().chain(std::iter::once((before.len(), after.len()))) {
+        pair_gap(before, after, i..anchor_i, j..anchor_j, &mut pairs);
+        if anchor_i < before.len() && anchor_j < after.len() {
+            pairs.push((Some(anchor_i), Some(anchor_j)));
+        }
+        (i, j) = (anchor_i + 1, anchor_j + 1);
+    }
+    pairs
+}
+
+/// Matching positions along the longest common subsequence of signatures
+fn common_subsequence(before: &[DomNode], after: &[DomNode]) -> Vec<(usize, usize)> {
+    let (n, m) = (before.len(), after.len());
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[at(i, j)] = if before[i].signature() == after[j].signature() {
+                lengths[at(i + 1, j + 1)] + 1
+            } else {
+                lengths[at(i + 1, j)].max(lengths[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut matches = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if before[i].signature() == after[j].signature() {
+            matches.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[at(i + 1, j)] >= lengths[at(i, j + 1)] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    matches
+}
+
+fn pair_gap(
+    before: &[DomNode],
+    after: &[DomNode],
+    before_gap: std::ops::Range<usize>,
+    after_gap: std::ops::Range<usize>,
+    pairs: &mut Vec<(Option<usize>, Option<usize>)>,
+) {
+    let mut unmatched_after: Vec<usize> = after_gap.collect();
+    for i in before_gap {
+        match unmatched_after.iter().position(|&j| after[j].tag == before[i].tag) {
+            Some(position) => {
+                let j = unmatched_after.remove(position);
+                pairs.push((Some(i), Some(j)));
+            }
+            None => pairs.push((Some(i), None)),
+        }
+    }
+    pairs.extend(unmatched_after.into_iter().map(|j| (None, Some(j))));
+}
+
+/// Named snapshots per session
+#[derive(Debug, Clone, Default)]
+pub struct DomSnapshotStore {
+    sessions: Arc<Mutex<HashMap<String, Vec<DomSnapshot>>>>,
+}
+
+impl DomSnapshotStore {
+    /// Keep `snapshot`, replacing one with the same name
+    pub fn insert(&self, session: &str, snapshot: DomSnapshot) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let snapshots = sessions.entry(session.to_string()).or_default();
+        snapshots.retain(|existing| existing.name != snapshot.name);
+        snapshots.push(snapshot);
+        if snapshots.len() > MAX_SNAPSHOTS_PER_SESSION {
+            snapshots.remove(0);
+        }
+    }
+
+    pub fn get(&self, session: &str, name: &str) -> Option<DomSnapshot> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.get(session)?.iter().find(|snapshot| snapshot.name == name).cloned()
+    }
+
+    /// Names of the snapshots kept for `session`, oldest first
+    pub fn names(&self, session: &str) -> Vec<String> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions
+            .get(session)
+            .map(|snapshots| snapshots.iter().map(|snapshot| snapshot.name.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget a closed session's snapshots
+    pub fn remove(&self, session: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(session);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn node(value: serde_json::Value) -> DomNode {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_diff_matches_siblings_by_signature() {
+        let before = node(json!({ "tag": "ul", "children": [
+            { "tag": "li", "text": "One" },
+            { "tag": "li", "text": "Two" },
+            { "tag": "button", "attributes": { "class": "closed" }, "text": "Menu" }
+        ]}));
+        let after = node(json!({ "tag": "ul", "children": [
+            { "tag": "li", "text": "One" },
+            { "tag": "li", "attributes": { "class": "new" }, "text": "Inserted", "children": [{ "tag": "b" }] },
+            { "tag": "li", "text": "Two" },
+            { "tag": "button", "attributes": { "class": "open" }, "text": "Menu" }
+        ]}));
+
+        let changes = diff(&before, &after, "ul");
+        assert_eq!(changes.len(), 2, "{changes:?}");
+        assert_eq!(
+            changes[0],
+            DomChange::Added {
+                path: "ul > li:nth-of-type(2)".to_string(),
+                node: "<li class=\"new\">Inserted".to_string(),
+                descendants: 1,
+            }
+        );
+        match &changes[1] {
+            DomChange::Changed { path, attributes, text, .. } => {
+                assert_eq!(path, "ul > button");
+                assert_eq!(attributes["class"].after.as_deref(), Some("open"));
+                assert!(text.is_none());
+            }
+            other => panic!("expected a change, got {other:?}"),
+        }
+
+        assert!(diff(&after, &after, "ul").is_empty());
+        let removals = diff(&after, &before, "ul");
+        assert!(matches!(&removals[0], DomChange::Removed { descendants: 1, .. }));
+    }
+
+    #[test]
+    fn test_store_replaces_by_name_and_caps_per_session() {
+        let store = DomSnapshotStore::default();
+        let snapshot = |name: String| DomSnapshot {
+            name,
+            url: "https://example.com/".to_string(),
+            root: None,
+            taken_at: String::new(),
+            node_count: 1,
+            truncated: false,
+            tree: node(json!({ "tag": "html" })),
+        };
+        for i in 0..=MAX_SNAPSHOTS_PER_SESSION {
+            store.insert("a", snapshot(format!("s{i}")));
+        }
+        store.insert("a", snapshot("s5".to_string()));
+
+        let names = store.names("a");
+        assert_eq!(names.len(), MAX_SNAPSHOTS_PER_SESSION);
+        assert_eq!(names.first().map(String::as_str), Some("s1"));
+        assert_eq!(names.last().map(String::as_str), Some("s5"));
+        assert!(store.get("a", "s0").is_none());
+        assert!(store.get("b", "s1").is_none());
+    }
+}
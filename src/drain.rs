@@ -0,0 +1,109 @@
+//! Tool-call draining for graceful shutdown
+//!
+//! Once draining starts, new tool calls are refused while calls already running
+//! (including whole recipe runs) are allowed to finish, so browsers are not torn
+//! down halfway through a multi-step flow.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct Inner {
+    draining: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+/// Tracks in-flight tool calls; clones share the same state
+#[derive(Clone, Default)]
+pub struct DrainGate {
+    inner: Arc<Inner>,
+}
+
+impl DrainGate {
+    /// Register a new call; `None` once draining has started
+    pub fn enter(&self) -> Option<InFlightCall> {
+        // Count first so a concurrent `wait_idle` cannot miss this call
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        if self.is_draining() {
+            self.leave();
+            return None;
+        }
+        Some(InFlightCall { gate: self.clone() })
+    }
+
+    pub fn start_draining(&self) {
+        self.inner.draining.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.inner.draining.load(Ordering::SeqCst)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no calls are running; `false` if `timeout` elapsed first
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let idle = self.inner.idle.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    fn leave(&self) {
+        if self.inner.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
+
+/// Marks one running tool call until dropped
+pub struct InFlightCall {
+    gate: DrainGate,
+}
+
+impl Drop for InFlightCall {
+    fn drop(&mut self) {
+        self.gate.leave();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_in_flight_calls() {
+        let gate = DrainGate::default();
+        let call = gate.enter().unwrap();
+
+        gate.start_draining();
+        assert!(gate.enter().is_none());
+        assert_eq!(gate.in_flight(), 1);
+        assert!(!gate.wait_idle(Duration::from_millis(20)).await);
+
+        let waiter = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.wait_idle(Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(call);
+        assert!(waiter.await.unwrap());
+    }
+}
@@ -10,7 +10,14 @@ use tokio::sync::Mutex;
 use tokio::{process::Child as TokioChild, time::sleep};
 use tracing::{debug, info, warn};
 
-use crate::error::{Result, WebDriverError};
+use crate::{
+    Config,
+    endpoints::{DriverStatus, EndpointRegistry},
+    error::{Result, WebDriverError},
+};
+
+/// Spawning a driver and probing its port, on top of each readiness wait
+const ATTEMPT_OVERHEAD: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum DriverType {
@@ -72,11 +79,86 @@ impl DriverType {
     }
 }
 
+/// How drivers are launched: how long each attempt waits for readiness, how
+/// often a driver that does not come up is retried, and whether
+/// `start_concurrent_drivers` starts them together
+#[derive(Debug, Clone)]
+pub struct DriverStartupPolicy {
+    /// How long one attempt waits for the driver to answer `/status`
+    pub ready_timeout: Duration,
+    /// Per-driver `ready_timeout` overrides
+    pub ready_timeouts: HashMap<DriverType, Duration>,
+    /// Extra attempts after the first one fails
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each one after
+    pub backoff: Duration,
+    pub parallel: bool,
+}
+
+impl Default for DriverStartupPolicy {
+    /// A single attempt with a 10 second readiness wait, one driver at a time
+    fn default() -> Self {
+        Self {
+            ready_timeout: Duration::from_secs(10),
+            ready_timeouts: HashMap::new(),
+            retries: 0,
+            backoff: Duration::from_secs(1),
+            parallel: false,
+        }
+    }
+}
+
+impl DriverStartupPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            ready_timeout: Duration::from_millis(config.driver_startup_timeout_ms),
+            ready_timeouts: config
+                .driver_startup_timeouts_ms
+                .iter()
+                .filter_map(|(name, ms)| Some((DriverType::from_string(name)?, Duration::from_millis(*ms))))
+                .collect(),
+            retries: config.driver_startup_retries,
+            backoff: Duration::from_millis(config.driver_startup_backoff_ms),
+            parallel: config.parallel_driver_startup,
+        }
+    }
+
+    pub fn ready_timeout_for(&self, driver_type: &DriverType) -> Duration {
+        self.ready_timeouts.get(driver_type).copied().unwrap_or(self.ready_timeout)
+    }
+
+    /// Delay before the given retry (1-based)
+    pub fn retry_delay(&self, retry: u32) -> Duration {
+        let multiplier = 1u32.checked_shl(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.backoff.saturating_mul(multiplier)
+    }
+
+    /// Longest starting `driver_type` can take, over all attempts
+    pub fn worst_case(&self, driver_type: &DriverType) -> Duration {
+        let attempt = self.ready_timeout_for(driver_type) + ATTEMPT_OVERHEAD;
+        let waits = attempt.saturating_mul(self.retries + 1);
+        (1..=self.retries).fold(waits, |total, retry| total.saturating_add(self.retry_delay(retry)))
+    }
+
+    /// Time to allow for starting all of `driver_types`
+    pub fn budget(&self, driver_types: &[DriverType]) -> Duration {
+        let each = driver_types.iter().map(|driver_type| self.worst_case(driver_type));
+        if self.parallel {
+            each.max().unwrap_or_default()
+        } else {
+            each.sum()
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct DriverManager {
     running_processes: Arc<Mutex<Vec<ManagedProcess>>>,
-    /// Map of driver type to endpoint URL for healthy running drivers
-    healthy_endpoints: Arc<Mutex<HashMap<DriverType, String>>>,
+    /// Endpoint, health and version of each known driver
+    endpoints: EndpointRegistry,
+    /// Number of processes spawned per driver type since startup
+    start_counts: Arc<std::sync::Mutex<HashMap<DriverType, u32>>>,
+    startup: DriverStartupPolicy,
 }
 
 struct ManagedProcess {
@@ -89,67 +171,86 @@ struct ManagedProcess {
 
 impl DriverManager {
     pub fn new() -> Self {
+        Self::with_startup_policy(DriverStartupPolicy::default())
+    }
+
+    pub fn with_startup_policy(startup: DriverStartupPolicy) -> Self {
         Self {
             running_processes: Arc::new(Mutex::new(Vec::new())),
-            healthy_endpoints: Arc::new(Mutex::new(HashMap::new())),
+            endpoints: EndpointRegistry::default(),
+            start_counts: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            startup,
         }
     }
 
-    /// Start multiple WebDriver processes concurrently with timeout
+    pub fn startup_policy(&self) -> &DriverStartupPolicy {
+        &self.startup
+    }
+
+    pub fn endpoint_registry(&self) -> &EndpointRegistry {
+        &self.endpoints
+    }
+
+    /// Start several WebDriver processes, one after another or all at once as the
+    /// startup policy says. Drivers that came up before `timeout` are kept and
+    /// returned even if others were still starting.
     pub async fn start_concurrent_drivers(
         &self,
         driver_names: &[String],
         timeout: Duration,
     ) -> Result<Vec<(DriverType, String)>> {
-        info!("Starting concurrent WebDriver processes: {:?} with timeout {:?}", driver_names, timeout);
-
-        let start_future = async {
-            let mut results = Vec::new();
-
-            for driver_name in driver_names {
-                if let Some(driver_type) = DriverType::from_string(driver_name) {
-                    match self.start_single_driver(driver_type.clone()).await {
-                        Ok(endpoint) => {
-                            info!(
-                                "Successfully started {} at {}",
-                                driver_type.browser_name(),
-                                endpoint
-                            );
+        info!(
+            "Starting WebDriver processes {:?} ({}) with timeout {:?}",
+            driver_names,
+            if self.startup.parallel { "in parallel" } else { "sequentially" },
+            timeout
+        );
 
-                            // Mark as healthy
-                            {
-                                let mut healthy = self.healthy_endpoints.lock().await;
-                                healthy.insert(driver_type.clone(), endpoint.clone());
-                            }
+        let mut driver_types: Vec<DriverType> = Vec::new();
+        for driver_name in driver_names {
+            match DriverType::from_string(driver_name) {
+                Some(driver_type) if !driver_types.contains(&driver_type) => driver_types.push(driver_type),
+                Some(_) => {}
+                None => warn!("Unknown driver type '{}', skipping", driver_name),
+            }
+        }
 
-                            results.push((driver_type, endpoint));
-                        }
-                        Err(e) => {
-                            warn!(
-                                "Failed to start {}: {}",
-                                driver_type.browser_name(),
-                                e
-                            );
-                        }
-                    }
-                } else {
-                    warn!("Unknown driver type '{}', skipping", driver_name);
+        let started = std::sync::Mutex::new(Vec::new());
+        let start_all = async {
+            if self.startup.parallel {
+                let starts = driver_types.iter().map(|driver_type| self.start_and_register(driver_type, &started));
+                futures::future::join_all(starts).await;
+            } else {
+                for driver_type in &driver_types {
+                    self.start_and_register(driver_type, &started).await;
                 }
             }
-
-            results
         };
+        if tokio::time::timeout(timeout, start_all).await.is_err() {
+            warn!("Driver startup timed out after {:?}", timeout);
+        }
 
-        match tokio::time::timeout(timeout, start_future).await {
-            Ok(results) => {
-                info!("Concurrent driver startup completed. {} drivers running", results.len());
-                Ok(results)
-            }
-            Err(_) => {
-                warn!("Driver startup timed out after {:?}", timeout);
-                // Return empty vec since we can't access partial results after timeout
-                Ok(Vec::new())
+        let results = started.into_inner().unwrap_or_else(|e| e.into_inner());
+        info!("Driver startup completed. {} drivers running", results.len());
+        Ok(results)
+    }
+
+    /// Start one driver, marking it healthy and adding it to `started` on success
+    async fn start_and_register(
+        &self,
+        driver_type: &DriverType,
+        started: &std::sync::Mutex<Vec<(DriverType, String)>>,
+    ) {
+        match self.start_single_driver(driver_type.clone()).await {
+            Ok(endpoint) => {
+                info!("Successfully started {} at {}", driver_type.browser_name(), endpoint);
+                let managed = self.is_managed(driver_type).await;
+                let status = self.probe_status(driver_type.default_port()).await;
+                let version = status.ok().and_then(|status| status.version);
+                self.endpoints.record_healthy(driver_type, &endpoint, managed, version);
+                started.lock().unwrap_or_else(|e| e.into_inner()).push((driver_type.clone(), endpoint));
             }
+            Err(e) => warn!("Failed to start {}: {}", driver_type.browser_name(), e),
         }
     }
 
@@ -177,48 +278,50 @@ impl DriverManager {
             port
         );
         
-        self.start_driver(&driver_type, &driver_path, port).await?;
-
-        // Wait for service to be ready
-        self.wait_for_service_ready(&endpoint, Duration::from_secs(10))
-            .await?;
-
-        Ok(endpoint)
+        self.launch(&driver_type, &driver_path, port).await
     }
 
     /// Get all healthy endpoints
     pub async fn get_healthy_endpoints(&self) -> HashMap<DriverType, String> {
-        let healthy = self.healthy_endpoints.lock().await;
-        healthy.clone()
+        self.endpoints.healthy_endpoints()
     }
 
     /// Check if a specific driver type is healthy
     pub async fn is_driver_healthy(&self, driver_type: &DriverType) -> bool {
-        let healthy = self.healthy_endpoints.lock().await;
-        healthy.contains_key(driver_type)
+        self.endpoints.healthy_endpoint(driver_type).is_some()
+    }
+
+    /// Whether a process of `driver_type` was started by this manager
+    async fn is_managed(&self, driver_type: &DriverType) -> bool {
+        let processes = self.running_processes.lock().await;
+        processes.iter().any(|p| &p.driver_type == driver_type)
     }
 
-    /// Perform health checks on all running drivers and update healthy_endpoints
+    /// Perform health checks on all running drivers and record them in the endpoint registry
     pub async fn refresh_driver_health(&self) -> Result<()> {
-        let mut healthy_endpoints_updated = HashMap::new();
-        
         // Get current running processes to check their health
         let processes = {
             let processes = self.running_processes.lock().await;
             processes.iter().map(|p| (p.driver_type.clone(), p.port)).collect::<Vec<_>>()
         };
+        let mut checked = Vec::new();
 
         for (driver_type, port) in &processes {
-            if self.is_service_running(*port).await {
-                let endpoint = format!("http://localhost:{port}");
-                healthy_endpoints_updated.insert(driver_type.clone(), endpoint);
-                debug!("Health check passed for {} on port {}", driver_type.browser_name(), port);
-            } else {
-                warn!("Health check failed for {} on port {}", driver_type.browser_name(), port);
+            let endpoint = format!("http://localhost:{port}");
+            match self.probe_status(*port).await {
+                Ok(status) => {
+                    self.endpoints.record_healthy(driver_type, &endpoint, true, status.version);
+                    debug!("Health check passed for {} on port {}", driver_type.browser_name(), port);
+                }
+                Err(e) => {
+                    self.endpoints.record_unhealthy(driver_type, &endpoint, true, e);
+                    warn!("Health check failed for {} on port {}", driver_type.browser_name(), port);
+                }
             }
+            checked.push(driver_type.clone());
         }
 
-        // CRITICAL FIX: Also check for externally running drivers on standard ports
+        // Also check for externally running drivers on standard ports
         // This handles drivers started outside of our process management
         let standard_drivers = [
             (DriverType::Chrome, 9515),
@@ -228,22 +331,25 @@ impl DriverManager {
 
         for (driver_type, port) in standard_drivers {
             // Skip if we already checked this as a managed process
-            if processes.iter().any(|(existing_type, existing_port)| existing_type == &driver_type && existing_port == &port) {
+            if checked.contains(&driver_type) {
                 continue;
             }
 
-            if self.is_service_running(port).await {
-                let endpoint = format!("http://localhost:{port}");
-                healthy_endpoints_updated.insert(driver_type.clone(), endpoint);
-                debug!("External {} driver detected and registered on port {}", driver_type.browser_name(), port);
+            let Ok(status) = self.probe_status(port).await else {
+                continue;
+            };
+            // The driver on a shared port says which one it is; one that does not is taken at its port
+            if status.driver_type().is_some_and(|running| running != driver_type) {
+                continue;
             }
+            let endpoint = format!("http://localhost:{port}");
+            self.endpoints.record_healthy(&driver_type, &endpoint, false, status.version);
+            checked.push(driver_type.clone());
+            debug!("External {} driver detected and registered on port {}", driver_type.browser_name(), port);
         }
 
-        // Update healthy endpoints atomically
-        {
-            let mut healthy = self.healthy_endpoints.lock().await;
-            *healthy = healthy_endpoints_updated;
-        }
+        // External drivers that went away are forgotten
+        self.endpoints.retain_checked(&checked);
 
         Ok(())
     }
@@ -279,7 +385,7 @@ impl DriverManager {
     }
 
     /// Find the executable path for a specific driver type
-    fn find_driver_executable(&self, driver_type: &DriverType) -> Option<PathBuf> {
+    pub fn find_driver_executable(&self, driver_type: &DriverType) -> Option<PathBuf> {
         let exe_name = driver_type.executable_name();
 
         // First, check if it's in PATH
@@ -370,13 +476,7 @@ impl DriverManager {
                 driver_path
             );
 
-            let actual_endpoint = self.start_driver(&driver_type, &driver_path, port).await?;
-
-            // Wait for service to be ready
-            self.wait_for_service_ready(&actual_endpoint, Duration::from_secs(10))
-                .await?;
-
-            Ok(actual_endpoint)
+            self.launch(&driver_type, &driver_path, port).await
         } else {
             Err(WebDriverError::Session(format!(
                 "Could not find {} executable. Please install {} or set a custom WEBDRIVER_ENDPOINT.\n\n{}",
@@ -419,20 +519,74 @@ impl DriverManager {
     }
 
     async fn is_service_running(&self, port: u16) -> bool {
-        // Try to connect to the service
-        let endpoint = format!("http://localhost:{port}/status");
+        self.probe_status(port).await.is_ok()
+    }
 
-        match reqwest::Client::new()
+    /// Ask the driver on `port` for its status, or say why it did not answer
+    async fn probe_status(&self, port: u16) -> std::result::Result<DriverStatus, String> {
+        let endpoint = format!("http://localhost:{port}/status");
+        let response = reqwest::Client::new()
             .get(&endpoint)
             .timeout(Duration::from_secs(2))
             .send()
             .await
-        {
-            Ok(response) => response.status().is_success(),
-            Err(_) => false,
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("GET /status returned {}", response.status()));
+        }
+        // A driver that answers without a JSON body is up all the same
+        Ok(response.json::<serde_json::Value>().await.map(|body| DriverStatus::from_json(&body)).unwrap_or_default())
+    }
+
+    /// Spawn the driver and wait for it to become ready, retrying with backoff
+    /// per the startup policy. A process that never became ready is killed
+    /// before the next attempt.
+    async fn launch(&self, driver_type: &DriverType, driver_path: &Path, port: u16) -> Result<String> {
+        let attempts = self.startup.retries + 1;
+        let ready_timeout = self.startup.ready_timeout_for(driver_type);
+        let mut attempt = 1;
+        loop {
+            let result = match self.start_driver(driver_type, driver_path, port).await {
+                Ok(endpoint) => self.wait_for_service_ready(&endpoint, ready_timeout).await.map(|()| endpoint),
+                Err(e) => Err(e),
+            };
+            let error = match result {
+                Ok(endpoint) => return Ok(endpoint),
+                Err(e) => e,
+            };
+            self.abandon_process(port).await;
+            if attempt >= attempts {
+                return Err(error);
+            }
+
+            let delay = self.startup.retry_delay(attempt);
+            warn!(
+                "{} did not start (attempt {}/{}): {}. Retrying in {:?}",
+                driver_type.browser_name(),
+                attempt,
+                attempts,
+                error,
+                delay
+            );
+            sleep(delay).await;
+            attempt += 1;
         }
     }
 
+    /// Kill and forget the managed process listening on `port`
+    async fn abandon_process(&self, port: u16) {
+        let mut processes = self.running_processes.lock().await;
+        processes.retain_mut(|managed_process| {
+            if managed_process.port != port {
+                return true;
+            }
+            if let Err(e) = managed_process.process.start_kill() {
+                warn!("Failed to kill {} process: {}", managed_process.driver_type.browser_name(), e);
+            }
+            false
+        });
+    }
+
     async fn start_driver(
         &self,
         driver_type: &DriverType,
@@ -494,9 +648,24 @@ impl DriverManager {
             });
         }
 
+        let starts = {
+            let mut counts = self.start_counts.lock().unwrap_or_else(|e| e.into_inner());
+            let count = counts.entry(driver_type.clone()).or_insert(0);
+            *count += 1;
+            *count
+        };
+        if starts > 1 {
+            info!("Restarted {} (start #{})", driver_type.browser_name(), starts);
+        }
+
         Ok(format!("http://localhost:{port}"))
     }
 
+    /// Processes spawned per driver type since startup; anything above one is a restart
+    pub fn get_driver_start_counts(&self) -> HashMap<DriverType, u32> {
+        self.start_counts.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
     async fn wait_for_service_ready(&self, endpoint: &str, timeout: Duration) -> Result<()> {
         let status_endpoint = format!("{endpoint}/status");
         let client = reqwest::Client::new();
@@ -578,13 +747,9 @@ impl DriverManager {
         }
 
         processes.clear();
-        drop(processes); // Release lock before acquiring healthy_endpoints lock
+        drop(processes);
 
-        // Clear healthy endpoints
-        {
-            let mut healthy = self.healthy_endpoints.lock().await;
-            healthy.clear();
-        }
+        self.endpoints.mark_all_stopped();
 
         Ok(())
     }
@@ -618,14 +783,10 @@ impl DriverManager {
             driver_type.browser_name(),
             port
         );
-        self.start_driver(&driver_type, &driver_path, port).await?;
-
-        // Wait for service to be ready
-        self.wait_for_service_ready(&endpoint, Duration::from_secs(10))
-            .await?;
+        self.launch(&driver_type, &driver_path, port).await?;
 
         // CRITICAL FIX: Refresh health endpoints after starting driver manually
-        // This ensures the driver is registered in healthy_endpoints for recipe execution
+        // This ensures the driver is registered in the endpoint registry for recipe execution
         if let Err(e) = self.refresh_driver_health().await {
             warn!("Failed to refresh driver health after starting {}: {}", driver_type.browser_name(), e);
         } else {
@@ -667,15 +828,25 @@ impl DriverManager {
             }
         }
 
-        // Remove from healthy endpoints
-        {
-            let mut healthy = self.healthy_endpoints.lock().await;
-            healthy.remove(driver_type);
-        }
+        self.endpoints.mark_stopped(driver_type);
 
         Ok(())
     }
 
+    /// Manage `process` as the `driver_type` driver listening on `port`, as if
+    /// this manager had started it, so tests can stand a `MockDriver` in for it
+    #[cfg(feature = "mock-driver")]
+    pub async fn adopt_process(&self, driver_type: DriverType, process: TokioChild, port: u16) {
+        let pid = process.id().unwrap_or_default();
+        self.running_processes.lock().await.push(ManagedProcess {
+            driver_type,
+            process,
+            pid,
+            port,
+            browser_pids: Vec::new(),
+        });
+    }
+
     /// Get status of all managed processes
     pub async fn get_managed_processes_status(&self) -> Vec<(DriverType, u32, u16)> {
         let processes = self.running_processes.lock().await;
@@ -888,11 +1059,8 @@ impl DriverManager {
             }
         }
 
-        // Clear healthy endpoints since all processes are dead
-        {
-            let mut endpoints = self.healthy_endpoints.lock().await;
-            endpoints.clear();
-        }
+        // All processes are dead, so none of their endpoints serve anymore
+        self.endpoints.mark_all_stopped();
 
         // Perform comprehensive cleanup of any remaining orphaned processes
         self.kill_all_orphaned_browser_processes().await?;
@@ -988,3 +1156,33 @@ impl Drop for DriverManager {
         processes.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_startup_policy_budget() {
+        let config = Config {
+            driver_startup_timeout_ms: 10_000,
+            driver_startup_timeouts_ms: [("firefox".to_string(), 30_000)].into_iter().collect(),
+            driver_startup_retries: 2,
+            driver_startup_backoff_ms: 1000,
+            ..Config::default()
+        };
+        let policy = DriverStartupPolicy::from_config(&config);
+        assert_eq!(policy.ready_timeout_for(&DriverType::Chrome), Duration::from_secs(10));
+        assert_eq!(policy.ready_timeout_for(&DriverType::Firefox), Duration::from_secs(30));
+        assert_eq!(policy.retry_delay(1), Duration::from_secs(1));
+        assert_eq!(policy.retry_delay(2), Duration::from_secs(2));
+
+        // Three attempts of (timeout + overhead), plus 1s and 2s of backoff
+        assert_eq!(policy.worst_case(&DriverType::Chrome), Duration::from_secs(3 * 12 + 3));
+        assert_eq!(policy.worst_case(&DriverType::Firefox), Duration::from_secs(3 * 32 + 3));
+
+        let both = [DriverType::Chrome, DriverType::Firefox];
+        assert_eq!(policy.budget(&both), Duration::from_secs(39 + 99));
+        let parallel = DriverStartupPolicy { parallel: true, ..policy };
+        assert_eq!(parallel.budget(&both), Duration::from_secs(99));
+    }
+}
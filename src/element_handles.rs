@@ -0,0 +1,138 @@
+//! Handles for elements found by `find_element` and `find_elements`
+//!
+//! Each found element is registered under a short handle such as `el-7` that
+//! keeps the WebDriver element reference. Scripts can then be given the exact
+//! node they were told about with `{"element": "el-7"}` in their `args`, instead
+//! of re-querying a selector that may match something else by the time they run.
+//! A handle outlives page changes only as long as the browser keeps the node;
+//! afterwards the driver reports it as stale.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use serde_json::{Value, json};
+
+/// Handles kept per session; the oldest are forgotten first
+pub const MAX_HANDLES_PER_SESSION: usize = 1000;
+
+/// Key of a W3C WebDriver element reference object
+pub const ELEMENT_KEY: &str = "element-6066-11e4-a52e-4f735466cecf";
+
+/// W3C reference object for the element with WebDriver id `element_id`
+pub fn element_reference(element_id: &str) -> Value {
+    json!({ ELEMENT_KEY: element_id })
+}
+
+/// The handle named by a `{"element": "el-N"}` script argument
+fn handle_argument(argument: &Value) -> Option<&str> {
+    match argument {
+        Value::Object(object) if object.len() == 1 => object.get("element")?.as_str(),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default)]
+struct SessionHandles {
+    next: u64,
+    /// Handle and element reference, oldest first
+    handles: VecDeque<(String, Value)>,
+}
+
+/// Element handles registered per session
+#[derive(Debug, Clone, Default)]
+pub struct ElementHandleStore {
+    sessions: Arc<Mutex<HashMap<String, SessionHandles>>>,
+}
+
+impl ElementHandleStore {
+    /// Register an element reference and return its new handle
+    pub fn insert(&self, session: &str, reference: Value) -> String {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = sessions.entry(session.to_string()).or_default();
+        entry.next += 1;
+        let handle = format!("el-{}", entry.next);
+        entry.handles.push_back((handle.clone(), reference));
+        if entry.handles.len() > MAX_HANDLES_PER_SESSION {
+            entry.handles.pop_front();
+        }
+        handle
+    }
+
+    /// Element reference registered as `handle`
+    pub fn get(&self, session: &str, handle: &str) -> Option<Value> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions
+            .get(session)?
+            .handles
+            .iter()
+            .find(|(name, _)| name == handle)
+            .map(|(_, reference)| reference.clone())
+    }
+
+    /// Replace every `{"element": "el-N"}` in script arguments, at any depth, with
+    /// the element it names. Fails on the first handle this session does not know.
+    pub fn resolve_arguments(&self, session: &str, arguments: Vec<Value>) -> Result<Vec<Value>, String> {
+        arguments.into_iter().map(|argument| self.resolve(session, argument)).collect()
+    }
+
+    fn resolve(&self, session: &str, argument: Value) -> Result<Value, String> {
+        if let Some(handle) = handle_argument(&argument) {
+            return self.get(session, handle).ok_or_else(|| {
+                format!("Unknown element handle '{handle}': use a handle returned by find_element or find_elements")
+            });
+        }
+        Ok(match argument {
+            Value::Array(items) => Value::Array(self.resolve_arguments(session, items)?),
+            Value::Object(object) => Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| Ok((key, self.resolve(session, value)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            other => other,
+        })
+    }
+
+    /// Forget a closed session's handles
+    pub fn remove(&self, session: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(session);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_handles_in_script_arguments() {
+        let store = ElementHandleStore::default();
+        let first = store.insert("a", element_reference("node-1"));
+        let second = store.insert("a", element_reference("node-2"));
+        assert_eq!((first.as_str(), second.as_str()), ("el-1", "el-2"));
+
+        let resolved = store
+            .resolve_arguments(
+                "a",
+                vec![json!({ "element": "el-2" }), json!([{ "element": "el-1" }, 3]), json!({ "element": 1, "x": 2 })],
+            )
+            .unwrap();
+        assert_eq!(resolved[0], json!({ ELEMENT_KEY: "node-2" }));
+        assert_eq!(resolved[1], json!([{ ELEMENT_KEY: "node-1" }, 3]));
+        assert_eq!(resolved[2], json!({ "element": 1, "x": 2 }));
+
+        let unknown = store.resolve_arguments("b", vec![json!({ "element": "el-1" })]).unwrap_err();
+        assert!(unknown.contains("Unknown element handle 'el-1'"));
+
+        for i in 0..MAX_HANDLES_PER_SESSION {
+            store.insert("a", element_reference(&format!("extra-{i}")));
+        }
+        assert!(store.get("a", "el-2").is_none());
+        assert!(store.get("a", &format!("el-{}", MAX_HANDLES_PER_SESSION + 2)).is_some());
+
+        store.remove("a");
+        assert!(store.get("a", &format!("el-{}", MAX_HANDLES_PER_SESSION + 2)).is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! Registry of WebDriver endpoints and their health
+//!
+//! One entry per browser: where its driver listens, whether the last health
+//! check reached it, when that was, and the driver version it reported.
+//! `DriverManager` records every start, stop and health check here, and
+//! `ClientManager` picks session endpoints from the healthy entries.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::driver::DriverType;
+
+/// Outcome of the last health check of an endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EndpointStatus {
+    Healthy,
+    Unhealthy,
+    /// The driver was stopped by the server
+    Stopped,
+}
+
+/// What a driver's `GET /status` reported
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DriverStatus {
+    pub version: Option<String>,
+    pub message: String,
+}
+
+impl DriverStatus {
+    /// Read the body of a `GET /status` response
+    pub fn from_json(body: &Value) -> Self {
+        let value = &body["value"];
+        Self {
+            version: value.pointer("/build/version").and_then(Value::as_str).map(str::to_string),
+            message: value["message"].as_str().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// The driver the status message names, when it names one. ChromeDriver and
+    /// msedgedriver share a default port, so this tells them apart.
+    pub fn driver_type(&self) -> Option<DriverType> {
+        let message = self.message.to_lowercase();
+        if message.contains("msedgedriver") {
+            Some(DriverType::Edge)
+        } else if message.contains("chromedriver") {
+            Some(DriverType::Chrome)
+        } else if message.contains("geckodriver") {
+            Some(DriverType::Firefox)
+        } else {
+            None
+        }
+    }
+}
+
+/// An endpoint as of its last check
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointEntry {
+    #[serde(skip)]
+    pub driver_type: DriverType,
+    pub browser: String,
+    pub endpoint: String,
+    pub status: EndpointStatus,
+    /// Started by this server rather than found running
+    pub managed: bool,
+    pub last_check: DateTime<Utc>,
+    pub version: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Endpoints by browser, shared by every clone
+#[derive(Debug, Clone, Default)]
+pub struct EndpointRegistry {
+    entries: Arc<Mutex<HashMap<DriverType, EndpointEntry>>>,
+}
+
+impl EndpointRegistry {
+    /// Record a check that reached `endpoint`
+    pub fn record_healthy(&self, driver_type: &DriverType, endpoint: &str, managed: bool, version: Option<String>) {
+        self.record(driver_type, endpoint, EndpointStatus::Healthy, managed, version, None);
+    }
+
+    /// Record a check that did not reach `endpoint`
+    pub fn record_unhealthy(&self, driver_type: &DriverType, endpoint: &str, managed: bool, error: String) {
+        self.record(driver_type, endpoint, EndpointStatus::Unhealthy, managed, None, Some(error));
+    }
+
+    /// Mark the endpoint of `driver_type` stopped, keeping where it was
+    pub fn mark_stopped(&self, driver_type: &DriverType) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = entries.get_mut(driver_type) {
+            entry.status = EndpointStatus::Stopped;
+            entry.last_check = Utc::now();
+        }
+    }
+
+    /// Mark every endpoint stopped
+    pub fn mark_all_stopped(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        for entry in entries.values_mut() {
+            entry.status = EndpointStatus::Stopped;
+            entry.last_check = Utc::now();
+        }
+    }
+
+    /// Forget endpoints a full health check no longer found, keeping managed and stopped ones
+    pub fn retain_checked(&self, checked: &[DriverType]) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|driver_type, entry| {
+            checked.contains(driver_type) || entry.managed || entry.status == EndpointStatus::Stopped
+        });
+    }
+
+    /// Endpoint of `driver_type` if its last check passed
+    pub fn healthy_endpoint(&self, driver_type: &DriverType) -> Option<String> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(driver_type)
+            .filter(|entry| entry.status == EndpointStatus::Healthy)
+            .map(|entry| entry.endpoint.clone())
+    }
+
+    /// Endpoints whose last check passed
+    pub fn healthy_endpoints(&self) -> HashMap<DriverType, String> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .iter()
+            .filter(|(_, entry)| entry.status == EndpointStatus::Healthy)
+            .map(|(driver_type, entry)| (driver_type.clone(), entry.endpoint.clone()))
+            .collect()
+    }
+
+    /// Every known endpoint, in Chrome, Firefox, Edge order
+    pub fn entries(&self) -> Vec<EndpointEntry> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        [DriverType::Chrome, DriverType::Firefox, DriverType::Edge]
+            .iter()
+            .filter_map(|driver_type| entries.get(driver_type).cloned())
+            .collect()
+    }
+
+    fn record(
+        &self,
+        driver_type: &DriverType,
+        endpoint: &str,
+        status: EndpointStatus,
+        managed: bool,
+        version: Option<String>,
+        error: Option<String>,
+    ) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        // A failed check does not know the version, so keep the last one seen
+        let version = version.or_else(|| entries.get(driver_type).and_then(|entry| entry.version.clone()));
+        entries.insert(
+            driver_type.clone(),
+            EndpointEntry {
+                driver_type: driver_type.clone(),
+                browser: driver_type.browser_name().to_lowercase(),
+                endpoint: endpoint.to_string(),
+                status,
+                managed,
+                last_check: Utc::now(),
+                version,
+                error,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_registry_tracks_status_per_browser() {
+        let registry = EndpointRegistry::default();
+        registry.record_healthy(&DriverType::Chrome, "http://localhost:9515", true, Some("126.0".to_string()));
+        registry.record_unhealthy(&DriverType::Firefox, "http://localhost:4444", false, "refused".to_string());
+        assert_eq!(registry.healthy_endpoint(&DriverType::Chrome).as_deref(), Some("http://localhost:9515"));
+        assert_eq!(registry.healthy_endpoint(&DriverType::Firefox), None);
+        assert_eq!(registry.healthy_endpoints().len(), 1);
+
+        registry.record_unhealthy(&DriverType::Chrome, "http://localhost:9515", true, "timeout".to_string());
+        let chrome = &registry.entries()[0];
+        assert_eq!(chrome.status, EndpointStatus::Unhealthy);
+        assert_eq!(chrome.version.as_deref(), Some("126.0"));
+
+        registry.retain_checked(&[DriverType::Chrome]);
+        assert_eq!(registry.entries().len(), 1);
+        registry.mark_stopped(&DriverType::Chrome);
+        let json = serde_json::to_value(registry.entries()).unwrap();
+        assert_eq!(json[0]["browser"], "chrome");
+        assert_eq!(json[0]["status"], "stopped");
+    }
+
+    #[test]
+    fn test_driver_status_names_the_driver() {
+        let edge = DriverStatus::from_json(&json!({
+            "value": {
+                "ready": true,
+                "message": "msedgedriver ready for new sessions.",
+                "build": { "version": "126.0.2592.68" }
+            }
+        }));
+        assert_eq!(edge.driver_type(), Some(DriverType::Edge));
+        assert_eq!(edge.version.as_deref(), Some("126.0.2592.68"));
+        let chrome = DriverStatus::from_json(&json!({ "value": { "message": "ChromeDriver ready for new sessions." } }));
+        assert_eq!(chrome.driver_type(), Some(DriverType::Chrome));
+        let unnamed = DriverStatus::from_json(&json!({ "value": { "message": "Session already started" } }));
+        assert_eq!(unnamed.driver_type(), None);
+    }
+}
@@ -4,8 +4,15 @@ use std::fmt;
 pub enum WebDriverError {
     Client(fantoccini::error::CmdError),
     Session(String),
+    /// The tool needs a browser session that has not been created yet
+    NoActiveSession { session: String },
     ElementNotFound { selector: String },
     Timeout { selector: String },
+    /// A WebDriver command did not answer within `webdriver_call_timeout_secs`
+    CommandTimeout { operation: String, timeout_secs: u64 },
+    /// The page's renderer crashed (Chrome's "Aw, Snap!"); `recovery` says what
+    /// was done about the session
+    SessionCrashed { session: String, recovery: CrashRecovery },
     FileSystem(String),
     Serialization(String),
     NotFound(String),
@@ -19,8 +26,18 @@ impl fmt::Display for WebDriverError {
         match self {
             Self::Client(e) => write!(f, "WebDriver client error: {e}"),
             Self::Session(msg) => write!(f, "Session error: {msg}"),
+            Self::NoActiveSession { session } => write!(
+                f,
+                "No active session '{session}': call create_session or navigate to open a browser first"
+            ),
             Self::ElementNotFound { selector } => write!(f, "Element not found: {selector}"),
             Self::Timeout { selector } => write!(f, "Timeout waiting for element: {selector}"),
+            Self::CommandTimeout { operation, timeout_secs } => {
+                write!(f, "WebDriver command '{operation}' timed out after {timeout_secs}s")
+            }
+            Self::SessionCrashed { session, recovery } => {
+                write!(f, "Tab crashed in session '{session}' (renderer crash, \"Aw, Snap!\"): {recovery}")
+            }
             Self::FileSystem(msg) => write!(f, "File system error: {msg}"),
             Self::Serialization(msg) => write!(f, "Serialization error: {msg}"),
             Self::NotFound(msg) => write!(f, "Not found: {msg}"),
@@ -33,6 +50,27 @@ impl fmt::Display for WebDriverError {
 
 impl std::error::Error for WebDriverError {}
 
+/// What happened to a session after its page crashed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashRecovery {
+    /// A fresh tab replaced the crashed one; the session lives on at `about:blank`
+    NewTab,
+    /// The driver no longer answered, so the session was closed; the next call opens a new one
+    SessionClosed,
+    /// Recovery is turned off (`recover_crashed_tabs`); the session is left as it is
+    Disabled,
+}
+
+impl fmt::Display for CrashRecovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NewTab => write!(f, "a new tab was opened in the same session; navigate again to continue"),
+            Self::SessionClosed => write!(f, "the session was closed; the next call starts a new browser"),
+            Self::Disabled => write!(f, "automatic recovery is disabled; close the session to start over"),
+        }
+    }
+}
+
 impl From<fantoccini::error::CmdError> for WebDriverError {
     fn from(err: fantoccini::error::CmdError) -> Self {
         Self::Client(err)
@@ -52,3 +90,199 @@ impl From<fantoccini::error::NewSessionError> for WebDriverError {
 }
 
 pub type Result<T> = std::result::Result<T, WebDriverError>;
+
+/// Machine-readable category reported with every failed tool call, so clients
+/// can decide whether to retry, wait, change their input or give up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// No element matches the selector
+    ElementNotFound,
+    /// Another element, such as an overlay or sticky header, would receive the click
+    ClickIntercepted,
+    /// A wait, page load or script ran out of time
+    Timeout,
+    /// The browser session was closed or crashed; a new one is created on the next call
+    SessionDead,
+    /// The page's renderer crashed ("Aw, Snap!"); the session was given a fresh tab or replaced
+    SessionCrashed,
+    /// The session has not been created; this tool does not open a browser by itself
+    NoSession,
+    /// No WebDriver could be reached or started
+    DriverUnavailable,
+    /// Refused by server configuration, scopes or content policy
+    PolicyBlocked,
+    /// Too many calls from this client; retry after the reported delay
+    RateLimited,
+    /// The session used up a limit set when it was created
+    QuotaExceeded,
+    /// The server is draining for shutdown
+    ShuttingDown,
+    /// Missing or malformed tool arguments
+    InvalidArgument,
+    /// Page JavaScript threw or returned an unusable value
+    ScriptError,
+    /// The page could not be loaded
+    NavigationFailed,
+    /// Reading or writing a file failed
+    FileSystem,
+    /// A recipe, resource or other named object does not exist
+    NotFound,
+    /// A check the caller asked for did not pass, e.g. audit violations above `fail_on`
+    CheckFailed,
+    /// A CAPTCHA or bot-check page stands in the way; retrying will not help
+    BlockedByChallenge,
+    /// Stopping a driver would orphan the sessions it still serves
+    SessionsActive,
+    /// The client cancelled the call
+    Cancelled,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ElementNotFound => "ELEMENT_NOT_FOUND",
+            Self::ClickIntercepted => "CLICK_INTERCEPTED",
+            Self::Timeout => "TIMEOUT",
+            Self::SessionDead => "SESSION_DEAD",
+            Self::SessionCrashed => "SESSION_CRASHED",
+            Self::NoSession => "NO_SESSION",
+            Self::DriverUnavailable => "DRIVER_UNAVAILABLE",
+            Self::PolicyBlocked => "POLICY_BLOCKED",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::QuotaExceeded => "QUOTA_EXCEEDED",
+            Self::ShuttingDown => "SHUTTING_DOWN",
+            Self::InvalidArgument => "INVALID_ARGUMENT",
+            Self::ScriptError => "SCRIPT_ERROR",
+            Self::NavigationFailed => "NAVIGATION_FAILED",
+            Self::FileSystem => "FILE_SYSTEM",
+            Self::NotFound => "NOT_FOUND",
+            Self::CheckFailed => "CHECK_FAILED",
+            Self::BlockedByChallenge => "BLOCKED_BY_CHALLENGE",
+            Self::SessionsActive => "SESSIONS_ACTIVE",
+            Self::Cancelled => "CANCELLED",
+            Self::Internal => "INTERNAL",
+        }
+    }
+
+    /// Whether repeating the same call unchanged may succeed
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            Self::Timeout
+                | Self::SessionDead
+                | Self::SessionCrashed
+                | Self::DriverUnavailable
+                | Self::RateLimited
+                | Self::NavigationFailed
+        )
+    }
+
+    /// Best-effort category of an error message, covering WebDriver error texts
+    /// (`no such element`, `invalid session id`, ...) and this crate's own messages
+    pub fn classify(message: &str) -> Self {
+        let text = message.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|needle| text.contains(needle));
+
+        if has(&["cancelled by client", "execution cancelled"]) {
+            Self::Cancelled
+        } else if text.contains("element click intercepted") {
+            // The driver quotes the covering element's markup, which may contain any other needle
+            Self::ClickIntercepted
+        } else if has(&["disabled by server configuration", "requires one of the scopes", "blocked by"]) {
+            Self::PolicyBlocked
+        } else if has(&["tab crashed", "page crash", "renderer crashed", "target crashed"]) {
+            // Checked before dead sessions: Chrome reports "session deleted because of page crash"
+            Self::SessionCrashed
+        } else if text.contains("no active session") {
+            Self::NoSession
+        } else if has(&["invalid session id", "session deleted", "no such window", "browser has closed", "disconnected"]) {
+            Self::SessionDead
+        } else if has(&["failed to create webdriver client", "connection refused", "session not created", "no supported browsers", "is not available"]) {
+            Self::DriverUnavailable
+        } else if has(&["timeout", "timed out"]) || Self::mentions_duration_limit(&text) {
+            Self::Timeout
+        } else if has(&["no such element", "failed to find element", "element not found", "no elements found"])
+            || (text.contains("element") && text.contains("not found"))
+        {
+            Self::ElementNotFound
+        } else if text.contains("not found") {
+            Self::NotFound
+        } else if has(&["parameter required", "missing", "invalid", "must be"]) {
+            Self::InvalidArgument
+        } else if has(&["javascript", "script"]) {
+            Self::ScriptError
+        } else if has(&["navigat", "net::err", "failed to load"]) {
+            Self::NavigationFailed
+        } else if has(&["file system", "failed to save", "failed to write", "failed to read"]) {
+            Self::FileSystem
+        } else {
+            Self::Internal
+        }
+    }
+
+    /// Messages like "not found within 10.0s" report an expired wait
+    fn mentions_duration_limit(text: &str) -> bool {
+        text.split("within ")
+            .skip(1)
+            .any(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+    }
+}
+
+impl WebDriverError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::ElementNotFound { .. } => ErrorCode::ElementNotFound,
+            Self::Timeout { .. } | Self::CommandTimeout { .. } => ErrorCode::Timeout,
+            Self::Session(_) => ErrorCode::SessionDead,
+            Self::SessionCrashed { .. } => ErrorCode::SessionCrashed,
+            Self::NoActiveSession { .. } => ErrorCode::NoSession,
+            Self::FileSystem(_) => ErrorCode::FileSystem,
+            Self::Serialization(_) => ErrorCode::Internal,
+            Self::NotFound(_) => ErrorCode::NotFound,
+            Self::InvalidRecipe(_) => ErrorCode::InvalidArgument,
+            Self::Client(e) => ErrorCode::classify(&e.to_string()),
+            Self::Execution(msg) => ErrorCode::classify(msg),
+            Self::Generic(e) => ErrorCode::classify(&e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_error_messages() {
+        let cases = [
+            ("Failed to find element '#login': no such element", ErrorCode::ElementNotFound),
+            ("Element '#spinner' not found within 10.0s: timed out", ErrorCode::Timeout),
+            ("Failed to click: invalid session id", ErrorCode::SessionDead),
+            ("unknown error: session deleted because of page crash", ErrorCode::SessionCrashed),
+            ("Failed to execute script: unknown error: tab crashed", ErrorCode::SessionCrashed),
+            ("Failed to create webdriver client: connection refused", ErrorCode::DriverUnavailable),
+            ("Failed to create webdriver client: No active session 'default'", ErrorCode::NoSession),
+            ("Tool 'execute_script' is disabled by server configuration", ErrorCode::PolicyBlocked),
+            ("Child element 'li' not found within parent '#menu': no such element", ErrorCode::ElementNotFound),
+            ("Recipe 'checkout' not found", ErrorCode::NotFound),
+            ("element click intercepted: Element <a> is not clickable", ErrorCode::ClickIntercepted),
+            ("selector parameter required", ErrorCode::InvalidArgument),
+            ("Waiting for element '#x' cancelled by client", ErrorCode::Cancelled),
+            ("Failed to execute script: TypeError", ErrorCode::ScriptError),
+            ("something unexpected", ErrorCode::Internal),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(ErrorCode::classify(message), expected, "{message}");
+        }
+        assert!(ErrorCode::Timeout.is_retryable());
+        let expired = WebDriverError::CommandTimeout { operation: "get_page_source".to_string(), timeout_secs: 60 };
+        assert_eq!(expired.code(), ErrorCode::Timeout);
+        assert_eq!(ErrorCode::classify(&format!("Failed to get page source: {expired}")), ErrorCode::Timeout);
+        assert!(!ErrorCode::ElementNotFound.is_retryable());
+
+        let crashed =
+            WebDriverError::SessionCrashed { session: "default".to_string(), recovery: CrashRecovery::NewTab };
+        assert_eq!(crashed.code(), ErrorCode::SessionCrashed);
+        assert_eq!(ErrorCode::classify(&format!("Failed to get title: {crashed}")), ErrorCode::SessionCrashed);
+    }
+}
@@ -0,0 +1,337 @@
+//! Canned pages for integration tests
+//!
+//! [`FixtureServer`] serves a handful of small pages on a random local port so
+//! browser tests have something deterministic to load instead of external sites.
+//! It only uses the crate's own dependencies.
+//!
+//! | Path | Page |
+//! |------|------|
+//! | `/` | Links to everything below |
+//! | `/form` | Text, email, password, select, checkbox, radio and textarea fields |
+//! | `/form/submitted` | Echoes the submitted fields as `<dd data-field="name">` |
+//! | `/login` | Login form; the fixture credentials redirect to `/account`, anything else shows an error |
+//! | `/iframe` | Page embedding `/iframe/inner`, which has a button |
+//! | `/slow?delay_ms=N` | Responds after N ms (default 2000, at most 30000) |
+//! | `/slow-content?after_ms=N` | Loads at once, adds `#content` after N ms (default 1500) |
+//! | `/js-error` | Logs console errors and throws an uncaught error after load |
+//! | `/status/{code}` | Responds with that HTTP status |
+//! | `/challenge/captcha` | Sign-up form with a visible reCAPTCHA checkbox |
+//! | `/challenge/interstitial` | Cloudflare-style "Just a moment..." page |
+//! | `/challenge/invisible` | Normal page protected by invisible reCAPTCHA (badge only) |
+
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+
+use axum::{
+    Form, Router,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::get,
+};
+use tokio::task::JoinHandle;
+
+/// Username the `/login` fixture accepts
+pub const FIXTURE_USERNAME: &str = "fixture-user";
+/// Password the `/login` fixture accepts
+pub const FIXTURE_PASSWORD: &str = "fixture-pass";
+
+const MAX_DELAY_MS: u64 = 30_000;
+
+const INDEX_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Fixtures</title></head>
+<body>
+  <h1>Fixtures</h1>
+  <ul>
+    <li><a id="form-link" href="/form">Form</a></li>
+    <li><a id="login-link" href="/login">Login</a></li>
+    <li><a id="iframe-link" href="/iframe">Iframe</a></li>
+    <li><a id="slow-link" href="/slow">Slow response</a></li>
+    <li><a id="slow-content-link" href="/slow-content">Slow content</a></li>
+    <li><a id="js-error-link" href="/js-error">JavaScript errors</a></li>
+  </ul>
+</body></html>"#;
+
+const FORM_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Form fixture</title></head>
+<body>
+  <h1>Sign up</h1>
+  <form id="signup" method="post" action="/form/submitted">
+    <label for="name">Name</label> <input type="text" id="name" name="name" required>
+    <label for="email">Email</label> <input type="email" id="email" name="email">
+    <label for="password">Password</label> <input type="password" id="password" name="password">
+    <label for="country">Country</label>
+    <select id="country" name="country">
+      <option value="">Choose...</option>
+      <option value="no">Norway</option>
+      <option value="se">Sweden</option>
+      <option value="dk">Denmark</option>
+    </select>
+    <label><input type="checkbox" id="newsletter" name="newsletter" value="yes"> Newsletter</label>
+    <label><input type="radio" name="plan" value="free" checked> Free</label>
+    <label><input type="radio" name="plan" value="pro"> Pro</label>
+    <label for="comments">Comments</label> <textarea id="comments" name="comments"></textarea>
+    <button type="submit" id="submit">Sign up</button>
+  </form>
+</body></html>"#;
+
+const IFRAME_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Iframe fixture</title></head>
+<body>
+  <h1>Outer page</h1>
+  <iframe id="frame" name="inner" src="/iframe/inner" width="400" height="200"></iframe>
+</body></html>"#;
+
+const IFRAME_INNER_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Inner frame</title></head>
+<body>
+  <p id="inner-text">Inside the frame</p>
+  <button id="inner-button" onclick="document.getElementById('inner-text').textContent = 'Clicked'">Click me</button>
+</body></html>"#;
+
+const JS_ERROR_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>JavaScript error fixture</title></head>
+<body>
+  <h1>Broken scripts</h1>
+  <script>
+    console.warn('fixture warning');
+    console.error('fixture error');
+    window.addEventListener('load', () => { throw new Error('fixture uncaught error'); });
+  </script>
+</body></html>"#;
+
+// The reCAPTCHA frames point back at this server, so no page needs the network
+const CAPTCHA_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Sign up</title></head>
+<body>
+  <form><input name="email" type="email"><button type="submit">Sign up</button></form>
+  <iframe title="reCAPTCHA" src="/recaptcha/api2/anchor?k=fixture&amp;size=normal" width="304" height="78"></iframe>
+</body></html>"#;
+
+const INTERSTITIAL_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Just a moment...</title></head>
+<body>
+  <h1>Checking if the site connection is secure</h1>
+  <form id="challenge-form" action="/" method="POST"><input type="hidden" name="md" value="fixture"></form>
+</body></html>"#;
+
+const INVISIBLE_CAPTCHA_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Newsletter</title></head>
+<body>
+  <h1>Subscribe</h1>
+  <form><input name="email" type="email"><button type="submit">Subscribe</button></form>
+  <div class="grecaptcha-badge" style="position: fixed; right: 0; bottom: 14px; width: 256px; height: 60px">
+    <iframe title="reCAPTCHA" src="/recaptcha/api2/anchor?k=fixture&amp;size=invisible"
+      width="256" height="60"></iframe>
+  </div>
+  <div style="visibility: hidden; position: absolute; top: -10000px">
+    <iframe title="recaptcha challenge expires in two minutes" src="/recaptcha/api2/bframe?k=fixture"
+      width="400" height="580"></iframe>
+  </div>
+</body></html>"#;
+
+/// An HTTP server for the fixture pages, stopped when dropped
+pub struct FixtureServer {
+    base_url: String,
+    server: JoinHandle<()>,
+}
+
+impl FixtureServer {
+    /// Listen on `127.0.0.1` with an OS-assigned port
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+        let base_url = format!("http://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router()).await {
+                tracing::warn!("Fixture server stopped: {}", e);
+            }
+        });
+        Ok(Self { base_url, server })
+    }
+
+    /// `http://127.0.0.1:<port>`, without a trailing slash
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Absolute URL of a fixture path such as `/form`
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+}
+
+impl Drop for FixtureServer {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn router() -> Router {
+    Router::new()
+        .route("/", get(|| async { Html(INDEX_PAGE) }))
+        .route("/form", get(|| async { Html(FORM_PAGE) }))
+        .route("/form/submitted", get(form_submitted).post(form_submitted_post))
+        .route("/login", get(|| async { Html(login_page(None)) }).post(login))
+        .route("/account", get(|| async { Html(account_page()) }))
+        .route("/iframe", get(|| async { Html(IFRAME_PAGE) }))
+        .route("/iframe/inner", get(|| async { Html(IFRAME_INNER_PAGE) }))
+        .route("/slow", get(slow))
+        .route("/slow-content", get(slow_content))
+        .route("/js-error", get(|| async { Html(JS_ERROR_PAGE) }))
+        .route("/status/{code}", get(status))
+        .route("/challenge/captcha", get(|| async { Html(CAPTCHA_PAGE) }))
+        .route("/challenge/interstitial", get(|| async { Html(INTERSTITIAL_PAGE) }))
+        .route("/challenge/invisible", get(|| async { Html(INVISIBLE_CAPTCHA_PAGE) }))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn delay_param(params: &HashMap<String, String>, key: &str, default_ms: u64) -> Duration {
+    let ms = params.get(key).and_then(|value| value.parse().ok()).unwrap_or(default_ms);
+    Duration::from_millis(ms.min(MAX_DELAY_MS))
+}
+
+async fn form_submitted(Query(fields): Query<Vec<(String, String)>>) -> Html<String> {
+    submitted_page(&fields)
+}
+
+async fn form_submitted_post(Form(fields): Form<Vec<(String, String)>>) -> Html<String> {
+    submitted_page(&fields)
+}
+
+/// Echoes each submitted field as `<dd data-field="name">value</dd>`
+fn submitted_page(fields: &[(String, String)]) -> Html<String> {
+    let rows: String = fields
+        .iter()
+        .map(|(name, value)| {
+            let name = escape_html(name);
+            format!("<dt>{name}</dt><dd data-field=\"{name}\">{}</dd>", escape_html(value))
+        })
+        .collect();
+    Html(format!(
+        "<!DOCTYPE html><html><head><title>Form submitted</title></head>\
+         <body><h1>Form submitted</h1><dl id=\"submitted\">{rows}</dl></body></html>"
+    ))
+}
+
+fn login_page(error: Option<&str>) -> String {
+    let error = error
+        .map(|message| format!("<div class=\"error\" role=\"alert\">{}</div>", escape_html(message)))
+        .unwrap_or_default();
+    format!(
+        r#"<!DOCTYPE html>
+<html><head><title>Login fixture</title></head>
+<body>
+  <h1>Log in</h1>
+  {error}
+  <form id="login" method="post" action="/login">
+    <input type="text" id="username" name="username" autocomplete="username">
+    <input type="password" id="password" name="password" autocomplete="current-password">
+    <button type="submit" id="login-button">Log in</button>
+  </form>
+</body></html>"#
+    )
+}
+
+fn account_page() -> String {
+    format!(
+        "<!DOCTYPE html><html><head><title>Account</title></head>\
+         <body><h1 id=\"welcome\">Welcome, {FIXTURE_USERNAME}</h1>\
+         <a id=\"logout\" href=\"/login\">Log out</a></body></html>"
+    )
+}
+
+async fn login(Form(credentials): Form<HashMap<String, String>>) -> Response {
+    let field = |name: &str| credentials.get(name).map(String::as_str);
+    if field("username") == Some(FIXTURE_USERNAME) && field("password") == Some(FIXTURE_PASSWORD) {
+        Redirect::to("/account").into_response()
+    } else {
+        Html(login_page(Some("Invalid username or password"))).into_response()
+    }
+}
+
+async fn slow(Query(params): Query<HashMap<String, String>>) -> Html<String> {
+    let delay = delay_param(&params, "delay_ms", 2000);
+    tokio::time::sleep(delay).await;
+    Html(format!(
+        "<!DOCTYPE html><html><head><title>Slow response</title></head>\
+         <body><p id=\"slow\">Served after {} ms</p></body></html>",
+        delay.as_millis()
+    ))
+}
+
+async fn slow_content(Query(params): Query<HashMap<String, String>>) -> Html<String> {
+    let after = delay_param(&params, "after_ms", 1500);
+    Html(format!(
+        r#"<!DOCTYPE html>
+<html><head><title>Slow content</title></head>
+<body>
+  <p id="loading">Loading...</p>
+  <script>
+    setTimeout(() => {{
+      document.getElementById('loading').remove();
+      const content = document.createElement('div');
+      content.id = 'content';
+      content.textContent = 'Loaded after {0} ms';
+      document.body.appendChild(content);
+    }}, {0});
+  </script>
+</body></html>"#,
+        after.as_millis()
+    ))
+}
+
+async fn status(Path(code): Path<u16>) -> Response {
+    let status = StatusCode::from_u16(code).unwrap_or(StatusCode::BAD_REQUEST);
+    let body = format!(
+        "<!DOCTYPE html><html><head><title>{0}</title></head><body><h1>{0}</h1></body></html>",
+        status
+    );
+    (status, Html(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fixture_pages() {
+        let fixtures = FixtureServer::start().await.unwrap();
+        let http = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+
+        let form = http.get(fixtures.url("/form")).send().await.unwrap().text().await.unwrap();
+        assert!(form.contains("<select id=\"country\""));
+
+        let echoed = http
+            .post(fixtures.url("form/submitted"))
+            .form(&[("name", "Ada <Lovelace>"), ("country", "no")])
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(echoed.contains("<dd data-field=\"name\">Ada &lt;Lovelace&gt;</dd>"));
+
+        let accepted = http
+            .post(fixtures.url("/login"))
+            .form(&[("username", FIXTURE_USERNAME), ("password", FIXTURE_PASSWORD)])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(accepted.headers()["location"], "/account");
+        let rejected = http.post(fixtures.url("/login")).form(&[("username", "x")]).send().await.unwrap();
+        assert!(rejected.text().await.unwrap().contains("Invalid username or password"));
+
+        let invisible = http.get(fixtures.url("/challenge/invisible")).send().await.unwrap().text().await.unwrap();
+        assert!(invisible.contains("grecaptcha-badge"));
+
+        let missing = http.get(fixtures.url("/status/404")).send().await.unwrap();
+        assert_eq!(missing.status().as_u16(), 404);
+
+        let started = std::time::Instant::now();
+        http.get(fixtures.url("/slow?delay_ms=200")).send().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,370 @@
+//! Filling form fields of any input type
+//!
+//! `fill_and_submit_form` takes an ordered list of field actions. Each action
+//! names a selector and a value, and optionally how to apply it; without one the
+//! element decides: `<select>` options are chosen, checkboxes set, radios
+//! picked, file inputs given paths and everything else typed into. After
+//! filling, the page can be read back to confirm each field holds what was asked.
+
+use fantoccini::{Client, Locator, elements::Element, error::CmdError};
+use serde_json::{Value, json};
+
+use crate::{ClientManager, Result};
+
+/// Reads the state of the first match of `arguments[0]` and, for radio groups,
+/// which of all matches is checked
+const FIELD_STATE_SCRIPT: &str = r#"
+    const [selector] = arguments;
+    let matches;
+    try {
+        matches = Array.from(document.querySelectorAll(selector));
+    } catch (e) {
+        return { error: String(e) };
+    }
+    const el = matches[0];
+    if (!el) {
+        return { error: 'no such element' };
+    }
+    const state = { tag: el.tagName.toLowerCase(), type: el.type || null, value: el.value ?? null, checked: !!el.checked };
+    if (el.tagName === 'SELECT') {
+        state.selected = Array.from(el.selectedOptions).map(option => ({ value: option.value, label: option.text.trim() }));
+    }
+    if (el.type === 'radio') {
+        const checked = matches.find(radio => radio.checked);
+        state.checked_value = checked ? checked.value : null;
+    }
+    if (el.type === 'file') {
+        state.files = Array.from(el.files || []).map(file => file.name);
+    }
+    return state;
+"#;
+
+/// How a field's value is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// Decide from the element once it is found
+    Auto,
+    /// Clear and type text
+    Type,
+    /// Choose `<select>` options by value or visible label
+    Select,
+    /// Set a checkbox to checked (`true`, the default) or unchecked
+    Checkbox,
+    /// Pick the radio with the given value, or the matched radio itself
+    Radio,
+    /// Attach files by path on the server's machine
+    File,
+    /// Empty the field
+    Clear,
+}
+
+impl FieldKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "type" | "text" => Some(Self::Type),
+            "select" => Some(Self::Select),
+            "check" | "checkbox" => Some(Self::Checkbox),
+            "radio" => Some(Self::Radio),
+            "file" | "upload" => Some(Self::File),
+            "clear" => Some(Self::Clear),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Type => "type",
+            Self::Select => "select",
+            Self::Checkbox => "checkbox",
+            Self::Radio => "radio",
+            Self::File => "file",
+            Self::Clear => "clear",
+        }
+    }
+
+    /// The kind an element calls for, from its tag name and `type` attribute
+    pub fn detect(tag_name: &str, input_type: Option<&str>) -> Self {
+        match (tag_name.to_lowercase().as_str(), input_type.map(str::to_lowercase).as_deref()) {
+            ("select", _) => Self::Select,
+            ("input", Some("checkbox")) => Self::Checkbox,
+            ("input", Some("radio")) => Self::Radio,
+            ("input", Some("file")) => Self::File,
+            _ => Self::Type,
+        }
+    }
+}
+
+/// One entry of the `fields` argument
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldAction {
+    pub selector: String,
+    pub kind: FieldKind,
+    pub value: Value,
+    /// Seconds to wait for this field to appear, overriding the call's default
+    pub wait_timeout: Option<f64>,
+}
+
+impl FieldAction {
+    /// Read `fields`: an array of `{selector, value, action, wait_timeout}`
+    /// objects filled in order, or the older object of selector → text, which
+    /// is filled in key order
+    pub fn parse_all(fields: &Value) -> std::result::Result<Vec<Self>, String> {
+        match fields {
+            Value::Array(items) => items.iter().map(Self::parse).collect(),
+            Value::Object(map) => Ok(map
+                .iter()
+                .map(|(selector, value)| Self {
+                    selector: selector.clone(),
+                    kind: FieldKind::Type,
+                    value: value.clone(),
+                    wait_timeout: None,
+                })
+                .collect()),
+            _ => Err("fields must be an array of {selector, value, action} objects".to_string()),
+        }
+    }
+
+    fn parse(item: &Value) -> std::result::Result<Self, String> {
+        let selector = item
+            .get("selector")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| format!("Field {item} needs a 'selector'"))?;
+        let kind = match item.get("action").and_then(|v| v.as_str()) {
+            Some(name) => FieldKind::from_name(name).ok_or_else(|| {
+                format!(
+                    "Field '{selector}' has unknown action '{name}'. Must be one of: auto, type, select, checkbox, radio, file, clear"
+                )
+            })?,
+            None => FieldKind::Auto,
+        };
+        let value = item.get("value").cloned().unwrap_or(Value::Null);
+        if value.is_null() && matches!(kind, FieldKind::Type | FieldKind::Select | FieldKind::File) {
+            return Err(format!("Field '{selector}' ({}) needs a 'value'", kind.as_str()));
+        }
+        Ok(Self {
+            selector: selector.to_string(),
+            kind,
+            value,
+            wait_timeout: item.get("wait_timeout").and_then(|v| v.as_f64()),
+        })
+    }
+
+    /// Values as strings: one for a scalar, several for an array
+    fn values(&self) -> Vec<String> {
+        match &self.value {
+            Value::Array(items) => items.iter().map(value_text).collect(),
+            Value::Null => Vec::new(),
+            value => vec![value_text(value)],
+        }
+    }
+
+    /// Whether a checkbox or radio should end up checked
+    fn wants_checked(&self) -> bool {
+        match &self.value {
+            Value::Bool(checked) => *checked,
+            Value::String(text) => !matches!(text.to_lowercase().as_str(), "false" | "off" | "no" | "0" | ""),
+            Value::Number(number) => number.as_f64() != Some(0.0),
+            _ => true,
+        }
+    }
+
+    /// Radio value to pick among the matches, when the value names one
+    fn radio_value(&self) -> Option<&str> {
+        self.value.as_str().filter(|text| !matches!(text.to_lowercase().as_str(), "true" | "on" | "yes"))
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Apply `field` to its element and return the kind that was used
+pub async fn apply(element: Element, field: &FieldAction) -> std::result::Result<FieldKind, CmdError> {
+    let kind = match field.kind {
+        FieldKind::Auto => {
+            let tag_name = element.tag_name().await?;
+            let input_type = element.attr("type").await?;
+            FieldKind::detect(&tag_name, input_type.as_deref())
+        }
+        kind => kind,
+    };
+
+    match kind {
+        FieldKind::Auto | FieldKind::Type => {
+            element.clear().await?;
+            element.send_keys(&field.values().join("")).await?;
+        }
+        FieldKind::Clear => element.clear().await?,
+        FieldKind::Select => {
+            for value in field.values() {
+                if element.select_by_value(&value).await.is_err() {
+                    element.select_by_label(&value).await?;
+                }
+            }
+        }
+        FieldKind::Checkbox => {
+            if element.is_selected().await? != field.wants_checked() {
+                element.click().await?;
+            }
+        }
+        FieldKind::Radio => match field.radio_value() {
+            Some(value) if element.attr("value").await?.as_deref() != Some(value) => {
+                let option = format!("{}[value=\"{}\"]", field.selector, value.replace('"', "\\\""));
+                element.client().find(Locator::Css(&option)).await?.click().await?;
+            }
+            _ => {
+                if !element.is_selected().await? {
+                    element.click().await?;
+                }
+            }
+        },
+        FieldKind::File => element.send_keys(&field.values().join("\n")).await?,
+    }
+    Ok(kind)
+}
+
+/// Read `field` back from the page. `Ok(None)` when it holds what was asked,
+/// otherwise what it holds instead.
+pub async fn verify(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    field: &FieldAction,
+    kind: FieldKind,
+) -> Result<Option<String>> {
+    let state = client_manager
+        .run_command(
+            session,
+            "verify_field",
+            client.execute(FIELD_STATE_SCRIPT, vec![json!(field.selector)]),
+        )
+        .await?;
+    Ok(mismatch(field, kind, &state))
+}
+
+/// What `state` holds instead of `field`'s value, if anything
+fn mismatch(field: &FieldAction, kind: FieldKind, state: &Value) -> Option<String> {
+    if let Some(error) = state.get("error").and_then(|v| v.as_str()) {
+        return Some(format!("could not read field: {error}"));
+    }
+    let text = |key: &str| state.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let password = state.get("type").and_then(|v| v.as_str()) == Some("password");
+    let shown = |value: String| if password { "(hidden)".to_string() } else { format!("'{value}'") };
+
+    match kind {
+        FieldKind::Auto | FieldKind::Type => {
+            let expected = field.values().join("");
+            let actual = text("value");
+            (actual != expected).then(|| format!("value is {}", shown(actual)))
+        }
+        FieldKind::Clear => {
+            let actual = text("value");
+            (!actual.is_empty()).then(|| format!("value is {}", shown(actual)))
+        }
+        FieldKind::Select => {
+            let selected = state.get("selected").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+            let is_selected = |wanted: &str| {
+                selected.iter().any(|option| {
+                    option.get("value").and_then(|v| v.as_str()) == Some(wanted)
+                        || option.get("label").and_then(|v| v.as_str()) == Some(wanted)
+                })
+            };
+            let missing: Vec<String> = field.values().into_iter().filter(|wanted| !is_selected(wanted)).collect();
+            (!missing.is_empty()).then(|| {
+                let labels: Vec<&str> = selected.iter().filter_map(|o| o.get("label").and_then(|v| v.as_str())).collect();
+                format!("selected options are [{}], missing [{}]", labels.join(", "), missing.join(", "))
+            })
+        }
+        FieldKind::Checkbox => {
+            let checked = state.get("checked").and_then(|v| v.as_bool()).unwrap_or(false);
+            (checked != field.wants_checked()).then(|| format!("checked is {checked}"))
+        }
+        FieldKind::Radio => match field.radio_value() {
+            Some(value) => {
+                let actual = state.get("checked_value").and_then(|v| v.as_str());
+                (actual != Some(value)).then(|| format!("checked radio is {}", actual.map_or("none".to_string(), |v| format!("'{v}'"))))
+            }
+            None => {
+                let checked = state.get("checked").and_then(|v| v.as_bool()).unwrap_or(false);
+                (!checked).then(|| "radio is not checked".to_string())
+            }
+        },
+        FieldKind::File => {
+            let files: Vec<String> = state
+                .get("files")
+                .and_then(|v| v.as_array())
+                .map(|files| files.iter().filter_map(|f| f.as_str().map(str::to_string)).collect())
+                .unwrap_or_default();
+            let missing: Vec<String> = field
+                .values()
+                .into_iter()
+                .filter(|path| {
+                    let name = std::path::Path::new(path)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.clone());
+                    !files.contains(&name)
+                })
+                .collect();
+            (!missing.is_empty()).then(|| format!("files attached are [{}], missing [{}]", files.join(", "), missing.join(", ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fields() {
+        let fields = FieldAction::parse_all(&json!([
+            { "selector": "#country", "value": "NO", "action": "select" },
+            { "selector": "#terms", "value": true },
+            { "selector": "#email", "value": "a@example.com", "wait_timeout": 2.5 }
+        ]))
+        .unwrap();
+        assert_eq!(fields[0].kind, FieldKind::Select);
+        assert_eq!(fields[1].kind, FieldKind::Auto);
+        assert_eq!(fields[2].wait_timeout, Some(2.5));
+
+        let legacy = FieldAction::parse_all(&json!({ "#name": "Ada" })).unwrap();
+        assert_eq!((legacy[0].selector.as_str(), legacy[0].kind), ("#name", FieldKind::Type));
+
+        assert!(FieldAction::parse_all(&json!([{ "value": "x" }])).is_err());
+        assert!(FieldAction::parse_all(&json!([{ "selector": "#a", "action": "drag" }])).is_err());
+        assert!(FieldAction::parse_all(&json!([{ "selector": "#a", "action": "select" }])).is_err());
+        assert!(FieldAction::parse_all(&json!("#a")).is_err());
+    }
+
+    #[test]
+    fn test_detect_and_mismatch() {
+        assert_eq!(FieldKind::detect("SELECT", None), FieldKind::Select);
+        assert_eq!(FieldKind::detect("input", Some("Checkbox")), FieldKind::Checkbox);
+        assert_eq!(FieldKind::detect("input", Some("email")), FieldKind::Type);
+        assert_eq!(FieldKind::detect("textarea", None), FieldKind::Type);
+
+        let field = |value: Value| FieldAction { selector: "#f".to_string(), kind: FieldKind::Auto, value, wait_timeout: None };
+
+        assert_eq!(mismatch(&field(json!("Ada")), FieldKind::Type, &json!({ "value": "Ada" })), None);
+        assert_eq!(
+            mismatch(&field(json!("secret")), FieldKind::Type, &json!({ "type": "password", "value": "secre" })),
+            Some("value is (hidden)".to_string())
+        );
+        let selected = json!({ "selected": [{ "value": "no", "label": "Norway" }] });
+        assert_eq!(mismatch(&field(json!("Norway")), FieldKind::Select, &selected), None);
+        assert!(mismatch(&field(json!(["no", "se"])), FieldKind::Select, &selected).is_some());
+        assert_eq!(mismatch(&field(json!(false)), FieldKind::Checkbox, &json!({ "checked": false })), None);
+        assert_eq!(mismatch(&field(json!("b")), FieldKind::Radio, &json!({ "checked_value": "b" })), None);
+        assert!(mismatch(&field(json!("b")), FieldKind::Radio, &json!({ "checked_value": null })).is_some());
+        assert_eq!(
+            mismatch(&field(json!("/tmp/cv.pdf")), FieldKind::File, &json!({ "files": ["cv.pdf"] })),
+            None
+        );
+        assert!(mismatch(&field(json!("x")), FieldKind::Type, &json!({ "error": "no such element" })).is_some());
+    }
+}
@@ -0,0 +1,83 @@
+//! Accessibility handlers
+//!
+//! Handles accessibility checks:
+//! - Auditing the current page with the bundled rule engine
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    ClientManager, ErrorCode,
+    accessibility::{self, Impact},
+    tools::{coded_error_response, error_response, structured_response},
+};
+use super::{extract_session_id, session_error_response};
+
+/// Audit the current page for accessibility violations
+pub async fn handle_audit_accessibility(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let scope = arguments
+        .as_ref()
+        .and_then(|args| args.get("selector"))
+        .and_then(|v| v.as_str());
+
+    let rules: Vec<String> = arguments
+        .as_ref()
+        .and_then(|args| args.get("rules"))
+        .and_then(|v| v.as_array())
+        .map(|rules| rules.iter().filter_map(|r| r.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+
+    let fail_on = match arguments
+        .as_ref()
+        .and_then(|args| args.get("fail_on"))
+        .and_then(|v| v.as_str())
+    {
+        Some(name) => Some(Impact::from_name(name).ok_or_else(|| {
+            McpError::invalid_params(
+                format!("Invalid fail_on '{name}'. Must be one of: minor, moderate, serious, critical"),
+                None,
+            )
+        })?),
+        None => None,
+    };
+
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            let report = match accessibility::run_audit(client_manager, &session, &client, scope, &rules).await {
+                Ok(report) => report,
+                Err(e) => return Ok(error_response(format!("Failed to run accessibility audit: {e}"))),
+            };
+
+            let failing = fail_on.map(|threshold| report.failing(threshold).len()).unwrap_or(0);
+            let data = json!({
+                "session": session,
+                "url": &report.url,
+                "selector": scope,
+                "rules": &report.rules,
+                "counts": report.counts(),
+                "violations": &report.violations,
+                "fail_on": fail_on.map(Impact::as_str),
+                "passed": failing == 0,
+            });
+
+            if failing > 0 {
+                return Ok(coded_error_response(
+                    ErrorCode::CheckFailed,
+                    format!(
+                        "{failing} accessibility violation(s) at or above '{}' impact\n\n{}",
+                        fail_on.map(Impact::as_str).unwrap_or_default(),
+                        report.summary()
+                    ),
+                    data,
+                ));
+            }
+            Ok(structured_response(format!("{} (session: {session})", report.summary()), data))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
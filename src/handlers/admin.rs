@@ -0,0 +1,118 @@
+//! Administrative handlers
+//!
+//! Handles operator-facing tools:
+//! - Reading the tool-call audit log
+//! - Reporting runtime statistics
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    ClientManager,
+    audit::AuditLog,
+    tools::structured_response,
+};
+use super::extract_session_id;
+
+/// Return the most recent audit log entries
+pub async fn handle_get_audit_log(
+    audit: &AuditLog,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let limit = arguments
+        .as_ref()
+        .and_then(|args| args.get("limit"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(50) as usize;
+
+    let tool = arguments
+        .as_ref()
+        .and_then(|args| args.get("tool"))
+        .and_then(|v| v.as_str());
+
+    let session_id = extract_session_id(arguments);
+
+    let entries = audit.recent(limit, tool, session_id.as_deref());
+    let file = audit
+        .file_path()
+        .map(|path| path.display().to_string());
+
+    let mut text = format!("Audit log: {} entries", entries.len());
+    if let Some(file) = &file {
+        text.push_str(&format!(" (file: {file})"));
+    }
+    for entry in &entries {
+        text.push_str(&format!(
+            "\n{} {} [{:?}] session={} {}ms{}",
+            entry.timestamp,
+            entry.tool,
+            entry.outcome,
+            entry.session_id.as_deref().unwrap_or("-"),
+            entry.duration_ms,
+            entry.client.as_ref().map(|c| format!(" client={c}")).unwrap_or_default(),
+        ));
+    }
+
+    Ok(structured_response(
+        text,
+        json!({ "file": file, "count": entries.len(), "entries": entries }),
+    ))
+}
+
+/// Report uptime, active sessions, per-tool latency, recipe runs and driver restarts
+pub async fn handle_get_server_stats(
+    client_manager: &ClientManager,
+    _arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let stats = client_manager.get_stats();
+    let uptime_secs = stats.uptime().as_secs();
+    let sessions = client_manager.get_active_sessions_by_browser().await;
+    let tools = stats.tool_stats();
+    let (recipe_runs, recipe_failures) = stats.recipe_runs();
+    let page_crashes = stats.page_crashes();
+
+    let mut drivers = Map::new();
+    for (driver_type, starts) in client_manager.get_driver_manager().get_driver_start_counts() {
+        drivers.insert(
+            driver_type.browser_name().to_lowercase(),
+            json!({ "starts": starts, "restarts": starts.saturating_sub(1) }),
+        );
+    }
+
+    let total_calls: u64 = tools.values().map(|t| t.calls).sum();
+    let mut text = format!(
+        "Uptime: {}h {}m {}s\nActive sessions: {}",
+        uptime_secs / 3600,
+        (uptime_secs % 3600) / 60,
+        uptime_secs % 60,
+        sessions.values().sum::<usize>(),
+    );
+    for (browser, count) in &sessions {
+        text.push_str(&format!("\n  {browser}: {count}"));
+    }
+    text.push_str(&format!("\nTool calls: {total_calls}"));
+    for (name, tool) in &tools {
+        text.push_str(&format!(
+            "\n  {name}: {} calls, {} errors, p50 {}ms, p90 {}ms, p99 {}ms",
+            tool.calls, tool.errors, tool.p50_ms, tool.p90_ms, tool.p99_ms
+        ));
+    }
+    text.push_str(&format!("\nRecipe runs: {recipe_runs} ({recipe_failures} failed)"));
+    text.push_str(&format!("\nPage crashes: {page_crashes}"));
+    for (browser, counts) in &drivers {
+        text.push_str(&format!("\nDriver {browser}: {} restarts", counts["restarts"]));
+    }
+
+    Ok(structured_response(
+        text,
+        json!({
+            "uptime_secs": uptime_secs,
+            "active_sessions": sessions,
+            "tools": tools,
+            "total_tool_calls": total_calls,
+            "recipes": { "runs": recipe_runs, "failures": recipe_failures },
+            "page_crashes": page_crashes,
+            "drivers": drivers,
+        }),
+    ))
+}
@@ -0,0 +1,145 @@
+//! Batch handlers
+//!
+//! `run_batch` runs an ordered list of tool calls server-side against one
+//! session, saving a client round trip per step. The server owns tool dispatch
+//! and runs the steps; this module prepares their arguments and reports them.
+
+use std::time::Duration;
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde_json::{Map, Value, json};
+
+use crate::tools::{params::BatchStep, structured_response};
+
+/// Steps one `run_batch` call accepts at most
+pub const MAX_BATCH_STEPS: usize = 50;
+
+/// The batch tool itself, which a step may not call
+pub const RUN_BATCH_TOOL: &str = "run_batch";
+
+/// Arguments for `step`, on the batch's session unless the step names its own
+pub fn step_arguments(step: &BatchStep, session_id: Option<&str>) -> Map<String, Value> {
+    let mut arguments = step.arguments.clone();
+    if let Some(session_id) = session_id
+        && !arguments.contains_key("session_id")
+    {
+        arguments.insert("session_id".to_string(), json!(session_id));
+    }
+    arguments
+}
+
+/// Whether a step's result counts as a failure for `stop_on_error`
+pub fn step_failed(result: &Result<CallToolResult, McpError>) -> bool {
+    !matches!(result, Ok(r) if r.is_error != Some(true))
+}
+
+/// What a step returned: its text as `message`, JSON content as `data`, and the
+/// error code of a failed step
+pub fn step_outcome(index: usize, tool: &str, result: &Result<CallToolResult, McpError>, elapsed: Duration) -> Value {
+    let mut outcome = json!({
+        "index": index,
+        "tool": tool,
+        "ok": !step_failed(result),
+        "duration_ms": elapsed.as_millis() as u64,
+    });
+    match result {
+        Ok(result) => {
+            let mut messages = Vec::new();
+            let mut data = Vec::new();
+            let mut images = 0;
+            for content in &result.content {
+                if let Some(text) = content.raw.as_text() {
+                    match serde_json::from_str::<Value>(&text.text) {
+                        Ok(value @ Value::Object(_)) => data.push(value),
+                        _ => messages.push(text.text.clone()),
+                    }
+                } else if content.raw.as_image().is_some() {
+                    images += 1;
+                }
+            }
+            outcome["message"] = json!(messages.join("\n"));
+            match data.len() {
+                0 => {}
+                1 => outcome["data"] = data.remove(0),
+                _ => outcome["data"] = json!(data),
+            }
+            if images > 0 {
+                outcome["images"] = json!(images);
+            }
+            if let Some(code) = outcome["data"].pointer("/error/code").cloned() {
+                outcome["code"] = code;
+            }
+        }
+        Err(e) => {
+            outcome["message"] = json!(e.message);
+            if let Some(code) = e.data.as_ref().and_then(|data| data.get("code")) {
+                outcome["code"] = code.clone();
+            }
+        }
+    }
+    outcome
+}
+
+/// Result of a whole batch; marked as an error when any step failed
+pub fn batch_response(session_id: Option<&str>, total: usize, outcomes: Vec<Value>, stopped: bool) -> CallToolResult {
+    let failed = outcomes.iter().filter(|outcome| outcome["ok"] == false).count();
+    let ran = outcomes.len();
+    let mut message = format!("Ran {ran} of {total} step(s), {failed} failed");
+    if stopped {
+        let first_failure = outcomes.iter().find(|outcome| outcome["ok"] == false);
+        if let Some(failure) = first_failure {
+            message.push_str(&format!(
+                "; stopped after step {} ({}): {}",
+                failure["index"],
+                failure["tool"].as_str().unwrap_or_default(),
+                failure["message"].as_str().unwrap_or_default()
+            ));
+        }
+    }
+    let mut result = structured_response(
+        message,
+        json!({
+            "session_id": session_id,
+            "total": total,
+            "ran": ran,
+            "failed": failed,
+            "stopped": stopped,
+            "steps": outcomes,
+        }),
+    );
+    if failed > 0 {
+        result.is_error = Some(true);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{error_response, success_response};
+
+    #[test]
+    fn test_step_arguments_and_outcomes() {
+        let step: BatchStep =
+            serde_json::from_value(json!({ "tool": "click", "arguments": { "selector": "#a" } })).unwrap();
+        let arguments = step_arguments(&step, Some("s1"));
+        assert_eq!(arguments["session_id"], "s1");
+        let own: BatchStep =
+            serde_json::from_value(json!({ "tool": "get_title", "arguments": { "session_id": "s2" } })).unwrap();
+        assert_eq!(step_arguments(&own, Some("s1"))["session_id"], "s2");
+
+        let clicked = step_outcome(0, "click", &Ok(success_response("Clicked".to_string())), Duration::from_millis(5));
+        assert_eq!(clicked["ok"], true);
+        assert_eq!(clicked["message"], "Clicked");
+
+        let missing = Ok(error_response("Failed to find element #b: no such element".to_string()));
+        let failed = step_outcome(1, "click", &missing, Duration::ZERO);
+        assert_eq!(failed["ok"], false);
+        assert_eq!(failed["code"], "ELEMENT_NOT_FOUND");
+
+        let response = batch_response(Some("s1"), 3, vec![clicked, failed], true);
+        assert_eq!(response.is_error, Some(true));
+        let text = response.content[0].raw.as_text().unwrap().text.clone();
+        assert!(text.starts_with("Ran 2 of 3 step(s), 1 failed; stopped after step 1 (click)"), "{text}");
+    }
+}
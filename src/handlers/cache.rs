@@ -0,0 +1,233 @@
+//! Service worker and cache handlers
+//!
+//! Handles what stands between a page and the network:
+//! - Listing service worker registrations and Cache Storage caches
+//! - Unregistering service workers, optionally with their caches
+//! - Bypassing the HTTP cache through the Chrome DevTools Protocol
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    ClientManager, cdp,
+    tools::{
+        error_response, structured_response,
+        params::{SessionParams, SetCacheDisabledParams, UnregisterServiceWorkersParams},
+    },
+};
+use super::{parse_params, session_error_response};
+
+/// Service worker registrations of the page's origin and its Cache Storage cache names
+const LIST_SERVICE_WORKERS_SCRIPT: &str = r#"
+    const done = arguments[arguments.length - 1];
+    (async () => {
+        if (!('serviceWorker' in navigator)) {
+            return { supported: false, controlled: false, registrations: [], caches: [] };
+        }
+        const worker = w => w ? { script_url: w.scriptURL, state: w.state } : null;
+        const registrations = (await navigator.serviceWorker.getRegistrations()).map(r => ({
+            scope: r.scope,
+            active: worker(r.active),
+            waiting: worker(r.waiting),
+            installing: worker(r.installing),
+            update_via_cache: r.updateViaCache
+        }));
+        const caches = window.caches ? await window.caches.keys() : [];
+        return { supported: true, controlled: !!navigator.serviceWorker.controller, registrations, caches };
+    })().then(done, e => done({ error: String(e) }));
+"#;
+
+/// Unregisters the workers whose scope starts with `arguments[0]` (all when null),
+/// and deletes every Cache Storage cache when `arguments[1]` is true
+const UNREGISTER_SERVICE_WORKERS_SCRIPT: &str = r#"
+    const [scope, clearCaches, done] = arguments;
+    (async () => {
+        const unregistered = [];
+        if ('serviceWorker' in navigator) {
+            for (const registration of await navigator.serviceWorker.getRegistrations()) {
+                if (scope && !registration.scope.startsWith(scope)) continue;
+                if (await registration.unregister()) unregistered.push(registration.scope);
+            }
+        }
+        const cleared = [];
+        if (clearCaches && window.caches) {
+            for (const name of await window.caches.keys()) {
+                if (await window.caches.delete(name)) cleared.push(name);
+            }
+        }
+        return { unregistered, cleared_caches: cleared };
+    })().then(done, e => done({ error: String(e) }));
+"#;
+
+/// List service worker registrations and Cache Storage caches of the current page's origin
+pub async fn handle_list_service_workers(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let SessionParams { session_id } = parse_params(arguments)?;
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            let listed = client_manager
+                .run_command(
+                    &session,
+                    "list_service_workers",
+                    client.execute_async(LIST_SERVICE_WORKERS_SCRIPT, vec![]),
+                )
+                .await;
+            match listed {
+                Ok(result) => {
+                    if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+                        return Ok(error_response(format!("Failed to list service workers: {error}")));
+                    }
+                    Ok(structured_response(
+                        format!("{} (session: {session})", service_workers_summary(&result)),
+                        json!({ "session": session, "service_workers": result }),
+                    ))
+                }
+                Err(e) => Ok(error_response(format!("Failed to list service workers: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Unregister service workers of the current page's origin
+pub async fn handle_unregister_service_workers(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let UnregisterServiceWorkersParams { scope, clear_caches, session } = parse_params(arguments)?;
+
+    match client_manager.get_or_create_client(session.session_id).await {
+        Ok((session, client)) => {
+            let script_args = vec![json!(scope), json!(clear_caches)];
+            let removed = client_manager
+                .run_command(
+                    &session,
+                    "unregister_service_workers",
+                    client.execute_async(UNREGISTER_SERVICE_WORKERS_SCRIPT, script_args),
+                )
+                .await;
+            match removed {
+                Ok(result) => {
+                    if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+                        return Ok(error_response(format!("Failed to unregister service workers: {error}")));
+                    }
+                    Ok(structured_response(
+                        format!("{} (session: {session})", unregistered_summary(&result)),
+                        json!({
+                            "session": session,
+                            "unregistered": result["unregistered"],
+                            "cleared_caches": result["cleared_caches"],
+                        }),
+                    ))
+                }
+                Err(e) => Ok(error_response(format!("Failed to unregister service workers: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Turn the browser's HTTP cache off or back on for a session (Chrome and Edge only)
+pub async fn handle_set_cache_disabled(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let SetCacheDisabledParams { disabled, bypass_service_worker, session } = parse_params(arguments)?;
+
+    match client_manager.get_or_create_client(session.session_id).await {
+        Ok((session, client)) => {
+            let applied = async {
+                cdp::execute(client_manager, &session, &client, "Network.enable", json!({})).await?;
+                cdp::execute(
+                    client_manager,
+                    &session,
+                    &client,
+                    "Network.setCacheDisabled",
+                    json!({ "cacheDisabled": disabled }),
+                )
+                .await?;
+                if let Some(bypass) = bypass_service_worker {
+                    cdp::execute(
+                        client_manager,
+                        &session,
+                        &client,
+                        "Network.setBypassServiceWorker",
+                        json!({ "bypass": bypass }),
+                    )
+                    .await?;
+                }
+                crate::Result::Ok(())
+            };
+            match applied.await {
+                Ok(()) => {
+                    Ok(structured_response(
+                        format!("{} (session: {session})", cache_setting_summary(disabled, bypass_service_worker)),
+                        json!({
+                            "session": session,
+                            "cache_disabled": disabled,
+                            "bypass_service_worker": bypass_service_worker,
+                        }),
+                    ))
+                }
+                Err(e) => Ok(error_response(format!("Failed to change the HTTP cache setting: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// What `LIST_SERVICE_WORKERS_SCRIPT` found, in one line
+fn service_workers_summary(result: &Value) -> String {
+    let count = result["registrations"].as_array().map_or(0, Vec::len);
+    let caches = result["caches"].as_array().map_or(0, Vec::len);
+    let controlled = if result["controlled"].as_bool() == Some(true) { ", page is controlled by a worker" } else { "" };
+    format!("{count} service worker(s), {caches} cache(s){controlled}")
+}
+
+/// What `UNREGISTER_SERVICE_WORKERS_SCRIPT` removed, in one line
+fn unregistered_summary(result: &Value) -> String {
+    let count = result["unregistered"].as_array().map_or(0, Vec::len);
+    let caches = result["cleared_caches"].as_array().map_or(0, Vec::len);
+    format!(
+        "Unregistered {count} service worker(s), deleted {caches} cache(s); reload the page to load it without them"
+    )
+}
+
+/// The HTTP cache setting `set_cache_disabled` applied, in one line
+fn cache_setting_summary(disabled: bool, bypass_service_worker: Option<bool>) -> String {
+    let state = if disabled { "disabled" } else { "enabled" };
+    let bypass = match bypass_service_worker {
+        Some(true) => ", service workers bypassed",
+        Some(false) => ", service workers in use",
+        None => "",
+    };
+    format!("HTTP cache {state}{bypass}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_worker_and_cache_summaries() {
+        let listed = json!({
+            "supported": true,
+            "controlled": true,
+            "registrations": [{ "scope": "https://shop.test/", "active": { "script_url": "https://shop.test/sw.js" } }],
+            "caches": ["static-v1", "api-v3"],
+        });
+        assert_eq!(service_workers_summary(&listed), "1 service worker(s), 2 cache(s), page is controlled by a worker");
+        let unsupported = json!({ "supported": false, "controlled": false, "registrations": [], "caches": [] });
+        assert_eq!(service_workers_summary(&unsupported), "0 service worker(s), 0 cache(s)");
+
+        let removed = json!({ "unregistered": ["https://shop.test/"], "cleared_caches": [] });
+        assert!(unregistered_summary(&removed).starts_with("Unregistered 1 service worker(s), deleted 0 cache(s)"));
+
+        assert_eq!(cache_setting_summary(true, None), "HTTP cache disabled");
+        assert_eq!(cache_setting_summary(true, Some(true)), "HTTP cache disabled, service workers bypassed");
+        assert_eq!(cache_setting_summary(false, Some(false)), "HTTP cache enabled, service workers in use");
+    }
+}
@@ -0,0 +1,497 @@
+//! Cross-browser comparison handlers
+//!
+//! Handles loading one page in several browsers at once:
+//! - A throwaway session per browser, driven concurrently
+//! - An optional list of actions run the same way in each browser
+//! - Side-by-side screenshots, titles, console error counts and load metrics
+
+use std::time::{Duration, Instant};
+
+use base64::{Engine as _, engine::general_purpose};
+use fantoccini::Client;
+use rmcp::{ErrorData as McpError, model::{CallToolResult, Content}};
+use serde_json::{Map, Value, json};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    ClientManager, DriverType, screenshots,
+    tools::{error_response, structured_response},
+};
+use super::{cancelled_response, navigation::setup_console_monitoring};
+
+/// Actions accepted in one comparison
+const MAX_ACTIONS: usize = 50;
+/// Pause after the last action so late rendering shows in the screenshot
+const DEFAULT_SETTLE_MS: u64 = 500;
+
+/// Load timings from the Navigation and Paint Timing APIs, counted from the
+/// start of the navigation, plus transfer size and resource count
+const LOAD_METRICS_SCRIPT: &str = r#"
+    const nav = performance.getEntriesByType('navigation')[0];
+    const paint = performance.getEntriesByName('first-contentful-paint')[0];
+    const resources = performance.getEntriesByType('resource');
+    const round = value => (typeof value === 'number' && value > 0 ? Math.round(value) : null);
+    return {
+        url: location.href,
+        title: document.title,
+        ttfb_ms: nav ? round(nav.responseStart) : null,
+        dom_content_loaded_ms: nav ? round(nav.domContentLoadedEventEnd) : null,
+        load_ms: nav ? round(nav.loadEventEnd) : null,
+        first_contentful_paint_ms: paint ? round(paint.startTime) : null,
+        transfer_bytes: resources.reduce((sum, r) => sum + (r.transferSize || 0), nav ? nav.transferSize || 0 : 0),
+        resource_count: resources.length,
+    };
+"#;
+
+/// Scrolls the first element matching `arguments[0]` into view
+const SCROLL_SCRIPT: &str = r#"
+    const el = document.querySelector(arguments[0]);
+    if (!el) return false;
+    el.scrollIntoView({ block: 'center' });
+    return true;
+"#;
+
+/// One step run in every browser before capturing
+#[derive(Debug, Clone, PartialEq)]
+enum CompareAction {
+    Click { selector: String },
+    Type { selector: String, text: String },
+    Scroll { selector: String },
+    Wait { duration_ms: u64 },
+    WaitFor { selector: String, timeout: Option<f64> },
+    Navigate { url: String },
+}
+
+impl CompareAction {
+    /// Parse `{"type": "click", "selector": ...}` and friends
+    fn parse(value: &Value) -> Result<Self, String> {
+        let object = value.as_object().ok_or("each action must be an object")?;
+        let kind = object.get("type").and_then(|v| v.as_str()).ok_or("each action needs a 'type'")?;
+        let string = |key: &str| {
+            object
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| format!("'{kind}' action needs '{key}'"))
+        };
+        Ok(match kind {
+            "click" => Self::Click { selector: string("selector")? },
+            "type" | "send_keys" => Self::Type { selector: string("selector")?, text: string("text")? },
+            "scroll" => Self::Scroll { selector: string("selector")? },
+            "wait" => Self::Wait {
+                duration_ms: object
+                    .get("duration_ms")
+                    .and_then(|v| v.as_u64())
+                    .ok_or("'wait' action needs 'duration_ms'")?,
+            },
+            "wait_for" | "wait_for_element" => Self::WaitFor {
+                selector: string("selector")?,
+                timeout: object.get("timeout").and_then(|v| v.as_f64()),
+            },
+            "navigate" => Self::Navigate { url: string("url")? },
+            other => {
+                return Err(format!(
+                    "unknown action type '{other}' (use click, type, scroll, wait, wait_for or navigate)"
+                ));
+            }
+        })
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Click { selector } => format!("click '{selector}'"),
+            Self::Type { selector, .. } => format!("type into '{selector}'"),
+            Self::Scroll { selector } => format!("scroll to '{selector}'"),
+            Self::Wait { duration_ms } => format!("wait {duration_ms}ms"),
+            Self::WaitFor { selector, .. } => format!("wait for '{selector}'"),
+            Self::Navigate { url } => format!("navigate to {url}"),
+        }
+    }
+
+    async fn run(&self, client_manager: &ClientManager, session: &str, client: &Client) -> Result<(), String> {
+        let wait = Some(client_manager.get_config().default_wait_timeout_secs);
+        match self {
+            Self::Click { selector } => {
+                let element = client_manager.find_element_with_wait(client, selector, wait).await;
+                let element = element.map_err(|e| e.to_string())?;
+                client_manager.run_command(session, "click", element.click()).await.map_err(|e| e.to_string())
+            }
+            Self::Type { selector, text } => {
+                let element = client_manager.find_element_with_wait(client, selector, wait).await;
+                let element = element.map_err(|e| e.to_string())?;
+                let typed = element.send_keys(text);
+                client_manager.run_command(session, "send_keys", typed).await.map_err(|e| e.to_string())
+            }
+            Self::Scroll { selector } => {
+                let found = client_manager
+                    .run_command(session, "scroll", client.execute(SCROLL_SCRIPT, vec![json!(selector)]))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if found.as_bool() == Some(true) { Ok(()) } else { Err(format!("no element matches '{selector}'")) }
+            }
+            Self::Wait { duration_ms } => {
+                tokio::time::sleep(Duration::from_millis(*duration_ms)).await;
+                Ok(())
+            }
+            Self::WaitFor { selector, timeout } => client_manager
+                .find_element_with_wait(client, selector, timeout.or(wait))
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            Self::Navigate { url } => {
+                if let Err(e) = client_manager.drain_console_logs(session, client).await {
+                    tracing::debug!("Failed to keep console logs of session '{}': {}", session, e);
+                }
+                client_manager.navigate(session, client, url).await.map_err(|e| e.to_string())?;
+                setup_console_monitoring(client_manager, session, client).await.map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+/// What one browser produced
+struct BrowserRun {
+    metrics: Value,
+    console_errors: usize,
+    console_warnings: usize,
+    /// Description of the first action that failed; later actions were skipped
+    failed_action: Option<String>,
+    png_data: Vec<u8>,
+    elapsed_ms: u64,
+}
+
+/// Load a URL in several browsers at once and compare what each one shows
+pub async fn handle_compare_across_browsers(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    let url = arguments
+        .as_ref()
+        .and_then(|args| args.get("url"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| McpError::invalid_params("url parameter required", None))?;
+
+    let browsers_argument = arguments.as_ref().and_then(|args| args.get("browsers")).and_then(|v| v.as_array());
+    let requested: Vec<String> = match browsers_argument {
+        Some(values) => values.iter().filter_map(|v| v.as_str()).map(str::to_string).collect(),
+        None => client_manager.get_config().concurrent_drivers.clone(),
+    };
+    let mut browsers: Vec<DriverType> = Vec::new();
+    for name in &requested {
+        let browser = DriverType::from_string(name).ok_or_else(|| {
+            McpError::invalid_params(format!("Unknown browser '{name}': use chrome, firefox or edge"), None)
+        })?;
+        if !browsers.contains(&browser) {
+            browsers.push(browser);
+        }
+    }
+    if browsers.len() < 2 {
+        return Err(McpError::invalid_params("Name at least two different browsers to compare", None));
+    }
+
+    let actions = match arguments.as_ref().and_then(|args| args.get("actions")).and_then(|v| v.as_array()) {
+        Some(values) if values.len() > MAX_ACTIONS => {
+            return Err(McpError::invalid_params(format!("At most {MAX_ACTIONS} actions can be run"), None));
+        }
+        Some(values) => values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| CompareAction::parse(value).map_err(|e| format!("action {}: {}", i + 1, e)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| McpError::invalid_params(e, None))?,
+        None => Vec::new(),
+    };
+
+    let settle = Duration::from_millis(
+        arguments
+            .as_ref()
+            .and_then(|args| args.get("settle_ms"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_SETTLE_MS)
+            .min(10_000),
+    );
+
+    let flag = |name: &str, default: bool| {
+        arguments
+            .as_ref()
+            .and_then(|args| args.get(name))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(default)
+    };
+    let stitch = flag("stitch", true);
+    let inline = flag("inline", false);
+    let keep_sessions = flag("keep_sessions", false);
+
+    let save_prefix = arguments
+        .as_ref()
+        .and_then(|args| args.get("save_prefix"))
+        .and_then(|v| v.as_str());
+
+    // Session names pick the driver, so each browser gets its own prefix
+    let stamp = chrono::Utc::now().format("%H%M%S%3f");
+    let sessions: Vec<String> = browsers
+        .iter()
+        .map(|browser| format!("{}_compare_{}", browser.browser_name().to_lowercase(), stamp))
+        .collect();
+
+    let runs = futures::future::join_all(
+        sessions
+            .iter()
+            .map(|session| run_in_browser(client_manager, session, url, &actions, settle)),
+    );
+    let Some(runs) = cancellation.run_until_cancelled(runs).await else {
+        close_sessions(client_manager, &sessions).await;
+        return Ok(cancelled_response("Browser comparison"));
+    };
+
+    let artifacts = client_manager.get_artifact_store();
+    let mut summary = format!("Compared {url} in {} browsers:", browsers.len());
+    let mut content = Vec::new();
+    let mut results = Vec::with_capacity(browsers.len());
+    let mut encoded = Vec::new();
+    let mut stitch_session = None;
+    for ((browser, session), run) in browsers.iter().zip(&sessions).zip(runs) {
+        let name = browser.browser_name();
+        let run = match run {
+            Ok(run) => run,
+            Err(e) => {
+                summary.push_str(&format!("\n- {name}: failed: {e}"));
+                results.push(json!({ "browser": name, "session": session, "error": e }));
+                continue;
+            }
+        };
+
+        let saved = match save_prefix {
+            Some(prefix) => {
+                let file = format!("{prefix}-{}.png", name.to_lowercase());
+                match artifacts.write(Some("compare"), &file, run.png_data.clone()).await {
+                    Ok(path) => Some(path.display().to_string()),
+                    Err(e) => return Ok(error_response(format!("Failed to save screenshot: {e}"))),
+                }
+            }
+            None => None,
+        };
+        let base64_data = general_purpose::STANDARD.encode(&run.png_data);
+        let uri = client_manager.get_resource_store().add_screenshot(session, run.png_data);
+
+        let metric = |key: &str| run.metrics.get(key).cloned().unwrap_or(Value::Null);
+        summary.push_str(&format!(
+            "\n- {}: '{}', load {}, {} console error(s), {} warning(s): {}",
+            name,
+            run.metrics.get("title").and_then(|v| v.as_str()).unwrap_or_default(),
+            metric("load_ms").as_u64().map(|ms| format!("{ms}ms")).unwrap_or_else(|| "n/a".to_string()),
+            run.console_errors,
+            run.console_warnings,
+            uri
+        ));
+        if let Some(failed) = &run.failed_action {
+            summary.push_str(&format!("\n  action failed: {failed}"));
+        }
+        if inline && !stitch {
+            content.push(Content::text(name.to_string()));
+            content.push(Content::image(base64_data.clone(), "image/png"));
+        }
+        encoded.push((name.to_string(), base64_data));
+        stitch_session.get_or_insert(session.clone());
+        results.push(json!({
+            "browser": name,
+            "session": session,
+            "url": metric("url"),
+            "title": metric("title"),
+            "console_errors": run.console_errors,
+            "console_warnings": run.console_warnings,
+            "load": {
+                "ttfb_ms": metric("ttfb_ms"),
+                "dom_content_loaded_ms": metric("dom_content_loaded_ms"),
+                "load_ms": metric("load_ms"),
+                "first_contentful_paint_ms": metric("first_contentful_paint_ms"),
+                "transfer_bytes": metric("transfer_bytes"),
+                "resource_count": metric("resource_count"),
+            },
+            "failed_action": run.failed_action,
+            "screenshot": { "resource": uri, "path": saved },
+            "elapsed_ms": run.elapsed_ms,
+        }));
+    }
+
+    if encoded.is_empty() {
+        close_sessions(client_manager, &sessions).await;
+        return Ok(error_response(summary));
+    }
+
+    let differences = differences(&results);
+    if differences.is_empty() {
+        summary.push_str("\nTitles, final URLs and console error counts match");
+    } else {
+        for difference in &differences {
+            summary.push_str(&format!("\nDifference: {difference}"));
+        }
+    }
+
+    let mut comparison = Value::Null;
+    if stitch
+        && encoded.len() > 1
+        && let Some(session) = stitch_session
+    {
+        let stitched = match client_manager.get_existing_client(Some(session.clone())).await {
+            Ok((session, client)) => screenshots::stitch(client_manager, &session, &client, &encoded).await,
+            Err(e) => Err(e),
+        };
+        match stitched.and_then(|data| {
+            general_purpose::STANDARD
+                .decode(&data)
+                .map(|png_data| (data, png_data))
+                .map_err(|e| crate::WebDriverError::Execution(e.to_string()))
+        }) {
+            Ok((data, png_data)) => {
+                let saved = match save_prefix {
+                    Some(prefix) => artifacts
+                        .write(Some("compare"), &format!("{prefix}-comparison.png"), png_data.clone())
+                        .await
+                        .map(|path| path.display().to_string())
+                        .ok(),
+                    None => None,
+                };
+                let uri = client_manager.get_resource_store().add_screenshot(&session, png_data);
+                summary.push_str(&format!("\nSide-by-side image: {uri}"));
+                if let Some(path) = &saved {
+                    summary.push_str(&format!(" saved to {path}"));
+                }
+                comparison = json!({ "resource": uri, "path": saved });
+                if inline {
+                    content.push(Content::image(data, "image/png"));
+                }
+            }
+            Err(e) => summary.push_str(&format!("\nCould not build the side-by-side image: {e}")),
+        }
+    }
+
+    if keep_sessions {
+        summary.push_str(&format!("\nSessions kept open: {}", sessions.join(", ")));
+    } else {
+        close_sessions(client_manager, &sessions).await;
+    }
+
+    let data = json!({
+        "url": url,
+        "browsers": results,
+        "differences": differences,
+        "comparison": comparison,
+        "sessions_kept": keep_sessions,
+    });
+    let mut result = structured_response(summary, data);
+    result.content.extend(content);
+    Ok(result)
+}
+
+/// Open `session`, load the page, run the actions and capture the outcome
+async fn run_in_browser(
+    client_manager: &ClientManager,
+    session: &str,
+    url: &str,
+    actions: &[CompareAction],
+    settle: Duration,
+) -> Result<BrowserRun, String> {
+    let started = Instant::now();
+    let (session, client) = client_manager
+        .get_or_create_client(Some(session.to_string()))
+        .await
+        .map_err(|e| format!("could not open a session: {e}"))?;
+    client_manager
+        .navigate(&session, &client, url)
+        .await
+        .map_err(|e| format!("failed to navigate: {e}"))?;
+    if let Err(e) = setup_console_monitoring(client_manager, &session, &client).await {
+        tracing::debug!("Failed to set up console monitoring in session '{}': {}", session, e);
+    }
+
+    let mut failed_action = None;
+    for (i, action) in actions.iter().enumerate() {
+        if let Err(e) = action.run(client_manager, &session, &client).await {
+            failed_action = Some(format!("action {} ({}): {}", i + 1, action.describe(), e));
+            break;
+        }
+    }
+    tokio::time::sleep(settle).await;
+
+    let metrics = client_manager
+        .run_command(&session, "load_metrics", client.execute(LOAD_METRICS_SCRIPT, vec![]))
+        .await
+        .map_err(|e| format!("failed to read load metrics: {e}"))?;
+    if let Err(e) = client_manager.drain_console_logs(&session, &client).await {
+        tracing::debug!("Failed to read console logs of session '{}': {}", session, e);
+    }
+    let logs = client_manager.console_logs(&session);
+    let level_count = |level: &str| {
+        logs.iter()
+            .filter(|entry| entry.get("level").and_then(|v| v.as_str()) == Some(level))
+            .count()
+    };
+    let png_data = client_manager
+        .run_command(&session, "screenshot", client.screenshot())
+        .await
+        .map_err(|e| format!("failed to take screenshot: {e}"))?;
+
+    Ok(BrowserRun {
+        metrics,
+        console_errors: level_count("error"),
+        console_warnings: level_count("warn"),
+        failed_action,
+        png_data,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Titles, final URLs and console error counts that are not the same in all browsers
+fn differences(results: &[Value]) -> Vec<String> {
+    let succeeded: Vec<&Value> = results.iter().filter(|result| result.get("error").is_none()).collect();
+    ["title", "url", "console_errors"]
+        .iter()
+        .filter_map(|key| {
+            let values: Vec<&Value> = succeeded.iter().map(|result| &result[*key]).collect();
+            if values.windows(2).all(|pair| pair[0] == pair[1]) {
+                return None;
+            }
+            let listed: Vec<String> = succeeded
+                .iter()
+                .map(|result| format!("{} {}", result["browser"].as_str().unwrap_or_default(), result[*key]))
+                .collect();
+            Some(format!("{} differs: {}", key.replace('_', " "), listed.join(", ")))
+        })
+        .collect()
+}
+
+async fn close_sessions(client_manager: &ClientManager, sessions: &[String]) {
+    for session in sessions {
+        if let Err(e) = client_manager.close_session(session).await {
+            tracing::debug!("Failed to close comparison session '{}': {}", session, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_actions_and_differences() {
+        assert_eq!(
+            CompareAction::parse(&json!({ "type": "type", "selector": "#q", "text": "shoes" })).unwrap(),
+            CompareAction::Type { selector: "#q".to_string(), text: "shoes".to_string() }
+        );
+        assert_eq!(
+            CompareAction::parse(&json!({ "type": "wait_for", "selector": ".results" })).unwrap(),
+            CompareAction::WaitFor { selector: ".results".to_string(), timeout: None }
+        );
+        assert!(CompareAction::parse(&json!({ "type": "click" })).is_err());
+        assert!(CompareAction::parse(&json!({ "type": "hover", "selector": "a" })).is_err());
+
+        let results = [
+            json!({ "browser": "Chrome", "title": "Shop", "url": "https://a.test/", "console_errors": 0 }),
+            json!({ "browser": "Firefox", "title": "Shop", "url": "https://a.test/", "console_errors": 2 }),
+            json!({ "browser": "Edge", "error": "could not open a session" }),
+        ];
+        assert_eq!(differences(&results), vec!["console errors differs: Chrome 0, Firefox 2".to_string()]);
+    }
+}
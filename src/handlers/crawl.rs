@@ -0,0 +1,429 @@
+//! Crawl handlers
+//!
+//! Handles multi-page crawls:
+//! - Seeding from a start URL or a sitemap.xml
+//! - Following same-origin links breadth-first within depth and page limits
+//! - Per-page title, status, broken resources and optional selector extraction
+//! - Skipping pages robots.txt disallows when `respect_robots_txt` is on
+
+use std::{
+    collections::{HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde_json::{Map, Value, json};
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use crate::{
+    ClientManager, ErrorCode, text,
+    tools::{coded_error_response, error_response, structured_response},
+};
+use super::{ProgressReporter, extract_session_id, session_error_response};
+
+const DEFAULT_MAX_DEPTH: u64 = 2;
+const DEFAULT_MAX_PAGES: u64 = 20;
+/// Upper bound on `max_pages`, whatever the caller asks for
+const MAX_PAGES_LIMIT: u64 = 500;
+/// Pause between page loads so crawls do not hammer the site
+const DEFAULT_DELAY_MS: u64 = 1000;
+/// Items returned per page for `extract_selector`
+const MAX_EXTRACTED_ITEMS: usize = 50;
+/// Child sitemaps followed from a sitemap index
+const MAX_CHILD_SITEMAPS: usize = 10;
+
+/// Links to these files are never loaded as pages
+const SKIPPED_EXTENSIONS: &[&str] = &[
+    ".pdf", ".zip", ".gz", ".tar", ".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp", ".ico", ".mp3", ".mp4",
+    ".webm", ".css", ".js", ".json", ".xml", ".exe", ".dmg",
+];
+
+/// Collects title, HTTP status (where the browser reports it), links, broken
+/// resources and optionally the text of elements matching `arguments[0]`
+const PAGE_SCRIPT: &str = r#"
+    const [extractSelector, maxItems] = arguments;
+    const nav = performance.getEntriesByType('navigation')[0];
+    const status = nav && typeof nav.responseStatus === 'number' && nav.responseStatus > 0 ? nav.responseStatus : null;
+    const links = Array.from(document.querySelectorAll('a[href]'))
+        .map(a => a.href)
+        .filter(href => href.startsWith('http'));
+
+    const broken = new Map();
+    document.querySelectorAll('img').forEach(img => {
+        if (img.complete && img.naturalWidth === 0 && img.currentSrc) {
+            broken.set(img.currentSrc, { url: img.currentSrc, type: 'img', reason: 'failed to load' });
+        }
+    });
+    performance.getEntriesByType('resource').forEach(entry => {
+        if (typeof entry.responseStatus === 'number' && entry.responseStatus >= 400) {
+            broken.set(entry.name, { url: entry.name, type: entry.initiatorType, status: entry.responseStatus });
+        }
+    });
+
+    let extracted = null;
+    if (extractSelector) {
+        try {
+            extracted = Array.from(document.querySelectorAll(extractSelector))
+                .slice(0, maxItems)
+                .map(el => (el.innerText || el.textContent || '').trim());
+        } catch (e) {
+            extracted = { error: String(e) };
+        }
+    }
+
+    return {
+        url: location.href,
+        title: document.title,
+        status,
+        links: Array.from(new Set(links)),
+        broken: Array.from(broken.values()),
+        extracted,
+    };
+"#;
+
+/// Crawl a site from a start URL or sitemap, reporting each page as it is visited
+pub async fn handle_crawl(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
+    progress: &ProgressReporter,
+) -> Result<CallToolResult, McpError> {
+    let start = arguments
+        .as_ref()
+        .and_then(|args| args.get("url"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| McpError::invalid_params("url parameter required", None))?;
+    let start_url = Url::parse(start)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .ok_or_else(|| McpError::invalid_params(format!("Invalid url '{start}': use an http(s) URL"), None))?;
+
+    let arg_u64 = |name: &str| {
+        arguments
+            .as_ref()
+            .and_then(|args| args.get(name))
+            .and_then(|v| v.as_u64())
+    };
+    let max_depth = arg_u64("max_depth").unwrap_or(DEFAULT_MAX_DEPTH);
+    let max_pages = arg_u64("max_pages").unwrap_or(DEFAULT_MAX_PAGES).clamp(1, MAX_PAGES_LIMIT);
+    let delay = Duration::from_millis(arg_u64("delay_ms").unwrap_or(DEFAULT_DELAY_MS));
+
+    let extract_selector = arguments
+        .as_ref()
+        .and_then(|args| args.get("extract_selector"))
+        .and_then(|v| v.as_str());
+
+    // A URL ending in .xml is taken to be a sitemap unless told otherwise
+    let from_sitemap = arguments
+        .as_ref()
+        .and_then(|args| args.get("sitemap"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or_else(|| start_url.path().ends_with(".xml"));
+
+    let mut queue: VecDeque<(Url, u64)> = VecDeque::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    if from_sitemap {
+        let locations = match fetch_sitemap(&start_url).await {
+            Ok(locations) => locations,
+            Err(e) => return Ok(error_response(format!("Failed to read sitemap {start_url}: {e}"))),
+        };
+        for location in locations {
+            if let Some(url) = crawlable(&start_url, &location)
+                && seen.insert(url.to_string())
+            {
+                queue.push_back((url, 0));
+            }
+        }
+    } else {
+        if let Some(block) = client_manager.robots_block(start_url.as_str()).await {
+            let context = json!({ "url": start_url.as_str(), "robots": block });
+            return Ok(coded_error_response(ErrorCode::PolicyBlocked, block.describe(), context));
+        }
+        let mut url = start_url.clone();
+        url.set_fragment(None);
+        seen.insert(url.to_string());
+        queue.push_back((url, 0));
+    }
+
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_or_create_client(session_id).await {
+        Ok((session, client)) => {
+            let started = Instant::now();
+            let mut pages: Vec<Value> = Vec::new();
+            let mut disallowed: Vec<Value> = Vec::new();
+            let mut cancelled = false;
+
+            while let Some((url, depth)) = queue.pop_front() {
+                if pages.len() as u64 >= max_pages {
+                    queue.push_front((url, depth));
+                    break;
+                }
+                if let Some(block) = client_manager.robots_block(url.as_str()).await {
+                    disallowed.push(json!({ "url": url.as_str(), "depth": depth, "rule": block.rule }));
+                    continue;
+                }
+                if !pages.is_empty() && !delay.is_zero() {
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = cancellation.cancelled() => {}
+                    }
+                }
+                if cancellation.is_cancelled() {
+                    queue.push_front((url, depth));
+                    cancelled = true;
+                    break;
+                }
+
+                let page = crawl_page(client_manager, &session, &client, &url, depth, extract_selector).await;
+                if depth < max_depth {
+                    let links = page.get("links").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+                    for link in links.iter().filter_map(|v| v.as_str()) {
+                        if let Some(link) = crawlable(&start_url, link)
+                            && seen.insert(link.to_string())
+                        {
+                            queue.push_back((link, depth + 1));
+                        }
+                    }
+                }
+
+                progress
+                    .report(
+                        pages.len() as u32 + 1,
+                        Some(max_pages.min(pages.len() as u64 + 1 + queue.len() as u64) as u32),
+                        format!("{} {}", page_status_label(&page), url),
+                    )
+                    .await;
+                pages.push(page);
+            }
+
+            let failed = pages.iter().filter(|page| page_failed(page)).count();
+            let broken_total: usize = pages
+                .iter()
+                .filter_map(|page| page.get("broken_resources").and_then(|v| v.as_array()))
+                .map(Vec::len)
+                .sum();
+
+            let mut report_text = format!(
+                "Crawled {} page(s) from {} in {:.1}s (session: {}): {} failed, {} broken resource(s){}\n\n",
+                pages.len(),
+                start_url,
+                started.elapsed().as_secs_f64(),
+                session,
+                failed,
+                broken_total,
+                if cancelled { ", cancelled by client" } else { "" }
+            );
+            for page in &pages {
+                let title = page.get("title").and_then(|v| v.as_str()).unwrap_or_default();
+                report_text.push_str(&format!(
+                    "{} {} {} {}\n",
+                    if page_failed(page) { "❌" } else { "✅" },
+                    page_status_label(page),
+                    page.get("url").and_then(|v| v.as_str()).unwrap_or_default(),
+                    text::preview(title, 80)
+                ));
+                if let Some(error) = page.get("error").and_then(|v| v.as_str()) {
+                    report_text.push_str(&format!("    error: {}\n", text::preview(error, 200)));
+                }
+                for resource in page.get("broken_resources").and_then(|v| v.as_array()).into_iter().flatten() {
+                    report_text.push_str(&format!(
+                        "    broken {}: {}\n",
+                        resource.get("type").and_then(|v| v.as_str()).unwrap_or("resource"),
+                        resource.get("url").and_then(|v| v.as_str()).unwrap_or_default()
+                    ));
+                }
+            }
+            if !disallowed.is_empty() {
+                report_text.push_str(&format!(
+                    "\n{} URL(s) were skipped because robots.txt disallows them\n",
+                    disallowed.len()
+                ));
+            }
+            if !queue.is_empty() {
+                report_text.push_str(&format!(
+                    "\n{} more URL(s) were queued; raise max_pages to crawl them\n",
+                    queue.len()
+                ));
+            }
+
+            let report = json!({
+                "session": session,
+                "start_url": start_url.as_str(),
+                "max_depth": max_depth,
+                "max_pages": max_pages,
+                "pages_crawled": pages.len(),
+                "failed": failed,
+                "broken_resources": broken_total,
+                "not_crawled": queue.len(),
+                "disallowed_by_robots": disallowed,
+                "cancelled": cancelled,
+                "duration_ms": started.elapsed().as_millis() as u64,
+                "pages": pages,
+            });
+
+            let name = format!(
+                "crawl-{}-{}.json",
+                chrono::Utc::now().format("%H%M%S"),
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            );
+            let report_path = match serde_json::to_vec_pretty(&report) {
+                Ok(bytes) => client_manager
+                    .get_artifact_store()
+                    .write(Some("crawls"), &name, bytes)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match &report_path {
+                Ok(path) => report_text.push_str(&format!("\nReport saved to: {}\n", path.display())),
+                Err(e) => tracing::warn!("Failed to save crawl report: {}", e),
+            }
+
+            let mut data = report;
+            data["report_path"] = json!(report_path.ok().map(|path| path.display().to_string()));
+            Ok(structured_response(report_text, data))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Load one page and collect its data; failures are recorded on the page, not returned
+async fn crawl_page(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &fantoccini::Client,
+    url: &Url,
+    depth: u64,
+    extract_selector: Option<&str>,
+) -> Value {
+    let started = Instant::now();
+    if let Err(e) = client_manager.navigate(session, client, url.as_str()).await {
+        return json!({ "url": url.as_str(), "depth": depth, "error": format!("Failed to load: {e}") });
+    }
+    let load_ms = started.elapsed().as_millis() as u64;
+
+    let info = client_manager
+        .run_command(
+            session,
+            "crawl_page",
+            client.execute(PAGE_SCRIPT, vec![json!(extract_selector), json!(MAX_EXTRACTED_ITEMS)]),
+        )
+        .await;
+    match info {
+        Ok(info) => json!({
+            "url": url.as_str(),
+            "final_url": info.get("url"),
+            "depth": depth,
+            "title": info.get("title"),
+            "status": info.get("status"),
+            "load_ms": load_ms,
+            "links": info.get("links"),
+            "broken_resources": info.get("broken"),
+            "extracted": info.get("extracted"),
+        }),
+        Err(e) => json!({
+            "url": url.as_str(),
+            "depth": depth,
+            "load_ms": load_ms,
+            "error": format!("Failed to read page: {e}"),
+        }),
+    }
+}
+
+fn page_failed(page: &Value) -> bool {
+    page.get("error").is_some() || page.get("status").and_then(|v| v.as_u64()).is_some_and(|status| status >= 400)
+}
+
+fn page_status_label(page: &Value) -> String {
+    if page.get("error").is_some() {
+        return "ERR".to_string();
+    }
+    page.get("status")
+        .and_then(|v| v.as_u64())
+        .map(|status| status.to_string())
+        .unwrap_or_else(|| "---".to_string())
+}
+
+/// `link` as a crawlable page URL: same origin as the start, without fragment,
+/// and not an obvious download or asset
+fn crawlable(start: &Url, link: &str) -> Option<Url> {
+    let mut url = start.join(link).ok()?;
+    url.set_fragment(None);
+    if url.origin() != start.origin() {
+        return None;
+    }
+    let path = url.path().to_lowercase();
+    if SKIPPED_EXTENSIONS.iter().any(|extension| path.ends_with(extension)) {
+        return None;
+    }
+    Some(url)
+}
+
+/// Page URLs listed in a sitemap, following a sitemap index one level down
+async fn fetch_sitemap(url: &Url) -> Result<Vec<String>, String> {
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let fetch = |url: String| {
+        let http = http.clone();
+        async move {
+            let response = http.get(&url).send().await.map_err(|e| e.to_string())?;
+            if !response.status().is_success() {
+                return Err(format!("{url} answered {}", response.status()));
+            }
+            response.text().await.map_err(|e| e.to_string())
+        }
+    };
+
+    let body = fetch(url.to_string()).await?;
+    if !body.contains("<sitemapindex") {
+        return Ok(sitemap_locations(&body));
+    }
+
+    let mut locations = Vec::new();
+    for child in sitemap_locations(&body).into_iter().take(MAX_CHILD_SITEMAPS) {
+        match fetch(child.clone()).await {
+            Ok(body) => locations.extend(sitemap_locations(&body)),
+            Err(e) => tracing::warn!("Skipping sitemap {}: {}", child, e),
+        }
+    }
+    Ok(locations)
+}
+
+/// Contents of every `<loc>` element
+fn sitemap_locations(xml: &str) -> Vec<String> {
+    xml.split("<loc>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</loc>").map(|(location, _)| location))
+        .map(|location| {
+            location
+                .trim()
+                .trim_start_matches("<![CDATA[")
+                .trim_end_matches("]]>")
+                .replace("&amp;", "&")
+        })
+        .filter(|location| !location.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sitemap_locations_and_crawlable_links() {
+        let xml = r#"<urlset><url><loc> https://example.com/a?x=1&amp;y=2 </loc></url>
+            <url><loc><![CDATA[https://example.com/b]]></loc></url></urlset>"#;
+        assert_eq!(sitemap_locations(xml), ["https://example.com/a?x=1&y=2", "https://example.com/b"]);
+
+        let start = Url::parse("https://example.com/docs/").unwrap();
+        assert_eq!(crawlable(&start, "intro#top").unwrap().as_str(), "https://example.com/docs/intro");
+        assert!(crawlable(&start, "https://other.com/").is_none());
+        assert!(crawlable(&start, "/files/manual.PDF").is_none());
+        assert!(crawlable(&start, "mailto:team@example.com").is_none());
+    }
+}
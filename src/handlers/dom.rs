@@ -0,0 +1,214 @@
+//! DOM snapshot handlers
+//!
+//! Handles comparing page states:
+//! - Capturing named, normalized DOM snapshots
+//! - Reporting added, removed and changed nodes between two snapshots
+
+use fantoccini::Client;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    ClientManager,
+    dom_snapshot::{self, DomChange, DomSnapshot, MAX_SNAPSHOT_NODES, SNAPSHOT_SCRIPT},
+    tools::{error_response, structured_response},
+};
+use super::{extract_session_id, session_error_response};
+
+/// Snapshot name used when a call gives none
+const DEFAULT_SNAPSHOT_NAME: &str = "default";
+/// Changes `diff_dom` returns when the call sets no `max_changes`
+const DEFAULT_MAX_CHANGES: u64 = 200;
+
+/// Capture the current DOM and keep it under a name for `diff_dom`
+pub async fn handle_snapshot_dom(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let name = arguments
+        .as_ref()
+        .and_then(|args| args.get("name"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_SNAPSHOT_NAME);
+
+    let root = arguments
+        .as_ref()
+        .and_then(|args| args.get("selector"))
+        .and_then(|v| v.as_str());
+
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            let snapshot = match capture(client_manager, &session, &client, name, root).await {
+                Ok(snapshot) => snapshot,
+                Err(e) => return Ok(error_response(e)),
+            };
+
+            let mut message = format!(
+                "Saved DOM snapshot '{}' of {} ({} elements{}, session: {})",
+                name,
+                snapshot.url,
+                snapshot.node_count,
+                root.map(|root| format!(" under '{root}'")).unwrap_or_default(),
+                session
+            );
+            if snapshot.truncated {
+                message.push_str(&format!(
+                    "\nOnly the first {MAX_SNAPSHOT_NODES} elements were captured; pass a selector to snapshot a smaller part of the page"
+                ));
+            }
+            let data = json!({
+                "session": session,
+                "name": name,
+                "url": &snapshot.url,
+                "selector": root,
+                "node_count": snapshot.node_count,
+                "truncated": snapshot.truncated,
+                "taken_at": &snapshot.taken_at,
+            });
+            client_manager.save_dom_snapshot(&session, snapshot);
+            Ok(structured_response(message, data))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Compare two snapshots, or a snapshot with the page as it is now
+pub async fn handle_diff_dom(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let before_name = arguments
+        .as_ref()
+        .and_then(|args| args.get("before"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_SNAPSHOT_NAME);
+
+    let after_name = arguments
+        .as_ref()
+        .and_then(|args| args.get("after"))
+        .and_then(|v| v.as_str());
+
+    let max_changes = arguments
+        .as_ref()
+        .and_then(|args| args.get("max_changes"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_MAX_CHANGES) as usize;
+
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            let missing = |name: &str| {
+                let names = client_manager.dom_snapshot_names(&session);
+                error_response(format!(
+                    "No DOM snapshot named '{}' in session '{}'. Saved snapshots: {}",
+                    name,
+                    session,
+                    if names.is_empty() { "none".to_string() } else { names.join(", ") }
+                ))
+            };
+            let Some(before) = client_manager.dom_snapshot(&session, before_name) else {
+                return Ok(missing(before_name));
+            };
+            let after = match after_name {
+                Some(name) => match client_manager.dom_snapshot(&session, name) {
+                    Some(snapshot) => snapshot,
+                    None => return Ok(missing(name)),
+                },
+                // Compare with the page as it is now, under the same root
+                None => match capture(client_manager, &session, &client, "current", before.root.as_deref()).await {
+                    Ok(snapshot) => snapshot,
+                    Err(e) => return Ok(error_response(e)),
+                },
+            };
+
+            let root_path = before.root.clone().unwrap_or_else(|| before.tree.tag.clone());
+            let mut changes = dom_snapshot::diff(&before.tree, &after.tree, &root_path);
+            let added = changes.iter().filter(|change| matches!(change, DomChange::Added { .. })).count();
+            let removed = changes.iter().filter(|change| matches!(change, DomChange::Removed { .. })).count();
+            let changed = changes.iter().filter(|change| matches!(change, DomChange::Changed { .. })).count();
+            let total = changes.len();
+            changes.truncate(max_changes);
+
+            let mut message = format!(
+                "DOM diff '{}' → '{}' (session: {}): {} added, {} removed, {} changed",
+                before.name, after.name, session, added, removed, changed
+            );
+            if before.url != after.url {
+                message.push_str(&format!("\nURL changed: {} → {}", before.url, after.url));
+            }
+            if before.truncated || after.truncated {
+                message.push_str("\nA snapshot was cut off at the element limit, so changes past it are not reported");
+            }
+            if total == 0 {
+                message.push_str("\nNo differences");
+            } else {
+                message.push('\n');
+                for change in &changes {
+                    message.push_str(&format!("\n{}", change.summary()));
+                }
+                if total > changes.len() {
+                    message.push_str(&format!(
+                        "\n... showing the first {} of {} changes; raise `max_changes` to see more",
+                        changes.len(),
+                        total
+                    ));
+                }
+            }
+
+            Ok(structured_response(
+                message,
+                json!({
+                    "session": session,
+                    "before": { "name": before.name, "url": before.url, "taken_at": before.taken_at },
+                    "after": { "name": after.name, "url": after.url, "taken_at": after.taken_at },
+                    "added": added,
+                    "removed": removed,
+                    "changed": changed,
+                    "total": total,
+                    "truncated": total > changes.len(),
+                    "changes": changes,
+                }),
+            ))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Run the snapshot script and parse its tree
+async fn capture(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    name: &str,
+    root: Option<&str>,
+) -> Result<DomSnapshot, String> {
+    let result = client_manager
+        .run_command(
+            session,
+            "snapshot_dom",
+            client.execute(SNAPSHOT_SCRIPT, vec![json!(root), json!(MAX_SNAPSHOT_NODES)]),
+        )
+        .await
+        .map_err(|e| format!("Failed to snapshot DOM: {e}"))?;
+    if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+        return Err(format!("Failed to snapshot DOM: {error}"));
+    }
+
+    let tree = result
+        .get("tree")
+        .cloned()
+        .ok_or_else(|| "Failed to snapshot DOM: the page returned no tree".to_string())
+        .and_then(|tree| serde_json::from_value(tree).map_err(|e| format!("Failed to read DOM snapshot: {e}")))?;
+    Ok(DomSnapshot {
+        name: name.to_string(),
+        url: result.get("url").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        root: root.map(str::to_string),
+        taken_at: chrono::Utc::now().to_rfc3339(),
+        node_count: result.get("node_count").and_then(|v| v.as_u64()).unwrap_or(0),
+        truncated: result.get("truncated").and_then(|v| v.as_bool()).unwrap_or(false),
+        tree,
+    })
+}
@@ -6,12 +6,13 @@
 //! - Orphaned process cleanup
 
 use rmcp::{ErrorData as McpError, model::CallToolResult};
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
 
 use crate::{
-    ClientManager,
+    ClientManager, ErrorCode, ManagedSessions,
     driver::DriverType,
-    tools::{error_response, success_response},
+    endpoints::EndpointStatus,
+    tools::{coded_error_response, error_response, structured_response, success_response},
 };
 
 /// Get currently healthy WebDriver endpoints
@@ -19,12 +20,11 @@ pub async fn handle_get_healthy_endpoints(
     client_manager: &ClientManager,
     _arguments: &Option<Map<String, Value>>,
 ) -> Result<CallToolResult, McpError> {
-    let driver_manager = client_manager.get_driver_manager();
-    let healthy_endpoints = driver_manager.get_healthy_endpoints().await;
+    let registry = client_manager.get_driver_manager().endpoint_registry();
 
     let mut result = serde_json::Map::new();
-    for (driver_type, endpoint) in healthy_endpoints {
-        result.insert(driver_type.browser_name().to_lowercase(), Value::String(endpoint));
+    for entry in registry.entries().into_iter().filter(|entry| entry.status == EndpointStatus::Healthy) {
+        result.insert(entry.browser, Value::String(entry.endpoint));
     }
 
     Ok(success_response(format!(
@@ -33,6 +33,38 @@ pub async fn handle_get_healthy_endpoints(
     )))
 }
 
+/// Endpoint, health, last check and version of every known driver; with
+/// `refresh`, checks them all first
+pub async fn handle_get_endpoint_status(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let refresh = arguments
+        .as_ref()
+        .and_then(|args| args.get("refresh"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let driver_manager = client_manager.get_driver_manager();
+    if refresh && let Err(e) = driver_manager.refresh_driver_health().await {
+        return Ok(error_response(format!("Health check failed: {e}")));
+    }
+
+    let entries = driver_manager.endpoint_registry().entries();
+    let healthy = entries.iter().filter(|entry| entry.status == EndpointStatus::Healthy).count();
+    let mut message = format!("{healthy} of {} known endpoint(s) healthy", entries.len());
+    for entry in &entries {
+        let status = serde_json::to_value(entry.status).unwrap_or_default();
+        message.push_str(&format!(
+            "\n  {}: {} ({}{})",
+            entry.browser,
+            entry.endpoint,
+            status.as_str().unwrap_or_default(),
+            entry.version.as_ref().map(|version| format!(", version {version}")).unwrap_or_default()
+        ));
+    }
+    Ok(structured_response(message, json!({ "healthy": healthy, "endpoints": entries })))
+}
+
 /// Refresh health status of all WebDriver endpoints
 pub async fn handle_refresh_driver_health(
     client_manager: &ClientManager,
@@ -106,7 +138,49 @@ pub async fn handle_start_driver(
     }
 }
 
-/// Stop a specific WebDriver process by type
+/// "a, b and 2 session(s) of other users": the caller sees only their own session ids
+fn describe_sessions(sessions: &ManagedSessions) -> String {
+    let mut parts = sessions.own();
+    if sessions.others() > 0 {
+        parts.push(format!("{} session(s) of other users", sessions.others()));
+    }
+    parts.join(", ")
+}
+
+/// Refusal to stop `what` while it still serves `sessions`
+fn sessions_active_response(what: &str, sessions: &ManagedSessions) -> CallToolResult {
+    coded_error_response(
+        ErrorCode::SessionsActive,
+        format!(
+            "Stopping {what} would orphan open sessions: {}. Close them first, or pass force: true to close \
+             them and stop anyway",
+            describe_sessions(sessions)
+        ),
+        json!({ "sessions": sessions.own(), "other_sessions": sessions.others() }),
+    )
+}
+
+/// Close `sessions` before their driver goes away. Returns the caller's sessions
+/// whose browser did not close cleanly; the server forgets them either way.
+async fn close_sessions(client_manager: &ClientManager, sessions: &ManagedSessions) -> Vec<Value> {
+    client_manager
+        .close_managed_sessions(sessions)
+        .await
+        .into_iter()
+        .map(|(session, e)| json!({ "session_id": session, "error": e.to_string() }))
+        .collect()
+}
+
+fn force_flag(arguments: &Option<Map<String, Value>>) -> bool {
+    arguments
+        .as_ref()
+        .and_then(|args| args.get("force"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Stop a specific WebDriver process by type. Sessions it serves are closed
+/// first with `force`; without it the stop is refused while they are open.
 pub async fn handle_stop_driver(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
@@ -120,13 +194,31 @@ pub async fn handle_stop_driver(
     let driver_type = DriverType::from_string(driver_type_str)
         .ok_or_else(|| McpError::invalid_params("Invalid driver_type. Use: chrome, firefox, or edge", None))?;
 
+    let sessions = client_manager.sessions_on_managed_driver(Some(&driver_type)).await;
+    if !sessions.is_empty() && !force_flag(arguments) {
+        let what = format!("the {} WebDriver", driver_type.browser_name());
+        return Ok(sessions_active_response(&what, &sessions));
+    }
+    let close_errors = close_sessions(client_manager, &sessions).await;
+
     let driver_manager = client_manager.get_driver_manager();
 
     match driver_manager.stop_driver_by_type(&driver_type).await {
-        Ok(_) => Ok(success_response(format!(
-            "Successfully stopped {} WebDriver",
-            driver_type.browser_name()
-        ))),
+        Ok(_) => {
+            let mut message = format!("Successfully stopped {} WebDriver", driver_type.browser_name());
+            if !sessions.is_empty() {
+                message.push_str(&format!(" after closing {}", describe_sessions(&sessions)));
+            }
+            Ok(structured_response(
+                message,
+                json!({
+                    "driver_type": driver_type.browser_name().to_lowercase(),
+                    "closed_sessions": sessions.own(),
+                    "other_sessions_closed": sessions.others(),
+                    "close_errors": close_errors,
+                }),
+            ))
+        }
         Err(e) => Ok(error_response(format!(
             "Failed to stop {} WebDriver: {}",
             driver_type.browser_name(),
@@ -135,15 +227,32 @@ pub async fn handle_stop_driver(
     }
 }
 
-/// Stop all running WebDriver processes
+/// Stop all running WebDriver processes, with the same session checks as `stop_driver`
 pub async fn handle_stop_all_drivers(
     client_manager: &ClientManager,
-    _arguments: &Option<Map<String, Value>>,
+    arguments: &Option<Map<String, Value>>,
 ) -> Result<CallToolResult, McpError> {
+    let sessions = client_manager.sessions_on_managed_driver(None).await;
+    if !sessions.is_empty() && !force_flag(arguments) {
+        return Ok(sessions_active_response("all WebDriver processes", &sessions));
+    }
+    let close_errors = close_sessions(client_manager, &sessions).await;
+
     let driver_manager = client_manager.get_driver_manager();
 
     match driver_manager.stop_all_drivers().await {
-        Ok(_) => Ok(success_response("Successfully stopped all WebDriver processes".to_string())),
+        Ok(_) => {
+            let mut message = "Successfully stopped all WebDriver processes".to_string();
+            if !sessions.is_empty() {
+                message.push_str(&format!(" after closing {}", describe_sessions(&sessions)));
+            }
+            let data = json!({
+                "closed_sessions": sessions.own(),
+                "other_sessions_closed": sessions.others(),
+                "close_errors": close_errors,
+            });
+            Ok(structured_response(message, data))
+        }
         Err(e) => Ok(error_response(format!("Failed to stop all drivers: {e}"))),
     }
 }
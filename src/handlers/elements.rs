@@ -4,18 +4,502 @@
 //! - Finding elements (single and multiple)
 //! - Element interaction (click, send_keys, hover, scroll)
 //! - Element information (attributes, properties, computed styles)
-//! - Waiting for elements and conditions
-//! - Form filling and submission
+//! - Waiting for elements, conditions and the DOM to settle
+//! - Form discovery, filling and submission
 
-use fantoccini::Locator;
+use std::{future::Future, time::Duration};
+
+use fantoccini::{Client, Locator, elements::Element, error::CmdError};
 use rmcp::{ErrorData as McpError, model::CallToolResult};
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-    ClientManager,
-    tools::{error_response, success_response},
+    ClientManager, ErrorCode, WebDriverError, challenge,
+    click_intercept::{self, Interception},
+    element_handles::element_reference,
+    forms::{self, FieldAction},
+    login::{
+        self, DEFAULT_LOGIN_VERIFY_TIMEOUT_SECS, DEFAULT_OTP_FIELD_TIMEOUT_SECS, LoginCheck, LoginOutcome, LoginResult,
+        OTP_FIELD, TokenCookie,
+    },
+    page_scripts::selector_for_script,
+    popups, stability, text,
+    totp::Totp,
+    tools::{
+        coded_error_response, error_response, error_response_with,
+        params::{LoginViaTokenParams, WaitForAttributeParams, WaitForDomStableParams, WaitForPropertyParams},
+        structured_response, success_response,
+    },
+    typing::{self, TypingOptions},
 };
-use super::{extract_session_id, extract_wait_timeout};
+use super::{cancelled_response, extract_session_id, extract_wait_timeout, parse_params, session_error_response};
+
+/// WebDriver errors that usually succeed when the element is found again: the
+/// page re-rendered it or an overlay was animating away. The driver refused the
+/// action, so trying it again cannot repeat a click or keystroke.
+const TRANSIENT_ERRORS: &[&str] = &[
+    "stale element reference",
+    "element click intercepted",
+    "element not interactable",
+];
+
+/// Connection blips, retried only before the action was sent: afterwards the
+/// driver may have performed it, and a retry would click or type twice
+const CONNECTION_ERRORS: &[&str] = &["connection reset", "connection closed", "broken pipe", "unexpected eof"];
+
+/// Starts counting mutations under the first match of `arguments[0]`, or the
+/// whole document, replacing an observer left by an earlier wait. Returns
+/// `false` when nothing matches.
+const OBSERVE_MUTATIONS_SCRIPT: &str = r#"
+    const [selector] = arguments;
+    window.__mcpDomStable?.observer.disconnect();
+    const root = selector ? document.querySelector(selector) : document.documentElement;
+    if (!root) {
+        return false;
+    }
+    const state = { last: performance.now(), mutations: 0 };
+    state.observer = new MutationObserver(records => {
+        state.mutations += records.length;
+        state.last = performance.now();
+    });
+    state.observer.observe(root, { childList: true, subtree: true, attributes: true, characterData: true });
+    window.__mcpDomStable = state;
+    return true;
+"#;
+
+/// Milliseconds since the last mutation and mutations seen so far, or `null`
+/// when a navigation took the observer away
+const MUTATION_IDLE_SCRIPT: &str = r#"
+    const state = window.__mcpDomStable;
+    return state ? { idle_ms: performance.now() - state.last, mutations: state.mutations } : null;
+"#;
+
+const STOP_OBSERVING_SCRIPT: &str = r#"
+    window.__mcpDomStable?.observer.disconnect();
+    delete window.__mcpDomStable;
+"#;
+
+/// How often `wait_for_dom_stable` asks the page how long it has been quiet
+const DOM_STABLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Reads attribute or property `arguments[2]` (per `arguments[1]`) of the
+/// first match of `arguments[0]` and whether it equals `arguments[3]`, matches
+/// the regular expression `arguments[4]`, is gone (`arguments[5]`), or else is
+/// present (attributes) or truthy (properties)
+const ELEMENT_VALUE_SCRIPT: &str = r#"
+    const [selector, kind, name, expected, pattern, absent] = arguments;
+    try {
+        const regex = pattern === null ? null : new RegExp(pattern);
+        const el = document.querySelector(selector);
+        if (!el) {
+            return { found: false, matched: false, value: null };
+        }
+        const value = kind === 'attribute'
+            ? el.getAttribute(name)
+            : name.split('.').reduce((object, key) => object == null ? undefined : object[key], el);
+        let matched;
+        if (regex) {
+            matched = value != null && regex.test(String(value));
+        } else if (expected !== null) {
+            matched = JSON.stringify(value) === JSON.stringify(expected);
+        } else if (kind === 'attribute') {
+            matched = absent ? value === null : value !== null;
+        } else {
+            matched = !!value;
+        }
+        const plain = value == null || ['string', 'number', 'boolean'].includes(typeof value);
+        return { found: true, matched, value: plain ? (value ?? null) : String(value) };
+    } catch (e) {
+        return { error: String(e) };
+    }
+"#;
+
+/// Elements `find_elements` describes when the call sets no `limit`
+const DEFAULT_FIND_ELEMENTS_LIMIT: u64 = 100;
+
+/// Collects tag name and rendered text of up to `limit` elements matching
+/// `arguments[0]`, scoped to the first match of `arguments[1]` when given
+const FIND_ELEMENTS_SCRIPT: &str = r#"
+    const [selector, parentSelector, limit] = arguments;
+    try {
+        const root = parentSelector ? document.querySelector(parentSelector) : document;
+        if (!root) {
+            return { error: 'no such element' };
+        }
+        const matches = root.querySelectorAll(selector);
+        const elements = Array.from(matches).slice(0, limit).map(el => ({
+            element: el,
+            tag_name: el.tagName.toLowerCase(),
+            text: (el.innerText ?? el.textContent ?? '').trim(),
+        }));
+        return { total: matches.length, elements };
+    } catch (e) {
+        return { error: String(e) };
+    }
+"#;
+
+/// Reads computed CSS properties `arguments[1]` (all when empty) of the first match
+/// of `arguments[0]`, or of its pseudo-element `arguments[2]`
+const COMPUTED_STYLE_SCRIPT: &str = r#"
+    const [selector, properties, pseudoElement] = arguments;
+    let element;
+    try {
+        element = document.querySelector(selector);
+    } catch (e) {
+        return { error: String(e) };
+    }
+    if (!element) {
+        return { error: 'no such element' };
+    }
+    const style = window.getComputedStyle(element, pseudoElement || null);
+    const kebab = name => name.startsWith('--') || name.includes('-')
+        ? name
+        : name.replace(/[A-Z]/g, letter => '-' + letter.toLowerCase());
+    const styles = {};
+    const unknown = [];
+    if (properties.length) {
+        for (const property of properties) {
+            const value = style.getPropertyValue(kebab(property));
+            if (value === '' && !property.startsWith('--')) {
+                unknown.push(property);
+            } else {
+                styles[property] = value.trim();
+            }
+        }
+    } else {
+        for (let i = 0; i < style.length; i++) {
+            styles[style[i]] = style.getPropertyValue(style[i]).trim();
+        }
+    }
+    return { tag_name: element.tagName.toLowerCase(), styles, unknown };
+"#;
+
+/// Elements `get_element_rects` measures when the call sets no `limit`
+const DEFAULT_ELEMENT_RECTS_LIMIT: u64 = 100;
+
+/// Viewport-relative boxes and visibility of up to `arguments[1]` matches of
+/// `arguments[0]`, with the pairs of visible boxes that overlap
+const ELEMENT_RECTS_SCRIPT: &str = r#"
+    const [selector, limit] = arguments;
+    let matches;
+    try {
+        matches = Array.from(document.querySelectorAll(selector));
+    } catch (e) {
+        return { error: String(e) };
+    }
+    const viewport = { width: window.innerWidth, height: window.innerHeight, scroll_x: window.scrollX, scroll_y: window.scrollY };
+    const round = value => Math.round(value * 10) / 10;
+    const elements = matches.slice(0, limit).map((el, index) => {
+        const rect = el.getBoundingClientRect();
+        const style = window.getComputedStyle(el);
+        const visible = rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden'
+            && style.display !== 'none' && parseFloat(style.opacity) > 0;
+        const intersects = rect.bottom > 0 && rect.right > 0 && rect.top < viewport.height && rect.left < viewport.width;
+        const contained = rect.top >= 0 && rect.left >= 0 && rect.bottom <= viewport.height && rect.right <= viewport.width;
+        // What a click at the center would actually hit
+        let hit_target = null;
+        if (visible && intersects) {
+            const hit = document.elementFromPoint(rect.left + rect.width / 2, rect.top + rect.height / 2);
+            hit_target = !!hit && (hit === el || el.contains(hit));
+        }
+        return {
+            index,
+            tag_name: el.tagName.toLowerCase(),
+            text: (el.innerText ?? el.textContent ?? '').trim().replace(/\s+/g, ' ').slice(0, 80),
+            x: round(rect.x),
+            y: round(rect.y),
+            width: round(rect.width),
+            height: round(rect.height),
+            top: round(rect.top),
+            right: round(rect.right),
+            bottom: round(rect.bottom),
+            left: round(rect.left),
+            center: { x: round(rect.left + rect.width / 2), y: round(rect.top + rect.height / 2) },
+            visible,
+            in_viewport: contained ? 'full' : intersects ? 'partial' : 'none',
+            hit_target,
+        };
+    });
+    const overlaps = [];
+    for (let i = 0; i < elements.length; i++) {
+        for (let j = i + 1; j < elements.length; j++) {
+            const a = elements[i];
+            const b = elements[j];
+            if (!a.visible || !b.visible) continue;
+            const width = Math.min(a.right, b.right) - Math.max(a.left, b.left);
+            const height = Math.min(a.bottom, b.bottom) - Math.max(a.top, b.top);
+            if (width > 0 && height > 0) {
+                overlaps.push({ a: a.index, b: b.index, area: round(width * height) });
+            }
+        }
+    }
+    return { total: matches.length, viewport, elements, overlaps };
+"#;
+
+/// Counts matches of `arguments[0]`, and how many of them are rendered, scoped to
+/// the first match of `arguments[1]` when given
+const COUNT_ELEMENTS_SCRIPT: &str = r#"
+    const [selector, parentSelector] = arguments;
+    try {
+        const root = parentSelector ? document.querySelector(parentSelector) : document;
+        if (!root) {
+            return { parent_found: false, total: 0, visible: 0 };
+        }
+        const matches = Array.from(root.querySelectorAll(selector));
+        const visible = matches.filter(el => {
+            if (!(el.offsetWidth || el.offsetHeight || el.getClientRects().length)) return false;
+            const style = window.getComputedStyle(el);
+            return style.visibility !== 'hidden' && style.display !== 'none';
+        }).length;
+        return { parent_found: true, total: matches.length, visible };
+    } catch (e) {
+        return { error: String(e) };
+    }
+"#;
+
+/// How often `element_exists` looks again while waiting
+const EXISTS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Describes every form (or only `arguments[0]`) with its fields and submit
+/// controls, giving each a selector that matches exactly one element.
+/// Fields outside any form are reported as one extra standalone group.
+const DISCOVER_FORMS_SCRIPT: &str = concat!(selector_for_script!(), r#"
+    const [formSelector, includeHidden] = arguments;
+    const textOf = el => (el.innerText ?? el.textContent ?? '').trim().replace(/\s+/g, ' ');
+    const labelFor = el => {
+        const labels = el.labels ? Array.from(el.labels).map(textOf).filter(Boolean) : [];
+        if (labels.length) return labels.join(' ');
+        if (el.getAttribute('aria-label')) return el.getAttribute('aria-label').trim();
+        const labelledBy = (el.getAttribute('aria-labelledby') || '').split(/\s+/)
+            .map(id => document.getElementById(id))
+            .filter(Boolean)
+            .map(textOf)
+            .join(' ');
+        if (labelledBy) return labelledBy;
+        return el.getAttribute('placeholder') || el.getAttribute('title') || null;
+    };
+    const visible = el => !!(el.offsetWidth || el.offsetHeight || el.getClientRects().length);
+    const isSubmit = el => (el.tagName === 'BUTTON' && el.type === 'submit')
+        || (el.tagName === 'INPUT' && (el.type === 'submit' || el.type === 'image'));
+    const isField = el => ['INPUT', 'SELECT', 'TEXTAREA'].includes(el.tagName)
+        && !['submit', 'image', 'button', 'reset'].includes(el.type)
+        && (includeHidden || el.type !== 'hidden');
+
+    const describeFields = controls => {
+        const fields = [];
+        const radioGroups = new Map();
+        for (const el of controls.filter(isField)) {
+            const type = el.tagName === 'INPUT' ? el.type : el.tagName === 'SELECT' ? el.type : 'textarea';
+            const field = {
+                selector: selectorFor(el),
+                name: el.getAttribute('name'),
+                type,
+                label: labelFor(el),
+                required: el.required,
+                disabled: el.disabled,
+                visible: visible(el),
+            };
+            if (el.placeholder) field.placeholder = el.placeholder;
+            if (el.getAttribute('autocomplete')) field.autocomplete = el.getAttribute('autocomplete');
+            if (type === 'radio') {
+                const key = el.name || field.selector;
+                const option = { value: el.value, label: labelFor(el), selector: field.selector, checked: el.checked };
+                if (radioGroups.has(key)) {
+                    const group = radioGroups.get(key);
+                    group.options.push(option);
+                    group.required = group.required || el.required;
+                    if (el.checked) group.value = el.value;
+                    continue;
+                }
+                field.selector = el.name ? `input[type="radio"][name="${el.name.replace(/"/g, '\\"')}"]` : field.selector;
+                const legend = el.closest('fieldset')?.querySelector('legend');
+                field.label = legend ? textOf(legend) : el.name || null;
+                field.options = [option];
+                field.value = el.checked ? el.value : null;
+                radioGroups.set(key, field);
+            } else if (type === 'checkbox') {
+                field.value = el.value;
+                field.checked = el.checked;
+            } else if (el.tagName === 'SELECT') {
+                field.multiple = el.multiple;
+                field.options = Array.from(el.options).map(option => ({
+                    value: option.value,
+                    label: textOf(option),
+                    selected: option.selected,
+                }));
+                field.value = el.multiple
+                    ? Array.from(el.selectedOptions).map(option => option.value)
+                    : el.value;
+            } else if (type === 'password') {
+                field.value = el.value ? '[redacted]' : '';
+            } else if (type === 'file') {
+                field.value = Array.from(el.files || []).map(file => file.name);
+            } else {
+                field.value = el.value;
+            }
+            fields.push(field);
+        }
+        return fields;
+    };
+    const describeSubmits = (controls, alsoSubmits = () => false) => controls.filter(el => isSubmit(el) || alsoSubmits(el)).map(el => ({
+        selector: selectorFor(el),
+        text: textOf(el) || el.value || el.getAttribute('aria-label') || el.alt || null,
+        type: el.tagName === 'BUTTON' ? 'button' : el.type,
+        disabled: el.disabled,
+    }));
+
+    try {
+        let formElements;
+        if (formSelector) {
+            const form = document.querySelector(formSelector);
+            if (!form) return { error: `no form matches '${formSelector}'` };
+            if (form.tagName !== 'FORM') return { error: `'${formSelector}' is a <${form.tagName.toLowerCase()}>, not a <form>` };
+            formElements = [form];
+        } else {
+            formElements = Array.from(document.forms);
+        }
+
+        const forms = formElements.map((form, index) => {
+            const controls = Array.from(form.elements);
+            return {
+                index,
+                selector: selectorFor(form),
+                id: form.id || null,
+                name: form.getAttribute('name'),
+                action: form.action || null,
+                method: (form.method || 'get').toUpperCase(),
+                visible: visible(form),
+                fields: describeFields(controls),
+                submit: describeSubmits(controls),
+            };
+        });
+
+        if (!formSelector) {
+            const standalone = Array.from(document.querySelectorAll('input, select, textarea, button'))
+                .filter(el => !el.form);
+            const fields = describeFields(standalone);
+            if (fields.length) {
+                forms.push({
+                    index: forms.length,
+                    selector: null,
+                    standalone: true,
+                    visible: fields.some(field => field.visible),
+                    fields,
+                    // Script-driven forms often submit through plain type="button" buttons
+                    submit: describeSubmits(standalone, el => el.tagName === 'BUTTON' && el.type === 'button'),
+                });
+            }
+        }
+
+        return { url: location.href, forms };
+    } catch (e) {
+        return { error: String(e) };
+    }
+"#);
+
+/// Why an element interaction failed once retries ran out
+enum InteractionError {
+    /// The element could not be found
+    Find(WebDriverError),
+    /// The element was found but the action on it failed
+    Action(WebDriverError),
+    /// The element did not become visible, enabled and stationary in time
+    Unstable(String),
+}
+
+impl InteractionError {
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Find(e) => is_transient_error(&e.to_string(), false),
+            Self::Action(e) => is_transient_error(&e.to_string(), true),
+            Self::Unstable(reason) => is_transient_error(reason, false),
+        }
+    }
+}
+
+/// Whether a failure is worth another attempt, given whether the action was sent
+fn is_transient_error(message: &str, action_sent: bool) -> bool {
+    let message = message.to_lowercase();
+    let matches = |errors: &[&str]| errors.iter().any(|error| message.contains(error));
+    matches(TRANSIENT_ERRORS) || (!action_sent && matches(CONNECTION_ERRORS))
+}
+
+/// Find `selector` and run `action` on it as the driver command `operation`,
+/// allowed `timeout`, first waiting for the element to hold still when
+/// `wait_for_stable` is set. Transient failures re-find the element and try
+/// again, up to `element_retry_attempts` times with a growing delay.
+#[allow(clippy::too_many_arguments)]
+async fn interact_with_element<T, F, Fut>(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    selector: &str,
+    wait_timeout: Option<f64>,
+    wait_for_stable: bool,
+    operation: &str,
+    timeout: Duration,
+    action: F,
+) -> Result<T, InteractionError>
+where
+    F: Fn(Element) -> Fut,
+    Fut: Future<Output = Result<T, CmdError>>,
+{
+    let act = |element: Element| async {
+        client_manager
+            .run_command_within(session, operation, timeout, action(element))
+            .await
+            .map_err(InteractionError::Action)
+    };
+    let config = client_manager.get_config();
+    let stability_timeout = Duration::from_millis(config.element_stability_timeout_ms);
+    let mut attempt = 1;
+    loop {
+        let outcome = match client_manager.find_element_with_wait(client, selector, wait_timeout).await {
+            Ok(element) if wait_for_stable => {
+                match stability::wait_until_stable(client_manager, session, client, &element, stability_timeout).await {
+                    Ok(None) => act(element).await,
+                    Ok(Some(reason)) => Err(InteractionError::Unstable(format!(
+                        "waited {}ms, but {reason}",
+                        stability_timeout.as_millis()
+                    ))),
+                    Err(e) => Err(InteractionError::Unstable(e.to_string())),
+                }
+            }
+            Ok(element) => act(element).await,
+            Err(e) => Err(InteractionError::Find(e)),
+        };
+        match outcome {
+            Err(e) if attempt < config.element_retry_attempts && e.is_transient() => {
+                let delay = Duration::from_millis(config.element_retry_backoff_ms * u64::from(attempt));
+                tracing::debug!("Retrying interaction with '{}' in {:?} (attempt {})", selector, delay, attempt + 1);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            outcome => return outcome,
+        }
+    }
+}
+
+/// First match of `selector`, found under the call timeout without waiting for it
+async fn find_element(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    selector: &str,
+) -> Result<Element, WebDriverError> {
+    client_manager.run_command(session, "find_element", client.find(Locator::Css(selector))).await
+}
+
+/// Whether to wait for the target to hold still: the call's `wait_for_stable`,
+/// else the server's `wait_for_stable_elements`
+fn extract_wait_for_stable(client_manager: &ClientManager, arguments: &Option<Map<String, Value>>) -> bool {
+    arguments
+        .as_ref()
+        .and_then(|args| args.get("wait_for_stable"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(client_manager.get_config().wait_for_stable_elements)
+}
 
 /// Click an element by CSS selector
 pub async fn handle_click(
@@ -29,31 +513,125 @@ pub async fn handle_click(
         .ok_or_else(|| McpError::invalid_params("selector parameter required", None))?;
 
     let wait_timeout = extract_wait_timeout(arguments);
+    let wait_for_stable = extract_wait_for_stable(client_manager, arguments);
+    let scroll_on_intercept = arguments
+        .as_ref()
+        .and_then(|args| args.get("scroll_on_intercept"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
     let session_id = extract_session_id(arguments);
 
     match client_manager.get_or_create_client(session_id).await {
         Ok((session, client)) => {
-            match client_manager
-                .find_element_with_wait(&client, selector, wait_timeout)
-                .await
+            let click = |element: Element| async move { element.click().await };
+            let timeout = client_manager.call_timeout();
+            let clicking = interact_with_element(
+                client_manager, &session, &client, selector, wait_timeout, wait_for_stable, "click", timeout, click,
+            );
+            let mut outcome = clicking.await;
+            let mut scrolled_clear_of = None;
+            if scroll_on_intercept
+                && let Err(InteractionError::Action(e)) = &outcome
+                && click_intercept::is_click_intercepted(&e.to_string())
             {
-                Ok(element) => match element.click().await {
-                    Ok(_) => Ok(success_response(format!(
-                        "Successfully clicked element {selector} (session: {session})"
-                    ))),
-                    Err(e) => Ok(error_response(format!("Failed to click element: {e}"))),
-                },
-                Err(e) => Ok(error_response(format!(
-                    "Failed to find element {selector}: {e}"
-                ))),
+                (outcome, scrolled_clear_of) = scroll_and_click(client_manager, &session, &client, selector).await;
+            }
+            match outcome {
+                Ok(()) => {
+                    let scrolled = scrolled_clear_of
+                        .map(|interception| format!(" after scrolling it clear of {}", interception.describe()))
+                        .unwrap_or_default();
+                    let mut message = format!("Successfully clicked element {selector}{scrolled} (session: {session})");
+                    match popups::apply_policy(client_manager, &session, &client).await {
+                        Ok(events) if !events.is_empty() => {
+                            message.push_str(&format!(". {}", popups::summarize(&events)));
+                        }
+                        Ok(_) => {}
+                        Err(e) => tracing::debug!("Failed to check popups in session '{}': {}", session, e),
+                    }
+                    Ok(success_response(message))
+                }
+                Err(InteractionError::Action(e)) if click_intercept::is_click_intercepted(&e.to_string()) => {
+                    let response = intercepted_click_response(
+                        client_manager, &session, &client, selector, scroll_on_intercept, e,
+                    );
+                    Ok(response.await)
+                }
+                Err(InteractionError::Action(e)) => Ok(error_response(format!("Failed to click element: {e}"))),
+                Err(InteractionError::Unstable(reason)) => Ok(error_response_with(
+                    format!("Did not click element {selector}: {reason}"),
+                    json!({ "selector": selector, "session_id": session }),
+                )),
+                Err(InteractionError::Find(e)) => Ok(error_response_with(
+                    format!("Failed to find element {selector}: {e}"),
+                    json!({ "selector": selector, "session_id": session }),
+                )),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
+/// Centre `selector` in the viewport, out from under sticky headers and
+/// footers, and click it once more. Returns what covered it before.
+async fn scroll_and_click(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    selector: &str,
+) -> (Result<(), InteractionError>, Option<Interception>) {
+    let element = match find_element(client_manager, session, client, selector).await {
+        Ok(element) => element,
+        Err(e) => return (Err(InteractionError::Action(e)), None),
+    };
+    let interception = click_intercept::diagnose(client_manager, session, client, &element).await.ok().flatten();
+    if let Err(e) = click_intercept::scroll_clear(client_manager, session, client, &element).await {
+        tracing::debug!("Failed to scroll '{}' clear in session '{}': {}", selector, session, e);
+    }
+    let clicked = client_manager.run_command(session, "click", element.click()).await;
+    (clicked.map_err(InteractionError::Action), interception)
+}
+
+/// CLICK_INTERCEPTED error naming what covers `selector`, as far as the page
+/// can still tell, and what to try next
+async fn intercepted_click_response(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    selector: &str,
+    tried_scrolling: bool,
+    error: WebDriverError,
+) -> CallToolResult {
+    let interception = match find_element(client_manager, session, client, selector).await {
+        Ok(element) => click_intercept::diagnose(client_manager, session, client, &element).await.ok().flatten(),
+        Err(_) => None,
+    };
+    let Some(interception) = interception else {
+        return coded_error_response(
+            ErrorCode::ClickIntercepted,
+            format!("Failed to click element {selector}: another element would receive the click: {error}"),
+            json!({ "selector": selector, "session_id": session }),
+        );
+    };
+    let hint = if interception.scrolling_may_help() && !tried_scrolling {
+        "; retry with scroll_on_intercept: true to scroll the element clear of it"
+    } else if interception.kind == "overlay" {
+        "; close it, e.g. with dismiss_overlays, and retry"
+    } else {
+        "; click what covers it first or wait for it to go away"
+    };
+    coded_error_response(
+        ErrorCode::ClickIntercepted,
+        format!("Did not click element {selector}: the click would land on {}{hint}", interception.describe()),
+        json!({
+            "selector": selector,
+            "session_id": session,
+            "intercepted_by": interception,
+            "scrolled": tried_scrolling,
+        }),
+    )
+}
+
 /// Send keys to an element by CSS selector
 pub async fn handle_send_keys(
     client_manager: &ClientManager,
@@ -71,29 +649,56 @@ pub async fn handle_send_keys(
         .and_then(|v| v.as_str())
         .ok_or_else(|| McpError::invalid_params("text parameter required", None))?;
 
+    let clear_first = arguments
+        .as_ref()
+        .and_then(|args| args.get("clear_first"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let typing = arguments
+        .as_ref()
+        .map(TypingOptions::from_arguments)
+        .unwrap_or_default();
+
     let wait_timeout = extract_wait_timeout(arguments);
+    let wait_for_stable = extract_wait_for_stable(client_manager, arguments);
     let session_id = extract_session_id(arguments);
 
     match client_manager.get_or_create_client(session_id).await {
         Ok((session, client)) => {
-            match client_manager
-                .find_element_with_wait(&client, selector, wait_timeout)
-                .await
-            {
-                Ok(element) => match element.send_keys(text).await {
-                    Ok(_) => Ok(success_response(format!(
-                        "Successfully sent keys to element {selector} (session: {session})"
-                    ))),
-                    Err(e) => Ok(error_response(format!("Failed to send keys: {e}"))),
-                },
-                Err(e) => Ok(error_response(format!(
-                    "Failed to find element {selector}: {e}"
+            let type_text = |element: Element| async move {
+                if clear_first {
+                    element.clear().await?;
+                }
+                typing::type_into(&element, text, typing).await
+            };
+            // Slow typing is one command per key, with pauses in between
+            let timeout = client_manager.call_timeout() * 2 + typing.max_pauses(text);
+            let typing_text = interact_with_element(
+                client_manager, &session, &client, selector, wait_timeout, wait_for_stable, "send_keys", timeout,
+                type_text,
+            );
+            match typing_text.await {
+                Ok(()) if typing.is_instant() => Ok(success_response(format!(
+                    "Successfully sent keys to element {selector} (session: {session})"
                 ))),
+                Ok(()) => Ok(success_response(format!(
+                    "Successfully typed {} character(s) into element {selector} at ~{}ms per key (session: {session})",
+                    text.chars().count(),
+                    typing.delay_ms
+                ))),
+                Err(InteractionError::Action(e)) => Ok(error_response(format!("Failed to send keys: {e}"))),
+                Err(InteractionError::Unstable(reason)) => Ok(error_response_with(
+                    format!("Did not type into element {selector}: {reason}"),
+                    json!({ "selector": selector, "session_id": session }),
+                )),
+                Err(InteractionError::Find(e)) => Ok(error_response_with(
+                    format!("Failed to find element {selector}: {e}"),
+                    json!({ "selector": selector, "session_id": session }),
+                )),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -101,6 +706,7 @@ pub async fn handle_send_keys(
 pub async fn handle_wait_for_element(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
 ) -> Result<CallToolResult, McpError> {
     let selector = arguments
         .as_ref()
@@ -112,34 +718,116 @@ pub async fn handle_wait_for_element(
         .as_ref()
         .and_then(|args| args.get("timeout_seconds"))
         .and_then(|v| v.as_f64())
-        .unwrap_or(10.0);
+        .unwrap_or(client_manager.get_config().default_wait_timeout_secs);
 
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
-            match client_manager
-                .find_element_with_wait(&client, selector, Some(timeout_seconds))
-                .await
-            {
-                Ok(_element) => Ok(success_response(format!(
+            let wait = client_manager.find_element_with_wait(&client, selector, Some(timeout_seconds));
+            match cancellation.run_until_cancelled(wait).await {
+                Some(Ok(_element)) => Ok(success_response(format!(
                     "Element '{selector}' found within {timeout_seconds:.1}s (session: {session})"
                 ))),
-                Err(e) => Ok(error_response(format!(
-                    "Element '{selector}' not found within {timeout_seconds:.1}s: {e}"
-                ))),
+                Some(Err(e)) => Ok(coded_error_response(
+                    ErrorCode::Timeout,
+                    format!("Element '{selector}' not found within {timeout_seconds:.1}s: {e}"),
+                    json!({ "selector": selector, "session_id": session, "timeout_seconds": timeout_seconds }),
+                )),
+                None => Ok(cancelled_response(&format!("Waiting for element '{selector}'"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Wait until no DOM mutation happened for `quiet_ms`, under `selector` or in
+/// the whole document. A navigation during the wait starts the quiet period over.
+pub async fn handle_wait_for_dom_stable(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    let WaitForDomStableParams { quiet_ms, timeout_seconds, selector, session } = parse_params(arguments)?;
+    let timeout_seconds = timeout_seconds.unwrap_or(client_manager.get_config().default_wait_timeout_secs);
+
+    let (session, client) = match client_manager.get_existing_client(session.session_id).await {
+        Ok(found) => found,
+        Err(e) => return Ok(session_error_response(e)),
+    };
+    let observe = || client.execute(OBSERVE_MUTATIONS_SCRIPT, vec![json!(selector)]);
+    let context = json!({ "selector": selector, "session_id": session, "quiet_ms": quiet_ms });
+    match client_manager.run_command(&session, "wait_for_dom_stable", observe()).await {
+        Ok(Value::Bool(true)) => {}
+        Ok(_) => {
+            let selector = selector.as_deref().unwrap_or_default();
+            return Ok(error_response_with(format!("Failed to find element {selector}: no such element"), context));
+        }
+        Err(e) => return Ok(error_response(format!("Failed to observe DOM mutations: {e}"))),
+    }
+
+    let start = std::time::Instant::now();
+    let deadline = crate::waits::clamped_wait(timeout_seconds);
+    let mut mutations = 0;
+    let outcome = loop {
+        let idle = client.execute(MUTATION_IDLE_SCRIPT, vec![]);
+        let poll = client_manager.run_command(&session, "wait_for_dom_stable", idle);
+        let state = match cancellation.run_until_cancelled(poll).await {
+            Some(Ok(state)) => state,
+            Some(Err(e)) => break Err(error_response(format!("Failed to check DOM mutations: {e}"))),
+            None => break Err(cancelled_response("Waiting for the DOM to settle")),
+        };
+        if state.is_null() {
+            // A new document has no observer yet; its quiet period starts now
+            if let Err(e) = client_manager.run_command(&session, "wait_for_dom_stable", observe()).await {
+                break Err(error_response(format!("Failed to observe DOM mutations: {e}")));
+            }
+        } else {
+            mutations = state["mutations"].as_u64().unwrap_or(mutations);
+            if state["idle_ms"].as_f64().unwrap_or_default() >= quiet_ms as f64 {
+                break Ok(());
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        if start.elapsed() >= deadline {
+            break Err(coded_error_response(
+                ErrorCode::Timeout,
+                format!(
+                    "DOM did not stay unchanged for {quiet_ms}ms within {timeout_seconds:.1}s; {mutations} mutation(s) \
+                     seen (session: {session})"
+                ),
+                json!({ "selector": selector, "session_id": session, "quiet_ms": quiet_ms, "mutations": mutations }),
+            ));
+        }
+        if cancellation.run_until_cancelled(tokio::time::sleep(DOM_STABLE_POLL_INTERVAL)).await.is_none() {
+            break Err(cancelled_response("Waiting for the DOM to settle"));
+        }
+    };
+
+    let stop = client.execute(STOP_OBSERVING_SCRIPT, vec![]);
+    if let Err(e) = client_manager.run_command(&session, "wait_for_dom_stable", stop).await {
+        tracing::debug!("Failed to stop observing DOM mutations in session '{}': {}", session, e);
+    }
+    if let Err(response) = outcome {
+        return Ok(response);
     }
+    let elapsed = start.elapsed().as_secs_f64();
+    Ok(structured_response(
+        format!("DOM stable for {quiet_ms}ms after {elapsed:.1}s, {mutations} mutation(s) seen (session: {session})"),
+        json!({
+            "session": session,
+            "selector": selector,
+            "quiet_ms": quiet_ms,
+            "elapsed_ms": (elapsed * 1000.0).round() as u64,
+            "mutations": mutations,
+        }),
+    ))
 }
 
 /// Wait for a JavaScript condition to become true
 pub async fn handle_wait_for_condition(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
 ) -> Result<CallToolResult, McpError> {
     let condition = arguments
         .as_ref()
@@ -151,7 +839,9 @@ pub async fn handle_wait_for_condition(
         .as_ref()
         .and_then(|args| args.get("timeout_seconds"))
         .and_then(|v| v.as_f64())
-        .unwrap_or(10.0);
+        .unwrap_or(client_manager.get_config().default_wait_timeout_secs);
+    let timeout_duration = crate::waits::wait_duration("timeout_seconds", timeout_seconds)
+        .map_err(|e| McpError::invalid_params(e, None))?;
 
     let check_interval_ms = arguments
         .as_ref()
@@ -161,15 +851,21 @@ pub async fn handle_wait_for_condition(
 
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
             let start_time = std::time::Instant::now();
-            let timeout_duration = std::time::Duration::from_secs_f64(timeout_seconds);
             let check_interval = std::time::Duration::from_millis(check_interval_ms);
 
             loop {
                 // Check if condition is true
-                match client.execute(condition, vec![]).await {
+                let evaluate = client.execute(condition, vec![]);
+                let evaluate = client_manager.run_command(&session, "wait_for_condition", evaluate);
+                let evaluation = match cancellation.run_until_cancelled(evaluate).await {
+                    Some(evaluation) => evaluation,
+                    None => return Ok(cancelled_response(&format!("Waiting for condition '{condition}'"))),
+                };
+
+                match evaluation {
                     Ok(result) => {
                         // Check if result is truthy
                         let is_true = match result {
@@ -193,28 +889,193 @@ pub async fn handle_wait_for_condition(
                     }
                     Err(e) => {
                         // JavaScript error - condition might be malformed
-                        return Ok(error_response(format!(
-                            "Error evaluating condition '{}': {}",
-                            condition, e
-                        )));
+                        return Ok(coded_error_response(
+                            ErrorCode::ScriptError,
+                            format!("Error evaluating condition '{}': {}", condition, e),
+                            json!({ "condition": condition, "session_id": session }),
+                        ));
                     }
                 }
 
                 // Check timeout
                 if start_time.elapsed() >= timeout_duration {
-                    return Ok(error_response(format!(
-                        "Condition '{}' did not become true within {:.1}s (session: {})",
-                        condition, timeout_seconds, session
-                    )));
+                    return Ok(coded_error_response(
+                        ErrorCode::Timeout,
+                        format!(
+                            "Condition '{}' did not become true within {:.1}s (session: {})",
+                            condition, timeout_seconds, session
+                        ),
+                        json!({ "condition": condition, "session_id": session, "timeout_seconds": timeout_seconds }),
+                    ));
                 }
 
-                // Wait before next check
-                tokio::time::sleep(check_interval).await;
+                // Wait before next check, bailing out if the client cancelled the request
+                if cancellation.run_until_cancelled(tokio::time::sleep(check_interval)).await.is_none() {
+                    return Ok(cancelled_response(&format!("Waiting for condition '{condition}'")));
+                }
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// What `wait_for_attribute` and `wait_for_property` wait for
+struct ValueWait {
+    selector: String,
+    /// "attribute" or "property"
+    kind: &'static str,
+    name: String,
+    expected: Option<Value>,
+    pattern: Option<String>,
+    absent: bool,
+    timeout_seconds: f64,
+    check_interval: Duration,
+}
+
+impl ValueWait {
+    fn subject(&self) -> String {
+        let kind = if self.kind == "attribute" { "Attribute" } else { "Property" };
+        format!("{kind} '{}' of '{}'", self.name, self.selector)
+    }
+
+    fn expectation(&self) -> String {
+        match (&self.pattern, &self.expected) {
+            (Some(pattern), _) => format!("match /{pattern}/"),
+            (None, Some(value)) => format!("become {value}"),
+            (None, None) if self.absent => "disappear".to_string(),
+            (None, None) if self.kind == "attribute" => "appear".to_string(),
+            (None, None) => "become truthy".to_string(),
+        }
+    }
+}
+
+/// Wait until an element attribute has a value, matches a pattern, appears or is removed
+pub async fn handle_wait_for_attribute(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    let params: WaitForAttributeParams = parse_params(arguments)?;
+    if [params.value.is_some(), params.pattern.is_some(), params.absent].iter().filter(|set| **set).count() > 1 {
+        return Err(McpError::invalid_params("Pass at most one of value, pattern and absent", None));
+    }
+    let wait = ValueWait {
+        selector: params.selector,
+        kind: "attribute",
+        name: params.attribute,
+        expected: params.value.map(Value::String),
+        pattern: params.pattern,
+        absent: params.absent,
+        timeout_seconds: params.timeout_seconds.unwrap_or(client_manager.get_config().default_wait_timeout_secs),
+        check_interval: Duration::from_millis(params.check_interval_ms.unwrap_or(100)),
+    };
+    wait_for_element_value(client_manager, params.session.session_id, wait, cancellation).await
+}
+
+/// Wait until a DOM property of an element has a value, matches a pattern or is truthy
+pub async fn handle_wait_for_property(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    let params: WaitForPropertyParams = parse_params(arguments)?;
+    if params.value.is_some() && params.pattern.is_some() {
+        return Err(McpError::invalid_params("Pass either value or pattern, not both", None));
+    }
+    let wait = ValueWait {
+        selector: params.selector,
+        kind: "property",
+        name: params.property,
+        expected: params.value,
+        pattern: params.pattern,
+        absent: false,
+        timeout_seconds: params.timeout_seconds.unwrap_or(client_manager.get_config().default_wait_timeout_secs),
+        check_interval: Duration::from_millis(params.check_interval_ms.unwrap_or(100)),
+    };
+    wait_for_element_value(client_manager, params.session.session_id, wait, cancellation).await
+}
+
+/// Poll the element's attribute or property until it meets `wait` or the wait times out
+async fn wait_for_element_value(
+    client_manager: &ClientManager,
+    session_id: Option<String>,
+    wait: ValueWait,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    let (session, client) = match client_manager.get_existing_client(session_id).await {
+        Ok(found) => found,
+        Err(e) => return Ok(session_error_response(e)),
+    };
+    let subject = wait.subject();
+    let operation = format!("wait_for_{}", wait.kind);
+    let script_args = vec![
+        json!(wait.selector),
+        json!(wait.kind),
+        json!(wait.name),
+        json!(wait.expected),
+        json!(wait.pattern),
+        json!(wait.absent),
+    ];
+    let mut context = json!({
+        "selector": wait.selector,
+        "session_id": session,
+        wait.kind: wait.name,
+        "expected": wait.expected,
+        "pattern": wait.pattern,
+        "timeout_seconds": wait.timeout_seconds,
+    });
+
+    let start = std::time::Instant::now();
+    let timeout = crate::waits::clamped_wait(wait.timeout_seconds);
+    loop {
+        let read = client.execute(ELEMENT_VALUE_SCRIPT, script_args.clone());
+        let check = client_manager.run_command(&session, &operation, read);
+        let state = match cancellation.run_until_cancelled(check).await {
+            Some(Ok(state)) => state,
+            Some(Err(e)) => return Ok(error_response_with(format!("Failed to read {subject}: {e}"), context)),
+            None => return Ok(cancelled_response(&format!("Waiting for {subject}"))),
+        };
+        if let Some(error) = state["error"].as_str() {
+            let message = format!("Cannot wait for {subject}: {error}");
+            return Ok(coded_error_response(ErrorCode::InvalidArgument, message, context));
+        }
+
+        if state["matched"].as_bool() == Some(true) {
+            let elapsed = start.elapsed().as_secs_f64();
+            let outcome = if wait.absent { "was removed".to_string() } else { format!("is {}", state["value"]) };
+            return Ok(structured_response(
+                format!("{subject} {outcome} after {elapsed:.1}s (session: {session})"),
+                json!({
+                    "session": session,
+                    "selector": wait.selector,
+                    wait.kind: wait.name,
+                    "value": state["value"],
+                    "elapsed_ms": (elapsed * 1000.0).round() as u64,
+                }),
+            ));
+        }
+
+        if start.elapsed() >= timeout {
+            let last_seen = if state["found"].as_bool() == Some(true) {
+                format!("last value: {}", state["value"])
+            } else {
+                "no element matches the selector".to_string()
+            };
+            context["found"] = state["found"].clone();
+            context["last_value"] = state["value"].clone();
+            return Ok(coded_error_response(
+                ErrorCode::Timeout,
+                format!(
+                    "{subject} did not {} within {:.1}s; {last_seen} (session: {session})",
+                    wait.expectation(),
+                    wait.timeout_seconds
+                ),
+                context,
+            ));
+        }
+        if cancellation.run_until_cancelled(tokio::time::sleep(wait.check_interval)).await.is_none() {
+            return Ok(cancelled_response(&format!("Waiting for {subject}")));
+        }
     }
 }
 
@@ -243,7 +1104,7 @@ pub async fn handle_get_element_info(
 
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
             let _element = if wait_timeout > 0.0 {
                 match client_manager
@@ -258,7 +1119,7 @@ pub async fn handle_get_element_info(
                     }
                 }
             } else {
-                match client.find(Locator::Css(selector)).await {
+                match find_element(client_manager, &session, &client, selector).await {
                     Ok(element) => element,
                     Err(e) => {
                         return Ok(error_response(format!(
@@ -359,7 +1220,7 @@ pub async fn handle_get_element_info(
                 }
             );
 
-            match client.execute(&info_script, vec![]).await {
+            match client_manager.run_command(&session, "get_element_info", client.execute(&info_script, vec![])).await {
                 Ok(result) => {
                     if let Ok(info) = serde_json::from_value::<serde_json::Map<String, serde_json::Value>>(result.clone()) {
                         if let Some(error) = info.get("error") {
@@ -367,10 +1228,13 @@ pub async fn handle_get_element_info(
                         } else {
                             let formatted_info = serde_json::to_string_pretty(&info)
                                 .unwrap_or_else(|_| format!("{:?}", info));
-                            Ok(success_response(format!(
-                                "Element info for '{}' (session: {}):\n{}",
-                                selector, session, formatted_info
-                            )))
+                            Ok(structured_response(
+                                format!(
+                                    "Element info for '{}' (session: {}):\n{}",
+                                    selector, session, formatted_info
+                                ),
+                                json!({ "session": session, "selector": selector, "info": info }),
+                            ))
                         }
                     } else {
                         Ok(error_response(format!("Failed to parse element info: {:?}", result)))
@@ -379,13 +1243,196 @@ pub async fn handle_get_element_info(
                 Err(e) => Ok(error_response(format!("Failed to get element info: {e}"))),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
 /// Get an element's HTML attribute
+/// Get computed CSS properties of an element as structured data
+pub async fn handle_get_computed_style(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let selector = arguments
+        .as_ref()
+        .and_then(|args| args.get("selector"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| McpError::invalid_params("selector parameter required", None))?;
+
+    let properties: Vec<String> = arguments
+        .as_ref()
+        .and_then(|args| args.get("properties"))
+        .and_then(|v| v.as_array())
+        .map(|properties| properties.iter().filter_map(|p| p.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+
+    let pseudo_element = arguments
+        .as_ref()
+        .and_then(|args| args.get("pseudo_element"))
+        .and_then(|v| v.as_str());
+
+    let wait_timeout = extract_wait_timeout(arguments);
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            if let Some(timeout) = wait_timeout.filter(|timeout| *timeout > 0.0)
+                && let Err(e) = client_manager.find_element_with_wait(&client, selector, Some(timeout)).await
+            {
+                return Ok(error_response(format!(
+                    "Element '{selector}' not found within {timeout:.1}s: {e}"
+                )));
+            }
+
+            let result = match client_manager
+                .run_command(
+                    &session,
+                    "get_computed_style",
+                    client.execute(
+                        COMPUTED_STYLE_SCRIPT,
+                        vec![json!(selector), json!(properties), json!(pseudo_element)],
+                    ),
+                )
+                .await
+            {
+                Ok(result) => result,
+                Err(e) => return Ok(error_response(format!("Failed to get computed style of '{selector}': {e}"))),
+            };
+            if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+                return Ok(error_response(format!("Element '{selector}' not found: {error}")));
+            }
+
+            let styles = result.get("styles").and_then(|v| v.as_object()).cloned().unwrap_or_default();
+            let unknown = result.get("unknown").cloned().unwrap_or_else(|| json!([]));
+            let target = match pseudo_element {
+                Some(pseudo) => format!("{selector}{pseudo}"),
+                None => selector.to_string(),
+            };
+
+            let mut message = format!("Computed style of '{}' ({} properties, session: {}):", target, styles.len(), session);
+            for (property, value) in &styles {
+                message.push_str(&format!("\n{}: {}", property, value.as_str().unwrap_or_default()));
+            }
+            if let Some(unknown) = unknown.as_array().filter(|unknown| !unknown.is_empty()) {
+                let names: Vec<&str> = unknown.iter().filter_map(|v| v.as_str()).collect();
+                message.push_str(&format!("\nUnknown properties: {}", names.join(", ")));
+            }
+
+            Ok(structured_response(
+                message,
+                json!({
+                    "session": session,
+                    "selector": selector,
+                    "pseudo_element": pseudo_element,
+                    "tag_name": result.get("tag_name"),
+                    "styles": styles,
+                    "unknown_properties": unknown,
+                }),
+            ))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Bounding boxes and visibility of every match of a selector
+pub async fn handle_get_element_rects(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let selector = arguments
+        .as_ref()
+        .and_then(|args| args.get("selector"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| McpError::invalid_params("selector parameter required", None))?;
+
+    let limit = arguments
+        .as_ref()
+        .and_then(|args| args.get("limit"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_ELEMENT_RECTS_LIMIT);
+
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            let result = match client_manager
+                .run_command(
+                    &session,
+                    "get_element_rects",
+                    client.execute(ELEMENT_RECTS_SCRIPT, vec![json!(selector), json!(limit)]),
+                )
+                .await
+            {
+                Ok(result) => result,
+                Err(e) => return Ok(error_response(format!("Failed to measure elements '{selector}': {e}"))),
+            };
+            if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+                return Ok(coded_error_response(
+                    ErrorCode::InvalidArgument,
+                    format!("Invalid selector '{selector}': {error}"),
+                    json!({ "selector": selector }),
+                ));
+            }
+
+            let total = result.get("total").and_then(|v| v.as_u64()).unwrap_or(0);
+            let elements = result.get("elements").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+            let overlaps = result.get("overlaps").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+            let number = |element: &Value, key: &str| element.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+
+            let mut message = format!("{} element(s) match '{}' (session: {}):\n", total, selector, session);
+            for element in &elements {
+                let hit = match element.get("hit_target").and_then(|v| v.as_bool()) {
+                    Some(false) => ", covered by another element",
+                    _ => "",
+                };
+                message.push_str(&format!(
+                    "\n{}. <{}> x={} y={} {}x{} {}, {} in viewport{} \"{}\"",
+                    number(element, "index") as u64 + 1,
+                    element.get("tag_name").and_then(|v| v.as_str()).unwrap_or("unknown"),
+                    number(element, "x"),
+                    number(element, "y"),
+                    number(element, "width"),
+                    number(element, "height"),
+                    if element.get("visible").and_then(|v| v.as_bool()).unwrap_or(false) { "visible" } else { "hidden" },
+                    element.get("in_viewport").and_then(|v| v.as_str()).unwrap_or("none"),
+                    hit,
+                    text::preview(element.get("text").and_then(|v| v.as_str()).unwrap_or_default(), 60)
+                ));
+            }
+            for overlap in &overlaps {
+                message.push_str(&format!(
+                    "\nOverlap: {} and {} ({} px²)",
+                    number(overlap, "a") as u64 + 1,
+                    number(overlap, "b") as u64 + 1,
+                    number(overlap, "area")
+                ));
+            }
+            let truncated = (elements.len() as u64) < total;
+            if truncated {
+                message.push_str(&format!(
+                    "\n... measured the first {} of {}; raise `limit` to see more",
+                    elements.len(),
+                    total
+                ));
+            }
+
+            Ok(structured_response(
+                message,
+                json!({
+                    "session": session,
+                    "selector": selector,
+                    "total": total,
+                    "truncated": truncated,
+                    "viewport": result.get("viewport"),
+                    "elements": elements,
+                    "overlaps": overlaps,
+                }),
+            ))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
 pub async fn handle_get_element_attribute(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
@@ -405,33 +1452,42 @@ pub async fn handle_get_element_attribute(
     let wait_timeout = extract_wait_timeout(arguments);
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
             match client_manager
                 .find_element_with_wait(&client, selector, wait_timeout)
                 .await
             {
-                Ok(element) => match element.attr(attribute).await {
+                Ok(element) => match client_manager
+                    .run_command(&session, "get_attribute", element.attr(attribute))
+                    .await
+                {
                     Ok(attr_value) => {
+                        let data = json!({
+                            "session": session,
+                            "selector": selector,
+                            "attribute": attribute,
+                            "value": attr_value,
+                        });
                         let value_text = attr_value.unwrap_or_else(|| {
                             format!("[attribute '{attribute}' not found or empty]")
                         });
-                        Ok(success_response(format!(
-                            "Element '{selector}' attribute '{attribute}': {value_text} (session: {session})"
-                        )))
+                        Ok(structured_response(
+                            format!("Element '{selector}' attribute '{attribute}': {value_text} (session: {session})"),
+                            data,
+                        ))
                     }
                     Err(e) => Ok(error_response(format!(
                         "Failed to get attribute '{attribute}' from element '{selector}': {e}"
                     ))),
                 },
-                Err(e) => Ok(error_response(format!(
-                    "Failed to find element '{selector}': {e}"
-                ))),
+                Err(e) => Ok(error_response_with(
+                    format!("Failed to find element '{selector}': {e}"),
+                    json!({ "selector": selector, "session_id": session }),
+                )),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -455,34 +1511,43 @@ pub async fn handle_get_element_property(
     let wait_timeout = extract_wait_timeout(arguments);
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
             match client_manager
                 .find_element_with_wait(&client, selector, wait_timeout)
                 .await
             {
-                Ok(element) => match element.prop(property).await {
+                Ok(element) => match client_manager
+                    .run_command(&session, "get_property", element.prop(property))
+                    .await
+                {
                     Ok(prop_value) => {
+                        let data = json!({
+                            "session": session,
+                            "selector": selector,
+                            "property": property,
+                            "value": prop_value,
+                        });
                         let value_text = match prop_value {
                             Some(s) => s,
                             None => "[null/undefined]".to_string(),
                         };
-                        Ok(success_response(format!(
-                            "Element '{selector}' property '{property}': {value_text} (session: {session})"
-                        )))
+                        Ok(structured_response(
+                            format!("Element '{selector}' property '{property}': {value_text} (session: {session})"),
+                            data,
+                        ))
                     }
                     Err(e) => Ok(error_response(format!(
                         "Failed to get property '{property}' from element '{selector}': {e}"
                     ))),
                 },
-                Err(e) => Ok(error_response(format!(
-                    "Failed to find element '{selector}': {e}"
-                ))),
+                Err(e) => Ok(error_response_with(
+                    format!("Failed to find element '{selector}': {e}"),
+                    json!({ "selector": selector, "session_id": session }),
+                )),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -510,7 +1575,7 @@ pub async fn handle_find_element(
 
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
             // If parent_selector is provided, find within parent
             let search_result = if let Some(parent_sel) = parent_selector {
@@ -529,7 +1594,7 @@ pub async fn handle_find_element(
                         }
                     }
                 } else {
-                    match client.find(Locator::Css(parent_sel)).await {
+                    match find_element(client_manager, &session, &client, parent_sel).await {
                         Ok(element) => element,
                         Err(e) => {
                             return Ok(error_response(format!(
@@ -541,7 +1606,9 @@ pub async fn handle_find_element(
                 };
 
                 // Then find child element within parent
-                parent_element.find(Locator::Css(selector)).await
+                client_manager
+                    .run_command(&session, "find_element", parent_element.find(Locator::Css(selector)))
+                    .await
                     .map_err(|e| format!("Child element '{}' not found within parent '{}': {}", selector, parent_sel, e))
             } else {
                 // Standard search without parent
@@ -551,26 +1618,32 @@ pub async fn handle_find_element(
                         .await
                         .map_err(|e| format!("Element '{}' not found within {:.1}s: {}", selector, wait_timeout, e))
                 } else {
-                    client.find(Locator::Css(selector)).await
+                    find_element(client_manager, &session, &client, selector)
+                        .await
                         .map_err(|e| format!("Element '{}' not found: {}", selector, e))
                 }
             };
 
             match search_result {
                 Ok(element) => {
-                    let tag_name = element
-                        .tag_name()
+                    let tag_name = client_manager
+                        .run_command(&session, "get_tag_name", element.tag_name())
                         .await
                         .unwrap_or_else(|_| "unknown".to_string());
-                    let text_content = element
-                        .text()
+                    let text_content = client_manager
+                        .run_command(&session, "get_text", element.text())
                         .await
                         .unwrap_or_else(|_| "[no text]".to_string());
-                    let text_preview = if text_content.len() > 100 {
-                        format!("{}...", &text_content[..97])
-                    } else {
-                        text_content
-                    };
+                    let handle = client_manager.remember_element(&session, element_reference(&element.element_id()));
+                    let data = json!({
+                        "session": session,
+                        "selector": selector,
+                        "parent_selector": parent_selector,
+                        "handle": handle,
+                        "tag_name": tag_name,
+                        "text": text_content,
+                    });
+                    let text_preview = text::preview(&text_content, 100);
 
                     let scope_msg = if let Some(parent_sel) = parent_selector {
                         format!(" within parent '{}'", parent_sel)
@@ -578,17 +1651,18 @@ pub async fn handle_find_element(
                         String::new()
                     };
 
-                    Ok(success_response(format!(
-                        "Found element '{}'{} (session: {}): <{}> - Text: \"{}\"",
-                        selector, scope_msg, session, tag_name, text_preview
-                    )))
+                    Ok(structured_response(
+                        format!(
+                            "Found element '{}'{} (session: {}): <{}> - Text: \"{}\" [handle: {}]",
+                            selector, scope_msg, session, tag_name, text_preview, handle
+                        ),
+                        data,
+                    ))
                 }
                 Err(e) => Ok(error_response(e)),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -614,94 +1688,118 @@ pub async fn handle_find_elements(
         .and_then(|v| v.as_f64())
         .unwrap_or(0.0);
 
+    let limit = arguments
+        .as_ref()
+        .and_then(|args| args.get("limit"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_FIND_ELEMENTS_LIMIT);
+
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
-            // If parent_selector is provided, find within parent
-            let search_result = if let Some(parent_sel) = parent_selector {
-                // First find the parent element
-                let parent_element = if wait_timeout > 0.0 {
-                    match client_manager
-                        .find_element_with_wait(&client, parent_sel, Some(wait_timeout))
-                        .await
-                    {
-                        Ok(element) => element,
-                        Err(e) => {
-                            return Ok(error_response(format!(
-                                "Parent element '{}' not found within {:.1}s: {}",
-                                parent_sel, wait_timeout, e
-                            )));
-                        }
-                    }
-                } else {
-                    match client.find(Locator::Css(parent_sel)).await {
-                        Ok(element) => element,
-                        Err(e) => {
-                            return Ok(error_response(format!(
-                                "Parent element '{}' not found: {}",
-                                parent_sel, e
-                            )));
-                        }
-                    }
-                };
+            // Only the parent is waited for; children are collected as soon as it exists
+            if let Some(parent_sel) = parent_selector
+                && wait_timeout > 0.0
+                && let Err(e) = client_manager
+                    .find_element_with_wait(&client, parent_sel, Some(wait_timeout))
+                    .await
+            {
+                return Ok(error_response(format!(
+                    "Parent element '{}' not found within {:.1}s: {}",
+                    parent_sel, wait_timeout, e
+                )));
+            }
 
-                // Then find child elements within parent
-                parent_element.find_all(Locator::Css(selector)).await
-                    .map_err(|e| format!("Child elements '{}' not found within parent '{}': {}", selector, parent_sel, e))
-            } else {
-                // Standard search without parent
-                client.find_all(Locator::Css(selector)).await
-                    .map_err(|e| format!("Elements '{}' not found: {}", selector, e))
+            // One script round trip collects every element's metadata, instead of two
+            // WebDriver commands per element
+            let collected = client_manager
+                .run_command(
+                    &session,
+                    "find_elements",
+                    client.execute(
+                        FIND_ELEMENTS_SCRIPT,
+                        vec![json!(selector), json!(parent_selector), json!(limit)],
+                    ),
+                )
+                .await;
+
+            let result = match collected {
+                Ok(result) => result,
+                Err(e) => return Ok(error_response(format!("Elements '{}' not found: {}", selector, e))),
             };
+            if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+                return Ok(error_response(match parent_selector {
+                    Some(parent_sel) => format!("Parent element '{}' not found: {}", parent_sel, error),
+                    None => format!("Elements '{}' not found: {}", selector, error),
+                }));
+            }
 
-            match search_result {
-                Ok(elements) => {
-                    let scope_msg = if let Some(parent_sel) = parent_selector {
-                        format!(" within parent '{}'", parent_sel)
-                    } else {
-                        String::new()
-                    };
-
-                    let mut result_text = format!(
-                        "Found {} element(s) matching '{}'{} (session: {}):\n\n",
-                        elements.len(),
-                        selector,
-                        scope_msg,
-                        session
-                    );
+            let total = result.get("total").and_then(|v| v.as_u64()).unwrap_or(0);
+            let elements = result
+                .get("elements")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
 
-                    for (i, element) in elements.iter().enumerate() {
-                        let tag_name = element
-                            .tag_name()
-                            .await
-                            .unwrap_or_else(|_| "unknown".to_string());
-                        let text_content = element
-                            .text()
-                            .await
-                            .unwrap_or_else(|_| "[no text]".to_string());
-                        let text_preview = if text_content.len() > 100 {
-                            format!("{}...", &text_content[..97])
-                        } else {
-                            text_content
-                        };
+            let scope_msg = if let Some(parent_sel) = parent_selector {
+                format!(" within parent '{}'", parent_sel)
+            } else {
+                String::new()
+            };
 
-                        result_text.push_str(&format!(
-                            "{}. <{}> - Text: \"{}\"\n",
-                            i + 1,
-                            tag_name,
-                            text_preview
-                        ));
-                    }
+            let mut result_text = format!(
+                "Found {} element(s) matching '{}'{} (session: {}):\n\n",
+                total, selector, scope_msg, session
+            );
 
-                    Ok(success_response(result_text))
-                }
-                Err(e) => Ok(error_response(e)),
+            let mut element_data = Vec::with_capacity(elements.len());
+            for (i, element) in elements.iter().enumerate() {
+                let tag_name = element.get("tag_name").and_then(|v| v.as_str()).unwrap_or("unknown");
+                let text_content = element.get("text").and_then(|v| v.as_str()).unwrap_or("[no text]");
+                // The driver serializes returned DOM nodes as W3C element references
+                let handle = element
+                    .get("element")
+                    .filter(|reference| reference.is_object())
+                    .map(|reference| client_manager.remember_element(&session, reference.clone()));
+                element_data.push(json!({
+                    "index": i,
+                    "handle": handle,
+                    "tag_name": tag_name,
+                    "text": text_content,
+                }));
+                let handle_note = handle.map(|handle| format!(" [handle: {handle}]")).unwrap_or_default();
+                result_text.push_str(&format!(
+                    "{}. <{}> - Text: \"{}\"{}\n",
+                    i + 1,
+                    tag_name,
+                    text::preview(text_content, 100),
+                    handle_note
+                ));
+            }
+            let truncated = (element_data.len() as u64) < total;
+            if truncated {
+                result_text.push_str(&format!(
+                    "\n... showing the first {} of {}; raise `limit` to see more\n",
+                    element_data.len(),
+                    total
+                ));
             }
+
+            Ok(structured_response(
+                result_text,
+                json!({
+                    "session": session,
+                    "selector": selector,
+                    "parent_selector": parent_selector,
+                    "count": element_data.len(),
+                    "total": total,
+                    "truncated": truncated,
+                    "elements": element_data,
+                }),
+            ))
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -721,7 +1819,7 @@ pub async fn handle_scroll_to_element(
     match client_manager.get_or_create_client(session_id).await {
         Ok((session, client)) => {
             // First, try to find the element
-            match client.find(Locator::Css(selector)).await {
+            match find_element(client_manager, &session, &client, selector).await {
                 Ok(_element) => {
                     // Scroll the element into view using JavaScript with CSS selector
                     let scroll_script = format!(
@@ -729,10 +1827,12 @@ pub async fn handle_scroll_to_element(
                         selector.replace("'", "\\'")
                     );
 
-                    match client.execute(&scroll_script, vec![]).await {
+                    let scrolled = client.execute(&scroll_script, vec![]);
+                    match client_manager.run_command(&session, "scroll_to_element", scrolled).await {
                         Ok(_) => {
                             // Wait a moment for smooth scrolling to complete
-                            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                            let settle_ms = client_manager.get_config().scroll_settle_ms;
+                            tokio::time::sleep(std::time::Duration::from_millis(settle_ms)).await;
                             Ok(success_response(format!(
                                 "Successfully scrolled to element '{selector}' (session: {session})"
                             )))
@@ -742,14 +1842,13 @@ pub async fn handle_scroll_to_element(
                         }
                     }
                 }
-                Err(e) => Ok(error_response(format!(
-                    "Failed to find element '{selector}': {e}"
-                ))),
+                Err(e) => Ok(error_response_with(
+                    format!("Failed to find element '{selector}': {e}"),
+                    json!({ "selector": selector, "session_id": session }),
+                )),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -793,7 +1892,7 @@ pub async fn handle_hover(
                         selector.replace("'", "\\'")
                     );
 
-                    match client.execute(&hover_script, vec![]).await {
+                    match client_manager.run_command(&session, "hover", client.execute(&hover_script, vec![])).await {
                         Ok(_) => Ok(success_response(format!(
                             "Successfully hovered over element '{selector}' (session: {session})"
                         ))),
@@ -802,18 +1901,271 @@ pub async fn handle_hover(
                         }
                     }
                 }
-                Err(e) => Ok(error_response(format!(
-                    "Failed to find element '{selector}': {e}"
-                ))),
+                Err(e) => Ok(error_response_with(
+                    format!("Failed to find element '{selector}': {e}"),
+                    json!({ "selector": selector, "session_id": session }),
+                )),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
 /// Fill form fields and submit
+/// Count matches of a selector; `Err` carries the error response for a bad selector
+async fn count_elements(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    selector: &str,
+    parent_selector: Option<&str>,
+) -> Result<(u64, u64, bool), CallToolResult> {
+    let counted = client_manager
+        .run_command(
+            session,
+            "count_elements",
+            client.execute(COUNT_ELEMENTS_SCRIPT, vec![json!(selector), json!(parent_selector)]),
+        )
+        .await
+        .map_err(|e| error_response(format!("Failed to count elements '{selector}': {e}")))?;
+    if let Some(error) = counted.get("error").and_then(|v| v.as_str()) {
+        return Err(coded_error_response(
+            ErrorCode::InvalidArgument,
+            format!("Invalid selector '{selector}': {error}"),
+            json!({ "selector": selector, "parent_selector": parent_selector }),
+        ));
+    }
+    Ok((
+        counted.get("total").and_then(|v| v.as_u64()).unwrap_or(0),
+        counted.get("visible").and_then(|v| v.as_u64()).unwrap_or(0),
+        counted.get("parent_found").and_then(|v| v.as_bool()).unwrap_or(true),
+    ))
+}
+
+/// Report whether a selector matches, as data rather than as an error
+pub async fn handle_element_exists(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    let selector = arguments
+        .as_ref()
+        .and_then(|args| args.get("selector"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| McpError::invalid_params("selector parameter required", None))?;
+
+    let parent_selector = arguments
+        .as_ref()
+        .and_then(|args| args.get("parent_selector"))
+        .and_then(|v| v.as_str());
+
+    let visible_only = arguments
+        .as_ref()
+        .and_then(|args| args.get("visible_only"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let wait_timeout = extract_wait_timeout(arguments).unwrap_or(0.0).max(0.0);
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            let deadline = tokio::time::Instant::now() + crate::waits::clamped_wait(wait_timeout);
+            loop {
+                let (total, visible, parent_found) =
+                    match count_elements(client_manager, &session, &client, selector, parent_selector).await {
+                        Ok(counts) => counts,
+                        Err(response) => return Ok(response),
+                    };
+                let exists = if visible_only { visible > 0 } else { total > 0 };
+
+                if exists || tokio::time::Instant::now() >= deadline {
+                    let scope_msg = parent_selector
+                        .map(|parent| format!(" within parent '{parent}'"))
+                        .unwrap_or_default();
+                    let message = if exists {
+                        format!("Element '{selector}'{scope_msg} exists: {total} match(es), {visible} visible (session: {session})")
+                    } else if !parent_found {
+                        format!("Element '{selector}' does not exist: parent '{}' not found (session: {session})", parent_selector.unwrap_or_default())
+                    } else if total > 0 {
+                        format!("Element '{selector}'{scope_msg} does not exist as a visible element: {total} hidden match(es) (session: {session})")
+                    } else {
+                        format!("Element '{selector}'{scope_msg} does not exist (session: {session})")
+                    };
+                    return Ok(structured_response(
+                        message,
+                        json!({
+                            "session": session,
+                            "selector": selector,
+                            "parent_selector": parent_selector,
+                            "exists": exists,
+                            "count": total,
+                            "visible_count": visible,
+                            "parent_found": parent_found,
+                        }),
+                    ));
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(EXISTS_POLL_INTERVAL) => {}
+                    _ = cancellation.cancelled() => {
+                        return Ok(cancelled_response(&format!("Checking for element '{selector}'")));
+                    }
+                }
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Count the elements matching a selector
+pub async fn handle_count_elements(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let selector = arguments
+        .as_ref()
+        .and_then(|args| args.get("selector"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| McpError::invalid_params("selector parameter required", None))?;
+
+    let parent_selector = arguments
+        .as_ref()
+        .and_then(|args| args.get("parent_selector"))
+        .and_then(|v| v.as_str());
+
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            let (total, visible, parent_found) =
+                match count_elements(client_manager, &session, &client, selector, parent_selector).await {
+                    Ok(counts) => counts,
+                    Err(response) => return Ok(response),
+                };
+            let message = match parent_selector {
+                Some(parent) if !parent_found => {
+                    format!("0 elements match '{selector}': parent '{parent}' not found (session: {session})")
+                }
+                Some(parent) => format!(
+                    "{total} element(s) match '{selector}' within parent '{parent}', {visible} visible (session: {session})"
+                ),
+                None => format!("{total} element(s) match '{selector}', {visible} visible (session: {session})"),
+            };
+            Ok(structured_response(
+                message,
+                json!({
+                    "session": session,
+                    "selector": selector,
+                    "parent_selector": parent_selector,
+                    "count": total,
+                    "visible_count": visible,
+                    "parent_found": parent_found,
+                }),
+            ))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Describe the forms on the page so callers can fill them without guessing selectors
+pub async fn handle_discover_forms(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let form_selector = arguments
+        .as_ref()
+        .and_then(|args| args.get("form_selector"))
+        .and_then(|v| v.as_str());
+
+    let include_hidden = arguments
+        .as_ref()
+        .and_then(|args| args.get("include_hidden"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            let discovered = client_manager
+                .run_command(
+                    &session,
+                    "discover_forms",
+                    client.execute(DISCOVER_FORMS_SCRIPT, vec![json!(form_selector), json!(include_hidden)]),
+                )
+                .await;
+
+            let result = match discovered {
+                Ok(result) => result,
+                Err(e) => return Ok(error_response(format!("Failed to discover forms: {e}"))),
+            };
+            if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+                return Ok(error_response(format!("Failed to discover forms: {error}")));
+            }
+
+            let forms = result.get("forms").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+
+            let mut result_text = format!("Found {} form(s) (session: {}):\n", forms.len(), session);
+            for form in &forms {
+                let heading = match form.get("selector").and_then(|v| v.as_str()) {
+                    Some(selector) => format!(
+                        "{} ({} {})",
+                        selector,
+                        form.get("method").and_then(|v| v.as_str()).unwrap_or("GET"),
+                        form.get("action").and_then(|v| v.as_str()).unwrap_or_default()
+                    ),
+                    None => "Fields outside any <form>".to_string(),
+                };
+                result_text.push_str(&format!("\n{}\n", heading));
+
+                for field in form.get("fields").and_then(|v| v.as_array()).into_iter().flatten() {
+                    let label = field.get("label").and_then(|v| v.as_str()).unwrap_or_default();
+                    result_text.push_str(&format!(
+                        "  - {} [{}]{}{} → {}\n",
+                        field
+                            .get("name")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("(unnamed)"),
+                        field.get("type").and_then(|v| v.as_str()).unwrap_or("text"),
+                        if label.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" \"{}\"", text::preview(label, 60))
+                        },
+                        if field.get("required").and_then(|v| v.as_bool()).unwrap_or(false) {
+                            " required"
+                        } else {
+                            ""
+                        },
+                        field.get("selector").and_then(|v| v.as_str()).unwrap_or_default()
+                    ));
+                }
+                for submit in form.get("submit").and_then(|v| v.as_array()).into_iter().flatten() {
+                    result_text.push_str(&format!(
+                        "  submit: \"{}\" → {}\n",
+                        text::preview(submit.get("text").and_then(|v| v.as_str()).unwrap_or_default(), 60),
+                        submit.get("selector").and_then(|v| v.as_str()).unwrap_or_default()
+                    ));
+                }
+            }
+
+            Ok(structured_response(
+                result_text,
+                json!({
+                    "session": session,
+                    "url": result.get("url"),
+                    "form_selector": form_selector,
+                    "count": forms.len(),
+                    "forms": forms,
+                }),
+            ))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Fill form fields in order, optionally check what they hold, then submit
 pub async fn handle_fill_and_submit_form(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
@@ -821,8 +2173,8 @@ pub async fn handle_fill_and_submit_form(
     let fields = arguments
         .as_ref()
         .and_then(|args| args.get("fields"))
-        .and_then(|v| v.as_object())
         .ok_or_else(|| McpError::invalid_params("fields parameter required", None))?;
+    let fields = FieldAction::parse_all(fields).map_err(|e| McpError::invalid_params(e, None))?;
 
     let submit_selector = arguments
         .as_ref()
@@ -830,61 +2182,108 @@ pub async fn handle_fill_and_submit_form(
         .and_then(|v| v.as_str())
         .ok_or_else(|| McpError::invalid_params("submit_selector parameter required", None))?;
 
+    let verify = arguments
+        .as_ref()
+        .and_then(|args| args.get("verify"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let wait_timeout = extract_wait_timeout(arguments).unwrap_or(client_manager.get_config().default_wait_timeout_secs);
+    let wait_for_stable = extract_wait_for_stable(client_manager, arguments);
     let session_id = extract_session_id(arguments);
 
     match client_manager.get_or_create_client(session_id).await {
         Ok((session, client)) => {
-            let mut filled_fields = Vec::new();
-
-            // Fill each field
-            for (field_selector, value) in fields {
-                if let Some(text_value) = value.as_str() {
-                    match client.find(Locator::Css(field_selector)).await {
-                        Ok(element) => {
-                            // Clear the field first
-                            if let Err(e) = element.clear().await {
-                                return Ok(error_response(format!(
-                                    "Failed to clear field '{field_selector}': {e}"
-                                )));
-                            }
-
-                            // Then send keys
-                            if let Err(e) = element.send_keys(text_value).await {
-                                return Ok(error_response(format!(
-                                    "Failed to fill field '{field_selector}': {e}"
-                                )));
-                            }
-
-                            filled_fields.push(field_selector.clone());
-                        }
-                        Err(e) => {
-                            return Ok(error_response(format!(
-                                "Failed to find field '{field_selector}': {e}"
-                            )));
-                        }
+            let mut filled = Vec::with_capacity(fields.len());
+            let mut kinds = Vec::with_capacity(fields.len());
+
+            // Fill each field in the order given, waiting for it to appear
+            for field in &fields {
+                let field_wait = Some(field.wait_timeout.unwrap_or(wait_timeout));
+                let fill = |element: Element| forms::apply(element, field);
+                let filling = interact_with_element(
+                    client_manager, &session, &client, &field.selector, field_wait, wait_for_stable, "fill_field",
+                    client_manager.call_timeout(), fill,
+                );
+                let kind = match filling.await {
+                    Ok(kind) => kind,
+                    Err(InteractionError::Action(e)) => {
+                        return Ok(error_response_with(
+                            format!("Failed to fill field '{}': {e}", field.selector),
+                            json!({ "session": session, "selector": field.selector, "filled": filled }),
+                        ));
+                    }
+                    Err(InteractionError::Unstable(reason)) => {
+                        return Ok(error_response_with(
+                            format!("Did not fill field '{}': {reason}", field.selector),
+                            json!({ "session": session, "selector": field.selector, "filled": filled }),
+                        ));
+                    }
+                    Err(InteractionError::Find(e)) => {
+                        return Ok(error_response_with(
+                            format!("Failed to find field '{}': {e}", field.selector),
+                            json!({ "session": session, "selector": field.selector, "filled": filled }),
+                        ));
+                    }
+                };
+                filled.push(json!({ "selector": field.selector, "action": kind.as_str() }));
+                kinds.push(kind);
+            }
+
+            // Read every field back before submitting, so a mismatch stops the submit
+            if verify {
+                let mut mismatches = Vec::new();
+                for ((field, kind), entry) in fields.iter().zip(kinds).zip(filled.iter_mut()) {
+                    let problem = match forms::verify(client_manager, &session, &client, field, kind).await {
+                        Ok(problem) => problem,
+                        Err(e) => Some(format!("could not read field: {e}")),
+                    };
+                    entry["verified"] = json!(problem.is_none());
+                    if let Some(problem) = problem {
+                        mismatches.push(format!("'{}': {}", field.selector, problem));
+                        entry["problem"] = json!(problem);
                     }
                 }
+                if !mismatches.is_empty() {
+                    return Ok(coded_error_response(
+                        ErrorCode::CheckFailed,
+                        format!(
+                            "{} field(s) do not hold the requested value; the form was not submitted:\n{}",
+                            mismatches.len(),
+                            mismatches.join("\n")
+                        ),
+                        json!({ "session": session, "fields": filled, "submitted": false }),
+                    ));
+                }
             }
 
-            // Submit the form
-            match client.find(Locator::Css(submit_selector)).await {
-                Ok(submit_element) => match submit_element.click().await {
-                    Ok(_) => Ok(success_response(format!(
-                        "Successfully filled {} fields and submitted form (session: {}). Fields: {}",
-                        filled_fields.len(),
+            let selectors: Vec<&str> = fields.iter().map(|field| field.selector.as_str()).collect();
+            let click = |element: Element| async move { element.click().await };
+            let submitting = interact_with_element(
+                client_manager, &session, &client, submit_selector, Some(wait_timeout), wait_for_stable, "click",
+                client_manager.call_timeout(), click,
+            );
+            match submitting.await {
+                Ok(()) => Ok(structured_response(
+                    format!(
+                        "Successfully filled {} fields{} and submitted form (session: {}). Fields: {}",
+                        filled.len(),
+                        if verify { " (verified)" } else { "" },
                         session,
-                        filled_fields.join(", ")
-                    ))),
-                    Err(e) => Ok(error_response(format!("Failed to submit form: {e}"))),
-                },
-                Err(e) => Ok(error_response(format!(
+                        selectors.join(", ")
+                    ),
+                    json!({ "session": session, "fields": filled, "submitted": true }),
+                )),
+                Err(InteractionError::Action(e)) => Ok(error_response(format!("Failed to submit form: {e}"))),
+                Err(InteractionError::Unstable(reason)) => Ok(error_response(format!(
+                    "Did not click submit element '{submit_selector}': {reason}"
+                ))),
+                Err(InteractionError::Find(e)) => Ok(error_response(format!(
                     "Failed to find submit element '{submit_selector}': {e}"
                 ))),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -921,10 +2320,30 @@ pub async fn handle_login_form(
         .and_then(|args| args.get("submit_selector"))
         .and_then(|v| v.as_str());
 
+    let verify = arguments
+        .as_ref()
+        .and_then(|args| args.get("verify"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let check = arguments.as_ref().map(LoginCheck::from_arguments).unwrap_or_default();
+
+    // A two-factor step follows when a code or TOTP secret is given
+    let otp_requested = arguments
+        .as_ref()
+        .is_some_and(|args| args.contains_key("otp_code") || args.contains_key("totp_secret"));
+    if let Some(secret) = arguments.as_ref().and_then(|args| args.get("totp_secret")).and_then(|v| v.as_str()) {
+        Totp::parse(secret).map_err(|e| McpError::invalid_params(e, None))?;
+    }
+
     let session_id = extract_session_id(arguments);
 
     match client_manager.get_or_create_client(session_id).await {
         Ok((session, client)) => {
+            if let Some(blocked) = challenge_response(client_manager, &session, &client, "before filling the login form").await {
+                return Ok(blocked);
+            }
+
             // Define common login field selectors to try
             let default_username_selectors = vec![
                 "input[type='email']",
@@ -961,14 +2380,15 @@ pub async fn handle_login_form(
             // Try to find and fill username field
             let username_found = if let Some(selector) = username_selector {
                 // Use custom selector
-                match client.find(Locator::Css(selector)).await {
+                match find_element(client_manager, &session, &client, selector).await {
                     Ok(element) => {
-                        if let Err(e) = element.clear().await {
+                        if let Err(e) = client_manager.run_command(&session, "clear", element.clear()).await {
                             return Ok(error_response(format!(
                                 "Failed to clear username field '{selector}': {e}"
                             )));
                         }
-                        if let Err(e) = element.send_keys(username).await {
+                        let typed = client_manager.run_command(&session, "send_keys", element.send_keys(username));
+                        if let Err(e) = typed.await {
                             return Ok(error_response(format!(
                                 "Failed to fill username field '{selector}': {e}"
                             )));
@@ -985,8 +2405,10 @@ pub async fn handle_login_form(
                 // Try default selectors
                 let mut found = false;
                 for selector in &default_username_selectors {
-                    if let Ok(element) = client.find(Locator::Css(selector)).await {
-                        if element.clear().await.is_ok() && element.send_keys(username).await.is_ok() {
+                    if let Ok(element) = find_element(client_manager, &session, &client, selector).await {
+                        let cleared = client_manager.run_command(&session, "clear", element.clear()).await;
+                        let typed = client_manager.run_command(&session, "send_keys", element.send_keys(username));
+                        if cleared.is_ok() && typed.await.is_ok() {
                             found = true;
                             break;
                         }
@@ -1004,14 +2426,15 @@ pub async fn handle_login_form(
             // Try to find and fill password field
             let password_found = if let Some(selector) = password_selector {
                 // Use custom selector
-                match client.find(Locator::Css(selector)).await {
+                match find_element(client_manager, &session, &client, selector).await {
                     Ok(element) => {
-                        if let Err(e) = element.clear().await {
+                        if let Err(e) = client_manager.run_command(&session, "clear", element.clear()).await {
                             return Ok(error_response(format!(
                                 "Failed to clear password field '{selector}': {e}"
                             )));
                         }
-                        if let Err(e) = element.send_keys(password).await {
+                        let typed = client_manager.run_command(&session, "send_keys", element.send_keys(password));
+                        if let Err(e) = typed.await {
                             return Ok(error_response(format!(
                                 "Failed to fill password field '{selector}': {e}"
                             )));
@@ -1028,8 +2451,10 @@ pub async fn handle_login_form(
                 // Try default selectors
                 let mut found = false;
                 for selector in &default_password_selectors {
-                    if let Ok(element) = client.find(Locator::Css(selector)).await {
-                        if element.clear().await.is_ok() && element.send_keys(password).await.is_ok() {
+                    if let Ok(element) = find_element(client_manager, &session, &client, selector).await {
+                        let cleared = client_manager.run_command(&session, "clear", element.clear()).await;
+                        let typed = client_manager.run_command(&session, "send_keys", element.send_keys(password));
+                        if cleared.is_ok() && typed.await.is_ok() {
                             found = true;
                             break;
                         }
@@ -1044,46 +2469,377 @@ pub async fn handle_login_form(
                 ));
             }
 
+            let start_url = match client_manager.run_command(&session, "current_url", client.current_url()).await {
+                Ok(url) => url.to_string(),
+                Err(e) => return Ok(error_response(format!("Failed to read the login page URL: {e}"))),
+            };
+
             // Try to find and click submit button
             if let Some(selector) = submit_selector {
                 // Use custom selector
-                match client.find(Locator::Css(selector)).await {
-                    Ok(element) => match element.click().await {
-                        Ok(_) => Ok(success_response(format!(
-                            "Successfully filled login form and submitted (session: {session})"
-                        ))),
-                        Err(e) => Ok(error_response(format!(
-                            "Login form filled but failed to click submit button. Error: {e}"
-                        ))),
-                    },
-                    Err(e) => Ok(error_response(format!(
-                        "Failed to find submit button with custom selector '{selector}': {e}"
-                    ))),
+                match find_element(client_manager, &session, &client, selector).await {
+                    Ok(element) => {
+                        if let Err(e) = client_manager.run_command(&session, "click", element.click()).await {
+                            return Ok(error_response(format!(
+                                "Login form filled but failed to click submit button. Error: {e}"
+                            )));
+                        }
+                    }
+                    Err(e) => {
+                        return Ok(error_response(format!(
+                            "Failed to find submit button with custom selector '{selector}': {e}"
+                        )));
+                    }
                 }
             } else {
                 // Try default selectors
                 let mut submit_clicked = false;
                 for selector in &default_submit_selectors {
-                    if let Ok(element) = client.find(Locator::Css(selector)).await {
-                        if element.click().await.is_ok() {
-                            submit_clicked = true;
-                            break;
-                        }
+                    if let Ok(element) = find_element(client_manager, &session, &client, selector).await
+                        && client_manager.run_command(&session, "click", element.click()).await.is_ok()
+                    {
+                        submit_clicked = true;
+                        break;
                     }
                 }
-                if submit_clicked {
-                    Ok(success_response(format!(
-                        "Successfully filled login form and submitted (session: {session})"
-                    )))
-                } else {
-                    Ok(error_response(
+                if !submit_clicked {
+                    return Ok(error_response(
                         "Could not find submit button. Try providing a custom submit_selector".to_string()
-                    ))
+                    ));
                 }
             }
+
+            if !verify && !otp_requested {
+                return Ok(success_response(format!(
+                    "Successfully filled login form and submitted (session: {session})"
+                )));
+            }
+
+            if otp_requested {
+                // The success element only shows once the code is in, so the
+                // password step is judged by leaving the password form alone
+                let password_check = LoginCheck { success_selector: None, ..check.clone() };
+                let password_step = if verify {
+                    let result =
+                        login::await_login_result(client_manager, &session, &client, &start_url, &password_check).await;
+                    if result.outcome != LoginOutcome::Success
+                        && let Some(blocked) =
+                            challenge_response(client_manager, &session, &client, "after submitting the login form").await
+                    {
+                        return Ok(blocked);
+                    }
+                    if result.outcome == LoginOutcome::Failed {
+                        let message =
+                            format!("Login failed at the password step (session: {}): {}", session, result.reason);
+                        let mut data = json!(result);
+                        data["session"] = json!(session);
+                        return Ok(coded_error_response(ErrorCode::CheckFailed, message, data));
+                    }
+                    Some(result)
+                } else {
+                    None
+                };
+                let arguments = arguments.as_ref().expect("otp_requested implies arguments");
+                return Ok(complete_otp_step(
+                    client_manager, &session, &client, arguments, "otp_code", &check, verify, password_step,
+                )
+                .await);
+            }
+
+            let result = login::await_login_result(client_manager, &session, &client, &start_url, &check).await;
+            if result.outcome != LoginOutcome::Success
+                && let Some(blocked) = challenge_response(client_manager, &session, &client, "after submitting the login form").await
+            {
+                return Ok(blocked);
+            }
+            let message = format!(
+                "Login {} after {}ms (session: {}): {}\nURL: {}",
+                result.outcome.as_str(),
+                result.elapsed_ms,
+                session,
+                result.reason,
+                result.url
+            );
+            let mut data = json!(result);
+            data["session"] = json!(session);
+            match result.outcome {
+                LoginOutcome::Failed => Ok(coded_error_response(ErrorCode::CheckFailed, message, data)),
+                LoginOutcome::Success | LoginOutcome::Unverified => Ok(structured_response(message, data)),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Enter the one-time code of a two-factor login and report whether it worked
+pub async fn handle_enter_otp(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let args = arguments
+        .as_ref()
+        .filter(|args| args.contains_key("code") || args.contains_key("totp_secret"))
+        .ok_or_else(|| McpError::invalid_params("code or totp_secret parameter required", None))?;
+    if let Some(secret) = args.get("totp_secret").and_then(|v| v.as_str()) {
+        Totp::parse(secret).map_err(|e| McpError::invalid_params(e, None))?;
+    }
+
+    let verify = args.get("verify").and_then(|v| v.as_bool()).unwrap_or(true);
+    let check = LoginCheck::from_arguments(args);
+
+    let session_id = extract_session_id(arguments);
+
+    match client_manager.get_or_create_client(session_id).await {
+        Ok((session, client)) => {
+            Ok(complete_otp_step(client_manager, &session, &client, args, "code", &check, verify, None).await)
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Log in by putting known token material into the app's cookies and storage,
+/// then check that the app recognizes the session
+pub async fn handle_login_via_token(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let LoginViaTokenParams {
+        url,
+        cookies,
+        local_storage,
+        session_storage,
+        probe_url,
+        success_selector,
+        failure_selector,
+        verify,
+        verify_timeout,
+        session,
+    } = parse_params(arguments)?;
+    if cookies.is_empty() && local_storage.is_empty() && session_storage.is_empty() {
+        return Err(McpError::invalid_params("cookies, local_storage or session_storage parameter required", None));
+    }
+    if let Some(page) = [&url, &probe_url].into_iter().flatten().find(|page| url::Url::parse(page).is_err()) {
+        return Err(McpError::invalid_params(format!("Invalid URL '{page}'"), None));
+    }
+    let cookies = cookies
+        .iter()
+        .map(TokenCookie::to_cookie)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| McpError::invalid_params(e, None))?;
+
+    match client_manager.get_or_create_client(session.session_id).await {
+        Ok((session, client)) => {
+            if let Some(url) = &url
+                && let Err(e) = client_manager.navigate(&session, &client, url).await
+            {
+                return Ok(error_response_with(
+                    format!("Failed to navigate to {url}: {e}"),
+                    json!({ "url": url, "session": session }),
+                ));
+            }
+            let page = match client_manager.run_command(&session, "current_url", client.current_url()).await {
+                Ok(page) => page,
+                Err(e) => return Ok(error_response(format!("Failed to read the page URL: {e}"))),
+            };
+            if !matches!(page.scheme(), "http" | "https") {
+                return Ok(coded_error_response(
+                    ErrorCode::InvalidArgument,
+                    format!("The session is on {page}, which holds no cookies or storage. Pass url, a page of the app"),
+                    json!({ "session": session, "url": page.as_str() }),
+                ));
+            }
+
+            // Only names and keys are reported back; the values are credentials
+            let cookie_names: Vec<String> = cookies.iter().map(|cookie| cookie.name().to_string()).collect();
+            for cookie in cookies {
+                let name = cookie.name().to_string();
+                if let Err(e) = client_manager.run_command(&session, "add_cookie", client.add_cookie(cookie)).await {
+                    return Ok(error_response(format!("Failed to set cookie '{name}' on {page}: {e}")));
+                }
+            }
+            if !(local_storage.is_empty() && session_storage.is_empty())
+                && let Err(e) =
+                    login::set_storage(client_manager, &session, &client, &local_storage, &session_storage).await
+            {
+                return Ok(error_response(format!("Failed to set storage entries on {page}: {e}")));
+            }
+            let origin = page.origin().ascii_serialization();
+            let injected = json!({
+                "origin": origin,
+                "cookies": cookie_names,
+                "local_storage": local_storage.keys().collect::<Vec<_>>(),
+                "session_storage": session_storage.keys().collect::<Vec<_>>(),
+            });
+            let count = cookie_names.len() + local_storage.len() + session_storage.len();
+
+            if !verify.unwrap_or(true) {
+                return Ok(structured_response(
+                    format!("Set {count} cookie(s) and storage entr(ies) on {origin} (session: {session})"),
+                    json!({ "session": session, "injected": injected }),
+                ));
+            }
+
+            let probe_url = probe_url.unwrap_or_else(|| page.to_string());
+            if let Err(e) = client_manager.navigate(&session, &client, &probe_url).await {
+                return Ok(error_response_with(
+                    format!("Set the token, but failed to open probe page {probe_url}: {e}"),
+                    json!({ "session": session, "injected": injected }),
+                ));
+            }
+            let check = LoginCheck {
+                success_selector,
+                failure_selector,
+                timeout_secs: verify_timeout.unwrap_or(DEFAULT_LOGIN_VERIFY_TIMEOUT_SECS),
+                ..LoginCheck::default()
+            };
+            let result = login::await_session_recognized(client_manager, &session, &client, &probe_url, &check).await;
+            let message = format!(
+                "Token login {} after {}ms (session: {}): {}\nURL: {}",
+                result.outcome.as_str(),
+                result.elapsed_ms,
+                session,
+                result.reason,
+                result.url
+            );
+            let mut data = json!(result);
+            data["session"] = json!(session);
+            data["injected"] = injected;
+            match result.outcome {
+                LoginOutcome::Failed => Ok(coded_error_response(ErrorCode::CheckFailed, message, data)),
+                LoginOutcome::Success | LoginOutcome::Unverified => Ok(structured_response(message, data)),
+            }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// `BLOCKED_BY_CHALLENGE` result when the page shows a CAPTCHA or bot check
+async fn challenge_response(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    when: &str,
+) -> Option<CallToolResult> {
+    let found = challenge::detect(client_manager, session, client).await?;
+    let url = client_manager.run_command(session, "current_url", client.current_url()).await;
+    let url = url.map(|url| url.to_string()).unwrap_or_default();
+    Some(coded_error_response(
+        ErrorCode::BlockedByChallenge,
+        format!("Login stopped {} (session: {}). {}", when, session, found.describe()),
+        json!({ "session": session, "url": url, "challenge": found }),
+    ))
+}
+
+/// Type and submit the one-time code from `code_key` or `totp_secret`, then,
+/// when `verify` is set, watch the page like the password step
+#[allow(clippy::too_many_arguments)]
+async fn complete_otp_step(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    arguments: &Map<String, Value>,
+    code_key: &str,
+    check: &LoginCheck,
+    verify: bool,
+    password_step: Option<LoginResult>,
+) -> CallToolResult {
+    let code = match login::otp_code(arguments, code_key).await {
+        Ok(Some(code)) => code,
+        Ok(None) => return error_response(format!("{code_key} or totp_secret parameter required")),
+        Err(e) => return coded_error_response(ErrorCode::InvalidArgument, e, json!({ "session": session })),
+    };
+    let field_selector = arguments.get("otp_selector").and_then(|v| v.as_str()).unwrap_or(OTP_FIELD);
+    let submit_selector = arguments.get("otp_submit_selector").and_then(|v| v.as_str());
+    let field_timeout = arguments
+        .get("otp_timeout")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(DEFAULT_OTP_FIELD_TIMEOUT_SECS);
+
+    let entered =
+        login::enter_otp(client_manager, session, client, &code, field_selector, submit_selector, field_timeout).await;
+    let start_url = match entered {
+        Ok(url) => url,
+        Err(e) => return error_response(e),
+    };
+    if !verify {
+        return success_response(format!(
+            "Entered a {}-digit one-time code and submitted (session: {})",
+            code.chars().count(),
+            session
+        ));
+    }
+
+    let otp_check = check.for_otp(field_selector);
+    let result = login::await_login_result(client_manager, session, client, &start_url, &otp_check).await;
+    if result.outcome != LoginOutcome::Success
+        && let Some(blocked) =
+            challenge_response(client_manager, session, client, "after submitting the one-time code").await
+    {
+        return blocked;
+    }
+    let message = format!(
+        "Login {} after the one-time code step, {}ms (session: {}): {}\nURL: {}",
+        result.outcome.as_str(),
+        result.elapsed_ms,
+        session,
+        result.reason,
+        result.url
+    );
+    let mut data = json!(result);
+    data["session"] = json!(session);
+    if let Some(password_step) = password_step {
+        data["password_step"] = json!(password_step);
+    }
+    match result.outcome {
+        LoginOutcome::Failed => coded_error_response(ErrorCode::CheckFailed, message, data),
+        LoginOutcome::Success | LoginOutcome::Unverified => structured_response(message, data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_refused_actions_are_retried() {
+        assert!(is_transient_error("stale element reference: element is not attached", true));
+        assert!(is_transient_error("Element Click Intercepted: <div class=\"overlay\">", true));
+        assert!(is_transient_error("element not interactable", false));
+
+        // The click may have landed before the connection dropped
+        assert!(!is_transient_error("connection reset by peer", true));
+        assert!(!is_transient_error("unexpected EOF", true));
+        assert!(is_transient_error("connection reset by peer", false));
+        assert!(is_transient_error("broken pipe", false));
+
+        assert!(!is_transient_error("no such element", false));
+        let find = InteractionError::Find(WebDriverError::Execution("connection closed".to_string()));
+        assert!(find.is_transient());
+        assert!(InteractionError::Unstable("element not interactable".to_string()).is_transient());
+    }
+
+    #[test]
+    fn test_value_wait_describes_what_it_waits_for() {
+        let wait = |kind, expected, pattern: Option<&str>, absent| ValueWait {
+            selector: "#list".to_string(),
+            kind,
+            name: "aria-busy".to_string(),
+            expected,
+            pattern: pattern.map(str::to_string),
+            absent,
+            timeout_seconds: 1.0,
+            check_interval: Duration::from_millis(100),
+        };
+        let value = wait("attribute", Some(json!("false")), None, false);
+        assert_eq!(value.subject(), "Attribute 'aria-busy' of '#list'");
+        assert_eq!(value.expectation(), "become \"false\"");
+        assert_eq!(wait("attribute", None, Some("^(false|done)$"), false).expectation(), "match /^(false|done)$/");
+        assert_eq!(wait("attribute", None, None, true).expectation(), "disappear");
+        assert_eq!(wait("attribute", None, None, false).expectation(), "appear");
+
+        let property = wait("property", None, None, false);
+        assert_eq!(property.subject(), "Property 'aria-busy' of '#list'");
+        assert_eq!(property.expectation(), "become truthy");
+        assert_eq!(wait("property", Some(json!(0)), None, false).expectation(), "become 0");
     }
 }
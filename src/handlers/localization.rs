@@ -0,0 +1,74 @@
+//! Localization handlers
+//!
+//! Handles i18n checks:
+//! - Loading one page under several locales and diffing its strings
+
+use std::time::Duration;
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde_json::{Map, Value, json};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    ClientManager, ErrorCode,
+    localization::{self, LocalizationCheck},
+    tools::{coded_error_response, error_response, params::CheckLocalizationParams, structured_response},
+};
+use super::{cancelled_response, parse_params, session_error_response};
+
+/// Strings listed per finding and locale unless the call asks otherwise
+const DEFAULT_MAX_STRINGS: usize = 50;
+
+/// Load a page under several locales and report strings left untranslated,
+/// missing or shown as raw translation keys
+pub async fn handle_check_localization(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    let params: CheckLocalizationParams = parse_params(arguments)?;
+    let check = localization_check(&params).map_err(|e| McpError::invalid_params(e, None))?;
+    let max_strings = params.max_strings.unwrap_or(DEFAULT_MAX_STRINGS);
+
+    match client_manager.get_or_create_client(params.session.session_id).await {
+        Ok((session, client)) => {
+            let checking = localization::run_check(client_manager, &session, &client, &check);
+            let report = match cancellation.run_until_cancelled(checking).await {
+                Some(Ok(report)) => report,
+                Some(Err(e)) => return Ok(error_response(format!("Failed to check localization: {e}"))),
+                None => {
+                    localization::clear_overrides(client_manager, &session, &client).await;
+                    return Ok(cancelled_response("Localization check"));
+                }
+            };
+
+            let issues = report.issue_count();
+            let data = json!({
+                "session": session,
+                "url": &report.url,
+                "base_locale": &report.base_locale,
+                "locales": report.locales.iter().map(|findings| findings.to_json(max_strings)).collect::<Vec<_>>(),
+                "issues": issues,
+                "passed": issues == 0,
+            });
+            if params.fail_on_issues && issues > 0 {
+                return Ok(coded_error_response(ErrorCode::CheckFailed, report.summary(), data));
+            }
+            Ok(structured_response(format!("{} (session: {session})", report.summary()), data))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// The check the arguments of `check_localization` ask for
+pub fn localization_check(params: &CheckLocalizationParams) -> Result<LocalizationCheck, String> {
+    let check = LocalizationCheck::new(&params.url, &params.locales, params.base_locale.as_deref())?;
+    Ok(LocalizationCheck {
+        selector: params.selector.clone(),
+        ignore: params.ignore.clone(),
+        settle: Duration::from_millis(
+            params.settle_ms.unwrap_or(localization::DEFAULT_LOCALIZATION_SETTLE_MS).min(10_000),
+        ),
+        ..check
+    })
+}
@@ -7,16 +7,47 @@
 //! - `page`: Page content operations (title, text, screenshot, source)
 //! - `performance`: Performance monitoring (console logs, metrics, memory)
 //! - `recipes`: Recipe management (create, execute, list, delete)
+//! - `sessions`: Explicit browser session creation
+//! - `accessibility`: Accessibility audits
+//! - `crawl`: Multi-page site crawls
+//! - `compare`: Loading one page in several browsers side by side
+//! - `dom`: DOM snapshots and diffs between page states
+//! - `localization`: Loading one page under several locales and diffing its strings
+//! - `admin`: Operator tools (audit log)
 
+pub mod accessibility;
+pub mod admin;
+pub mod batch;
+pub mod cache;
+pub mod compare;
+pub mod crawl;
+pub mod dom;
 pub mod drivers;
 pub mod navigation;
+pub mod network;
 pub mod elements;
+pub mod localization;
 pub mod page;
 pub mod performance;
 pub mod recipes;
+pub mod sessions;
 
+use rmcp::{
+    ErrorData as McpError, Peer,
+    model::{CallToolResult, ProgressNotificationParam, ProgressToken},
+    service::{RequestContext, RoleServer},
+};
+use serde::de::DeserializeOwned;
 use serde_json::{Map, Value};
 
+use crate::{ErrorCode, WebDriverError, tools::{coded_error_response, error_response}};
+
+/// Parse a tool's arguments into its typed params struct (see `tools::params`)
+pub fn parse_params<T: DeserializeOwned>(arguments: &Option<Map<String, Value>>) -> Result<T, McpError> {
+    let arguments = Value::Object(arguments.clone().unwrap_or_default());
+    serde_json::from_value(arguments).map_err(|e| McpError::invalid_params(format!("Invalid arguments: {e}"), None))
+}
+
 /// Common utility to extract session_id from arguments
 pub fn extract_session_id(arguments: &Option<Map<String, Value>>) -> Option<String> {
     arguments
@@ -34,3 +65,58 @@ pub fn extract_wait_timeout(arguments: &Option<Map<String, Value>>) -> Option<f6
         .and_then(|v| v.as_f64())
 }
 
+
+/// Response for a long-running tool whose request was cancelled by the client
+pub fn cancelled_response(operation: &str) -> CallToolResult {
+    coded_error_response(
+        ErrorCode::Cancelled,
+        format!("{operation} cancelled by client"),
+        serde_json::json!({ "operation": operation }),
+    )
+}
+
+/// Response for a tool call that could not get a browser session
+pub fn session_error_response(error: WebDriverError) -> CallToolResult {
+    if let WebDriverError::NoActiveSession { session } = &error {
+        return coded_error_response(
+            ErrorCode::NoSession,
+            error.to_string(),
+            serde_json::json!({ "session_id": session }),
+        );
+    }
+    error_response(format!("Failed to create webdriver client: {error}"))
+}
+
+/// Sends MCP progress notifications for a long-running tool call, when the
+/// client asked for them by attaching a progress token to the request
+#[derive(Clone)]
+pub struct ProgressReporter {
+    target: Option<(Peer<RoleServer>, ProgressToken)>,
+}
+
+impl ProgressReporter {
+    pub fn from_context(context: &RequestContext<RoleServer>) -> Self {
+        Self {
+            target: context
+                .meta
+                .get_progress_token()
+                .map(|token| (context.peer.clone(), token)),
+        }
+    }
+
+    pub async fn report(&self, progress: u32, total: Option<u32>, message: String) {
+        let Some((peer, token)) = &self.target else {
+            return;
+        };
+        let notification = ProgressNotificationParam {
+            progress_token: token.clone(),
+            progress,
+            total,
+            message: Some(message),
+        };
+        // Progress is best effort; a client that went away must not fail the call
+        if let Err(e) = peer.notify_progress(notification).await {
+            tracing::debug!("Failed to send progress notification: {}", e);
+        }
+    }
+}
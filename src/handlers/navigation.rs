@@ -7,14 +7,23 @@
 //! - Current URL retrieval
 //! - Page load status
 
+use std::time::Duration;
+
 use rmcp::{ErrorData as McpError, model::CallToolResult};
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
 
 use crate::{
-    ClientManager,
-    tools::{error_response, success_response},
+    ClientManager, ConsentChoice, ErrorCode, PopupEvent, SessionBinding, challenge, cold_load, overlays, page_events,
+    popups,
+    tools::{
+        coded_error_response, error_response, error_response_with, structured_response, success_response,
+        params::{NavigateParams, SessionParams},
+    },
 };
-use super::extract_session_id;
+use super::{parse_params, session_error_response, sessions};
+
+/// How often `navigate` re-checks an interstitial during `challenge_wait`
+const CHALLENGE_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 /// JavaScript to set up console log monitoring in the browser
 const CONSOLE_MONITOR_SCRIPT: &str = r#"
@@ -30,10 +39,19 @@ const CONSOLE_MONITOR_SCRIPT: &str = r#"
                 debug: console.debug
             };
 
+            // Script location of the code that called console.*: the third
+            // frame with a position, after this function and the wrapper
+            const callerLocation = () => {
+                const frames = (new Error().stack || '').split('\n').filter(line => /:\d+:\d+\)?\s*$/.test(line));
+                const match = frames[2] && frames[2].trim().match(/([^\s(@]+):(\d+):(\d+)\)?$/);
+                return match ? { source: match[1], line: +match[2], column: +match[3] } : {};
+            };
+
             ['log', 'error', 'warn', 'info', 'debug'].forEach(level => {
                 console[level] = function(...args) {
                     originalConsole[level].apply(console, args);
                     window.__mcpConsoleLogs.push({
+                        ...callerLocation(),
                         level: level,
                         message: args.map(arg => {
                             if (typeof arg === 'object') {
@@ -57,6 +75,9 @@ const CONSOLE_MONITOR_SCRIPT: &str = r#"
                     message: message + ' at ' + source + ':' + lineno + ':' + colno,
                     timestamp: Date.now(),
                     url: window.location.href,
+                    source: source,
+                    line: lineno,
+                    column: colno,
                     stack: error ? error.stack : null
                 });
                 return false;
@@ -78,40 +99,142 @@ const CONSOLE_MONITOR_SCRIPT: &str = r#"
 "#;
 
 /// Set up console log monitoring for a browser session
-pub async fn setup_console_monitoring(client: &fantoccini::Client) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    client.execute(CONSOLE_MONITOR_SCRIPT, vec![]).await?;
+pub async fn setup_console_monitoring(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &fantoccini::Client,
+) -> crate::Result<()> {
+    let installed = client.execute(CONSOLE_MONITOR_SCRIPT, vec![]);
+    client_manager.run_command(session, "setup_console_monitoring", installed).await?;
     Ok(())
 }
 
+/// Move the current page's console output to the server-side buffer before
+/// it is lost to a page load
+async fn keep_console_logs(client_manager: &ClientManager, session: &str, client: &fantoccini::Client) {
+    if let Err(e) = client_manager.drain_console_logs(session, client).await {
+        tracing::debug!("Failed to keep console logs of session '{}': {}", session, e);
+    }
+}
+
+/// Move the current page's lifecycle events to the session's buffer before
+/// the page is left
+async fn keep_page_events(client_manager: &ClientManager, session: &str, client: &fantoccini::Client) {
+    if let Err(e) = page_events::drain(client_manager, session, client).await {
+        tracing::debug!("Failed to keep page events of session '{}': {}", session, e);
+    }
+}
+
+/// Record the lifecycle of the freshly loaded page
+async fn watch_page_events(client_manager: &ClientManager, session: &str, client: &fantoccini::Client) {
+    if let Err(e) = page_events::install_recorder(client_manager, session, client).await {
+        tracing::debug!("Failed to record page events in session '{}': {}", session, e);
+    }
+}
+
+/// Guard the freshly loaded page against popups and apply the session's popup
+/// policy to windows opened while it loaded
+async fn watch_popups(client_manager: &ClientManager, session: &str, client: &fantoccini::Client) -> Vec<PopupEvent> {
+    if let Err(e) = popups::install_guard(client_manager, session, client).await {
+        tracing::debug!("Failed to install popup guard in session '{}': {}", session, e);
+    }
+    popups::apply_policy(client_manager, session, client).await.unwrap_or_else(|e| {
+        tracing::debug!("Failed to check popups in session '{}': {}", session, e);
+        Vec::new()
+    })
+}
+
 /// Navigate to a URL
 pub async fn handle_navigate(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
 ) -> Result<CallToolResult, McpError> {
-    let url = arguments
-        .as_ref()
-        .and_then(|args| args.get("url"))
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| McpError::invalid_params("url parameter required", None))?;
-
-    let session_id = extract_session_id(arguments);
+    let NavigateParams { url, challenge_wait, browser, cold_load, session } = parse_params(arguments)?;
+    let url = url.as_str();
+    if let Some(block) = client_manager.robots_block(url).await {
+        let context = json!({ "url": url, "session_id": session.session_id, "robots": block });
+        return Ok(coded_error_response(ErrorCode::PolicyBlocked, block.describe(), context));
+    }
+    let mut session_id = client_manager.route_navigation(session.session_id, url).await;
+    if let Some(browser) = browser {
+        let binding = SessionBinding::new(sessions::parse_browser(&browser)?);
+        match client_manager.bind_session(session_id.clone(), binding).await {
+            Ok(session) => session_id = Some(session),
+            Err(e) => {
+                let context = json!({ "session_id": session_id });
+                let code = match e.code() {
+                    ErrorCode::PolicyBlocked => ErrorCode::PolicyBlocked,
+                    _ => ErrorCode::InvalidArgument,
+                };
+                return Ok(coded_error_response(code, e.to_string(), context));
+            }
+        }
+    }
 
     match client_manager.get_or_create_client(session_id).await {
-        Ok((session, client)) => match client.goto(url).await {
-            Ok(_) => {
-                // Set up console monitoring immediately after navigation
-                if let Err(e) = setup_console_monitoring(&client).await {
-                    eprintln!("Warning: Failed to setup console monitoring: {}", e);
-                }
-                Ok(success_response(format!(
-                    "Successfully navigated to {url} (session: {session})"
-                )))
-            },
-            Err(e) => Ok(error_response(format!("Failed to navigate: {e}"))),
-        },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Ok((session, client)) => {
+            keep_console_logs(client_manager, &session, &client).await;
+            keep_page_events(client_manager, &session, &client).await;
+            let loaded = if cold_load {
+                cold_load::navigate_cold(client_manager, &session, &client, url).await.map(Some)
+            } else {
+                client_manager.navigate(&session, &client, url).await.map(|()| None)
+            };
+            match loaded {
+                Ok(cold) => {
+                    // Set up console monitoring immediately after navigation
+                    if let Err(e) = setup_console_monitoring(client_manager, &session, &client).await {
+                        eprintln!("Warning: Failed to setup console monitoring: {}", e);
+                    }
+
+                    watch_page_events(client_manager, &session, &client).await;
+                    let popup_events = watch_popups(client_manager, &session, &client).await;
+
+                    // Interstitials often let the browser through after a few
+                    // seconds; CAPTCHA widgets never do
+                    let deadline = tokio::time::Instant::now() + crate::waits::clamped_wait(challenge_wait);
+                    let mut found = challenge::detect(client_manager, &session, &client).await;
+                    while let Some(current) = &found
+                        && current.kind == "interstitial"
+                        && tokio::time::Instant::now() < deadline
+                    {
+                        tokio::time::sleep(CHALLENGE_POLL_INTERVAL).await;
+                        found = challenge::detect(client_manager, &session, &client).await;
+                    }
+                    if let Some(found) = found {
+                        return Ok(coded_error_response(
+                            ErrorCode::BlockedByChallenge,
+                            format!("Navigated to {url}, but the site answered with a challenge. {}", found.describe()),
+                            json!({ "url": url, "session_id": session, "challenge": found }),
+                        ));
+                    }
+
+                    let mut message = format!("Successfully navigated to {url} (session: {session})");
+                    if let Some(cold) = cold {
+                        message.push_str(&format!(". Cold load with {} cleared", cold.cleared.join(", ")));
+                    }
+                    if client_manager.auto_dismiss_banners(&session) {
+                        match overlays::dismiss(client_manager, &session, &client, ConsentChoice::Accept).await {
+                            Ok(dismissed) if !dismissed.is_empty() => {
+                                let names: Vec<String> = dismissed.iter().map(|overlay| overlay.describe()).collect();
+                                message.push_str(&format!(". Dismissed: {}", names.join(", ")));
+                            }
+                            Ok(_) => {}
+                            Err(e) => tracing::debug!("Failed to dismiss banners in session '{}': {}", session, e),
+                        }
+                    }
+                    if !popup_events.is_empty() {
+                        message.push_str(&format!(". {}", popups::summarize(&popup_events)));
+                    }
+                    Ok(success_response(message))
+                },
+                Err(e) => Ok(error_response_with(
+                    format!("Failed to navigate to {url}: {e}"),
+                    json!({ "url": url, "session_id": session }),
+                )),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -120,18 +243,17 @@ pub async fn handle_get_current_url(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
 ) -> Result<CallToolResult, McpError> {
-    let session_id = extract_session_id(arguments);
+    let SessionParams { session_id } = parse_params(arguments)?;
 
-    match client_manager.get_or_create_client(session_id).await {
-        Ok((session, client)) => match client.current_url().await {
-            Ok(url) => Ok(success_response(format!(
-                "Current URL: {url} (session: {session})"
-            ))),
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => match client_manager.run_command(&session, "get_current_url", client.current_url()).await {
+            Ok(url) => Ok(structured_response(
+                format!("Current URL: {url} (session: {session})"),
+                json!({ "session": session, "url": url.as_str() }),
+            )),
             Err(e) => Ok(error_response(format!("Failed to get current URL: {e}"))),
         },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -140,18 +262,23 @@ pub async fn handle_back(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
 ) -> Result<CallToolResult, McpError> {
-    let session_id = extract_session_id(arguments);
+    let SessionParams { session_id } = parse_params(arguments)?;
 
     match client_manager.get_or_create_client(session_id).await {
-        Ok((session, client)) => match client.back().await {
-            Ok(_) => Ok(success_response(format!(
-                "Successfully navigated back (session: {session})"
-            ))),
-            Err(e) => Ok(error_response(format!("Failed to navigate back: {e}"))),
-        },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Ok((session, client)) => {
+            keep_console_logs(client_manager, &session, &client).await;
+            keep_page_events(client_manager, &session, &client).await;
+            match client_manager.run_command(&session, "back", client.back()).await {
+                Ok(_) => {
+                    watch_page_events(client_manager, &session, &client).await;
+                    Ok(success_response(format!(
+                        "Successfully navigated back (session: {session})"
+                    )))
+                }
+                Err(e) => Ok(error_response(format!("Failed to navigate back: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -160,18 +287,23 @@ pub async fn handle_forward(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
 ) -> Result<CallToolResult, McpError> {
-    let session_id = extract_session_id(arguments);
+    let SessionParams { session_id } = parse_params(arguments)?;
 
     match client_manager.get_or_create_client(session_id).await {
-        Ok((session, client)) => match client.forward().await {
-            Ok(_) => Ok(success_response(format!(
-                "Successfully navigated forward (session: {session})"
-            ))),
-            Err(e) => Ok(error_response(format!("Failed to navigate forward: {e}"))),
-        },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Ok((session, client)) => {
+            keep_console_logs(client_manager, &session, &client).await;
+            keep_page_events(client_manager, &session, &client).await;
+            match client_manager.run_command(&session, "forward", client.forward()).await {
+                Ok(_) => {
+                    watch_page_events(client_manager, &session, &client).await;
+                    Ok(success_response(format!(
+                        "Successfully navigated forward (session: {session})"
+                    )))
+                }
+                Err(e) => Ok(error_response(format!("Failed to navigate forward: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -180,24 +312,28 @@ pub async fn handle_refresh(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
 ) -> Result<CallToolResult, McpError> {
-    let session_id = extract_session_id(arguments);
+    let SessionParams { session_id } = parse_params(arguments)?;
 
     match client_manager.get_or_create_client(session_id).await {
-        Ok((session, client)) => match client.refresh().await {
-            Ok(_) => {
-                // Set up console monitoring immediately after refresh
-                if let Err(e) = setup_console_monitoring(&client).await {
-                    eprintln!("Warning: Failed to setup console monitoring: {}", e);
-                }
-                Ok(success_response(format!(
-                    "Successfully refreshed page (session: {session})"
-                )))
-            },
-            Err(e) => Ok(error_response(format!("Failed to refresh page: {e}"))),
-        },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Ok((session, client)) => {
+            keep_console_logs(client_manager, &session, &client).await;
+            keep_page_events(client_manager, &session, &client).await;
+            match client_manager.run_command(&session, "refresh", client.refresh()).await {
+                Ok(_) => {
+                    // Set up console monitoring immediately after refresh
+                    if let Err(e) = setup_console_monitoring(client_manager, &session, &client).await {
+                        eprintln!("Warning: Failed to setup console monitoring: {}", e);
+                    }
+                    watch_page_events(client_manager, &session, &client).await;
+                    watch_popups(client_manager, &session, &client).await;
+                    Ok(success_response(format!(
+                        "Successfully refreshed page (session: {session})"
+                    )))
+                },
+                Err(e) => Ok(error_response(format!("Failed to refresh page: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -206,11 +342,13 @@ pub async fn handle_get_page_load_status(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
 ) -> Result<CallToolResult, McpError> {
-    let session_id = extract_session_id(arguments);
+    let SessionParams { session_id } = parse_params(arguments)?;
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
-            match client.execute("return document.readyState;", vec![]).await {
+            match client_manager
+                .run_command(&session, "get_page_load_status", client.execute("return document.readyState;", vec![]))
+                .await {
                 Ok(result) => {
                     let status = result.as_str().unwrap_or("unknown");
                     Ok(success_response(format!(
@@ -220,8 +358,6 @@ pub async fn handle_get_page_load_status(
                 Err(e) => Ok(error_response(format!("Failed to get page load status: {e}"))),
             }
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
@@ -0,0 +1,675 @@
+//! Network analysis handlers
+//!
+//! Summarizes what a page loaded, from the browser's resource timing entries:
+//! - Page weight: transfer size and request count by resource type
+//! - Third parties: requests grouped by registrable domain, known trackers, and
+//!   main-thread blocking time from Long Animation Frame script attribution
+//! - Network identity: egress IP, headers, languages, timezone and WebRTC
+//!   exposure as an echo endpoint sees the session
+
+use std::collections::{BTreeMap, HashMap};
+
+use fantoccini::Client;
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    ClientManager, WebDriverError, cold_load,
+    network_identity::{self, EchoedRequest, WebRtcReport},
+    tools::{
+        error_response,
+        params::{AnalyzeThirdPartiesParams, GetPageWeightParams, WhoamiNetworkParams},
+        structured_response,
+    },
+};
+use super::{cancelled_response, parse_params, session_error_response};
+
+/// Longest WebRTC candidate gathering `whoami_network` allows
+const MAX_WEBRTC_WAIT_MS: u64 = 15_000;
+
+/// The echo endpoint's answer as text. Firefox shows JSON in a viewer, so
+/// unless the page's text is already JSON or an IP, it is fetched again raw.
+const ECHO_BODY_SCRIPT: &str = r#"
+    const done = arguments[arguments.length - 1];
+    const text = document.body ? document.body.innerText : '';
+    const readable = t => {
+        try {
+            JSON.parse(t);
+            return true;
+        } catch (e) {
+            return /^[0-9a-f.:]+$/i.test(t.trim());
+        }
+    };
+    if (readable(text)) {
+        done(text);
+        return;
+    }
+    fetch(location.href, { cache: 'no-store', credentials: 'include' })
+        .then(response => response.text())
+        .then(done, () => done(text));
+"#;
+
+/// Navigation and resource timing entries of the current page
+const RESOURCE_ENTRIES_SCRIPT: &str = r#"
+    const entry = (e, type) => ({
+        url: e.name,
+        initiator_type: type || e.initiatorType || 'other',
+        transfer_size: e.transferSize || 0,
+        encoded_size: e.encodedBodySize || 0,
+        decoded_size: e.decodedBodySize || 0,
+        start_time: e.startTime,
+        duration: e.duration,
+        render_blocking: e.renderBlockingStatus === 'blocking'
+    });
+    const navigation = performance.getEntriesByType('navigation').map(e => entry(e, 'navigation'));
+    const resources = performance.getEntriesByType('resource').map(e => entry(e));
+    return { page_url: location.href, entries: navigation.concat(resources) };
+"#;
+
+/// Resource entries plus the scripts that ran during long animation frames,
+/// each with its share of the frame's blocking time; `scripts` is null where
+/// the browser lacks the Long Animation Frames API (Chromium 123+ has it)
+const THIRD_PARTY_SCRIPT: &str = r#"
+    const done = arguments[arguments.length - 1];
+    const resources = performance.getEntriesByType('resource').map(e => ({
+        url: e.name,
+        initiator_type: e.initiatorType || 'other',
+        transfer_size: e.transferSize || 0,
+        encoded_size: e.encodedBodySize || 0,
+        decoded_size: e.decodedBodySize || 0,
+        start_time: e.startTime,
+        duration: e.duration,
+        render_blocking: e.renderBlockingStatus === 'blocking'
+    }));
+    const finish = scripts => done({ page_url: location.href, resources, scripts });
+    if (!(PerformanceObserver.supportedEntryTypes || []).includes('long-animation-frame')) {
+        return finish(null);
+    }
+    const scripts = [];
+    const observer = new PerformanceObserver(list => {
+        for (const frame of list.getEntries()) {
+            const total = frame.scripts.reduce((sum, s) => sum + s.duration, 0);
+            for (const s of frame.scripts) {
+                scripts.push({
+                    url: s.sourceURL || '',
+                    duration: s.duration,
+                    blocking: total > 0 ? frame.blockingDuration * s.duration / total : 0
+                });
+            }
+        }
+    });
+    observer.observe({ type: 'long-animation-frame', buffered: true });
+    // Buffered entries arrive in the observer's first callback
+    setTimeout(() => { observer.disconnect(); finish(scripts); }, 100);
+"#;
+
+/// Public suffixes of two labels, under which a site is registered one label deeper.
+/// Not the full Public Suffix List: enough for the common country-code domains.
+const TWO_LABEL_SUFFIXES: &[&str] = &[
+    "co.uk", "org.uk", "ac.uk", "gov.uk", "me.uk", "com.au", "net.au", "org.au", "co.nz", "co.jp", "ne.jp",
+    "or.jp", "co.kr", "co.in", "co.za", "com.br", "com.cn", "com.mx", "com.tr", "com.sg", "com.hk", "com.tw",
+];
+
+/// Registrable domains of well-known tracking, advertising and session replay services
+const KNOWN_TRACKERS: &[(&str, &str)] = &[
+    ("google-analytics.com", "analytics"),
+    ("googletagmanager.com", "tag manager"),
+    ("doubleclick.net", "advertising"),
+    ("googlesyndication.com", "advertising"),
+    ("googleadservices.com", "advertising"),
+    ("facebook.net", "social/advertising"),
+    ("ads-twitter.com", "advertising"),
+    ("adnxs.com", "advertising"),
+    ("criteo.com", "advertising"),
+    ("criteo.net", "advertising"),
+    ("taboola.com", "advertising"),
+    ("outbrain.com", "advertising"),
+    ("amazon-adsystem.com", "advertising"),
+    ("scorecardresearch.com", "analytics"),
+    ("quantserve.com", "analytics"),
+    ("segment.com", "analytics"),
+    ("segment.io", "analytics"),
+    ("mixpanel.com", "analytics"),
+    ("amplitude.com", "analytics"),
+    ("hs-analytics.net", "analytics"),
+    ("hotjar.com", "session replay"),
+    ("fullstory.com", "session replay"),
+    ("clarity.ms", "session replay"),
+    ("mouseflow.com", "session replay"),
+];
+
+/// Resource timing keeps this many entries unless the page raises the limit
+const DEFAULT_RESOURCE_BUFFER: usize = 250;
+
+/// One request of the page, as resource timing reports it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ResourceEntry {
+    pub url: String,
+    pub initiator_type: String,
+    /// Bytes received over the network, headers included; 0 when served from
+    /// cache or cross-origin without `Timing-Allow-Origin`
+    pub transfer_size: u64,
+    pub encoded_size: u64,
+    pub decoded_size: u64,
+    pub start_time: f64,
+    pub duration: f64,
+    /// Held up the first render (Chromium only)
+    #[serde(default)]
+    pub render_blocking: bool,
+}
+
+/// A script that ran during a long animation frame
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptTiming {
+    /// Source of the script; empty for inline handlers the browser cannot attribute
+    pub url: String,
+    pub duration: f64,
+    /// The script's share of its frame's blocking time
+    pub blocking: f64,
+}
+
+impl ResourceEntry {
+    /// Bytes the resource weighs: what was transferred, or its body size when cached
+    pub fn bytes(&self) -> u64 {
+        self.transfer_size.max(self.encoded_size)
+    }
+
+    /// Served from the browser cache, so nothing crossed the network
+    fn is_cached(&self) -> bool {
+        self.transfer_size == 0 && self.encoded_size > 0
+    }
+
+    /// Cross-origin response whose sizes the browser hides
+    fn is_opaque(&self) -> bool {
+        self.transfer_size == 0 && self.encoded_size == 0 && self.decoded_size == 0
+    }
+
+    /// Kind of resource: document, js, css, img, font, xhr, media or other
+    pub fn resource_type(&self) -> &'static str {
+        let path = self.url.split(['?', '#']).next().unwrap_or_default().to_lowercase();
+        let extension = path.rsplit_once('.').map(|(_, extension)| extension).unwrap_or_default();
+        match (self.initiator_type.as_str(), extension) {
+            ("navigation" | "iframe" | "frame", _) => "document",
+            ("xmlhttprequest" | "fetch" | "beacon", _) => "xhr",
+            (_, "js" | "mjs") | ("script", _) => "js",
+            (_, "css") => "css",
+            (_, "woff" | "woff2" | "ttf" | "otf" | "eot") => "font",
+            (_, "png" | "jpg" | "jpeg" | "gif" | "webp" | "avif" | "svg" | "ico" | "bmp") | ("img" | "image", _) => {
+                "img"
+            }
+            (_, "mp4" | "webm" | "mp3" | "ogg" | "wav" | "m3u8") | ("video" | "audio" | "track", _) => "media",
+            _ => "other",
+        }
+    }
+}
+
+/// `bytes` as B, KB or MB
+pub fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..1_000 => format!("{bytes} B"),
+        1_000..1_000_000 => format!("{:.1} KB", bytes as f64 / 1e3),
+        _ => format!("{:.1} MB", bytes as f64 / 1e6),
+    }
+}
+
+/// Totals per resource type and the `top` heaviest resources
+pub fn page_weight(entries: &[ResourceEntry], top: usize) -> Value {
+    let mut by_type: BTreeMap<&str, (u64, u64, u64)> = BTreeMap::new();
+    for entry in entries {
+        let totals = by_type.entry(entry.resource_type()).or_default();
+        totals.0 += 1;
+        totals.1 += entry.transfer_size;
+        totals.2 += entry.bytes();
+    }
+    let mut heaviest: Vec<&ResourceEntry> = entries.iter().collect();
+    heaviest.sort_by_key(|e| std::cmp::Reverse(e.bytes()));
+
+    json!({
+        "requests": entries.len(),
+        "transfer_bytes": entries.iter().map(|e| e.transfer_size).sum::<u64>(),
+        "total_bytes": entries.iter().map(ResourceEntry::bytes).sum::<u64>(),
+        "cached_requests": entries.iter().filter(|e| e.is_cached()).count(),
+        "unknown_size_requests": entries.iter().filter(|e| e.is_opaque()).count(),
+        "by_type": by_type
+            .iter()
+            .map(|(kind, (requests, transfer, total))| {
+                (kind.to_string(), json!({ "requests": requests, "transfer_bytes": transfer, "total_bytes": total }))
+            })
+            .collect::<Map<String, Value>>(),
+        "heaviest": heaviest
+            .iter()
+            .take(top)
+            .map(|e| json!({ "url": e.url, "type": e.resource_type(), "bytes": e.bytes(), "cached": e.is_cached() }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Host of `url`, if it has one
+fn host_of(url: &str) -> Option<String> {
+    url::Url::parse(url).ok()?.host_str().map(|host| host.trim_end_matches('.').to_lowercase())
+}
+
+/// Domain a host is registered under, e.g. `cdn.shop.example.co.uk` -> `example.co.uk`.
+/// IP addresses and single-label hosts are returned as they are.
+pub fn registrable_domain(host: &str) -> String {
+    if host.parse::<std::net::IpAddr>().is_ok() || host.starts_with('[') {
+        return host.to_string();
+    }
+    let labels: Vec<&str> = host.split('.').collect();
+    let suffix_labels = match labels.len() {
+        0..=2 => return host.to_string(),
+        n if TWO_LABEL_SUFFIXES.contains(&labels[n - 2..].join(".").as_str()) => 2,
+        _ => 1,
+    };
+    labels[labels.len() - suffix_labels - 1..].join(".")
+}
+
+/// Category of a known tracker domain
+fn tracker_category(domain: &str) -> Option<&'static str> {
+    KNOWN_TRACKERS.iter().find(|(tracker, _)| *tracker == domain).map(|(_, category)| *category)
+}
+
+#[derive(Default)]
+struct DomainUsage {
+    requests: u64,
+    bytes: u64,
+    types: BTreeMap<&'static str, u64>,
+    render_blocking_ms: f64,
+    script_ms: f64,
+    blocking_ms: f64,
+}
+
+/// Third-party requests of `page_url` grouped by registrable domain, heaviest
+/// blockers first. `scripts` is `None` when blocking time could not be measured.
+pub fn third_parties(page_url: &str, resources: &[ResourceEntry], scripts: Option<&[ScriptTiming]>) -> Value {
+    let first_party = host_of(page_url).map(|host| registrable_domain(&host));
+    let domain_of = |url: &str| {
+        let domain = registrable_domain(&host_of(url)?);
+        (Some(&domain) != first_party.as_ref()).then_some(domain)
+    };
+
+    let mut domains: HashMap<String, DomainUsage> = HashMap::new();
+    for entry in resources {
+        let Some(domain) = domain_of(&entry.url) else {
+            continue;
+        };
+        let usage = domains.entry(domain).or_default();
+        usage.requests += 1;
+        usage.bytes += entry.bytes();
+        *usage.types.entry(entry.resource_type()).or_default() += 1;
+        if entry.render_blocking {
+            usage.render_blocking_ms += entry.duration;
+        }
+    }
+    let mut first_party_blocking_ms = 0.0;
+    for script in scripts.unwrap_or_default() {
+        match domain_of(&script.url) {
+            Some(domain) => {
+                let usage = domains.entry(domain).or_default();
+                usage.script_ms += script.duration;
+                usage.blocking_ms += script.blocking;
+            }
+            None => first_party_blocking_ms += script.blocking,
+        }
+    }
+
+    let mut sorted: Vec<(String, DomainUsage)> = domains.into_iter().collect();
+    sorted.sort_by(|(a_domain, a), (b_domain, b)| {
+        (b.blocking_ms + b.render_blocking_ms)
+            .total_cmp(&(a.blocking_ms + a.render_blocking_ms))
+            .then(b.requests.cmp(&a.requests))
+            .then(a_domain.cmp(b_domain))
+    });
+    let round = |ms: f64| (ms * 10.0).round() / 10.0;
+    let measured = scripts.is_some();
+    json!({
+        "first_party": first_party,
+        "third_party_requests": sorted.iter().map(|(_, usage)| usage.requests).sum::<u64>(),
+        "third_party_bytes": sorted.iter().map(|(_, usage)| usage.bytes).sum::<u64>(),
+        "total_requests": resources.len(),
+        "trackers": sorted
+            .iter()
+            .filter(|(domain, _)| tracker_category(domain).is_some())
+            .map(|(domain, _)| domain.clone())
+            .collect::<Vec<_>>(),
+        "blocking_time_measured": measured,
+        "third_party_blocking_ms": measured.then(|| round(sorted.iter().map(|(_, usage)| usage.blocking_ms).sum())),
+        "first_party_blocking_ms": measured.then(|| round(first_party_blocking_ms)),
+        "domains": sorted
+            .iter()
+            .map(|(domain, usage)| json!({
+                "domain": domain,
+                "tracker": tracker_category(domain),
+                "requests": usage.requests,
+                "bytes": usage.bytes,
+                "types": usage.types,
+                "render_blocking_ms": round(usage.render_blocking_ms),
+                "script_ms": measured.then(|| round(usage.script_ms)),
+                "blocking_ms": measured.then(|| round(usage.blocking_ms)),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Read the current page's resource timing entries
+async fn resource_entries(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &fantoccini::Client,
+) -> crate::Result<(String, Vec<ResourceEntry>)> {
+    let result = client_manager
+        .run_command(session, "resource_entries", client.execute(RESOURCE_ENTRIES_SCRIPT, vec![]))
+        .await?;
+    let page_url = result["page_url"].as_str().unwrap_or_default().to_string();
+    let entries = serde_json::from_value(result["entries"].clone())
+        .map_err(|e| WebDriverError::Serialization(e.to_string()))?;
+    Ok((page_url, entries))
+}
+
+/// Summarize the current page's weight by resource type
+pub async fn handle_get_page_weight(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let GetPageWeightParams { top, cold_load, session } = parse_params(arguments)?;
+
+    match client_manager.get_existing_client(session.session_id).await {
+        Ok((session, client)) => {
+            if cold_load && let Err(e) = cold_load::reload_cold(client_manager, &session, &client).await {
+                return Ok(error_response(format!("Failed to reload the page cold: {e}")));
+            }
+            match resource_entries(client_manager, &session, &client).await {
+                Ok((page_url, entries)) => {
+                    let mut weight = page_weight(&entries, top.unwrap_or(10));
+                    let mut message = format!(
+                        "Page weight of {page_url}: {} in {} request(s), {} transferred",
+                        format_bytes(weight["total_bytes"].as_u64().unwrap_or(0)),
+                        entries.len(),
+                        format_bytes(weight["transfer_bytes"].as_u64().unwrap_or(0)),
+                    );
+                    if let Some(by_type) = weight["by_type"].as_object() {
+                        let types: Vec<String> = by_type
+                            .iter()
+                            .map(|(kind, totals)| {
+                                let bytes = format_bytes(totals["total_bytes"].as_u64().unwrap_or(0));
+                                format!("{kind} {bytes} ({})", totals["requests"])
+                            })
+                            .collect();
+                        message.push_str(&format!("\n{}", types.join(", ")));
+                    }
+                    if let Some(heaviest) = weight["heaviest"].as_array() {
+                        for resource in heaviest {
+                            message.push_str(&format!(
+                                "\n  {} {} {}",
+                                format_bytes(resource["bytes"].as_u64().unwrap_or(0)),
+                                resource["type"].as_str().unwrap_or_default(),
+                                resource["url"].as_str().unwrap_or_default()
+                            ));
+                        }
+                    }
+                    // The browser drops entries once its buffer is full
+                    let resources = entries.iter().filter(|e| e.initiator_type != "navigation").count();
+                    let truncated = resources >= DEFAULT_RESOURCE_BUFFER;
+                    if truncated {
+                        message.push_str("\nResource timing buffer is full; later requests may be missing");
+                    }
+                    weight["truncated"] = json!(truncated);
+                    weight["page_url"] = json!(page_url);
+                    weight["session"] = json!(session);
+                    weight["cold_load"] = json!(cold_load);
+                    Ok(structured_response(message, weight))
+                }
+                Err(e) => Ok(error_response(format!("Failed to read resource timing: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Group the current page's third-party requests by domain, flag trackers and
+/// report the main-thread time their scripts blocked
+pub async fn handle_analyze_third_parties(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let AnalyzeThirdPartiesParams { session } = parse_params(arguments)?;
+
+    match client_manager.get_existing_client(session.session_id).await {
+        Ok((session, client)) => {
+            let collected = client_manager
+                .run_command(&session, "analyze_third_parties", client.execute_async(THIRD_PARTY_SCRIPT, vec![]))
+                .await;
+            let result = match collected {
+                Ok(result) => result,
+                Err(e) => return Ok(error_response(format!("Failed to analyze third parties: {e}"))),
+            };
+            let page_url = result["page_url"].as_str().unwrap_or_default();
+            let resources: Vec<ResourceEntry> = serde_json::from_value(result["resources"].clone()).unwrap_or_default();
+            let scripts: Option<Vec<ScriptTiming>> = serde_json::from_value(result["scripts"].clone()).ok();
+            let mut analysis = third_parties(page_url, &resources, scripts.as_deref());
+
+            let domains = analysis["domains"].as_array().cloned().unwrap_or_default();
+            let mut message = format!(
+                "{} of {} request(s) on {page_url} went to {} third-party domain(s), {}",
+                analysis["third_party_requests"],
+                analysis["total_requests"],
+                domains.len(),
+                format_bytes(analysis["third_party_bytes"].as_u64().unwrap_or(0)),
+            );
+            match analysis["third_party_blocking_ms"].as_f64() {
+                Some(ms) => message.push_str(&format!("; their scripts blocked the main thread for {ms} ms")),
+                None => message.push_str("; blocking time needs the Long Animation Frames API (Chrome/Edge 123+)"),
+            }
+            for domain in &domains {
+                message.push_str(&format!(
+                    "\n  {}: {} request(s), {}",
+                    domain["domain"].as_str().unwrap_or_default(),
+                    domain["requests"],
+                    format_bytes(domain["bytes"].as_u64().unwrap_or(0))
+                ));
+                if let Some(ms) = domain["blocking_ms"].as_f64().filter(|ms| *ms > 0.0) {
+                    message.push_str(&format!(", blocked {ms} ms"));
+                }
+                if let Some(category) = domain["tracker"].as_str() {
+                    message.push_str(&format!(" [tracker: {category}]"));
+                }
+            }
+            analysis["page_url"] = json!(page_url);
+            analysis["session"] = json!(session);
+            Ok(structured_response(message, analysis))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Load the echo endpoint and report the session's egress IP, request headers,
+/// languages, timezone and whether WebRTC exposes other addresses
+pub async fn handle_whoami_network(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    let WhoamiNetworkParams { echo_url, stun_server, webrtc_wait_ms, session } = parse_params(arguments)?;
+    let echo_url = echo_url.unwrap_or_else(|| client_manager.get_config().network_echo_url.clone());
+    if !url::Url::parse(&echo_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+        return Err(McpError::invalid_params(format!("Invalid echo_url '{echo_url}': use an http(s) URL"), None));
+    }
+    let stun_server = stun_server.unwrap_or_else(|| network_identity::DEFAULT_STUN_SERVER.to_string());
+    let wait_ms = webrtc_wait_ms.unwrap_or(network_identity::DEFAULT_WEBRTC_WAIT_MS).min(MAX_WEBRTC_WAIT_MS);
+
+    match client_manager.get_or_create_client(session.session_id).await {
+        Ok((session, client)) => {
+            let looking = look_up_identity(client_manager, &session, &client, &echo_url, &stun_server, wait_ms);
+            let (echoed, browser) = match cancellation.run_until_cancelled(looking).await {
+                Some(Ok(found)) => found,
+                Some(Err(e)) => return Ok(error_response(format!("Failed to check network identity: {e}"))),
+                None => return Ok(cancelled_response("Network identity check")),
+            };
+            let webrtc = WebRtcReport::from_candidates(&browser["webrtc"], echoed.ip.as_deref());
+            let mismatches = network_identity::mismatches(&echoed, &browser);
+
+            let languages: Vec<&str> =
+                browser["languages"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+            let mut message = format!(
+                "Network identity of session '{session}' via {echo_url}:\n  egress IP: {}",
+                echoed.ip.as_deref().unwrap_or("not reported by the endpoint")
+            );
+            if !echoed.geo.is_empty() {
+                let geo: Vec<String> =
+                    echoed.geo.iter().map(|(key, value)| format!("{key}={}", text_of(value))).collect();
+                message.push_str(&format!("\n  location: {}", geo.join(", ")));
+            }
+            message.push_str(&format!(
+                "\n  user agent: {}\n  languages: {}\n  timezone: {} (UTC{:+} min)",
+                browser["user_agent"].as_str().unwrap_or_default(),
+                languages.join(", "),
+                browser["timezone"].as_str().unwrap_or_default(),
+                browser["utc_offset_minutes"].as_i64().unwrap_or_default()
+            ));
+            message.push_str(&format!("\n  WebRTC: {}", webrtc.status.as_str()));
+            if !webrtc.public_ips.is_empty() || !webrtc.local_ips.is_empty() {
+                let ips = [webrtc.public_ips.clone(), webrtc.local_ips.clone()].concat();
+                message.push_str(&format!(" ({})", ips.join(", ")));
+            }
+            for mismatch in &mismatches {
+                message.push_str(&format!("\n  ⚠️ {mismatch}"));
+            }
+
+            Ok(structured_response(
+                message,
+                json!({
+                    "session": session,
+                    "echo_url": echo_url,
+                    "egress_ip": echoed.ip,
+                    "echoed": echoed,
+                    "browser": {
+                        "user_agent": browser["user_agent"],
+                        "languages": browser["languages"],
+                        "timezone": browser["timezone"],
+                        "utc_offset_minutes": browser["utc_offset_minutes"],
+                    },
+                    "webrtc": webrtc,
+                    "mismatches": mismatches,
+                }),
+            ))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Load `echo_url` in the session, read what it saw and what the browser says
+/// of itself, then go back to the page the session was on
+async fn look_up_identity(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    echo_url: &str,
+    stun_server: &str,
+    wait_ms: u64,
+) -> crate::Result<(EchoedRequest, Value)> {
+    let previous_url = client_manager.run_command(session, "get_current_url", client.current_url()).await.ok();
+    client_manager.navigate(session, client, echo_url).await?;
+    let body = client_manager
+        .run_command(session, "whoami_network", client.execute_async(ECHO_BODY_SCRIPT, vec![]))
+        .await?;
+    let echoed = EchoedRequest::parse(body.as_str().unwrap_or_default());
+    let identity_args = vec![json!(stun_server), json!(wait_ms)];
+    let browser = client_manager
+        .run_command(
+            session,
+            "whoami_network",
+            client.execute_async(network_identity::BROWSER_IDENTITY_SCRIPT, identity_args),
+        )
+        .await?;
+
+    if let Some(url) = previous_url.filter(|url| matches!(url.scheme(), "http" | "https") && url.as_str() != echo_url)
+        && let Err(e) = client_manager.navigate(session, client, url.as_str()).await
+    {
+        tracing::debug!("Failed to return session '{}' to {}: {}", session, url, e);
+    }
+    Ok((echoed, browser))
+}
+
+/// A JSON value as plain text, without the quotes of strings
+fn text_of(value: &Value) -> String {
+    value.as_str().map_or_else(|| value.to_string(), str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str, initiator_type: &str, transfer_size: u64, encoded_size: u64) -> ResourceEntry {
+        ResourceEntry {
+            url: url.to_string(),
+            initiator_type: initiator_type.to_string(),
+            transfer_size,
+            encoded_size,
+            decoded_size: encoded_size,
+            start_time: 0.0,
+            duration: 0.0,
+            render_blocking: false,
+        }
+    }
+
+    #[test]
+    fn test_page_weight_by_type() {
+        let entries = vec![
+            entry("https://a.test/", "navigation", 12_300, 12_000),
+            entry("https://a.test/app.js?v=3", "script", 250_300, 250_000),
+            entry("https://a.test/site.css", "link", 0, 40_000),
+            entry("https://cdn.test/inter.woff2", "css", 30_300, 30_000),
+            entry("https://a.test/hero", "img", 500_300, 500_000),
+            entry("https://api.test/items", "fetch", 0, 0),
+        ];
+        assert_eq!(entries[3].resource_type(), "font");
+        let weight = page_weight(&entries, 2);
+        assert_eq!(weight["requests"], 6);
+        assert_eq!(weight["transfer_bytes"], 793_200);
+        assert_eq!(weight["cached_requests"], 1);
+        assert_eq!(weight["unknown_size_requests"], 1);
+        assert_eq!(weight["by_type"]["css"], json!({ "requests": 1, "transfer_bytes": 0, "total_bytes": 40_000 }));
+        assert_eq!(weight["by_type"]["xhr"]["requests"], 1);
+        assert_eq!(weight["heaviest"][0]["type"], "img");
+        assert_eq!(weight["heaviest"][1]["url"], "https://a.test/app.js?v=3");
+        assert_eq!(weight["heaviest"].as_array().unwrap().len(), 2);
+        assert_eq!(format_bytes(1_536_000), "1.5 MB");
+    }
+
+    #[test]
+    fn test_third_parties_grouped_by_registrable_domain() {
+        assert_eq!(registrable_domain("cdn.shop.example.co.uk"), "example.co.uk");
+        assert_eq!(registrable_domain("www.google-analytics.com"), "google-analytics.com");
+        assert_eq!(registrable_domain("127.0.0.1"), "127.0.0.1");
+
+        let mut font = entry("https://fonts.gstatic.com/inter.woff2", "css", 30_000, 29_000);
+        font.render_blocking = true;
+        font.duration = 120.0;
+        let resources = vec![
+            entry("https://static.shop.test/app.js", "script", 100_000, 99_000),
+            entry("https://www.google-analytics.com/analytics.js", "script", 20_000, 19_000),
+            entry("https://www.google-analytics.com/collect?v=1", "beacon", 500, 0),
+            font,
+        ];
+        let script = |url: &str, duration, blocking| ScriptTiming { url: url.to_string(), duration, blocking };
+        let scripts = vec![
+            script("https://www.google-analytics.com/analytics.js", 80.0, 30.0),
+            script("https://static.shop.test/app.js", 200.0, 150.0),
+        ];
+        let analysis = third_parties("https://www.shop.test/cart", &resources, Some(&scripts));
+        assert_eq!(analysis["first_party"], "shop.test");
+        assert_eq!(analysis["third_party_requests"], 3);
+        assert_eq!(analysis["trackers"], json!(["google-analytics.com"]));
+        assert_eq!(analysis["third_party_blocking_ms"], 30.0);
+        assert_eq!(analysis["first_party_blocking_ms"], 150.0);
+        assert_eq!(analysis["domains"][0]["domain"], "gstatic.com");
+        assert_eq!(analysis["domains"][0]["render_blocking_ms"], 120.0);
+        assert_eq!(analysis["domains"][1]["tracker"], "analytics");
+        assert_eq!(analysis["domains"][1]["types"], json!({ "js": 1, "xhr": 1 }));
+
+        let unmeasured = third_parties("https://www.shop.test/", &resources, None);
+        assert_eq!(unmeasured["third_party_blocking_ms"], Value::Null);
+    }
+}
@@ -1,22 +1,140 @@
 //! Page content handlers
 //!
 //! Handles page-level operations:
-//! - Getting page title and source
-//! - Getting element text
-//! - Taking screenshots
-//! - Executing JavaScript
-//! - Resizing browser window
+//! - Getting page title and source, and a compact summary of the page
+//! - Getting element text, of one match or all of them, or the rendered text of the whole page
+//! - Taking screenshots (single, multi-viewport and highlighted) and listing saved artifacts
+//! - Executing JavaScript, synchronous or async, with element handles as arguments
+//! - Resizing, moving, maximizing, minimizing and fullscreening the browser window
+//! - Reporting popups and lifecycle events of the session's pages
+
+use std::time::Duration;
 
 use base64::{Engine as _, engine::general_purpose};
-use fantoccini::Locator;
+use fantoccini::{Client, Locator};
 use rmcp::{ErrorData as McpError, model::{CallToolResult, Content}};
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
 
 use crate::{
-    ClientManager,
-    tools::{error_response, success_response},
+    ClientManager, ErrorCode, PAGE_EVENT_TYPES, overlays, page_events, popups, text,
+    screenshots::{self, DEFAULT_VIEWPORTS, Viewport},
+    tools::{
+        coded_error_response, error_response, error_response_with,
+        params::{
+            DismissOverlaysParams, GetPageEventsParams, GetPopupEventsParams, GetTextParams, ScreenshotOnChangeParams,
+            SessionParams, GetPageTextParams, SetWindowPositionParams, SummarizePageParams,
+        },
+        structured_response, success_response,
+    },
 };
-use super::extract_session_id;
+use super::{extract_session_id, parse_params, session_error_response};
+
+/// Matches `get_text` with `all` reads at most; the total is still reported
+const MAX_TEXT_MATCHES: u64 = 1000;
+
+/// Rendered text of up to `arguments[2]` elements matching `arguments[0]`, scoped to
+/// the first match of `arguments[1]` when given
+const ALL_TEXTS_SCRIPT: &str = r#"
+    const [selector, parentSelector, limit] = arguments;
+    try {
+        const root = parentSelector ? document.querySelector(parentSelector) : document;
+        if (!root) {
+            return { error: 'no such element' };
+        }
+        const matches = root.querySelectorAll(selector);
+        const texts = Array.from(matches).slice(0, limit).map(el => (el.innerText ?? el.textContent ?? '').trim());
+        return { total: matches.length, texts };
+    } catch (e) {
+        return { error: String(e) };
+    }
+"#;
+
+/// Title, description, heading outline, main text excerpt, form and link
+/// counts and detected frameworks of the page. Takes the excerpt length and
+/// the number of headings to list.
+const SUMMARIZE_PAGE_SCRIPT: &str = r#"
+    const [excerptLength, maxHeadings] = arguments;
+    const clean = (text) => (text || '').replace(/\s+/g, ' ').trim();
+    const meta = (selector) => clean(document.querySelector(selector)?.content);
+
+    const headings = Array.from(document.querySelectorAll('h1, h2, h3'))
+        .filter(h => h.offsetParent !== null || h.getClientRects().length > 0)
+        .map(h => ({ level: Number(h.tagName[1]), text: clean(h.innerText).slice(0, 120) }))
+        .filter(h => h.text);
+
+    const main = [['main', 'main'], ['[role=main]', 'role=main'], ['article', 'article']]
+        .map(([selector, name]) => [document.querySelector(selector), name])
+        .find(([el]) => el && clean(el.innerText)) || [document.body, 'body'];
+    const mainText = clean(main[0]?.innerText);
+
+    const links = Array.from(document.querySelectorAll('a[href]'));
+    const external = links.filter(a => {
+        try { return new URL(a.href, location.href).origin !== location.origin; } catch (e) { return false; }
+    }).length;
+
+    const frameworks = [];
+    const found = (name, version) => frameworks.push(version ? { name, version: String(version) } : { name });
+    const hasKey = (el, prefix) => el && Object.keys(el).some(key => key.startsWith(prefix));
+    const root = document.getElementById('root') || document.getElementById('__next') || document.body.firstElementChild;
+    if (window.__NEXT_DATA__ || document.getElementById('__next')) found('Next.js', window.next?.version);
+    if (window.__NUXT__ || document.getElementById('__nuxt')) found('Nuxt');
+    if (document.getElementById('___gatsby')) found('Gatsby');
+    if (window.__remixContext) found('Remix');
+    if (window.React || document.querySelector('[data-reactroot]') || hasKey(root, '__react')) found('React', window.React?.version);
+    if (window.Vue || window.__VUE__ || document.querySelector('[data-v-app]') || root?.__vue_app__ || root?.__vue__) {
+        found('Vue', window.Vue?.version || root?.__vue_app__?.version);
+    }
+    const ng = document.querySelector('[ng-version]');
+    if (ng) found('Angular', ng.getAttribute('ng-version'));
+    else if (window.angular) found('AngularJS', window.angular.version?.full);
+    if (document.querySelector('[class*="svelte-"]')) found('Svelte');
+    if (window.Ember) found('Ember', window.Ember.VERSION);
+    if (window.Alpine) found('Alpine.js', window.Alpine.version);
+    if (window.jQuery) found('jQuery', window.jQuery.fn?.jquery);
+    const generator = meta('meta[name="generator"]');
+    if (/wordpress/i.test(generator)) found('WordPress');
+    if (window.Shopify) found('Shopify');
+
+    return {
+        url: location.href,
+        title: document.title,
+        description: meta('meta[name="description"]') || meta('meta[property="og:description"]') || null,
+        lang: document.documentElement.lang || null,
+        outline: headings.slice(0, maxHeadings),
+        headings_total: headings.length,
+        main: {
+            source: main[1],
+            text: mainText.slice(0, excerptLength),
+            truncated: mainText.length > excerptLength,
+            words: mainText ? mainText.split(' ').length : 0,
+        },
+        forms: document.forms.length,
+        links: { total: links.length, external },
+        images: document.images.length,
+        frameworks,
+    };
+"#;
+
+/// Rendered text of the first match of `arguments[0]`, or of the whole body;
+/// `null` when nothing matches
+const PAGE_TEXT_SCRIPT: &str = r#"
+    const [selector] = arguments;
+    const root = selector ? document.querySelector(selector) : document.body;
+    if (!root) {
+        return null;
+    }
+    return root.innerText ?? root.textContent ?? '';
+"#;
+
+/// Characters `get_page_text` returns when the call gives no `max_length`
+const DEFAULT_PAGE_TEXT_LENGTH: usize = 20_000;
+
+/// Viewports `responsive_screenshots` captures in one call at most
+const MAX_RESPONSIVE_VIEWPORTS: usize = 10;
+/// Pause after each resize so responsive layouts settle before capture
+const DEFAULT_SETTLE_MS: u64 = 500;
+/// How long `screenshot_on_change` watches when the call gives no `wait_timeout`
+const DEFAULT_CHANGE_WAIT_SECS: f64 = 10.0;
 
 /// Get the current page title
 pub async fn handle_get_title(
@@ -25,54 +143,228 @@ pub async fn handle_get_title(
 ) -> Result<CallToolResult, McpError> {
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
-        Ok((session, client)) => match client.title().await {
-            Ok(title) => Ok(success_response(format!(
-                "Page title: {title} (session: {session})"
-            ))),
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => match client_manager.run_command(&session, "get_title", client.title()).await {
+            Ok(title) => Ok(structured_response(
+                format!("Page title: {title} (session: {session})"),
+                json!({ "session": session, "title": title }),
+            )),
             Err(e) => Ok(error_response(format!("Failed to get title: {e}"))),
         },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
-/// Get text content of an element
+/// Get text content of an element, or of every matching element with `all`
 pub async fn handle_get_text(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
 ) -> Result<CallToolResult, McpError> {
-    let selector = arguments
-        .as_ref()
-        .and_then(|args| args.get("selector"))
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| McpError::invalid_params("selector parameter required", None))?;
+    let params: GetTextParams = parse_params(arguments)?;
+    let selector = params.selector.as_str();
+    let parent_selector = params.parent_selector.as_deref();
 
-    let session_id = extract_session_id(arguments);
+    let (session, client) = match client_manager.get_existing_client(params.session.session_id.clone()).await {
+        Ok(found) => found,
+        Err(e) => return Ok(session_error_response(e)),
+    };
+    let scope_msg = parent_selector.map(|parent| format!(" within '{parent}'")).unwrap_or_default();
 
-    match client_manager.get_or_create_client(session_id).await {
-        Ok((session, client)) => match client.find(Locator::Css(selector)).await {
-            Ok(element) => match element.text().await {
-                Ok(text) => Ok(success_response(format!(
-                    "Element text: {text} (session: {session})"
-                ))),
-                Err(e) => Ok(error_response(format!("Failed to get element text: {e}"))),
-            },
-            Err(e) => Ok(error_response(format!(
-                "Failed to find element {selector}: {e}"
-            ))),
+    if params.all {
+        // One script round trip reads every match, instead of a text command per element
+        let collected = client_manager
+            .run_command(
+                &session,
+                "get_text",
+                client.execute(
+                    ALL_TEXTS_SCRIPT,
+                    vec![json!(selector), json!(parent_selector), json!(MAX_TEXT_MATCHES)],
+                ),
+            )
+            .await;
+        let result = match collected {
+            Ok(result) => result,
+            Err(e) => return Ok(error_response(format!("Failed to get text of '{selector}': {e}"))),
+        };
+        if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+            return Ok(error_response_with(
+                match parent_selector {
+                    Some(parent) => format!("Parent element '{parent}' not found: {error}"),
+                    None => format!("Failed to get text of '{selector}': {error}"),
+                },
+                json!({ "selector": selector, "parent_selector": parent_selector, "session_id": session }),
+            ));
+        }
+        let (texts, total, any_truncated) = tidy_texts(&params, &result);
+        return Ok(structured_response(
+            texts_summary(&texts, total, &format!("'{selector}'{scope_msg}"), &session),
+            json!({
+                "session": session,
+                "selector": selector,
+                "parent_selector": parent_selector,
+                "count": texts.len(),
+                "total": total,
+                "truncated": any_truncated,
+                "texts": texts,
+            }),
+        ));
+    }
+
+    let found = match parent_selector {
+        Some(parent) => match client_manager
+            .run_command(&session, "find_element", client.find(Locator::Css(parent)))
+            .await
+        {
+            Ok(container) => {
+                client_manager.run_command(&session, "find_element", container.find(Locator::Css(selector))).await
+            }
+            Err(e) => {
+                return Ok(error_response_with(
+                    format!("Failed to find parent element {parent}: {e}"),
+                    json!({ "selector": parent, "session_id": session }),
+                ));
+            }
         },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        None => client_manager.run_command(&session, "find_element", client.find(Locator::Css(selector))).await,
+    };
+    match found {
+        Ok(element) => match client_manager.run_command(&session, "get_text", element.text()).await {
+            Ok(text) => {
+                let (text, truncated) = tidy_text(&params, &text);
+                Ok(structured_response(
+                    format!("Element text: {text} (session: {session})"),
+                    json!({
+                        "session": session,
+                        "selector": selector,
+                        "parent_selector": parent_selector,
+                        "text": text,
+                        "truncated": truncated,
+                    }),
+                ))
+            }
+            Err(e) => Ok(error_response(format!("Failed to get element text: {e}"))),
+        },
+        Err(e) => Ok(error_response_with(
+            format!("Failed to find element {selector}{scope_msg}: {e}"),
+            json!({ "selector": selector, "parent_selector": parent_selector, "session_id": session }),
+        )),
+    }
+}
+
+/// `text` with whitespace collapsed if asked, cut to `max_length` characters,
+/// and whether it was cut
+fn tidy_text(params: &GetTextParams, text: &str) -> (String, bool) {
+    let text = if params.normalize_whitespace { text::normalize_whitespace(text) } else { text.to_string() };
+    match params.max_length {
+        Some(max_length) => {
+            let (kept, truncated) = text::truncate_chars(&text, max_length);
+            (kept.to_string(), truncated)
+        }
+        None => (text, false),
     }
 }
 
+/// Tidied texts of an `ALL_TEXTS_SCRIPT` result, the number of matches on the
+/// page, and whether any text was cut
+fn tidy_texts(params: &GetTextParams, result: &Value) -> (Vec<String>, u64, bool) {
+    let total = result.get("total").and_then(|v| v.as_u64()).unwrap_or(0);
+    let mut any_truncated = false;
+    let texts = result
+        .get("texts")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .map(|text| {
+            let (text, truncated) = tidy_text(params, text.as_str().unwrap_or_default());
+            any_truncated |= truncated;
+            text
+        })
+        .collect();
+    (texts, total, any_truncated)
+}
+
+/// Numbered preview of the texts of `matched` elements
+fn texts_summary(texts: &[String], total: u64, matched: &str, session: &str) -> String {
+    let mut summary = format!("Text of {} element(s) matching {} (session: {}):\n", texts.len(), matched, session);
+    for (i, text) in texts.iter().enumerate() {
+        summary.push_str(&format!("{}. {}\n", i + 1, text::preview(text, 100)));
+    }
+    if (texts.len() as u64) < total {
+        summary.push_str(&format!("... only the first {} of {} matches are read\n", texts.len(), total));
+    }
+    summary
+}
+
+/// Rendered text of the page, or of the first element matching `selector`, as
+/// the user sees it: hidden elements, scripts and markup left out
+pub async fn handle_get_page_text(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let GetPageTextParams { selector, max_length, normalize_whitespace, session } = parse_params(arguments)?;
+    let max_length = max_length.unwrap_or(DEFAULT_PAGE_TEXT_LENGTH);
+
+    let (session, client) = match client_manager.get_existing_client(session.session_id).await {
+        Ok(found) => found,
+        Err(e) => return Ok(session_error_response(e)),
+    };
+    let script = client.execute(PAGE_TEXT_SCRIPT, vec![json!(selector)]);
+    let text = match client_manager.run_command(&session, "get_page_text", script).await {
+        Ok(Value::String(text)) => text,
+        Ok(_) => {
+            let selector = selector.as_deref().unwrap_or("body");
+            return Ok(error_response_with(
+                format!("Failed to find element {selector}: no such element"),
+                json!({ "selector": selector, "session_id": session }),
+            ));
+        }
+        Err(e) => return Ok(error_response(format!("Failed to get page text: {e}"))),
+    };
+
+    let text = if normalize_whitespace { text::normalize_whitespace(&text) } else { text };
+    let length = text.chars().count();
+    let (text, truncated) = text::truncate_chars(&text, max_length);
+    let scope = selector.as_deref().map(|selector| format!(" of '{selector}'")).unwrap_or_default();
+    let mut message = format!("Page text{scope}, {length} character(s) (session: {session})");
+    if truncated {
+        message.push_str(&format!(", cut to the first {max_length}"));
+    }
+    message.push_str(&format!(":\n\n{text}"));
+    Ok(structured_response(
+        message,
+        json!({
+            "session": session,
+            "selector": selector,
+            "text": text,
+            "length": length,
+            "truncated": truncated,
+        }),
+    ))
+}
+
 /// Execute JavaScript in the page context
 pub async fn handle_execute_script(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    run_script(client_manager, arguments, false).await
+}
+
+/// Execute JavaScript that reports its result through the callback passed as its last argument
+pub async fn handle_execute_async_script(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    run_script(client_manager, arguments, true).await
+}
+
+/// Shared by `execute_script` and `execute_async_script`: `args` become the script's
+/// `arguments`, with `{"element": "el-N"}` handles from `find_element(s)` replaced by the
+/// elements themselves
+async fn run_script(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    asynchronous: bool,
 ) -> Result<CallToolResult, McpError> {
     let script = arguments
         .as_ref()
@@ -80,19 +372,83 @@ pub async fn handle_execute_script(
         .and_then(|v| v.as_str())
         .ok_or_else(|| McpError::invalid_params("script parameter required", None))?;
 
+    let script_args = match arguments.as_ref().and_then(|args| args.get("args")) {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(items)) => items.clone(),
+        Some(_) => return Err(McpError::invalid_params("args must be an array", None)),
+    };
+
     let session_id = extract_session_id(arguments);
+    let operation = if asynchronous { "execute_async_script" } else { "execute_script" };
 
     match client_manager.get_or_create_client(session_id).await {
-        Ok((session, client)) => match client.execute(script, vec![]).await {
-            Ok(result) => Ok(success_response(format!(
-                "Script result: {result:?} (session: {session})"
-            ))),
-            Err(e) => Ok(error_response(format!("Failed to execute script: {e}"))),
-        },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Ok((session, client)) => {
+            let script_args = match client_manager.resolve_script_arguments(&session, script_args) {
+                Ok(script_args) => script_args,
+                Err(e) => {
+                    return Ok(coded_error_response(ErrorCode::InvalidArgument, e, json!({ "session": session })));
+                }
+            };
+            let executed = if asynchronous {
+                client_manager.run_command(&session, operation, client.execute_async(script, script_args)).await
+            } else {
+                client_manager.run_command(&session, operation, client.execute(script, script_args)).await
+            };
+            match executed {
+                Ok(result) => Ok(success_response(format!(
+                    "Script result: {result:?} (session: {session})"
+                ))),
+                Err(e) if e.to_string().to_lowercase().contains("stale element reference") => {
+                    Ok(coded_error_response(
+                        ErrorCode::ElementNotFound,
+                        format!(
+                            "Failed to execute script: an element handle in args is stale (the node left the page); \
+                             find the element again: {e}"
+                        ),
+                        json!({ "session": session }),
+                    ))
+                }
+                Err(e) => Ok(error_response(format!("Failed to execute script: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Check a capture, save it when asked and keep it as a resource. Returns the text
+/// describing it and, with `inline`, the image itself.
+async fn screenshot_content(
+    client_manager: &ClientManager,
+    session: &str,
+    png_data: Vec<u8>,
+    save_path: Option<&str>,
+    inline: bool,
+) -> Result<(String, Option<Content>), CallToolResult> {
+    // Validate that we have valid PNG data
+    if png_data.is_empty() {
+        return Err(error_response("Screenshot data is empty".to_string()));
     }
+
+    // Check if data starts with PNG signature
+    if png_data.len() < 4 || &png_data[0..4] != b"\x89PNG" {
+        return Err(error_response("Screenshot data is not valid PNG format".to_string()));
+    }
+
+    // Save under the artifacts directory if a file name is provided
+    let summary = if let Some(name) = save_path {
+        let path = client_manager
+            .get_artifact_store()
+            .write(None, name, png_data.clone())
+            .await
+            .map_err(|e| error_response(format!("Failed to save screenshot: {e}")))?;
+        format!("Screenshot saved to: {} ({} bytes)", path.display(), png_data.len())
+    } else {
+        format!("Screenshot taken ({} bytes)", png_data.len())
+    };
+
+    let image = inline.then(|| Content::image(general_purpose::STANDARD.encode(&png_data), "image/png"));
+    let uri = client_manager.get_resource_store().add_screenshot(session, png_data);
+    Ok((format!("{summary}\nResource: {uri}"), image))
 }
 
 /// Take a screenshot of the current page
@@ -107,59 +463,427 @@ pub async fn handle_screenshot(
         .and_then(|args| args.get("save_path"))
         .and_then(|v| v.as_str());
 
+    let inline = arguments
+        .as_ref()
+        .and_then(|args| args.get("inline"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let highlights = arguments
+        .as_ref()
+        .and_then(|args| args.get("highlight"))
+        .map(screenshots::parse_highlights)
+        .transpose()
+        .map_err(|e| McpError::invalid_params(e, None))?;
+
     match client_manager.get_or_create_client(session_id).await {
-        Ok((_session, client)) => match client.screenshot().await {
-            Ok(png_data) => {
-                // Validate that we have valid PNG data
-                if png_data.is_empty() {
-                    return Ok(error_response("Screenshot data is empty".to_string()));
+        Ok((session, client)) => {
+            let highlighted = match &highlights {
+                Some(specs) => match screenshots::highlight(
+                    client_manager,
+                    &session,
+                    &client,
+                    specs,
+                    screenshots::MAX_HIGHLIGHTS_PER_SELECTOR,
+                )
+                .await
+                {
+                    Ok(result) => Some(result),
+                    Err(e) => return Ok(error_response(format!("Failed to highlight elements: {e}"))),
+                },
+                None => None,
+            };
+            let captured = client_manager.run_command(&session, "screenshot", client.screenshot()).await;
+            if highlighted.is_some()
+                && let Err(e) = screenshots::clear_highlights(client_manager, &session, &client).await
+            {
+                tracing::warn!("Failed to remove screenshot highlights: {}", e);
+            }
+
+            match captured {
+                Ok(png_data) => {
+                    let saved = screenshot_content(client_manager, &session, png_data, save_path, inline).await;
+                    let (mut text, image) = match saved {
+                        Ok(parts) => parts,
+                        Err(response) => return Ok(response),
+                    };
+                    if let Some(result) = &highlighted {
+                        text.push_str(&highlight_summary(result));
+                    }
+                    let mut content = vec![Content::text(text)];
+                    content.extend(image);
+
+                    Ok(CallToolResult {
+                        content,
+                        is_error: Some(false),
+                    })
                 }
+                Err(e) => Ok(error_response(format!("Failed to take screenshot: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Watch an element and capture the page the moment it changes, or when the wait runs out
+pub async fn handle_screenshot_on_change(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let ScreenshotOnChangeParams { selector, wait_timeout, save_path, inline, session } = parse_params(arguments)?;
+    let wait = crate::waits::clamped_wait(wait_timeout.unwrap_or(DEFAULT_CHANGE_WAIT_SECS));
+
+    match client_manager.get_or_create_client(session.session_id).await {
+        Ok((session, client)) => {
+            let change = match screenshots::wait_for_change(client_manager, &session, &client, &selector, wait).await {
+                Ok(change) => change,
+                Err(e) => return Ok(error_response(format!("Failed to watch {selector}: {e}"))),
+            };
+            let captured = client_manager.run_command(&session, "screenshot", client.screenshot()).await;
+            if let Err(e) = screenshots::stop_watching(client_manager, &session, &client).await {
+                tracing::debug!("Failed to stop watching {} in session '{}': {}", selector, session, e);
+            }
+
+            match captured {
+                Ok(png_data) => {
+                    let saved =
+                        screenshot_content(client_manager, &session, png_data, save_path.as_deref(), inline).await;
+                    let (text, image) = match saved {
+                        Ok(parts) => parts,
+                        Err(response) => return Ok(response),
+                    };
+                    let outcome = change_outcome(&selector, change.as_ref(), wait);
+                    let mut result = structured_response(
+                        format!("{outcome} (session: {session})\n{text}"),
+                        json!({ "session": session, "changed": change.is_some(), "change": change }),
+                    );
+                    result.content.extend(image);
+                    Ok(result)
+                }
+                Err(e) => Ok(error_response(format!("Failed to take screenshot: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// What `screenshot_on_change` waited for: the change it saw, or the timeout
+fn change_outcome(selector: &str, change: Option<&Value>, wait: Duration) -> String {
+    match change {
+        Some(change) => format!("Captured after {selector} changed ({})", change["kind"].as_str().unwrap_or("changed")),
+        None => format!("No change to {selector} within {:.1}s; captured anyway", wait.as_secs_f64()),
+    }
+}
+
+/// Draw labeled boxes around elements, left on the page until cleared or replaced
+pub async fn handle_highlight_elements(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let session_id = extract_session_id(arguments);
+
+    let clear = arguments
+        .as_ref()
+        .and_then(|args| args.get("clear"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let specs = match arguments.as_ref().and_then(|args| args.get("selectors")) {
+        Some(value) => screenshots::parse_highlights(value).map_err(|e| McpError::invalid_params(e, None))?,
+        None if clear => Vec::new(),
+        None => return Err(McpError::invalid_params("selectors parameter required", None)),
+    };
+
+    let max_per_selector = arguments
+        .as_ref()
+        .and_then(|args| args.get("max_per_selector"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(screenshots::MAX_HIGHLIGHTS_PER_SELECTOR)
+        .min(screenshots::MAX_HIGHLIGHTS_PER_SELECTOR);
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => {
+            let specs = if clear { &[][..] } else { &specs[..] };
+            match screenshots::highlight(client_manager, &session, &client, specs, max_per_selector).await {
+                Ok(result) if specs.is_empty() => Ok(structured_response(
+                    format!("Highlights cleared (session: {session})"),
+                    json!({ "session": session, "highlighted": result.get("highlighted"), "cleared": true }),
+                )),
+                Ok(result) => Ok(structured_response(
+                    format!(
+                        "Highlights drawn (session: {session}); they stay until cleared with clear=true or replaced by another call{}",
+                        highlight_summary(&result)
+                    ),
+                    json!({
+                        "session": session,
+                        "highlighted": result.get("highlighted"),
+                        "missing": result.get("missing"),
+                    }),
+                )),
+                Err(e) => Ok(error_response(format!("Failed to highlight elements: {e}"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// One line per highlighted box and per selector that matched nothing visible
+fn highlight_summary(result: &Value) -> String {
+    let highlighted = result.get("highlighted").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let mut summary = format!("\nHighlighted {} element(s):", highlighted.len());
+    for element in &highlighted {
+        summary.push_str(&format!(
+            "\n  [{}] {} #{} at ({}, {}) {}x{}{} \"{}\"",
+            element.get("label").and_then(|v| v.as_str()).unwrap_or_default(),
+            element.get("selector").and_then(|v| v.as_str()).unwrap_or_default(),
+            element.get("index").and_then(|v| v.as_u64()).unwrap_or(0) + 1,
+            element.get("x").and_then(|v| v.as_i64()).unwrap_or(0),
+            element.get("y").and_then(|v| v.as_i64()).unwrap_or(0),
+            element.get("width").and_then(|v| v.as_i64()).unwrap_or(0),
+            element.get("height").and_then(|v| v.as_i64()).unwrap_or(0),
+            if element.get("in_viewport").and_then(|v| v.as_bool()).unwrap_or(true) {
+                ""
+            } else {
+                " (outside viewport)"
+            },
+            text::preview(element.get("text").and_then(|v| v.as_str()).unwrap_or_default(), 60)
+        ));
+    }
+    for missing in result.get("missing").and_then(|v| v.as_array()).into_iter().flatten() {
+        summary.push_str(&format!(
+            "\n  not highlighted: {} ({})",
+            missing.get("selector").and_then(|v| v.as_str()).unwrap_or_default(),
+            missing.get("reason").and_then(|v| v.as_str()).unwrap_or_default()
+        ));
+    }
+    summary
+}
+
+/// Capture the page at several viewport sizes, optionally combined into one image
+pub async fn handle_responsive_screenshots(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let session_id = extract_session_id(arguments);
+
+    let viewports: Result<Vec<Viewport>, String> = match arguments
+        .as_ref()
+        .and_then(|args| args.get("viewports"))
+        .and_then(|v| v.as_array())
+    {
+        Some(values) if !values.is_empty() => values.iter().map(Viewport::parse).collect(),
+        _ => DEFAULT_VIEWPORTS.iter().map(|name| Viewport::parse(&json!(name))).collect(),
+    };
+    let viewports = viewports.map_err(|e| McpError::invalid_params(e, None))?;
+    if viewports.len() > MAX_RESPONSIVE_VIEWPORTS {
+        return Err(McpError::invalid_params(
+            format!("At most {MAX_RESPONSIVE_VIEWPORTS} viewports can be captured in one call"),
+            None,
+        ));
+    }
+
+    let url = arguments
+        .as_ref()
+        .and_then(|args| args.get("url"))
+        .and_then(|v| v.as_str());
+
+    let settle = Duration::from_millis(
+        arguments
+            .as_ref()
+            .and_then(|args| args.get("settle_ms"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_SETTLE_MS)
+            .min(10_000),
+    );
+
+    let stitch = arguments
+        .as_ref()
+        .and_then(|args| args.get("stitch"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let inline = arguments
+        .as_ref()
+        .and_then(|args| args.get("inline"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
 
-                // Check if data starts with PNG signature
-                if png_data.len() < 4 || &png_data[0..4] != b"\x89PNG" {
-                    return Ok(error_response("Screenshot data is not valid PNG format".to_string()));
+    let save_prefix = arguments
+        .as_ref()
+        .and_then(|args| args.get("save_prefix"))
+        .and_then(|v| v.as_str());
+
+    match client_manager.get_or_create_client(session_id).await {
+        Ok((session, client)) => {
+            if let Some(url) = url
+                && let Err(e) = client_manager.navigate(&session, &client, url).await
+            {
+                return Ok(error_response(format!("Failed to navigate to {url}: {e}")));
+            }
+
+            let original_size = client.get_window_size().await.ok();
+
+            let mut captures = Vec::with_capacity(viewports.len());
+            let mut failure = None;
+            for viewport in &viewports {
+                let captured: crate::Result<((u32, u32), Vec<u8>)> = async {
+                    let reached =
+                        screenshots::set_viewport(client_manager, &session, &client, viewport.width, viewport.height)
+                            .await?;
+                    tokio::time::sleep(settle).await;
+                    let png_data = client_manager.run_command(&session, "screenshot", client.screenshot()).await?;
+                    Ok((reached, png_data))
                 }
+                .await;
+                match captured {
+                    Ok((reached, png_data)) => captures.push((viewport, reached, png_data)),
+                    Err(e) => {
+                        failure = Some(format!("Failed to capture '{}': {e}", viewport.label));
+                        break;
+                    }
+                }
+            }
+
+            if let Some((width, height)) = original_size
+                && let Err(e) = client.set_window_size(width as u32, height as u32).await
+            {
+                tracing::warn!("Failed to restore window size after responsive screenshots: {}", e);
+            }
+            if let Some(failure) = failure {
+                return Ok(error_response(failure));
+            }
 
-                // Save to disk if path is provided
-                if let Some(path) = save_path {
-                    match std::fs::write(path, &png_data) {
-                        Ok(_) => {
-                            // Also return the image data for display
-                            let base64_data = general_purpose::STANDARD.encode(&png_data);
-                            Ok(CallToolResult {
-                                content: vec![
-                                    Content::text(format!("Screenshot saved to: {} ({} bytes)", path, png_data.len())),
-                                    Content::image(
-                                        base64_data,
-                                        "image/png",
-                                    )
-                                ],
-                                is_error: Some(false),
-                            })
+            let artifacts = client_manager.get_artifact_store();
+            let mut summary = format!("Captured {} viewport(s) (session: {}):", captures.len(), session);
+            let mut content = Vec::new();
+            let mut images = Vec::with_capacity(captures.len());
+            let mut encoded = Vec::with_capacity(captures.len());
+            for (viewport, (width, height), png_data) in captures {
+                let saved = match save_prefix {
+                    Some(prefix) => {
+                        let name = format!("{prefix}-{}.png", viewport.label);
+                        match artifacts.write(Some("responsive"), &name, png_data.clone()).await {
+                            Ok(path) => Some(path.display().to_string()),
+                            Err(e) => return Ok(error_response(format!("Failed to save screenshot: {e}"))),
                         }
-                        Err(e) => Ok(error_response(format!("Failed to save screenshot to {path}: {e}"))),
                     }
-                } else {
-                    // Just return the image data
-                    let base64_data = general_purpose::STANDARD.encode(&png_data);
-                    Ok(CallToolResult {
-                        content: vec![
-                            Content::text(format!("Screenshot taken ({} bytes)", png_data.len())),
-                            Content::image(
-                                base64_data,
-                                "image/png",
-                            )
-                        ],
-                        is_error: Some(false),
-                    })
+                    None => None,
+                };
+                let base64_data = general_purpose::STANDARD.encode(&png_data);
+                let size_bytes = png_data.len();
+                let uri = client_manager.get_resource_store().add_screenshot(&session, png_data);
+
+                summary.push_str(&format!(
+                    "\n- {} ({}x{} requested, {}x{} viewport): {}",
+                    viewport.label, viewport.width, viewport.height, width, height, uri
+                ));
+                if let Some(path) = &saved {
+                    summary.push_str(&format!(" saved to {path}"));
+                }
+                images.push(json!({
+                    "label": viewport.label,
+                    "requested": { "width": viewport.width, "height": viewport.height },
+                    "viewport": { "width": width, "height": height },
+                    "resource": uri,
+                    "path": saved,
+                    "size_bytes": size_bytes,
+                }));
+                if inline && !stitch {
+                    content.push(Content::text(viewport.label.clone()));
+                    content.push(Content::image(base64_data.clone(), "image/png"));
                 }
+                encoded.push((viewport.label.clone(), base64_data));
             }
-            Err(e) => Ok(error_response(format!("Failed to take screenshot: {e}"))),
-        },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+
+            let mut comparison = json!(null);
+            if stitch {
+                match screenshots::stitch(client_manager, &session, &client, &encoded).await {
+                    Ok(data) => match general_purpose::STANDARD.decode(&data) {
+                        Ok(png_data) => {
+                            let saved = match save_prefix {
+                                Some(prefix) => artifacts
+                                    .write(Some("responsive"), &format!("{prefix}-comparison.png"), png_data.clone())
+                                    .await
+                                    .map(|path| path.display().to_string())
+                                    .ok(),
+                                None => None,
+                            };
+                            let uri = client_manager.get_resource_store().add_screenshot(&session, png_data);
+                            summary.push_str(&format!("\nComparison image: {uri}"));
+                            if let Some(path) = &saved {
+                                summary.push_str(&format!(" saved to {path}"));
+                            }
+                            comparison = json!({ "resource": uri, "path": saved });
+                            if inline {
+                                content.push(Content::image(data, "image/png"));
+                            }
+                        }
+                        Err(e) => summary.push_str(&format!("\nCould not build the comparison image: {e}")),
+                    },
+                    Err(e) => summary.push_str(&format!("\nCould not build the comparison image: {e}")),
+                }
+            }
+
+            let data = json!({
+                "session": session,
+                "images": images,
+                "comparison": comparison,
+            });
+            let mut result = structured_response(summary, data);
+            result.content.extend(content);
+            Ok(result)
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// List files saved under the artifacts directory
+pub async fn handle_list_artifacts(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let artifacts = client_manager.get_artifact_store();
+
+    let run = arguments
+        .as_ref()
+        .and_then(|args| args.get("run"))
+        .and_then(|v| v.as_str())
+        .map(|run| if run == "current" { artifacts.run_id() } else { run });
+
+    let limit = arguments
+        .as_ref()
+        .and_then(|args| args.get("limit"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(100) as usize;
+
+    let mut listed = match artifacts.list(run).await {
+        Ok(listed) => listed,
+        Err(e) => return Ok(error_response(format!("Failed to list artifacts: {e}"))),
+    };
+    let total = listed.len();
+    listed.truncate(limit);
+
+    let root = artifacts.root().display().to_string();
+    let mut text = format!("{total} artifact(s) in {root} (current run: {})", artifacts.run_id());
+    for artifact in &listed {
+        text.push_str(&format!(
+            "\n{} ({} bytes, {})",
+            artifact.path, artifact.size_bytes, artifact.modified
+        ));
     }
+    if total > listed.len() {
+        text.push_str(&format!("\n... {} more", total - listed.len()));
+    }
+
+    Ok(structured_response(
+        text,
+        json!({
+            "root": root,
+            "current_run": artifacts.run_id(),
+            "total": total,
+            "artifacts": listed,
+        }),
+    ))
 }
 
 /// Resize the browser window
@@ -207,12 +931,292 @@ pub async fn handle_resize_window(
             }
             Err(e) => Ok(error_response(format!("Failed to resize window: {e}"))),
         },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Window size and position plus the page's viewport, reported by `get_window_size`
+/// and after every window state change
+const VIEWPORT_SCRIPT: &str = r#"
+    return {
+        width: window.innerWidth,
+        height: window.innerHeight,
+        device_pixel_ratio: window.devicePixelRatio,
+        fullscreen: !!document.fullscreenElement,
+        visibility: document.visibilityState,
+    };
+"#;
+
+/// Window rect and viewport of `session` as JSON; the viewport is left out when
+/// the page cannot run scripts
+async fn window_state(client_manager: &ClientManager, session: &str, client: &Client) -> Result<Value, String> {
+    let (x, y, width, height) = client_manager
+        .run_command(session, "get_window_rect", client.get_window_rect())
+        .await
+        .map_err(|e| e.to_string())?;
+    let viewport = client_manager
+        .run_command(session, "get_viewport", client.execute(VIEWPORT_SCRIPT, vec![]))
+        .await
+        .ok();
+    Ok(json!({
+        "session": session,
+        "window": { "x": x, "y": y, "width": width, "height": height },
+        "viewport": viewport,
+    }))
+}
+
+fn describe_window_state(state: &Value) -> String {
+    let window = &state["window"];
+    let mut text = format!(
+        "Window {}x{} at ({}, {})",
+        window["width"], window["height"], window["x"], window["y"]
+    );
+    if let Some(viewport) = state["viewport"].as_object() {
+        text.push_str(&format!(", viewport {}x{}", viewport["width"], viewport["height"]));
+    }
+    text
+}
+
+/// Get the window size and position, and the page viewport inside it
+pub async fn handle_get_window_size(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let SessionParams { session_id } = parse_params(arguments)?;
+
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => match window_state(client_manager, &session, &client).await {
+            Ok(state) => Ok(structured_response(
+                format!("{} (session: {})", describe_window_state(&state), session),
+                state,
+            )),
+            Err(e) => Ok(error_response(format!("Failed to get window size: {e}"))),
+        },
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Window state commands without arguments of their own
+#[derive(Debug, Clone, Copy)]
+pub enum WindowState {
+    Maximized,
+    Minimized,
+    Fullscreen,
+}
+
+impl WindowState {
+    fn operation(self) -> &'static str {
+        match self {
+            Self::Maximized => "maximize_window",
+            Self::Minimized => "minimize_window",
+            Self::Fullscreen => "fullscreen_window",
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Maximized => "maximized",
+            Self::Minimized => "minimized",
+            Self::Fullscreen => "fullscreen",
+        }
+    }
+}
+
+/// Maximize, minimize or fullscreen the browser window
+pub async fn handle_set_window_state(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+    state: WindowState,
+) -> Result<CallToolResult, McpError> {
+    let SessionParams { session_id } = parse_params(arguments)?;
+
+    match client_manager.get_or_create_client(session_id).await {
+        Ok((session, client)) => {
+            let operation = state.operation();
+            let changed = match state {
+                WindowState::Maximized => {
+                    client_manager.run_command(&session, operation, client.maximize_window()).await
+                }
+                WindowState::Minimized => {
+                    client_manager.run_command(&session, operation, client.minimize_window()).await
+                }
+                WindowState::Fullscreen => {
+                    client_manager.run_command(&session, operation, client.fullscreen_window()).await
+                }
+            };
+            if let Err(e) = changed {
+                return Ok(error_response(format!("Failed to {}: {e}", operation.replace('_', " "))));
+            }
+            match window_state(client_manager, &session, &client).await {
+                Ok(mut data) => {
+                    data["state"] = json!(state.as_str());
+                    Ok(structured_response(
+                        format!("Window {}: {} (session: {})", state.as_str(), describe_window_state(&data), session),
+                        data,
+                    ))
+                }
+                Err(_) => Ok(success_response(format!("Window {} (session: {})", state.as_str(), session))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Move the browser window without changing its size
+pub async fn handle_set_window_position(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let SetWindowPositionParams { x, y, session } = parse_params(arguments)?;
+
+    match client_manager.get_or_create_client(session.session_id).await {
+        Ok((session, client)) => {
+            let moved = client_manager
+                .run_command(&session, "set_window_position", client.set_window_position(x, y))
+                .await;
+            if let Err(e) = moved {
+                return Ok(error_response(format!("Failed to move window: {e}")));
+            }
+            match window_state(client_manager, &session, &client).await {
+                Ok(data) => Ok(structured_response(
+                    format!("Window moved: {} (session: {})", describe_window_state(&data), session),
+                    data,
+                )),
+                Err(_) => Ok(success_response(format!("Window moved to ({x}, {y}) (session: {session})"))),
+            }
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Click away consent banners, cookie dialogs and similar overlays
+pub async fn handle_dismiss_overlays(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let DismissOverlaysParams { choice, session } = parse_params(arguments)?;
+
+    match client_manager.get_or_create_client(session.session_id).await {
+        Ok((session, client)) => match overlays::dismiss(client_manager, &session, &client, choice).await {
+            Ok(dismissed) => {
+                let message = if dismissed.is_empty() {
+                    format!("No known overlays found (session: {session})")
+                } else {
+                    let names: Vec<String> = dismissed.iter().map(|overlay| overlay.describe()).collect();
+                    format!("Dismissed {} overlay(s): {} (session: {})", dismissed.len(), names.join(", "), session)
+                };
+                Ok(structured_response(
+                    message,
+                    json!({ "session": session, "choice": choice, "dismissed": dismissed }),
+                ))
+            }
+            Err(e) => Ok(error_response(format!("Failed to dismiss overlays: {e}"))),
+        },
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Report what pages did with popups and what the session's popup policy made of it
+pub async fn handle_get_popup_events(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let GetPopupEventsParams { clear, session } = parse_params(arguments)?;
+
+    match client_manager.get_existing_client(session.session_id).await {
+        Ok((session, client)) => {
+            // Pick up windows opened since the last navigate or click
+            if let Err(e) = popups::apply_policy(client_manager, &session, &client).await {
+                tracing::debug!("Failed to check popups in session '{}': {}", session, e);
+            }
+            let policy = client_manager.popup_policy(&session);
+            let events = client_manager.popup_events(&session, clear);
+            Ok(structured_response(
+                format!("{} popup event(s), policy '{}' (session: {})", events.len(), policy.as_str(), session),
+                json!({ "session": session, "policy": policy, "events": events }),
+            ))
+        }
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
+/// Report the lifecycle events recorded on the session's pages
+pub async fn handle_get_page_events(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let GetPageEventsParams { types, clear, session } = parse_params(arguments)?;
+    if let Some(unknown) = types.iter().find(|kind| !PAGE_EVENT_TYPES.contains(&kind.as_str())) {
+        return Err(McpError::invalid_params(
+            format!("Unknown page event type '{unknown}'. Must be one of: {}", PAGE_EVENT_TYPES.join(", ")),
+            None,
+        ));
+    }
+
+    match client_manager.get_existing_client(session.session_id).await {
+        Ok((session, client)) => {
+            // A page reached by a click has no recorder yet; installing one fills in its load events
+            if let Err(e) = page_events::install_recorder(client_manager, &session, &client).await {
+                tracing::debug!("Failed to record page events in session '{}': {}", session, e);
+            }
+            if let Err(e) = page_events::drain(client_manager, &session, &client).await {
+                tracing::debug!("Failed to read page events of session '{}': {}", session, e);
+            }
+            let events: Vec<_> = client_manager
+                .page_events(&session, clear)
+                .into_iter()
+                .filter(|event| types.is_empty() || types.contains(&event.kind))
+                .collect();
+            Ok(structured_response(
+                format!("{} page event(s) (session: {})", events.len(), session),
+                json!({ "session": session, "events": events }),
+            ))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// Compact overview of the current page, cheap to read before deciding what to do on it
+pub async fn handle_summarize_page(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let SummarizePageParams { excerpt_length, max_headings, session } = parse_params(arguments)?;
+
+    let (session, client) = match client_manager.get_existing_client(session.session_id).await {
+        Ok(found) => found,
+        Err(e) => return Ok(session_error_response(e)),
+    };
+    let script = client.execute(SUMMARIZE_PAGE_SCRIPT, vec![json!(excerpt_length), json!(max_headings)]);
+    let mut summary = match client_manager.run_command(&session, "summarize_page", script).await {
+        Ok(summary) => summary,
+        Err(e) => return Ok(error_response(format!("Failed to summarize page: {e}"))),
+    };
+
+    let message = page_summary_message(&summary, &session);
+    summary["session"] = json!(session);
+    Ok(structured_response(message, summary))
+}
+
+/// One line from a `SUMMARIZE_PAGE_SCRIPT` result
+fn page_summary_message(summary: &Value, session: &str) -> String {
+    let frameworks: Vec<&str> = summary["frameworks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|framework| framework["name"].as_str())
+        .collect();
+    format!(
+        "'{}': {} heading(s), {} form(s), {} link(s){} (session: {})",
+        summary["title"].as_str().unwrap_or_default(),
+        summary["headings_total"],
+        summary["forms"],
+        summary["links"]["total"],
+        if frameworks.is_empty() { String::new() } else { format!(", built with {}", frameworks.join(", ")) },
+        session
+    )
+}
+
 /// Get the page HTML source
 pub async fn handle_get_page_source(
     client_manager: &ClientManager,
@@ -220,15 +1224,84 @@ pub async fn handle_get_page_source(
 ) -> Result<CallToolResult, McpError> {
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
-        Ok((session, client)) => match client.source().await {
+    match client_manager.get_existing_client(session_id).await {
+        Ok((session, client)) => match client_manager.run_command(&session, "get_page_source", client.source()).await {
             Ok(html) => Ok(success_response(format!(
                 "Page HTML source (session: {session}):\n\n{html}"
             ))),
             Err(e) => Ok(error_response(format!("Failed to get page source: {e}"))),
         },
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_text_params(arguments: Value) -> GetTextParams {
+        serde_json::from_value(arguments).unwrap()
+    }
+
+    #[test]
+    fn test_get_text_tidies_every_match() {
+        let raw = get_text_params(json!({ "selector": "li", "all": true }));
+        let tidy = get_text_params(json!({ "selector": "li", "all": true, "normalize_whitespace": true }));
+        let short = get_text_params(json!({ "selector": "li", "normalize_whitespace": true, "max_length": 4 }));
+
+        assert_eq!(tidy_text(&raw, "  Blue\n\t mug "), ("  Blue\n\t mug ".to_string(), false));
+        assert_eq!(tidy_text(&tidy, "  Blue\n\t mug "), ("Blue mug".to_string(), false));
+        // Cut after normalizing, by characters rather than bytes
+        assert_eq!(tidy_text(&short, "  Blue\n\t mug "), ("Blue".to_string(), true));
+        assert_eq!(tidy_text(&short, "漢字です。"), ("漢字です".to_string(), true));
+
+        let result = json!({ "total": 3, "texts": ["  Mug\n 250 ml ", "Plate"] });
+        let (texts, total, truncated) = tidy_texts(&tidy, &result);
+        assert_eq!(texts, vec!["Mug 250 ml", "Plate"]);
+        assert_eq!(total, 3);
+        assert!(!truncated);
+        assert!(tidy_texts(&short, &result).2);
+        assert_eq!(tidy_texts(&tidy, &json!({ "total": 0, "texts": [] })).0, Vec::<String>::new());
+
+        let summary = texts_summary(&texts, total, "'li' within '#cart'", "default");
+        assert!(summary.starts_with("Text of 2 element(s) matching 'li' within '#cart' (session: default):\n"));
+        assert!(summary.contains("1. Mug 250 ml\n2. Plate\n"));
+        assert!(summary.ends_with("... only the first 2 of 3 matches are read\n"));
+        assert!(!texts_summary(&texts, 2, "'li'", "default").contains("only the first"));
+    }
+
+    #[test]
+    fn test_change_outcome_names_the_change_or_the_timeout() {
+        let appeared = json!({ "kind": "appeared", "attribute": null, "timestamp": 1, "text": "Saved" });
+        assert_eq!(
+            change_outcome(".toast", Some(&appeared), Duration::from_secs(10)),
+            "Captured after .toast changed (appeared)"
+        );
+        let attribute = json!({ "kind": "attributes", "attribute": "class" });
+        assert_eq!(
+            change_outcome("#banner", Some(&attribute), Duration::from_secs(10)),
+            "Captured after #banner changed (attributes)"
+        );
+        assert_eq!(
+            change_outcome(".toast", None, Duration::from_millis(2500)),
+            "No change to .toast within 2.5s; captured anyway"
+        );
+    }
+
+    #[test]
+    fn test_page_summary_message() {
+        let summary = json!({
+            "title": "Cart",
+            "headings_total": 4,
+            "forms": 1,
+            "links": { "total": 12, "external": 2 },
+            "frameworks": [{ "name": "Next.js", "version": "14.1.0" }, { "name": "React" }],
+        });
+        assert_eq!(
+            page_summary_message(&summary, "default"),
+            "'Cart': 4 heading(s), 1 form(s), 12 link(s), built with Next.js, React (session: default)"
+        );
+        let plain = json!({ "title": "", "headings_total": 0, "forms": 0, "links": { "total": 0 }, "frameworks": [] });
+        assert_eq!(page_summary_message(&plain, "s1"), "'': 0 heading(s), 0 form(s), 0 link(s) (session: s1)");
     }
 }
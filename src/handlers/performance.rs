@@ -4,21 +4,45 @@
 //! - Console log collection
 //! - Performance metrics (navigation, resources, paint)
 //! - Memory usage monitoring
-//! - CPU and FPS monitoring
+//! - CPU and frame timing monitoring, from the Chrome DevTools Protocol where available
+//! - Session health, to tell when a long-lived session should be recycled
 //! - Performance testing with actions
 
+use std::time::{Duration, Instant};
+
 use base64::{Engine as _, engine::general_purpose};
 use fantoccini::Locator;
 use rmcp::{ErrorData as McpError, model::CallToolResult};
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-    ClientManager,
-    tools::{error_response, success_response},
+    ClientManager, ErrorCode, SessionHealth, cdp, cold_load, console,
+    session_health::{self, MAX_HEALTH_SAMPLE_MS},
+    tools::{
+        coded_error_response, error_response, params::GetSessionHealthParams, structured_response, success_response,
+    },
 };
-use super::extract_session_id;
+use super::{cancelled_response, extract_session_id, parse_params, session_error_response};
+
+/// How often `get_console_logs` drains the page while waiting for entries
+const CONSOLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Entries `get_console_logs` returns per page unless asked for a `limit`
+const DEFAULT_CONSOLE_LIMIT: usize = 100;
+
+/// Milliseconds `get_session_health` samples CPU use over unless asked
+const DEFAULT_HEALTH_SAMPLE_MS: u64 = 1000;
 
 /// Get console logs from the browser
+///
+/// Entries are drained from the page into the server-side buffer, so the call
+/// returns right away unless it asks to wait for `min_entries` matching entries
+/// and/or a `quiet_period_ms` without new output, bounded by `wait_timeout`.
+/// Repeated messages are collapsed into one entry with a `count`, and the
+/// result is paged with `offset` and `limit`. The returned `high_watermark`
+/// passed back as `since_watermark` fetches only entries buffered after it,
+/// and `clear` empties the buffer up to it once read.
 pub async fn handle_get_console_logs(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
@@ -34,89 +58,185 @@ pub async fn handle_get_console_logs(
         .and_then(|args| args.get("since_timestamp"))
         .and_then(|v| v.as_f64());
 
+    let since_watermark = arguments
+        .as_ref()
+        .and_then(|args| args.get("since_watermark"))
+        .and_then(|v| v.as_u64());
+
+    let clear = arguments
+        .as_ref()
+        .and_then(|args| args.get("clear"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let min_entries = arguments
+        .as_ref()
+        .and_then(|args| args.get("min_entries"))
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize);
+
+    let quiet_period = arguments
+        .as_ref()
+        .and_then(|args| args.get("quiet_period_ms"))
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_millis);
+
     let wait_timeout = arguments
         .as_ref()
         .and_then(|args| args.get("wait_timeout"))
         .and_then(|v| v.as_f64())
-        .unwrap_or(2.0);
+        .unwrap_or(client_manager.get_config().console_log_wait_secs)
+        .max(0.0);
+
+    let dedup = arguments
+        .as_ref()
+        .and_then(|args| args.get("dedup"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let offset = arguments
+        .as_ref()
+        .and_then(|args| args.get("offset"))
+        .and_then(|v| v.as_u64())
+        .map_or(0, |n| n as usize);
+
+    let limit = arguments
+        .as_ref()
+        .and_then(|args| args.get("limit"))
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_CONSOLE_LIMIT, |n| n as usize)
+        .max(1);
 
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
-            // Wait for JavaScript execution to complete before capturing logs
-            if wait_timeout > 0.0 {
-                tokio::time::sleep(std::time::Duration::from_secs_f64(wait_timeout)).await;
-            }
-
-            // Simple script to retrieve stored console logs
-            let retrieve_script = r#"
-                try {
-                    return window.__mcpConsoleLogs || [];
-                } catch (e) {
-                    return [];
+            let matches = |log: &Value| {
+                if level_filter != "all" {
+                    let log_level = log.get("level").and_then(|v| v.as_str()).unwrap_or("");
+                    if log_level != level_filter {
+                        return false;
+                    }
+                }
+                if let Some(since) = since_timestamp {
+                    let log_timestamp = log.get("timestamp").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                    if log_timestamp < since {
+                        return false;
+                    }
+                }
+                since_watermark.is_none_or(|since| console::entry_seq(log) > since)
+            };
+
+            let started = Instant::now();
+            let deadline = started + crate::waits::clamped_wait(wait_timeout);
+            let mut last_output = started;
+            let (logs, satisfied) = loop {
+                match client_manager.drain_console_logs(&session, &client).await {
+                    Ok(added) if added > 0 => last_output = Instant::now(),
+                    Ok(_) => {}
+                    Err(e) => return Ok(error_response(format!("Failed to retrieve console logs: {e}"))),
                 }
-            "#;
-
-            match client.execute(retrieve_script, vec![]).await {
-                Ok(result) => {
-                    // Try to parse the result as JSON array of log entries
-                    let formatted_logs = if let Ok(logs) = serde_json::from_value::<Vec<serde_json::Value>>(result.clone()) {
-                        if logs.is_empty() {
-                            "No console logs found.".to_string()
-                        } else {
-                            logs.into_iter()
-                                .filter(|log| {
-                                    // Filter by level
-                                    if level_filter != "all" {
-                                        let log_level = log.get("level").and_then(|v| v.as_str()).unwrap_or("");
-                                        if log_level != level_filter {
-                                            return false;
-                                        }
-                                    }
 
-                                    // Filter by timestamp
-                                    if let Some(since) = since_timestamp {
-                                        let log_timestamp = log.get("timestamp").and_then(|v| v.as_f64()).unwrap_or(0.0);
-                                        if log_timestamp < since {
-                                            return false;
-                                        }
-                                    }
+                let logs = client_manager.console_logs(&session);
+                let matching = logs.iter().filter(|log| matches(log)).count();
+                let satisfied = min_entries.is_none_or(|min| matching >= min)
+                    && quiet_period.is_none_or(|quiet| last_output.elapsed() >= quiet);
+                if satisfied || Instant::now() >= deadline {
+                    break (logs, satisfied);
+                }
+                tokio::time::sleep(CONSOLE_POLL_INTERVAL.min(deadline - Instant::now())).await;
+            };
+
+            let high_watermark = logs
+                .iter()
+                .map(console::entry_seq)
+                .max()
+                .unwrap_or_else(|| client_manager.console_high_watermark(&session));
+            if clear {
+                client_manager.clear_console_logs(&session, high_watermark);
+            }
 
-                                    true
-                                })
-                                .map(|log| {
-                                    let level = log.get("level").and_then(|v| v.as_str()).unwrap_or("unknown");
-                                    let message = log.get("message").and_then(|v| v.as_str()).unwrap_or("");
-                                    let timestamp = log.get("timestamp").and_then(|v| v.as_u64()).unwrap_or(0);
-                                    let _url = log.get("url").and_then(|v| v.as_str()).unwrap_or("");
-
-                                    let time_str = if timestamp > 0 {
-                                        format!("[{}ms] ", timestamp)
-                                    } else {
-                                        "".to_string()
-                                    };
-
-                                    format!("{time_str}{level}: {message}")
-                                })
-                                .collect::<Vec<_>>()
-                                .join("\n")
-                        }
-                    } else {
-                        // Fallback if parsing fails
-                        format!("Raw result: {result:?}")
-                    };
+            // Keep the full, unfiltered buffer available as a resource
+            let resource_uri = (!logs.is_empty()).then(|| {
+                client_manager
+                    .get_resource_store()
+                    .add_console_log(&session, &Value::Array(logs.clone()))
+            });
 
-                    Ok(success_response(format!(
-                        "Console logs (session: {session}):\n{formatted_logs}"
-                    )))
-                }
-                Err(e) => Ok(error_response(format!("Failed to retrieve console logs: {e}"))),
+            let mut filtered: Vec<Value> = logs.into_iter().filter(|log| matches(log)).collect();
+            if dedup {
+                filtered = console::dedup_entries(filtered);
             }
+            let total = filtered.len();
+            let page: Vec<Value> = filtered.into_iter().skip(offset).take(limit).collect();
+            let next_offset = (offset + page.len() < total).then_some(offset + page.len());
+
+            let formatted_logs = if page.is_empty() {
+                "No console logs found.".to_string()
+            } else {
+                page.iter()
+                    .map(|log| {
+                        let level = log.get("level").and_then(|v| v.as_str()).unwrap_or("unknown");
+                        let message = log.get("message").and_then(|v| v.as_str()).unwrap_or("");
+                        let timestamp = log.get("timestamp").and_then(|v| v.as_u64()).unwrap_or(0);
+
+                        let time_str = if timestamp > 0 {
+                            format!("[{}ms] ", timestamp)
+                        } else {
+                            "".to_string()
+                        };
+                        let location = match (log.get("source").and_then(|v| v.as_str()), log.get("line")) {
+                            (Some(source), Some(line)) if !line.is_null() => format!(" ({source}:{line})"),
+                            _ => String::new(),
+                        };
+                        let repeats = match log.get("count").and_then(|v| v.as_u64()) {
+                            Some(count) if count > 1 => format!(" (x{count})"),
+                            _ => String::new(),
+                        };
+
+                        format!("{time_str}{level}: {message}{location}{repeats}")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            let watermark_line = format!(
+                "\nHigh watermark: {high_watermark}{}",
+                if clear { " (buffer cleared)" } else { "" }
+            );
+            let more_line = next_offset
+                .map(|next| format!("\nShowing {} of {total} entries; pass offset {next} for more", page.len()))
+                .unwrap_or_default();
+
+            let wait_line = if satisfied {
+                String::new()
+            } else {
+                format!("\nWait condition not met within {wait_timeout:.1}s")
+            };
+            let resource_line = resource_uri
+                .as_ref()
+                .map(|uri| format!("\nResource: {uri}"))
+                .unwrap_or_default();
+
+            let footer = format!("{more_line}{watermark_line}{wait_line}{resource_line}");
+            Ok(structured_response(
+                format!("Console logs (session: {session}):\n{formatted_logs}{footer}"),
+                json!({
+                    "session": session,
+                    "level": level_filter,
+                    "count": page.len(),
+                    "total": total,
+                    "offset": offset,
+                    "next_offset": next_offset,
+                    "high_watermark": high_watermark,
+                    "cleared": clear,
+                    "logs": page,
+                    "waited_ms": started.elapsed().as_millis() as u64,
+                    "wait_satisfied": satisfied,
+                    "resource_uri": resource_uri,
+                }),
+            ))
         }
-        Err(e) => Ok(error_response(format!(
-            "Failed to create webdriver client: {e}"
-        ))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -140,10 +260,18 @@ pub async fn handle_get_performance_metrics(
         .and_then(|args| args.get("include_paint"))
         .and_then(|v| v.as_bool())
         .unwrap_or(true);
+    let cold_load = arguments
+        .as_ref()
+        .and_then(|args| args.get("cold_load"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
+            if cold_load && let Err(e) = cold_load::reload_cold(client_manager, &session, &client).await {
+                return Ok(error_response(format!("Failed to reload the page cold: {e}")));
+            }
             let performance_script = format!(r#"
                 const metrics = {{}};
 
@@ -217,14 +345,16 @@ pub async fn handle_get_performance_metrics(
                 return metrics;
             "#);
 
-            match client.execute(&performance_script, vec![]).await {
+            let collected = client_manager
+                .run_command(&session, "get_performance_metrics", client.execute(&performance_script, vec![]));
+            match collected.await {
                 Ok(result) => Ok(success_response(format!(
                     "Performance metrics collected (session: {session}):\n{result:#?}"
                 ))),
                 Err(e) => Ok(error_response(format!("Failed to collect performance metrics: {e}"))),
             }
         }
-        Err(e) => Ok(error_response(format!("Failed to create webdriver client: {e}"))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -232,6 +362,7 @@ pub async fn handle_get_performance_metrics(
 pub async fn handle_monitor_memory_usage(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
 ) -> Result<CallToolResult, McpError> {
     let duration_seconds = arguments
         .as_ref()
@@ -250,7 +381,7 @@ pub async fn handle_monitor_memory_usage(
         .unwrap_or(true);
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
             let memory_script = format!(r#"
                 return new Promise((resolve) => {{
@@ -323,14 +454,20 @@ pub async fn handle_monitor_memory_usage(
                 }});
             "#);
 
-            match client.execute(&memory_script, vec![]).await {
-                Ok(result) => Ok(success_response(format!(
+            // The sampling promise keeps running in the page until its duration elapses,
+            // but the tool call itself returns as soon as the request is cancelled
+            let timeout = client_manager.call_timeout() + crate::waits::clamped_wait(duration_seconds);
+            let sampling = client.execute(&memory_script, vec![]);
+            let sampling = client_manager.run_command_within(&session, "monitor_memory_usage", timeout, sampling);
+            match cancellation.run_until_cancelled(sampling).await {
+                Some(Ok(result)) => Ok(success_response(format!(
                     "Memory monitoring completed (session: {session}):\n{result:#?}"
                 ))),
-                Err(e) => Ok(error_response(format!("Failed to monitor memory usage: {e}"))),
+                Some(Err(e)) => Ok(error_response(format!("Failed to monitor memory usage: {e}"))),
+                None => Ok(cancelled_response("Memory monitoring")),
             }
         }
-        Err(e) => Ok(error_response(format!("Failed to create webdriver client: {e}"))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
@@ -338,6 +475,7 @@ pub async fn handle_monitor_memory_usage(
 pub async fn handle_run_performance_test(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
 ) -> Result<CallToolResult, McpError> {
     let test_actions = arguments
         .as_ref()
@@ -354,6 +492,11 @@ pub async fn handle_run_performance_test(
         .and_then(|args| args.get("collect_screenshots"))
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
+    let cold_load = arguments
+        .as_ref()
+        .and_then(|args| args.get("cold_load"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
     let session_id = extract_session_id(arguments);
 
     match client_manager.get_or_create_client(session_id).await {
@@ -369,10 +512,15 @@ pub async fn handle_run_performance_test(
                     window.__perfTestMarks = [];
                     return "Performance test started";
                 "#;
-                client.execute(start_script, vec![]).await.ok();
+                let started = client.execute(start_script, vec![]);
+                client_manager.run_command(&session, "run_performance_test", started).await.ok();
 
                 // Execute test actions
                 for (action_idx, action) in test_actions.iter().enumerate() {
+                    if cancellation.is_cancelled() {
+                        return Ok(cancelled_response("Performance test"));
+                    }
+
                     let action_obj = action.as_object().ok_or_else(|| {
                         McpError::invalid_params("Each test action must be an object", None)
                     })?;
@@ -388,30 +536,47 @@ pub async fn handle_run_performance_test(
                             timestamp: performance.now() - window.__perfTestStart
                         }});
                     "#);
-                    client.execute(&mark_script, vec![]).await.ok();
+                    let marked = client.execute(&mark_script, vec![]);
+                    client_manager.run_command(&session, "run_performance_test", marked).await.ok();
 
                     match action_type {
                         "click" => {
                             if let Some(selector) = action_obj.get("selector").and_then(|v| v.as_str()) {
-                                if let Ok(element) = client.find(Locator::Css(selector)).await {
-                                    element.click().await.ok();
+                                let found = client.find(Locator::Css(selector));
+                                if let Ok(element) = client_manager.run_command(&session, "find_element", found).await {
+                                    client_manager.run_command(&session, "click", element.click()).await.ok();
                                 }
                             }
                         }
                         "scroll" => {
                             if let Some(selector) = action_obj.get("selector").and_then(|v| v.as_str()) {
                                 let scroll_script = format!("document.querySelector('{selector}')?.scrollIntoView();");
-                                client.execute(&scroll_script, vec![]).await.ok();
+                                let scrolled = client.execute(&scroll_script, vec![]);
+                                client_manager.run_command(&session, "run_performance_test", scrolled).await.ok();
                             }
                         }
                         "wait" => {
                             if let Some(duration_ms) = action_obj.get("duration_ms").and_then(|v| v.as_f64()) {
-                                tokio::time::sleep(std::time::Duration::from_millis(duration_ms as u64)).await;
+                                let wait = tokio::time::sleep(std::time::Duration::from_millis(duration_ms as u64));
+                                if cancellation.run_until_cancelled(wait).await.is_none() {
+                                    return Ok(cancelled_response("Performance test"));
+                                }
                             }
                         }
                         "navigate" => {
                             if let Some(url) = action_obj.get("url").and_then(|v| v.as_str()) {
-                                client.goto(url).await.ok();
+                                if cold_load {
+                                    // Without this the iteration would quietly measure a warm load
+                                    let loaded = cold_load::navigate_cold(client_manager, &session, &client, url).await;
+                                    if let Err(e) = loaded {
+                                        iteration_results.push(serde_json::json!({
+                                            "action": action_idx,
+                                            "error": format!("Cold load of {url} failed: {e}")
+                                        }));
+                                    }
+                                } else {
+                                    client_manager.navigate(&session, &client, url).await.ok();
+                                }
                             }
                         }
                         _ => {
@@ -453,12 +618,14 @@ pub async fn handle_run_performance_test(
                     return result;
                 "#;
 
-                match client.execute(end_script, vec![]).await {
+                let ended = client.execute(end_script, vec![]);
+                match client_manager.run_command(&session, "run_performance_test", ended).await {
                     Ok(iteration_result) => {
                         iteration_results.push(iteration_result);
 
                         if collect_screenshots {
-                            if let Ok(screenshot) = client.screenshot().await {
+                            let captured = client_manager.run_command(&session, "screenshot", client.screenshot());
+                            if let Ok(screenshot) = captured.await {
                                 // Convert screenshot to base64
                                 let screenshot_b64 = general_purpose::STANDARD.encode(&screenshot);
                                 iteration_results.push(serde_json::json!({
@@ -484,14 +651,94 @@ pub async fn handle_run_performance_test(
                 "Performance test completed (session: {session}):\n{results:#?}"
             )))
         }
-        Err(e) => Ok(error_response(format!("Failed to create webdriver client: {e}"))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
 
-/// Monitor resource usage (network, FPS, CPU)
+/// Frames longer than this count as jank
+const JANK_FRAME_MS: f64 = 50.0;
+
+/// Main-thread counters of CDP `Performance.getMetrics`, in seconds
+const MAIN_THREAD_METRICS: [&str; 4] = ["TaskDuration", "ScriptDuration", "LayoutDuration", "RecalcStyleDuration"];
+
+/// Frame rate, dropped frames and jank from the intervals between consecutive
+/// frames. The display's frame budget is taken from the typical interval, so a
+/// 120 Hz screen is not reported as dropping every other frame.
+fn frame_timing(intervals: &[f64], long_frames: Option<&[Value]>) -> Value {
+    let mut sorted: Vec<f64> = intervals.iter().copied().filter(|ms| *ms > 0.0).collect();
+    sorted.sort_by(f64::total_cmp);
+    let Some(&budget) = sorted.get(sorted.len() / 2) else {
+        return json!({ "frames": 0 });
+    };
+    let budget = budget.max(1000.0 / 240.0);
+    let elapsed: f64 = sorted.iter().sum();
+    let dropped: u64 = sorted.iter().map(|ms| ((ms / budget).round() as u64).saturating_sub(1)).sum();
+    let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+    let round = |ms: f64| (ms * 10.0).round() / 10.0;
+
+    let mut timing = json!({
+        "frames": sorted.len(),
+        "average_fps": round(sorted.len() as f64 * 1000.0 / elapsed),
+        "frame_budget_ms": round(budget),
+        "dropped_frames": dropped,
+        "jank_frames": sorted.iter().filter(|ms| **ms > JANK_FRAME_MS).count(),
+        "p95_frame_ms": round(percentile(0.95)),
+        "max_frame_ms": round(percentile(1.0)),
+    });
+    // Long Animation Frames attribute the slow frames to script, style and layout work
+    if let Some(long_frames) = long_frames {
+        let blocking: f64 = long_frames.iter().filter_map(|frame| frame["blocking"].as_f64()).sum();
+        timing["long_animation_frames"] = json!(long_frames.len());
+        timing["blocking_ms"] = json!(round(blocking));
+    }
+    timing
+}
+
+/// Current main-thread counters of a Chromium session, or `None` without CDP
+async fn main_thread_metrics(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &fantoccini::Client,
+) -> Option<Map<String, Value>> {
+    let result = cdp::execute(client_manager, session, client, "Performance.getMetrics", json!({})).await.ok()?;
+    Some(
+        result["metrics"]
+            .as_array()?
+            .iter()
+            .filter(|metric| MAIN_THREAD_METRICS.contains(&metric["name"].as_str().unwrap_or_default()))
+            .map(|metric| (metric["name"].as_str().unwrap_or_default().to_string(), metric["value"].clone()))
+            .collect(),
+    )
+}
+
+/// Milliseconds the main thread spent per counter between two metric snapshots
+fn main_thread_usage(before: &Map<String, Value>, after: &Map<String, Value>, window_ms: f64) -> Value {
+    let spent = |name: &str| {
+        let delta = after.get(name).and_then(Value::as_f64).unwrap_or(0.0)
+            - before.get(name).and_then(Value::as_f64).unwrap_or(0.0);
+        (delta.max(0.0) * 10_000.0).round() / 10.0
+    };
+    let task_ms = spent("TaskDuration");
+    json!({
+        "task_ms": task_ms,
+        "script_ms": spent("ScriptDuration"),
+        "layout_ms": spent("LayoutDuration"),
+        "style_ms": spent("RecalcStyleDuration"),
+        "busy_percent": if window_ms > 0.0 { (task_ms * 1000.0 / window_ms).round() / 10.0 } else { 0.0 },
+    })
+}
+
+/// Monitor resource usage (network, frame timing, CPU)
+///
+/// On Chrome and Edge, main-thread time comes from CDP `Performance.getMetrics`
+/// and slow frames are attributed with Long Animation Frames; other browsers
+/// fall back to counting `requestAnimationFrame` callbacks and timing a probe task.
+/// An optional `interaction_script` runs once the window opens, so the numbers
+/// cover e.g. a scroll or an animation rather than an idle page.
 pub async fn handle_monitor_resource_usage(
     client_manager: &ClientManager,
     arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
 ) -> Result<CallToolResult, McpError> {
     let duration_seconds = arguments
         .as_ref()
@@ -518,15 +765,45 @@ pub async fn handle_monitor_resource_usage(
         .and_then(|args| args.get("network_filter"))
         .and_then(|v| v.as_str())
         .unwrap_or(".*");
+    let interaction_script = arguments
+        .as_ref()
+        .and_then(|args| args.get("interaction_script"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
     let session_id = extract_session_id(arguments);
 
-    match client_manager.get_or_create_client(session_id).await {
+    if interaction_script.is_some()
+        && let Err(e) = crate::auth::check_tool_allowed(client_manager.get_config(), "execute_script")
+    {
+        return Ok(coded_error_response(
+            ErrorCode::PolicyBlocked,
+            format!("interaction_script refused: {e}"),
+            json!({ "tool": "monitor_resource_usage" }),
+        ));
+    }
+
+    match client_manager.get_existing_client(session_id).await {
         Ok((session, client)) => {
+            let metrics_before = if include_cpu || include_fps {
+                match cdp::execute(client_manager, &session, &client, "Performance.enable", json!({})).await {
+                    Ok(_) => main_thread_metrics(client_manager, &session, &client).await,
+                    Err(e) => {
+                        tracing::debug!("No CDP metrics for session '{}', sampling in the page: {}", session, e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            let use_cdp = metrics_before.is_some();
+
             let resource_script = format!(r#"
                 return new Promise((resolve) => {{
                     const results = {{
                         network: [],
                         fps: [],
+                        frameIntervals: [],
+                        longFrames: null,
                         cpu: [],
                         summary: {{}}
                     }};
@@ -555,14 +832,33 @@ pub async fn handle_monitor_resource_usage(
                         observer.observe({{entryTypes: ['resource']}});
                     }}
 
-                    // FPS monitoring
+                    // Frame timing: intervals between frames, plus Long Animation
+                    // Frames where the browser reports them
                     if ({include_fps}) {{
                         let frameCount = 0;
                         let lastTime = performance.now();
+                        let lastFrame = null;
+
+                        if ((PerformanceObserver.supportedEntryTypes || []).includes('long-animation-frame')) {{
+                            results.longFrames = [];
+                            new PerformanceObserver((list) => {{
+                                for (const frame of list.getEntries()) {{
+                                    results.longFrames.push({{
+                                        start: frame.startTime - startTime,
+                                        duration: frame.duration,
+                                        blocking: frame.blockingDuration
+                                    }});
+                                }}
+                            }}).observe({{type: 'long-animation-frame'}});
+                        }}
 
-                        function countFrame() {{
+                        function countFrame(frameTime) {{
                             frameCount++;
                             const currentTime = performance.now();
+                            if (lastFrame !== null) {{
+                                results.frameIntervals.push(Math.round((frameTime - lastFrame) * 10) / 10);
+                            }}
+                            lastFrame = frameTime;
 
                             if (currentTime - lastTime >= 1000) {{
                                 results.fps.push({{
@@ -580,8 +876,8 @@ pub async fn handle_monitor_resource_usage(
                         requestAnimationFrame(countFrame);
                     }}
 
-                    // CPU monitoring (approximation using timing)
-                    if ({include_cpu}) {{
+                    // CPU monitoring (approximation using timing, unless CDP measures it)
+                    if ({include_cpu} && !{use_cdp}) {{
                         let cpuSamples = [];
 
                         function sampleCPU() {{
@@ -609,6 +905,20 @@ pub async fn handle_monitor_resource_usage(
                         setTimeout(sampleCPU, 100);
                     }}
 
+                    // The interaction to measure, e.g. a scroll or an animation
+                    const interaction = arguments[0];
+                    if (interaction) {{
+                        setTimeout(() => {{
+                            try {{
+                                Promise.resolve(new Function(interaction)()).catch((e) => {{
+                                    results.interactionError = String(e);
+                                }});
+                            }} catch (e) {{
+                                results.interactionError = String(e);
+                            }}
+                        }}, 0);
+                    }}
+
                     // Final collection
                     setTimeout(() => {{
                         results.summary = {{
@@ -625,13 +935,122 @@ pub async fn handle_monitor_resource_usage(
                 }});
             "#);
 
-            match client.execute(&resource_script, vec![]).await {
-                Ok(result) => Ok(success_response(format!(
-                    "Resource usage monitoring completed (session: {session}):\n{result:#?}"
-                ))),
-                Err(e) => Ok(error_response(format!("Failed to monitor resource usage: {e}"))),
+            let script_args = vec![json!(interaction_script)];
+            let timeout = client_manager.call_timeout() + crate::waits::clamped_wait(duration_seconds);
+            let monitoring = client.execute(&resource_script, script_args);
+            let monitoring = client_manager.run_command_within(&session, "monitor_resource_usage", timeout, monitoring);
+            let monitored = cancellation.run_until_cancelled(monitoring).await;
+            let mut result = match monitored {
+                Some(Ok(result)) => result,
+                Some(Err(e)) => return Ok(error_response(format!("Failed to monitor resource usage: {e}"))),
+                None => return Ok(cancelled_response("Resource usage monitoring")),
+            };
+
+            let mut message = format!("Resource usage monitoring completed (session: {session})");
+            if include_fps {
+                let intervals: Vec<f64> = result["frameIntervals"]
+                    .as_array()
+                    .map(|intervals| intervals.iter().filter_map(Value::as_f64).collect())
+                    .unwrap_or_default();
+                let mut timing = frame_timing(&intervals, result["longFrames"].as_array().map(Vec::as_slice));
+                timing["source"] = json!(if result["longFrames"].is_array() { "long_animation_frames" } else { "raf" });
+                if let Some(dropped) = timing["dropped_frames"].as_u64() {
+                    message.push_str(&format!(
+                        "\nFrames: {} at {} fps, {dropped} dropped, {} janky (> {JANK_FRAME_MS} ms)",
+                        timing["frames"], timing["average_fps"], timing["jank_frames"]
+                    ));
+                }
+                result["frame_timing"] = timing;
+            }
+            if let Some(before) = &metrics_before
+                && let Some(after) = main_thread_metrics(client_manager, &session, &client).await
+            {
+                let usage = main_thread_usage(before, &after, duration_seconds * 1000.0);
+                message.push_str(&format!(
+                    "\nMain thread busy {}% ({} ms script, {} ms layout)",
+                    usage["busy_percent"], usage["script_ms"], usage["layout_ms"]
+                ));
+                result["main_thread"] = usage;
             }
+            if let Some(error) = result["interactionError"].as_str() {
+                message.push_str(&format!("\ninteraction_script failed: {error}"));
+            }
+            if let Some(results) = result.as_object_mut() {
+                results.remove("frameIntervals");
+            }
+            Ok(structured_response(message, result))
         }
-        Err(e) => Ok(error_response(format!("Failed to create webdriver client: {e}"))),
+        Err(e) => Ok(session_error_response(e)),
     }
 }
+
+/// Report what one or all sessions use and whether they should be recycled
+///
+/// Sessions are sampled at the same time, so checking all of them takes one
+/// sampling window rather than one per session.
+pub async fn handle_get_session_health(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let GetSessionHealthParams { all_sessions, sample_ms, session } = parse_params(arguments)?;
+    let sample = Duration::from_millis(sample_ms.unwrap_or(DEFAULT_HEALTH_SAMPLE_MS).min(MAX_HEALTH_SAMPLE_MS));
+
+    let open_sessions = client_manager.list_sessions().await;
+    let session_ids: Vec<String> = if all_sessions {
+        open_sessions.iter().map(|info| info.session_id.clone()).collect()
+    } else {
+        match client_manager.get_existing_client(session.session_id).await {
+            Ok((session, _)) => vec![session],
+            Err(e) => return Ok(session_error_response(e)),
+        }
+    };
+
+    let checks = session_ids.iter().map(|session_id| {
+        let info = open_sessions.iter().find(|info| &info.session_id == session_id);
+        let browser = info.and_then(|info| info.browser.clone());
+        let local = info.and_then(|info| info.endpoint.as_deref()).is_some_and(session_health::is_local_endpoint);
+        async move {
+            // A session closed since it was listed has nothing to report
+            let (session, client) = client_manager.get_existing_client(Some(session_id.clone())).await.ok()?;
+            Some(session_health::check(client_manager, &session, &client, browser, local, sample).await)
+        }
+    });
+    let reports: Vec<SessionHealth> = futures::future::join_all(checks).await.into_iter().flatten().collect();
+
+    let recycle = reports.iter().filter(|report| report.recycle_recommended).count();
+    let mut text = format!("{} session(s) checked, {} due for recycling", reports.len(), recycle);
+    for report in &reports {
+        text.push_str(&format!("\n  {}", report.describe()));
+    }
+    Ok(structured_response(text, json!({ "sessions": reports, "sample_ms": sample.as_millis() as u64 })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_timing_counts_dropped_and_janky_frames() {
+        // 60 Hz with one frame taking three budgets and one long stall
+        let mut intervals = vec![16.7; 58];
+        intervals.extend([50.1, 120.0]);
+        let long_frames = [json!({ "start": 10.0, "duration": 120.0, "blocking": 70.0 })];
+        let timing = frame_timing(&intervals, Some(&long_frames));
+        assert_eq!(timing["frames"], 60);
+        assert_eq!(timing["frame_budget_ms"], 16.7);
+        assert_eq!(timing["dropped_frames"], 2 + 6);
+        assert_eq!(timing["jank_frames"], 2);
+        assert_eq!(timing["max_frame_ms"], 120.0);
+        assert_eq!(timing["blocking_ms"], 70.0);
+        assert_eq!(frame_timing(&[], None), json!({ "frames": 0 }));
+
+        let before = json!({ "TaskDuration": 1.0, "ScriptDuration": 0.5 });
+        let after = json!({ "TaskDuration": 1.5, "ScriptDuration": 0.8, "LayoutDuration": 0.05 });
+        let usage = main_thread_usage(before.as_object().unwrap(), after.as_object().unwrap(), 1000.0);
+        assert_eq!(usage["task_ms"], 500.0);
+        assert_eq!(usage["script_ms"], 300.0);
+        assert_eq!(usage["layout_ms"], 50.0);
+        assert_eq!(usage["busy_percent"], 50.0);
+    }
+}
+
@@ -6,16 +6,28 @@
 //! - Executing recipes with parameters
 //! - Creating recipes from templates
 
-use rmcp::{ErrorData as McpError, model::CallToolResult};
+use rmcp::{ErrorData as McpError, model::{CallToolResult, Content}};
 use serde_json::{Map, Value};
+use tokio_util::sync::CancellationToken;
+
+use std::collections::HashMap;
 
 use crate::{
     Recipe,
-    recipes::{RecipeManager, RecipeTemplate, RecipeExecutor, ExecutionContext},
-    tools::{error_response, success_response},
+    recipes::{RecipeManager, RecipeTemplate, RecipeExecutor, ExecutionContext, ReportFormat},
+    tools::{error_response, success_response, RecipeTools},
     WebDriverServer,
 };
 
+/// Convert a JSON parameter value to the string form used for substitution
+fn parameter_value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
 /// Create a new recipe from JSON
 pub async fn handle_create_recipe(
     recipe_manager: &RecipeManager,
@@ -32,10 +44,10 @@ pub async fn handle_create_recipe(
             match recipe.validate() {
                 Ok(_) => {
                     match recipe_manager.save_recipe(&recipe).await {
-                        Ok(file_path) => Ok(success_response(format!(
+                        Ok(location) => Ok(success_response(format!(
                             "Recipe '{}' created successfully at {}",
                             recipe.name,
-                            file_path.display()
+                            location
                         ))),
                         Err(e) => Ok(error_response(format!("Failed to save recipe: {}", e))),
                     }
@@ -86,7 +98,21 @@ pub async fn handle_get_recipe(
     match recipe_manager.load_recipe(name).await {
         Ok(recipe) => {
             match recipe.to_json() {
-                Ok(json) => Ok(success_response(json)),
+                Ok(json) => {
+                    let schema = serde_json::to_string_pretty(&recipe.parameters_schema())
+                        .unwrap_or_else(|_| "{}".to_string());
+                    Ok(CallToolResult {
+                        content: vec![
+                            Content::text(json),
+                            Content::text(format!(
+                                "{}\n\nParameters schema:\n{}",
+                                recipe.tool_description(),
+                                schema
+                            )),
+                        ],
+                        is_error: Some(false),
+                    })
+                }
                 Err(e) => Ok(error_response(format!("Failed to serialize recipe: {}", e))),
             }
         }
@@ -100,6 +126,7 @@ pub async fn handle_execute_recipe(
     server: &WebDriverServer,
     recipe_manager: &RecipeManager,
     arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
 ) -> Result<CallToolResult, McpError> {
     let name = arguments
         .as_ref()
@@ -107,13 +134,13 @@ pub async fn handle_execute_recipe(
         .and_then(|v| v.as_str())
         .ok_or_else(|| McpError::invalid_params("name parameter required", None))?;
 
-    let parameters: Option<std::collections::HashMap<String, String>> = arguments
+    let parameters: Option<HashMap<String, String>> = arguments
         .as_ref()
         .and_then(|args| args.get("parameters"))
         .and_then(|v| v.as_object())
         .map(|obj| {
             obj.iter()
-                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .filter_map(|(k, v)| parameter_value_to_string(v).map(|s| (k.clone(), s)))
                 .collect()
         });
 
@@ -129,22 +156,124 @@ pub async fn handle_execute_recipe(
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let variables = parse_variables(arguments.as_ref().and_then(|args| args.get("variables")))?;
+    let report = parse_report(server, arguments.as_ref().and_then(|args| args.get("report")))?;
+
     // Load the recipe
     let recipe = match recipe_manager.load_recipe(name).await {
         Ok(recipe) => recipe,
         Err(e) => return Ok(error_response(format!("Failed to load recipe '{}': {}", name, e))),
     };
 
-    // Create execution context
-    let context = ExecutionContext {
-        session_id,
-        variables: std::collections::HashMap::new(),
-        continue_on_error,
+    let context = ExecutionContext { session_id, variables, continue_on_error };
+    run_recipe(server, &recipe, parameters, context, report, cancellation).await
+}
+
+/// Execute a saved recipe published as its own `recipe_<name>` tool.
+/// Top-level arguments are the recipe parameters; `session_id`, `continue_on_error`, `variables`
+/// and `report` keep their `execute_recipe` meaning unless the recipe declares parameters with those names.
+pub async fn handle_recipe_tool(
+    server: &WebDriverServer,
+    recipe_manager: &RecipeManager,
+    tool_name: &str,
+    arguments: &Option<Map<String, Value>>,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    let recipes = match recipe_manager.load_all_recipes().await {
+        Ok(recipes) => recipes,
+        Err(e) => return Ok(error_response(format!("Failed to load recipes: {}", e))),
+    };
+    let recipe = RecipeTools::published_names(&recipes)
+        .into_iter()
+        .find(|(name, _)| name == tool_name)
+        .map(|(_, recipe)| recipe.clone());
+
+    let Some(recipe) = recipe else {
+        return Err(McpError::invalid_params(format!("No recipe is published as tool '{}'", tool_name), None));
+    };
+
+    let declares = |param: &str| {
+        recipe.parameters.as_ref().is_some_and(|definitions| definitions.contains_key(param))
+    };
+
+    let mut parameters = HashMap::new();
+    let mut variables = HashMap::new();
+    let mut session_id = None;
+    let mut continue_on_error = false;
+    let mut report = parse_report(server, None)?;
+    for (key, value) in arguments.iter().flatten() {
+        match key.as_str() {
+            "variables" if !declares("variables") => {
+                variables = parse_variables(Some(value))?;
+            }
+            "report" if !declares("report") => {
+                report = parse_report(server, Some(value))?;
+            }
+            "session_id" if !declares("session_id") => {
+                session_id = value.as_str().map(|s| s.to_string());
+            }
+            "continue_on_error" if !declares("continue_on_error") => {
+                continue_on_error = value.as_bool().unwrap_or(false);
+            }
+            _ => {
+                if let Some(value) = parameter_value_to_string(value) {
+                    parameters.insert(key.clone(), value);
+                }
+            }
+        }
+    }
+
+    let context = ExecutionContext { session_id, variables, continue_on_error };
+    run_recipe(server, &recipe, Some(parameters), context, report, cancellation).await
+}
+
+/// Run-time variables injected by the caller: an object of string, number or boolean values
+fn parse_variables(value: Option<&Value>) -> Result<HashMap<String, String>, McpError> {
+    let Some(value) = value else {
+        return Ok(HashMap::new());
+    };
+    let object = value
+        .as_object()
+        .ok_or_else(|| McpError::invalid_params("variables must be an object of name/value pairs", None))?;
+    object
+        .iter()
+        .map(|(name, value)| {
+            parameter_value_to_string(value).map(|value| (name.clone(), value)).ok_or_else(|| {
+                McpError::invalid_params(format!("Variable '{}' must be a string, number or boolean", name), None)
+            })
+        })
+        .collect()
+}
+
+/// Report format for a run: the `report` argument ("html", "markdown" or "none"),
+/// or the server's `recipe_report` when the argument is absent
+fn parse_report(server: &WebDriverServer, value: Option<&Value>) -> Result<Option<ReportFormat>, McpError> {
+    let Some(value) = value else {
+        let config = server.get_client_manager().get_config();
+        return Ok(config.recipe_report.as_deref().and_then(ReportFormat::from_name));
     };
+    match value.as_str() {
+        Some("none") => Ok(None),
+        Some(name) if ReportFormat::from_name(name).is_some() => Ok(ReportFormat::from_name(name)),
+        _ => Err(McpError::invalid_params("report must be one of: html, markdown, none", None)),
+    }
+}
+
+async fn run_recipe(
+    server: &WebDriverServer,
+    recipe: &Recipe,
+    parameters: Option<HashMap<String, String>>,
+    context: ExecutionContext,
+    report: Option<ReportFormat>,
+    cancellation: &CancellationToken,
+) -> Result<CallToolResult, McpError> {
+    // Execute the recipe; a cancelled request stops it at the next step boundary
+    let executor = RecipeExecutor::new(server).with_cancellation(cancellation.clone()).with_report(report);
+    let outcome = executor.execute_recipe(recipe, parameters, context).await;
+    let stats = server.get_client_manager().get_stats();
+    stats.record_recipe_run(outcome.as_ref().is_ok_and(|result| result.success));
 
-    // Execute the recipe
-    let executor = RecipeExecutor::new(server);
-    match executor.execute_recipe(&recipe, parameters, context).await {
+    match outcome {
         Ok(result) => {
             if result.success {
                 Ok(success_response(result.to_summary_string()))
@@ -272,10 +401,10 @@ pub async fn handle_create_recipe_template(
     match recipe_manager.create_recipe_from_template(template).await {
         Ok(recipe) => {
             match recipe_manager.save_recipe(&recipe).await {
-                Ok(file_path) => Ok(success_response(format!(
+                Ok(location) => Ok(success_response(format!(
                     "Recipe '{}' created from template at {}",
                     recipe.name,
-                    file_path.display()
+                    location
                 ))),
                 Err(e) => Ok(error_response(format!("Failed to save recipe: {}", e))),
             }
@@ -0,0 +1,198 @@
+//! Session handlers
+//!
+//! Handles explicit browser session lifecycle:
+//! - Creating a session before any other tool uses it
+//! - Binding a session to a browser, endpoint and capabilities
+//! - Limiting what a new session may be used for
+//! - Adding hooks that run when the session is created or closed
+//! - Applying a named session template from the config
+//! - Listing open sessions and the endpoints they run at
+
+use rmcp::{ErrorData as McpError, model::CallToolResult};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    ClientManager, ErrorCode, PopupPolicy, SessionBinding, SessionHook, SessionOptions, SessionQuota,
+    driver::DriverType,
+    popups,
+    tools::{coded_error_response, error_response, structured_response},
+};
+use super::{extract_session_id, session_error_response};
+
+/// Browser named by a `browser` argument
+pub(crate) fn parse_browser(name: &str) -> Result<DriverType, McpError> {
+    DriverType::from_string(name).ok_or_else(|| {
+        McpError::invalid_params(format!("Invalid browser '{name}'. Must be one of: chrome, firefox, edge"), None)
+    })
+}
+
+/// Binding asked for with `browser`, `endpoint` and `capabilities`, if any.
+/// `default_browser` stands in for a missing `browser`.
+fn session_binding(
+    arguments: &Option<Map<String, Value>>,
+    default_browser: Option<&str>,
+) -> Result<Option<SessionBinding>, McpError> {
+    let argument = |name: &str| arguments.as_ref().and_then(|args| args.get(name));
+    let Some(browser) = argument("browser").and_then(|v| v.as_str()).or(default_browser) else {
+        if argument("endpoint").is_some() || argument("capabilities").is_some() {
+            return Err(McpError::invalid_params("endpoint and capabilities require browser", None));
+        }
+        return Ok(None);
+    };
+    let mut binding = SessionBinding::new(parse_browser(browser)?);
+    binding.endpoint = argument("endpoint").and_then(|v| v.as_str()).map(str::to_string);
+    match argument("capabilities") {
+        Some(Value::Object(capabilities)) => binding.capabilities = capabilities.clone(),
+        Some(_) => return Err(McpError::invalid_params("capabilities must be an object", None)),
+        None => {}
+    }
+    Ok(Some(binding))
+}
+
+/// Hooks asked for with `hooks`
+fn session_hooks(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<Vec<SessionHook>, McpError> {
+    let Some(value) = arguments.as_ref().and_then(|args| args.get("hooks")) else {
+        return Ok(Vec::new());
+    };
+    let hooks: Vec<SessionHook> = serde_json::from_value(value.clone())
+        .map_err(|e| McpError::invalid_params(format!("Invalid hooks: {e}"), None))?;
+    for hook in &hooks {
+        hook.validate()
+            .and_then(|()| hook.check_script_allowed(client_manager.get_config()))
+            .map_err(|e| McpError::invalid_params(format!("Invalid hook: {e}"), None))?;
+    }
+    Ok(hooks)
+}
+
+/// Open a browser session, or report the running one under the same id
+pub async fn handle_create_session(
+    client_manager: &ClientManager,
+    arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let mut session_id = extract_session_id(arguments);
+    let template_name = arguments.as_ref().and_then(|args| args.get("template")).and_then(|v| v.as_str());
+    let template = match template_name {
+        Some(name) => {
+            let templates = &client_manager.get_config().session_templates;
+            Some(templates.get(name).ok_or_else(|| {
+                let known: Vec<&str> = templates.keys().map(String::as_str).collect();
+                let known = if known.is_empty() { "none configured".to_string() } else { known.join(", ") };
+                McpError::invalid_params(format!("Unknown session template '{name}' (templates: {known})"), None)
+            })?)
+        }
+        None => None,
+    };
+    let binding = session_binding(arguments, template.and_then(|template| template.browser.as_deref()))?;
+    let popup_policy = match arguments.as_ref().and_then(|args| args.get("popup_policy")).and_then(|v| v.as_str()) {
+        Some(name) => Some(PopupPolicy::from_name(name).ok_or_else(|| {
+            let message = format!("Invalid popup_policy '{name}'. Must be one of: allow, block, switch");
+            McpError::invalid_params(message, None)
+        })?),
+        None => None,
+    };
+    let limit = |name: &str| arguments.as_ref().and_then(|args| args.get(name)).and_then(|v| v.as_u64());
+    let quota = SessionQuota {
+        max_tool_calls: limit("max_tool_calls"),
+        max_navigations: limit("max_navigations"),
+        max_runtime_secs: limit("max_runtime_secs"),
+    };
+    let hooks = session_hooks(client_manager, arguments)?;
+
+    let mut options = SessionOptions {
+        headless: arguments
+            .as_ref()
+            .and_then(|args| args.get("headless"))
+            .and_then(|v| v.as_bool()),
+        private: arguments
+            .as_ref()
+            .and_then(|args| args.get("private"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        auto_dismiss_banners: arguments
+            .as_ref()
+            .and_then(|args| args.get("auto_dismiss_banners"))
+            .and_then(|v| v.as_bool()),
+        popup_policy,
+        quota,
+        hooks,
+        ..SessionOptions::default()
+    };
+    if let Some(template) = template {
+        template.apply(&mut options).map_err(|e| McpError::invalid_params(e, None))?;
+    }
+
+    if let Some(binding) = binding.clone() {
+        match client_manager.bind_session(session_id.clone(), binding).await {
+            Ok(session) => session_id = Some(session),
+            Err(e) => {
+                let context = json!({ "session_id": session_id });
+                let code = match e.code() {
+                    ErrorCode::PolicyBlocked => ErrorCode::PolicyBlocked,
+                    _ => ErrorCode::InvalidArgument,
+                };
+                return Ok(coded_error_response(code, e.to_string(), context));
+            }
+        }
+    }
+
+    match client_manager.create_session(session_id, &options).await {
+        Ok((session, created)) => {
+            // A running page keeps following the policy it was loaded with until told otherwise
+            if popup_policy.is_some()
+                && let Ok((_, client)) = client_manager.get_existing_client(Some(session.clone())).await
+                && let Err(e) = popups::install_guard(client_manager, &session, &client).await
+            {
+                tracing::debug!("Failed to update popup guard in session '{}': {}", session, e);
+            }
+            // Limits only bind a session from its start, and must not be raised by its user
+            if !created && !quota.is_unlimited() {
+                return Ok(error_response(format!(
+                    "Session '{session}' is already active; quotas can only be set when a session is created"
+                )));
+            }
+            if !created && !options.hooks.is_empty() {
+                return Ok(error_response(format!(
+                    "Session '{session}' is already active; hooks can only be set when a session is created"
+                )));
+            }
+            let message = if created {
+                format!("Created session '{session}'")
+            } else {
+                format!("Session '{session}' is already active")
+            };
+            let mut data = json!({ "session": session, "created": created });
+            if let Some(usage) = client_manager.quota_usage(&session) {
+                data["quota"] = usage["quota"].clone();
+            }
+            if let Some(binding) = binding {
+                data["binding"] = json!(binding);
+            }
+            if let Some(name) = template_name {
+                data["template"] = json!(name);
+            }
+            Ok(structured_response(message, data))
+        }
+        Err(e) => Ok(session_error_response(e)),
+    }
+}
+
+/// List the caller's open sessions with their browser and WebDriver endpoint
+pub async fn handle_list_sessions(
+    client_manager: &ClientManager,
+    _arguments: &Option<Map<String, Value>>,
+) -> Result<CallToolResult, McpError> {
+    let sessions = client_manager.list_sessions().await;
+    let mut text = format!("{} open session(s)", sessions.len());
+    for session in &sessions {
+        text.push_str(&format!(
+            "\n  {}: {} at {}",
+            session.session_id,
+            session.browser.as_deref().unwrap_or("unknown browser"),
+            session.endpoint.as_deref().unwrap_or("unknown endpoint")
+        ));
+    }
+    Ok(structured_response(text, json!({ "sessions": sessions })))
+}
@@ -1,18 +1,85 @@
+mod accessibility;
+mod artifacts;
+mod audit;
+mod cdp;
+mod challenge;
+mod click_intercept;
 mod client;
+mod cold_load;
 mod config;
+mod console;
+mod dom_snapshot;
+mod drain;
 mod driver;
+mod element_handles;
+mod endpoints;
 mod error;
+#[cfg(feature = "test-fixtures")]
+mod fixtures;
+mod forms;
 mod handlers;
+mod localization;
+mod log_file;
+mod logging;
+mod login;
+#[cfg(feature = "mock-driver")]
+mod mock_driver;
+mod network_identity;
+mod overlays;
+mod page_events;
+mod page_scripts;
+mod politeness;
 mod pool;
+mod popups;
+mod quotas;
+mod rate_limit;
+mod robots;
+mod rotating_file;
+mod screenshots;
 mod server;
+mod session;
+mod session_aliases;
+mod session_health;
+mod session_hooks;
+mod session_locks;
+mod session_templates;
+mod site_pool;
+mod stability;
+mod stats;
+mod text;
+mod totp;
+mod typing;
+mod waits;
 
 pub mod auth;
 pub mod recipes;
+pub mod resources;
 pub mod tools;
 
-pub use client::ClientManager;
+pub use artifacts::{ArtifactInfo, ArtifactStore};
+pub use audit::{AuditEntry, AuditLog, AuditOutcome};
+pub use client::{ClientManager, ManagedSessions, SessionInfo, SessionOptions};
 pub use config::Config;
-pub use driver::{DriverManager, DriverType};
-pub use error::{Result, WebDriverError};
-pub use recipes::{Recipe, RecipeStep, RecipeManager, RecipeInfo, RecipeTemplate, RecipeExecutor, ExecutionContext, ExecutionResult};
+pub use driver::{DriverManager, DriverStartupPolicy, DriverType};
+pub use endpoints::{EndpointEntry, EndpointRegistry, EndpointStatus};
+pub use error::{CrashRecovery, ErrorCode, Result, WebDriverError};
+#[cfg(feature = "test-fixtures")]
+pub use fixtures::{FIXTURE_PASSWORD, FIXTURE_USERNAME, FixtureServer};
+pub use log_file::{LogRotation, file_log_layer};
+pub use logging::mcp_log_layer;
+#[cfg(feature = "mock-driver")]
+pub use mock_driver::{MockDriver, MockElement, MockPage};
+pub use overlays::{ConsentChoice, DismissedOverlay, OVERLAY_RULES, OverlayRule};
+pub use page_events::{PAGE_EVENT_TYPES, PageEvent};
+pub use popups::{PopupEvent, PopupPolicy};
+pub use quotas::SessionQuota;
+pub use resources::{ResourceKind, ResourceStore};
+pub use rate_limit::{CallPermit, RateLimitExceeded, RateLimiter};
+pub use recipes::{Recipe, RecipeBuilder, RecipeStep, RecipeManager, RecipeInfo, RecipeStore, RecipeTemplate, ReportFormat, RecipeExecutor, ExecutionContext, ExecutionResult};
 pub use server::WebDriverServer;
+pub use session::{BrowserSession, Page, Screenshot};
+pub use session_aliases::SessionBinding;
+pub use session_health::SessionHealth;
+pub use session_hooks::{HookEvent, SessionHook};
+pub use session_templates::SessionTemplate;
+pub use stats::{ServerStats, ToolStats};
@@ -0,0 +1,545 @@
+//! Localized page checks
+//!
+//! A page is loaded once per locale with the browser told it speaks that
+//! language: the `Accept-Language` header, `navigator.language(s)` and the
+//! `Intl` default locale are overridden through the DevTools protocol, so
+//! Chrome and Edge sessions are needed. The visible strings of each load,
+//! keyed by the element they sit in, are compared with the base locale's:
+//! a string shown unchanged is reported as untranslated, one the base page
+//! has and this page lacks as missing, and raw translation keys or template
+//! placeholders as unresolved. Keys follow the page structure, so a locale
+//! whose page is laid out differently reports its moved strings as missing.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use fantoccini::Client;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::{ClientManager, cdp};
+
+/// Locales one check may load
+pub const MAX_LOCALES: usize = 20;
+/// Pause after each load so strings rendered late are included
+pub const DEFAULT_LOCALIZATION_SETTLE_MS: u64 = 500;
+/// Strings collected from one page
+const MAX_PAGE_STRINGS: usize = 5000;
+/// Examples per finding in the summary text
+const SUMMARY_EXAMPLES: usize = 3;
+
+/// Collects the visible strings under `arguments[0]` (default: the body), at
+/// most `arguments[1]`, keyed by a selector path of the element holding them.
+/// Attribute texts get the key `path@attribute`.
+const PAGE_STRINGS_SCRIPT: &str = r#"
+    const [rootSelector, limit] = arguments;
+    const root = rootSelector ? document.querySelector(rootSelector) : document.body;
+    if (!root) return { found: false };
+
+    const skipped = new Set(['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE', 'SVG', 'CODE', 'PRE']);
+    const visible = el => {
+        const style = window.getComputedStyle(el);
+        if (style.display === 'none' || style.visibility === 'hidden') return false;
+        const rect = el.getBoundingClientRect();
+        return rect.width > 0 || rect.height > 0 || style.display === 'contents';
+    };
+    const path = el => {
+        const parts = [];
+        for (let node = el; node && node !== document.documentElement; node = node.parentElement) {
+            if (node.id) {
+                parts.unshift('#' + CSS.escape(node.id));
+                break;
+            }
+            const siblings = node.parentElement
+                ? Array.from(node.parentElement.children).filter(sibling => sibling.tagName === node.tagName)
+                : [];
+            const tag = node.tagName.toLowerCase();
+            parts.unshift(siblings.length > 1 ? `${tag}:nth-of-type(${siblings.indexOf(node) + 1})` : tag);
+        }
+        return parts.join(' > ');
+    };
+
+    const strings = [];
+    const add = (key, text) => {
+        const clean = (text || '').replace(/\s+/g, ' ').trim();
+        if (clean && /\p{L}/u.test(clean) && strings.length < limit) strings.push([key, clean]);
+    };
+    if (!rootSelector) add('title', document.title);
+    const walker = document.createTreeWalker(root, NodeFilter.SHOW_ELEMENT, {
+        acceptNode: el => skipped.has(el.tagName.toUpperCase()) || !visible(el)
+            ? NodeFilter.FILTER_REJECT
+            : NodeFilter.FILTER_ACCEPT,
+    });
+    for (let el = root; el && strings.length < limit; el = walker.nextNode()) {
+        const key = path(el);
+        const ownText = Array.from(el.childNodes).filter(node => node.nodeType === Node.TEXT_NODE);
+        add(key, ownText.map(node => node.textContent).join(' '));
+        ['placeholder', 'title', 'alt', 'aria-label'].forEach(name => add(`${key}@${name}`, el.getAttribute(name)));
+        if (el.tagName === 'INPUT' && ['submit', 'button', 'reset'].includes(el.type)) add(`${key}@value`, el.value);
+    }
+    return {
+        found: true,
+        lang: document.documentElement.lang || null,
+        navigator_language: navigator.language,
+        strings,
+        truncated: strings.length >= limit,
+    };
+"#;
+
+/// What to load and how to compare it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalizationCheck {
+    pub url: String,
+    /// Locales to load, the base first
+    pub locales: Vec<String>,
+    /// Only compare strings inside the first element matching this selector
+    pub selector: Option<String>,
+    /// Strings that are the same in every language, such as brand names
+    pub ignore: Vec<String>,
+    pub settle: Duration,
+}
+
+impl LocalizationCheck {
+    /// Check `url` under `locales`, comparing against `base_locale`, or the
+    /// first of `locales` when it is not given
+    pub fn new(url: &str, locales: &[String], base_locale: Option<&str>) -> Result<Self, String> {
+        let parsed = url::Url::parse(url).map_err(|e| format!("invalid url '{url}': {e}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("invalid url '{url}': use an http(s) URL"));
+        }
+        let mut checked: Vec<String> = Vec::new();
+        for locale in base_locale.into_iter().chain(locales.iter().map(String::as_str)) {
+            let locale = normalize_locale(locale)?;
+            if !checked.contains(&locale) {
+                checked.push(locale);
+            }
+        }
+        if checked.len() < 2 {
+            return Err("name at least two different locales to compare".to_string());
+        }
+        if checked.len() > MAX_LOCALES {
+            return Err(format!("at most {MAX_LOCALES} locales can be checked at once"));
+        }
+        Ok(Self {
+            url: url.to_string(),
+            locales: checked,
+            selector: None,
+            ignore: Vec::new(),
+            settle: Duration::from_millis(DEFAULT_LOCALIZATION_SETTLE_MS),
+        })
+    }
+
+    pub fn base_locale(&self) -> &str {
+        &self.locales[0]
+    }
+}
+
+/// A string as shown on one page
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PageString {
+    /// Selector path of the element holding it, `@attribute` for attribute texts
+    pub key: String,
+    pub text: String,
+}
+
+/// What one locale's page showed and what is wrong with it
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LocaleFindings {
+    pub locale: String,
+    /// `lang` attribute of the page's `<html>` element
+    pub lang: Option<String>,
+    /// `navigator.language` as the page saw it
+    pub navigator_language: Option<String>,
+    /// Strings collected from the page
+    pub strings: usize,
+    /// Strings shown exactly as on the base locale's page
+    pub untranslated: Vec<PageString>,
+    /// Strings of the base locale's page with no counterpart on this one
+    pub missing: Vec<PageString>,
+    /// Translation keys or template placeholders shown instead of text
+    pub unresolved_keys: Vec<PageString>,
+    /// Strings this page has that the base locale's does not
+    pub extra: usize,
+    /// The `lang` attribute names another language than the locale
+    pub wrong_lang: bool,
+    /// More strings than could be collected; the rest were not compared
+    pub truncated: bool,
+    /// Why the page could not be checked in this locale
+    pub error: Option<String>,
+}
+
+impl LocaleFindings {
+    pub fn issue_count(&self) -> usize {
+        self.untranslated.len()
+            + self.missing.len()
+            + self.unresolved_keys.len()
+            + usize::from(self.wrong_lang)
+            + usize::from(self.error.is_some())
+    }
+
+    /// The findings with each list cut to `limit` entries and its full length
+    /// reported next to it
+    pub fn to_json(&self, limit: usize) -> Value {
+        let first = |strings: &[PageString]| json!(&strings[..strings.len().min(limit)]);
+        json!({
+            "locale": self.locale,
+            "lang": self.lang,
+            "navigator_language": self.navigator_language,
+            "strings": self.strings,
+            "untranslated": first(&self.untranslated),
+            "untranslated_count": self.untranslated.len(),
+            "missing": first(&self.missing),
+            "missing_count": self.missing.len(),
+            "unresolved_keys": first(&self.unresolved_keys),
+            "unresolved_key_count": self.unresolved_keys.len(),
+            "extra": self.extra,
+            "wrong_lang": self.wrong_lang,
+            "truncated": self.truncated,
+            "error": self.error,
+        })
+    }
+}
+
+/// Findings of every locale, the base locale's first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocalizationReport {
+    pub url: String,
+    pub base_locale: String,
+    pub locales: Vec<LocaleFindings>,
+}
+
+impl LocalizationReport {
+    pub fn issue_count(&self) -> usize {
+        self.locales.iter().map(LocaleFindings::issue_count).sum()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Checked {} in {} locales against {}: {} issue(s)",
+            self.url,
+            self.locales.len(),
+            self.base_locale,
+            self.issue_count()
+        );
+        for findings in &self.locales {
+            summary.push_str(&format!("\n- {}", findings.locale));
+            if let Some(error) = &findings.error {
+                summary.push_str(&format!(": failed: {error}"));
+                continue;
+            }
+            summary.push_str(&format!(": {} string(s)", findings.strings));
+            if findings.locale != self.base_locale {
+                summary.push_str(&format!(
+                    ", {} untranslated, {} missing",
+                    findings.untranslated.len(),
+                    findings.missing.len()
+                ));
+            }
+            summary.push_str(&format!(", {} unresolved key(s)", findings.unresolved_keys.len()));
+            if findings.wrong_lang {
+                summary.push_str(&format!(", <html lang=\"{}\">", findings.lang.as_deref().unwrap_or_default()));
+            }
+            for (label, strings) in [
+                ("untranslated", &findings.untranslated),
+                ("missing", &findings.missing),
+                ("unresolved", &findings.unresolved_keys),
+            ] {
+                if strings.is_empty() {
+                    continue;
+                }
+                let examples: Vec<String> = strings
+                    .iter()
+                    .take(SUMMARY_EXAMPLES)
+                    .map(|string| format!("\"{}\" ({})", string.text, string.key))
+                    .collect();
+                let more = strings.len().saturating_sub(SUMMARY_EXAMPLES);
+                let more = if more > 0 { format!(" and {more} more") } else { String::new() };
+                summary.push_str(&format!("\n  {label}: {}{more}", examples.join(", ")));
+            }
+        }
+        summary
+    }
+}
+
+/// Strings of one page load, or why there are none
+type PageLoad = Result<PageStrings, String>;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct PageStrings {
+    lang: Option<String>,
+    navigator_language: Option<String>,
+    strings: BTreeMap<String, String>,
+    truncated: bool,
+}
+
+/// Load the page of `check` under each of its locales in `session` and
+/// compare what they show. The overrides are lifted afterwards and the
+/// session goes back to the page it was on.
+pub async fn run_check(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    check: &LocalizationCheck,
+) -> crate::Result<LocalizationReport> {
+    let user_agent = browser_user_agent(client_manager, session, client).await?;
+    let previous_url = client_manager.run_command(session, "get_current_url", client.current_url()).await.ok();
+
+    let mut loads = Vec::with_capacity(check.locales.len());
+    for locale in &check.locales {
+        let load = load_under_locale(client_manager, session, client, check, locale, &user_agent).await;
+        loads.push((locale.clone(), load.map_err(|e| e.to_string()).and_then(parse_page_strings)));
+    }
+
+    clear_overrides(client_manager, session, client).await;
+    if let Some(url) = previous_url.filter(|url| matches!(url.scheme(), "http" | "https"))
+        && let Err(e) = client_manager.navigate(session, client, url.as_str()).await
+    {
+        tracing::debug!("Failed to return session '{}' to {}: {}", session, url, e);
+    }
+    Ok(compare_loads(check, loads))
+}
+
+async fn load_under_locale(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    check: &LocalizationCheck,
+    locale: &str,
+    user_agent: &str,
+) -> crate::Result<Value> {
+    // Chrome refuses a new locale override while another is in effect
+    cdp::execute(client_manager, session, client, "Emulation.setLocaleOverride", json!({})).await?;
+    let icu_locale = locale.replace('-', "_");
+    cdp::execute(client_manager, session, client, "Emulation.setLocaleOverride", json!({ "locale": icu_locale }))
+        .await?;
+    let user_agent = json!({ "userAgent": user_agent, "acceptLanguage": accept_language(locale) });
+    cdp::execute(client_manager, session, client, "Emulation.setUserAgentOverride", user_agent).await?;
+    let navigator = json!({ "source": navigator_override_script(locale) });
+    let added =
+        cdp::execute(client_manager, session, client, "Page.addScriptToEvaluateOnNewDocument", navigator).await?;
+
+    let loaded = async {
+        client_manager.navigate(session, client, &check.url).await?;
+        tokio::time::sleep(check.settle).await;
+        let arguments = vec![json!(check.selector), json!(MAX_PAGE_STRINGS)];
+        client_manager.run_command(session, "page_strings", client.execute(PAGE_STRINGS_SCRIPT, arguments)).await
+    }
+    .await;
+
+    if let Some(identifier) = added.get("identifier").and_then(Value::as_str) {
+        let remove = json!({ "identifier": identifier });
+        if let Err(e) =
+            cdp::execute(client_manager, session, client, "Page.removeScriptToEvaluateOnNewDocument", remove).await
+        {
+            tracing::debug!("Failed to remove the navigator override in session '{}': {}", session, e);
+        }
+    }
+    loaded
+}
+
+/// Lift the locale and language overrides of a check. The user agent is set
+/// back to the browser's own, which the overrides never change.
+pub async fn clear_overrides(client_manager: &ClientManager, session: &str, client: &Client) {
+    let cleared = async {
+        cdp::execute(client_manager, session, client, "Emulation.setLocaleOverride", json!({})).await?;
+        let user_agent = browser_user_agent(client_manager, session, client).await?;
+        let user_agent = json!({ "userAgent": user_agent });
+        cdp::execute(client_manager, session, client, "Emulation.setUserAgentOverride", user_agent).await
+    };
+    if let Err(e) = cleared.await {
+        tracing::debug!("Failed to clear locale overrides of session '{}': {}", session, e);
+    }
+}
+
+async fn browser_user_agent(client_manager: &ClientManager, session: &str, client: &Client) -> crate::Result<String> {
+    let version = cdp::execute(client_manager, session, client, "Browser.getVersion", json!({})).await?;
+    Ok(version.get("userAgent").and_then(Value::as_str).unwrap_or_default().to_string())
+}
+
+fn parse_page_strings(result: Value) -> PageLoad {
+    if !result.get("found").and_then(Value::as_bool).unwrap_or(false) {
+        return Err("no element matches the selector".to_string());
+    }
+    let text = |key: &str| result.get(key).and_then(Value::as_str).map(str::to_string);
+    let strings = result
+        .get("strings")
+        .and_then(Value::as_array)
+        .map(|strings| {
+            strings
+                .iter()
+                .filter_map(|entry| Some((entry.get(0)?.as_str()?.to_string(), entry.get(1)?.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(PageStrings {
+        lang: text("lang"),
+        navigator_language: text("navigator_language"),
+        strings,
+        truncated: result.get("truncated").and_then(Value::as_bool).unwrap_or(false),
+    })
+}
+
+/// Compare every load with the base locale's, the first of `loads`
+fn compare_loads(check: &LocalizationCheck, loads: Vec<(String, PageLoad)>) -> LocalizationReport {
+    let base = loads.first().and_then(|(_, load)| load.as_ref().ok()).cloned();
+    let base_locale = check.base_locale().to_string();
+    let locales = loads
+        .into_iter()
+        .map(|(locale, load)| {
+            let page = match load {
+                Ok(page) => page,
+                Err(error) => return LocaleFindings { locale, error: Some(error), ..LocaleFindings::default() },
+            };
+            let unresolved_keys = page
+                .strings
+                .iter()
+                .filter(|(_, text)| looks_like_translation_key(text))
+                .map(|(key, text)| PageString { key: key.clone(), text: text.clone() })
+                .collect();
+            let language = primary_language(&locale);
+            let wrong_lang = page.lang.as_deref().is_some_and(|lang| primary_language(lang) != language);
+            let mut findings = LocaleFindings {
+                strings: page.strings.len(),
+                unresolved_keys,
+                wrong_lang,
+                truncated: page.truncated,
+                lang: page.lang,
+                navigator_language: page.navigator_language,
+                locale,
+                ..LocaleFindings::default()
+            };
+            if let Some(base) = base.as_ref().filter(|_| findings.locale != base_locale) {
+                let same_language = primary_language(&findings.locale) == primary_language(&base_locale);
+                for (key, text) in &base.strings {
+                    match page.strings.get(key) {
+                        None => findings.missing.push(PageString { key: key.clone(), text: text.clone() }),
+                        Some(shown) if shown == text && !same_language && translatable(text, &check.ignore) => {
+                            findings.untranslated.push(PageString { key: key.clone(), text: text.clone() });
+                        }
+                        Some(_) => {}
+                    }
+                }
+                findings.extra = page.strings.keys().filter(|key| !base.strings.contains_key(*key)).count();
+            }
+            findings
+        })
+        .collect();
+    LocalizationReport { url: check.url.clone(), base_locale, locales }
+}
+
+/// Whether a string shown unchanged in another language points at a missing
+/// translation: it has words, and is not on the caller's ignore list
+fn translatable(text: &str, ignore: &[String]) -> bool {
+    text.chars().filter(|c| c.is_alphabetic()).count() >= 2 && !ignore.iter().any(|ignored| ignored == text)
+}
+
+/// Raw keys like `checkout.submit_button`, placeholders like `{{ cart.total }}`
+/// and "translation missing" notices of common i18n libraries
+fn looks_like_translation_key(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    if (text.contains("{{") && text.contains("}}"))
+        || (lower.contains("missing") && lower.contains("translation"))
+    {
+        return true;
+    }
+    let segments: Vec<&str> = text.split('.').collect();
+    !text.starts_with("www.")
+        && (segments.len() >= 3 || text.contains('_'))
+        && segments.len() >= 2
+        && segments.iter().all(|segment| {
+            segment.starts_with(|c: char| c.is_ascii_lowercase())
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+}
+
+/// `de_de` and `de-DE` become `de-DE`
+fn normalize_locale(locale: &str) -> Result<String, String> {
+    let parts: Vec<&str> = locale.trim().split(['-', '_']).collect();
+    let valid = parts[0].len() >= 2
+        && parts[0].len() <= 3
+        && parts[0].chars().all(|c| c.is_ascii_alphabetic())
+        && parts[1..]
+            .iter()
+            .all(|part| (2..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric()));
+    if !valid {
+        return Err(format!("invalid locale '{locale}': use a language tag such as 'de' or 'pt-BR'"));
+    }
+    let mut normalized = parts[0].to_lowercase();
+    for part in &parts[1..] {
+        normalized.push('-');
+        match part.len() {
+            2 => normalized.push_str(&part.to_uppercase()),
+            _ => normalized.push_str(part),
+        }
+    }
+    Ok(normalized)
+}
+
+fn primary_language(locale: &str) -> String {
+    locale.split(['-', '_']).next().unwrap_or_default().to_lowercase()
+}
+
+/// `Accept-Language` header preferring `locale`, then its language
+fn accept_language(locale: &str) -> String {
+    let language = primary_language(locale);
+    if language == locale { language } else { format!("{locale},{language};q=0.9") }
+}
+
+/// Makes `navigator.language` and `navigator.languages` report `locale` on every new document
+fn navigator_override_script(locale: &str) -> String {
+    let languages = json!([locale, primary_language(locale)]);
+    format!(
+        "Object.defineProperty(Navigator.prototype, 'language', {{ get: () => {}, configurable: true }});\n\
+         Object.defineProperty(Navigator.prototype, 'languages', {{ get: () => {}, configurable: true }});",
+        json!(locale),
+        languages
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_localized_pages() {
+        let locales = ["en-US".to_string(), "de_de".to_string(), "en-GB".to_string()];
+        let check = LocalizationCheck {
+            ignore: vec!["Acme".to_string()],
+            ..LocalizationCheck::new("https://shop.example.com/", &locales, None).unwrap()
+        };
+        assert_eq!(check.locales, ["en-US", "de-DE", "en-GB"]);
+        assert_eq!(LocalizationCheck::new("https://x.test/", &locales, Some("fr")).unwrap().base_locale(), "fr");
+        assert!(LocalizationCheck::new("https://x.test/", &locales[..1], None).is_err());
+        assert!(LocalizationCheck::new("file:///tmp/a.html", &locales, None).is_err());
+        assert!(normalize_locale("english").is_err());
+        assert_eq!(accept_language("de-DE"), "de-DE,de;q=0.9");
+        assert_eq!(accept_language("de"), "de");
+        assert!(navigator_override_script("de-DE").contains("[\"de-DE\",\"de\"]"));
+
+        let page = |lang: &str, strings: &[(&str, &str)]| -> PageLoad {
+            parse_page_strings(json!({ "found": true, "lang": lang, "navigator_language": lang, "strings": strings }))
+        };
+        let loads = vec![
+            ("en-US".to_string(), page("en", &[("h1", "Welcome"), ("#buy", "Add to cart"), ("#brand", "Acme")])),
+            ("de-DE".to_string(), page("en", &[("h1", "Willkommen"), ("#buy", "Add to cart"), ("#brand", "Acme")])),
+            ("en-GB".to_string(), page("en", &[("h1", "Welcome"), ("#buy", "checkout.add_to_cart"), ("nav", "Hi")])),
+        ];
+        let report = compare_loads(&check, loads);
+        let [base, german, british] = &report.locales[..] else { panic!("three locales expected") };
+        assert_eq!(base.issue_count(), 0);
+        assert_eq!(german.untranslated, [PageString { key: "#buy".to_string(), text: "Add to cart".to_string() }]);
+        assert!(german.wrong_lang);
+        assert_eq!(german.issue_count(), 2);
+        assert!(british.untranslated.is_empty());
+        assert_eq!(british.missing[0].key, "#brand");
+        assert_eq!(british.unresolved_keys[0].text, "checkout.add_to_cart");
+        assert_eq!(british.extra, 1);
+        assert_eq!(british.to_json(0)["missing_count"], 1);
+        assert!(report.summary().contains("untranslated: \"Add to cart\" (#buy)"));
+
+        assert!(looks_like_translation_key("{{ cart.total }}"));
+        assert!(looks_like_translation_key("[missing \"de.cart.title\" translation]"));
+        assert!(!looks_like_translation_key("example.com"));
+        assert!(!looks_like_translation_key("www.example.com"));
+        assert!(!looks_like_translation_key("Add to cart."));
+    }
+}
@@ -0,0 +1,186 @@
+//! Optional log file output
+//!
+//! Stdio clients swallow stderr and HTTP deployments want logs that outlive the
+//! process, so the server can also write its log to a file, as plain text or as
+//! one JSON object per line. The file is rotated once it grows past a size limit
+//! and, optionally, at every hour or day boundary. Rotated files are renamed
+//! `<name>.1`, `<name>.2`, ... like the audit log.
+
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use serde_json::{Map, Value};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::{EnvFilter, Layer, fmt::MakeWriter, layer::Context, registry::LookupSpan};
+
+use crate::{config::Config, logging::FieldVisitor, rotating_file::RotatingFile};
+
+/// Time-based rotation schedule for the log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRotation {
+    Never,
+    Hourly,
+    Daily,
+}
+
+impl LogRotation {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "never" => Some(Self::Never),
+            "hourly" => Some(Self::Hourly),
+            "daily" => Some(Self::Daily),
+            _ => None,
+        }
+    }
+
+    /// Label of the period a write falls in; the file rotates when it changes
+    fn current_period(self) -> Option<String> {
+        match self {
+            Self::Never => None,
+            Self::Hourly => Some(Utc::now().format("%Y-%m-%dT%H").to_string()),
+            Self::Daily => Some(Utc::now().format("%Y-%m-%d").to_string()),
+        }
+    }
+}
+
+/// The log file, also rotated when a time period ends
+struct TimedFile {
+    file: RotatingFile,
+    rotation: LogRotation,
+    /// Period the open file was started in
+    period: Option<String>,
+}
+
+impl TimedFile {
+    fn write_record(&mut self, record: &[u8]) -> io::Result<()> {
+        let period = self.rotation.current_period();
+        if self.file.is_open() && period != self.period {
+            self.file.rotate()?;
+        }
+        if !self.file.is_open() {
+            self.period = period;
+        }
+        self.file.write_record(record)
+    }
+}
+
+/// Shared handle to the log file; every formatted event is written as one record
+#[derive(Clone)]
+struct LogFile(Arc<Mutex<TimedFile>>);
+
+impl LogFile {
+    fn write_record(&self, record: &[u8]) {
+        let mut file = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        // Logging about a failing logger would recurse; report on stderr instead
+        if let Err(e) = file.write_record(record) {
+            eprintln!("Failed to write log file {}: {e}", file.file.path.display());
+        }
+    }
+}
+
+impl Write for LogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_record(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogFile {
+    type Writer = LogFile;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Writes each event as a JSON line: timestamp, level, target, message and fields
+struct JsonLogLayer {
+    file: LogFile,
+}
+
+impl<S: Subscriber> Layer<S> for JsonLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let mut line = Map::new();
+        line.insert("timestamp".to_string(), Value::String(Utc::now().to_rfc3339()));
+        line.insert("level".to_string(), Value::String(metadata.level().to_string()));
+        line.insert("target".to_string(), Value::String(metadata.target().to_string()));
+        line.insert("message".to_string(), Value::String(visitor.message));
+        if !visitor.fields.is_empty() {
+            line.insert("fields".to_string(), Value::Object(visitor.fields));
+        }
+
+        let mut record = Value::Object(line).to_string();
+        record.push('\n');
+        self.file.write_record(record.as_bytes());
+    }
+}
+
+/// Layer writing the log to `log_file` in the configured format, or `None` when
+/// file logging is off. The file is opened here so a bad path fails at startup.
+pub fn file_log_layer<S>(config: &Config) -> io::Result<Option<Box<dyn Layer<S> + Send + Sync>>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let Some(path) = &config.log_file else {
+        return Ok(None);
+    };
+
+    let mut file = RotatingFile::new(path, config.log_file_max_bytes, config.log_file_max_files);
+    file.open()?;
+    let rotation = LogRotation::from_name(&config.log_file_rotation).unwrap_or(LogRotation::Never);
+    let period = rotation.current_period();
+    let file = LogFile(Arc::new(Mutex::new(TimedFile { file, rotation, period })));
+
+    let filter = EnvFilter::try_new(&config.log_file_level)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+    let layer = if config.log_file_format == "json" {
+        JsonLogLayer { file }.with_filter(filter).boxed()
+    } else {
+        tracing_subscriber::fmt::layer()
+            .with_writer(file)
+            .with_ansi(false)
+            .with_filter(filter)
+            .boxed()
+    };
+    Ok(Some(layer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_rotates_by_size_keeping_max_files() {
+        let dir = std::env::temp_dir().join(format!("log-file-test-{}", uuid::Uuid::new_v4().simple()));
+        let path = dir.join("server.log");
+        let mut file = TimedFile {
+            file: RotatingFile::new(&path, 10, 2),
+            rotation: LogRotation::Never,
+            period: None,
+        };
+
+        for record in ["first-123\n", "second-12\n", "third-123\n", "fourth-12\n"] {
+            file.write_record(record.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth-12\n");
+        assert_eq!(fs::read_to_string(RotatingFile::rotated_path(&path, 1)).unwrap(), "third-123\n");
+        assert_eq!(fs::read_to_string(RotatingFile::rotated_path(&path, 2)).unwrap(), "second-12\n");
+        assert!(!RotatingFile::rotated_path(&path, 3).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,240 @@
+//! Bridge from `tracing` to MCP `notifications/message`
+//!
+//! Driver lifecycle, session and connection pool events are captured by
+//! [`mcp_log_layer`] and broadcast to connected clients through an
+//! [`McpLogForwarder`], filtered by the level the client picked with
+//! `logging/setLevel`. This lets stdio users see driver restarts and health
+//! check failures without watching stderr.
+//!
+//! Events logged while serving a tool call belong to that call's principal and
+//! reach only that principal's clients. Other events are server-wide, except
+//! ones that may name a session key (`id@principal`), which are kept from
+//! authenticated clients since they could belong to someone else.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU8, Ordering},
+};
+
+use once_cell::sync::Lazy;
+use rmcp::{
+    Peer, RoleServer,
+    model::{LoggingLevel, LoggingMessageNotificationParam},
+};
+use serde_json::{Map, Value};
+use tokio::{sync::broadcast, task::JoinHandle};
+use tracing::{Event, Level, Subscriber, field::Field};
+use tracing_subscriber::{
+    Layer,
+    filter::Targets,
+    layer::Context,
+};
+
+use crate::ClientManager;
+
+/// Modules whose events are forwarded to MCP clients
+pub const MCP_LOG_TARGETS: &[&str] = &[
+    "rust_browser_mcp::driver",
+    "rust_browser_mcp::client",
+    "rust_browser_mcp::pool",
+    "rust_browser_mcp::page_events",
+];
+
+/// Level used until the client sends `logging/setLevel`
+pub const DEFAULT_MCP_LOG_LEVEL: LoggingLevel = LoggingLevel::Warning;
+
+/// A captured event and who may see it
+#[derive(Clone)]
+struct LogEvent {
+    /// Principal of the tool call the event was logged in
+    owner: Option<String>,
+    /// Whether the text contains an `@`, so could name another principal's session
+    may_name_session: bool,
+    message: LoggingMessageNotificationParam,
+}
+
+impl LogEvent {
+    /// Whether a client authenticated as `principal` may receive the event
+    fn visible_to(&self, principal: Option<&str>) -> bool {
+        match &self.owner {
+            Some(owner) => principal == Some(owner.as_str()),
+            None => principal.is_none() || !self.may_name_session,
+        }
+    }
+}
+
+static LOG_CHANNEL: Lazy<broadcast::Sender<LogEvent>> =
+    Lazy::new(|| broadcast::channel(256).0);
+
+/// `tracing` layer that publishes events from [`MCP_LOG_TARGETS`] to connected clients.
+/// It carries its own target filter, so it works alongside a stricter stderr filter.
+pub fn mcp_log_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    let targets = MCP_LOG_TARGETS
+        .iter()
+        .fold(Targets::new(), |targets, target| targets.with_target(*target, Level::DEBUG));
+    McpLogLayer.with_filter(targets)
+}
+
+struct McpLogLayer;
+
+impl<S: Subscriber> Layer<S> for McpLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        // Nobody connected: skip formatting entirely
+        if LOG_CHANNEL.receiver_count() == 0 {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let may_name_session = visitor.message.contains('@')
+            || visitor.fields.values().any(|value| value.as_str().is_some_and(|text| text.contains('@')));
+        let mut data = Map::new();
+        data.insert("message".to_string(), Value::String(visitor.message));
+        if !visitor.fields.is_empty() {
+            data.insert("fields".to_string(), Value::Object(visitor.fields));
+        }
+
+        let metadata = event.metadata();
+        let _ = LOG_CHANNEL.send(LogEvent {
+            owner: ClientManager::current_principal(),
+            may_name_session,
+            message: LoggingMessageNotificationParam {
+                level: level_from_tracing(metadata.level()),
+                logger: Some(metadata.target().to_string()),
+                data: Value::Object(data),
+            },
+        });
+    }
+}
+
+/// Collects an event's `message` and remaining fields
+#[derive(Default)]
+pub(crate) struct FieldVisitor {
+    pub(crate) message: String,
+    pub(crate) fields: Map<String, Value>,
+}
+
+impl tracing::field::Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.insert(field.name().to_string(), Value::String(value.to_string()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            self.fields.insert(field.name().to_string(), Value::String(format!("{value:?}")));
+        }
+    }
+}
+
+fn level_from_tracing(level: &Level) -> LoggingLevel {
+    match *level {
+        Level::ERROR => LoggingLevel::Error,
+        Level::WARN => LoggingLevel::Warning,
+        Level::INFO => LoggingLevel::Info,
+        Level::DEBUG | Level::TRACE => LoggingLevel::Debug,
+    }
+}
+
+/// Severity rank used to compare MCP logging levels
+fn level_rank(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
+
+/// Forwards captured log events to one client, authenticated as `principal`, until dropped
+pub struct McpLogForwarder {
+    min_level: Arc<AtomicU8>,
+    task: JoinHandle<()>,
+}
+
+impl McpLogForwarder {
+    pub fn spawn(peer: Peer<RoleServer>, level: LoggingLevel, principal: Option<String>) -> Self {
+        let min_level = Arc::new(AtomicU8::new(level_rank(level)));
+        let task_level = min_level.clone();
+        let mut receiver = LOG_CHANNEL.subscribe();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                if !event.visible_to(principal.as_deref())
+                    || level_rank(event.message.level) < task_level.load(Ordering::Relaxed)
+                {
+                    continue;
+                }
+
+                // The client went away; stop forwarding
+                if peer.notify_logging_message(event.message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { min_level, task }
+    }
+
+    pub fn set_level(&self, level: LoggingLevel) {
+        self.min_level.store(level_rank(level), Ordering::Relaxed);
+    }
+}
+
+impl Drop for McpLogForwarder {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(owner: Option<&str>, text: &str) -> LogEvent {
+        LogEvent {
+            owner: owner.map(str::to_string),
+            may_name_session: text.contains('@'),
+            message: LoggingMessageNotificationParam {
+                level: LoggingLevel::Info,
+                logger: None,
+                data: Value::String(text.to_string()),
+            },
+        }
+    }
+
+    #[test]
+    fn test_events_reach_only_their_principal() {
+        let owned = event(Some("alice"), "Page event in session 'shop': load");
+        assert!(owned.visible_to(Some("alice")));
+        assert!(!owned.visible_to(Some("bob")));
+        assert!(!owned.visible_to(None));
+
+        // Server-wide events reach everyone, unless they may name someone's session
+        let driver = event(None, "Started chromedriver on port 9515");
+        assert!(driver.visible_to(Some("bob")));
+        assert!(driver.visible_to(None));
+        let session = event(None, "Closing session: shop@alice");
+        assert!(!session.visible_to(Some("bob")));
+        assert!(session.visible_to(None));
+    }
+}
@@ -0,0 +1,604 @@
+//! Telling whether a login attempt worked
+//!
+//! Clicking a login form's submit button says nothing about the result. After
+//! the click the page is polled: a visible error banner or a caller-supplied
+//! failure element means the login failed, a caller-supplied success element
+//! means it worked, and otherwise leaving the login page (a new URL, or the
+//! password field going away in single-page apps) counts as success. Still
+//! sitting on the login URL with the password field when time runs out counts
+//! as failure. The second, one-time code step of two-factor logins is entered
+//! and judged the same way, watching the code field instead.
+//!
+//! Apps whose auth artifacts are known can skip the form: the token's cookies
+//! and storage entries are put into the app's origin, then a probe page is
+//! opened and watched the same way. Being sent to a login page or shown a
+//! password field means the app did not take the token; a caller-supplied
+//! success element, or the probe page staying put for a moment, means it did.
+
+use std::time::Duration;
+
+use fantoccini::{Client, Locator, cookies::Cookie};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use url::Url;
+
+use crate::{ClientManager, totp::{self, Totp}};
+
+/// How long to watch the page after submitting when the call does not say
+pub const DEFAULT_LOGIN_VERIFY_TIMEOUT_SECS: f64 = 10.0;
+
+/// How long to wait for the one-time code field when the call does not say
+pub const DEFAULT_OTP_FIELD_TIMEOUT_SECS: f64 = 15.0;
+
+/// How often the page is checked while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Field that stays on screen while the password step is unfinished
+pub const PASSWORD_FIELD: &str = "input[type='password']";
+
+/// Fields two-factor pages commonly use for the one-time code
+pub const OTP_FIELD: &str = "input[autocomplete='one-time-code'], input[name*='otp' i], input[id*='otp' i], \
+    input[name*='totp' i], input[name*='mfa' i], input[id*='mfa' i], input[name*='2fa' i], \
+    input[name*='code' i], input[id*='code' i], input[placeholder*='code' i]";
+
+/// How long the probe page of a token login must show no sign of a login page
+/// before it counts as recognized, for apps that redirect after rendering
+const SESSION_SETTLE_TIME: Duration = Duration::from_millis(1500);
+
+/// Path fragments of the pages apps send unrecognized visitors to
+const LOGIN_PATH_MARKERS: &[&str] = &["login", "signin", "sign-in", "sign_in", "logon", "sso"];
+
+/// A freshly computed code this close to expiry is skipped for the next one
+const MIN_CODE_LIFETIME_SECS: u64 = 3;
+
+/// Selectors of elements sites commonly use to report a failed login
+const ERROR_MARKERS: &[&str] = &[
+    "[role='alert']",
+    "[aria-live='assertive']",
+    ".error",
+    ".errors",
+    ".error-message",
+    ".form-error",
+    ".alert-danger",
+    ".alert-error",
+    ".flash-error",
+    ".invalid-feedback",
+    ".notification.is-danger",
+    "#error",
+    "#login-error",
+];
+
+/// Reads the URL, whether the login field `arguments[3]` is still visible, and
+/// the text of the first visible error marker (or of `arguments[1]`) and
+/// whether `arguments[2]` is present
+const LOGIN_STATE_SCRIPT: &str = r#"
+    const [errorMarkers, failureSelector, successSelector, fieldSelector] = arguments;
+    const visible = el => !!(el.offsetWidth || el.offsetHeight || el.getClientRects().length)
+        && window.getComputedStyle(el).visibility !== 'hidden';
+    const firstVisible = selector => {
+        try {
+            return Array.from(document.querySelectorAll(selector)).find(el => visible(el)) || null;
+        } catch (e) {
+            return null;
+        }
+    };
+    const textOf = el => (el.innerText ?? el.textContent ?? '').trim().replace(/\s+/g, ' ');
+
+    let errorText = null;
+    let customFailure = false;
+    if (failureSelector) {
+        const failure = firstVisible(failureSelector);
+        if (failure) {
+            customFailure = true;
+            errorText = textOf(failure) || failureSelector;
+        }
+    }
+    if (!errorText) {
+        for (const selector of errorMarkers) {
+            const marker = firstVisible(selector);
+            if (marker && textOf(marker)) {
+                errorText = textOf(marker);
+                break;
+            }
+        }
+    }
+
+    return {
+        url: location.href,
+        ready_state: document.readyState,
+        field_visible: !!firstVisible(fieldSelector),
+        error_text: errorText ? errorText.slice(0, 300) : null,
+        custom_failure: customFailure,
+        success_found: successSelector ? !!firstVisible(successSelector) : false,
+    };
+"#;
+
+/// Stores `arguments[0]` in localStorage and `arguments[1]` in sessionStorage;
+/// values that are not strings are stored as JSON
+const SET_STORAGE_SCRIPT: &str = r#"
+    const [local, session] = arguments;
+    const put = (storage, entries) => Object.entries(entries || {}).forEach(([key, value]) =>
+        storage.setItem(key, typeof value === 'string' ? value : JSON.stringify(value)));
+    put(window.localStorage, local);
+    put(window.sessionStorage, session);
+    return true;
+"#;
+
+/// A cookie carrying token material
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct TokenCookie {
+    pub name: String,
+    pub value: String,
+    /// Domain the cookie is sent to (default: the page's host only)
+    pub domain: Option<String>,
+    /// Path the cookie is sent for (default: "/")
+    pub path: Option<String>,
+    /// Only send the cookie over HTTPS
+    #[serde(default)]
+    pub secure: bool,
+    /// Hide the cookie from page scripts
+    #[serde(default)]
+    pub http_only: bool,
+}
+
+impl TokenCookie {
+    pub fn to_cookie(&self) -> Result<Cookie<'static>, String> {
+        if self.name.trim().is_empty() {
+            return Err("cookie names must not be empty".to_string());
+        }
+        let mut cookie = Cookie::new(self.name.clone(), self.value.clone());
+        if let Some(domain) = &self.domain {
+            cookie.set_domain(domain.clone());
+        }
+        cookie.set_path(self.path.clone().unwrap_or_else(|| "/".to_string()));
+        cookie.set_secure(self.secure);
+        cookie.set_http_only(self.http_only);
+        Ok(cookie)
+    }
+}
+
+/// Put token entries into the storage of the page `session` is on
+pub async fn set_storage(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    local_storage: &Map<String, Value>,
+    session_storage: &Map<String, Value>,
+) -> crate::Result<()> {
+    let arguments = vec![json!(local_storage), json!(session_storage)];
+    client_manager
+        .run_command(session, "set_storage", client.execute(SET_STORAGE_SCRIPT, arguments))
+        .await?;
+    Ok(())
+}
+
+/// What the caller knows about the page after a successful or failed login
+#[derive(Debug, Clone)]
+pub struct LoginCheck {
+    /// Element that only appears after a successful login
+    pub success_selector: Option<String>,
+    /// Element that only appears when the login failed
+    pub failure_selector: Option<String>,
+    /// Seconds to watch the page after submitting
+    pub timeout_secs: f64,
+    /// Field whose staying on screen means the step did not go through
+    pub field_selector: String,
+    /// What that field is called in reasons
+    pub field_name: &'static str,
+}
+
+impl Default for LoginCheck {
+    fn default() -> Self {
+        Self {
+            success_selector: None,
+            failure_selector: None,
+            timeout_secs: DEFAULT_LOGIN_VERIFY_TIMEOUT_SECS,
+            field_selector: PASSWORD_FIELD.to_string(),
+            field_name: "password field",
+        }
+    }
+}
+
+impl LoginCheck {
+    /// Read `success_selector`, `failure_selector` and `verify_timeout`
+    pub fn from_arguments(arguments: &Map<String, Value>) -> Self {
+        Self {
+            success_selector: arguments.get("success_selector").and_then(|v| v.as_str()).map(str::to_string),
+            failure_selector: arguments.get("failure_selector").and_then(|v| v.as_str()).map(str::to_string),
+            timeout_secs: arguments
+                .get("verify_timeout")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(DEFAULT_LOGIN_VERIFY_TIMEOUT_SECS),
+            ..Self::default()
+        }
+    }
+
+    /// The same check for the one-time code step, watching `field_selector`
+    pub fn for_otp(&self, field_selector: &str) -> Self {
+        Self { field_selector: field_selector.to_string(), field_name: "one-time code field", ..self.clone() }
+    }
+}
+
+/// How a login attempt ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginOutcome {
+    Success,
+    Failed,
+    /// The page changed in a way that is neither clearly success nor failure
+    Unverified,
+}
+
+impl LoginOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failed => "failed",
+            Self::Unverified => "unverified",
+        }
+    }
+}
+
+/// Result of watching the page after submitting
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginResult {
+    pub outcome: LoginOutcome,
+    /// Why the outcome was chosen
+    pub reason: String,
+    pub start_url: String,
+    pub url: String,
+    pub url_changed: bool,
+    pub error_text: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Poll the page after submitting until the login clearly worked or failed, or
+/// `check.timeout_secs` pass
+pub async fn await_login_result(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    start_url: &str,
+    check: &LoginCheck,
+) -> LoginResult {
+    let started = tokio::time::Instant::now();
+    let deadline = started + crate::waits::clamped_wait(check.timeout_secs);
+    let arguments = vec![
+        json!(ERROR_MARKERS),
+        json!(check.failure_selector),
+        json!(check.success_selector),
+        json!(check.field_selector),
+    ];
+
+    let mut last_state = json!({ "url": start_url });
+    loop {
+        // Reads fail while the next page is loading; keep the last good state
+        if let Ok(state) = client_manager
+            .run_command(session, "login_state", client.execute(LOGIN_STATE_SCRIPT, arguments.clone()))
+            .await
+        {
+            last_state = state;
+        }
+
+        let finished = tokio::time::Instant::now() >= deadline;
+        if let Some((outcome, reason)) = judge(&last_state, start_url, check, finished) {
+            let url = last_state.get("url").and_then(|v| v.as_str()).unwrap_or(start_url).to_string();
+            return LoginResult {
+                outcome,
+                reason,
+                url_changed: !same_page(start_url, &url),
+                start_url: start_url.to_string(),
+                url,
+                error_text: last_state.get("error_text").and_then(|v| v.as_str()).map(str::to_string),
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            };
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Poll the probe page of a token login until the app clearly recognized the
+/// session or sent it to log in, or `check.timeout_secs` pass
+pub async fn await_session_recognized(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    probe_url: &str,
+    check: &LoginCheck,
+) -> LoginResult {
+    let started = tokio::time::Instant::now();
+    let deadline = started + crate::waits::clamped_wait(check.timeout_secs);
+    let arguments = vec![
+        json!(ERROR_MARKERS),
+        json!(check.failure_selector),
+        json!(check.success_selector),
+        json!(check.field_selector),
+    ];
+
+    let mut last_state = json!({ "url": probe_url });
+    loop {
+        if let Ok(state) = client_manager
+            .run_command(session, "session_state", client.execute(LOGIN_STATE_SCRIPT, arguments.clone()))
+            .await
+        {
+            last_state = state;
+        }
+
+        let settled = started.elapsed() >= SESSION_SETTLE_TIME;
+        let finished = tokio::time::Instant::now() >= deadline;
+        if let Some((outcome, reason)) = judge_session(&last_state, probe_url, check, settled, finished) {
+            let url = last_state.get("url").and_then(|v| v.as_str()).unwrap_or(probe_url).to_string();
+            return LoginResult {
+                outcome,
+                reason,
+                url_changed: !same_page(probe_url, &url),
+                start_url: probe_url.to_string(),
+                url,
+                error_text: last_state.get("error_text").and_then(|v| v.as_str()).map(str::to_string),
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            };
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Decide from one reading of the probe page. Without a success element the
+/// page has to stay clear of login pages until `settled`.
+fn judge_session(
+    state: &Value,
+    probe_url: &str,
+    check: &LoginCheck,
+    settled: bool,
+    finished: bool,
+) -> Option<(LoginOutcome, String)> {
+    let url = state.get("url").and_then(|v| v.as_str()).unwrap_or(probe_url);
+    let loaded = state.get("ready_state").and_then(|v| v.as_str()).is_none_or(|ready| ready != "loading");
+    let field_visible = state.get("field_visible").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    if state.get("custom_failure").and_then(|v| v.as_bool()).unwrap_or(false) {
+        let error_text = state.get("error_text").and_then(|v| v.as_str()).unwrap_or_default();
+        return Some((LoginOutcome::Failed, format!("failure element appeared: {error_text}")));
+    }
+    if state.get("success_found").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Some((LoginOutcome::Success, "success element appeared".to_string()));
+    }
+    if loaded && !same_page(probe_url, url) && is_login_url(url) {
+        return Some((LoginOutcome::Failed, format!("the app sent the session to log in at {url}")));
+    }
+    if loaded && field_visible {
+        return Some((LoginOutcome::Failed, format!("{url} asks for a password")));
+    }
+    if check.success_selector.is_none() && loaded && settled {
+        return Some((LoginOutcome::Success, format!("{url} opened without asking to log in")));
+    }
+    if !finished {
+        return None;
+    }
+    Some((
+        LoginOutcome::Unverified,
+        format!("the success element did not appear; the page is now {url}"),
+    ))
+}
+
+/// Whether `url` looks like a login page
+fn is_login_url(url: &str) -> bool {
+    let path = Url::parse(url).map(|url| url.path().to_ascii_lowercase()).unwrap_or_default();
+    LOGIN_PATH_MARKERS.iter().any(|marker| path.contains(marker))
+}
+
+/// Decide from one reading of the page. `None` means keep waiting; once
+/// `finished` is set an answer is always given.
+fn judge(state: &Value, start_url: &str, check: &LoginCheck, finished: bool) -> Option<(LoginOutcome, String)> {
+    let url = state.get("url").and_then(|v| v.as_str()).unwrap_or(start_url);
+    let loaded = state.get("ready_state").and_then(|v| v.as_str()).is_none_or(|ready| ready != "loading");
+    let field_visible = state.get("field_visible").and_then(|v| v.as_bool()).unwrap_or(true);
+    let error_text = state.get("error_text").and_then(|v| v.as_str());
+    let moved = !same_page(start_url, url);
+
+    if state.get("custom_failure").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Some((LoginOutcome::Failed, format!("failure element appeared: {}", error_text.unwrap_or_default())));
+    }
+    if state.get("success_found").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Some((LoginOutcome::Success, "success element appeared".to_string()));
+    }
+    if let Some(error) = error_text
+        && field_visible
+        && loaded
+    {
+        return Some((LoginOutcome::Failed, format!("the page shows an error: {error}")));
+    }
+    if check.success_selector.is_none() && loaded && !field_visible {
+        let reason = if moved {
+            format!("left the login page for {url}")
+        } else {
+            format!("the {} went away", check.field_name)
+        };
+        return Some((LoginOutcome::Success, reason));
+    }
+    if !finished {
+        return None;
+    }
+
+    Some(match (moved, field_visible) {
+        (false, true) => (LoginOutcome::Failed, format!("still on the same page with the {}", check.field_name)),
+        (true, true) => (LoginOutcome::Unverified, format!("moved to {url}, which still shows a {}", check.field_name)),
+        _ => (
+            LoginOutcome::Unverified,
+            format!("the success element did not appear; the page is now {url}"),
+        ),
+    })
+}
+
+/// The one-time code to enter: `code_key` as given, or one computed from
+/// `totp_secret`. A computed code about to expire is skipped by waiting for the
+/// next one, so it is still valid when the page checks it.
+pub async fn otp_code(arguments: &Map<String, Value>, code_key: &str) -> Result<Option<String>, String> {
+    if let Some(code) = arguments.get(code_key).and_then(|v| v.as_str()) {
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        if code.is_empty() {
+            return Err(format!("'{code_key}' is empty"));
+        }
+        return Ok(Some(code));
+    }
+    let Some(secret) = arguments.get("totp_secret").and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    let totp = Totp::parse(secret)?;
+    let remaining = totp.seconds_remaining(totp::now());
+    if remaining < MIN_CODE_LIFETIME_SECS {
+        tokio::time::sleep(Duration::from_secs(remaining)).await;
+    }
+    Ok(Some(totp.code_at(totp::now())))
+}
+
+/// Wait for the one-time code field, type `code` into it and submit. Returns
+/// the page URL before submitting, for [`await_login_result`].
+///
+/// Codes split over one box per digit are typed a digit per box. Without a
+/// `submit_selector` the usual submit buttons are tried, then Enter; pages that
+/// submit on their own once the last digit is in make those fail harmlessly.
+pub async fn enter_otp(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    code: &str,
+    field_selector: &str,
+    submit_selector: Option<&str>,
+    wait_timeout: f64,
+) -> Result<String, String> {
+    let field = client_manager
+        .find_element_with_wait(client, field_selector, Some(wait_timeout))
+        .await
+        .map_err(|e| format!("No one-time code field '{field_selector}' appeared within {wait_timeout}s: {e}"))?;
+    let start_url = client_manager
+        .run_command(session, "current_url", client.current_url())
+        .await
+        .map_err(|e| format!("Failed to read the page URL: {e}"))?
+        .to_string();
+
+    let single_digit = field.attr("maxlength").await.ok().flatten().as_deref() == Some("1");
+    let boxes = if single_digit {
+        client.find_all(Locator::Css("input[maxlength='1']")).await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    if single_digit && boxes.len() >= code.chars().count() {
+        let mut buffer = [0u8; 4];
+        for (digit, input) in code.chars().zip(&boxes) {
+            input
+                .send_keys(digit.encode_utf8(&mut buffer))
+                .await
+                .map_err(|e| format!("Failed to type into a one-time code box: {e}"))?;
+        }
+    } else {
+        field.clear().await.map_err(|e| format!("Failed to clear the one-time code field: {e}"))?;
+        field.send_keys(code).await.map_err(|e| format!("Failed to type the one-time code: {e}"))?;
+    }
+
+    match submit_selector {
+        Some(selector) => {
+            let button = client
+                .find(Locator::Css(selector))
+                .await
+                .map_err(|e| format!("Failed to find submit button '{selector}': {e}"))?;
+            button.click().await.map_err(|e| format!("Failed to click submit button '{selector}': {e}"))?;
+        }
+        None => {
+            let clicked = match client.find(Locator::Css("button[type='submit'], input[type='submit']")).await {
+                Ok(button) => button.click().await.is_ok(),
+                Err(_) => false,
+            };
+            if !clicked {
+                let _ = field.send_keys("\n").await;
+            }
+        }
+    }
+    Ok(start_url)
+}
+
+/// Whether two URLs are the same page, ignoring the fragment
+fn same_page(a: &str, b: &str) -> bool {
+    match (Url::parse(a), Url::parse(b)) {
+        (Ok(mut a), Ok(mut b)) => {
+            a.set_fragment(None);
+            b.set_fragment(None);
+            a == b
+        }
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGIN: &str = "https://example.com/login";
+
+    fn state(url: &str, field_visible: bool, error: Option<&str>) -> Value {
+        json!({ "url": url, "ready_state": "complete", "field_visible": field_visible, "error_text": error })
+    }
+
+    #[test]
+    fn test_judge_login_outcomes() {
+        let check = LoginCheck::default();
+        let outcome = |state: &Value, finished| judge(state, LOGIN, &check, finished).map(|(outcome, _)| outcome);
+
+        assert_eq!(outcome(&state("https://example.com/home", false, None), false), Some(LoginOutcome::Success));
+        assert_eq!(outcome(&state(LOGIN, false, None), false), Some(LoginOutcome::Success));
+        assert_eq!(outcome(&state(LOGIN, true, Some("Wrong password")), false), Some(LoginOutcome::Failed));
+        assert_eq!(outcome(&state(LOGIN, true, None), false), None);
+        assert_eq!(outcome(&state(&format!("{LOGIN}#"), true, None), true), Some(LoginOutcome::Failed));
+        assert_eq!(
+            outcome(&state("https://example.com/login?retry=1", true, None), true),
+            Some(LoginOutcome::Unverified)
+        );
+
+        let with_selectors = LoginCheck {
+            success_selector: Some("#dashboard".to_string()),
+            failure_selector: Some(".oops".to_string()),
+            ..LoginCheck::default()
+        };
+        let mut found = state("https://example.com/home", false, None);
+        assert_eq!(judge(&found, LOGIN, &with_selectors, false), None);
+        found["success_found"] = json!(true);
+        assert_eq!(judge(&found, LOGIN, &with_selectors, false).unwrap().0, LoginOutcome::Success);
+        found["custom_failure"] = json!(true);
+        assert_eq!(judge(&found, LOGIN, &with_selectors, false).unwrap().0, LoginOutcome::Failed);
+    }
+
+    #[test]
+    fn test_judge_token_sessions() {
+        const PROBE: &str = "https://example.com/account";
+        let check = LoginCheck::default();
+        let outcome = |state: &Value, settled, finished| {
+            judge_session(state, PROBE, &check, settled, finished).map(|(outcome, _)| outcome)
+        };
+
+        assert_eq!(outcome(&state(PROBE, false, None), false, false), None);
+        assert_eq!(outcome(&state(PROBE, false, None), true, false), Some(LoginOutcome::Success));
+        assert_eq!(outcome(&state(PROBE, true, None), false, false), Some(LoginOutcome::Failed));
+        let redirected = state("https://example.com/users/sign_in?return_to=%2Faccount", false, None);
+        assert_eq!(outcome(&redirected, false, false), Some(LoginOutcome::Failed));
+        assert_eq!(outcome(&state("https://example.com/home", false, None), true, false), Some(LoginOutcome::Success));
+
+        let with_selector = LoginCheck { success_selector: Some("#avatar".to_string()), ..LoginCheck::default() };
+        let mut found = state(PROBE, false, None);
+        assert_eq!(judge_session(&found, PROBE, &with_selector, true, false), None);
+        assert_eq!(judge_session(&found, PROBE, &with_selector, true, true).unwrap().0, LoginOutcome::Unverified);
+        found["success_found"] = json!(true);
+        assert_eq!(judge_session(&found, PROBE, &with_selector, false, false).unwrap().0, LoginOutcome::Success);
+
+        let cookie = TokenCookie {
+            name: "sid".to_string(),
+            value: "abc".to_string(),
+            domain: Some(".example.com".to_string()),
+            path: None,
+            secure: true,
+            http_only: true,
+        };
+        let cookie = cookie.to_cookie().unwrap();
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(cookie.http_only(), Some(true));
+    }
+}
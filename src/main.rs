@@ -1,34 +1,178 @@
 use anyhow::Result;
-use clap::{Parser, ValueEnum};
-use rust_browser_mcp::{Config, WebDriverServer};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rust_browser_mcp::{Config, WebDriverServer, file_log_layer, mcp_log_layer};
+use tracing_subscriber::{Layer, layer::SubscriberExt, util::SubscriberInitExt};
 
+mod commands;
 mod servers;
-use servers::{run_http_server, run_stdio_server};
+use commands::{RecipesCommand, run_cleanup, run_doctor, run_recipes_command};
+use servers::{run_http_server, run_stdio_server, run_ws_server};
 
 #[derive(Parser)]
 #[command(name = "rust-browser-mcp")]
 #[command(about = "Rust Browser MCP Server - Browser automation for Claude")]
 #[command(version)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Read settings from this TOML file; WEBDRIVER_* environment variables and flags override it
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<std::path::PathBuf>,
+
+    /// Server options when no subcommand is given (same as `serve`)
+    #[command(flatten)]
+    serve: ServeArgs,
+
+    #[command(flatten)]
+    options: ConfigArgs,
+}
+
+#[derive(Args)]
+struct ServeArgs {
     /// Server transport mode
     #[arg(short, long, default_value = "stdio")]
     transport: TransportMode,
 
-    /// HTTP server bind address (only used with --transport=http)
+    /// Server bind address (only used with --transport=http or --transport=ws)
     #[arg(long, default_value = "127.0.0.1:8080")]
     bind: String,
 
-    /// Disable OAuth authentication for HTTP server
+    /// Disable OAuth authentication for the HTTP and WebSocket servers
     #[arg(long)]
     no_auth: bool,
+}
+
+/// Settings overriding the config file and environment, accepted by every subcommand
+#[derive(Args)]
+struct ConfigArgs {
+    /// Accept this API key instead of OAuth, as `id:key` or a bare key (repeatable)
+    #[arg(long = "api-key", value_name = "KEY", global = true)]
+    api_keys: Vec<String>,
+
+    /// Accept API keys listed in this file (one `id:key` per line), reloaded when it changes
+    #[arg(long, value_name = "PATH", global = true)]
+    api_keys_file: Option<String>,
+
+    /// Header carrying the API key (default: Authorization, as `Bearer <key>`)
+    #[arg(long, value_name = "NAME", global = true)]
+    api_key_header: Option<String>,
+
+    /// Reject OAuth tokens that carry no `browser:*` scope
+    #[arg(long, global = true)]
+    require_scopes: bool,
+
+    /// Validate bearer JWTs against this external identity provider JWKS endpoint
+    #[arg(long, value_name = "URL", global = true)]
+    jwt_jwks_url: Option<String>,
+
+    /// Required JWT issuer (`iss` claim)
+    #[arg(long, value_name = "ISSUER", global = true)]
+    jwt_issuer: Option<String>,
+
+    /// Accepted JWT audience (`aud` claim; repeatable or comma-separated; required with --jwt-jwks-url)
+    #[arg(long = "jwt-audience", value_delimiter = ',', global = true)]
+    jwt_audiences: Vec<String>,
+
+    /// Tool calls each HTTP/WebSocket client may make per rolling minute
+    #[arg(long, value_name = "N", global = true)]
+    rate_limit_per_minute: Option<u32>,
+
+    /// Tool calls each HTTP/WebSocket client may have in flight at once
+    #[arg(long, value_name = "N", global = true)]
+    max_concurrent_calls: Option<usize>,
+
+    /// Seconds wait_for_element and wait_for_condition wait when the call sets no timeout
+    #[arg(long, value_name = "SECS", global = true)]
+    default_wait_timeout_secs: Option<f64>,
+
+    /// Page load timeout for new browser sessions, in milliseconds
+    #[arg(long, value_name = "MS", global = true)]
+    navigation_timeout_ms: Option<u64>,
+
+    /// Script timeout for new browser sessions, in milliseconds
+    #[arg(long, value_name = "MS", global = true)]
+    script_timeout_ms: Option<u64>,
+
+    /// Hard limit on any single WebDriver command, in seconds
+    #[arg(long, value_name = "SECS", global = true)]
+    call_timeout_secs: Option<u64>,
+
+    /// Seconds to let in-flight tool calls finish on shutdown before closing browsers
+    #[arg(long, value_name = "SECS", global = true)]
+    drain_timeout_secs: Option<u64>,
+
+    /// Allow browser-based clients from this origin (repeatable or comma-separated; default: any)
+    #[arg(long = "cors-origin", value_delimiter = ',', global = true)]
+    cors_origins: Vec<String>,
 
-    /// Browser driver to use
-    #[arg(short, long, default_value = "chrome")]
-    browser: BrowserType,
+    /// Serve under this URL prefix when behind a reverse proxy, e.g. /mcp/browser
+    #[arg(long, value_name = "PATH", global = true)]
+    base_path: Option<String>,
+
+    /// Browser driver to use [default: chrome, unless chosen by --config]
+    #[arg(short, long, global = true)]
+    browser: Option<BrowserType>,
 
     /// Enable Chrome performance memory APIs for enhanced memory monitoring
-    #[arg(long)]
+    #[arg(long, global = true)]
     enable_performance_memory: bool,
+
+    /// Never start a browser implicitly; sessions must be opened with create_session
+    #[arg(long, global = true)]
+    no_auto_create_sessions: bool,
+
+    /// Publish each saved recipe as its own `recipe_<name>` tool
+    #[arg(long, global = true)]
+    expose_recipe_tools: bool,
+
+    /// Disable a tool category: automation, scripting, performance, recipes, driver_management, admin (repeatable or comma-separated)
+    #[arg(long = "disable-category", value_delimiter = ',', global = true)]
+    disable_categories: Vec<String>,
+
+    /// Disable an individual tool, e.g. execute_script (repeatable or comma-separated)
+    #[arg(long = "disable-tool", value_delimiter = ',', global = true)]
+    disable_tools: Vec<String>,
+
+    /// Keep an individual tool enabled even if its category is disabled (repeatable or comma-separated)
+    #[arg(long = "enable-tool", value_delimiter = ',', global = true)]
+    enable_tools: Vec<String>,
+
+    /// Append an audit entry for every tool call to this JSONL file
+    #[arg(long, value_name = "PATH", global = true)]
+    audit_log: Option<String>,
+
+    /// Also write the server log to this file
+    #[arg(long, value_name = "PATH", global = true)]
+    log_file: Option<String>,
+
+    /// Log file format
+    #[arg(long, value_name = "FORMAT", value_parser = ["text", "json"], global = true)]
+    log_format: Option<String>,
+
+    /// Rotate the log file at every hour or day boundary, in addition to its size limit
+    #[arg(long, value_name = "SCHEDULE", value_parser = ["never", "hourly", "daily"], global = true)]
+    log_rotation: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the MCP server (the default when no subcommand is given)
+    Serve(ServeArgs),
+    /// Check browsers, drivers and ports, and print how to fix problems
+    Doctor,
+    /// List, validate or run saved recipes without an MCP client
+    #[command(subcommand)]
+    Recipes(RecipesCommand),
+    /// Kill orphaned WebDriver and browser processes without starting a server
+    Cleanup {
+        /// Kill every WebDriver and automated browser process, including ones
+        /// other programs or a running server still use
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print the effective configuration (file, environment and flags merged) as TOML
+    PrintConfig,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -37,6 +181,8 @@ enum TransportMode {
     Stdio,
     /// HTTP streaming server
     Http,
+    /// WebSocket server, one JSON-RPC message per text frame
+    Ws,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -52,6 +198,7 @@ enum BrowserType {
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let config = build_config(cli.config.as_deref(), cli.options)?;
 
     let default_log_level = if cfg!(debug_assertions) {
         "info"
@@ -59,37 +206,42 @@ async fn main() -> Result<()> {
         "error"
     };
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(default_log_level)),
+    // stderr honours RUST_LOG; driver/session events are also forwarded to MCP
+    // clients as log notifications, filtered by the level each client sets.
+    // The optional log file has its own filter (`log_file_level`).
+    let file_layer = file_log_layer(&config)
+        .map_err(|e| anyhow::anyhow!("Failed to open log file: {e}"))?;
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_ansi(false)
+                .with_filter(
+                    tracing_subscriber::EnvFilter::try_from_default_env()
+                        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(default_log_level)),
+                ),
         )
-        .with_writer(std::io::stderr)
-        .with_ansi(false)
+        .with(mcp_log_layer())
         .init();
 
-    // Create config with preferred browser and CLI options
-    let mut config = Config::from_env();
-    let preferred_browser = match cli.browser {
-        BrowserType::Chrome => "chrome".to_string(),
-        BrowserType::Firefox => "firefox".to_string(),
-        BrowserType::Edge => "edge".to_string(),
+    let serve = match cli.command {
+        None => cli.serve,
+        Some(Command::Serve(serve)) => serve,
+        Some(Command::Doctor) => return run_doctor(&config).await,
+        Some(Command::Recipes(command)) => return run_recipes_command(config, command).await,
+        Some(Command::Cleanup { force }) => return run_cleanup(force).await,
+        Some(Command::PrintConfig) => {
+            print!("{}", toml::to_string(&config.redacted())?);
+            return Ok(());
+        }
     };
 
-    config.preferred_driver = Some(preferred_browser.clone());
-    // When a specific browser is chosen via CLI, only start that browser instead of all concurrent drivers
-    config.concurrent_drivers = vec![preferred_browser];
-
-    // Override performance memory setting from CLI if provided
-    if cli.enable_performance_memory {
-        config.enable_performance_memory = true;
-    }
-
     let server = WebDriverServer::with_config(config).inspect_err(|e| {
         tracing::error!("Failed to create WebDriver server: {}", e);
     })?;
 
-    match cli.transport {
+    match serve.transport {
         TransportMode::Stdio => {
             tracing::info!(
                 "Starting WebDriver MCP Server on stdio with auto-detection and auto-start"
@@ -99,8 +251,116 @@ async fn main() -> Result<()> {
             run_stdio_server(server).await
         }
         TransportMode::Http => {
-            tracing::info!("Starting WebDriver MCP Server on HTTP at {}", cli.bind);
-            run_http_server(server, &cli.bind, cli.no_auth).await
+            tracing::info!("Starting WebDriver MCP Server on HTTP at {}", serve.bind);
+            run_http_server(server, &serve.bind, serve.no_auth).await
+        }
+        TransportMode::Ws => {
+            tracing::info!("Starting WebDriver MCP Server on WebSocket at {}", serve.bind);
+            run_ws_server(server, &serve.bind, serve.no_auth).await
         }
     }
 }
+
+/// Layer config: built-in defaults, then the config file, then environment, then CLI options
+fn build_config(config_path: Option<&std::path::Path>, options: ConfigArgs) -> Result<Config> {
+    let mut config = match config_path {
+        Some(path) => Config::from_file(path).map_err(anyhow::Error::msg)?,
+        None => Config::from_env(),
+    };
+
+    // A config file picks its own drivers unless a browser is chosen explicitly
+    let browser = options.browser.or_else(|| config_path.is_none().then_some(BrowserType::Chrome));
+    if let Some(browser) = browser {
+        let preferred_browser = match browser {
+            BrowserType::Chrome => "chrome".to_string(),
+            BrowserType::Firefox => "firefox".to_string(),
+            BrowserType::Edge => "edge".to_string(),
+        };
+
+        config.preferred_driver = Some(preferred_browser.clone());
+        // When a specific browser is chosen via CLI, only start that browser instead of all concurrent drivers
+        config.concurrent_drivers = vec![preferred_browser];
+    }
+
+    // Override performance memory setting from CLI if provided
+    if options.enable_performance_memory {
+        config.enable_performance_memory = true;
+    }
+
+    if options.no_auto_create_sessions {
+        config.auto_create_sessions = false;
+    }
+
+    if options.expose_recipe_tools {
+        config.expose_recipes_as_tools = true;
+    }
+
+    config.disabled_tool_categories.extend(options.disable_categories);
+    config.disabled_tools.extend(options.disable_tools);
+    config.enabled_tools.extend(options.enable_tools);
+
+    if let Some(path) = options.audit_log {
+        config.audit_log_path = Some(path);
+    }
+
+    if let Some(path) = options.log_file {
+        config.log_file = Some(path);
+    }
+    if let Some(format) = options.log_format {
+        config.log_file_format = format;
+    }
+    if let Some(rotation) = options.log_rotation {
+        config.log_file_rotation = rotation;
+    }
+
+    config.api_keys.extend(options.api_keys);
+    if let Some(path) = options.api_keys_file {
+        config.api_keys_file = Some(path);
+    }
+    if let Some(header) = options.api_key_header {
+        config.api_key_header = header;
+    }
+
+    if options.require_scopes {
+        config.require_scopes = true;
+    }
+
+    if let Some(url) = options.jwt_jwks_url {
+        config.jwt_jwks_url = Some(url);
+    }
+    if let Some(issuer) = options.jwt_issuer {
+        config.jwt_issuer = Some(issuer);
+    }
+    config.jwt_audience.extend(options.jwt_audiences);
+
+    if let Some(limit) = options.rate_limit_per_minute {
+        config.rate_limit_per_minute = Some(limit);
+    }
+    if let Some(limit) = options.max_concurrent_calls {
+        config.max_concurrent_calls = Some(limit);
+    }
+
+    if let Some(secs) = options.default_wait_timeout_secs {
+        config.default_wait_timeout_secs = secs;
+    }
+    if let Some(ms) = options.navigation_timeout_ms {
+        config.navigation_timeout_ms = Some(ms);
+    }
+    if let Some(ms) = options.script_timeout_ms {
+        config.script_timeout_ms = Some(ms);
+    }
+    if let Some(secs) = options.call_timeout_secs {
+        config.webdriver_call_timeout_secs = secs;
+    }
+
+    if let Some(secs) = options.drain_timeout_secs {
+        config.shutdown_drain_timeout_secs = secs;
+    }
+
+    config.cors_allowed_origins.extend(options.cors_origins);
+    if let Some(path) = options.base_path {
+        config.base_path = Some(path);
+    }
+
+    Ok(config)
+}
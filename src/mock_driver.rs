@@ -0,0 +1,671 @@
+//! An in-process WebDriver for tests
+//!
+//! [`MockDriver`] speaks enough of the W3C WebDriver protocol for the tools to
+//! run against it: sessions, navigation and history, element lookup and
+//! interaction, scripts, screenshots and window geometry. Pages are canned
+//! [`MockPage`]s registered per URL, so tests exercise the whole pipeline —
+//! MCP call, handler, [`ClientManager`](crate::ClientManager), HTTP to the
+//! driver — without installing a browser or spawning a driver process.
+//!
+//! Element lookups compare the selector string with each [`MockElement`]'s
+//! selector verbatim; there is no CSS engine. Scripts return what was
+//! registered with [`MockDriver::on_script`], or `null`. Chrome DevTools
+//! Protocol commands fail as on geckodriver unless [`MockDriver::enable_cdp`]
+//! was called.
+//!
+//! ```no_run
+//! use rust_browser_mcp::{BrowserSession, ClientManager, MockDriver, MockElement, MockPage};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let driver = MockDriver::start().await?;
+//! driver.add_page(
+//!     "https://shop.test/",
+//!     MockPage::new("Shop").element(MockElement::new("#buy").text("Buy").navigates_to("https://shop.test/thanks")),
+//! );
+//! driver.add_page("https://shop.test/thanks", MockPage::new("Thank you"));
+//!
+//! let manager = ClientManager::new(driver.config())?;
+//! let session = BrowserSession::open(&manager, "default").await?;
+//! session.navigate("https://shop.test/").await?;
+//! session.click("#buy").await?;
+//! assert_eq!(session.current_page().await?.title, "Thank you");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::State,
+    http::{Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use serde_json::{Value, json};
+use tokio::task::JoinHandle;
+
+use crate::Config;
+
+/// W3C key that identifies an element reference in a response
+const ELEMENT_KEY: &str = "element-6066-11e4-a52e-4f735466cecf";
+
+/// A 1x1 transparent PNG, returned for every screenshot
+const SCREENSHOT_PNG: &str =
+    "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+
+const WINDOW_HANDLE: &str = "mock-window";
+
+/// An element on a [`MockPage`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockElement {
+    /// Selector the element answers to, matched verbatim
+    pub selector: String,
+    pub tag: String,
+    pub text: String,
+    pub attributes: HashMap<String, String>,
+    /// Current `value` property; typing appends to it
+    pub value: String,
+    pub displayed: bool,
+    /// Page loaded when the element is clicked or Enter is typed into it
+    pub navigates_to: Option<String>,
+}
+
+impl MockElement {
+    /// A visible, empty `<div>` matching `selector`
+    pub fn new(selector: impl Into<String>) -> Self {
+        Self {
+            selector: selector.into(),
+            tag: "div".to_string(),
+            text: String::new(),
+            attributes: HashMap::new(),
+            value: String::new(),
+            displayed: true,
+            navigates_to: None,
+        }
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = tag.into();
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    pub fn attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn value(mut self, value: impl Into<String>) -> Self {
+        self.value = value.into();
+        self
+    }
+
+    pub fn hidden(mut self) -> Self {
+        self.displayed = false;
+        self
+    }
+
+    pub fn navigates_to(mut self, url: impl Into<String>) -> Self {
+        self.navigates_to = Some(url.into());
+        self
+    }
+
+    fn property(&self, name: &str) -> Value {
+        match name {
+            "value" => Value::String(self.value.clone()),
+            "tagName" => Value::String(self.tag.to_uppercase()),
+            "textContent" | "innerText" => Value::String(self.text.clone()),
+            "checked" | "selected" | "disabled" => Value::Bool(self.attributes.contains_key(name)),
+            _ => self.attributes.get(name).map_or(Value::Null, |value| Value::String(value.clone())),
+        }
+    }
+}
+
+/// A canned page served for one URL
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockPage {
+    pub title: String,
+    /// Page source; generated from the title and element texts when unset
+    pub source: Option<String>,
+    pub elements: Vec<MockElement>,
+    /// How long navigating to the page takes; other sessions are served meanwhile
+    pub load_time: Duration,
+}
+
+impl MockPage {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self { title: title.into(), ..Self::default() }
+    }
+
+    pub fn element(mut self, element: MockElement) -> Self {
+        self.elements.push(element);
+        self
+    }
+
+    pub fn source(mut self, html: impl Into<String>) -> Self {
+        self.source = Some(html.into());
+        self
+    }
+
+    /// Make navigations to the page take `load_time`, like a slow site
+    pub fn load_time(mut self, load_time: Duration) -> Self {
+        self.load_time = load_time;
+        self
+    }
+
+    fn html(&self) -> String {
+        if let Some(source) = &self.source {
+            return source.clone();
+        }
+        let body: Vec<String> = self
+            .elements
+            .iter()
+            .map(|element| format!("<{0}>{1}</{0}>", element.tag, element.text))
+            .collect();
+        format!("<html><head><title>{}</title></head><body>{}</body></html>", self.title, body.join(""))
+    }
+}
+
+/// Page state of one WebDriver session
+struct MockSession {
+    history: Vec<String>,
+    position: usize,
+    page: MockPage,
+    /// Changes on every load so element references from earlier pages go stale
+    generation: u64,
+    window: Value,
+}
+
+#[derive(Default)]
+struct DriverState {
+    pages: HashMap<String, MockPage>,
+    scripts: Vec<(String, Value)>,
+    /// Source and arguments of every script run, oldest first
+    script_calls: Vec<(String, Value)>,
+    sessions: HashMap<String, MockSession>,
+    commands: Vec<String>,
+    next_id: u64,
+    /// Set by `crash_page`: session commands fail as after a renderer crash
+    crashed: bool,
+    /// Set by `enable_cdp`: `goog/cdp/execute` is answered as by chromedriver
+    cdp: bool,
+}
+
+impl DriverState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Show `url` in `session`, replacing the page and its elements
+    fn load(&mut self, session: &str, url: &str) {
+        let page = self.pages.get(&normalize_url(url)).cloned().unwrap_or_default();
+        let generation = self.next_id();
+        if let Some(state) = self.sessions.get_mut(session) {
+            state.page = page;
+            state.generation = generation;
+        }
+    }
+
+    fn navigate(&mut self, session: &str, url: &str) {
+        let url = normalize_url(url);
+        if let Some(state) = self.sessions.get_mut(session) {
+            state.history.truncate(state.position + 1);
+            state.history.push(url.clone());
+            state.position = state.history.len() - 1;
+        }
+        self.load(session, &url);
+    }
+
+    fn script_result(&self, script: &str) -> Value {
+        self.scripts
+            .iter()
+            .find(|(fragment, _)| script.contains(fragment.as_str()))
+            .map_or(Value::Null, |(_, result)| result.clone())
+    }
+}
+
+/// A WebDriver server on a random local port, stopped when dropped
+pub struct MockDriver {
+    endpoint: String,
+    state: Arc<Mutex<DriverState>>,
+    server: JoinHandle<()>,
+}
+
+impl MockDriver {
+    /// Listen on `127.0.0.1` with an OS-assigned port
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+        let endpoint = format!("http://{}", listener.local_addr()?);
+        let state = Arc::new(Mutex::new(DriverState::default()));
+        let router = Router::new().fallback(dispatch).with_state(state.clone());
+        let server = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                tracing::warn!("Mock WebDriver stopped: {}", e);
+            }
+        });
+        Ok(Self { endpoint, state, server })
+    }
+
+    /// Port the driver listens on
+    pub fn port(&self) -> u16 {
+        self.endpoint.rsplit(':').next().and_then(|port| port.parse().ok()).unwrap_or_default()
+    }
+
+    /// Base URL to point a WebDriver client at
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Default config using this driver as the WebDriver endpoint, with driver
+    /// auto-start off. Mock pages have no layout, so clicks do not wait for
+    /// their target to hold still.
+    pub fn config(&self) -> Config {
+        Config {
+            webdriver_endpoint: self.endpoint.clone(),
+            auto_start_driver: false,
+            wait_for_stable_elements: false,
+            politeness_min_delay_ms: 0,
+            politeness_max_requests_per_minute: 0,
+            ..Config::default()
+        }
+    }
+
+    /// Serve `page` at `url`; unregistered URLs load an empty, untitled page
+    pub fn add_page(&self, url: &str, page: MockPage) {
+        self.lock().pages.insert(normalize_url(url), page);
+    }
+
+    /// Answer scripts containing `fragment` with `result`. Earlier registrations win.
+    pub fn on_script(&self, fragment: impl Into<String>, result: Value) {
+        self.lock().scripts.push((fragment.into(), result));
+    }
+
+    /// Arguments of the scripts run so far that contain `fragment`, oldest first
+    pub fn script_args(&self, fragment: &str) -> Vec<Value> {
+        let state = self.lock();
+        let calls = state.script_calls.iter().filter(|(script, _)| script.contains(fragment));
+        calls.map(|(_, args)| args.clone()).collect()
+    }
+
+    /// Commands received so far, e.g. `"navigate https://shop.test/"` or `"click #buy"`
+    pub fn commands(&self) -> Vec<String> {
+        self.lock().commands.clone()
+    }
+
+    /// Make the current page crash like Chrome's "Aw, Snap!": session commands
+    /// fail with `tab crashed` until a new tab is opened
+    pub fn crash_page(&self) {
+        self.lock().crashed = true;
+    }
+
+    /// Answer Chrome DevTools Protocol commands like chromedriver, each with an
+    /// empty result and recorded as e.g. `"cdp Network.clearBrowserCache"`
+    pub fn enable_cdp(&self) {
+        self.lock().cdp = true;
+    }
+
+    /// Number of sessions currently open
+    pub fn session_count(&self) -> usize {
+        self.lock().sessions.len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DriverState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for MockDriver {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn normalize_url(url: &str) -> String {
+    url::Url::parse(url).map_or_else(|_| url.to_string(), |parsed| parsed.to_string())
+}
+
+fn success(value: Value) -> Response {
+    Json(json!({ "value": value })).into_response()
+}
+
+/// A W3C error response, e.g. `no such element`
+fn failure(status: StatusCode, error: &str, message: impl Into<String>) -> Response {
+    let body = json!({ "value": { "error": error, "message": message.into(), "stacktrace": "" } });
+    (status, Json(body)).into_response()
+}
+
+fn unknown_command(method: &Method, uri: &Uri) -> Response {
+    let message = format!("{} {} is not supported by the mock driver", method, uri.path());
+    failure(StatusCode::NOT_FOUND, "unknown command", message)
+}
+
+async fn dispatch(State(state): State<Arc<Mutex<DriverState>>>, method: Method, uri: Uri, body: Bytes) -> Response {
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    let segments: Vec<&str> = uri.path().split('/').filter(|segment| !segment.is_empty()).collect();
+    if let ("POST", ["session", _, "url"]) = (method.as_str(), segments.as_slice()) {
+        let load_time = page_load_time(&state, body["url"].as_str().unwrap_or_default());
+        tokio::time::sleep(load_time).await;
+    }
+    let mut state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    match (method.as_str(), segments.as_slice()) {
+        ("GET", ["status"]) => success(json!({ "ready": true, "message": "mock driver ready" })),
+        ("POST", ["session"]) => {
+            let id = format!("mock-session-{}", state.next_id());
+            state.commands.push("new_session".to_string());
+            state.sessions.insert(
+                id.clone(),
+                MockSession {
+                    history: vec!["about:blank".to_string()],
+                    position: 0,
+                    page: MockPage::default(),
+                    generation: 0,
+                    window: json!({ "x": 0, "y": 0, "width": 1280, "height": 720 }),
+                },
+            );
+            success(json!({
+                "sessionId": id,
+                "capabilities": { "browserName": "mock", "browserVersion": env!("CARGO_PKG_VERSION") }
+            }))
+        }
+        (_, ["session", session, command @ ..]) => {
+            let session = session.to_string();
+            if !state.sessions.contains_key(&session) {
+                return failure(StatusCode::NOT_FOUND, "invalid session id", format!("No session {}", session));
+            }
+            session_command(&mut state, &session, method.as_str(), command, &body)
+                .unwrap_or_else(|| unknown_command(&method, &uri))
+        }
+        _ => unknown_command(&method, &uri),
+    }
+}
+
+/// Load time of the page registered for `url`, waited out before the driver state is locked
+fn page_load_time(state: &Mutex<DriverState>, url: &str) -> Duration {
+    let state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    state.pages.get(&normalize_url(url)).map_or(Duration::ZERO, |page| page.load_time)
+}
+
+/// Handle `/session/{id}/...`; `None` for commands the mock does not know
+fn session_command(
+    state: &mut DriverState,
+    session: &str,
+    method: &str,
+    command: &[&str],
+    body: &Value,
+) -> Option<Response> {
+    let recovers = matches!((method, command), ("DELETE", []) | ("POST", ["window"]) | ("POST", ["window", "new"]));
+    if state.crashed && !recovers {
+        return Some(failure(StatusCode::INTERNAL_SERVER_ERROR, "unknown error", "tab crashed"));
+    }
+    let response = match (method, command) {
+        ("POST", ["window", "new"]) => {
+            state.commands.push("new_window".to_string());
+            state.crashed = false;
+            state.load(session, "about:blank");
+            success(json!({ "handle": format!("mock-tab-{}", state.next_id()), "type": "tab" }))
+        }
+        ("DELETE", []) => {
+            state.commands.push("delete_session".to_string());
+            state.sessions.remove(session);
+            success(Value::Null)
+        }
+        ("POST", ["url"]) => {
+            let url = body["url"].as_str().unwrap_or("about:blank").to_string();
+            state.commands.push(format!("navigate {}", url));
+            state.navigate(session, &url);
+            success(Value::Null)
+        }
+        ("GET", ["url"]) => {
+            let current = &state.sessions[session];
+            success(Value::String(current.history[current.position].clone()))
+        }
+        ("GET", ["title"]) => success(Value::String(state.sessions[session].page.title.clone())),
+        ("GET", ["source"]) => success(Value::String(state.sessions[session].page.html())),
+        ("POST", [direction @ ("back" | "forward" | "refresh")]) => {
+            state.commands.push(direction.to_string());
+            let current = state.sessions.get_mut(session)?;
+            match *direction {
+                "back" => current.position = current.position.saturating_sub(1),
+                "forward" => current.position = (current.position + 1).min(current.history.len() - 1),
+                _ => {}
+            }
+            let url = current.history[current.position].clone();
+            state.load(session, &url);
+            success(Value::Null)
+        }
+        ("POST", ["element" | "elements"]) | ("POST", ["element", _, "element" | "elements"]) => {
+            let selector = body["value"].as_str().unwrap_or_default();
+            state.commands.push(format!("find {}", selector));
+            let current = &state.sessions[session];
+            let found: Vec<Value> = current
+                .page
+                .elements
+                .iter()
+                .enumerate()
+                .filter(|(_, element)| element.selector == selector)
+                .map(|(index, _)| json!({ ELEMENT_KEY: format!("{}-{}", current.generation, index) }))
+                .collect();
+            if command.last() == Some(&"elements") {
+                success(Value::Array(found))
+            } else {
+                match found.into_iter().next() {
+                    Some(element) => success(element),
+                    None => {
+                        failure(StatusCode::NOT_FOUND, "no such element", format!("No element matches {}", selector))
+                    }
+                }
+            }
+        }
+        (_, ["element", element, action @ ..]) => {
+            let current = state.sessions.get(session)?;
+            let index = element
+                .split_once('-')
+                .filter(|(generation, _)| *generation == current.generation.to_string())
+                .and_then(|(_, index)| index.parse::<usize>().ok())
+                .filter(|index| *index < current.page.elements.len());
+            let Some(index) = index else {
+                return Some(failure(
+                    StatusCode::NOT_FOUND,
+                    "stale element reference",
+                    format!("Element {} is not attached to the current page", element),
+                ));
+            };
+            return element_command(state, session, index, method, action, body);
+        }
+        ("POST", ["execute", "sync" | "async"]) => {
+            let script = body["script"].as_str().unwrap_or_default();
+            state.commands.push("execute_script".to_string());
+            state.script_calls.push((script.to_string(), body["args"].clone()));
+            success(state.script_result(script))
+        }
+        ("POST", ["goog", "cdp", "execute"]) if state.cdp => {
+            state.commands.push(format!("cdp {}", body["cmd"].as_str().unwrap_or_default()));
+            success(json!({}))
+        }
+        ("GET", ["screenshot"]) => {
+            state.commands.push("screenshot".to_string());
+            success(Value::String(SCREENSHOT_PNG.to_string()))
+        }
+        ("GET", ["window"]) => success(Value::String(WINDOW_HANDLE.to_string())),
+        ("GET", ["window", "handles"]) => success(json!([WINDOW_HANDLE])),
+        ("POST", ["window"]) => success(Value::Null),
+        ("DELETE", ["window"]) => success(json!([])),
+        ("GET", ["window", "rect"]) => success(state.sessions[session].window.clone()),
+        ("POST", ["window", "rect"]) => {
+            let window = &mut state.sessions.get_mut(session)?.window;
+            for key in ["x", "y", "width", "height"] {
+                if let Some(value) = body.get(key).filter(|value| value.is_number()) {
+                    window[key] = value.clone();
+                }
+            }
+            success(window.clone())
+        }
+        ("POST", ["window", mode @ ("maximize" | "minimize" | "fullscreen")]) => {
+            state.commands.push(format!("{mode}_window"));
+            success(state.sessions[session].window.clone())
+        }
+        ("GET", ["timeouts"]) => success(json!({ "script": 30000, "pageLoad": 300000, "implicit": 0 })),
+        ("GET", ["cookie"]) => success(json!([])),
+        ("POST", ["timeouts" | "cookie" | "actions" | "frame"]) | ("POST", ["frame", "parent"]) => {
+            success(Value::Null)
+        }
+        ("DELETE", ["cookie" | "actions"]) | ("DELETE", ["cookie", _]) => success(Value::Null),
+        ("GET", ["alert", "text"]) => failure(StatusCode::NOT_FOUND, "no such alert", "No alert is open"),
+        _ => return None,
+    };
+    Some(response)
+}
+
+/// Handle `/session/{id}/element/{element}/...` for a live element
+fn element_command(
+    state: &mut DriverState,
+    session: &str,
+    index: usize,
+    method: &str,
+    action: &[&str],
+    body: &Value,
+) -> Option<Response> {
+    let element = state.sessions.get_mut(session)?.page.elements.get_mut(index)?;
+    let response = match (method, action) {
+        ("GET", ["text"]) => success(Value::String(element.text.clone())),
+        ("GET", ["name"]) => success(Value::String(element.tag.clone())),
+        ("GET", ["displayed"]) => success(Value::Bool(element.displayed)),
+        ("GET", ["enabled"]) => success(Value::Bool(!element.attributes.contains_key("disabled"))),
+        ("GET", ["selected"]) => {
+            let selected = ["checked", "selected"].iter().any(|name| element.attributes.contains_key(*name));
+            success(Value::Bool(selected))
+        }
+        ("GET", ["rect"]) => success(json!({ "x": 0, "y": 0, "width": 100, "height": 20 })),
+        ("GET", ["screenshot"]) => success(Value::String(SCREENSHOT_PNG.to_string())),
+        ("GET", ["attribute", name]) => {
+            let value = match *name {
+                "value" => Some(element.value.clone()),
+                _ => element.attributes.get(*name).cloned(),
+            };
+            success(value.map_or(Value::Null, Value::String))
+        }
+        ("GET", ["property", name]) => success(element.property(name)),
+        ("GET", ["css", _]) => success(Value::String(String::new())),
+        ("POST", ["clear"]) => {
+            element.value.clear();
+            success(Value::Null)
+        }
+        ("POST", ["click"]) => {
+            let (selector, target) = (element.selector.clone(), element.navigates_to.clone());
+            state.commands.push(format!("click {}", selector));
+            if let Some(url) = target {
+                state.navigate(session, &url);
+            }
+            success(Value::Null)
+        }
+        ("POST", ["value"]) => {
+            let text = body["text"].as_str().unwrap_or_default();
+            // Enter, as a newline or the WebDriver key code, submits
+            let submits = text.contains(['\n', '\u{E007}']);
+            element.value.extend(text.chars().filter(|c| !matches!(c, '\n' | '\u{E007}')));
+            let (selector, target) = (element.selector.clone(), element.navigates_to.clone());
+            state.commands.push(format!("send_keys {}", selector));
+            if let Some(url) = target.filter(|_| submits) {
+                state.navigate(session, &url);
+            }
+            success(Value::Null)
+        }
+        _ => return None,
+    };
+    Some(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Method;
+
+    /// Sends raw WebDriver requests to the mock and returns status and `value`
+    struct Wire {
+        http: reqwest::Client,
+        base: String,
+    }
+
+    impl Wire {
+        async fn call(&self, method: Method, path: &str, body: Value) -> (u16, Value) {
+            let request = self.http.request(method, format!("{}{}", self.base, path)).json(&body);
+            let response = request.send().await.unwrap();
+            (response.status().as_u16(), response.json::<Value>().await.unwrap()["value"].clone())
+        }
+
+        async fn find(&self, session: &str, selector: &str) -> (u16, Value) {
+            let body = json!({ "using": "css selector", "value": selector });
+            self.call(Method::POST, &format!("{session}/element"), body).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_driver_protocol() {
+        let driver = MockDriver::start().await.unwrap();
+        driver.add_page(
+            "https://shop.test",
+            MockPage::new("Shop")
+                .element(MockElement::new("#q").tag("input").navigates_to("https://shop.test/results"))
+                .element(MockElement::new("h1").text("Welcome")),
+        );
+        driver.add_page("https://shop.test/results", MockPage::new("Results"));
+        driver.on_script("document.readyState", json!("complete"));
+        let wire = Wire { http: reqwest::Client::new(), base: driver.endpoint().to_string() };
+
+        let (_, created) = wire.call(Method::POST, "/session", json!({ "capabilities": {} })).await;
+        let session = format!("/session/{}", created["sessionId"].as_str().unwrap());
+        wire.call(Method::POST, &format!("{session}/url"), json!({ "url": "https://shop.test" })).await;
+        let (_, title) = wire.call(Method::GET, &format!("{session}/title"), Value::Null).await;
+        assert_eq!(title, "Shop");
+
+        let (_, heading) = wire.find(&session, "h1").await;
+        let heading = heading[ELEMENT_KEY].as_str().unwrap().to_string();
+        let (_, text) = wire.call(Method::GET, &format!("{session}/element/{heading}/text"), Value::Null).await;
+        assert_eq!(text, "Welcome");
+
+        let (status, missing) = wire.find(&session, "#missing").await;
+        assert_eq!((status, missing["error"].as_str()), (404, Some("no such element")));
+
+        let (_, input) = wire.find(&session, "#q").await;
+        let input = input[ELEMENT_KEY].as_str().unwrap().to_string();
+        wire.call(Method::POST, &format!("{session}/element/{input}/value"), json!({ "text": "shoes\n" })).await;
+        let (_, url) = wire.call(Method::GET, &format!("{session}/url"), Value::Null).await;
+        assert_eq!(url, "https://shop.test/results");
+
+        // The search box belonged to the previous page
+        let (status, stale) = wire.call(Method::GET, &format!("{session}/element/{input}/text"), Value::Null).await;
+        assert_eq!((status, stale["error"].as_str()), (404, Some("stale element reference")));
+
+        wire.call(Method::POST, &format!("{session}/back"), Value::Null).await;
+        let (_, title) = wire.call(Method::GET, &format!("{session}/title"), Value::Null).await;
+        assert_eq!(title, "Shop");
+
+        let script = json!({ "script": "return document.readyState", "args": [] });
+        let (_, ready) = wire.call(Method::POST, &format!("{session}/execute/sync"), script).await;
+        assert_eq!(ready, "complete");
+        assert_eq!(driver.script_args("readyState"), vec![json!([])]);
+
+        driver.crash_page();
+        let (status, crashed) = wire.call(Method::GET, &format!("{session}/title"), Value::Null).await;
+        assert_eq!((status, crashed["message"].as_str()), (500, Some("tab crashed")));
+        wire.call(Method::POST, &format!("{session}/window/new"), json!({ "type": "tab" })).await;
+        let (status, _) = wire.call(Method::GET, &format!("{session}/title"), Value::Null).await;
+        assert_eq!(status, 200);
+
+        wire.call(Method::DELETE, &session, Value::Null).await;
+        assert_eq!(driver.session_count(), 0);
+        assert!(driver.commands().contains(&"send_keys #q".to_string()));
+    }
+}
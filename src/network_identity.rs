@@ -0,0 +1,304 @@
+//! What a session looks like to the sites it visits
+//!
+//! `whoami_network` loads an echo endpoint (`network_echo_url`, by default
+//! httpbin's `/anything`) and reads the egress IP and request headers it
+//! reports, then asks the browser for its user agent, languages and timezone
+//! and gathers WebRTC ICE candidates through a STUN server. Together they show
+//! whether proxy, locale and user agent settings actually took effect, and
+//! whether WebRTC reveals an address the proxy was meant to hide.
+
+use std::net::IpAddr;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// STUN server asked for the session's public address unless the call names another
+pub const DEFAULT_STUN_SERVER: &str = "stun:stun.l.google.com:19302";
+/// Milliseconds to gather ICE candidates before reporting what arrived
+pub const DEFAULT_WEBRTC_WAIT_MS: u64 = 3000;
+
+/// Browser-side identity: user agent, languages, timezone and the ICE
+/// candidates of a data channel offer made with STUN server `arguments[0]`,
+/// gathered for at most `arguments[1]` milliseconds
+pub const BROWSER_IDENTITY_SCRIPT: &str = r#"
+    const [stunServer, waitMs, done] = arguments;
+    const result = {
+        user_agent: navigator.userAgent,
+        languages: Array.from(navigator.languages || [navigator.language]),
+        timezone: Intl.DateTimeFormat().resolvedOptions().timeZone,
+        utc_offset_minutes: -new Date().getTimezoneOffset(),
+        webrtc: { supported: typeof RTCPeerConnection === 'function', candidates: [] },
+    };
+    if (!result.webrtc.supported) {
+        done(result);
+        return;
+    }
+    let connection;
+    try {
+        connection = new RTCPeerConnection({ iceServers: stunServer ? [{ urls: stunServer }] : [] });
+    } catch (e) {
+        result.webrtc.error = String(e);
+        done(result);
+        return;
+    }
+    let finished = false;
+    const finish = () => {
+        if (finished) return;
+        finished = true;
+        clearTimeout(timer);
+        try { connection.close(); } catch (e) {}
+        done(result);
+    };
+    const timer = setTimeout(finish, waitMs);
+    connection.onicecandidate = event => {
+        if (!event.candidate) {
+            finish();
+        } else if (event.candidate.candidate) {
+            result.webrtc.candidates.push(event.candidate.candidate);
+        }
+    };
+    connection.createDataChannel('whoami');
+    connection.createOffer()
+        .then(offer => connection.setLocalDescription(offer))
+        .catch(e => {
+            result.webrtc.error = String(e);
+            finish();
+        });
+"#;
+
+/// Fields of an echo endpoint's answer reported as the egress IP's location
+const GEO_FIELDS: &[&str] =
+    &["country", "countryCode", "country_code", "region", "regionName", "city", "timezone", "isp", "org"];
+
+/// What the echo endpoint saw of the request
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EchoedRequest {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub accept_language: Option<String>,
+    /// Location fields some endpoints add, e.g. country, city and timezone
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    pub geo: serde_json::Map<String, Value>,
+}
+
+impl EchoedRequest {
+    /// Read the endpoint's answer: JSON with the IP under `origin` (httpbin),
+    /// `ip` or `query` (ip-api) and optionally `headers`, or just the IP as text
+    pub fn parse(body: &str) -> Self {
+        let body = body.trim();
+        let Ok(Value::Object(json)) = serde_json::from_str::<Value>(body) else {
+            return Self { ip: body.parse::<IpAddr>().ok().map(|ip| ip.to_string()), ..Self::default() };
+        };
+        let text = |value: Option<&Value>| value.and_then(Value::as_str).map(str::to_string);
+        // httpbin lists every proxy hop in `origin`; the first is the client
+        let ip = ["origin", "ip", "query", "ip_addr"]
+            .iter()
+            .find_map(|key| text(json.get(*key)))
+            .and_then(|ip| ip.split(',').next().map(|ip| ip.trim().to_string()));
+        let header = |name: &str| {
+            let headers = json.get("headers")?.as_object()?;
+            text(headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value))
+        };
+        let geo = json
+            .iter()
+            .filter(|(key, _)| GEO_FIELDS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Self {
+            ip,
+            user_agent: header("User-Agent").or_else(|| text(json.get("user_agent"))),
+            accept_language: header("Accept-Language"),
+            geo,
+        }
+    }
+}
+
+/// One ICE candidate's address and type (host, srflx, prflx or relay)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IceAddress {
+    pub address: String,
+    pub kind: String,
+}
+
+impl IceAddress {
+    /// Address and type of an SDP candidate line such as
+    /// `candidate:1 1 udp 2113937151 192.0.2.4 56143 typ host generation 0`
+    pub fn parse(candidate: &str) -> Option<Self> {
+        let fields: Vec<&str> = candidate.split_whitespace().collect();
+        let address = fields.get(4)?;
+        let kind = fields.iter().position(|field| *field == "typ").and_then(|at| fields.get(at + 1))?;
+        Some(Self { address: address.to_string(), kind: kind.to_string() })
+    }
+}
+
+/// Whether WebRTC reveals more than the HTTP egress address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebRtcStatus {
+    /// WebRTC is off or failed, so it cannot leak anything
+    Unavailable,
+    /// A public address other than the egress IP showed up, typically the real one behind a proxy or VPN
+    Leak,
+    /// A private network address showed up unmasked by mDNS
+    LocalIpExposed,
+    /// Only the egress IP and mDNS-masked local addresses showed up
+    Protected,
+    /// Public addresses showed up, but the echo endpoint reported no egress IP to compare them with
+    Unverified,
+}
+
+impl WebRtcStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unavailable => "unavailable",
+            Self::Leak => "leak",
+            Self::LocalIpExposed => "local_ip_exposed",
+            Self::Protected => "protected",
+            Self::Unverified => "unverified",
+        }
+    }
+}
+
+/// ICE candidate addresses sorted into public and local ones, with the verdict
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebRtcReport {
+    pub status: WebRtcStatus,
+    pub public_ips: Vec<String>,
+    pub local_ips: Vec<String>,
+    /// Host candidates hidden behind random `.local` names
+    pub mdns_hosts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl WebRtcReport {
+    /// Judge the `webrtc` part of [`BROWSER_IDENTITY_SCRIPT`]'s result against the egress IP
+    pub fn from_candidates(webrtc: &Value, egress_ip: Option<&str>) -> Self {
+        let error = webrtc["error"].as_str().map(str::to_string);
+        let mut report = Self {
+            status: WebRtcStatus::Unavailable,
+            public_ips: Vec::new(),
+            local_ips: Vec::new(),
+            mdns_hosts: 0,
+            error,
+        };
+        if webrtc["supported"].as_bool() != Some(true) || report.error.is_some() {
+            return report;
+        }
+
+        let candidates = webrtc["candidates"].as_array().into_iter().flatten().filter_map(Value::as_str);
+        for address in candidates.filter_map(IceAddress::parse).map(|candidate| candidate.address) {
+            let list = match address.parse::<IpAddr>() {
+                Ok(ip) if is_public(ip) => &mut report.public_ips,
+                Ok(ip) if !ip.is_unspecified() => &mut report.local_ips,
+                Ok(_) => continue,
+                Err(_) => {
+                    report.mdns_hosts += usize::from(address.ends_with(".local"));
+                    continue;
+                }
+            };
+            if !list.contains(&address) {
+                list.push(address);
+            }
+        }
+
+        let egress_ip = egress_ip.and_then(|ip| ip.parse::<IpAddr>().ok());
+        report.status = match egress_ip {
+            Some(egress) if report.public_ips.iter().any(|ip| ip.parse::<IpAddr>().ok() != Some(egress)) => {
+                WebRtcStatus::Leak
+            }
+            None if !report.public_ips.is_empty() => WebRtcStatus::Unverified,
+            _ if !report.local_ips.is_empty() => WebRtcStatus::LocalIpExposed,
+            _ => WebRtcStatus::Protected,
+        };
+        report
+    }
+}
+
+/// Whether `ip` is reachable from the internet, i.e. not loopback, private or link-local
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            // Carrier-grade NAT, 100.64.0.0/10
+            let shared = ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64;
+            !(ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified() || shared)
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            !(ip.is_loopback() || ip.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Where the browser's own settings disagree with what the endpoint saw, e.g.
+/// a user agent override that only reached JavaScript
+pub fn mismatches(echoed: &EchoedRequest, browser: &Value) -> Vec<String> {
+    let mut found = Vec::new();
+    if let (Some(sent), Some(reported)) = (&echoed.user_agent, browser["user_agent"].as_str())
+        && sent != reported
+    {
+        found.push(format!("User-Agent header '{sent}' differs from navigator.userAgent '{reported}'"));
+    }
+    let primary = |languages: &str| {
+        languages.split([',', ';']).next().map(|language| language.trim().to_lowercase()).unwrap_or_default()
+    };
+    if let (Some(sent), Some(language)) = (&echoed.accept_language, browser["languages"][0].as_str())
+        && primary(sent) != primary(language)
+    {
+        found.push(format!("Accept-Language '{sent}' does not start with navigator.languages[0] '{language}'"));
+    }
+    let located = echoed.geo.get("timezone").and_then(Value::as_str);
+    if let (Some(located), Some(timezone)) = (located, browser["timezone"].as_str())
+        && located != timezone
+    {
+        found.push(format!("The egress IP is located in timezone {located}, but the browser reports {timezone}"));
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_echo_parsing_and_webrtc_verdicts() {
+        let httpbin = r#"{"headers": {"Accept-Language": "de-DE,de;q=0.9", "User-Agent": "Mozilla/5.0 Test"},
+            "origin": "203.0.113.7, 198.51.100.2", "url": "https://httpbin.org/anything"}"#;
+        let echoed = EchoedRequest::parse(httpbin);
+        assert_eq!(echoed.ip.as_deref(), Some("203.0.113.7"));
+        assert_eq!(echoed.user_agent.as_deref(), Some("Mozilla/5.0 Test"));
+        assert_eq!(echoed.accept_language.as_deref(), Some("de-DE,de;q=0.9"));
+        assert!(echoed.geo.is_empty());
+
+        let ip_api = r#"{"status":"success","country":"Germany","timezone":"Europe/Berlin","query":"203.0.113.7"}"#;
+        let ip_api = EchoedRequest::parse(ip_api);
+        assert_eq!(ip_api.ip.as_deref(), Some("203.0.113.7"));
+        assert_eq!(ip_api.geo.get("timezone"), Some(&json!("Europe/Berlin")));
+        assert_eq!(EchoedRequest::parse("2001:db8::1\n").ip.as_deref(), Some("2001:db8::1"));
+
+        let browser =
+            json!({ "user_agent": "Mozilla/5.0 Test", "languages": ["en-US", "en"], "timezone": "America/New_York" });
+        let found = mismatches(&EchoedRequest { geo: ip_api.geo.clone(), ..echoed.clone() }, &browser);
+        assert_eq!(found.len(), 2, "{found:?}");
+        assert!(found[0].starts_with("Accept-Language 'de-DE"));
+        assert!(found[1].contains("Europe/Berlin"));
+
+        let webrtc = json!({ "supported": true, "candidates": [
+            "candidate:1 1 udp 2113937151 3f2a1b7c-1111-2222-3333-444455556666.local 56143 typ host generation 0",
+            "candidate:2 1 udp 1677729535 192.0.2.44 56143 typ srflx raddr 0.0.0.0 rport 0 generation 0",
+        ]});
+        let report = WebRtcReport::from_candidates(&webrtc, Some("203.0.113.7"));
+        assert_eq!(report.status, WebRtcStatus::Leak);
+        assert_eq!(report.public_ips, vec!["192.0.2.44"]);
+        assert_eq!(report.mdns_hosts, 1);
+        assert_eq!(WebRtcReport::from_candidates(&webrtc, Some("192.0.2.44")).status, WebRtcStatus::Protected);
+        assert_eq!(WebRtcReport::from_candidates(&webrtc, None).status, WebRtcStatus::Unverified);
+
+        let local = json!({ "supported": true, "candidates": ["candidate:1 1 udp 2113937151 10.0.0.2 5000 typ host"] });
+        assert_eq!(WebRtcReport::from_candidates(&local, Some("203.0.113.7")).status, WebRtcStatus::LocalIpExposed);
+        let off = json!({ "supported": false, "candidates": [] });
+        assert_eq!(WebRtcReport::from_candidates(&off, Some("203.0.113.7")).status, WebRtcStatus::Unavailable);
+    }
+}
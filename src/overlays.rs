@@ -0,0 +1,291 @@
+//! Dismissing consent banners, cookie dialogs and other overlays
+//!
+//! Most public sites greet a fresh browser with a GDPR consent dialog that
+//! covers the page and swallows clicks. [`OVERLAY_RULES`] lists the consent
+//! platforms and overlay widgets seen most often, with the buttons that accept
+//! or reject them; the page script clicks the first visible one per rule, or
+//! removes the overlay when a rule has no button for the requested choice.
+//! Banners without a known vendor are caught by a fallback that looks for an
+//! "accept"/"reject" button inside an element whose id or class mentions
+//! cookies or consent.
+
+use fantoccini::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{ClientManager, page_scripts::selector_for_script};
+
+/// What to answer a consent dialog with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsentChoice {
+    /// Click "accept all" (most dialogs only go away completely this way)
+    #[default]
+    Accept,
+    /// Click "reject all" where the dialog offers it, otherwise remove the dialog
+    Reject,
+}
+
+/// One consent platform or overlay widget
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct OverlayRule {
+    pub name: &'static str,
+    /// "consent", "paywall" or "newsletter"
+    pub kind: &'static str,
+    /// Buttons that accept, first visible match wins
+    pub accept: &'static str,
+    /// Buttons that reject; empty when the widget has none
+    pub reject: &'static str,
+    /// The overlay itself, removed when no button fits the choice
+    pub container: &'static str,
+}
+
+/// Known consent platforms and overlays, most common first
+pub const OVERLAY_RULES: &[OverlayRule] = &[
+    OverlayRule {
+        name: "OneTrust",
+        kind: "consent",
+        accept: "#onetrust-accept-btn-handler, #accept-recommended-btn-handler",
+        reject: "#onetrust-reject-all-handler, .ot-pc-refuse-all-handler",
+        container: "#onetrust-consent-sdk",
+    },
+    OverlayRule {
+        name: "Cookiebot",
+        kind: "consent",
+        accept: "#CybotCookiebotDialogBodyLevelButtonLevelOptinAllowAll, #CybotCookiebotDialogBodyButtonAccept",
+        reject: "#CybotCookiebotDialogBodyButtonDecline",
+        container: "#CybotCookiebotDialog",
+    },
+    OverlayRule {
+        name: "Quantcast Choice",
+        kind: "consent",
+        accept: ".qc-cmp2-summary-buttons button[mode='primary']",
+        reject: ".qc-cmp2-summary-buttons button[mode='secondary']",
+        container: ".qc-cmp2-container",
+    },
+    OverlayRule {
+        name: "Didomi",
+        kind: "consent",
+        accept: "#didomi-notice-agree-button",
+        reject: "#didomi-notice-disagree-button, .didomi-continue-without-agreeing",
+        container: "#didomi-host",
+    },
+    OverlayRule {
+        name: "TrustArc",
+        kind: "consent",
+        accept: "#truste-consent-button",
+        reject: "#truste-consent-required",
+        container: "#truste-consent-track, .truste_overlay, .truste_box_overlay",
+    },
+    OverlayRule {
+        name: "Usercentrics",
+        kind: "consent",
+        accept: "[data-testid='uc-accept-all-button']",
+        reject: "[data-testid='uc-deny-all-button']",
+        container: "#usercentrics-root",
+    },
+    OverlayRule {
+        name: "Osano",
+        kind: "consent",
+        accept: ".osano-cm-accept-all, .osano-cm-button--type_accept",
+        reject: ".osano-cm-denyAll, .osano-cm-button--type_deny",
+        container: ".osano-cm-window",
+    },
+    OverlayRule {
+        name: "CookieYes",
+        kind: "consent",
+        accept: ".cky-btn-accept",
+        reject: ".cky-btn-reject",
+        container: ".cky-consent-container, .cky-overlay",
+    },
+    OverlayRule {
+        name: "Complianz",
+        kind: "consent",
+        accept: ".cmplz-btn.cmplz-accept",
+        reject: ".cmplz-btn.cmplz-deny",
+        container: "#cmplz-cookiebanner-container",
+    },
+    OverlayRule {
+        name: "iubenda",
+        kind: "consent",
+        accept: ".iubenda-cs-accept-btn",
+        reject: ".iubenda-cs-reject-btn",
+        container: "#iubenda-cs-banner",
+    },
+    OverlayRule {
+        name: "Klaro",
+        kind: "consent",
+        accept: ".klaro .cm-btn-accept-all, .klaro .cm-btn-success",
+        reject: ".klaro .cn-decline",
+        container: ".klaro .cookie-notice, .klaro .cookie-modal",
+    },
+    OverlayRule {
+        name: "Borlabs Cookie",
+        kind: "consent",
+        accept: "a[data-cookie-accept-all], #BorlabsCookieBox a[data-cookie-accept]",
+        reject: "#BorlabsCookieBox a[data-cookie-refuse]",
+        container: "#BorlabsCookieBox",
+    },
+    OverlayRule {
+        name: "Cookie Notice",
+        kind: "consent",
+        accept: "#cn-accept-cookie",
+        reject: "#cn-refuse-cookie",
+        container: "#cookie-notice",
+    },
+    OverlayRule {
+        name: "Termly",
+        kind: "consent",
+        accept: "button[data-tid='banner-accept']",
+        reject: "button[data-tid='banner-decline']",
+        container: "#termly-code-snippet-support",
+    },
+    OverlayRule {
+        name: "Google consent",
+        kind: "consent",
+        accept: "form[action*='consent'] button[aria-label*='Accept'], button#L2AGLb",
+        reject: "form[action*='consent'] button[aria-label*='Reject'], button#W0wltc",
+        container: "",
+    },
+    OverlayRule {
+        name: "Piano",
+        kind: "paywall",
+        accept: "",
+        reject: "",
+        container: ".tp-modal, .tp-backdrop",
+    },
+    OverlayRule {
+        name: "Klaviyo",
+        kind: "newsletter",
+        accept: "",
+        reject: ".klaviyo-close-form",
+        container: "",
+    },
+];
+
+/// Clicks or removes the overlays of `arguments[0]` (the rules) for choice
+/// `arguments[1]`, then tries the generic fallback. Returns what it did.
+const DISMISS_SCRIPT: &str = concat!(selector_for_script!(), r#"
+    const [rules, choice] = arguments;
+    const visible = el => {
+        const rect = el.getBoundingClientRect();
+        const style = window.getComputedStyle(el);
+        return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none';
+    };
+    const firstVisible = selector => {
+        if (!selector) return null;
+        try {
+            return Array.from(document.querySelectorAll(selector)).find(visible) || null;
+        } catch (e) {
+            return null;
+        }
+    };
+    const dismissed = [];
+
+    for (const rule of rules) {
+        const button = firstVisible(choice === 'reject' ? rule.reject : rule.accept)
+            || (rule.kind !== 'consent' ? firstVisible(rule.reject) : null);
+        if (button) {
+            button.click();
+            dismissed.push({ name: rule.name, kind: rule.kind, method: 'click', element: selectorFor(button) });
+            continue;
+        }
+        const container = firstVisible(rule.container);
+        if (container) {
+            container.remove();
+            dismissed.push({ name: rule.name, kind: rule.kind, method: 'remove', element: selectorFor(container) });
+        }
+    }
+
+    if (!dismissed.length) {
+        const wanted = choice === 'reject'
+            ? /^(reject|decline|deny|refuse)( all)?( cookies)?$|^only (necessary|essential)|^necessary only$/i
+            : /^(accept|allow|agree)( all)?( cookies)?$|^i (agree|accept)$|^got it$|^ok(ay)?$/i;
+        const banners = Array.from(document.querySelectorAll(
+            "[id*='cookie' i], [class*='cookie' i], [id*='consent' i], [class*='consent' i], [id*='gdpr' i], [class*='gdpr' i]"
+        )).filter(visible);
+        outer: for (const banner of banners) {
+            for (const button of banner.querySelectorAll("button, a[role='button'], [role='button'], input[type='button']")) {
+                const label = (button.innerText || button.value || button.getAttribute('aria-label') || '').trim();
+                if (visible(button) && wanted.test(label)) {
+                    button.click();
+                    const element = selectorFor(button);
+                    dismissed.push({ name: 'generic', kind: 'consent', method: 'click', element, label });
+                    break outer;
+                }
+            }
+        }
+    }
+
+    // Dialogs lock scrolling while open; removing them does not always undo that
+    if (dismissed.some(entry => entry.method === 'remove')) {
+        for (const el of [document.documentElement, document.body]) {
+            if (el && window.getComputedStyle(el).overflow === 'hidden') el.style.overflow = 'auto';
+        }
+    }
+    return dismissed;
+"#);
+
+/// An overlay that was clicked away or removed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DismissedOverlay {
+    /// Rule name, e.g. "OneTrust", or "generic" for the fallback
+    pub name: String,
+    /// "consent", "paywall" or "newsletter"
+    pub kind: String,
+    /// "click" when a button was clicked, "remove" when the overlay was taken out of the page
+    pub method: String,
+    /// Tag and id of the element acted on
+    pub element: String,
+    /// Button text, for the generic fallback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl DismissedOverlay {
+    pub fn describe(&self) -> String {
+        let action = if self.method == "click" { "clicked" } else { "removed" };
+        format!("{} {} ({} {})", self.name, self.kind, action, self.element)
+    }
+}
+
+/// Dismiss every known overlay on the current page of `session`
+pub async fn dismiss(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    choice: ConsentChoice,
+) -> crate::Result<Vec<DismissedOverlay>> {
+    let dismissed = client_manager
+        .run_command(session, "dismiss_overlays", dismiss_command(client, choice))
+        .await?;
+    serde_json::from_value(dismissed).map_err(|e| crate::WebDriverError::Serialization(e.to_string()))
+}
+
+/// The dismissal as a bare WebDriver command, for callers that apply their own timeout
+pub(crate) async fn dismiss_command(
+    client: &Client,
+    choice: ConsentChoice,
+) -> Result<serde_json::Value, fantoccini::error::CmdError> {
+    client.execute(DISMISS_SCRIPT, vec![json!(OVERLAY_RULES), json!(choice)]).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_rules_are_well_formed() {
+        for rule in OVERLAY_RULES {
+            assert!(["consent", "paywall", "newsletter"].contains(&rule.kind), "{}", rule.name);
+            assert!(
+                !(rule.accept.is_empty() && rule.reject.is_empty() && rule.container.is_empty()),
+                "{} matches nothing",
+                rule.name
+            );
+        }
+        let rules = serde_json::to_value(OVERLAY_RULES).unwrap();
+        assert_eq!(rules[0]["accept"], "#onetrust-accept-btn-handler, #accept-recommended-btn-handler");
+        assert_eq!(json!(ConsentChoice::Reject), "reject");
+    }
+}
@@ -0,0 +1,230 @@
+//! Page lifecycle events
+//!
+//! WebDriver has no event channel a server can listen on, so a recorder
+//! installed on every page load notes what the page goes through into
+//! `window.__mcpPageEvents`: the navigation committing, `DOMContentLoaded`,
+//! `load`, dialogs opening, downloads starting and frames being attached.
+//! What happened before the recorder was installed is filled in from the
+//! page's Navigation Timing entry, so a page reached by a click still reports
+//! when it committed and loaded. Each drain moves the events into a
+//! per-session buffer here and logs them, so MCP clients that lowered their
+//! log level to `info` get them as a notification stream as well.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use fantoccini::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::ClientManager;
+
+/// Events kept per session before the oldest are dropped
+pub const MAX_PAGE_EVENTS: usize = 1000;
+
+/// Event types the recorder reports
+pub const PAGE_EVENT_TYPES: &[&str] = &[
+    "navigation_committed",
+    "dom_content_loaded",
+    "load",
+    "dialog_opened",
+    "download_started",
+    "frame_attached",
+];
+
+/// Installs the recorder once per document
+const PAGE_EVENT_RECORDER_SCRIPT: &str = r#"
+    try {
+        if (!window.__mcpPageEvents) {
+            const events = window.__mcpPageEvents = [];
+            const record = (type, details, timestamp) => events.push({
+                type: type,
+                url: window.location.href,
+                timestamp: Math.round(timestamp || Date.now()),
+                ...details
+            });
+
+            // Stamp what happened before the recorder was installed from Navigation Timing
+            const entry = performance.getEntriesByType('navigation')[0];
+            const at = offset => performance.timeOrigin + offset;
+            record('navigation_committed', { navigation_type: entry && entry.type }, at(entry ? entry.responseStart : 0));
+            if (entry && entry.domContentLoadedEventStart > 0) {
+                record('dom_content_loaded', {}, at(entry.domContentLoadedEventStart));
+            } else {
+                document.addEventListener('DOMContentLoaded', () => record('dom_content_loaded'), { once: true });
+            }
+            if (entry && entry.loadEventStart > 0) {
+                record('load', {}, at(entry.loadEventStart));
+            } else {
+                window.addEventListener('load', () => record('load'), { once: true });
+            }
+
+            // Same-document navigations of single-page apps
+            ['pushState', 'replaceState'].forEach(method => {
+                const original = history[method];
+                history[method] = function() {
+                    const result = original.apply(this, arguments);
+                    record('navigation_committed', { same_document: true, navigation_type: method });
+                    return result;
+                };
+            });
+            window.addEventListener('popstate', () => record('navigation_committed', { same_document: true, navigation_type: 'popstate' }));
+
+            ['alert', 'confirm', 'prompt'].forEach(kind => {
+                const original = window[kind];
+                window[kind] = function(message) {
+                    record('dialog_opened', { dialog_type: kind, message: message === undefined ? '' : String(message) });
+                    return original.apply(window, arguments);
+                };
+            });
+
+            document.addEventListener('click', event => {
+                const link = event.target instanceof Element && event.target.closest('a[href]');
+                if (link && (link.hasAttribute('download') || /^(blob|data):/.test(link.href))) {
+                    record('download_started', { download_url: link.href, filename: link.getAttribute('download') || undefined });
+                }
+            }, true);
+
+            const frameAttached = frame => record('frame_attached', { frame_url: frame.src || 'about:blank', frame_name: frame.name || undefined });
+            document.querySelectorAll('iframe, frame').forEach(frameAttached);
+            new MutationObserver(mutations => mutations.forEach(mutation => mutation.addedNodes.forEach(node => {
+                if (node.nodeType !== Node.ELEMENT_NODE) return;
+                if (node.matches('iframe, frame')) frameAttached(node);
+                node.querySelectorAll('iframe, frame').forEach(frameAttached);
+            }))).observe(document.documentElement, { childList: true, subtree: true });
+        }
+        return true;
+    } catch (e) {
+        return false;
+    }
+"#;
+
+/// Removes and returns everything the recorder noted since the last drain
+const DRAIN_PAGE_EVENTS_SCRIPT: &str = r#"
+    try {
+        const events = window.__mcpPageEvents;
+        return events ? events.splice(0, events.length) : [];
+    } catch (e) {
+        return [];
+    }
+"#;
+
+/// Something that happened to a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageEvent {
+    /// One of [`PAGE_EVENT_TYPES`]
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Page the event happened on
+    pub url: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// Fields of the event type, such as `dialog_type` or `frame_url`
+    #[serde(flatten)]
+    pub details: Map<String, Value>,
+}
+
+impl PageEvent {
+    pub fn describe(&self) -> String {
+        let detail = ["dialog_type", "download_url", "frame_url", "navigation_type"]
+            .iter()
+            .find_map(|key| self.details.get(*key).and_then(Value::as_str));
+        match detail {
+            Some(detail) => format!("{} ({}) on {}", self.kind, detail, self.url),
+            None => format!("{} on {}", self.kind, self.url),
+        }
+    }
+}
+
+/// Buffered page events per session
+#[derive(Debug, Clone, Default)]
+pub struct PageEventBuffer {
+    sessions: Arc<Mutex<HashMap<String, VecDeque<PageEvent>>>>,
+}
+
+impl PageEventBuffer {
+    /// Add drained events, dropping the oldest beyond [`MAX_PAGE_EVENTS`]
+    pub fn append(&self, session: &str, events: Vec<PageEvent>) -> usize {
+        let count = events.len();
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let buffer = sessions.entry(session.to_string()).or_default();
+        buffer.extend(events);
+        while buffer.len() > MAX_PAGE_EVENTS {
+            buffer.pop_front();
+        }
+        count
+    }
+
+    /// Events of `session`, oldest first; `clear` forgets them
+    pub fn events(&self, session: &str, clear: bool) -> Vec<PageEvent> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        match sessions.get_mut(session) {
+            Some(buffer) if clear => buffer.drain(..).collect(),
+            Some(buffer) => buffer.iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Forget a closed session
+    pub fn remove(&self, session: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(session);
+    }
+}
+
+/// Move the events the current page of `session` recorded to the buffer,
+/// logging each one. Returns how many there were.
+pub async fn drain(client_manager: &ClientManager, session: &str, client: &Client) -> crate::Result<usize> {
+    let drained = client_manager
+        .run_command(session, "drain_page_events", client.execute(DRAIN_PAGE_EVENTS_SCRIPT, vec![]))
+        .await?;
+    let events: Vec<PageEvent> = match drained {
+        Value::Array(events) => events.into_iter().filter_map(|event| serde_json::from_value(event).ok()).collect(),
+        _ => Vec::new(),
+    };
+    for event in &events {
+        tracing::info!("Page event in session '{}': {}", session, event.describe());
+    }
+    Ok(client_manager.page_event_buffer().append(&ClientManager::session_key(session), events))
+}
+
+/// Start recording the lifecycle of the page `session` is on
+pub async fn install_recorder(client_manager: &ClientManager, session: &str, client: &Client) -> crate::Result<()> {
+    client_manager
+        .run_command(session, "record_page_events", client.execute(PAGE_EVENT_RECORDER_SCRIPT, vec![]))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_buffer_keeps_newest_events() {
+        let event = |kind: &str, timestamp: u64| -> PageEvent {
+            serde_json::from_value(json!({
+                "type": kind,
+                "url": "https://shop.example.com/",
+                "timestamp": timestamp,
+                "dialog_type": "confirm"
+            }))
+            .unwrap()
+        };
+        assert_eq!(event("dialog_opened", 1).describe(), "dialog_opened (confirm) on https://shop.example.com/");
+        assert_eq!(json!(event("load", 2))["dialog_type"], "confirm");
+
+        let buffer = PageEventBuffer::default();
+        buffer.append("work", (0..MAX_PAGE_EVENTS as u64 + 5).map(|n| event("load", n)).collect());
+        let events = buffer.events("work", false);
+        assert_eq!(events.len(), MAX_PAGE_EVENTS);
+        assert_eq!(events[0].timestamp, 5);
+        assert_eq!(buffer.events("work", true).len(), MAX_PAGE_EVENTS);
+        assert!(buffer.events("work", false).is_empty());
+        buffer.remove("work");
+        assert!(buffer.events("other", false).is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+//! JavaScript helpers shared by the scripts the server injects
+//!
+//! Scripts are `&str` constants passed to `execute`, so helpers are macros
+//! expanding to string literals that the scripts `concat!` in front of their
+//! own code.
+
+/// Defines `selectorFor(el)`, a CSS selector matching only `el` where the page
+/// allows: its id, its `name` (scoped to its form if needed), or an
+/// `:nth-of-type` path up to the nearest ancestor with a unique id. Also
+/// defines the `escape` and `unique` helpers it uses.
+macro_rules! selector_for_script {
+    () => {
+        r#"
+    const escape = value => window.CSS && CSS.escape ? CSS.escape(value) : String(value).replace(/[^a-zA-Z0-9_-]/g, '\\$&');
+    const unique = selector => {
+        try {
+            return document.querySelectorAll(selector).length === 1;
+        } catch (e) {
+            return false;
+        }
+    };
+    const selectorFor = el => {
+        if (el.id && unique('#' + escape(el.id))) {
+            return '#' + escape(el.id);
+        }
+        const tag = el.tagName.toLowerCase();
+        const name = el.getAttribute('name');
+        if (name) {
+            const byName = `${tag}[name="${name.replace(/"/g, '\\"')}"]`;
+            if (unique(byName)) {
+                return byName;
+            }
+            if (el.form && el.form !== el) {
+                const scoped = `${selectorFor(el.form)} ${byName}`;
+                if (unique(scoped)) {
+                    return scoped;
+                }
+            }
+        }
+        const parts = [];
+        for (let node = el; node && node !== document.documentElement; node = node.parentElement) {
+            if (node !== el && node.id && unique('#' + escape(node.id))) {
+                parts.unshift('#' + escape(node.id));
+                break;
+            }
+            let index = 1;
+            for (let sibling = node.previousElementSibling; sibling; sibling = sibling.previousElementSibling) {
+                if (sibling.tagName === node.tagName) index++;
+            }
+            parts.unshift(`${node.tagName.toLowerCase()}:nth-of-type(${index})`);
+        }
+        return parts.join(' > ');
+    };
+"#
+    };
+}
+
+pub(crate) use selector_for_script;
@@ -0,0 +1,162 @@
+//! Per-origin politeness for page loads
+//!
+//! Every page load the server starts (`navigate`, crawls, recipe steps,
+//! comparisons, ...) first takes a slot from its target origin: at least
+//! `politeness_min_delay_ms` plus a random jitter after the previous load, and
+//! no more than `politeness_max_requests_per_minute` loads in any rolling
+//! minute. Slots are shared by all sessions and users, so parallel sessions
+//! cannot hammer a site together. `politeness_origins` sets other limits for
+//! single origins; loopback hosts have none unless listed there.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    hash::{BuildHasher, Hasher, RandomState},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, site_pool};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// How often one origin may be loaded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolitenessLimit {
+    /// Least milliseconds between two loads (0 for none)
+    pub min_delay_ms: u64,
+    /// Loads in any rolling minute (unlimited when 0)
+    pub max_requests_per_minute: u32,
+}
+
+#[derive(Default)]
+struct OriginSlots {
+    /// Start of the latest load, which may lie in the future
+    last: Option<Instant>,
+    /// Starts of the loads of the last minute, oldest first
+    minute: VecDeque<Instant>,
+}
+
+/// Page load slots per origin
+#[derive(Clone)]
+pub struct Politeness {
+    origins: Arc<Mutex<HashMap<String, OriginSlots>>>,
+    default_limit: PolitenessLimit,
+    /// Limits of single origins, keyed by their serialized origin
+    limits: BTreeMap<String, PolitenessLimit>,
+    jitter: Duration,
+}
+
+impl Politeness {
+    pub fn from_config(config: &Config) -> Self {
+        let default_limit = PolitenessLimit {
+            min_delay_ms: config.politeness_min_delay_ms,
+            max_requests_per_minute: config.politeness_max_requests_per_minute,
+        };
+        let limits = config
+            .politeness_origins
+            .iter()
+            .filter_map(|(origin, limit)| Some((site_pool::origin_of(origin)?, *limit)))
+            .collect();
+        Self {
+            origins: Arc::default(),
+            default_limit,
+            limits,
+            jitter: Duration::from_millis(config.politeness_jitter_ms),
+        }
+    }
+
+    /// Take the next load slot of `url`'s origin and return how long to wait for it
+    pub fn reserve(&self, url: &str) -> Duration {
+        let Some(origin) = site_pool::origin_of(url) else {
+            return Duration::ZERO;
+        };
+        let limit = match self.limits.get(&origin) {
+            Some(limit) => *limit,
+            None if is_loopback(url) => return Duration::ZERO,
+            None => self.default_limit,
+        };
+        let jitter = match self.jitter.as_millis() as u64 {
+            0 => Duration::ZERO,
+            jitter_ms => Duration::from_millis(RandomState::new().build_hasher().finish() % (jitter_ms + 1)),
+        };
+        self.reserve_at(&origin, limit, jitter, Instant::now())
+    }
+
+    fn reserve_at(&self, origin: &str, limit: PolitenessLimit, jitter: Duration, now: Instant) -> Duration {
+        let min_delay = Duration::from_millis(limit.min_delay_ms);
+        if min_delay.is_zero() && limit.max_requests_per_minute == 0 {
+            return Duration::ZERO;
+        }
+        let mut origins = self.origins.lock().unwrap_or_else(|e| e.into_inner());
+        let keep = WINDOW.max(min_delay + self.jitter);
+        origins.retain(|_, slots| slots.last.is_some_and(|last| last + keep > now));
+
+        let slots = origins.entry(origin.to_string()).or_default();
+        while slots.minute.front().is_some_and(|start| *start + WINDOW <= now) {
+            slots.minute.pop_front();
+        }
+        let mut start = now;
+        if let Some(last) = slots.last
+            && !min_delay.is_zero()
+        {
+            start = start.max(last + min_delay + jitter);
+        }
+        let max = limit.max_requests_per_minute as usize;
+        if max > 0 && slots.minute.len() >= max {
+            start = start.max(slots.minute[slots.minute.len() - max] + WINDOW);
+        }
+        slots.last = Some(start);
+        slots.minute.push_back(start);
+        start - now
+    }
+}
+
+/// Whether `url` points at this machine, e.g. a development server or test fixture
+fn is_loopback(url: &str) -> bool {
+    match url::Url::parse(url).ok().and_then(|url| url.host().map(|host| host.to_owned())) {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(address)) => address.is_loopback(),
+        Some(url::Host::Ipv6(address)) => address.is_loopback(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slots_keep_delay_and_rate() {
+        let config = Config {
+            politeness_min_delay_ms: 1000,
+            politeness_max_requests_per_minute: 3,
+            politeness_jitter_ms: 0,
+            politeness_origins: BTreeMap::from([(
+                "http://localhost:8080".to_string(),
+                PolitenessLimit { min_delay_ms: 500, max_requests_per_minute: 0 },
+            )]),
+            ..Config::default()
+        };
+        let politeness = Politeness::from_config(&config);
+        let limit = politeness.default_limit;
+        let now = Instant::now();
+        let second = Duration::from_secs(1);
+
+        assert_eq!(politeness.reserve_at("https://a.test", limit, Duration::ZERO, now), Duration::ZERO);
+        assert_eq!(politeness.reserve_at("https://a.test", limit, Duration::from_millis(200), now), second * 6 / 5);
+        // Other origins keep their own slots
+        assert_eq!(politeness.reserve_at("https://b.test", limit, Duration::ZERO, now), Duration::ZERO);
+        assert_eq!(politeness.reserve_at("https://a.test", limit, Duration::ZERO, now + second * 30), Duration::ZERO);
+        // The fourth load within a minute waits for the first to leave the window
+        assert_eq!(politeness.reserve_at("https://a.test", limit, Duration::ZERO, now + second * 30), second * 30);
+        assert_eq!(politeness.reserve_at("https://a.test", limit, Duration::ZERO, now + second * 90), Duration::ZERO);
+
+        assert_eq!(politeness.reserve("http://127.0.0.1:3000/login"), Duration::ZERO);
+        assert_eq!(politeness.reserve("about:blank"), Duration::ZERO);
+        politeness.reserve("http://localhost:8080/");
+        assert!(politeness.reserve("http://localhost:8080/next") > Duration::from_millis(400));
+    }
+}
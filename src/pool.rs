@@ -83,10 +83,10 @@ impl DriverPool {
         }
     }
 
-    /// Try to acquire an idle connection from the pool
-    fn try_acquire(&mut self) -> Option<(Client, String)> {
+    /// Try to acquire an idle connection whose session id satisfies `matches`
+    fn try_acquire(&mut self, matches: impl Fn(&str) -> bool) -> Option<(Client, String)> {
         for conn in &mut self.connections {
-            if !conn.in_use {
+            if !conn.in_use && matches(&conn.session_id) {
                 conn.mark_in_use();
                 self.stats.in_use += 1;
                 self.stats.idle = self.stats.idle.saturating_sub(1);
@@ -270,44 +270,61 @@ impl ConnectionPool {
     pub async fn acquire(
         &self,
         driver_type: &DriverType,
+    ) -> Result<Option<(String, Client)>> {
+        self.acquire_matching(driver_type, |_| true).await
+    }
+
+    /// Acquire an idle connection previously registered under a session id accepted
+    /// by `matches`, e.g. one belonging to the same authenticated user
+    pub async fn acquire_matching(
+        &self,
+        driver_type: &DriverType,
+        matches: impl Fn(&str) -> bool,
     ) -> Result<Option<(String, Client)>> {
         if !self.enabled {
             return Ok(None);
         }
 
-        let pools = self.pools.read().await;
-        if let Some(pool_mutex) = pools.get(driver_type) {
-            let mut pool = pool_mutex.lock().await;
+        // Taking a connection marks it in use, so the pool need not stay locked
+        // while the connection is checked
+        let acquired = {
+            let pools = self.pools.read().await;
+            match pools.get(driver_type) {
+                Some(pool_mutex) => pool_mutex.lock().await.try_acquire(matches),
+                None => None,
+            }
+        };
+        let Some((client, session_id)) = acquired else {
+            return Ok(None);
+        };
 
-            // Try to acquire an existing idle connection
-            if let Some((client, session_id)) = pool.try_acquire() {
-                // Verify the connection is still healthy
-                match tokio::time::timeout(
-                    Duration::from_secs(2),
-                    client.current_url(),
-                ).await {
-                    Ok(Ok(_)) => {
-                        tracing::debug!(
-                            "Acquired pooled {} connection: {}",
-                            driver_type.browser_name(),
-                            session_id
-                        );
-                        return Ok(Some((session_id, client)));
-                    }
-                    _ => {
-                        // Connection is dead, remove it
-                        tracing::debug!(
-                            "Pooled connection {} is dead, removing",
-                            session_id
-                        );
-                        pool.remove(&session_id);
-                        pool.stats.total_health_check_failures += 1;
-                    }
+        // Verify the connection is still healthy
+        match tokio::time::timeout(
+            Duration::from_secs(2),
+            client.current_url(),
+        ).await {
+            Ok(Ok(_)) => {
+                tracing::debug!(
+                    "Acquired pooled {} connection: {}",
+                    driver_type.browser_name(),
+                    session_id
+                );
+                Ok(Some((session_id, client)))
+            }
+            _ => {
+                // Connection is dead, remove it
+                tracing::debug!(
+                    "Pooled connection {} is dead, removing",
+                    session_id
+                );
+                if let Some(pool_mutex) = self.pools.read().await.get(driver_type) {
+                    let mut pool = pool_mutex.lock().await;
+                    pool.remove(&session_id);
+                    pool.stats.total_health_check_failures += 1;
                 }
+                Ok(None)
             }
         }
-
-        Ok(None)
     }
 
     /// Add a new connection to the pool
@@ -457,17 +474,88 @@ mod tests {
     fn create_test_config() -> Config {
         Config {
             webdriver_endpoint: "auto".to_string(),
+            failover_endpoints: Vec::new(),
             default_session_timeout_ms: 2000,
             auto_start_driver: true,
             preferred_driver: None,
             headless: true,
+            auto_create_sessions: true,
+            recover_crashed_tabs: true,
+            auto_dismiss_banners: false,
+            popup_policy: "allow".to_string(),
+            session_hooks: Vec::new(),
+            session_templates: std::collections::BTreeMap::new(),
             concurrent_drivers: vec!["chrome".to_string()],
             driver_startup_timeout_ms: 10000,
+            driver_startup_timeouts_ms: Default::default(),
+            driver_startup_retries: 2,
+            driver_startup_backoff_ms: 1000,
+            parallel_driver_startup: false,
             enable_performance_memory: false,
+            network_echo_url: "https://httpbin.org/anything".to_string(),
+            extra_capabilities: std::collections::BTreeMap::new(),
+            allow_session_capabilities: false,
+            default_wait_timeout_secs: 10.0,
+            navigation_timeout_ms: None,
+            script_timeout_ms: None,
+            console_log_wait_secs: 2.0,
+            scroll_settle_ms: 500,
+            health_check_interval_secs: 30,
+            element_retry_attempts: 3,
+            element_retry_backoff_ms: 200,
+            wait_for_stable_elements: true,
+            element_stability_timeout_ms: 2000,
+            webdriver_call_timeout_secs: 60,
+            max_tool_timeout_ms: 600_000,
             pool_max_connections_per_driver: 3,
             pool_idle_timeout_secs: 300,
             pool_acquire_timeout_ms: 30000,
             pool_enabled: true,
+            site_pool_enabled: false,
+            site_pool_max_sessions: 8,
+            site_min_interval_ms: 1000,
+            politeness_min_delay_ms: 1000,
+            politeness_max_requests_per_minute: 30,
+            politeness_jitter_ms: 250,
+            politeness_origins: std::collections::BTreeMap::new(),
+            respect_robots_txt: false,
+            robots_user_agent: "rust-browser-mcp".to_string(),
+            recipes_dir: None,
+            recipe_store: "local".to_string(),
+            recipes_s3_bucket: None,
+            recipes_s3_endpoint: None,
+            recipes_s3_region: "us-east-1".to_string(),
+            recipes_s3_prefix: "recipes/".to_string(),
+            expose_recipes_as_tools: false,
+            recipe_report: None,
+            artifacts_dir: None,
+            artifacts_retention_hours: None,
+            artifacts_max_bytes: None,
+            disabled_tool_categories: Vec::new(),
+            disabled_tools: Vec::new(),
+            enabled_tools: Vec::new(),
+            audit_log_path: None,
+            audit_log_max_bytes: 10 * 1024 * 1024,
+            audit_log_max_files: 5,
+            log_file: None,
+            log_file_format: "text".to_string(),
+            log_file_level: "info".to_string(),
+            log_file_max_bytes: 10 * 1024 * 1024,
+            log_file_max_files: 5,
+            log_file_rotation: "never".to_string(),
+            api_keys: Vec::new(),
+            api_keys_file: None,
+            api_key_header: "Authorization".to_string(),
+            require_scopes: false,
+            jwt_jwks_url: None,
+            jwt_issuer: None,
+            jwt_audience: Vec::new(),
+            jwt_leeway_secs: 60,
+            rate_limit_per_minute: None,
+            max_concurrent_calls: None,
+            shutdown_drain_timeout_secs: 30,
+            cors_allowed_origins: Vec::new(),
+            base_path: None,
         }
     }
 
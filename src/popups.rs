@@ -0,0 +1,314 @@
+//! Handling of popups and other windows a page opens on its own
+//!
+//! Each session has a [`PopupPolicy`]. A guard installed on every page load
+//! wraps `window.open`, so under `block` the call returns `null` and no window
+//! ever appears. Windows that open anyway (`target="_blank"` links, forms, or
+//! pages loaded before the guard) are found by comparing the session's window
+//! handles after `navigate` and `click`: `block` closes them, `switch` makes the
+//! newest one current and returns to its opener once it closes itself, as OAuth
+//! popups do. Everything that happened is kept as [`PopupEvent`]s for
+//! `get_popup_events`.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use fantoccini::{Client, error::CmdError, wd::WindowHandle};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::ClientManager;
+
+/// Events kept per session before the oldest are dropped
+pub const MAX_POPUP_EVENTS: usize = 500;
+
+/// What to do when a page opens a new window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PopupPolicy {
+    /// Let popups open and stay on the current window
+    #[default]
+    Allow,
+    /// Suppress `window.open` and close any window that opens anyway
+    Block,
+    /// Move the session to the newest popup, and back to its opener when it closes
+    Switch,
+}
+
+impl PopupPolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "allow" => Some(Self::Allow),
+            "block" => Some(Self::Block),
+            "switch" => Some(Self::Switch),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Block => "block",
+            Self::Switch => "switch",
+        }
+    }
+}
+
+/// Wraps `window.open` once per page and sets the policy it follows to `arguments[0]`
+const POPUP_GUARD_SCRIPT: &str = r#"
+    try {
+        window.__mcpPopupPolicy = arguments[0];
+        if (!window.__mcpPopupEvents) {
+            window.__mcpPopupEvents = [];
+            const open = window.open;
+            window.open = function(url) {
+                const blocked = window.__mcpPopupPolicy === 'block';
+                let href = url ? String(url) : 'about:blank';
+                try { href = new URL(href, window.location.href).href; } catch (e) {}
+                window.__mcpPopupEvents.push({ action: blocked ? 'blocked' : 'requested', url: href, timestamp: Date.now() });
+                return blocked ? null : open.apply(window, arguments);
+            };
+        }
+        return true;
+    } catch (e) {
+        return false;
+    }
+"#;
+
+/// Removes and returns the `window.open` calls recorded since the last drain
+const DRAIN_POPUP_EVENTS_SCRIPT: &str = r#"
+    try {
+        const events = window.__mcpPopupEvents;
+        return events ? events.splice(0, events.length) : [];
+    } catch (e) {
+        return [];
+    }
+"#;
+
+/// Something a page did with windows, and what the policy made of it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PopupEvent {
+    /// "requested" or "blocked" for `window.open` calls; "opened", "closed" or
+    /// "switched" for new windows; "returned" when the session went back to an opener
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Handle of the window concerned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handle: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl PopupEvent {
+    fn new(action: &str, handle: &WindowHandle, url: Option<String>) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+        Self { action: action.to_string(), url, handle: Some(String::from(handle.clone())), timestamp }
+    }
+
+    pub fn describe(&self) -> String {
+        let target = self.url.as_deref().or(self.handle.as_deref()).unwrap_or("unknown window");
+        format!("{} {}", self.action, target)
+    }
+}
+
+/// One-line summary of popup events for a tool's success message
+pub fn summarize(events: &[PopupEvent]) -> String {
+    let described: Vec<String> = events.iter().map(PopupEvent::describe).collect();
+    format!("Popups: {}", described.join(", "))
+}
+
+#[derive(Debug, Default)]
+struct SessionPopups {
+    policy: Option<PopupPolicy>,
+    /// Windows seen at the last check; `None` until the first one
+    windows: Option<Vec<WindowHandle>>,
+    /// Window to go back to when the popup switched to closes
+    opener: Option<WindowHandle>,
+    events: VecDeque<PopupEvent>,
+}
+
+/// Popup policies, known windows and events per session
+#[derive(Debug, Clone, Default)]
+pub struct PopupTracker {
+    sessions: Arc<Mutex<HashMap<String, SessionPopups>>>,
+}
+
+impl PopupTracker {
+    /// The policy `session` chose, if it chose one
+    pub fn policy(&self, session: &str) -> Option<PopupPolicy> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.get(session)?.policy
+    }
+
+    pub fn set_policy(&self, session: &str, policy: PopupPolicy) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.entry(session.to_string()).or_default().policy = Some(policy);
+    }
+
+    /// Events of `session`, oldest first; `clear` forgets them afterwards
+    pub fn events(&self, session: &str, clear: bool) -> Vec<PopupEvent> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(entry) = sessions.get_mut(session) else {
+            return Vec::new();
+        };
+        if clear {
+            entry.events.drain(..).collect()
+        } else {
+            entry.events.iter().cloned().collect()
+        }
+    }
+
+    fn record(&self, session: &str, events: &[PopupEvent]) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = sessions.entry(session.to_string()).or_default();
+        entry.events.extend(events.iter().cloned());
+        while entry.events.len() > MAX_POPUP_EVENTS {
+            entry.events.pop_front();
+        }
+    }
+
+    fn windows(&self, session: &str) -> (Option<Vec<WindowHandle>>, Option<WindowHandle>) {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions
+            .get(session)
+            .map(|entry| (entry.windows.clone(), entry.opener.clone()))
+            .unwrap_or_default()
+    }
+
+    fn set_windows(&self, session: &str, windows: Vec<WindowHandle>, opener: Option<WindowHandle>) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = sessions.entry(session.to_string()).or_default();
+        entry.windows = Some(windows);
+        entry.opener = opener;
+    }
+
+    /// Take the session's current windows as the baseline at the next check, after
+    /// the server itself opened a window
+    pub fn forget_windows(&self, session: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = sessions.get_mut(session) {
+            entry.windows = None;
+            entry.opener = None;
+        }
+    }
+
+    /// Forget a closed session's policy and events
+    pub fn remove(&self, session: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(session);
+    }
+}
+
+/// Install the `window.open` guard on the current page of `session`
+pub async fn install_guard(client_manager: &ClientManager, session: &str, client: &Client) -> crate::Result<()> {
+    let policy = client_manager.popup_policy(session);
+    client_manager
+        .run_command(session, "install_popup_guard", client.execute(POPUP_GUARD_SCRIPT, vec![json!(policy)]))
+        .await
+        .map(|_| ())
+}
+
+/// Apply the session's policy to windows opened since the last check. Returns
+/// the new events, which are also kept for `get_popup_events`.
+pub async fn apply_policy(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+) -> crate::Result<Vec<PopupEvent>> {
+    let policy = client_manager.popup_policy(session);
+    let tracker = client_manager.popups();
+    let key = ClientManager::session_key(session);
+    let (known, opener) = tracker.windows(&key);
+
+    let check = async {
+        let drained = client.execute(DRAIN_POPUP_EVENTS_SCRIPT, vec![]).await?;
+        let mut events: Vec<PopupEvent> = serde_json::from_value(drained).unwrap_or_default();
+        let mut windows = client.windows().await?;
+        let mut opener = opener;
+        // The current window is gone when a popup we switched to closed itself
+        let mut current = match client.window().await {
+            Ok(handle) if windows.contains(&handle) => Some(handle),
+            _ => None,
+        };
+        let fallback = windows.first().cloned();
+        if current.is_none()
+            && let Some(back) = opener.take().filter(|handle| windows.contains(handle)).or(fallback)
+        {
+            client.switch_to_window(back.clone()).await?;
+            events.push(PopupEvent::new("returned", &back, None));
+            current = Some(back);
+        }
+
+        let Some(known) = known else {
+            return Ok::<_, CmdError>((events, windows, opener));
+        };
+        let opened: Vec<WindowHandle> = windows.iter().filter(|handle| !known.contains(handle)).cloned().collect();
+        match policy {
+            PopupPolicy::Allow => {
+                events.extend(opened.iter().map(|handle| PopupEvent::new("opened", handle, None)));
+            }
+            PopupPolicy::Block => {
+                for handle in &opened {
+                    client.switch_to_window(handle.clone()).await?;
+                    let url = client.current_url().await.ok().map(|url| url.to_string());
+                    client.close_window().await?;
+                    events.push(PopupEvent::new("closed", handle, url));
+                }
+                windows.retain(|handle| !opened.contains(handle));
+                if !opened.is_empty()
+                    && let Some(current) = &current
+                {
+                    client.switch_to_window(current.clone()).await?;
+                }
+            }
+            PopupPolicy::Switch => {
+                if let Some((newest, others)) = opened.split_last() {
+                    events.extend(others.iter().map(|handle| PopupEvent::new("opened", handle, None)));
+                    client.switch_to_window(newest.clone()).await?;
+                    let url = client.current_url().await.ok().map(|url| url.to_string());
+                    events.push(PopupEvent::new("switched", newest, url));
+                    opener = current.or(opener);
+                }
+            }
+        }
+        Ok((events, windows, opener))
+    };
+
+    let (events, windows, opener) = client_manager.run_command(session, "apply_popup_policy", check).await?;
+    tracker.set_windows(&key, windows, opener);
+    tracker.record(&key, &events);
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_keeps_policy_and_newest_events() {
+        let tracker = PopupTracker::default();
+        assert_eq!(tracker.policy("a"), None);
+        tracker.set_policy("a", PopupPolicy::Block);
+        assert_eq!(tracker.policy("a"), Some(PopupPolicy::Block));
+
+        let handle = WindowHandle::try_from("w1".to_string()).unwrap();
+        let events: Vec<PopupEvent> =
+            (0..MAX_POPUP_EVENTS + 1).map(|_| PopupEvent::new("closed", &handle, None)).collect();
+        tracker.record("a", &events);
+        assert_eq!(tracker.events("a", false).len(), MAX_POPUP_EVENTS);
+        assert_eq!(tracker.events("a", true)[0].handle.as_deref(), Some("w1"));
+        assert!(tracker.events("a", false).is_empty());
+
+        assert_eq!(PopupPolicy::from_name("Switch"), Some(PopupPolicy::Switch));
+        assert_eq!(PopupPolicy::from_name("popup"), None);
+        let page_event: PopupEvent =
+            serde_json::from_value(json!({ "action": "blocked", "url": "https://a.test/", "timestamp": 1 })).unwrap();
+        assert_eq!(page_event.handle, None);
+
+        tracker.remove("a");
+        assert_eq!(tracker.policy("a"), None);
+    }
+}
@@ -0,0 +1,184 @@
+//! Usage quotas for sandboxed sessions
+//!
+//! Whoever creates a session with `create_session` can cap how many tool calls
+//! and navigations it may serve and how long it may be used. Once a limit is
+//! reached, every further call to the session fails with `QUOTA_EXCEEDED`
+//! before it runs, which keeps an autonomous agent within a fixed budget.
+//! Sessions without limits are not tracked.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::tools::{RECIPE_TOOL_PREFIX, ToolCategory};
+
+/// Tools that count as a navigation
+const NAVIGATION_TOOLS: [&str; 4] = ["navigate", "back", "forward", "refresh"];
+
+/// Whether a call to `tool` counts against its session's quota. Tools that
+/// manage sessions, drivers or the server itself do not; a batch counts each
+/// of its steps instead of itself.
+pub fn is_metered(tool: &str) -> bool {
+    match ToolCategory::of_tool(tool) {
+        ToolCategory::Automation => !matches!(tool, "create_session" | "list_sessions" | "run_batch"),
+        ToolCategory::Scripting | ToolCategory::Performance => true,
+        ToolCategory::Recipes => tool == "execute_recipe" || tool.starts_with(RECIPE_TOOL_PREFIX),
+        ToolCategory::DriverManagement | ToolCategory::Admin => false,
+    }
+}
+
+/// Limits set when a session was created; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SessionQuota {
+    pub max_tool_calls: Option<u64>,
+    pub max_navigations: Option<u64>,
+    pub max_runtime_secs: Option<u64>,
+}
+
+impl SessionQuota {
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A call refused because its session used up a limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    pub session: String,
+    /// Name of the limit, e.g. `max_navigations`
+    pub limit: &'static str,
+    pub allowed: u64,
+    pub usage: Value,
+}
+
+impl QuotaExceeded {
+    pub fn message(&self) -> String {
+        let what = match self.limit {
+            "max_tool_calls" => format!("{} tool call(s)", self.allowed),
+            "max_navigations" => format!("{} navigation(s)", self.allowed),
+            _ => format!("{}s of runtime", self.allowed),
+        };
+        format!("Session '{}' used up its quota of {what}; create a new session to continue", self.session)
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "session_id": self.session, "limit": self.limit, "allowed": self.allowed, "usage": self.usage })
+    }
+}
+
+struct Usage {
+    quota: SessionQuota,
+    tool_calls: u64,
+    navigations: u64,
+    started: Instant,
+}
+
+impl Usage {
+    fn to_json(&self) -> Value {
+        json!({
+            "quota": self.quota,
+            "tool_calls": self.tool_calls,
+            "navigations": self.navigations,
+            "runtime_secs": self.started.elapsed().as_secs(),
+        })
+    }
+}
+
+/// Quotas and usage of limited sessions
+#[derive(Clone, Default)]
+pub struct QuotaTracker {
+    sessions: Arc<Mutex<HashMap<String, Usage>>>,
+}
+
+impl QuotaTracker {
+    /// Start metering session `key` from zero
+    pub fn set(&self, key: &str, quota: SessionQuota) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.insert(key.to_string(), Usage { quota, tool_calls: 0, navigations: 0, started: Instant::now() });
+    }
+
+    /// Count a call to `tool` against session `key`, or refuse it when a limit is used up
+    pub fn charge(&self, key: &str, session: &str, tool: &str) -> Result<(), QuotaExceeded> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(usage) = sessions.get_mut(key) else {
+            return Ok(());
+        };
+        let navigation = NAVIGATION_TOOLS.contains(&tool);
+        let quota = usage.quota;
+        let exceeded = |limit, allowed, usage: &Usage| QuotaExceeded {
+            session: session.to_string(),
+            limit,
+            allowed,
+            usage: usage.to_json(),
+        };
+
+        if let Some(max) = quota.max_runtime_secs
+            && usage.started.elapsed() >= Duration::from_secs(max)
+        {
+            return Err(exceeded("max_runtime_secs", max, usage));
+        }
+        if let Some(max) = quota.max_tool_calls
+            && usage.tool_calls >= max
+        {
+            return Err(exceeded("max_tool_calls", max, usage));
+        }
+        if navigation
+            && let Some(max) = quota.max_navigations
+            && usage.navigations >= max
+        {
+            return Err(exceeded("max_navigations", max, usage));
+        }
+
+        usage.tool_calls += 1;
+        if navigation {
+            usage.navigations += 1;
+        }
+        Ok(())
+    }
+
+    /// Quota and usage so far of session `key`, if it has limits
+    pub fn usage(&self, key: &str) -> Option<Value> {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.get(key).map(Usage::to_json)
+    }
+
+    /// Forget a closed session
+    pub fn remove(&self, key: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_refuses_calls_past_limits() {
+        let tracker = QuotaTracker::default();
+        assert!(tracker.charge("free", "free", "navigate").is_ok());
+        assert_eq!(tracker.usage("free"), None);
+
+        let quota = SessionQuota { max_tool_calls: Some(3), max_navigations: Some(1), max_runtime_secs: None };
+        tracker.set("agent", quota);
+        assert!(tracker.charge("agent", "agent", "navigate").is_ok());
+        let refused = tracker.charge("agent", "agent", "back").unwrap_err();
+        assert_eq!(refused.limit, "max_navigations");
+        assert_eq!(refused.usage["navigations"], 1);
+        assert!(tracker.charge("agent", "agent", "click").is_ok());
+        assert!(tracker.charge("agent", "agent", "get_title").is_ok());
+        let refused = tracker.charge("agent", "agent", "get_title").unwrap_err();
+        assert_eq!(refused.message(), "Session 'agent' used up its quota of 3 tool call(s); create a new session to continue");
+
+        tracker.set("brief", SessionQuota { max_runtime_secs: Some(0), ..SessionQuota::default() });
+        assert_eq!(tracker.charge("brief", "brief", "click").unwrap_err().limit, "max_runtime_secs");
+
+        assert!(is_metered("click") && is_metered("execute_recipe") && is_metered("recipe_login"));
+        assert!(!is_metered("create_session") && !is_metered("run_batch") && !is_metered("get_server_stats"));
+    }
+}
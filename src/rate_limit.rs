@@ -0,0 +1,280 @@
+//! Per-client tool-call rate limits and concurrency caps
+//!
+//! Shared HTTP/WebSocket deployments can be flooded by a single runaway agent loop.
+//! Each remote client (authenticated user, or MCP session when auth is off) gets a
+//! sliding one-minute budget of tool calls and a cap on calls in flight at once.
+//! Stdio has a single trusted client and is never limited.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+
+use crate::{ErrorCode, config::Config};
+
+/// JSON-RPC error code used for rejected calls, next to the MCP server error range
+pub const RATE_LIMITED_ERROR_CODE: i32 = -32029;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct ClientUsage {
+    recent_calls: VecDeque<Instant>,
+    in_flight: usize,
+}
+
+impl ClientUsage {
+    /// Drop calls that left the one-minute window
+    fn prune(&mut self, now: Instant) {
+        while self.recent_calls.front().is_some_and(|call| now.duration_since(*call) >= WINDOW) {
+            self.recent_calls.pop_front();
+        }
+    }
+
+    /// No call running and none left in the window; the client can be forgotten
+    fn is_idle(&mut self, now: Instant) -> bool {
+        self.prune(now);
+        self.in_flight == 0 && self.recent_calls.is_empty()
+    }
+}
+
+/// Why a call was refused
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateLimitExceeded {
+    /// More than `limit` calls in the last minute
+    CallsPerMinute { limit: u32, retry_after: Duration },
+    /// `limit` calls already running for this client
+    Concurrency { limit: usize },
+}
+
+impl RateLimitExceeded {
+    pub fn message(&self) -> String {
+        match self {
+            Self::CallsPerMinute { limit, retry_after } => format!(
+                "Rate limit exceeded: at most {limit} tool calls per minute (retry in {}s)",
+                retry_after.as_secs().max(1)
+            ),
+            Self::Concurrency { limit } => {
+                format!("Too many concurrent tool calls: at most {limit} may run at once")
+            }
+        }
+    }
+
+    /// Structured error data, modelled on an HTTP 429 response
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::CallsPerMinute { limit, retry_after } => json!({
+                "status": 429,
+                "error": "rate_limited",
+                "code": ErrorCode::RateLimited.as_str(),
+                "retryable": true,
+                "limit_per_minute": limit,
+                "retry_after_secs": retry_after.as_secs().max(1),
+            }),
+            Self::Concurrency { limit } => json!({
+                "status": 429,
+                "error": "too_many_concurrent_calls",
+                "code": ErrorCode::RateLimited.as_str(),
+                "retryable": true,
+                "max_concurrent_calls": limit,
+            }),
+        }
+    }
+}
+
+/// Shared limiter; clones count against the same per-client budgets
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    calls_per_minute: Option<u32>,
+    max_concurrent: Option<usize>,
+    clients: Arc<Mutex<HashMap<String, ClientUsage>>>,
+    /// When idle clients were last swept out of `clients`
+    last_sweep: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            calls_per_minute: config.rate_limit_per_minute,
+            max_concurrent: config.max_concurrent_calls,
+            clients: Arc::default(),
+            last_sweep: Arc::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.calls_per_minute.is_some() || self.max_concurrent.is_some()
+    }
+
+    /// Admit a call from `client`. The returned permit holds a concurrency slot
+    /// until dropped.
+    pub fn acquire(&self, client: &str) -> Result<CallPermit, RateLimitExceeded> {
+        let now = Instant::now();
+        let mut clients = self.lock();
+        self.sweep(&mut clients, now);
+        let usage = clients.entry(client.to_string()).or_default();
+        usage.prune(now);
+
+        if let Some(limit) = self.max_concurrent.filter(|limit| usage.in_flight >= *limit) {
+            return Err(RateLimitExceeded::Concurrency { limit });
+        }
+        self.count_call(usage, now)?;
+        usage.in_flight += 1;
+
+        Ok(CallPermit {
+            limiter: self.clone(),
+            client: client.to_string(),
+        })
+    }
+
+    /// Admit one step of a batch from `client` against the per-minute budget. The
+    /// batch call itself holds the concurrency slot its steps run in.
+    pub fn acquire_step(&self, client: &str) -> Result<(), RateLimitExceeded> {
+        let now = Instant::now();
+        let mut clients = self.lock();
+        self.sweep(&mut clients, now);
+        let usage = clients.entry(client.to_string()).or_default();
+        usage.prune(now);
+        self.count_call(usage, now)
+    }
+
+    /// Record a call in `usage`'s window unless it is full
+    fn count_call(&self, usage: &mut ClientUsage, now: Instant) -> Result<(), RateLimitExceeded> {
+        if let Some(limit) = self
+            .calls_per_minute
+            .filter(|limit| usage.recent_calls.len() >= *limit as usize)
+        {
+            let oldest = usage.recent_calls.front().copied().unwrap_or(now);
+            return Err(RateLimitExceeded::CallsPerMinute {
+                limit,
+                retry_after: WINDOW.saturating_sub(now.duration_since(oldest)),
+            });
+        }
+
+        usage.recent_calls.push_back(now);
+        Ok(())
+    }
+
+    fn release(&self, client: &str) {
+        let mut clients = self.lock();
+        if let Some(usage) = clients.get_mut(client) {
+            usage.in_flight = usage.in_flight.saturating_sub(1);
+            // Forget idle clients so the map doesn't grow with every MCP session
+            if usage.is_idle(Instant::now()) {
+                clients.remove(client);
+            }
+        }
+    }
+
+    /// Once per window, forget the clients whose calls all left it. Clients
+    /// usually release with calls still in the window, so `release` alone
+    /// would keep one entry per MCP session that ever called.
+    fn sweep(&self, clients: &mut HashMap<String, ClientUsage>, now: Instant) {
+        let mut last_sweep = self.last_sweep.lock().unwrap_or_else(|e| e.into_inner());
+        if last_sweep.is_some_and(|last| now.duration_since(last) < WINDOW) {
+            return;
+        }
+        *last_sweep = Some(now);
+        clients.retain(|_, usage| !usage.is_idle(now));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ClientUsage>> {
+        self.clients.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Concurrency slot for one in-flight call
+pub struct CallPermit {
+    limiter: RateLimiter,
+    client: String,
+}
+
+impl Drop for CallPermit {
+    fn drop(&mut self) {
+        self.limiter.release(&self.client);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_minute_and_concurrency_limits() {
+        let mut config = Config::from_env();
+        config.rate_limit_per_minute = Some(3);
+        config.max_concurrent_calls = Some(2);
+        let limiter = RateLimiter::from_config(&config);
+
+        let first = limiter.acquire("alice").unwrap();
+        let second = limiter.acquire("alice").unwrap();
+        assert_eq!(
+            limiter.acquire("alice").err(),
+            Some(RateLimitExceeded::Concurrency { limit: 2 })
+        );
+
+        // Other clients have their own budget
+        drop(limiter.acquire("bob").unwrap());
+
+        drop(first);
+        drop(second);
+        drop(limiter.acquire("alice").unwrap());
+
+        match limiter.acquire("alice") {
+            Err(RateLimitExceeded::CallsPerMinute { limit, retry_after }) => {
+                assert_eq!(limit, 3);
+                assert!(retry_after <= WINDOW);
+            }
+            other => panic!("expected per-minute limit, got {:?}", other.err()),
+        }
+        assert_eq!(
+            RateLimitExceeded::Concurrency { limit: 2 }.to_json()["status"],
+            429
+        );
+    }
+
+    #[test]
+    fn test_batch_steps_count_against_the_minute() {
+        let mut config = Config::from_env();
+        config.rate_limit_per_minute = Some(3);
+        config.max_concurrent_calls = Some(1);
+        let limiter = RateLimiter::from_config(&config);
+
+        let batch = limiter.acquire("alice").unwrap();
+        assert!(limiter.acquire_step("alice").is_ok());
+        assert!(limiter.acquire_step("alice").is_ok());
+        assert!(matches!(limiter.acquire_step("alice"), Err(RateLimitExceeded::CallsPerMinute { limit: 3, .. })));
+        drop(batch);
+        assert!(matches!(limiter.acquire("alice"), Err(RateLimitExceeded::CallsPerMinute { .. })));
+    }
+
+    #[test]
+    fn test_idle_clients_are_forgotten() {
+        let mut config = Config::from_env();
+        config.rate_limit_per_minute = Some(10);
+        let limiter = RateLimiter::from_config(&config);
+
+        for session in ["a", "b", "c"] {
+            drop(limiter.acquire(session).unwrap());
+        }
+        let running = limiter.acquire("d").unwrap();
+        assert_eq!(limiter.lock().len(), 4);
+
+        // A minute later every finished client has left the window
+        let later = Instant::now() + WINDOW;
+        limiter.sweep(&mut limiter.lock(), later);
+        assert_eq!(limiter.lock().keys().collect::<Vec<_>>(), ["d"]);
+
+        // Sweeps run once per window
+        limiter.acquire_step("e").unwrap();
+        limiter.sweep(&mut limiter.lock(), later);
+        assert_eq!(limiter.lock().len(), 2);
+
+        // The sweep pruned the running client's window, so its release forgets it
+        drop(running);
+        assert!(!limiter.lock().contains_key("d"));
+    }
+}
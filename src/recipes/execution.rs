@@ -1,15 +1,39 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
 use std::time::Duration;
 use serde_json::Value;
+use tokio_util::sync::CancellationToken;
 // base64 imports removed - recipe execution now handles PNG data directly
 
-use crate::recipes::recipe::{Recipe, RecipeStep};
+use crate::accessibility::{self, Impact};
+use crate::client::{ClientManager, SessionOptions};
+use crate::console;
+use crate::driver::DriverType;
+use crate::recipes::recipe::{self, Recipe, RecipeStep, SessionIsolation};
+use crate::recipes::report::{ReportEnvironment, ReportFormat, ReportScreenshot, RunReport};
 use crate::error::WebDriverError;
+use crate::forms::{self, FieldAction};
+use crate::handlers::{localization::localization_check, navigation};
+use crate::localization;
+use crate::login::{self, LoginCheck, LoginOutcome};
+use crate::resources::ResourceKind;
+use crate::screenshots;
 use crate::server::WebDriverServer;
+use crate::stability;
+use crate::tools::params::CheckLocalizationParams;
+use crate::typing::{self, TypingOptions};
 // Remove unused imports
 
 pub struct RecipeExecutor<'a> {
     server: &'a WebDriverServer,
+    cancellation: CancellationToken,
+    /// Subdirectory of the server run's artifacts directory receiving this run's files
+    artifact_scope: String,
+    /// Format of the report written after the run, if any
+    report: Option<ReportFormat>,
+    /// Console errors each browser logged, kept for the report
+    console_errors: Mutex<BTreeMap<String, Vec<Value>>>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +53,10 @@ pub struct ExecutionResult {
     pub execution_time_ms: u64,
     pub error_message: Option<String>,
     pub browser_results: HashMap<String, BrowserExecutionResult>,
+    pub attempts: u32,
+    pub on_failure_results: Vec<StepResult>,
+    /// Report written to the artifacts directory after the run, if one was asked for
+    pub report_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +68,8 @@ pub struct BrowserExecutionResult {
     pub step_results: Vec<StepResult>,
     pub execution_time_ms: u64,
     pub error_message: Option<String>,
+    pub attempts: u32,
+    pub on_failure_results: Vec<StepResult>,
 }
 
 #[derive(Debug, Clone)]
@@ -59,32 +89,67 @@ pub struct StepResult {
 
 impl<'a> RecipeExecutor<'a> {
     pub fn new(server: &'a WebDriverServer) -> Self {
-        Self { server }
+        Self {
+            server,
+            cancellation: CancellationToken::new(),
+            artifact_scope: format!(
+                "recipe-{}-{}",
+                chrono::Utc::now().format("%H%M%S"),
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+            report: None,
+            console_errors: Mutex::default(),
+        }
+    }
+
+    /// Write a report of the run in `format` to its artifacts directory
+    pub fn with_report(mut self, format: Option<ReportFormat>) -> Self {
+        self.report = format;
+        self
+    }
+
+    /// Stop the run at the next step boundary (or mid-wait) once `cancellation` fires.
+    /// Isolated sessions are still closed; retries and `on_failure` hooks are skipped.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
     }
 
-    fn resolve_browsers(&self, browsers: &[String]) -> Result<Vec<String>, WebDriverError> {
+    /// Concrete browsers to run on. "auto" picks the first of Chrome, Firefox and
+    /// Edge that can open a session; a named browser that cannot fails the run
+    /// up front instead of at its first step.
+    async fn resolve_browsers(&self, browsers: &[String]) -> Result<Vec<String>, WebDriverError> {
+        let client_manager = self.server.get_client_manager();
         let mut resolved_browsers = Vec::new();
         
         for browser in browsers {
             match browser.as_str() {
                 "auto" => {
                     // Auto-selection priority: Chrome → Firefox → Edge
-                    if self.is_browser_available("chrome") {
-                        resolved_browsers.push("chrome".to_string());
-                    } else if self.is_browser_available("firefox") {
-                        resolved_browsers.push("firefox".to_string());
-                    } else if self.is_browser_available("edge") {
-                        resolved_browsers.push("edge".to_string());
-                    } else {
-                        return Err(WebDriverError::Execution("No supported browsers available".to_string()));
+                    let mut reasons = Vec::new();
+                    for candidate in [DriverType::Chrome, DriverType::Firefox, DriverType::Edge] {
+                        match client_manager.browser_unavailable_reason(&candidate).await {
+                            None => {
+                                resolved_browsers.push(candidate.browser_name().to_lowercase());
+                                break;
+                            }
+                            Some(reason) => reasons.push(format!("{}: {}", candidate.browser_name(), reason)),
+                        }
+                    }
+                    if reasons.len() == 3 {
+                        return Err(WebDriverError::Execution(format!(
+                            "No supported browsers available ({})", reasons.join("; ")
+                        )));
                     }
                 }
                 "chrome" | "firefox" | "edge" => {
-                    if self.is_browser_available(browser) {
-                        resolved_browsers.push(browser.clone());
-                    } else {
-                        return Err(WebDriverError::Execution(format!("Browser {} is not available", browser)));
+                    let driver_type = DriverType::from_string(browser)
+                        .ok_or_else(|| WebDriverError::Execution(format!("Unsupported browser: {}", browser)))?;
+                    if let Some(reason) = client_manager.browser_unavailable_reason(&driver_type).await {
+                        let message = format!("Browser {} is not available: {}", browser, reason);
+                        return Err(WebDriverError::Execution(message));
                     }
+                    resolved_browsers.push(browser.clone());
                 }
                 _ => return Err(WebDriverError::Execution(format!("Unsupported browser: {}", browser))),
             }
@@ -93,12 +158,6 @@ impl<'a> RecipeExecutor<'a> {
         Ok(resolved_browsers)
     }
 
-    fn is_browser_available(&self, _browser: &str) -> bool {
-        // For now, assume Chrome is always available
-        // In a real implementation, this would check system availability
-        true
-    }
-
     pub async fn execute_recipe(
         &self,
         recipe: &Recipe,
@@ -106,22 +165,20 @@ impl<'a> RecipeExecutor<'a> {
         context: ExecutionContext,
     ) -> Result<ExecutionResult, WebDriverError> {
         let start_time = std::time::Instant::now();
+        let started_at = chrono::Utc::now();
         
-        // Substitute parameters if provided
-        let final_recipe = if let Some(params) = parameters {
-            recipe.substitute_parameters(&params)
-                .map_err(|e| WebDriverError::InvalidRecipe(format!("Parameter substitution failed: {}", e)))?
-        } else {
-            recipe.clone()
-        };
+        // Apply declared defaults, enforce the parameter schema and substitute
+        let params = recipe.resolve_parameters(&parameters.unwrap_or_default())
+            .map_err(|e| WebDriverError::InvalidRecipe(format!("Invalid parameters: {}", e)))?;
+        recipe.check_variables(&context.variables)
+            .map_err(|e| WebDriverError::InvalidRecipe(format!("Invalid variables: {}", e)))?;
+        let final_recipe = recipe.substitute_parameters(&params)
+            .map_err(|e| WebDriverError::InvalidRecipe(format!("Parameter substitution failed: {}", e)))?;
 
         // Validate the final recipe
         final_recipe.validate()
             .map_err(|e| WebDriverError::InvalidRecipe(format!("Recipe validation failed: {}", e)))?;
 
-        // Resolve browsers for execution
-        let browsers = self.resolve_browsers(&final_recipe.browsers)?;
-        
         // CRITICAL FIX: Refresh driver health before recipe execution
         // This ensures that running drivers are properly registered in healthy_endpoints
         tracing::debug!("🔄 Refreshing driver health before recipe execution");
@@ -132,6 +189,9 @@ impl<'a> RecipeExecutor<'a> {
             let healthy_count = driver_manager.get_healthy_endpoints().await.len();
             tracing::info!("✅ Health check completed: {} healthy endpoints found", healthy_count);
         }
+
+        // Resolve browsers for execution, against the drivers found healthy just now
+        let browsers = self.resolve_browsers(&final_recipe.browsers).await?;
         
         let mut browser_results = HashMap::new();
         let mut all_step_results = Vec::new();
@@ -139,12 +199,20 @@ impl<'a> RecipeExecutor<'a> {
         let mut total_failed_steps = 0;
         let mut overall_success = true;
         let mut global_error_message = None;
+        let mut max_attempts = 0;
+        let mut all_on_failure_results = Vec::new();
 
         // Execute recipe for each browser sequentially
         for browser in browsers {
+            if self.cancellation.is_cancelled() {
+                overall_success = false;
+                global_error_message.get_or_insert_with(|| "Recipe execution cancelled".to_string());
+                break;
+            }
+
             tracing::info!("🌐 Executing recipe for browser: {}", browser);
             
-            let browser_result = self.execute_recipe_for_browser(
+            let browser_result = self.execute_recipe_for_browser_with_retry(
                 &final_recipe, 
                 &browser, 
                 &context
@@ -154,6 +222,8 @@ impl<'a> RecipeExecutor<'a> {
                 Ok(result) => {
                     total_executed_steps += result.executed_steps;
                     total_failed_steps += result.failed_steps;
+                    max_attempts = max_attempts.max(result.attempts);
+                    all_on_failure_results.extend(result.on_failure_results.clone());
                     
                     if !result.success {
                         overall_success = false;
@@ -177,6 +247,8 @@ impl<'a> RecipeExecutor<'a> {
                         step_results: result.step_results,
                         execution_time_ms: result.execution_time_ms,
                         error_message: result.error_message,
+                        attempts: result.attempts,
+                        on_failure_results: result.on_failure_results,
                     });
                 }
                 Err(e) => {
@@ -194,6 +266,8 @@ impl<'a> RecipeExecutor<'a> {
                         step_results: Vec::new(),
                         execution_time_ms: 0,
                         error_message: Some(error_msg),
+                        attempts: 1,
+                        on_failure_results: Vec::new(),
                     });
                 }
             }
@@ -201,7 +275,7 @@ impl<'a> RecipeExecutor<'a> {
 
         let total_time = start_time.elapsed().as_millis() as u64;
 
-        Ok(ExecutionResult {
+        let mut result = ExecutionResult {
             success: overall_success,
             total_steps: final_recipe.steps.len(),
             executed_steps: total_executed_steps,
@@ -210,7 +284,251 @@ impl<'a> RecipeExecutor<'a> {
             execution_time_ms: total_time,
             error_message: global_error_message,
             browser_results,
-        })
+            attempts: max_attempts.max(1),
+            on_failure_results: all_on_failure_results,
+            report_path: None,
+        };
+
+        if let Some(format) = self.report {
+            match self.write_report(format, &final_recipe, &result, started_at).await {
+                Ok(path) => result.report_path = Some(path),
+                Err(e) => tracing::warn!("Failed to write report of recipe '{}': {}", final_recipe.name, e),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Render the run's report next to its screenshots and publish it as a resource
+    async fn write_report(
+        &self,
+        format: ReportFormat,
+        recipe: &Recipe,
+        result: &ExecutionResult,
+        started_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<PathBuf, WebDriverError> {
+        let client_manager = self.server.get_client_manager();
+        let config = client_manager.get_config();
+        let artifacts = client_manager.get_artifact_store();
+
+        // Images this run saved, linked relative to the report in the same directory
+        let scope_prefix = format!("{}/{}/", artifacts.run_id(), self.artifact_scope);
+        let base_dir = artifacts.base_dir();
+        let screenshots = artifacts
+            .list(Some(artifacts.run_id()))
+            .await?
+            .into_iter()
+            .filter_map(|info| {
+                let link = info.path.strip_prefix(&scope_prefix)?.to_string();
+                let image = [".png", ".jpg", ".jpeg", ".webp"].iter().any(|ext| link.to_lowercase().ends_with(ext));
+                image.then(|| ReportScreenshot {
+                    saved_as: base_dir.join(&info.path).display().to_string(),
+                    link,
+                })
+            })
+            .rev()
+            .collect();
+
+        let driver_versions = client_manager
+            .get_driver_manager()
+            .endpoint_registry()
+            .entries()
+            .into_iter()
+            .filter_map(|entry| entry.version.map(|version| (entry.browser, version)))
+            .collect();
+
+        let report = RunReport {
+            recipe,
+            result,
+            environment: ReportEnvironment {
+                started_at,
+                server_version: env!("CARGO_PKG_VERSION"),
+                platform: format!("{}/{}", std::env::consts::OS, std::env::consts::ARCH),
+                headless: config.headless,
+                driver_versions,
+            },
+            console_errors: self.console_errors.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+            screenshots,
+        };
+
+        let path = artifacts
+            .write(Some(&self.artifact_scope), format.file_name(), report.render(format).into_bytes())
+            .await?;
+        let description = format!("Report of recipe '{}'", recipe.name);
+        client_manager.get_resource_store().add_file(ResourceKind::Artifact, &path, description);
+        tracing::info!("📝 Wrote report of recipe '{}' to {}", recipe.name, path.display());
+        Ok(path)
+    }
+
+    /// Run the recipe for a single browser, honouring the recipe-level retry policy.
+    /// `on_failure` hooks run after every failed attempt so the environment can recover
+    /// before the next one.
+    async fn execute_recipe_for_browser_with_retry(
+        &self,
+        recipe: &Recipe,
+        browser: &str,
+        context: &ExecutionContext,
+    ) -> Result<ExecutionResult, WebDriverError> {
+        let max_retries = recipe.retry.as_ref().map_or(0, |retry| retry.count);
+        let mut on_failure_results = Vec::new();
+        let mut attempt = 0;
+
+        let finish = |outcome: Result<ExecutionResult, WebDriverError>, attempts: u32, hooks: Vec<StepResult>| {
+            outcome.map(|mut result| {
+                result.attempts = attempts;
+                result.on_failure_results = hooks;
+                result
+            })
+        };
+
+        loop {
+            let (outcome, hook_results) = self.execute_attempt(recipe, browser, context).await;
+            let failed = !outcome.as_ref().is_ok_and(|result| result.success);
+            on_failure_results.extend(hook_results);
+
+            if !failed || attempt >= max_retries || self.cancellation.is_cancelled() {
+                return finish(outcome, attempt + 1, on_failure_results);
+            }
+
+            attempt += 1;
+            let delay = recipe.retry.as_ref()
+                .map(|retry| retry.delay_for_attempt(attempt))
+                .unwrap_or_default();
+            tracing::info!("🔁 Retrying recipe '{}' for {} (attempt {}/{}) in {}ms",
+                recipe.name, browser, attempt + 1, max_retries + 1, delay.as_millis());
+            if self.cancellation.run_until_cancelled(tokio::time::sleep(delay)).await.is_none() {
+                return finish(outcome, attempt, on_failure_results);
+            }
+        }
+    }
+
+    /// Run a single attempt of the recipe for one browser, followed by the `on_failure`
+    /// hooks if it failed. With `isolation` set, the attempt runs in a brand-new session
+    /// that is closed afterwards instead of the shared `<browser>_recipe_session`.
+    async fn execute_attempt(
+        &self,
+        recipe: &Recipe,
+        browser: &str,
+        context: &ExecutionContext,
+    ) -> (Result<ExecutionResult, WebDriverError>, Vec<StepResult>) {
+        let mut browser_context = context.clone();
+        browser_context.variables.insert("browser".to_string(), browser.to_string());
+        browser_context.session_id = Some(format!("{}_recipe_session", browser));
+
+        let isolated_session = match &recipe.isolation {
+            Some(isolation) => {
+                let client_manager = self.server.get_client_manager();
+                match IsolatedSession::create(client_manager, browser, isolation).await {
+                    Ok(session) => {
+                        browser_context.session_id = Some(session.session_id.clone());
+                        Some(session)
+                    }
+                    Err(e) => return (Err(e), Vec::new()),
+                }
+            }
+            None => None,
+        };
+
+        let session = browser_context.session_id.clone().unwrap_or_default();
+        let console_mark = self.console_mark(&session).await;
+
+        let outcome = self.execute_recipe_for_browser(recipe, browser, &browser_context).await;
+        let failed = !outcome.as_ref().is_ok_and(|result| result.success);
+
+        let hook_results = if failed && !recipe.on_failure.is_empty() && !self.cancellation.is_cancelled() {
+            self.run_on_failure_hooks(recipe, browser, &browser_context).await
+        } else {
+            Vec::new()
+        };
+
+        if let Some(mark) = console_mark {
+            self.collect_console_errors(browser, &session, mark).await;
+        }
+
+        if let Some(session) = isolated_session {
+            session.close().await;
+        }
+
+        (outcome, hook_results)
+    }
+
+    /// Console watermark of `session` before an attempt, when the run is reported.
+    /// Output the current page recorded earlier is drained first so it stays out.
+    async fn console_mark(&self, session: &str) -> Option<u64> {
+        self.report?;
+        let client_manager = self.server.get_client_manager();
+        if let Ok((_, client)) = client_manager.get_existing_client(Some(session.to_string())).await {
+            let _ = client_manager.drain_console_logs(session, &client).await;
+        }
+        Some(client_manager.console_high_watermark(session))
+    }
+
+    /// Keep the console errors `session` logged after `mark` for the report
+    async fn collect_console_errors(&self, browser: &str, session: &str, mark: u64) {
+        let client_manager = self.server.get_client_manager();
+        if let Ok((_, client)) = client_manager.get_existing_client(Some(session.to_string())).await
+            && let Err(e) = client_manager.drain_console_logs(session, &client).await
+        {
+            tracing::debug!("Failed to read console logs of session '{}': {}", session, e);
+        }
+        let errors = client_manager
+            .console_logs(session)
+            .into_iter()
+            .filter(|entry| console::entry_seq(entry) > mark && entry["level"] == "error");
+        let mut console_errors = self.console_errors.lock().unwrap_or_else(|e| e.into_inner());
+        console_errors.entry(browser.to_string()).or_default().extend(errors);
+    }
+
+    /// Run the recipe's `on_failure` hooks. Hooks are best-effort: a failing hook is
+    /// recorded but does not stop the remaining hooks.
+    async fn run_on_failure_hooks(
+        &self,
+        recipe: &Recipe,
+        browser: &str,
+        browser_context: &ExecutionContext,
+    ) -> Vec<StepResult> {
+        let mut results = Vec::new();
+        for (index, hook) in recipe.on_failure.iter().enumerate() {
+            if hook.browser.as_deref().is_some_and(|b| b != browser) {
+                continue;
+            }
+            let resolved_hook = hook.for_browser(browser);
+            let hook = resolved_hook.as_ref();
+
+            tracing::info!("🧯 Running on_failure hook {} ({}) for {}", index + 1, hook.action, browser);
+            let hook_start_time = std::time::Instant::now();
+            let mut hook_context = browser_context.clone();
+            hook_context.variables.insert("step".to_string(), format!("on_failure_{}", index + 1));
+            let outcome = if hook.action == "run_recipe" {
+                self.execute_run_recipe(&hook.arguments, browser, &hook_context).await
+            } else {
+                self.execute_single_step(hook, &hook_context).await
+            };
+
+            let (success, result, error_message) = match outcome {
+                Ok(result) => (true, Some(result), None),
+                Err(e) => {
+                    tracing::warn!("on_failure hook {} ({}) failed: {}", index + 1, hook.action, e);
+                    (false, None, Some(e.to_string()))
+                }
+            };
+
+            results.push(StepResult {
+                step_index: index,
+                step_name: hook.name.clone(),
+                action: hook.action.clone(),
+                success,
+                execution_time_ms: hook_start_time.elapsed().as_millis() as u64,
+                attempts: 1,
+                result,
+                error_message,
+                skipped: false,
+                skipped_reason: None,
+                browser: Some(browser.to_string()),
+            });
+        }
+
+        results
     }
 
     async fn execute_recipe_for_browser(
@@ -220,11 +538,8 @@ impl<'a> RecipeExecutor<'a> {
         context: &ExecutionContext,
     ) -> Result<ExecutionResult, WebDriverError> {
         let start_time = std::time::Instant::now();
-        
-        // Create browser-specific session
-        let browser_session_id = format!("{}_recipe_session", browser);
+        // Variables stored by `execute_script` steps live for this run only
         let mut browser_context = context.clone();
-        browser_context.session_id = Some(browser_session_id);
 
         let mut step_results = Vec::new();
         let mut executed_steps = 0;
@@ -234,6 +549,12 @@ impl<'a> RecipeExecutor<'a> {
 
         // Execute each step
         for (index, step) in recipe.steps.iter().enumerate() {
+            if self.cancellation.is_cancelled() {
+                execution_failed = true;
+                error_message = Some(format!("Recipe execution cancelled before step {}", index + 1));
+                break;
+            }
+
             let step_start_time = std::time::Instant::now();
             
             // Determine which browser to use for this step
@@ -255,6 +576,10 @@ impl<'a> RecipeExecutor<'a> {
                 });
                 continue;
             }
+
+            // Apply the step's arguments for this browser, if it has any
+            let resolved_step = step.for_browser(browser);
+            let step = resolved_step.as_ref();
             
             // Check if step should be skipped based on condition
             if let Some(condition) = &step.condition {
@@ -305,12 +630,19 @@ impl<'a> RecipeExecutor<'a> {
             }
 
             // Execute the step with retries
+            browser_context.variables.insert("step".to_string(), (index + 1).to_string());
             let step_result = self.execute_step_with_retries(step, &browser_context, index).await;
             let step_duration = step_start_time.elapsed().as_millis() as u64;
             
             executed_steps += 1;
             
             if step_result.success {
+                if step.action == "execute_script"
+                    && let Some(name) = step.arguments.get("store_as").and_then(|v| v.as_str())
+                    && let Some(result) = &step_result.result
+                {
+                    browser_context.variables.insert(name.to_string(), recipe::variable_from_json(result));
+                }
                 step_results.push(StepResult {
                     step_index: index,
                     step_name: step.name.clone(),
@@ -361,6 +693,9 @@ impl<'a> RecipeExecutor<'a> {
             execution_time_ms: total_time,
             error_message,
             browser_results: HashMap::new(), // Empty for single browser execution
+            attempts: 1,
+            on_failure_results: Vec::new(),
+            report_path: None,
         })
     }
 
@@ -378,10 +713,16 @@ impl<'a> RecipeExecutor<'a> {
         for attempt in 0..=max_retries {
             if attempt > 0 {
                 tracing::debug!("Retrying step {} (attempt {}/{})", step_index + 1, attempt + 1, max_retries + 1);
-                tokio::time::sleep(retry_delay).await;
+                if self.cancellation.run_until_cancelled(tokio::time::sleep(retry_delay)).await.is_none() {
+                    return StepExecutionResult::cancelled(attempt);
+                }
             }
 
-            match self.execute_single_step(step, context).await {
+            let Some(outcome) = self.cancellation.run_until_cancelled(self.execute_single_step(step, context)).await else {
+                return StepExecutionResult::cancelled(attempt + 1);
+            };
+
+            match outcome {
                 Ok(result) => {
                     return StepExecutionResult {
                         success: true,
@@ -425,27 +766,30 @@ impl<'a> RecipeExecutor<'a> {
             arguments.insert("session_id".to_string(), Value::String(sid.clone()));
         }
 
-        // Substitute browser placeholder in arguments
-        if let Some(session_id) = &context.session_id {
-            if let Some(browser) = session_id.strip_suffix("_recipe_session") {
-                // Replace {{browser}} placeholder in arguments
-                let args_str = serde_json::to_string(&arguments)
-                    .map_err(|e| WebDriverError::Execution(format!("Failed to serialize arguments: {}", e)))?;
-                let substituted_str = args_str.replace("{{browser}}", browser);
-                arguments = serde_json::from_str(&substituted_str)
-                    .map_err(|e| WebDriverError::Execution(format!("Failed to deserialize substituted arguments: {}", e)))?;
-            }
+        // Substitute {{browser}} and variables stored by earlier steps
+        let mut variables = context.variables.clone();
+        if !variables.contains_key("browser")
+            && let Some(browser) = context.session_id.as_deref().and_then(|sid| sid.strip_suffix("_recipe_session"))
+        {
+            variables.insert("browser".to_string(), browser.to_string());
+        }
+        for value in arguments.values_mut() {
+            recipe::substitute_runtime_variables(value, &variables);
         }
 
         tracing::debug!("📋 Step arguments: {:?}", arguments);
         tracing::debug!("🆔 Using session_id: {:?}", session_id);
 
+        if let Err(e) = crate::auth::check_tool_allowed(self.server.get_client_manager().get_config(), &step.action) {
+            return Err(WebDriverError::Execution(format!("Action {e}")));
+        }
+
         // Execute the actual WebDriver tool based on the action
         let result = match step.action.as_str() {
             "navigate" => self.execute_navigate(&arguments).await,
             "click" => self.execute_click(&arguments).await,
             "send_keys" => self.execute_send_keys(&arguments).await,
-            "screenshot" => self.execute_screenshot(&arguments).await,
+            "screenshot" => self.execute_screenshot(&arguments, &variables).await,
             "get_title" => self.execute_get_title(&arguments).await,
             "get_text" => self.execute_get_text(&arguments).await,
             "wait_for_element" => self.execute_wait_for_element(&arguments).await,
@@ -463,6 +807,8 @@ impl<'a> RecipeExecutor<'a> {
             "get_attribute" => self.execute_get_attribute(&arguments).await,
             "get_property" => self.execute_get_property(&arguments).await,
             "fill_and_submit_form" => self.execute_fill_and_submit_form(&arguments).await,
+            "audit_accessibility" => self.execute_audit_accessibility(&arguments).await,
+            "check_localization" => self.execute_check_localization(&arguments).await,
             _ => Err(WebDriverError::Execution(format!("Unknown action: {}", step.action))),
         };
 
@@ -494,6 +840,67 @@ impl<'a> RecipeExecutor<'a> {
     }
 }
 
+/// A browser session dedicated to one recipe attempt. It is closed by `close`, or in
+/// the background if the attempt is dropped before reaching it.
+struct IsolatedSession {
+    client_manager: ClientManager,
+    session_id: String,
+    closed: bool,
+}
+
+impl IsolatedSession {
+    async fn create(
+        client_manager: &ClientManager,
+        browser: &str,
+        isolation: &SessionIsolation,
+    ) -> Result<Self, WebDriverError> {
+        let session_id = format!("{}_recipe_isolated_{}", browser, uuid::Uuid::new_v4().simple());
+        let options = SessionOptions {
+            headless: isolation.headless,
+            private: isolation.incognito,
+            ..SessionOptions::default()
+        };
+
+        client_manager.create_isolated_session(&session_id, &options).await
+            .map_err(|e| WebDriverError::Execution(format!("Failed to create isolated {} session: {}", browser, e)))?;
+        tracing::info!("🧪 Created isolated session '{}'", session_id);
+
+        Ok(Self {
+            client_manager: client_manager.clone(),
+            session_id,
+            closed: false,
+        })
+    }
+
+    async fn close(mut self) {
+        self.closed = true;
+        match self.client_manager.close_session(&self.session_id).await {
+            Ok(()) => tracing::info!("🧹 Closed isolated session '{}'", self.session_id),
+            Err(e) => tracing::warn!("Failed to close isolated session '{}': {}", self.session_id, e),
+        }
+    }
+}
+
+impl Drop for IsolatedSession {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+
+        let client_manager = self.client_manager.clone();
+        let session_id = std::mem::take(&mut self.session_id);
+        // The spawned task does not inherit the caller's principal, so carry it over
+        let principal = ClientManager::current_principal();
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(ClientManager::with_principal(principal, async move {
+                if let Err(e) = client_manager.close_session(&session_id).await {
+                    tracing::warn!("Failed to close isolated session '{}': {}", session_id, e);
+                }
+            }));
+        }
+    }
+}
+
 #[derive(Debug)]
 struct StepExecutionResult {
     success: bool,
@@ -502,9 +909,33 @@ struct StepExecutionResult {
     error_message: Option<String>,
 }
 
+impl StepExecutionResult {
+    fn cancelled(attempts: u32) -> Self {
+        Self {
+            success: false,
+            attempts,
+            result: None,
+            error_message: Some("Step cancelled".to_string()),
+        }
+    }
+}
+
+impl StepResult {
+    /// `SKIPPED`, `SUCCESS` or `FAILED`
+    pub fn status_label(&self) -> &'static str {
+        if self.skipped {
+            "SKIPPED"
+        } else if self.success {
+            "SUCCESS"
+        } else {
+            "FAILED"
+        }
+    }
+}
+
 impl ExecutionResult {
     pub fn to_summary_string(&self) -> String {
-        if self.success {
+        let summary = if self.success {
             format!(
                 "Recipe executed successfully! {} steps completed in {}ms",
                 self.executed_steps,
@@ -519,6 +950,10 @@ impl ExecutionResult {
                 self.execution_time_ms,
                 self.error_message.as_deref().unwrap_or("Unknown error")
             )
+        };
+        match &self.report_path {
+            Some(path) => format!("{}\nReport: {}", summary, path.display()),
+            None => summary,
         }
     }
 
@@ -527,13 +962,7 @@ impl ExecutionResult {
         result.push_str("\n\nStep Results:\n");
         
         for step_result in &self.step_results {
-            let status = if step_result.skipped {
-                "SKIPPED"
-            } else if step_result.success {
-                "SUCCESS"
-            } else {
-                "FAILED"
-            };
+            let status = step_result.status_label();
             
             let default_name = format!("Step {}", step_result.step_index + 1);
             let step_name = step_result.step_name.as_deref()
@@ -557,6 +986,25 @@ impl ExecutionResult {
             
             result.push('\n');
         }
+
+        if !self.on_failure_results.is_empty() {
+            result.push_str("\nOn-failure Hooks:\n");
+            for hook_result in &self.on_failure_results {
+                let status = if hook_result.success { "SUCCESS" } else { "FAILED" };
+                result.push_str(&format!(
+                    "  {} - {} [{}] ({}): {}ms",
+                    hook_result.step_index + 1,
+                    hook_result.action,
+                    hook_result.browser.as_deref().unwrap_or("unknown"),
+                    status,
+                    hook_result.execution_time_ms
+                ));
+                if let Some(error) = &hook_result.error_message {
+                    result.push_str(&format!(" - Error: {}", error));
+                }
+                result.push('\n');
+            }
+        }
         
         result
     }
@@ -569,13 +1017,16 @@ impl<'a> RecipeExecutor<'a> {
             .and_then(|v| v.as_str())
             .ok_or_else(|| WebDriverError::Execution("Missing 'url' parameter for navigate".to_string()))?;
         
+        let client_manager = self.server.get_client_manager();
+        // Spaces navigations per origin when the site pool is on
         let session_id = arguments.get("session_id")
             .and_then(|v| v.as_str())
-            .unwrap_or("default");
+            .map(str::to_string);
+        let session_id = client_manager.route_navigation(session_id, url).await
+            .unwrap_or_else(|| "default".to_string());
+        let session_id = session_id.as_str();
 
         tracing::debug!("🌐 Navigating to URL: {} with session_id: {}", url, session_id);
-
-        let client_manager = self.server.get_client_manager();
         tracing::debug!("📞 Getting WebDriver client from client manager");
         
         let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
@@ -584,21 +1035,80 @@ impl<'a> RecipeExecutor<'a> {
                 WebDriverError::Execution(format!("Failed to get client for navigation session '{}': {}", session_id, e))
             })?;
 
+        // Keep the console output of the page being left, for run reports
+        if self.report.is_some() {
+            let _ = client_manager.drain_console_logs(session_id, &client).await;
+        }
+
         tracing::debug!("🚀 Calling client.goto() with URL: {}", url);
-        client.goto(url).await
+        client_manager.navigate(session_id, &client, url).await
             .map_err(|e| {
                 tracing::error!("❌ Navigation to '{}' failed for session '{}': {}", url, session_id, e);
                 WebDriverError::Execution(format!("Navigation to '{}' failed for session '{}': {}", url, session_id, e))
             })?;
 
+        if self.report.is_some()
+            && let Err(e) = navigation::setup_console_monitoring(client_manager, session_id, &client).await
+        {
+            tracing::debug!("Failed to monitor console of session '{}': {}", session_id, e);
+        }
+
         tracing::debug!("✅ Navigation completed successfully");
         Ok(format!("Successfully navigated to {}", url))
     }
 
-    async fn execute_screenshot(&self, arguments: &serde_json::Map<String, Value>) -> Result<String, WebDriverError> {
-        let save_path = arguments.get("save_path")
-            .and_then(|v| v.as_str());
-            
+    async fn execute_run_recipe(
+        &self,
+        arguments: &serde_json::Map<String, Value>,
+        browser: &str,
+        context: &ExecutionContext,
+    ) -> Result<String, WebDriverError> {
+        let name = arguments.get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| WebDriverError::Execution("Missing 'name' parameter for run_recipe".to_string()))?;
+
+        let recipe = self.server.get_recipe_manager().load_recipe(name).await?;
+
+        let provided: HashMap<String, String> = arguments.get("parameters")
+            .and_then(|v| v.as_object())
+            .map(|params_obj| {
+                params_obj.iter()
+                    .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let params = recipe.resolve_parameters(&provided)
+            .map_err(|e| WebDriverError::InvalidRecipe(format!("Invalid parameters: {}", e)))?;
+        let recipe = recipe.substitute_parameters(&params)
+            .map_err(|e| WebDriverError::InvalidRecipe(format!("Parameter substitution failed: {}", e)))?;
+
+        tracing::debug!("🧹 Running cleanup recipe '{}' for {}", name, browser);
+        let result = self.execute_recipe_for_browser(&recipe, browser, context).await?;
+
+        if result.success {
+            Ok(format!("Recipe '{}' completed ({} steps)", name, result.executed_steps))
+        } else {
+            Err(WebDriverError::Execution(format!(
+                "Recipe '{}' failed: {}",
+                name,
+                result.error_message.as_deref().unwrap_or("Unknown error")
+            )))
+        }
+    }
+
+    /// Capture the viewport, the whole page (`full_page`) or one element (`selector`).
+    /// `save` without a `save_path`, or a `save_path` ending in `/`, saves under a
+    /// generated name that tells steps, browsers and runs apart.
+    async fn execute_screenshot(
+        &self,
+        arguments: &serde_json::Map<String, Value>,
+        variables: &HashMap<String, String>,
+    ) -> Result<String, WebDriverError> {
+        let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%3f").to_string();
+        let save_path = recipe::screenshot_file_name(arguments, variables, &timestamp);
+        let full_page = arguments.get("full_page").and_then(|v| v.as_bool()).unwrap_or(false);
+        let selector = arguments.get("selector").and_then(|v| v.as_str());
+
         let session_id = arguments.get("session_id")
             .and_then(|v| v.as_str())
             .unwrap_or("default");
@@ -614,24 +1124,40 @@ impl<'a> RecipeExecutor<'a> {
                 WebDriverError::Execution(format!("Failed to get client for screenshot session '{}': {}", session_id, e))
             })?;
 
-        tracing::debug!("📷 Calling client.screenshot()");
-        let screenshot_data = client.screenshot().await
-            .map_err(|e| {
-                tracing::error!("❌ Screenshot capture failed for session '{}': {}", session_id, e);
-                WebDriverError::Execution(format!("Screenshot capture failed for session '{}': {}", session_id, e))
-            })?;
+        let captured = match selector {
+            Some(selector) => {
+                let wait_timeout = arguments.get("wait_timeout").and_then(|v| v.as_f64());
+                let element = client_manager.find_element_with_wait(&client, selector, wait_timeout).await
+                    .map_err(|e| WebDriverError::Execution(format!("Failed to find element '{}': {}", selector, e)))?;
+                client_manager.run_command(session_id, "screenshot", element.screenshot()).await
+            }
+            None if full_page => screenshots::full_page(client_manager, session_id, &client).await,
+            None => client_manager.run_command(session_id, "screenshot", client.screenshot()).await,
+        };
+        let screenshot_data = captured.map_err(|e| {
+            tracing::error!("❌ Screenshot capture failed for session '{}': {}", session_id, e);
+            WebDriverError::Execution(format!("Screenshot capture failed for session '{}': {}", session_id, e))
+        })?;
 
         tracing::debug!("📊 Screenshot data received: {} bytes", screenshot_data.len());
 
-        if let Some(path) = save_path {
+        let resources = client_manager.get_resource_store();
+        let description = format!("Recipe screenshot of session '{}'", session_id);
+
+        if let Some(name) = save_path {
             // screenshot_data is already PNG binary data, no need to decode from base64
-            tracing::debug!("💾 Saving screenshot to: {}", path);
-            std::fs::write(path, &screenshot_data)
+            tracing::debug!("💾 Saving screenshot as: {}", name);
+            let path = client_manager.get_artifact_store()
+                .write(Some(&self.artifact_scope), &name, screenshot_data.clone())
+                .await
                 .map_err(|e| WebDriverError::Execution(format!("Failed to save screenshot: {}", e)))?;
-            
-            Ok(format!("Screenshot saved to: {} ({} bytes)", path, screenshot_data.len()))
+
+            let uri = resources.add_file(ResourceKind::Artifact, &path, description);
+            Ok(format!("Screenshot saved to: {} ({} bytes, resource: {})", path.display(), screenshot_data.len(), uri))
         } else {
-            Ok(format!("Screenshot captured ({} bytes)", screenshot_data.len()))
+            let size = screenshot_data.len();
+            let uri = resources.add_bytes(ResourceKind::Artifact, "screenshot.png", description, screenshot_data);
+            Ok(format!("Screenshot captured ({} bytes, resource: {})", size, uri))
         }
     }
 
@@ -640,22 +1166,22 @@ impl<'a> RecipeExecutor<'a> {
             .and_then(|v| v.as_str())
             .ok_or_else(|| WebDriverError::Execution("Missing 'condition' parameter".to_string()))?;
             
-        let timeout_seconds = arguments.get("timeout_seconds")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(10);
-            
         let session_id = arguments.get("session_id")
             .and_then(|v| v.as_str())
             .unwrap_or("default");
 
         let client_manager = self.server.get_client_manager();
+        let timeout_seconds = arguments.get("timeout_seconds")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(client_manager.get_config().default_wait_timeout_secs);
+        let timeout_duration = crate::waits::wait_duration("timeout_seconds", timeout_seconds)
+            .map_err(WebDriverError::InvalidRecipe)?;
         let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
         // For document.readyState === 'complete', use a direct approach
         if condition == "document.readyState === 'complete'" {
             let start_time = std::time::Instant::now();
-            let timeout_duration = Duration::from_secs(timeout_seconds);
             
             loop {
                 if start_time.elapsed() > timeout_duration {
@@ -675,7 +1201,7 @@ impl<'a> RecipeExecutor<'a> {
             }
         } else if condition == "true" {
             // Simple delay for "true" condition
-            tokio::time::sleep(Duration::from_secs(timeout_seconds)).await;
+            tokio::time::sleep(timeout_duration).await;
             Ok(format!("Waited {} seconds", timeout_seconds))
         } else {
             // For other conditions, try to evaluate the JavaScript
@@ -700,7 +1226,7 @@ impl<'a> RecipeExecutor<'a> {
             .unwrap_or("default");
 
         let client_manager = self.server.get_client_manager();
-        let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
+        let (session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
         // Try common username field selectors
@@ -754,6 +1280,10 @@ impl<'a> RecipeExecutor<'a> {
         password_el.send_keys(password).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to enter password: {}", e)))?;
 
+        let start_url = client.current_url().await
+            .map_err(|e| WebDriverError::Execution(format!("Failed to read the login page URL: {}", e)))?
+            .to_string();
+
         // Try to find and click submit button
         let submit_selectors = vec![
             "button[type='submit']",
@@ -781,7 +1311,19 @@ impl<'a> RecipeExecutor<'a> {
                 .map_err(|e| WebDriverError::Execution(format!("Failed to submit form with Enter: {}", e)))?;
         }
 
-        Ok("Login form submitted successfully".to_string())
+        if !arguments.get("verify").and_then(|v| v.as_bool()).unwrap_or(true) {
+            return Ok("Login form submitted successfully".to_string());
+        }
+        let check = LoginCheck::from_arguments(arguments);
+        let result = login::await_login_result(client_manager, &session, &client, &start_url, &check).await;
+        match result.outcome {
+            LoginOutcome::Failed => Err(WebDriverError::Execution(format!(
+                "Login failed: {} (URL: {})", result.reason, result.url
+            ))),
+            LoginOutcome::Success | LoginOutcome::Unverified => Ok(format!(
+                "Login form submitted, login {}: {} (URL: {})", result.outcome.as_str(), result.reason, result.url
+            )),
+        }
     }
 
     async fn execute_click(&self, arguments: &serde_json::Map<String, Value>) -> Result<String, WebDriverError> {
@@ -797,11 +1339,12 @@ impl<'a> RecipeExecutor<'a> {
             .unwrap_or("default");
 
         let client_manager = self.server.get_client_manager();
-        let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
+        let (session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
         let element = client_manager.find_element_with_wait(&client, selector, wait_timeout).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to find element '{}': {}", selector, e)))?;
+        self.wait_until_stable(client_manager, &session, &client, &element, selector, arguments).await?;
 
         element.click().await
             .map_err(|e| WebDriverError::Execution(format!("Failed to click element: {}", e)))?;
@@ -826,18 +1369,53 @@ impl<'a> RecipeExecutor<'a> {
             .unwrap_or("default");
 
         let client_manager = self.server.get_client_manager();
-        let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
+        let (session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
         let element = client_manager.find_element_with_wait(&client, selector, wait_timeout).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to find element '{}': {}", selector, e)))?;
+        self.wait_until_stable(client_manager, &session, &client, &element, selector, arguments).await?;
+
+        if arguments.get("clear_first").and_then(|v| v.as_bool()).unwrap_or(false) {
+            element.clear().await
+                .map_err(|e| WebDriverError::Execution(format!("Failed to clear element '{}': {}", selector, e)))?;
+        }
 
-        element.send_keys(text).await
+        typing::type_into(&element, text, TypingOptions::from_arguments(arguments)).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to send keys: {}", e)))?;
 
         Ok(format!("Successfully sent keys to element '{}'", selector))
     }
 
+    /// Wait for a step's target to hold still, unless the step or the server turns it off
+    async fn wait_until_stable(
+        &self,
+        client_manager: &ClientManager,
+        session: &str,
+        client: &fantoccini::Client,
+        element: &fantoccini::elements::Element,
+        selector: &str,
+        arguments: &serde_json::Map<String, Value>,
+    ) -> Result<(), WebDriverError> {
+        let config = client_manager.get_config();
+        let wait = arguments.get("wait_for_stable")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(config.wait_for_stable_elements);
+        if !wait {
+            return Ok(());
+        }
+        let timeout = Duration::from_millis(config.element_stability_timeout_ms);
+        match stability::wait_until_stable(client_manager, session, client, element, timeout).await? {
+            None => Ok(()),
+            Some(reason) => Err(WebDriverError::Execution(format!(
+                "Element '{}' did not settle within {}ms: {}",
+                selector,
+                timeout.as_millis(),
+                reason
+            ))),
+        }
+    }
+
     async fn execute_get_title(&self, arguments: &serde_json::Map<String, Value>) -> Result<String, WebDriverError> {
         let session_id = arguments.get("session_id")
             .and_then(|v| v.as_str())
@@ -847,12 +1425,70 @@ impl<'a> RecipeExecutor<'a> {
         let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
-        let title = client.title().await
+        let title = client_manager.run_command(session_id, "get_title", client.title()).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get title: {}", e)))?;
 
         Ok(format!("Page title: {}", title))
     }
 
+    /// Fails the step when `fail_on` is set and a violation reaches that impact
+    async fn execute_audit_accessibility(&self, arguments: &serde_json::Map<String, Value>) -> Result<String, WebDriverError> {
+        let session_id = arguments.get("session_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("default");
+        let scope = arguments.get("selector").and_then(|v| v.as_str());
+        let rules: Vec<String> = arguments.get("rules")
+            .and_then(|v| v.as_array())
+            .map(|rules| rules.iter().filter_map(|r| r.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+        let fail_on = match arguments.get("fail_on").and_then(|v| v.as_str()) {
+            Some(name) => Some(Impact::from_name(name).ok_or_else(|| {
+                WebDriverError::Execution(format!("Invalid fail_on '{}' for audit_accessibility", name))
+            })?),
+            None => None,
+        };
+
+        let client_manager = self.server.get_client_manager();
+        let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
+            .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
+
+        let report = accessibility::run_audit(client_manager, session_id, &client, scope, &rules).await?;
+        if let Some(threshold) = fail_on {
+            let failing = report.failing(threshold);
+            if !failing.is_empty() {
+                return Err(WebDriverError::Execution(format!(
+                    "Accessibility check failed: {} violation(s) at or above '{}' impact\n{}",
+                    failing.len(),
+                    threshold.as_str(),
+                    report.summary()
+                )));
+            }
+        }
+        Ok(report.summary())
+    }
+
+    async fn execute_check_localization(&self, arguments: &serde_json::Map<String, Value>) -> Result<String, WebDriverError> {
+        let params: CheckLocalizationParams = serde_json::from_value(Value::Object(arguments.clone()))
+            .map_err(|e| WebDriverError::Execution(format!("Invalid arguments for check_localization: {}", e)))?;
+        let check = localization_check(&params)
+            .map_err(|e| WebDriverError::Execution(format!("Invalid check_localization step: {}", e)))?;
+        let session_id = params.session.session_id.as_deref().unwrap_or("default");
+
+        let client_manager = self.server.get_client_manager();
+        let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
+            .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
+
+        let report = localization::run_check(client_manager, session_id, &client, &check).await?;
+        if params.fail_on_issues && report.issue_count() > 0 {
+            return Err(WebDriverError::Execution(format!(
+                "Localization check failed: {} issue(s)\n{}",
+                report.issue_count(),
+                report.summary()
+            )));
+        }
+        Ok(report.summary())
+    }
+
     async fn execute_get_text(&self, arguments: &serde_json::Map<String, Value>) -> Result<String, WebDriverError> {
         let selector = arguments.get("selector")
             .and_then(|v| v.as_str())
@@ -880,15 +1516,14 @@ impl<'a> RecipeExecutor<'a> {
             .and_then(|v| v.as_str())
             .ok_or_else(|| WebDriverError::Execution("Missing 'selector' parameter for wait_for_element".to_string()))?;
 
-        let timeout_seconds = arguments.get("timeout_seconds")
-            .and_then(|v| v.as_f64())
-            .unwrap_or(10.0);
-
         let session_id = arguments.get("session_id")
             .and_then(|v| v.as_str())
             .unwrap_or("default");
 
         let client_manager = self.server.get_client_manager();
+        let timeout_seconds = arguments.get("timeout_seconds")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(client_manager.get_config().default_wait_timeout_secs);
         let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
@@ -907,7 +1542,7 @@ impl<'a> RecipeExecutor<'a> {
         let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
-        client.back().await
+        client_manager.run_command(session_id, "back", client.back()).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to navigate back: {}", e)))?;
 
         Ok("Successfully navigated back".to_string())
@@ -922,7 +1557,7 @@ impl<'a> RecipeExecutor<'a> {
         let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
-        client.forward().await
+        client_manager.run_command(session_id, "forward", client.forward()).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to navigate forward: {}", e)))?;
 
         Ok("Successfully navigated forward".to_string())
@@ -937,7 +1572,7 @@ impl<'a> RecipeExecutor<'a> {
         let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
-        client.refresh().await
+        client_manager.run_command(session_id, "refresh", client.refresh()).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to refresh page: {}", e)))?;
 
         Ok("Successfully refreshed page".to_string())
@@ -956,10 +1591,17 @@ impl<'a> RecipeExecutor<'a> {
         let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
-        let result = client.execute(script, vec![]).await
+        let script_args = arguments.get("args")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let result = client_manager.run_command(session_id, "execute_script", client.execute(script, script_args)).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to execute script: {}", e)))?;
 
-        Ok(format!("Script result: {:?}", result))
+        // The JSON text is the step result, so it can be stored with `store_as`
+        serde_json::to_string(&result)
+            .map_err(|e| WebDriverError::Execution(format!("Failed to serialize script result: {}", e)))
     }
 
     async fn execute_resize_window(&self, arguments: &serde_json::Map<String, Value>) -> Result<String, WebDriverError> {
@@ -1015,7 +1657,7 @@ impl<'a> RecipeExecutor<'a> {
         let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
-        let url = client.current_url().await
+        let url = client_manager.run_command(session_id, "get_current_url", client.current_url()).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get current URL: {}", e)))?;
 
         Ok(format!("Current URL: {}", url))
@@ -1117,6 +1759,8 @@ impl<'a> RecipeExecutor<'a> {
             .map_err(|e| WebDriverError::Execution(format!("Failed to scroll: {}", e)))?;
 
         if result.as_bool() == Some(true) {
+            // Let smooth scrolling finish before the next step acts on the page
+            tokio::time::sleep(Duration::from_millis(client_manager.get_config().scroll_settle_ms)).await;
             Ok(format!("Successfully scrolled to element '{}'", selector))
         } else {
             Err(WebDriverError::Execution(format!("Element '{}' not found for scroll", selector)))
@@ -1183,33 +1827,42 @@ impl<'a> RecipeExecutor<'a> {
 
     async fn execute_fill_and_submit_form(&self, arguments: &serde_json::Map<String, Value>) -> Result<String, WebDriverError> {
         let fields = arguments.get("fields")
-            .ok_or_else(|| WebDriverError::Execution("Missing 'fields' parameter for fill_and_submit_form".to_string()))?;
+            .ok_or_else(|| WebDriverError::Execution("Missing 'fields' parameter for fill_and_submit_form".to_string()))
+            .and_then(|fields| FieldAction::parse_all(fields).map_err(WebDriverError::Execution))?;
 
         let submit_selector = arguments.get("submit_selector")
             .and_then(|v| v.as_str())
             .ok_or_else(|| WebDriverError::Execution("Missing 'submit_selector' parameter".to_string()))?;
 
+        let verify = arguments.get("verify")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
         let session_id = arguments.get("session_id")
             .and_then(|v| v.as_str())
             .unwrap_or("default");
 
         let client_manager = self.server.get_client_manager();
-        let (_session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
+        let wait_timeout = arguments.get("wait_timeout")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(client_manager.get_config().default_wait_timeout_secs);
+        let (session, client) = client_manager.get_or_create_client(Some(session_id.to_string())).await
             .map_err(|e| WebDriverError::Execution(format!("Failed to get client: {}", e)))?;
 
-        // Fill each field
-        if let Some(fields_obj) = fields.as_object() {
-            for (selector, value) in fields_obj {
-                if let Some(text) = value.as_str() {
-                    let element = client.find(fantoccini::Locator::Css(selector)).await
-                        .map_err(|e| WebDriverError::Execution(format!("Failed to find field '{}': {}", selector, e)))?;
+        // Fill each field in order
+        for field in &fields {
+            let element = client_manager
+                .find_element_with_wait(&client, &field.selector, Some(field.wait_timeout.unwrap_or(wait_timeout)))
+                .await
+                .map_err(|e| WebDriverError::Execution(format!("Failed to find field '{}': {}", field.selector, e)))?;
 
-                    element.clear().await
-                        .map_err(|e| WebDriverError::Execution(format!("Failed to clear field '{}': {}", selector, e)))?;
+            let kind = forms::apply(element, field).await
+                .map_err(|e| WebDriverError::Execution(format!("Failed to fill field '{}': {}", field.selector, e)))?;
 
-                    element.send_keys(text).await
-                        .map_err(|e| WebDriverError::Execution(format!("Failed to fill field '{}': {}", selector, e)))?;
-                }
+            if verify && let Some(problem) = forms::verify(client_manager, &session, &client, field, kind).await? {
+                return Err(WebDriverError::Execution(format!(
+                    "Field '{}' does not hold the requested value: {}", field.selector, problem
+                )));
             }
         }
 
@@ -1220,6 +1873,6 @@ impl<'a> RecipeExecutor<'a> {
         submit_btn.click().await
             .map_err(|e| WebDriverError::Execution(format!("Failed to click submit: {}", e)))?;
 
-        Ok("Form filled and submitted successfully".to_string())
+        Ok(format!("Filled {} field(s) and submitted the form", fields.len()))
     }
 }
\ No newline at end of file
@@ -1,60 +1,53 @@
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::sync::Arc;
 use serde_json::{Map, Value};
-use tokio::fs as async_fs;
 
-use crate::recipes::recipe::{Recipe, RecipeStep, ParameterDefinition};
+use crate::config::Config;
+use crate::recipes::recipe::{Recipe, RecipeStep, ParameterDefinition, ParameterType};
+use crate::recipes::storage::{LocalStore, RecipeStore, store_from_config};
 use crate::error::WebDriverError;
 
 #[derive(Clone)]
 pub struct RecipeManager {
-    recipes_dir: PathBuf,
+    store: Arc<dyn RecipeStore>,
 }
 
 impl RecipeManager {
+    /// Manage recipes in `recipes_dir`, or `./recipes` when unset
     pub fn new(recipes_dir: Option<PathBuf>) -> Self {
-        let recipes_dir = recipes_dir.unwrap_or_else(|| {
-            let mut current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-            current_dir.push("recipes");
-            current_dir
-        });
+        Self::with_store(Arc::new(LocalStore::new(recipes_dir)))
+    }
 
-        Self { recipes_dir }
+    pub fn with_store(store: Arc<dyn RecipeStore>) -> Self {
+        Self { store }
     }
 
-    pub async fn ensure_recipes_directory(&self) -> Result<(), WebDriverError> {
-        if !self.recipes_dir.exists() {
-            async_fs::create_dir_all(&self.recipes_dir).await
-                .map_err(|e| WebDriverError::FileSystem(format!("Failed to create recipes directory: {}", e)))?;
-        }
-        Ok(())
+    /// Manage recipes in the store `recipe_store` selects
+    pub fn from_config(config: &Config) -> Self {
+        Self::with_store(store_from_config(config))
     }
 
-    pub async fn save_recipe(&self, recipe: &Recipe) -> Result<PathBuf, WebDriverError> {
-        self.ensure_recipes_directory().await?;
+    /// Where recipes are loaded from and saved to
+    pub fn location(&self) -> String {
+        self.store.location()
+    }
 
-        let filename = format!("{}.json", sanitize_filename(&recipe.name));
-        let file_path = self.recipes_dir.join(filename);
+    /// Save `recipe`, replacing any recipe of the same name, and return where it was stored
+    pub async fn save_recipe(&self, recipe: &Recipe) -> Result<String, WebDriverError> {
+        let key = sanitize_filename(&recipe.name);
 
         let recipe_json = recipe.to_json()
             .map_err(|e| WebDriverError::Serialization(format!("Failed to serialize recipe: {}", e)))?;
 
-        async_fs::write(&file_path, recipe_json).await
-            .map_err(|e| WebDriverError::FileSystem(format!("Failed to write recipe file: {}", e)))?;
+        self.store.write(&key, &recipe_json).await?;
 
-        Ok(file_path)
+        Ok(self.store.locate(&key))
     }
 
     pub async fn load_recipe(&self, name: &str) -> Result<Recipe, WebDriverError> {
-        let filename = format!("{}.json", sanitize_filename(name));
-        let file_path = self.recipes_dir.join(filename);
-
-        if !file_path.exists() {
-            return Err(WebDriverError::NotFound(format!("Recipe '{}' not found", name)));
-        }
-
-        let recipe_json = async_fs::read_to_string(&file_path).await
-            .map_err(|e| WebDriverError::FileSystem(format!("Failed to read recipe file: {}", e)))?;
+        let recipe_json = self.store.read(&sanitize_filename(name)).await?
+            .ok_or_else(|| WebDriverError::NotFound(format!("Recipe '{}' not found", name)))?;
 
         let recipe = Recipe::from_json(&recipe_json)
             .map_err(|e| WebDriverError::Serialization(format!("Failed to parse recipe JSON: {}", e)))?;
@@ -65,25 +58,25 @@ impl RecipeManager {
         Ok(recipe)
     }
 
-    pub async fn list_recipes(&self) -> Result<Vec<RecipeInfo>, WebDriverError> {
-        if !self.recipes_dir.exists() {
-            return Ok(Vec::new());
+    /// The stored JSON of every saved recipe by where it is stored, unparsed
+    pub async fn load_recipe_sources(&self) -> Result<Vec<(String, Result<String, WebDriverError>)>, WebDriverError> {
+        let mut sources = Vec::new();
+        for key in self.store.list().await? {
+            let source = self.store.read(&key).await.and_then(|json| {
+                json.ok_or_else(|| WebDriverError::NotFound(format!("Recipe '{}' was removed", key)))
+            });
+            sources.push((self.store.locate(&key), source));
         }
+        Ok(sources)
+    }
 
+    pub async fn list_recipes(&self) -> Result<Vec<RecipeInfo>, WebDriverError> {
         let mut recipes = Vec::new();
-        let mut entries = async_fs::read_dir(&self.recipes_dir).await
-            .map_err(|e| WebDriverError::FileSystem(format!("Failed to read recipes directory: {}", e)))?;
-
-        while let Some(entry) = entries.next_entry().await
-            .map_err(|e| WebDriverError::FileSystem(format!("Failed to read directory entry: {}", e)))? {
-            
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                match self.load_recipe_info(&path).await {
-                    Ok(info) => recipes.push(info),
-                    Err(e) => {
-                        tracing::warn!("Failed to load recipe info from {}: {}", path.display(), e);
-                    }
+        for (location, source) in self.load_recipe_sources().await? {
+            match source.and_then(|json| recipe_info(location.clone(), &json)) {
+                Ok(info) => recipes.push(info),
+                Err(e) => {
+                    tracing::warn!("Failed to load recipe info from {}: {}", location, e);
                 }
             }
         }
@@ -92,17 +85,25 @@ impl RecipeManager {
         Ok(recipes)
     }
 
-    pub async fn delete_recipe(&self, name: &str) -> Result<(), WebDriverError> {
-        let filename = format!("{}.json", sanitize_filename(name));
-        let file_path = self.recipes_dir.join(filename);
+    /// Load every valid saved recipe, skipping (and logging) broken ones
+    pub async fn load_all_recipes(&self) -> Result<Vec<Recipe>, WebDriverError> {
+        let mut recipes = Vec::new();
+        for info in self.list_recipes().await? {
+            match self.load_recipe(&info.name).await {
+                Ok(recipe) => recipes.push(recipe),
+                Err(e) => {
+                    tracing::warn!("Skipping recipe '{}': {}", info.name, e);
+                }
+            }
+        }
+        Ok(recipes)
+    }
 
-        if !file_path.exists() {
+    pub async fn delete_recipe(&self, name: &str) -> Result<(), WebDriverError> {
+        if !self.store.delete(&sanitize_filename(name)).await? {
             return Err(WebDriverError::NotFound(format!("Recipe '{}' not found", name)));
         }
 
-        async_fs::remove_file(&file_path).await
-            .map_err(|e| WebDriverError::FileSystem(format!("Failed to delete recipe file: {}", e)))?;
-
         Ok(())
     }
 
@@ -116,22 +117,31 @@ impl RecipeManager {
                     author: None,
                     created_at: Some(chrono::Utc::now().to_rfc3339()),
                     browsers: browsers.unwrap_or_else(|| vec!["auto".to_string()]),
+                    retry: None,
+                    on_failure: Vec::new(),
+                    isolation: None,
                     parameters: Some({
                         let mut params = HashMap::new();
                         params.insert("base_url".to_string(), ParameterDefinition {
                             description: Some("Base URL of the website".to_string()),
                             default_value: Some(base_url),
                             required: true,
+                            param_type: ParameterType::Url,
+                            allowed_values: None,
                         });
                         params.insert("username".to_string(), ParameterDefinition {
                             description: Some("Username for login".to_string()),
                             default_value: Some(username),
                             required: true,
+                            param_type: ParameterType::String,
+                            allowed_values: None,
                         });
                         params.insert("password".to_string(), ParameterDefinition {
                             description: Some("Password for login".to_string()),
                             default_value: Some(password),
                             required: true,
+                            param_type: ParameterType::String,
+                            allowed_values: None,
                         });
                         params
                     }),
@@ -151,6 +161,7 @@ impl RecipeManager {
                             condition: None,
                             session_id: None,
                             browser: None,
+                            browser_overrides: HashMap::new(),
                         },
                         RecipeStep {
                             name: Some("Wait for page to load".to_string()),
@@ -168,6 +179,7 @@ impl RecipeManager {
                             condition: None,
                             session_id: None,
                             browser: None,
+                            browser_overrides: HashMap::new(),
                         },
                         RecipeStep {
                             name: Some("Login with credentials".to_string()),
@@ -185,6 +197,7 @@ impl RecipeManager {
                             condition: None,
                             session_id: None,
                             browser: None,
+                            browser_overrides: HashMap::new(),
                         },
                         RecipeStep {
                             name: Some("Wait after login".to_string()),
@@ -202,6 +215,7 @@ impl RecipeManager {
                             condition: None,
                             session_id: None,
                             browser: None,
+                            browser_overrides: HashMap::new(),
                         },
                         RecipeStep {
                             name: Some("Take screenshot".to_string()),
@@ -218,6 +232,7 @@ impl RecipeManager {
                             condition: None,
                             session_id: None,
                             browser: None,
+                            browser_overrides: HashMap::new(),
                         }
                     ],
                 }
@@ -230,12 +245,17 @@ impl RecipeManager {
                     author: None,
                     created_at: Some(chrono::Utc::now().to_rfc3339()),
                     browsers,
+                    retry: None,
+                    on_failure: Vec::new(),
+                    isolation: None,
                     parameters: Some({
                         let mut params = HashMap::new();
                         params.insert("url".to_string(), ParameterDefinition {
                             description: Some("URL to take screenshots of".to_string()),
                             default_value: Some(url),
                             required: true,
+                            param_type: ParameterType::Url,
+                            allowed_values: None,
                         });
                         params
                     }),
@@ -255,6 +275,7 @@ impl RecipeManager {
                             condition: None,
                             session_id: None,
                             browser: None,
+                            browser_overrides: HashMap::new(),
                         },
                         RecipeStep {
                             name: Some("Wait for page load".to_string()),
@@ -272,6 +293,7 @@ impl RecipeManager {
                             condition: None,
                             session_id: None,
                             browser: None,
+                            browser_overrides: HashMap::new(),
                         },
                         RecipeStep {
                             name: Some("Take browser-specific screenshot".to_string()),
@@ -288,6 +310,7 @@ impl RecipeManager {
                             condition: None,
                             session_id: None,
                             browser: None,
+                            browser_overrides: HashMap::new(),
                         }
                     ],
                 }
@@ -309,6 +332,7 @@ impl RecipeManager {
                         condition: None,
                         session_id: None,
                         browser: None,
+                        browser_overrides: HashMap::new(),
                     },
                     RecipeStep {
                         name: Some("Wait for page load".to_string()),
@@ -326,6 +350,7 @@ impl RecipeManager {
                         condition: None,
                         session_id: None,
                         browser: None,
+                        browser_overrides: HashMap::new(),
                     }
                 ];
 
@@ -348,6 +373,7 @@ impl RecipeManager {
                         condition: None,
                         session_id: None,
                         browser: None,
+                        browser_overrides: HashMap::new(),
                     });
                     steps.push(RecipeStep {
                         name: Some(format!("{} screenshot", resolution_name)),
@@ -364,6 +390,7 @@ impl RecipeManager {
                         condition: None,
                         session_id: None,
                         browser: None,
+                        browser_overrides: HashMap::new(),
                     });
                 }
 
@@ -374,12 +401,17 @@ impl RecipeManager {
                     author: None,
                     created_at: Some(chrono::Utc::now().to_rfc3339()),
                     browsers,
+                    retry: None,
+                    on_failure: Vec::new(),
+                    isolation: None,
                     parameters: Some({
                         let mut params = HashMap::new();
                         params.insert("url".to_string(), ParameterDefinition {
                             description: Some("URL to test for responsive design".to_string()),
                             default_value: Some(url),
                             required: true,
+                            param_type: ParameterType::Url,
+                            allowed_values: None,
                         });
                         params
                     }),
@@ -393,24 +425,21 @@ impl RecipeManager {
 
         Ok(recipe)
     }
+}
 
-    async fn load_recipe_info(&self, path: &Path) -> Result<RecipeInfo, WebDriverError> {
-        let recipe_json = async_fs::read_to_string(path).await
-            .map_err(|e| WebDriverError::FileSystem(format!("Failed to read recipe file: {}", e)))?;
-
-        let recipe: Recipe = serde_json::from_str(&recipe_json)
-            .map_err(|e| WebDriverError::Serialization(format!("Failed to parse recipe JSON: {}", e)))?;
-
-        Ok(RecipeInfo {
-            name: recipe.name,
-            description: recipe.description,
-            version: recipe.version,
-            author: recipe.author,
-            created_at: recipe.created_at,
-            step_count: recipe.steps.len(),
-            file_path: path.to_path_buf(),
-        })
-    }
+fn recipe_info(location: String, recipe_json: &str) -> Result<RecipeInfo, WebDriverError> {
+    let recipe: Recipe = serde_json::from_str(recipe_json)
+        .map_err(|e| WebDriverError::Serialization(format!("Failed to parse recipe JSON: {}", e)))?;
+
+    Ok(RecipeInfo {
+        name: recipe.name,
+        description: recipe.description,
+        version: recipe.version,
+        author: recipe.author,
+        created_at: recipe.created_at,
+        step_count: recipe.steps.len(),
+        location,
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -421,7 +450,8 @@ pub struct RecipeInfo {
     pub author: Option<String>,
     pub created_at: Option<String>,
     pub step_count: usize,
-    pub file_path: PathBuf,
+    /// Where the recipe is stored, e.g. its file path
+    pub location: String,
 }
 
 #[derive(Debug, Clone)]
@@ -1,7 +1,11 @@
 pub mod recipe;
 pub mod manager;
 pub mod execution;
+pub mod report;
+pub mod storage;
 
 pub use recipe::*;
 pub use manager::*;
-pub use execution::*;
\ No newline at end of file
+pub use execution::*;
+pub use report::{ReportFormat, RunReport};
+pub use storage::{GitStore, LocalStore, RecipeStore, S3Store};
\ No newline at end of file
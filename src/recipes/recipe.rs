@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::forms::FieldAction;
 
 fn default_browsers() -> Vec<String> {
     vec!["auto".to_string()]
@@ -17,13 +21,156 @@ pub struct Recipe {
     #[serde(default = "default_browsers")]
     pub browsers: Vec<String>,
     pub steps: Vec<RecipeStep>,
+    /// Recipe-level retry policy: re-runs the whole recipe for a browser when it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// Steps run (best-effort) after every failed attempt, e.g. screenshot, `run_recipe` or navigate
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_failure: Vec<RecipeStep>,
+    /// Run each attempt in a brand-new browser session that is torn down afterwards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isolation: Option<SessionIsolation>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionIsolation {
+    /// Overrides the server's headless setting for the isolated session
+    #[serde(default)]
+    pub headless: Option<bool>,
+    /// Open the browser in a private/incognito window
+    #[serde(default)]
+    pub incognito: bool,
+}
+
+fn default_retry_delay_ms() -> u64 {
+    1000
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Number of additional attempts after the first failure
+    pub count: u32,
+    #[serde(default = "default_retry_delay_ms")]
+    pub delay_ms: u64,
+    #[serde(default)]
+    pub backoff: RetryBackoff,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetryBackoff {
+    #[default]
+    Fixed,
+    Linear,
+    Exponential,
+}
+
+impl RetryPolicy {
+    /// Delay to wait before the given retry attempt (1-based)
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let multiplier = match self.backoff {
+            RetryBackoff::Fixed => 1,
+            RetryBackoff::Linear => attempt.max(1) as u64,
+            RetryBackoff::Exponential => 1u64.checked_shl(attempt.saturating_sub(1)).unwrap_or(u64::MAX),
+        };
+        Duration::from_millis(self.delay_ms.saturating_mul(multiplier))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParameterDefinition {
     pub description: Option<String>,
     pub default_value: Option<String>,
+    #[serde(default)]
     pub required: bool,
+    #[serde(default, rename = "type")]
+    pub param_type: ParameterType,
+    /// Restricts the parameter to a fixed set of values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_values: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterType {
+    #[default]
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Url,
+}
+
+impl ParameterType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParameterType::String => "string",
+            ParameterType::Number => "number",
+            ParameterType::Integer => "integer",
+            ParameterType::Boolean => "boolean",
+            ParameterType::Url => "url",
+        }
+    }
+
+    /// Check that a (string-encoded) value is valid for this type
+    pub fn check_value(&self, value: &str) -> Result<(), String> {
+        let valid = match self {
+            ParameterType::String => true,
+            ParameterType::Number => value.trim().parse::<f64>().is_ok(),
+            ParameterType::Integer => value.trim().parse::<i64>().is_ok(),
+            ParameterType::Boolean => matches!(value.trim(), "true" | "false"),
+            ParameterType::Url => url::Url::parse(value).is_ok(),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("expected a {} but got '{}'", self.as_str(), value))
+        }
+    }
+}
+
+impl ParameterDefinition {
+    /// Validate a value against the declared type and allowed values
+    pub fn check_value(&self, name: &str, value: &str) -> Result<(), String> {
+        self.param_type.check_value(value)
+            .map_err(|e| format!("Parameter '{}': {}", name, e))?;
+
+        let disallowed = self.allowed_values.as_ref()
+            .filter(|allowed| !allowed.iter().any(|v| v == value));
+        if let Some(allowed) = disallowed {
+            return Err(format!(
+                "Parameter '{}': '{}' is not one of [{}]",
+                name,
+                value,
+                allowed.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn json_schema(&self) -> Value {
+        let mut schema = Map::new();
+        let json_type = match self.param_type {
+            ParameterType::Url => "string",
+            other => other.as_str(),
+        };
+        schema.insert("type".to_string(), Value::String(json_type.to_string()));
+        if self.param_type == ParameterType::Url {
+            schema.insert("format".to_string(), Value::String("uri".to_string()));
+        }
+        if let Some(description) = &self.description {
+            schema.insert("description".to_string(), Value::String(description.clone()));
+        }
+        if let Some(default_value) = &self.default_value {
+            schema.insert("default".to_string(), Value::String(default_value.clone()));
+        }
+        if let Some(allowed) = &self.allowed_values {
+            schema.insert("enum".to_string(), Value::from(allowed.clone()));
+        }
+        Value::Object(schema)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +185,10 @@ pub struct RecipeStep {
     pub condition: Option<String>,
     pub session_id: Option<String>,
     pub browser: Option<String>,
+    /// Arguments by browser that replace the same-named ones in `arguments` when
+    /// the step runs on that browser, e.g. a selector Firefox renders differently
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub browser_overrides: HashMap<String, Map<String, Value>>,
 }
 
 impl Recipe {
@@ -52,8 +203,8 @@ impl Recipe {
     pub fn substitute_parameters(&self, parameters: &HashMap<String, String>) -> Result<Recipe, String> {
         let mut recipe = self.clone();
         
-        // Substitute parameters in steps
-        for step in &mut recipe.steps {
+        // Substitute parameters in steps and on-failure hooks
+        for step in recipe.steps.iter_mut().chain(recipe.on_failure.iter_mut()) {
             // Substitute in arguments
             let arguments_str = serde_json::to_string(&step.arguments)
                 .map_err(|e| format!("Failed to serialize arguments: {}", e))?;
@@ -62,6 +213,13 @@ impl Recipe {
             
             step.arguments = serde_json::from_str(&substituted_str)
                 .map_err(|e| format!("Failed to deserialize substituted arguments: {}", e))?;
+
+            for overrides in step.browser_overrides.values_mut() {
+                let overrides_str = serde_json::to_string(overrides)
+                    .map_err(|e| format!("Failed to serialize browser overrides: {}", e))?;
+                *overrides = serde_json::from_str(&substitute_variables(&overrides_str, parameters))
+                    .map_err(|e| format!("Failed to deserialize substituted browser overrides: {}", e))?;
+            }
             
             // Substitute in condition if present
             if let Some(condition) = &step.condition {
@@ -72,6 +230,129 @@ impl Recipe {
         Ok(recipe)
     }
 
+    /// Resolve the final parameter values for an execution: applies declared defaults,
+    /// rejects missing required or undeclared parameters and type-checks every value.
+    /// Recipes without declared parameters accept any values unchanged.
+    pub fn resolve_parameters(&self, provided: &HashMap<String, String>) -> Result<HashMap<String, String>, String> {
+        let Some(definitions) = &self.parameters else {
+            return Ok(provided.clone());
+        };
+
+        let mut unknown: Vec<&str> = provided.keys()
+            .filter(|key| !definitions.contains_key(*key))
+            .map(|key| key.as_str())
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            let mut declared: Vec<&str> = definitions.keys().map(|key| key.as_str()).collect();
+            declared.sort();
+            return Err(format!(
+                "Unknown parameter(s): {}. Declared parameters: {}",
+                unknown.join(", "),
+                declared.join(", ")
+            ));
+        }
+
+        let mut resolved = HashMap::new();
+        for (name, definition) in definitions {
+            match provided.get(name).or(definition.default_value.as_ref()) {
+                Some(value) => {
+                    definition.check_value(name, value)?;
+                    resolved.insert(name.clone(), value.clone());
+                }
+                None if definition.required => {
+                    return Err(format!("Missing required parameter '{}'", name));
+                }
+                None => {}
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Check the run-time variables a caller injects into one execution, which
+    /// steps reference as `{{name}}`. Names follow the parameter rules, cannot
+    /// shadow the executor's own `browser`, `step` and `timestamp`, and cannot
+    /// be a declared parameter, whose value belongs in the parameters instead.
+    pub fn check_variables(&self, variables: &HashMap<String, String>) -> Result<(), String> {
+        let mut names: Vec<&String> = variables.keys().collect();
+        names.sort();
+        for name in names {
+            if !is_valid_name(name) {
+                return Err(format!("Invalid variable name '{}': use letters, digits and underscores", name));
+            }
+            if BUILTIN_VARIABLES.contains(&name.as_str()) {
+                return Err(format!("'{}' is set by the executor and cannot be injected", name));
+            }
+            if self.parameters.as_ref().is_some_and(|definitions| definitions.contains_key(name)) {
+                return Err(format!("'{}' is a declared parameter; pass it in parameters instead", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// JSON Schema describing the recipe's parameters, suitable for an MCP tool input schema
+    pub fn parameters_schema(&self) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+
+        if let Some(definitions) = &self.parameters {
+            let mut names: Vec<&String> = definitions.keys().collect();
+            names.sort();
+            for name in names {
+                let definition = &definitions[name];
+                properties.insert(name.clone(), definition.json_schema());
+                if definition.required && definition.default_value.is_none() {
+                    required.push(Value::String(name.clone()));
+                }
+            }
+        }
+
+        let mut schema = Map::new();
+        schema.insert("type".to_string(), Value::String("object".to_string()));
+        schema.insert("properties".to_string(), Value::Object(properties));
+        if !required.is_empty() {
+            schema.insert("required".to_string(), Value::Array(required));
+        }
+        Value::Object(schema)
+    }
+
+    /// Human/LLM-readable description of the recipe and the inputs it needs
+    pub fn tool_description(&self) -> String {
+        let mut description = self.description.clone()
+            .unwrap_or_else(|| format!("Run the '{}' recipe", self.name));
+        description.push_str(&format!(" ({} steps", self.steps.len()));
+        if !self.browsers.is_empty() {
+            description.push_str(&format!(", browsers: {}", self.browsers.join(", ")));
+        }
+        description.push(')');
+
+        match &self.parameters {
+            Some(definitions) if !definitions.is_empty() => {
+                let mut names: Vec<&String> = definitions.keys().collect();
+                names.sort();
+                description.push_str(". Parameters:");
+                for name in names {
+                    let definition = &definitions[name];
+                    description.push_str(&format!("\n- {} ({}", name, definition.param_type.as_str()));
+                    if definition.required && definition.default_value.is_none() {
+                        description.push_str(", required");
+                    }
+                    if let Some(default_value) = &definition.default_value {
+                        description.push_str(&format!(", default: {}", default_value));
+                    }
+                    description.push(')');
+                    if let Some(desc) = &definition.description {
+                        description.push_str(&format!(": {}", desc));
+                    }
+                }
+            }
+            _ => description.push_str(". Takes no parameters."),
+        }
+
+        description
+    }
+
     pub fn validate(&self) -> Result<(), String> {
         if self.name.is_empty() {
             return Err("Recipe name cannot be empty".to_string());
@@ -89,77 +370,629 @@ impl Recipe {
             }
         }
 
+        // Validate parameter declarations
+        if let Some(definitions) = &self.parameters {
+            for (name, definition) in definitions {
+                if !is_valid_name(name) {
+                    return Err(format!("Invalid parameter name '{}': use letters, digits and underscores", name));
+                }
+                if let Some(default_value) = &definition.default_value {
+                    definition.check_value(name, default_value)
+                        .map_err(|e| format!("Invalid default value: {}", e))?;
+                }
+                if definition.allowed_values.as_ref().is_some_and(|allowed| allowed.is_empty()) {
+                    return Err(format!("Parameter '{}' has an empty allowed_values list", name));
+                }
+            }
+        }
+
         // Validate each step
         for (i, step) in self.steps.iter().enumerate() {
-            if step.action.is_empty() {
-                return Err(format!("Step {} has empty action", i + 1));
+            if step.action == "run_recipe" {
+                return Err(format!("Step {} (run_recipe) is only supported in on_failure hooks", i + 1));
             }
+            validate_step(step, &format!("Step {}", i + 1))?;
+        }
 
-            // Validate step-level browser if specified
-            if let Some(browser) = &step.browser {
-                match browser.as_str() {
-                    "chrome" | "firefox" | "edge" => {},
-                    _ => return Err(format!("Step {} has unsupported browser: {}", i + 1, browser)),
-                }
-            }
+        if self.retry.as_ref().is_some_and(|retry| retry.count > 10) {
+            return Err("Recipe retry count must be 10 or less".to_string());
+        }
 
-            // Validate known actions
-            match step.action.as_str() {
-                "navigate" => {
-                    if !step.arguments.contains_key("url") {
-                        return Err(format!("Step {} (navigate) missing required 'url' argument", i + 1));
-                    }
-                }
-                "click" | "wait_for_element" | "get_text" | "hover" | 
-                "scroll_to_element" => {
-                    if !step.arguments.contains_key("selector") {
-                        return Err(format!("Step {} ({}) missing required 'selector' argument", i + 1, step.action));
-                    }
-                }
-                "send_keys" => {
-                    if !step.arguments.contains_key("selector") {
-                        return Err(format!("Step {} (send_keys) missing required 'selector' argument", i + 1));
-                    }
-                    if !step.arguments.contains_key("text") {
-                        return Err(format!("Step {} (send_keys) missing required 'text' argument", i + 1));
-                    }
-                }
-                "execute_script" => {
-                    if !step.arguments.contains_key("script") {
-                        return Err(format!("Step {} (execute_script) missing required 'script' argument", i + 1));
-                    }
-                }
-                "wait_for_condition" => {
-                    if !step.arguments.contains_key("condition") {
-                        return Err(format!("Step {} (wait_for_condition) missing required 'condition' argument", i + 1));
-                    }
-                }
-                "get_attribute" => {
-                    if !step.arguments.contains_key("attribute") {
-                        return Err(format!("Step {} (get_attribute) missing required 'attribute' argument", i + 1));
-                    }
-                }
-                "get_property" => {
-                    if !step.arguments.contains_key("property") {
-                        return Err(format!("Step {} (get_property) missing required 'property' argument", i + 1));
+        // Validate on-failure hooks
+        for (i, step) in self.on_failure.iter().enumerate() {
+            let label = format!("on_failure step {}", i + 1);
+            if step.action == "run_recipe" {
+                match step.arguments.get("name").and_then(|v| v.as_str()) {
+                    Some(name) if name == self.name => {
+                        return Err(format!("{} (run_recipe) cannot run the recipe itself", label));
                     }
+                    Some(_) => {}
+                    None => return Err(format!("{} (run_recipe) missing required 'name' argument", label)),
                 }
-                "fill_and_submit_form" => {
-                    if !step.arguments.contains_key("fields") || !step.arguments.contains_key("submit_selector") {
-                        return Err(format!("Step {} (fill_and_submit_form) missing required arguments", i + 1));
+                continue;
+            }
+            validate_step(step, &label)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_step(step: &RecipeStep, label: &str) -> Result<(), String> {
+    if step.action.is_empty() {
+        return Err(format!("{} has empty action", label));
+    }
+
+    // Validate step-level browser if specified
+    if let Some(browser) = &step.browser {
+        match browser.as_str() {
+            "chrome" | "firefox" | "edge" => {},
+            _ => return Err(format!("{} has unsupported browser: {}", label, browser)),
+        }
+    }
+
+    // Overrides must name a browser the step runs on and leave valid arguments
+    let mut override_browsers: Vec<&String> = step.browser_overrides.keys().collect();
+    override_browsers.sort();
+    for browser in override_browsers {
+        if !matches!(browser.as_str(), "chrome" | "firefox" | "edge") {
+            return Err(format!("{} has browser_overrides for unsupported browser: {}", label, browser));
+        }
+        if step.browser.as_ref().is_some_and(|only| only != browser) {
+            return Err(format!("{} only runs on {} but has browser_overrides for {}",
+                label, step.browser.as_deref().unwrap_or_default(), browser));
+        }
+        let mut resolved = step.for_browser(browser).into_owned();
+        resolved.browser_overrides.clear();
+        validate_step(&resolved, &format!("{} ({} override)", label, browser))?;
+    }
+
+    // Validate known actions
+    match step.action.as_str() {
+        "navigate" => {
+            if !step.arguments.contains_key("url") {
+                return Err(format!("{} (navigate) missing required 'url' argument", label));
+            }
+        }
+        "click" | "wait_for_element" | "get_text" | "hover" | 
+        "scroll_to_element" => {
+            if !step.arguments.contains_key("selector") {
+                return Err(format!("{} ({}) missing required 'selector' argument", label, step.action));
+            }
+        }
+        "send_keys" => {
+            if !step.arguments.contains_key("selector") {
+                return Err(format!("{} (send_keys) missing required 'selector' argument", label));
+            }
+            if !step.arguments.contains_key("text") {
+                return Err(format!("{} (send_keys) missing required 'text' argument", label));
+            }
+        }
+        "execute_script" => {
+            if !step.arguments.contains_key("script") {
+                return Err(format!("{} (execute_script) missing required 'script' argument", label));
+            }
+            if step.arguments.get("args").is_some_and(|args| !args.is_array()) {
+                return Err(format!("{} (execute_script) 'args' must be an array", label));
+            }
+            if let Some(store_as) = step.arguments.get("store_as") {
+                match store_as.as_str() {
+                    Some(reserved @ ("browser" | "step")) => {
+                        return Err(format!("{} (execute_script) cannot store into the reserved '{}' variable",
+                            label, reserved));
                     }
-                }
-                "login_form" => {
-                    if !step.arguments.contains_key("username") || !step.arguments.contains_key("password") {
-                        return Err(format!("Step {} (login_form) missing required 'username' or 'password' argument", i + 1));
+                    Some(name) if is_valid_name(name) => {}
+                    _ => {
+                        return Err(format!(
+                            "{} (execute_script) has invalid 'store_as': use letters, digits and underscores",
+                            label
+                        ));
                     }
                 }
-                // Allow any action - some might be custom or new
-                _ => {}
             }
         }
+        "screenshot" => {
+            let full_page = step.arguments.get("full_page").and_then(|v| v.as_bool()) == Some(true);
+            if full_page && step.arguments.contains_key("selector") {
+                return Err(format!("{} (screenshot) takes either 'full_page' or 'selector', not both", label));
+            }
+        }
+        "wait_for_condition" => {
+            if !step.arguments.contains_key("condition") {
+                return Err(format!("{} (wait_for_condition) missing required 'condition' argument", label));
+            }
+        }
+        "get_attribute" => {
+            if !step.arguments.contains_key("attribute") {
+                return Err(format!("{} (get_attribute) missing required 'attribute' argument", label));
+            }
+        }
+        "get_property" => {
+            if !step.arguments.contains_key("property") {
+                return Err(format!("{} (get_property) missing required 'property' argument", label));
+            }
+        }
+        "fill_and_submit_form" => {
+            if !step.arguments.contains_key("fields") || !step.arguments.contains_key("submit_selector") {
+                return Err(format!("{} (fill_and_submit_form) missing required arguments", label));
+            }
+            // Fields built from variables are only known at run time
+            if let Some(fields) = step.arguments.get("fields").filter(|fields| !fields.is_string()) {
+                FieldAction::parse_all(fields)
+                    .map_err(|e| format!("{} (fill_and_submit_form): {}", label, e))?;
+            }
+        }
+        "audit_accessibility" => {
+            if let Some(fail_on) = step.arguments.get("fail_on").and_then(|v| v.as_str())
+                && crate::accessibility::Impact::from_name(fail_on).is_none()
+            {
+                return Err(format!(
+                    "{} (audit_accessibility) has invalid 'fail_on' '{}': use minor, moderate, serious or critical",
+                    label, fail_on
+                ));
+            }
+        }
+        "check_localization" if !step.arguments.contains_key("url") || !step.arguments.contains_key("locales") => {
+            return Err(format!("{} (check_localization) missing required 'url' or 'locales' argument", label));
+        }
+        "login_form" => {
+            if !step.arguments.contains_key("username") || !step.arguments.contains_key("password") {
+                return Err(format!("{} (login_form) missing required 'username' or 'password' argument", label));
+            }
+        }
+        // Allow any action - some might be custom or new
+        _ => {}
+    }
 
-        Ok(())
+    Ok(())
+}
+
+/// Fluent construction of a [`Recipe`] from Rust, without writing JSON
+///
+/// Each action method appends one step with its arguments typed; the
+/// `named`/`continue_on_error`/`when`/... modifiers adjust the step added last.
+/// [`RecipeBuilder::build`] runs the same validation as loading a recipe file.
+///
+/// ```
+/// use std::time::Duration;
+/// use rust_browser_mcp::Recipe;
+///
+/// let recipe = Recipe::builder("search")
+///     .description("Search the docs")
+///     .navigate("https://example.com")
+///     .wait_for_timeout("input[name='q']", Duration::from_secs(5))
+///     .send_keys("input[name='q']", "webdriver\n")
+///     .click("a.result").named("Open first result").continue_on_error()
+///     .screenshot("result.png")
+///     .build()
+///     .unwrap();
+/// assert_eq!(recipe.steps.len(), 5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecipeBuilder {
+    recipe: Recipe,
+    /// First misuse of a step modifier, reported by `build`
+    error: Option<String>,
+}
+
+impl Recipe {
+    /// Start a [`RecipeBuilder`] for a recipe called `name`, version 1.0.0
+    pub fn builder(name: impl Into<String>) -> RecipeBuilder {
+        RecipeBuilder::new(name)
+    }
+}
+
+impl RecipeStep {
+    /// A step running `action` with `arguments` and no step options
+    pub fn new(action: impl Into<String>, arguments: Map<String, Value>) -> Self {
+        Self {
+            name: None,
+            description: None,
+            action: action.into(),
+            arguments,
+            continue_on_error: None,
+            retry_count: None,
+            retry_delay_ms: None,
+            condition: None,
+            session_id: None,
+            browser: None,
+            browser_overrides: HashMap::new(),
+        }
+    }
+
+    /// The step as it runs on `browser`, with that browser's overrides applied
+    pub fn for_browser(&self, browser: &str) -> Cow<'_, RecipeStep> {
+        match self.browser_overrides.get(browser) {
+            Some(overrides) => {
+                let mut step = self.clone();
+                step.arguments.extend(overrides.iter().map(|(key, value)| (key.clone(), value.clone())));
+                Cow::Owned(step)
+            }
+            None => Cow::Borrowed(self),
+        }
+    }
+}
+
+impl RecipeBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            recipe: Recipe {
+                name: name.into(),
+                description: None,
+                version: "1.0.0".to_string(),
+                author: None,
+                created_at: None,
+                parameters: None,
+                browsers: default_browsers(),
+                steps: Vec::new(),
+                retry: None,
+                on_failure: Vec::new(),
+                isolation: None,
+            },
+            error: None,
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.recipe.description = Some(description.into());
+        self
+    }
+
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.recipe.version = version.into();
+        self
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.recipe.author = Some(author.into());
+        self
+    }
+
+    /// Browsers to run on ("auto", "chrome", "firefox" or "edge"), replacing the default "auto"
+    pub fn browsers<I, S>(mut self, browsers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.recipe.browsers = browsers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Declare a `${name}` parameter
+    pub fn parameter(mut self, name: impl Into<String>, definition: ParameterDefinition) -> Self {
+        self.recipe.parameters.get_or_insert_with(HashMap::new).insert(name.into(), definition);
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.recipe.retry = Some(retry);
+        self
+    }
+
+    pub fn isolation(mut self, isolation: SessionIsolation) -> Self {
+        self.recipe.isolation = Some(isolation);
+        self
+    }
+
+    /// Steps to run after a failed attempt, built with their own builder:
+    /// `.on_failure(|hook| hook.screenshot("failure.png").run_recipe("cleanup"))`
+    pub fn on_failure(mut self, hook: impl FnOnce(RecipeBuilder) -> RecipeBuilder) -> Self {
+        let hook = hook(RecipeBuilder::new(self.recipe.name.clone()));
+        if self.error.is_none() {
+            self.error = hook.error;
+        }
+        self.recipe.on_failure.extend(hook.recipe.steps);
+        self
+    }
+
+    /// Append a step for any action, including ones without a typed method
+    pub fn step(mut self, step: RecipeStep) -> Self {
+        self.recipe.steps.push(step);
+        self
+    }
+
+    pub fn navigate(self, url: impl Into<String>) -> Self {
+        self.action("navigate", [("url", Value::String(url.into()))])
+    }
+
+    pub fn back(self) -> Self {
+        self.action("back", [])
+    }
+
+    pub fn forward(self) -> Self {
+        self.action("forward", [])
+    }
+
+    pub fn refresh(self) -> Self {
+        self.action("refresh", [])
+    }
+
+    pub fn click(self, selector: impl Into<String>) -> Self {
+        self.action("click", [("selector", Value::String(selector.into()))])
+    }
+
+    pub fn hover(self, selector: impl Into<String>) -> Self {
+        self.action("hover", [("selector", Value::String(selector.into()))])
+    }
+
+    pub fn scroll_to(self, selector: impl Into<String>) -> Self {
+        self.action("scroll_to_element", [("selector", Value::String(selector.into()))])
+    }
+
+    /// Type `text` into the element; a trailing "\n" presses Enter
+    pub fn send_keys(self, selector: impl Into<String>, text: impl Into<String>) -> Self {
+        self.action(
+            "send_keys",
+            [("selector", Value::String(selector.into())), ("text", Value::String(text.into()))],
+        )
+    }
+
+    /// Wait for the element with the server's default wait timeout
+    pub fn wait_for(self, selector: impl Into<String>) -> Self {
+        self.action("wait_for_element", [("selector", Value::String(selector.into()))])
+    }
+
+    pub fn wait_for_timeout(self, selector: impl Into<String>, timeout: Duration) -> Self {
+        self.action(
+            "wait_for_element",
+            [
+                ("selector", Value::String(selector.into())),
+                ("timeout_seconds", Value::from(timeout.as_secs_f64())),
+            ],
+        )
+    }
+
+    /// Wait until the JavaScript expression `condition` is truthy
+    pub fn wait_for_condition(self, condition: impl Into<String>, timeout: Duration) -> Self {
+        self.action(
+            "wait_for_condition",
+            [
+                ("condition", Value::String(condition.into())),
+                ("timeout_seconds", Value::from(timeout.as_secs_f64())),
+            ],
+        )
+    }
+
+    pub fn get_title(self) -> Self {
+        self.action("get_title", [])
+    }
+
+    pub fn get_current_url(self) -> Self {
+        self.action("get_current_url", [])
+    }
+
+    pub fn get_text(self, selector: impl Into<String>) -> Self {
+        self.action("get_text", [("selector", Value::String(selector.into()))])
+    }
+
+    pub fn get_attribute(self, selector: impl Into<String>, attribute: impl Into<String>) -> Self {
+        self.action(
+            "get_attribute",
+            [("selector", Value::String(selector.into())), ("attribute", Value::String(attribute.into()))],
+        )
+    }
+
+    pub fn get_property(self, selector: impl Into<String>, property: impl Into<String>) -> Self {
+        self.action(
+            "get_property",
+            [("selector", Value::String(selector.into())), ("property", Value::String(property.into()))],
+        )
+    }
+
+    pub fn execute_script(self, script: impl Into<String>) -> Self {
+        self.action("execute_script", [("script", Value::String(script.into()))])
+    }
+
+    /// Store what the last `execute_script` step returns in the run-time
+    /// variable `name`, which later steps reference as `{{name}}`
+    pub fn store_as(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.modify_last("store_as", |step| {
+            step.arguments.insert("store_as".to_string(), Value::String(name));
+        })
+    }
+
+    pub fn resize_window(self, width: u32, height: u32) -> Self {
+        self.action("resize_window", [("width", Value::from(width)), ("height", Value::from(height))])
+    }
+
+    /// Capture the viewport to `save_path` on the server's machine
+    pub fn screenshot(self, save_path: impl Into<String>) -> Self {
+        self.action("screenshot", [("save_path", Value::String(save_path.into()))])
+    }
+
+    /// Capture the whole page, not just the viewport, to `save_path`
+    pub fn full_page_screenshot(self, save_path: impl Into<String>) -> Self {
+        self.action("screenshot", [("save_path", Value::String(save_path.into())), ("full_page", Value::Bool(true))])
+    }
+
+    /// Capture only the element matching `selector` to `save_path`
+    pub fn element_screenshot(self, selector: impl Into<String>, save_path: impl Into<String>) -> Self {
+        self.action(
+            "screenshot",
+            [("selector", Value::String(selector.into())), ("save_path", Value::String(save_path.into()))],
+        )
+    }
+
+    pub fn login_form(self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.action(
+            "login_form",
+            [("username", Value::String(username.into())), ("password", Value::String(password.into()))],
+        )
+    }
+
+    /// Fill fields in order, each as `(selector, value)`, then click `submit_selector`.
+    /// Values are applied as the element calls for: text typed, options
+    /// selected, `true`/`false` for checkboxes.
+    pub fn fill_and_submit_form<I, S, V>(self, fields: I, submit_selector: impl Into<String>) -> Self
+    where
+        I: IntoIterator<Item = (S, V)>,
+        S: Into<String>,
+        V: Into<Value>,
+    {
+        let fields = fields
+            .into_iter()
+            .map(|(selector, value)| {
+                let mut field = Map::new();
+                field.insert("selector".to_string(), Value::String(selector.into()));
+                field.insert("value".to_string(), value.into());
+                Value::Object(field)
+            })
+            .collect();
+        self.action(
+            "fill_and_submit_form",
+            [("fields", Value::Array(fields)), ("submit_selector", Value::String(submit_selector.into()))],
+        )
+    }
+
+    pub fn audit_accessibility(self) -> Self {
+        self.action("audit_accessibility", [])
+    }
+
+    /// Load `url` under each of `locales` and compare its strings with the first locale's
+    pub fn check_localization<S: Into<String>>(
+        self,
+        url: impl Into<String>,
+        locales: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let locales = locales.into_iter().map(|locale| Value::String(locale.into())).collect();
+        self.action("check_localization", [("url", Value::String(url.into())), ("locales", Value::Array(locales))])
+    }
+
+    /// Run another recipe by name; only valid inside [`RecipeBuilder::on_failure`]
+    pub fn run_recipe(self, name: impl Into<String>) -> Self {
+        self.action("run_recipe", [("name", Value::String(name.into()))])
+    }
+
+    /// Name the last step, as shown in execution results
+    pub fn named(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.modify_last("named", |step| step.name = Some(name))
+    }
+
+    /// Set an argument on the last step that has no typed parameter, e.g. `clear_first`
+    pub fn with_argument(self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        let (key, value) = (key.into(), value.into());
+        self.modify_last("with_argument", |step| {
+            step.arguments.insert(key, value);
+        })
+    }
+
+    /// Keep going when the last step fails
+    pub fn continue_on_error(self) -> Self {
+        self.modify_last("continue_on_error", |step| step.continue_on_error = Some(true))
+    }
+
+    /// Retry the last step up to `count` more times, `delay` apart
+    pub fn retry_step(self, count: u32, delay: Duration) -> Self {
+        self.modify_last("retry_step", |step| {
+            step.retry_count = Some(count);
+            step.retry_delay_ms = Some(delay.as_millis() as u64);
+        })
+    }
+
+    /// Only run the last step when `condition` holds
+    pub fn when(self, condition: impl Into<String>) -> Self {
+        let condition = condition.into();
+        self.modify_last("when", |step| step.condition = Some(condition))
+    }
+
+    /// Run the last step in session `session_id` instead of the recipe's
+    pub fn in_session(self, session_id: impl Into<String>) -> Self {
+        let session_id = session_id.into();
+        self.modify_last("in_session", |step| step.session_id = Some(session_id))
+    }
+
+    /// Only run the last step on `browser`
+    pub fn on_browser(self, browser: impl Into<String>) -> Self {
+        let browser = browser.into();
+        self.modify_last("on_browser", |step| step.browser = Some(browser))
+    }
+
+    /// Replace arguments of the last step when it runs on `browser`
+    pub fn override_on<const N: usize>(self, browser: impl Into<String>, arguments: [(&str, Value); N]) -> Self {
+        let browser = browser.into();
+        self.modify_last("override_on", |step| {
+            let overrides = step.browser_overrides.entry(browser).or_default();
+            overrides.extend(arguments.into_iter().map(|(key, value)| (key.to_string(), value)));
+        })
+    }
+
+    /// The validated recipe
+    pub fn build(self) -> Result<Recipe, String> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.recipe.validate()?;
+        Ok(self.recipe)
+    }
+
+    fn action<const N: usize>(self, action: &str, arguments: [(&str, Value); N]) -> Self {
+        let arguments = arguments.into_iter().map(|(key, value)| (key.to_string(), value)).collect();
+        self.step(RecipeStep::new(action, arguments))
+    }
+
+    fn modify_last(mut self, modifier: &str, modify: impl FnOnce(&mut RecipeStep)) -> Self {
+        match self.recipe.steps.last_mut() {
+            Some(step) => modify(step),
+            None if self.error.is_none() => {
+                self.error = Some(format!("{}() must follow a step", modifier));
+            }
+            None => {}
+        }
+        self
+    }
+}
+
+/// Run-time variables the executor fills in itself
+const BUILTIN_VARIABLES: [&str; 3] = ["browser", "step", "timestamp"];
+
+/// Whether `name` can be used as a parameter or variable name
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Replace `{{name}}` placeholders of run-time variables (`browser`, values
+/// stored by earlier steps) in every string of `value`
+pub fn substitute_runtime_variables(value: &mut Value, variables: &HashMap<String, String>) {
+    match value {
+        Value::String(text) if text.contains("{{") => {
+            for (name, variable) in variables {
+                *text = text.replace(&format!("{{{{{}}}}}", name), variable);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| substitute_runtime_variables(item, variables)),
+        Value::Object(fields) => fields.values_mut().for_each(|field| substitute_runtime_variables(field, variables)),
+        _ => {}
+    }
+}
+
+/// File name a `screenshot` step saves under, if it saves at all. `{{timestamp}}`
+/// in `save_path` becomes `timestamp`; `save: true` without a `save_path`, or a
+/// `save_path` ending in `/`, gets a generated `screenshot_<step>_<browser>_<timestamp>.png`.
+pub fn screenshot_file_name(
+    arguments: &Map<String, Value>,
+    variables: &HashMap<String, String>,
+    timestamp: &str,
+) -> Option<String> {
+    let generated = || {
+        let step = variables.get("step").map_or("step", String::as_str);
+        let browser = variables.get("browser").map_or("browser", String::as_str);
+        format!("screenshot_{}_{}_{}.png", step, browser, timestamp)
+    };
+    match arguments.get("save_path").and_then(|v| v.as_str()) {
+        Some(dir) if dir.ends_with('/') => Some(format!("{}{}", dir, generated())),
+        Some(path) => Some(path.replace("{{timestamp}}", timestamp)),
+        None if arguments.get("save").and_then(|v| v.as_bool()) == Some(true) => Some(generated()),
+        None => None,
+    }
+}
+
+/// Variable value for a step result holding JSON: strings as-is, anything else as JSON text
+pub fn variable_from_json(result: &str) -> String {
+    match serde_json::from_str(result) {
+        Ok(Value::String(text)) => text,
+        _ => result.to_string(),
     }
 }
 
@@ -186,6 +1019,9 @@ mod tests {
             created_at: None,
             parameters: None,
             browsers: vec!["auto".to_string()],
+            retry: None,
+            on_failure: Vec::new(),
+            isolation: None,
             steps: vec![
                 RecipeStep {
                     name: Some("Navigate to page".to_string()),
@@ -202,6 +1038,7 @@ mod tests {
                     condition: None,
                     session_id: None,
                     browser: None,
+                    browser_overrides: HashMap::new(),
                 }
             ],
         };
@@ -223,6 +1060,9 @@ mod tests {
             created_at: None,
             parameters: None,
             browsers: vec!["auto".to_string()],
+            retry: None,
+            on_failure: Vec::new(),
+            isolation: None,
             steps: vec![
                 RecipeStep {
                     name: None,
@@ -239,6 +1079,7 @@ mod tests {
                     condition: None,
                     session_id: None,
                     browser: None,
+                    browser_overrides: HashMap::new(),
                 }
             ],
         };
@@ -250,4 +1091,212 @@ mod tests {
         let url = substituted.steps[0].arguments.get("url").unwrap().as_str().unwrap();
         assert_eq!(url, "https://example.com/login");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parameter_schema_enforcement() {
+        let recipe = Recipe::from_json(r#"{
+            "name": "search",
+            "version": "1.0.0",
+            "parameters": {
+                "base_url": {"type": "url", "required": true, "description": "Site root"},
+                "max_results": {"type": "integer", "default_value": "10"},
+                "engine": {"allowed_values": ["google", "bing"], "default_value": "bing"}
+            },
+            "steps": [{"action": "navigate", "arguments": {"url": "${base_url}/search"}}]
+        }"#).unwrap();
+
+        assert!(recipe.validate().is_ok());
+
+        // Missing required parameter
+        assert!(recipe.resolve_parameters(&HashMap::new()).is_err());
+
+        let mut provided = HashMap::new();
+        provided.insert("base_url".to_string(), "https://example.com".to_string());
+        let resolved = recipe.resolve_parameters(&provided).unwrap();
+        assert_eq!(resolved.get("max_results").map(String::as_str), Some("10"));
+        assert_eq!(resolved.get("engine").map(String::as_str), Some("bing"));
+
+        // Wrong type, disallowed value and undeclared parameter
+        provided.insert("max_results".to_string(), "lots".to_string());
+        assert!(recipe.resolve_parameters(&provided).is_err());
+        provided.insert("max_results".to_string(), "5".to_string());
+        provided.insert("engine".to_string(), "yahoo".to_string());
+        assert!(recipe.resolve_parameters(&provided).is_err());
+        provided.insert("engine".to_string(), "google".to_string());
+        provided.insert("unknown".to_string(), "x".to_string());
+        assert!(recipe.resolve_parameters(&provided).is_err());
+
+        let schema = recipe.parameters_schema();
+        assert_eq!(schema["properties"]["base_url"]["format"], "uri");
+        assert_eq!(schema["required"], serde_json::json!(["base_url"]));
+        assert!(recipe.tool_description().contains("base_url (url, required)"));
+    }
+
+    #[test]
+    fn test_retry_policy_and_on_failure_parsing() {
+        let recipe = Recipe::from_json(r#"{
+            "name": "flaky",
+            "version": "1.0.0",
+            "steps": [{"action": "navigate", "arguments": {"url": "https://example.com"}}],
+            "retry": {"count": 2, "delay_ms": 500, "backoff": "exponential"},
+            "on_failure": [
+                {"action": "screenshot", "arguments": {"save_path": "failure_{{browser}}.png"}},
+                {"action": "run_recipe", "arguments": {"name": "cleanup"}}
+            ]
+        }"#).unwrap();
+
+        assert!(recipe.validate().is_ok());
+        let retry = recipe.retry.as_ref().unwrap();
+        assert_eq!(retry.backoff, RetryBackoff::Exponential);
+        assert_eq!(retry.delay_for_attempt(1), Duration::from_millis(500));
+        assert_eq!(retry.delay_for_attempt(3), Duration::from_millis(2000));
+        assert_eq!(recipe.on_failure.len(), 2);
+
+        let mut invalid = recipe.clone();
+        invalid.on_failure[1].arguments.clear();
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_recipe_builder() {
+        let recipe = Recipe::builder("checkout")
+            .browsers(["chrome", "firefox"])
+            .retry(RetryPolicy { count: 1, delay_ms: 100, backoff: RetryBackoff::Fixed })
+            .navigate("${base_url}/cart")
+            .wait_for_timeout("#checkout", Duration::from_millis(2500))
+            .fill_and_submit_form([("#email", Value::from("a@example.com")), ("#terms", Value::from(true))], "#pay")
+            .named("Pay")
+            .retry_step(2, Duration::from_secs(1))
+            .screenshot("done.png")
+            .on_browser("chrome")
+            .on_failure(|hook| hook.screenshot("failure.png").run_recipe("cleanup"))
+            .build()
+            .unwrap();
+
+        assert_eq!(recipe.version, "1.0.0");
+        assert_eq!(recipe.steps.len(), 4);
+        assert_eq!(recipe.steps[1].arguments["timeout_seconds"], 2.5);
+        assert_eq!(recipe.steps[2].name.as_deref(), Some("Pay"));
+        assert_eq!(recipe.steps[2].retry_delay_ms, Some(1000));
+        assert_eq!(recipe.steps[2].arguments["fields"][1]["value"], true);
+        assert_eq!(recipe.steps[3].browser.as_deref(), Some("chrome"));
+        assert_eq!(recipe.on_failure[1].action, "run_recipe");
+
+        // Round-trips through the JSON format recipe files use
+        let reloaded = Recipe::from_json(&recipe.to_json().unwrap()).unwrap();
+        assert!(reloaded.validate().is_ok());
+        assert_eq!(reloaded.steps[2].arguments, recipe.steps[2].arguments);
+
+        let localized = Recipe::builder("i18n").check_localization("${base_url}", ["en-US", "de-DE"]).build().unwrap();
+        assert_eq!(localized.steps[0].arguments["locales"][1], "de-DE");
+
+        assert!(Recipe::builder("empty").build().is_err());
+        assert!(Recipe::builder("early").named("first").navigate("https://example.com").build().is_err());
+        assert!(Recipe::builder("bad").navigate("https://example.com").on_browser("safari").build().is_err());
+    }
+
+    #[test]
+    fn test_script_results_feed_later_steps() {
+        let recipe = Recipe::builder("extract")
+            .navigate("https://example.com")
+            .execute_script("return document.querySelectorAll('a').length")
+            .store_as("links")
+            .navigate("https://example.com/?links={{links}}")
+            .build()
+            .unwrap();
+        assert_eq!(recipe.steps[1].arguments["store_as"], "links");
+        assert!(Recipe::builder("bad").execute_script("return 1").store_as("browser").build().is_err());
+        assert!(Recipe::builder("bad").execute_script("return 1").store_as("a-b").build().is_err());
+
+        let mut variables = HashMap::new();
+        variables.insert("browser".to_string(), "firefox".to_string());
+        variables.insert("links".to_string(), variable_from_json("12"));
+        variables.insert("title".to_string(), variable_from_json(&serde_json::json!("Say \"hi\"").to_string()));
+        let mut arguments = serde_json::json!({
+            "url": "https://example.com/?links={{links}}",
+            "fields": [{ "value": "{{title}} on {{browser}}" }],
+            "missing": "{{unknown}}",
+        });
+        substitute_runtime_variables(&mut arguments, &variables);
+        assert_eq!(arguments["url"], "https://example.com/?links=12");
+        assert_eq!(arguments["fields"][0]["value"], "Say \"hi\" on firefox");
+        assert_eq!(arguments["missing"], "{{unknown}}");
+        assert_eq!(variable_from_json(r#"{"a":1}"#), r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn test_browser_overrides() {
+        let recipe = Recipe::builder("search")
+            .click("#go")
+            .override_on("firefox", [("selector", Value::from("${button}"))])
+            .build()
+            .unwrap();
+        let parameters = HashMap::from([("button".to_string(), "button.go".to_string())]);
+        let recipe = recipe.substitute_parameters(&parameters).unwrap();
+        let step = &recipe.steps[0];
+        assert_eq!(step.for_browser("firefox").arguments["selector"], "button.go");
+        assert_eq!(step.for_browser("chrome").arguments["selector"], "#go");
+        assert!(matches!(step.for_browser("chrome"), Cow::Borrowed(_)));
+
+        let json = serde_json::to_value(&recipe).unwrap();
+        assert_eq!(json["steps"][0]["browser_overrides"]["firefox"]["selector"], "button.go");
+
+        let unsupported = Recipe::builder("bad").click("#go").override_on("safari", [("selector", Value::from("a"))]);
+        assert!(unsupported.build().is_err());
+        let pinned = Recipe::builder("bad")
+            .click("#go")
+            .on_browser("chrome")
+            .override_on("firefox", [("selector", Value::from("a"))])
+            .build();
+        assert!(pinned.unwrap_err().contains("only runs on chrome"));
+    }
+
+    #[test]
+    fn test_injected_variables_are_checked() {
+        let recipe = Recipe::builder("tenant")
+            .parameter("base_url", ParameterDefinition {
+                description: None,
+                default_value: None,
+                required: true,
+                param_type: ParameterType::Url,
+                allowed_values: None,
+            })
+            .navigate("${base_url}/t/{{tenant_id}}")
+            .build()
+            .unwrap();
+        let variables = |name: &str| HashMap::from([(name.to_string(), "acme".to_string())]);
+        assert!(recipe.check_variables(&variables("tenant_id")).is_ok());
+        assert!(recipe.check_variables(&variables("browser")).unwrap_err().contains("set by the executor"));
+        assert!(recipe.check_variables(&variables("base_url")).unwrap_err().contains("declared parameter"));
+        assert!(recipe.check_variables(&variables("tenant-id")).is_err());
+    }
+
+    #[test]
+    fn test_screenshot_file_names() {
+        let recipe = Recipe::builder("shots")
+            .full_page_screenshot("{{browser}}/page_{{step}}.png")
+            .element_screenshot("#cart", "shots/")
+            .build()
+            .unwrap();
+        let mut variables = HashMap::new();
+        variables.insert("browser".to_string(), "edge".to_string());
+        variables.insert("step".to_string(), "2".to_string());
+        let name = |arguments: &Map<String, Value>| screenshot_file_name(arguments, &variables, "20260101T000000000");
+
+        let mut first = serde_json::json!(recipe.steps[0].arguments);
+        substitute_runtime_variables(&mut first, &variables);
+        assert_eq!(name(first.as_object().unwrap()).as_deref(), Some("edge/page_2.png"));
+        assert_eq!(
+            name(&recipe.steps[1].arguments).as_deref(),
+            Some("shots/screenshot_2_edge_20260101T000000000.png")
+        );
+        let save = serde_json::json!({ "save": true });
+        assert_eq!(name(save.as_object().unwrap()).as_deref(), Some("screenshot_2_edge_20260101T000000000.png"));
+        let stamped = serde_json::json!({ "save_path": "run_{{timestamp}}.png" });
+        assert_eq!(name(stamped.as_object().unwrap()).as_deref(), Some("run_20260101T000000000.png"));
+        assert_eq!(name(&Map::new()), None);
+
+        let both = Recipe::builder("bad").full_page_screenshot("a.png").with_argument("selector", "#a").build();
+        assert!(both.is_err());
+    }
+}
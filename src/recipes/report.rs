@@ -0,0 +1,435 @@
+//! Human-readable reports of recipe runs
+//!
+//! With a report format chosen, a recipe run ends by writing `report.html` or
+//! `report.md` next to its screenshots in the run's artifacts directory: the
+//! outcome, every step per browser with its timing, the screenshots it saved,
+//! console errors and the environment it ran in, so the run can be reviewed
+//! without an MCP client.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::recipes::execution::{ExecutionResult, StepResult};
+use crate::recipes::recipe::Recipe;
+
+/// Longest step result shown in a report, in characters
+const MAX_RESULT_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "html" => Some(Self::Html),
+            "markdown" | "md" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    /// Name of the report file in the run's artifacts directory
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Html => "report.html",
+            Self::Markdown => "report.md",
+        }
+    }
+}
+
+/// Where and how a run happened
+#[derive(Debug, Clone)]
+pub struct ReportEnvironment {
+    pub started_at: DateTime<Utc>,
+    pub server_version: &'static str,
+    /// Operating system and architecture of the server
+    pub platform: String,
+    pub headless: bool,
+    /// Driver version each browser's endpoint reported, when known
+    pub driver_versions: BTreeMap<String, String>,
+}
+
+/// An image the run saved
+#[derive(Debug, Clone)]
+pub struct ReportScreenshot {
+    /// Path as step results mention it
+    pub saved_as: String,
+    /// Link relative to the report file
+    pub link: String,
+}
+
+/// Everything a report shows about one run
+pub struct RunReport<'a> {
+    pub recipe: &'a Recipe,
+    pub result: &'a ExecutionResult,
+    pub environment: ReportEnvironment,
+    /// Console errors each browser logged during the run
+    pub console_errors: BTreeMap<String, Vec<Value>>,
+    pub screenshots: Vec<ReportScreenshot>,
+}
+
+/// One table row: a step or hook result and the screenshots it saved
+struct Row<'a> {
+    label: String,
+    step: &'a StepResult,
+    screenshots: Vec<&'a ReportScreenshot>,
+}
+
+impl RunReport<'_> {
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Html => self.render_html(),
+            ReportFormat::Markdown => self.render_markdown(),
+        }
+    }
+
+    fn title(&self) -> String {
+        format!("Recipe report: {}", self.recipe.name)
+    }
+
+    fn outcome(&self) -> &'static str {
+        if self.result.success { "PASSED" } else { "FAILED" }
+    }
+
+    /// Browsers in name order with their step and hook rows
+    fn browser_rows(&self) -> Vec<(&str, bool, Vec<Row<'_>>)> {
+        let mut browsers: Vec<_> = self.result.browser_results.values().collect();
+        browsers.sort_by(|a, b| a.browser.cmp(&b.browser));
+        browsers
+            .into_iter()
+            .map(|browser| {
+                let steps = browser.step_results.iter().map(|step| {
+                    let label = step.step_name.clone().unwrap_or_else(|| format!("Step {}", step.step_index + 1));
+                    self.row(label, step)
+                });
+                let hooks = browser.on_failure_results.iter().map(|hook| {
+                    self.row(format!("on_failure hook {}", hook.step_index + 1), hook)
+                });
+                (browser.browser.as_str(), browser.success, steps.chain(hooks).collect())
+            })
+            .collect()
+    }
+
+    fn row<'a>(&'a self, label: String, step: &'a StepResult) -> Row<'a> {
+        let screenshots = self
+            .screenshots
+            .iter()
+            .filter(|shot| step.result.as_deref().is_some_and(|result| result.contains(&shot.saved_as)))
+            .collect();
+        Row { label, step, screenshots }
+    }
+
+    /// Screenshots no step result mentions, e.g. ones taken by a nested recipe
+    fn unmatched_screenshots(&self) -> Vec<&ReportScreenshot> {
+        let mentioned = |shot: &ReportScreenshot| {
+            self.result.browser_results.values().any(|browser| {
+                browser.step_results.iter().chain(&browser.on_failure_results).any(|step| {
+                    step.result.as_deref().is_some_and(|result| result.contains(&shot.saved_as))
+                })
+            })
+        };
+        self.screenshots.iter().filter(|shot| !mentioned(shot)).collect()
+    }
+
+    fn environment_lines(&self) -> Vec<(String, String)> {
+        let environment = &self.environment;
+        let mut lines = vec![
+            ("Started".to_string(), environment.started_at.to_rfc3339()),
+            ("Server".to_string(), format!("rust-browser-mcp {}", environment.server_version)),
+            ("Platform".to_string(), environment.platform.clone()),
+            ("Headless".to_string(), environment.headless.to_string()),
+        ];
+        for (browser, version) in &environment.driver_versions {
+            lines.push((format!("{browser} driver"), version.clone()));
+        }
+        lines
+    }
+
+    fn render_markdown(&self) -> String {
+        let result = self.result;
+        let mut out = format!("# {}\n\n", self.title());
+        if let Some(description) = &self.recipe.description {
+            out.push_str(&format!("{}\n\n", markdown_cell(description)));
+        }
+        out.push_str(&format!(
+            "**{}**: {}/{} steps executed, {} failed, {} ms, {} attempt(s)\n\n",
+            self.outcome(),
+            result.executed_steps,
+            result.total_steps,
+            result.failed_steps,
+            result.execution_time_ms,
+            result.attempts
+        ));
+        if let Some(error) = &result.error_message {
+            out.push_str(&format!("Error: {}\n\n", markdown_cell(error)));
+        }
+
+        out.push_str("## Environment\n\n");
+        for (name, value) in self.environment_lines() {
+            out.push_str(&format!("- {}: {}\n", name, markdown_cell(&value)));
+        }
+
+        for (browser, success, rows) in self.browser_rows() {
+            let outcome = if success { "passed" } else { "failed" };
+            out.push_str(&format!("\n## {browser} ({outcome})\n\n"));
+            out.push_str("| # | Step | Action | Status | Time (ms) | Attempts | Details |\n");
+            out.push_str("|---|------|--------|--------|-----------|----------|---------|\n");
+            let mut images = Vec::new();
+            for (index, row) in rows.iter().enumerate() {
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} | {} |\n",
+                    index + 1,
+                    markdown_cell(&row.label),
+                    markdown_cell(&row.step.action),
+                    row.step.status_label(),
+                    row.step.execution_time_ms,
+                    row.step.attempts,
+                    markdown_cell(&details(row.step))
+                ));
+                images.extend(row.screenshots.iter().map(|shot| (row.label.as_str(), *shot)));
+            }
+            for (label, shot) in images {
+                out.push_str(&format!("\n{}:\n\n![{}]({})\n", markdown_cell(label), markdown_cell(label), shot.link));
+            }
+
+            if let Some(errors) = self.console_errors.get(browser).filter(|errors| !errors.is_empty()) {
+                out.push_str("\n### Console errors\n\n");
+                for error in errors {
+                    out.push_str(&format!("- {}\n", markdown_cell(&console_line(error))));
+                }
+            }
+        }
+
+        let unmatched = self.unmatched_screenshots();
+        if !unmatched.is_empty() {
+            out.push_str("\n## Other screenshots\n");
+            for shot in unmatched {
+                out.push_str(&format!("\n![{}]({})\n", markdown_cell(&shot.link), shot.link));
+            }
+        }
+        out
+    }
+
+    fn render_html(&self) -> String {
+        let result = self.result;
+        let title = escape_html(&self.title());
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+             body {{ font-family: sans-serif; margin: 2em; color: #222; }}\n\
+             table {{ border-collapse: collapse; width: 100%; margin-bottom: 1em; }}\n\
+             th, td {{ border: 1px solid #ccc; padding: 0.4em; text-align: left; vertical-align: top; }}\n\
+             .SUCCESS, .passed {{ color: #1a7f37; }}\n\
+             .FAILED, .failed {{ color: #cf222e; }}\n\
+             .SKIPPED {{ color: #777; }}\n\
+             img {{ max-width: 480px; border: 1px solid #ccc; display: block; margin-top: 0.4em; }}\n\
+             </style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+        );
+        if let Some(description) = &self.recipe.description {
+            out.push_str(&format!("<p>{}</p>\n", escape_html(description)));
+        }
+        let outcome_class = if result.success { "passed" } else { "failed" };
+        out.push_str(&format!(
+            "<p><strong class=\"{outcome_class}\">{}</strong>: \
+             {}/{} steps executed, {} failed, {} ms, {} attempt(s)</p>\n",
+            self.outcome(),
+            result.executed_steps,
+            result.total_steps,
+            result.failed_steps,
+            result.execution_time_ms,
+            result.attempts
+        ));
+        if let Some(error) = &result.error_message {
+            out.push_str(&format!("<p class=\"failed\">Error: {}</p>\n", escape_html(error)));
+        }
+
+        out.push_str("<h2>Environment</h2>\n<ul>\n");
+        for (name, value) in self.environment_lines() {
+            out.push_str(&format!("<li>{}: {}</li>\n", escape_html(&name), escape_html(&value)));
+        }
+        out.push_str("</ul>\n");
+
+        for (browser, success, rows) in self.browser_rows() {
+            let outcome = if success { "passed" } else { "failed" };
+            out.push_str(&format!(
+                "<h2>{} <span class=\"{outcome}\">({outcome})</span></h2>\n<table>\n\
+                 <tr><th>#</th><th>Step</th><th>Action</th><th>Status</th><th>Time (ms)</th>\
+                 <th>Attempts</th><th>Details</th></tr>\n",
+                escape_html(browser)
+            ));
+            for (index, row) in rows.iter().enumerate() {
+                let status = row.step.status_label();
+                let mut details_cell = escape_html(&details(row.step));
+                for shot in &row.screenshots {
+                    let link = escape_html(&shot.link);
+                    details_cell.push_str(&format!("<a href=\"{link}\"><img src=\"{link}\" alt=\"{link}\"></a>"));
+                }
+                out.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"{status}\">{status}</td><td>{}</td>\
+                     <td>{}</td><td>{details_cell}</td></tr>\n",
+                    index + 1,
+                    escape_html(&row.label),
+                    escape_html(&row.step.action),
+                    row.step.execution_time_ms,
+                    row.step.attempts
+                ));
+            }
+            out.push_str("</table>\n");
+
+            if let Some(errors) = self.console_errors.get(browser).filter(|errors| !errors.is_empty()) {
+                out.push_str("<h3>Console errors</h3>\n<ul>\n");
+                for error in errors {
+                    out.push_str(&format!("<li><code>{}</code></li>\n", escape_html(&console_line(error))));
+                }
+                out.push_str("</ul>\n");
+            }
+        }
+
+        let unmatched = self.unmatched_screenshots();
+        if !unmatched.is_empty() {
+            out.push_str("<h2>Other screenshots</h2>\n");
+            for shot in unmatched {
+                let link = escape_html(&shot.link);
+                out.push_str(&format!("<p>{link}<a href=\"{link}\"><img src=\"{link}\" alt=\"{link}\"></a></p>\n"));
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// Error, skip reason or shortened result of a step
+fn details(step: &StepResult) -> String {
+    if let Some(error) = &step.error_message {
+        return error.clone();
+    }
+    if let Some(reason) = &step.skipped_reason {
+        return reason.clone();
+    }
+    let result = step.result.as_deref().unwrap_or_default();
+    if result.chars().count() > MAX_RESULT_CHARS {
+        format!("{}…", result.chars().take(MAX_RESULT_CHARS).collect::<String>())
+    } else {
+        result.to_string()
+    }
+}
+
+/// A console entry as `message (url)`
+fn console_line(entry: &Value) -> String {
+    let message = entry["message"].as_str().unwrap_or_default();
+    match entry["url"].as_str() {
+        Some(url) => format!("{message} ({url})"),
+        None => message.to_string(),
+    }
+}
+
+/// Text that is safe inside a Markdown table cell or list item
+fn markdown_cell(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::recipes::execution::BrowserExecutionResult;
+    use serde_json::json;
+
+    fn step(index: usize, action: &str, success: bool, result: Option<&str>, error: Option<&str>) -> StepResult {
+        StepResult {
+            step_index: index,
+            step_name: None,
+            action: action.to_string(),
+            success,
+            execution_time_ms: 12,
+            attempts: 1,
+            result: result.map(str::to_string),
+            error_message: error.map(str::to_string),
+            skipped: false,
+            skipped_reason: None,
+            browser: Some("firefox".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_report_renders_steps_screenshots_and_console_errors() {
+        let recipe = Recipe::builder("checkout").navigate("https://example.com").screenshot("cart.png");
+        let recipe = recipe.build().unwrap();
+        let steps = vec![
+            step(0, "navigate", true, Some("Successfully navigated to https://example.com"), None),
+            step(1, "screenshot", false, Some("Screenshot saved to: artifacts/run/recipe/cart.png (10 bytes)"), None),
+            step(2, "click", false, None, Some("Element not found: #pay|now")),
+        ];
+        let result = ExecutionResult {
+            success: false,
+            total_steps: 3,
+            executed_steps: 3,
+            failed_steps: 1,
+            step_results: steps.clone(),
+            execution_time_ms: 40,
+            error_message: Some("Step 3 failed".to_string()),
+            browser_results: HashMap::from([(
+                "firefox".to_string(),
+                BrowserExecutionResult {
+                    browser: "firefox".to_string(),
+                    success: false,
+                    executed_steps: 3,
+                    failed_steps: 1,
+                    step_results: steps,
+                    execution_time_ms: 40,
+                    error_message: Some("Step 3 failed".to_string()),
+                    attempts: 1,
+                    on_failure_results: Vec::new(),
+                },
+            )]),
+            attempts: 1,
+            on_failure_results: Vec::new(),
+            report_path: None,
+        };
+        let report = RunReport {
+            recipe: &recipe,
+            result: &result,
+            environment: ReportEnvironment {
+                started_at: Utc::now(),
+                server_version: "0.0.0",
+                platform: "linux/x86_64".to_string(),
+                headless: true,
+                driver_versions: BTreeMap::from([("firefox".to_string(), "0.35.0".to_string())]),
+            },
+            console_errors: BTreeMap::from([(
+                "firefox".to_string(),
+                vec![json!({ "level": "error", "message": "<b>boom</b>", "url": "https://example.com/" })],
+            )]),
+            screenshots: ["cart.png", "extra.png"]
+                .map(|link| ReportScreenshot {
+                    saved_as: format!("artifacts/run/recipe/{link}"),
+                    link: link.to_string(),
+                })
+                .to_vec(),
+        };
+
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.starts_with("# Recipe report: checkout"));
+        assert!(markdown.contains("**FAILED**: 3/3 steps executed"));
+        assert!(markdown.contains("| 3 | Step 3 | click | FAILED | 12 | 1 | Element not found: #pay\\|now |"));
+        assert!(markdown.contains("![Step 2](cart.png)"));
+        assert!(markdown.contains("## Other screenshots\n\n![extra.png](extra.png)"));
+        assert!(markdown.contains("- <b>boom</b> (https://example.com/)"));
+        assert!(markdown.contains("- firefox driver: 0.35.0"));
+
+        let html = report.render(ReportFormat::Html);
+        assert!(html.contains("<img src=\"cart.png\""));
+        assert!(html.contains("&lt;b&gt;boom&lt;/b&gt;"));
+        assert!(html.contains("<td class=\"FAILED\">FAILED</td>"));
+        assert_eq!(ReportFormat::from_name("MD"), Some(ReportFormat::Markdown));
+        assert_eq!(ReportFormat::from_name("pdf"), None);
+    }
+}
@@ -0,0 +1,329 @@
+//! Captured browser outputs exposed as MCP resources
+//!
+//! Screenshots, recipe artifacts and console log snapshots are registered here
+//! under a `browser://` URI so clients can list and read them on demand instead
+//! of receiving large payloads inline in tool results. Each resource belongs to
+//! the principal whose tool call captured it, and only they can list or read it.
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use base64::{Engine as _, engine::general_purpose};
+use rmcp::model::{AnnotateAble, RawResource, Resource, ResourceContents};
+
+use crate::error::{Result, WebDriverError};
+
+/// URI scheme used for every resource served by this crate
+pub const RESOURCE_URI_SCHEME: &str = "browser://";
+
+/// Default number of resources retained before the oldest are evicted
+pub const DEFAULT_MAX_RESOURCES: usize = 100;
+
+/// Category of a stored resource; determines its URI prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Screenshot,
+    Artifact,
+    ConsoleLog,
+}
+
+impl ResourceKind {
+    fn uri_segment(self) -> &'static str {
+        match self {
+            Self::Screenshot => "screenshots",
+            Self::Artifact => "artifacts",
+            Self::ConsoleLog => "console",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ResourceData {
+    Bytes(Vec<u8>),
+    Text(String),
+    /// Contents stay on disk and are read when the resource is requested
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+struct StoredResource {
+    /// Principal of the tool call that captured it; `None` in the shared namespace
+    owner: Option<String>,
+    uri: String,
+    name: String,
+    description: String,
+    mime_type: String,
+    size: Option<usize>,
+    data: ResourceData,
+}
+
+/// Bounded, shareable registry of captured resources
+#[derive(Debug, Clone)]
+pub struct ResourceStore {
+    entries: Arc<Mutex<VecDeque<StoredResource>>>,
+    max_entries: usize,
+}
+
+impl Default for ResourceStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_RESOURCES)
+    }
+}
+
+impl ResourceStore {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::new())),
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    /// Store a PNG screenshot taken in `session_id` and return its URI
+    pub fn add_screenshot(&self, session_id: &str, png_data: Vec<u8>) -> String {
+        let id = Self::new_id();
+        let uri = format!("{RESOURCE_URI_SCHEME}screenshots/{id}.png");
+        self.insert(StoredResource {
+            owner: crate::ClientManager::current_principal(),
+            uri: uri.clone(),
+            name: format!("screenshot-{id}.png"),
+            description: format!("Screenshot of session '{session_id}'"),
+            mime_type: "image/png".to_string(),
+            size: Some(png_data.len()),
+            data: ResourceData::Bytes(png_data),
+        });
+        uri
+    }
+
+    /// Store a JSON snapshot of the console logs captured in `session_id`
+    pub fn add_console_log(&self, session_id: &str, logs: &serde_json::Value) -> String {
+        let id = Self::new_id();
+        let uri = format!("{RESOURCE_URI_SCHEME}console/{}/{id}.json", Self::sanitize(session_id));
+        let text = serde_json::to_string_pretty(logs).unwrap_or_else(|_| logs.to_string());
+        self.insert(StoredResource {
+            owner: crate::ClientManager::current_principal(),
+            uri: uri.clone(),
+            name: format!("console-{session_id}-{id}.json"),
+            description: format!("Console log snapshot of session '{session_id}'"),
+            mime_type: "application/json".to_string(),
+            size: Some(text.len()),
+            data: ResourceData::Text(text),
+        });
+        uri
+    }
+
+    /// Store an in-memory artifact such as an unsaved recipe screenshot
+    pub fn add_bytes(
+        &self,
+        kind: ResourceKind,
+        name: &str,
+        description: impl Into<String>,
+        data: Vec<u8>,
+    ) -> String {
+        let id = Self::new_id();
+        let name = Self::sanitize(name);
+        let uri = format!("{RESOURCE_URI_SCHEME}{}/{id}/{name}", kind.uri_segment());
+        self.insert(StoredResource {
+            owner: crate::ClientManager::current_principal(),
+            uri: uri.clone(),
+            mime_type: Self::mime_type_for(&name).to_string(),
+            name,
+            description: description.into(),
+            size: Some(data.len()),
+            data: ResourceData::Bytes(data),
+        });
+        uri
+    }
+
+    /// Register a file on disk (saved screenshot, recipe artifact, ...) without
+    /// loading it into memory
+    pub fn add_file(
+        &self,
+        kind: ResourceKind,
+        path: impl AsRef<Path>,
+        description: impl Into<String>,
+    ) -> String {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "file".to_string());
+        let id = Self::new_id();
+        let name = Self::sanitize(&file_name);
+        let uri = format!("{RESOURCE_URI_SCHEME}{}/{id}/{name}", kind.uri_segment());
+        self.insert(StoredResource {
+            owner: crate::ClientManager::current_principal(),
+            uri: uri.clone(),
+            mime_type: Self::mime_type_for(&name).to_string(),
+            name,
+            description: description.into(),
+            size: std::fs::metadata(path).ok().map(|m| m.len() as usize),
+            data: ResourceData::File(path.to_path_buf()),
+        });
+        uri
+    }
+
+    /// List the resources of `principal`, newest first
+    pub fn list(&self, principal: Option<&str>) -> Vec<Resource> {
+        self.lock()
+            .iter()
+            .rev()
+            .filter(|entry| entry.owner.as_deref() == principal)
+            .map(|entry| {
+                let mut raw = RawResource::new(entry.uri.clone(), entry.name.clone());
+                raw.description = Some(entry.description.clone());
+                raw.mime_type = Some(entry.mime_type.clone());
+                raw.size = entry.size.and_then(|s| u32::try_from(s).ok());
+                raw.no_annotation()
+            })
+            .collect()
+    }
+
+    /// Read a resource of `principal` by URI. Returns `NotFound` for unknown or
+    /// evicted URIs and for other principals' resources.
+    pub fn read(&self, uri: &str, principal: Option<&str>) -> Result<ResourceContents> {
+        let entry = self
+            .lock()
+            .iter()
+            .find(|entry| entry.uri == uri && entry.owner.as_deref() == principal)
+            .cloned()
+            .ok_or_else(|| WebDriverError::NotFound(format!("Resource '{uri}'")))?;
+
+        let bytes = match entry.data {
+            ResourceData::Text(text) => {
+                return Ok(ResourceContents::TextResourceContents {
+                    uri: entry.uri,
+                    mime_type: Some(entry.mime_type),
+                    text,
+                });
+            }
+            ResourceData::Bytes(bytes) => bytes,
+            ResourceData::File(path) => std::fs::read(&path).map_err(|e| {
+                WebDriverError::FileSystem(format!("Failed to read {}: {e}", path.display()))
+            })?,
+        };
+
+        let bytes = if Self::is_text_mime(&entry.mime_type) {
+            match String::from_utf8(bytes) {
+                Ok(text) => {
+                    return Ok(ResourceContents::TextResourceContents {
+                        uri: entry.uri,
+                        mime_type: Some(entry.mime_type),
+                        text,
+                    });
+                }
+                Err(e) => e.into_bytes(),
+            }
+        } else {
+            bytes
+        };
+
+        Ok(ResourceContents::BlobResourceContents {
+            uri: entry.uri,
+            mime_type: Some(entry.mime_type),
+            blob: general_purpose::STANDARD.encode(bytes),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn insert(&self, entry: StoredResource) {
+        let mut entries = self.lock();
+        entries.push_back(entry);
+        while entries.len() > self.max_entries {
+            entries.pop_front();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<StoredResource>> {
+        // A poisoned lock only means another thread panicked mid-insert;
+        // the queue itself is still usable.
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn new_id() -> String {
+        uuid::Uuid::new_v4().simple().to_string()
+    }
+
+    fn sanitize(value: &str) -> String {
+        value
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+            .collect()
+    }
+
+    fn mime_type_for(name: &str) -> &'static str {
+        let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+        match extension.as_deref() {
+            Some("png") => "image/png",
+            Some("jpg" | "jpeg") => "image/jpeg",
+            Some("json" | "har") => "application/json",
+            Some("html" | "htm") => "text/html",
+            Some("txt" | "log") => "text/plain",
+            _ => "application/octet-stream",
+        }
+    }
+
+    fn is_text_mime(mime_type: &str) -> bool {
+        mime_type.starts_with("text/") || mime_type == "application/json"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_list_read_and_evict() {
+        let store = ResourceStore::new(2);
+
+        let png = b"\x89PNG\r\n\x1a\nrest".to_vec();
+        let screenshot_uri = store.add_screenshot("default", png.clone());
+        assert!(screenshot_uri.starts_with("browser://screenshots/"));
+
+        match store.read(&screenshot_uri, None).unwrap() {
+            ResourceContents::BlobResourceContents { blob, mime_type, .. } => {
+                assert_eq!(mime_type.as_deref(), Some("image/png"));
+                assert_eq!(general_purpose::STANDARD.decode(blob).unwrap(), png);
+            }
+            other => panic!("expected blob contents, got {other:?}"),
+        }
+
+        let logs_uri = store.add_console_log("my session", &serde_json::json!([{"level": "error"}]));
+        assert!(logs_uri.starts_with("browser://console/my_session/"));
+        assert!(matches!(
+            store.read(&logs_uri, None).unwrap(),
+            ResourceContents::TextResourceContents { .. }
+        ));
+
+        let listed = store.list(None);
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].raw.uri, logs_uri);
+
+        // Capacity is 2, so the screenshot is evicted
+        store.add_bytes(ResourceKind::Artifact, "report.txt", "Recipe report", b"ok".to_vec());
+        assert_eq!(store.len(), 2);
+        assert!(matches!(store.read(&screenshot_uri, None), Err(WebDriverError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_resources_are_private_to_their_principal() {
+        let store = ResourceStore::default();
+        let alice = Some("alice".to_string());
+        let uri = crate::ClientManager::with_principal(alice, async { store.add_screenshot("default", vec![1]) }).await;
+
+        assert_eq!(store.list(Some("alice")).len(), 1);
+        assert!(store.read(&uri, Some("alice")).is_ok());
+        assert!(store.list(Some("bob")).is_empty());
+        assert!(matches!(store.read(&uri, Some("bob")), Err(WebDriverError::NotFound(_))));
+        assert!(store.list(None).is_empty());
+    }
+}
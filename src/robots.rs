@@ -0,0 +1,302 @@
+//! robots.txt awareness
+//!
+//! With `respect_robots_txt` on, every page load the server starts first looks
+//! up the rules of its origin's robots.txt for `robots_user_agent` (RFC 9309):
+//! the group naming that agent, or the `*` group when none does, with the
+//! longest matching `Allow`/`Disallow` path deciding and `Allow` winning ties.
+//! A robots.txt that is missing (4xx) allows everything; one that cannot be
+//! read (5xx, network errors) disallows everything until it can. Rules are
+//! cached per origin and shared by all sessions.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::{config::Config, site_pool};
+
+/// How long fetched rules are used before robots.txt is read again
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+/// How long an unreadable robots.txt blocks its origin before it is tried again
+const UNREACHABLE_TTL: Duration = Duration::from_secs(5 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Bytes of robots.txt parsed; RFC 9309 asks for at least 500 KiB
+const MAX_ROBOTS_BYTES: usize = 512 * 1024;
+
+/// One `Allow` or `Disallow` line
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl Rule {
+    fn matches(&self, path: &str) -> bool {
+        let (pattern, anchored) = match self.pattern.strip_suffix('$') {
+            Some(pattern) => (pattern, true),
+            None => (self.pattern.as_str(), false),
+        };
+        let mut parts = pattern.split('*');
+        let Some(mut rest) = parts.next().and_then(|first| path.strip_prefix(first)) else {
+            return false;
+        };
+        let parts: Vec<&str> = parts.collect();
+        for (i, part) in parts.iter().enumerate() {
+            if i + 1 == parts.len() && anchored {
+                return rest.ends_with(part);
+            }
+            match rest.find(part) {
+                Some(at) => rest = &rest[at + part.len()..],
+                None => return false,
+            }
+        }
+        !anchored || rest.is_empty()
+    }
+
+    fn describe(&self) -> String {
+        format!("{}: {}", if self.allow { "Allow" } else { "Disallow" }, self.pattern)
+    }
+}
+
+/// Rules of one origin's robots.txt that apply to the configured user agent
+#[derive(Debug, Clone, PartialEq)]
+enum RobotsRules {
+    Rules(Vec<Rule>),
+    /// robots.txt could not be read, for this reason
+    Unreachable(String),
+}
+
+impl RobotsRules {
+    /// Rules of the groups naming `user_agent`, or of the `*` groups when none does
+    fn parse(body: &str, user_agent: &str) -> Self {
+        let agent = user_agent.split('/').next().unwrap_or_default().trim().to_lowercase();
+        let (mut named, mut wildcard) = (Vec::new(), Vec::new());
+        let (mut group_named, mut group_wildcard) = (false, false);
+        let (mut in_agents, mut any_named) = (false, false);
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    if !in_agents {
+                        (group_named, group_wildcard) = (false, false);
+                    }
+                    in_agents = true;
+                    let value = value.to_lowercase();
+                    group_named |= !agent.is_empty() && value == agent;
+                    group_wildcard |= value == "*";
+                    any_named |= group_named;
+                }
+                key @ ("allow" | "disallow") => {
+                    in_agents = false;
+                    // An empty Disallow allows everything, which is the default anyway
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = Rule { allow: key == "allow", pattern: value.to_string() };
+                    if group_named {
+                        named.push(rule.clone());
+                    }
+                    if group_wildcard {
+                        wildcard.push(rule);
+                    }
+                }
+                _ => in_agents = false,
+            }
+        }
+        Self::Rules(if any_named { named } else { wildcard })
+    }
+
+    /// The rule disallowing `path` (path and query), if any
+    fn disallowing(&self, path: &str) -> Option<String> {
+        let rules = match self {
+            Self::Rules(rules) => rules,
+            // RFC 9309 has crawlers assume a complete disallow
+            Self::Unreachable(_) => return Some("Disallow: /".to_string()),
+        };
+        if path == "/robots.txt" {
+            return None;
+        }
+        rules
+            .iter()
+            .filter(|rule| rule.matches(path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .filter(|rule| !rule.allow)
+            .map(Rule::describe)
+    }
+}
+
+/// A page load refused because of robots.txt
+#[derive(Debug, Clone, Serialize)]
+pub struct RobotsBlock {
+    pub url: String,
+    pub robots_url: String,
+    pub user_agent: String,
+    /// The deciding line, e.g. `Disallow: /admin/`
+    pub rule: String,
+    /// Why robots.txt could not be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unreachable: Option<String>,
+}
+
+impl RobotsBlock {
+    pub fn describe(&self) -> String {
+        match &self.unreachable {
+            Some(reason) => format!(
+                "Blocked by robots.txt: {} could not be read ({reason}), so no page of its origin may be loaded",
+                self.robots_url
+            ),
+            None => format!(
+                "Blocked by robots.txt: {} disallows {} for user agent '{}' ({})",
+                self.robots_url, self.url, self.user_agent, self.rule
+            ),
+        }
+    }
+}
+
+struct CachedRules {
+    fetched: Instant,
+    rules: Arc<RobotsRules>,
+}
+
+/// robots.txt rules per origin, fetched on first use
+#[derive(Clone)]
+pub struct RobotsTxt {
+    enabled: bool,
+    user_agent: String,
+    http: reqwest::Client,
+    cache: Arc<Mutex<HashMap<String, CachedRules>>>,
+}
+
+impl RobotsTxt {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            enabled: config.respect_robots_txt,
+            user_agent: config.robots_user_agent.clone(),
+            http: reqwest::Client::builder()
+                .timeout(FETCH_TIMEOUT)
+                .user_agent(config.robots_user_agent.clone())
+                .build()
+                .unwrap_or_default(),
+            cache: Arc::default(),
+        }
+    }
+
+    /// Why `url` may not be loaded, or `None` when robots.txt allows it or is not respected
+    pub async fn check(&self, url: &str) -> Option<RobotsBlock> {
+        if !self.enabled {
+            return None;
+        }
+        let parsed = url::Url::parse(url).ok().filter(|url| matches!(url.scheme(), "http" | "https"))?;
+        let origin = site_pool::origin_of(url)?;
+        let rules = self.rules(&origin).await;
+
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{query}", parsed.path()),
+            None => parsed.path().to_string(),
+        };
+        let rule = rules.disallowing(&path)?;
+        let unreachable = match rules.as_ref() {
+            RobotsRules::Unreachable(reason) => Some(reason.clone()),
+            RobotsRules::Rules(_) => None,
+        };
+        Some(RobotsBlock {
+            url: url.to_string(),
+            robots_url: format!("{origin}/robots.txt"),
+            user_agent: self.user_agent.clone(),
+            rule,
+            unreachable,
+        })
+    }
+
+    async fn rules(&self, origin: &str) -> Arc<RobotsRules> {
+        {
+            let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(cached) = cache.get(origin) {
+                let ttl = match cached.rules.as_ref() {
+                    RobotsRules::Rules(_) => CACHE_TTL,
+                    RobotsRules::Unreachable(_) => UNREACHABLE_TTL,
+                };
+                if cached.fetched.elapsed() < ttl {
+                    return cached.rules.clone();
+                }
+            }
+        }
+
+        let rules = Arc::new(self.fetch(origin).await);
+        if let RobotsRules::Unreachable(reason) = rules.as_ref() {
+            tracing::warn!("Treating {} as disallowed: its robots.txt could not be read ({})", origin, reason);
+        }
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.retain(|_, cached| cached.fetched.elapsed() < CACHE_TTL);
+        cache.insert(origin.to_string(), CachedRules { fetched: Instant::now(), rules: rules.clone() });
+        rules
+    }
+
+    async fn fetch(&self, origin: &str) -> RobotsRules {
+        let response = match self.http.get(format!("{origin}/robots.txt")).send().await {
+            Ok(response) => response,
+            Err(e) => return RobotsRules::Unreachable(e.to_string()),
+        };
+        let status = response.status();
+        if status.is_client_error() {
+            return RobotsRules::Rules(Vec::new());
+        }
+        if !status.is_success() {
+            return RobotsRules::Unreachable(format!("HTTP {status}"));
+        }
+        match response.bytes().await {
+            Ok(body) => {
+                let body = String::from_utf8_lossy(&body[..body.len().min(MAX_ROBOTS_BYTES)]);
+                RobotsRules::parse(&body, &self.user_agent)
+            }
+            Err(e) => RobotsRules::Unreachable(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robots_rules_pick_group_and_longest_match() {
+        let body = "\
+# Example
+User-agent: *
+Disallow: /private/
+Allow: /private/press/
+Disallow: /*.pdf$
+
+User-agent: OtherBot
+User-agent: rust-browser-mcp
+Disallow: /search
+Allow: /search/about
+Disallow:
+";
+        let rules = RobotsRules::parse(body, "rust-browser-mcp/0.3");
+        assert_eq!(rules.disallowing("/search?q=shoes").as_deref(), Some("Disallow: /search"));
+        assert_eq!(rules.disallowing("/search/about"), None);
+        // The named group replaces the `*` group entirely
+        assert_eq!(rules.disallowing("/private/page"), None);
+
+        let rules = RobotsRules::parse(body, "SomeCrawler");
+        assert_eq!(rules.disallowing("/private/page").as_deref(), Some("Disallow: /private/"));
+        assert_eq!(rules.disallowing("/private/press/2024"), None);
+        assert_eq!(rules.disallowing("/files/report.pdf").as_deref(), Some("Disallow: /*.pdf$"));
+        assert_eq!(rules.disallowing("/files/report.pdf?download=1"), None);
+        assert_eq!(rules.disallowing("/search"), None);
+        assert_eq!(rules.disallowing("/robots.txt"), None);
+
+        assert_eq!(RobotsRules::parse("", "rust-browser-mcp").disallowing("/anything"), None);
+        let unreachable = RobotsRules::Unreachable("HTTP 503".to_string());
+        assert!(unreachable.disallowing("/").is_some());
+    }
+}
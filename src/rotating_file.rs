@@ -0,0 +1,118 @@
+//! Append-only file rotated by size
+//!
+//! Shared by the audit log and the log file. Once a write would take the file
+//! past `max_bytes` it is renamed `<name>.1`, older files move up to `<name>.2`,
+//! ... and the oldest beyond `max_files` is deleted.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+pub(crate) struct RotatingFile {
+    pub(crate) path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    size: u64,
+    file: Option<File>,
+}
+
+impl RotatingFile {
+    /// A file at `path`, opened on the first write
+    pub(crate) fn new(path: impl Into<PathBuf>, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+            max_files,
+            size: 0,
+            file: None,
+        }
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Append `record`, rotating first if it would not fit
+    pub(crate) fn write_record(&mut self, record: &[u8]) -> io::Result<()> {
+        if self.file.is_none() {
+            self.open()?;
+        }
+        if self.size > 0 && self.size + record.len() as u64 > self.max_bytes {
+            self.rotate()?;
+            self.open()?;
+        }
+
+        let file = self.file.as_mut().expect("rotating file opened above");
+        file.write_all(record)?;
+        file.flush()?;
+        self.size += record.len() as u64;
+        Ok(())
+    }
+
+    /// Open the file for appending, creating it and its directory if needed
+    pub(crate) fn open(&mut self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Shift `server.log` → `server.log.1` → ... dropping the oldest beyond `max_files`.
+    /// The file stays closed until the next write.
+    pub(crate) fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        self.size = 0;
+
+        if self.max_files == 0 {
+            return fs::remove_file(&self.path);
+        }
+
+        let _ = fs::remove_file(Self::rotated_path(&self.path, self.max_files));
+        for index in (1..self.max_files).rev() {
+            let from = Self::rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(&from, Self::rotated_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, Self::rotated_path(&self.path, 1))
+    }
+
+    pub(crate) fn rotated_path(path: &Path, index: usize) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{index}"));
+        PathBuf::from(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_appends_to_an_existing_file_until_full() {
+        let dir = std::env::temp_dir().join(format!("rotating-file-test-{}", uuid::Uuid::new_v4().simple()));
+        let path = dir.join("audit.jsonl");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "old-12345\n").unwrap();
+
+        // A restarted server counts what the file already holds
+        let mut file = RotatingFile::new(&path, 20, 1);
+        file.write_record(b"new-12345\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old-12345\nnew-12345\n");
+        file.write_record(b"next-1234\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "next-1234\n");
+        assert_eq!(fs::read_to_string(RotatingFile::rotated_path(&path, 1)).unwrap(), "old-12345\nnew-12345\n");
+
+        // Without rotated files to keep, a full file is simply started over
+        let mut file = RotatingFile::new(&path, 10, 0);
+        file.write_record(b"last-1234\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "last-1234\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
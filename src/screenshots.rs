@@ -0,0 +1,521 @@
+//! Multi-viewport screenshots
+//!
+//! WebDriver can only size the outer browser window, so a requested viewport is
+//! reached by resizing once, measuring the page's inner size and resizing again
+//! to make up for toolbars and borders. Captures can be combined into one
+//! labeled comparison image; the browser's own canvas does the drawing, which
+//! keeps image decoding and encoding out of the server.
+//!
+//! Element highlights are drawn the same way: an overlay of labeled boxes is
+//! added to the page, captured with the rest of it, and removed again.
+//!
+//! [`wait_for_change`] lets a capture follow a DOM change as closely as
+//! WebDriver allows: a MutationObserver on the page answers a pending async
+//! script the moment a watched element changes, so the screenshot request goes
+//! out one round trip later instead of after a polling interval.
+//!
+//! WebDriver only captures the viewport. [`full_page`] captures the whole
+//! document through the Chrome DevTools Protocol on Chrome and Edge, and through
+//! geckodriver's full-page screenshot endpoint on Firefox.
+
+use std::time::Duration;
+
+use base64::{Engine as _, engine::general_purpose};
+use fantoccini::{Client, wd::WebDriverCompatibleCommand};
+use serde_json::{Value, json};
+
+use crate::{ClientManager, Result, WebDriverError, cdp};
+
+/// Named viewport sizes accepted wherever a viewport is expected
+pub const VIEWPORT_PRESETS: &[(&str, u32, u32)] = &[
+    ("mobile", 375, 667),
+    ("iphone-14", 390, 844),
+    ("pixel-7", 412, 915),
+    ("tablet", 768, 1024),
+    ("ipad-pro", 1024, 1366),
+    ("laptop", 1366, 768),
+    ("desktop", 1920, 1080),
+];
+
+/// Viewports captured when a call names none
+pub const DEFAULT_VIEWPORTS: &[&str] = &["mobile", "tablet", "desktop"];
+
+/// Largest width or height accepted for a viewport
+const MAX_VIEWPORT_DIMENSION: u32 = 10000;
+
+/// Page inner size, to correct the window size for browser chrome
+const INNER_SIZE_SCRIPT: &str = "return [window.innerWidth, window.innerHeight];";
+
+/// Draws the base64 PNGs in `arguments[0]` side by side under their labels and
+/// hands the result back as base64 PNG
+const STITCH_SCRIPT: &str = r#"
+    const shots = arguments[0];
+    const done = arguments[arguments.length - 1];
+    const gap = 24;
+    const header = 40;
+    Promise.all(shots.map(shot => new Promise((resolve, reject) => {
+        const img = new Image();
+        img.onload = () => resolve({ label: shot.label, img });
+        img.onerror = () => reject(new Error(`could not decode the ${shot.label} capture`));
+        img.src = 'data:image/png;base64,' + shot.data;
+    }))).then(loaded => {
+        const width = loaded.reduce((sum, shot) => sum + shot.img.naturalWidth, 0) + gap * (loaded.length + 1);
+        const height = Math.max(...loaded.map(shot => shot.img.naturalHeight)) + header + gap;
+        if (width > 32000 || height > 32000 || width * height > 268000000) {
+            return done({ error: `comparison image would be ${width}x${height} pixels, too large for a canvas` });
+        }
+        const canvas = document.createElement('canvas');
+        canvas.width = width;
+        canvas.height = height;
+        const ctx = canvas.getContext('2d');
+        ctx.fillStyle = '#f2f2f2';
+        ctx.fillRect(0, 0, width, height);
+        ctx.font = 'bold 20px sans-serif';
+        ctx.textBaseline = 'middle';
+        let x = gap;
+        for (const shot of loaded) {
+            ctx.fillStyle = '#222';
+            ctx.fillText(shot.label, x, header / 2 + 4);
+            ctx.drawImage(shot.img, x, header);
+            ctx.strokeStyle = '#999';
+            ctx.strokeRect(x - 0.5, header - 0.5, shot.img.naturalWidth + 1, shot.img.naturalHeight + 1);
+            x += shot.img.naturalWidth + gap;
+        }
+        done({ data: canvas.toDataURL('image/png').split(',')[1] });
+    }).catch(e => done({ error: String(e) }));
+"#;
+
+/// Boxes drawn per selector at most
+pub const MAX_HIGHLIGHTS_PER_SELECTOR: u64 = 50;
+
+/// Replaces any previous highlight overlay with labeled boxes around the matches
+/// of each `{selector, label, color}` in `arguments[0]`; an empty list only clears
+const HIGHLIGHT_SCRIPT: &str = r#"
+    const [specs, maxPerSelector] = arguments;
+    document.getElementById('__mcp_highlights')?.remove();
+    const highlighted = [];
+    const missing = [];
+    if (!specs.length) {
+        return { highlighted, missing };
+    }
+
+    const palette = ['#e6194b', '#3cb44b', '#4363d8', '#f58231', '#911eb4', '#008080', '#f032e6', '#9a6324'];
+    const overlay = document.createElement('div');
+    overlay.id = '__mcp_highlights';
+    overlay.setAttribute('aria-hidden', 'true');
+    overlay.style.cssText = 'position:absolute;left:0;top:0;width:0;height:0;z-index:2147483647;pointer-events:none;';
+
+    let number = 0;
+    specs.forEach((spec, specIndex) => {
+        const color = spec.color || palette[specIndex % palette.length];
+        let matches;
+        try {
+            matches = Array.from(document.querySelectorAll(spec.selector));
+        } catch (e) {
+            missing.push({ selector: spec.selector, reason: String(e) });
+            return;
+        }
+        const visible = matches
+            .map((el, index) => ({ el, index, rect: el.getBoundingClientRect() }))
+            .filter(match => match.rect.width > 0 || match.rect.height > 0)
+            .slice(0, maxPerSelector);
+        if (!visible.length) {
+            missing.push({ selector: spec.selector, reason: matches.length ? 'matches are not rendered' : 'no such element' });
+            return;
+        }
+        for (const { el, index, rect } of visible) {
+            number += 1;
+            const label = spec.label ? (matches.length > 1 ? `${spec.label} ${index + 1}` : spec.label) : String(number);
+            const left = rect.left + window.scrollX;
+            const top = rect.top + window.scrollY;
+
+            const box = document.createElement('div');
+            box.style.cssText = `position:absolute;box-sizing:border-box;left:${left}px;top:${top}px;`
+                + `width:${rect.width}px;height:${rect.height}px;border:3px solid ${color};background:${color}22;`;
+            const tag = document.createElement('div');
+            tag.textContent = label;
+            tag.style.cssText = `position:absolute;left:-3px;top:${top >= 22 ? -22 : 0}px;padding:1px 6px;`
+                + `font:bold 13px/18px sans-serif;color:#fff;background:${color};white-space:nowrap;`;
+            box.appendChild(tag);
+            overlay.appendChild(box);
+
+            highlighted.push({
+                label,
+                selector: spec.selector,
+                index,
+                x: Math.round(left),
+                y: Math.round(top),
+                width: Math.round(rect.width),
+                height: Math.round(rect.height),
+                in_viewport: rect.bottom > 0 && rect.right > 0 && rect.top < window.innerHeight && rect.left < window.innerWidth,
+                text: (el.innerText ?? el.textContent ?? '').trim().replace(/\s+/g, ' ').slice(0, 80),
+            });
+        }
+    });
+
+    document.documentElement.appendChild(overlay);
+    return { highlighted, missing };
+"#;
+
+/// Longest single wait of [`wait_for_change`]; longer watches take several, so no
+/// one async script outlives the driver's script timeout
+const CHANGE_WAIT_CHUNK: Duration = Duration::from_secs(5);
+
+/// Watches `arguments[0]` with a MutationObserver kept on `window` between calls
+/// (replaced when `arguments[2]` is true) and returns the first change, or null
+/// after `arguments[1]` ms without one
+const WATCH_CHANGE_SCRIPT: &str = r#"
+    const [selector, waitMs, reset, done] = arguments;
+    try {
+        document.querySelector(selector);
+    } catch (e) {
+        return done({ error: 'Invalid selector: ' + selector });
+    }
+    let watch = window.__mcpChangeWatch;
+    if (reset && watch) {
+        watch.observer.disconnect();
+        watch = null;
+    }
+    if (!watch) {
+        watch = window.__mcpChangeWatch = { change: null, waiters: [] };
+        const matches = node => node.nodeType === 1 && (node.matches(selector) || !!node.querySelector(selector));
+        const inside = node => {
+            const el = node.nodeType === 1 ? node : node.parentElement;
+            return el && el.closest(selector);
+        };
+        watch.observer = new MutationObserver(records => {
+            if (watch.change) return;
+            for (const record of records) {
+                let kind = null;
+                if (record.type === 'childList' && Array.from(record.addedNodes).some(matches)) kind = 'appeared';
+                else if (record.type === 'childList' && Array.from(record.removedNodes).some(matches)) kind = 'removed';
+                else if (inside(record.target)) kind = record.type === 'characterData' ? 'text' : record.type;
+                if (!kind) continue;
+                const el = document.querySelector(selector);
+                watch.change = {
+                    kind,
+                    attribute: record.attributeName || null,
+                    timestamp: Date.now(),
+                    text: el ? (el.innerText || el.textContent || '').trim().slice(0, 500) : null
+                };
+                watch.waiters.splice(0).forEach(waiter => waiter());
+                return;
+            }
+        });
+        watch.observer.observe(document.documentElement, {
+            subtree: true, childList: true, attributes: true, characterData: true
+        });
+    }
+    if (watch.change) return done(watch.change);
+    const waiter = () => {
+        clearTimeout(timer);
+        done(watch.change);
+    };
+    const timer = setTimeout(() => {
+        watch.waiters = watch.waiters.filter(other => other !== waiter);
+        done(null);
+    }, waitMs);
+    watch.waiters.push(waiter);
+"#;
+
+/// Disconnects the observer of [`WATCH_CHANGE_SCRIPT`]
+const STOP_WATCH_SCRIPT: &str = r#"
+    if (window.__mcpChangeWatch) {
+        window.__mcpChangeWatch.observer.disconnect();
+        delete window.__mcpChangeWatch;
+    }
+"#;
+
+/// A viewport to capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct Viewport {
+    pub label: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// A preset name (`"mobile"`), a `"WIDTHxHEIGHT"` string, or an object with
+    /// `width`, `height` and an optional `label`
+    pub fn parse(value: &Value) -> std::result::Result<Self, String> {
+        let viewport = match value {
+            Value::String(name) => {
+                let name = name.trim().to_lowercase();
+                if let Some((label, width, height)) = VIEWPORT_PRESETS.iter().find(|(preset, ..)| *preset == name) {
+                    Self { label: label.to_string(), width: *width, height: *height }
+                } else if let Some((width, height)) = name.split_once('x')
+                    && let (Ok(width), Ok(height)) = (width.parse(), height.parse())
+                {
+                    Self { label: name.clone(), width, height }
+                } else {
+                    let presets: Vec<&str> = VIEWPORT_PRESETS.iter().map(|(preset, ..)| *preset).collect();
+                    return Err(format!(
+                        "Unknown viewport '{name}'. Use WIDTHxHEIGHT or one of: {}",
+                        presets.join(", ")
+                    ));
+                }
+            }
+            Value::Object(fields) => {
+                let dimension = |key: &str| {
+                    fields
+                        .get(key)
+                        .and_then(|v| v.as_u64())
+                        .map(|v| v.min(u64::from(u32::MAX)) as u32)
+                        .ok_or_else(|| format!("Viewport {value} needs a numeric '{key}'"))
+                };
+                let (width, height) = (dimension("width")?, dimension("height")?);
+                let label = fields
+                    .get("label")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("{width}x{height}"));
+                Self { label, width, height }
+            }
+            _ => return Err(format!("Invalid viewport {value}: use a preset name, WIDTHxHEIGHT or an object")),
+        };
+
+        if viewport.width == 0 || viewport.height == 0 {
+            return Err(format!("Viewport '{}' must have a positive width and height", viewport.label));
+        }
+        if viewport.width > MAX_VIEWPORT_DIMENSION || viewport.height > MAX_VIEWPORT_DIMENSION {
+            return Err(format!(
+                "Viewport '{}' must be at most {MAX_VIEWPORT_DIMENSION} pixels in each dimension",
+                viewport.label
+            ));
+        }
+        Ok(viewport)
+    }
+}
+
+/// Size the window so the page's viewport is `width` x `height`, as closely as
+/// the browser allows. Returns the inner size reached.
+pub async fn set_viewport(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    width: u32,
+    height: u32,
+) -> Result<(u32, u32)> {
+    client_manager
+        .run_command(session, "set_window_size", client.set_window_size(width, height))
+        .await?;
+    let (inner_width, inner_height) = inner_size(client_manager, session, client).await?;
+    if (inner_width, inner_height) == (width, height) {
+        return Ok((inner_width, inner_height));
+    }
+
+    // Grow the window by whatever the browser chrome took away
+    let adjust = |target: u32, actual: u32| (2 * i64::from(target) - i64::from(actual)).max(1) as u32;
+    client_manager
+        .run_command(
+            session,
+            "set_window_size",
+            client.set_window_size(adjust(width, inner_width), adjust(height, inner_height)),
+        )
+        .await?;
+    inner_size(client_manager, session, client).await
+}
+
+async fn inner_size(client_manager: &ClientManager, session: &str, client: &Client) -> Result<(u32, u32)> {
+    let size = client_manager
+        .run_command(session, "viewport_size", client.execute(INNER_SIZE_SCRIPT, vec![]))
+        .await?;
+    let dimension = |index: usize| size.get(index).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    Ok((dimension(0), dimension(1)))
+}
+
+/// geckodriver's `GET /session/{id}/moz/screenshot/full`
+#[derive(Debug)]
+struct FirefoxFullScreenshot;
+
+impl WebDriverCompatibleCommand for FirefoxFullScreenshot {
+    fn endpoint(
+        &self,
+        base_url: &url::Url,
+        session_id: Option<&str>,
+    ) -> std::result::Result<url::Url, url::ParseError> {
+        base_url.join(&format!("session/{}/moz/screenshot/full", session_id.unwrap_or_default()))
+    }
+
+    fn method_and_body(&self, _request_url: &url::Url) -> (http::Method, Option<String>) {
+        (http::Method::GET, None)
+    }
+}
+
+/// PNG of the whole document rather than just the viewport
+pub async fn full_page(client_manager: &ClientManager, session: &str, client: &Client) -> Result<Vec<u8>> {
+    let data = match cdp::execute(client_manager, session, client, "Page.getLayoutMetrics", json!({})).await {
+        Ok(metrics) => {
+            let size = &metrics["cssContentSize"];
+            let clip = json!({
+                "x": 0,
+                "y": 0,
+                "width": size["width"].as_f64().unwrap_or(0.0).ceil(),
+                "height": size["height"].as_f64().unwrap_or(0.0).ceil(),
+                "scale": 1,
+            });
+            let params = json!({ "format": "png", "captureBeyondViewport": true, "clip": clip });
+            let capture = cdp::execute(client_manager, session, client, "Page.captureScreenshot", params).await?;
+            capture["data"].clone()
+        }
+        Err(cdp_error) => client_manager
+            .run_command(session, "full_page_screenshot", client.issue_cmd(FirefoxFullScreenshot))
+            .await
+            .map_err(|e| {
+                WebDriverError::Execution(format!(
+                    "Full-page screenshots need Chrome, Edge or Firefox ({cdp_error}; {e})"
+                ))
+            })?,
+    };
+    let data = data
+        .as_str()
+        .ok_or_else(|| WebDriverError::Execution("Full-page screenshot returned no image".to_string()))?;
+    general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| WebDriverError::Execution(format!("Full-page screenshot is not valid base64: {e}")))
+}
+
+/// Highlight requests from a tool argument: selector strings or
+/// `{selector, label, color}` objects
+pub fn parse_highlights(value: &Value) -> std::result::Result<Vec<Value>, String> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| "highlight must be an array of selectors or {selector, label, color} objects".to_string())?;
+    items
+        .iter()
+        .map(|item| match item {
+            Value::String(selector) => Ok(json!({ "selector": selector })),
+            Value::Object(fields) => match fields.get("selector").and_then(|v| v.as_str()) {
+                Some(selector) => Ok(json!({
+                    "selector": selector,
+                    "label": fields.get("label").and_then(|v| v.as_str()),
+                    "color": fields.get("color").and_then(|v| v.as_str()),
+                })),
+                None => Err(format!("Highlight {item} needs a 'selector'")),
+            },
+            _ => Err(format!("Invalid highlight {item}: use a selector or a {{selector, label, color}} object")),
+        })
+        .collect()
+}
+
+/// Draw labeled boxes around the matches of `specs`, replacing earlier ones.
+/// Returns the `highlighted` boxes and the selectors with nothing to draw.
+pub async fn highlight(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    specs: &[Value],
+    max_per_selector: u64,
+) -> Result<Value> {
+    client_manager
+        .run_command(
+            session,
+            "highlight_elements",
+            client.execute(HIGHLIGHT_SCRIPT, vec![json!(specs), json!(max_per_selector)]),
+        )
+        .await
+}
+
+/// Remove highlights drawn by [`highlight`]
+pub async fn clear_highlights(client_manager: &ClientManager, session: &str, client: &Client) -> Result<()> {
+    highlight(client_manager, session, client, &[], 0).await.map(|_| ())
+}
+
+/// Combine labeled PNG captures into one side-by-side comparison PNG
+pub async fn stitch(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    shots: &[(String, String)],
+) -> Result<String> {
+    let shots: Vec<Value> = shots
+        .iter()
+        .map(|(label, data)| json!({ "label": label, "data": data }))
+        .collect();
+    let result = client_manager
+        .run_command(session, "stitch_screenshots", client.execute_async(STITCH_SCRIPT, vec![json!(shots)]))
+        .await?;
+    if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+        return Err(WebDriverError::Execution(error.to_string()));
+    }
+    result
+        .get("data")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| WebDriverError::Execution("Stitching returned no image".to_string()))
+}
+
+/// Wait up to `timeout` for an element matching `selector` to change: appear, be
+/// removed, or have its children, attributes or text modified. Returns the change
+/// (`kind`, `attribute`, `timestamp`, `text`), or `None` on timeout. The observer
+/// stays on the page until [`stop_watching`].
+pub async fn wait_for_change(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    selector: &str,
+    timeout: Duration,
+) -> Result<Option<Value>> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut reset = true;
+    let change = loop {
+        let wait = deadline.saturating_duration_since(tokio::time::Instant::now()).min(CHANGE_WAIT_CHUNK);
+        let script_args = vec![json!(selector), json!(wait.as_millis() as u64), json!(reset)];
+        let result = client_manager
+            .run_command(session, "watch_for_change", client.execute_async(WATCH_CHANGE_SCRIPT, script_args))
+            .await?;
+        reset = false;
+        if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+            return Err(WebDriverError::Execution(error.to_string()));
+        }
+        if !result.is_null() {
+            break Some(result);
+        }
+        if tokio::time::Instant::now() >= deadline {
+            break None;
+        }
+    };
+    Ok(change)
+}
+
+/// Stop the observer [`wait_for_change`] leaves on the page, once whatever had to
+/// happen right after the change is done
+pub async fn stop_watching(client_manager: &ClientManager, session: &str, client: &Client) -> Result<()> {
+    client_manager
+        .run_command(session, "stop_watch", client.execute(STOP_WATCH_SCRIPT, vec![]))
+        .await
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewport_parsing() {
+        assert_eq!(
+            Viewport::parse(&json!("Mobile")).unwrap(),
+            Viewport { label: "mobile".to_string(), width: 375, height: 667 }
+        );
+        assert_eq!(Viewport::parse(&json!("800x600")).unwrap().width, 800);
+        let custom = Viewport::parse(&json!({ "width": 1280, "height": 720, "label": "hd" })).unwrap();
+        assert_eq!((custom.label.as_str(), custom.height), ("hd", 720));
+        assert_eq!(Viewport::parse(&json!({ "width": 640, "height": 480 })).unwrap().label, "640x480");
+
+        assert!(Viewport::parse(&json!("watch")).is_err());
+        assert!(Viewport::parse(&json!("0x600")).is_err());
+        assert!(Viewport::parse(&json!({ "width": 20000, "height": 600 })).is_err());
+        assert!(Viewport::parse(&json!(42)).is_err());
+    }
+
+    #[test]
+    fn test_highlight_parsing() {
+        let specs = parse_highlights(&json!([".card", { "selector": "#buy", "label": "Buy" }])).unwrap();
+        assert_eq!(specs[0], json!({ "selector": ".card" }));
+        assert_eq!(specs[1]["label"], "Buy");
+        assert!(specs[1]["color"].is_null());
+
+        assert!(parse_highlights(&json!(".card")).is_err());
+        assert!(parse_highlights(&json!([{ "label": "Buy" }])).is_err());
+    }
+}
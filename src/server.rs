@@ -3,58 +3,508 @@
 //! This module contains the main server struct and the ServerHandler implementation
 //! that dispatches MCP tool calls to the appropriate handler modules.
 
+use std::sync::{Arc, Mutex};
+
 use rmcp::{ErrorData as McpError, ServerHandler, model::*};
 
 use crate::{
     ClientManager,
+    audit::{AuditEntry, AuditLog, AuditOutcome},
+    auth::{oauth::AccessToken, scopes::GrantedScopes},
     config::Config,
-    handlers::{drivers, navigation, elements, page, performance, recipes},
+    drain::DrainGate,
+    error::WebDriverError,
+    handlers::{ProgressReporter, accessibility, admin, batch, cache, cancelled_response, compare, crawl, dom, drivers, extract_session_id, parse_params, localization, navigation, network, elements, page::{self, WindowState}, performance, recipes, sessions},
+    logging::{DEFAULT_MCP_LOG_LEVEL, McpLogForwarder},
+    rate_limit::{RATE_LIMITED_ERROR_CODE, RateLimiter},
     recipes::RecipeManager,
-    tools::{ToolDefinitions, ServerMode},
+    tools::{ToolDefinitions, ServerMode, RecipeTools, RECIPE_TOOL_PREFIX, TIMEOUT_ARGUMENT, coded_error_response, params::RunBatchParams},
 };
 
+/// JSON-RPC error code for calls refused during a graceful shutdown
+const SHUTTING_DOWN_ERROR_CODE: i32 = -32030;
+
 /// The main WebDriver MCP server
 #[derive(Clone)]
 pub struct WebDriverServer {
     client_manager: ClientManager,
     recipe_manager: RecipeManager,
     mode: ServerMode,
+    /// Per-client forwarding of tracing events as MCP log notifications
+    log_forwarder: Arc<Mutex<Option<McpLogForwarder>>>,
+    /// Record of every tool call, shared across client connections
+    audit: AuditLog,
+    /// Per-client call budgets, shared across client connections
+    rate_limiter: RateLimiter,
+    /// In-flight tool calls, refused once a graceful shutdown starts
+    drain: DrainGate,
+    /// Token authenticated when the connection was opened (WebSocket transport);
+    /// HTTP requests carry theirs per request
+    access_token: Option<AccessToken>,
 }
 
 impl WebDriverServer {
     /// Create a new server with default configuration
     pub fn new() -> crate::error::Result<Self> {
-        let config = Config::from_env();
-        Ok(Self {
-            client_manager: ClientManager::new(config)?,
-            recipe_manager: RecipeManager::new(None),
-            mode: ServerMode::Stdio,
-        })
+        Self::with_config(Config::from_env())
     }
 
     /// Create a new server with custom configuration
     pub fn with_config(config: Config) -> crate::error::Result<Self> {
-        Ok(Self {
-            client_manager: ClientManager::new(config)?,
-            recipe_manager: RecipeManager::new(None),
-            mode: ServerMode::Stdio,
-        })
+        Self::with_config_and_mode(config, ServerMode::Stdio)
     }
 
     /// Create a new server with custom configuration and mode
     pub fn with_config_and_mode(config: Config, mode: ServerMode) -> crate::error::Result<Self> {
         Ok(Self {
+            audit: AuditLog::from_config(&config),
+            rate_limiter: RateLimiter::from_config(&config),
+            drain: DrainGate::default(),
+            recipe_manager: RecipeManager::from_config(&config),
             client_manager: ClientManager::new(config)?,
-            recipe_manager: RecipeManager::new(None),
             mode,
+            log_forwarder: Arc::default(),
+            access_token: None,
         })
     }
 
+    /// Clone the server for another client connection, sharing browsers and recipes
+    /// but not per-client state such as the MCP log level
+    pub fn for_new_session(&self) -> Self {
+        Self {
+            log_forwarder: Arc::default(),
+            access_token: None,
+            ..self.clone()
+        }
+    }
+
+    /// Attribute every tool call on this connection to the token's user, giving it a
+    /// private browser session namespace and the token's scopes
+    pub fn with_access_token(mut self, access_token: Option<AccessToken>) -> Self {
+        self.access_token = access_token;
+        self
+    }
+
     /// Get the client manager
     pub fn get_client_manager(&self) -> &ClientManager {
         &self.client_manager
     }
 
+    /// Get the recipe manager
+    pub fn get_recipe_manager(&self) -> &RecipeManager {
+        &self.recipe_manager
+    }
+
+    /// Whether saved recipes are published as individual `recipe_<name>` tools
+    fn recipe_tools_enabled(&self) -> bool {
+        self.client_manager.get_config().expose_recipes_as_tools
+    }
+
+    /// The limit a call put on itself with `timeout_ms`, clamped to `max_tool_timeout_ms`
+    fn requested_timeout(&self, request: &CallToolRequestParam) -> Result<Option<std::time::Duration>, McpError> {
+        let Some(value) = request.arguments.as_ref().and_then(|args| args.get(TIMEOUT_ARGUMENT)) else {
+            return Ok(None);
+        };
+        match value.as_u64() {
+            Some(ms) if ms > 0 => {
+                let ms = ms.min(self.client_manager.get_config().max_tool_timeout_ms);
+                Ok(Some(std::time::Duration::from_millis(ms)))
+            }
+            _ => Err(McpError::invalid_params(
+                format!("{TIMEOUT_ARGUMENT} must be a positive integer number of milliseconds"),
+                Some(serde_json::json!({ "code": crate::ErrorCode::InvalidArgument.as_str(), "retryable": false })),
+            )),
+        }
+    }
+
+    /// Tell the client the tool list changed after a successful recipe create/delete
+    async fn notify_recipe_tools_changed(
+        &self,
+        context: &rmcp::service::RequestContext<rmcp::service::RoleServer>,
+        result: &Result<CallToolResult, McpError>,
+    ) {
+        let succeeded = matches!(result, Ok(r) if r.is_error != Some(true));
+        if !self.recipe_tools_enabled() || !succeeded {
+            return;
+        }
+
+        if let Err(e) = context.peer.notify_tool_list_changed().await {
+            tracing::warn!("Failed to send tools/list_changed notification: {}", e);
+        }
+    }
+
+    /// Run the steps of a `run_batch` call in order through [`Self::dispatch_tool`],
+    /// holding each to the same enablement, scope and rate limit checks as a direct
+    /// call and auditing each on its own
+    async fn run_batch(
+        &self,
+        arguments: &Option<serde_json::Map<String, serde_json::Value>>,
+        context: &rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let RunBatchParams { steps, stop_on_error, session } = parse_params(arguments)?;
+        if steps.len() > batch::MAX_BATCH_STEPS {
+            return Err(McpError::invalid_params(
+                format!("run_batch takes at most {} steps, got {}", batch::MAX_BATCH_STEPS, steps.len()),
+                None,
+            ));
+        }
+        let stop_on_error = stop_on_error.unwrap_or(true);
+        let session_id = session.session_id.as_deref();
+        let scopes = self.granted_scopes(context);
+        let client = self.client_identity(context).filter(|_| self.rate_limiter.is_enabled());
+
+        let mut outcomes = Vec::with_capacity(steps.len());
+        let mut stopped = false;
+        for (index, step) in steps.iter().enumerate() {
+            if context.ct.is_cancelled() {
+                return Ok(cancelled_response("run_batch"));
+            }
+            let request = CallToolRequestParam {
+                name: step.tool.clone().into(),
+                arguments: Some(batch::step_arguments(step, session_id)),
+            };
+            let started = std::time::Instant::now();
+            let nested = step.tool == batch::RUN_BATCH_TOOL;
+            let enabled = self.client_manager.get_config().is_tool_enabled(&step.tool);
+            let denied_scopes = scopes.as_ref().filter(|scopes| !scopes.allows(&step.tool));
+            let limited = match &client {
+                Some(client) if !nested && enabled && denied_scopes.is_none() => {
+                    self.rate_limiter.acquire_step(client).err()
+                }
+                _ => None,
+            };
+            let rejected = nested || !enabled || denied_scopes.is_some() || limited.is_some();
+            let result = if nested {
+                Err(McpError::invalid_params("run_batch cannot be nested in a batch", None))
+            } else if !enabled {
+                Err(McpError::invalid_params(
+                    format!("Tool '{}' is disabled by server configuration", step.tool),
+                    Some(serde_json::json!({ "code": crate::ErrorCode::PolicyBlocked.as_str(), "retryable": false })),
+                ))
+            } else if let Some(scopes) = denied_scopes {
+                Err(McpError::invalid_request(
+                    format!(
+                        "Tool '{}' requires one of the scopes: {}",
+                        step.tool,
+                        GrantedScopes::accepted_scopes(&step.tool).join(", ")
+                    ),
+                    Some(scopes.denial(&step.tool)),
+                ))
+            } else if let Some(exceeded) = limited {
+                Err(McpError::new(ErrorCode(RATE_LIMITED_ERROR_CODE), exceeded.message(), Some(exceeded.to_json())))
+            } else {
+                match self.requested_timeout(&request) {
+                    Err(e) => Err(e),
+                    // Boxed because a step goes back through dispatch_tool, which led here
+                    Ok(timeout) => {
+                        let call = Box::pin(self.dispatch_tool(&request, context));
+                        match timeout {
+                            Some(limit) => tokio::time::timeout(limit, call).await.unwrap_or_else(|_| {
+                                Ok(coded_error_response(
+                                    crate::ErrorCode::Timeout,
+                                    format!("Tool '{}' did not finish within {} ms", step.tool, limit.as_millis()),
+                                    serde_json::json!({ "tool": step.tool, "timeout_ms": limit.as_millis() as u64 }),
+                                ))
+                            }),
+                            None => call.await,
+                        }
+                    }
+                }
+            };
+            let elapsed = started.elapsed();
+            let failed = batch::step_failed(&result);
+            self.client_manager.get_stats().record_tool_call(&step.tool, elapsed, failed);
+            self.record_audit_entry(&request, context, elapsed, &result, rejected);
+            outcomes.push(batch::step_outcome(index, &step.tool, &result, elapsed));
+            if failed && stop_on_error {
+                stopped = index + 1 < steps.len();
+                break;
+            }
+        }
+
+        Ok(batch::batch_response(session_id, steps.len(), outcomes, stopped))
+    }
+
+    /// Route a tool call to its handler
+    async fn dispatch_tool(
+        &self,
+        request: &CallToolRequestParam,
+        context: &rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        // Checked here rather than in call_tool so each step of a batch counts
+        if let Err(exceeded) = self.client_manager.charge_quota(&request.name, extract_session_id(&request.arguments)) {
+            return Ok(coded_error_response(crate::ErrorCode::QuotaExceeded, exceeded.message(), exceeded.to_json()));
+        }
+
+        match request.name.as_ref() {
+            // Session tools
+            "create_session" => sessions::handle_create_session(&self.client_manager, &request.arguments).await,
+            "list_sessions" => sessions::handle_list_sessions(&self.client_manager, &request.arguments).await,
+
+            // Navigation tools
+            "navigate" => navigation::handle_navigate(&self.client_manager, &request.arguments).await,
+            "get_current_url" => navigation::handle_get_current_url(&self.client_manager, &request.arguments).await,
+            "back" => navigation::handle_back(&self.client_manager, &request.arguments).await,
+            "forward" => navigation::handle_forward(&self.client_manager, &request.arguments).await,
+            "refresh" => navigation::handle_refresh(&self.client_manager, &request.arguments).await,
+            "get_page_load_status" => navigation::handle_get_page_load_status(&self.client_manager, &request.arguments).await,
+
+            // Element tools
+            "click" => elements::handle_click(&self.client_manager, &request.arguments).await,
+            "send_keys" => elements::handle_send_keys(&self.client_manager, &request.arguments).await,
+            "wait_for_element" => elements::handle_wait_for_element(&self.client_manager, &request.arguments, &context.ct).await,
+            "wait_for_condition" => elements::handle_wait_for_condition(&self.client_manager, &request.arguments, &context.ct).await,
+            "wait_for_dom_stable" => {
+                elements::handle_wait_for_dom_stable(&self.client_manager, &request.arguments, &context.ct).await
+            }
+            "wait_for_attribute" => {
+                elements::handle_wait_for_attribute(&self.client_manager, &request.arguments, &context.ct).await
+            }
+            "wait_for_property" => {
+                elements::handle_wait_for_property(&self.client_manager, &request.arguments, &context.ct).await
+            }
+            "get_element_info" => elements::handle_get_element_info(&self.client_manager, &request.arguments).await,
+            "get_computed_style" => elements::handle_get_computed_style(&self.client_manager, &request.arguments).await,
+            "get_element_rects" => elements::handle_get_element_rects(&self.client_manager, &request.arguments).await,
+            "get_attribute" => elements::handle_get_element_attribute(&self.client_manager, &request.arguments).await,
+            "get_property" => elements::handle_get_element_property(&self.client_manager, &request.arguments).await,
+            "find_element" => elements::handle_find_element(&self.client_manager, &request.arguments).await,
+            "find_elements" => elements::handle_find_elements(&self.client_manager, &request.arguments).await,
+            "element_exists" => elements::handle_element_exists(&self.client_manager, &request.arguments, &context.ct).await,
+            "count_elements" => elements::handle_count_elements(&self.client_manager, &request.arguments).await,
+            "scroll_to_element" => elements::handle_scroll_to_element(&self.client_manager, &request.arguments).await,
+            "hover" => elements::handle_hover(&self.client_manager, &request.arguments).await,
+            "discover_forms" => elements::handle_discover_forms(&self.client_manager, &request.arguments).await,
+            "fill_and_submit_form" => elements::handle_fill_and_submit_form(&self.client_manager, &request.arguments).await,
+            "login_form" => elements::handle_login_form(&self.client_manager, &request.arguments).await,
+            "enter_otp" => elements::handle_enter_otp(&self.client_manager, &request.arguments).await,
+            "login_via_token" => elements::handle_login_via_token(&self.client_manager, &request.arguments).await,
+
+            // Page tools
+            "get_title" => page::handle_get_title(&self.client_manager, &request.arguments).await,
+            "get_text" => page::handle_get_text(&self.client_manager, &request.arguments).await,
+            "execute_script" => page::handle_execute_script(&self.client_manager, &request.arguments).await,
+            "execute_async_script" => page::handle_execute_async_script(&self.client_manager, &request.arguments).await,
+            "screenshot" => page::handle_screenshot(&self.client_manager, &request.arguments).await,
+            "screenshot_on_change" => page::handle_screenshot_on_change(&self.client_manager, &request.arguments).await,
+            "highlight_elements" => page::handle_highlight_elements(&self.client_manager, &request.arguments).await,
+            "responsive_screenshots" => page::handle_responsive_screenshots(&self.client_manager, &request.arguments).await,
+            "list_artifacts" => page::handle_list_artifacts(&self.client_manager, &request.arguments).await,
+            "resize_window" => page::handle_resize_window(&self.client_manager, &request.arguments).await,
+            "get_window_size" => page::handle_get_window_size(&self.client_manager, &request.arguments).await,
+            "maximize_window" => {
+                page::handle_set_window_state(&self.client_manager, &request.arguments, WindowState::Maximized).await
+            }
+            "minimize_window" => {
+                page::handle_set_window_state(&self.client_manager, &request.arguments, WindowState::Minimized).await
+            }
+            "fullscreen_window" => {
+                page::handle_set_window_state(&self.client_manager, &request.arguments, WindowState::Fullscreen).await
+            }
+            "set_window_position" => page::handle_set_window_position(&self.client_manager, &request.arguments).await,
+            "dismiss_overlays" => page::handle_dismiss_overlays(&self.client_manager, &request.arguments).await,
+            "get_popup_events" => page::handle_get_popup_events(&self.client_manager, &request.arguments).await,
+            "get_page_events" => page::handle_get_page_events(&self.client_manager, &request.arguments).await,
+            "list_service_workers" => cache::handle_list_service_workers(&self.client_manager, &request.arguments).await,
+            "unregister_service_workers" => {
+                cache::handle_unregister_service_workers(&self.client_manager, &request.arguments).await
+            }
+            "set_cache_disabled" => cache::handle_set_cache_disabled(&self.client_manager, &request.arguments).await,
+            "get_page_source" => page::handle_get_page_source(&self.client_manager, &request.arguments).await,
+            "summarize_page" => page::handle_summarize_page(&self.client_manager, &request.arguments).await,
+            "get_page_text" => page::handle_get_page_text(&self.client_manager, &request.arguments).await,
+            "snapshot_dom" => dom::handle_snapshot_dom(&self.client_manager, &request.arguments).await,
+            "diff_dom" => dom::handle_diff_dom(&self.client_manager, &request.arguments).await,
+            "audit_accessibility" => accessibility::handle_audit_accessibility(&self.client_manager, &request.arguments).await,
+            "crawl" => {
+                let progress = ProgressReporter::from_context(context);
+                crawl::handle_crawl(&self.client_manager, &request.arguments, &context.ct, &progress).await
+            }
+            "compare_across_browsers" => {
+                compare::handle_compare_across_browsers(&self.client_manager, &request.arguments, &context.ct).await
+            }
+            "check_localization" => {
+                localization::handle_check_localization(&self.client_manager, &request.arguments, &context.ct).await
+            }
+            "whoami_network" => network::handle_whoami_network(&self.client_manager, &request.arguments, &context.ct).await,
+
+            // Performance tools
+            "get_console_logs" => performance::handle_get_console_logs(&self.client_manager, &request.arguments).await,
+            "get_performance_metrics" => performance::handle_get_performance_metrics(&self.client_manager, &request.arguments).await,
+            "monitor_memory_usage" => performance::handle_monitor_memory_usage(&self.client_manager, &request.arguments, &context.ct).await,
+            "run_performance_test" => performance::handle_run_performance_test(&self.client_manager, &request.arguments, &context.ct).await,
+            "monitor_resource_usage" => performance::handle_monitor_resource_usage(&self.client_manager, &request.arguments, &context.ct).await,
+            "get_page_weight" => network::handle_get_page_weight(&self.client_manager, &request.arguments).await,
+            "analyze_third_parties" => network::handle_analyze_third_parties(&self.client_manager, &request.arguments).await,
+            "get_session_health" => performance::handle_get_session_health(&self.client_manager, &request.arguments).await,
+
+            // Driver lifecycle tools (stdio mode only)
+            "get_healthy_endpoints" => {
+                if self.mode == ServerMode::Stdio {
+                    drivers::handle_get_healthy_endpoints(&self.client_manager, &request.arguments).await
+                } else {
+                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+                }
+            }
+            "get_endpoint_status" => {
+                if self.mode == ServerMode::Stdio {
+                    drivers::handle_get_endpoint_status(&self.client_manager, &request.arguments).await
+                } else {
+                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+                }
+            }
+            "refresh_driver_health" => {
+                if self.mode == ServerMode::Stdio {
+                    drivers::handle_refresh_driver_health(&self.client_manager, &request.arguments).await
+                } else {
+                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+                }
+            }
+            "list_managed_drivers" => {
+                if self.mode == ServerMode::Stdio {
+                    drivers::handle_list_managed_drivers(&self.client_manager, &request.arguments).await
+                } else {
+                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+                }
+            }
+            "start_driver" => {
+                if self.mode == ServerMode::Stdio {
+                    drivers::handle_start_driver(&self.client_manager, &request.arguments).await
+                } else {
+                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+                }
+            }
+            "stop_driver" => {
+                if self.mode == ServerMode::Stdio {
+                    drivers::handle_stop_driver(&self.client_manager, &request.arguments).await
+                } else {
+                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+                }
+            }
+            "stop_all_drivers" => {
+                if self.mode == ServerMode::Stdio {
+                    drivers::handle_stop_all_drivers(&self.client_manager, &request.arguments).await
+                } else {
+                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+                }
+            }
+            "force_cleanup_orphaned_processes" => {
+                if self.mode == ServerMode::Stdio {
+                    drivers::handle_force_cleanup_orphaned_processes(&self.client_manager, &request.arguments).await
+                } else {
+                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+                }
+            }
+
+            // Recipe management tools (available in both modes)
+            "create_recipe" => {
+                let result = recipes::handle_create_recipe(&self.recipe_manager, &request.arguments).await;
+                self.notify_recipe_tools_changed(context, &result).await;
+                result
+            }
+            "list_recipes" => recipes::handle_list_recipes(&self.recipe_manager, &request.arguments).await,
+            "get_recipe" => recipes::handle_get_recipe(&self.recipe_manager, &request.arguments).await,
+            "execute_recipe" => recipes::handle_execute_recipe(self, &self.recipe_manager, &request.arguments, &context.ct).await,
+            "delete_recipe" => {
+                let result = recipes::handle_delete_recipe(&self.recipe_manager, &request.arguments).await;
+                self.notify_recipe_tools_changed(context, &result).await;
+                result
+            }
+            "create_recipe_template" => {
+                let result = recipes::handle_create_recipe_template(&self.recipe_manager, &request.arguments).await;
+                self.notify_recipe_tools_changed(context, &result).await;
+                result
+            }
+
+            "run_batch" => self.run_batch(&request.arguments, context).await,
+
+            // Admin tools (available in both modes)
+            "get_audit_log" => admin::handle_get_audit_log(&self.audit, &request.arguments).await,
+            "get_server_stats" => admin::handle_get_server_stats(&self.client_manager, &request.arguments).await,
+
+            // Saved recipes published as their own tools (opt-in)
+            name if self.recipe_tools_enabled() && name.starts_with(RECIPE_TOOL_PREFIX) => {
+                recipes::handle_recipe_tool(self, &self.recipe_manager, name, &request.arguments, &context.ct).await
+            }
+
+            _ => Err(McpError::method_not_found::<CallToolRequestMethod>()),
+        }
+    }
+
+    /// Append the outcome of a tool call to the audit log
+    fn record_audit_entry(
+        &self,
+        request: &CallToolRequestParam,
+        context: &rmcp::service::RequestContext<rmcp::service::RoleServer>,
+        duration: std::time::Duration,
+        result: &Result<CallToolResult, McpError>,
+        rejected: bool,
+    ) {
+        let (outcome, error) = match result {
+            Err(e) if rejected => (AuditOutcome::Rejected, Some(e.message.to_string())),
+            Ok(r) if r.is_error == Some(true) => (
+                AuditOutcome::Error,
+                r.content.first().and_then(|c| c.as_text()).map(|t| t.text.clone()),
+            ),
+            Ok(_) => (AuditOutcome::Success, None),
+            Err(e) => (AuditOutcome::Error, Some(e.message.to_string())),
+        };
+
+        self.audit.record(AuditEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            tool: request.name.to_string(),
+            arguments: AuditLog::redact_arguments(&request.arguments),
+            session_id: extract_session_id(&request.arguments),
+            duration_ms: duration.as_millis() as u64,
+            outcome,
+            error,
+            client: self.client_identity(context),
+        });
+    }
+
+    /// Token that authenticated a request: per request over HTTP, per connection over WebSocket
+    fn access_token(&self, context: &rmcp::service::RequestContext<rmcp::service::RoleServer>) -> Option<AccessToken> {
+        self.access_token_in(&context.extensions)
+    }
+
+    /// Token found in a request's or notification's extensions
+    fn access_token_in(&self, extensions: &rmcp::model::Extensions) -> Option<AccessToken> {
+        extensions
+            .get::<axum::http::request::Parts>()
+            .and_then(|parts| parts.extensions.get::<AccessToken>())
+            .or(self.access_token.as_ref())
+            .cloned()
+    }
+
+    /// Authenticated user (OAuth subject or `apikey:<id>`) behind a request
+    fn principal(&self, context: &rmcp::service::RequestContext<rmcp::service::RoleServer>) -> Option<String> {
+        self.access_token(context).map(|token| token.user_id)
+    }
+
+    /// Scopes limiting which tools a request may call; `None` means unrestricted.
+    /// OAuth tokens without `browser:*` scopes are unrestricted unless
+    /// `require_scopes` is set; API keys are never scope-limited.
+    fn granted_scopes(&self, context: &rmcp::service::RequestContext<rmcp::service::RoleServer>) -> Option<GrantedScopes> {
+        let token = self.access_token(context)?;
+        let scopes = GrantedScopes::from_token(&token);
+        let is_oauth = token.token_type.eq_ignore_ascii_case("bearer");
+        let enforce = !scopes.is_empty() || (is_oauth && self.client_manager.get_config().require_scopes);
+        enforce.then_some(scopes)
+    }
+
+    /// Authenticated user or MCP session id of a remote client; `None` on stdio
+    fn client_identity(&self, context: &rmcp::service::RequestContext<rmcp::service::RoleServer>) -> Option<String> {
+        if let Some(principal) = self.principal(context) {
+            return Some(principal);
+        }
+        let parts = context.extensions.get::<axum::http::request::Parts>()?;
+        parts
+            .headers
+            .get("mcp-session-id")
+            .and_then(|value| value.to_str().ok())
+            .map(|session| format!("session:{session}"))
+    }
+
     /// Start drivers proactively (for HTTP mode)
     pub async fn ensure_drivers_started(&mut self) -> crate::error::Result<()> {
         let config = self.client_manager.get_config();
@@ -64,7 +514,8 @@ impl WebDriverServer {
 
             let driver_manager = self.client_manager.get_driver_manager();
             let drivers = config.concurrent_drivers.clone();
-            let timeout = std::time::Duration::from_millis(config.driver_startup_timeout_ms);
+            let driver_types: Vec<_> = drivers.iter().filter_map(|name| crate::driver::DriverType::from_string(name)).collect();
+            let timeout = driver_manager.startup_policy().budget(&driver_types);
 
             match driver_manager.start_concurrent_drivers(&drivers, timeout).await {
                 Ok(started_drivers) => {
@@ -76,12 +527,9 @@ impl WebDriverServer {
                             format!("Failed to start any WebDriver processes. Requested: {drivers:?}")
                         ));
                     } else if started_count < requested_count {
-                        let started_types: std::collections::HashSet<_> = started_drivers.iter().map(|(dt, _)| dt.browser_name()).collect();
-                        for driver_name in &drivers {
-                            if let Some(driver_type) = crate::driver::DriverType::from_string(driver_name) {
-                                if !started_types.contains(driver_type.browser_name()) {
-                                    tracing::warn!("Failed to start {} WebDriver - it may not be installed or accessible", driver_type.browser_name());
-                                }
+                        for driver_type in &driver_types {
+                            if !started_drivers.iter().any(|(started, _)| started == driver_type) {
+                                tracing::warn!("Failed to start {} WebDriver - it may not be installed or accessible", driver_type.browser_name());
                             }
                         }
 
@@ -96,7 +544,7 @@ impl WebDriverServer {
                         }
                     }
 
-                    let health_check_interval = std::time::Duration::from_secs(30);
+                    let health_check_interval = std::time::Duration::from_secs(config.health_check_interval_secs);
                     let _health_check_handle = driver_manager.start_periodic_health_checks(health_check_interval);
                     tracing::debug!("Started periodic health checks (every {:?})", health_check_interval);
                 }
@@ -109,6 +557,31 @@ impl WebDriverServer {
         Ok(())
     }
 
+    /// Stop accepting tool calls and wait up to `timeout` for running calls and
+    /// recipe runs to finish. Returns `false` if some were still running.
+    pub async fn drain(&self, timeout: std::time::Duration) -> bool {
+        self.drain.start_draining();
+        let in_flight = self.drain.in_flight();
+        if in_flight > 0 {
+            tracing::info!("Waiting up to {:?} for {} in-flight tool calls to finish...", timeout, in_flight);
+        }
+
+        let drained = self.drain.wait_idle(timeout).await;
+        if !drained {
+            tracing::warn!(
+                "Drain timed out after {:?} with {} tool calls still running",
+                timeout,
+                self.drain.in_flight()
+            );
+        }
+        drained
+    }
+
+    /// Whether a graceful shutdown has started
+    pub fn is_draining(&self) -> bool {
+        self.drain.is_draining()
+    }
+
     /// Cleanup method to stop any managed driver processes
     pub async fn cleanup(&self) -> crate::error::Result<()> {
         tracing::info!("WebDriver MCP Server shutting down...");
@@ -155,131 +628,196 @@ impl ServerHandler for WebDriverServer {
             protocol_version: ProtocolVersion::V_2024_11_05,
             server_info: Implementation {
                 name: "rust-browser-mcp".to_string(),
-                version: "0.1.0".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
             },
             capabilities: ServerCapabilities {
-                tools: Some(ToolsCapability::default()),
+                tools: Some(ToolsCapability {
+                    list_changed: self.recipe_tools_enabled().then_some(true),
+                }),
+                resources: Some(ResourcesCapability::default()),
+                logging: Some(JsonObject::new()),
                 ..Default::default()
             },
             instructions: Some("WebDriver MCP Server - Browser automation for Claude".to_string()),
         }
     }
 
+    async fn on_initialized(&self, context: rmcp::service::NotificationContext<rmcp::service::RoleServer>) {
+        let principal = self.access_token_in(&context.extensions).map(|token| token.user_id);
+        let mut forwarder = self.log_forwarder.lock().unwrap_or_else(|e| e.into_inner());
+        if forwarder.is_none() {
+            *forwarder = Some(McpLogForwarder::spawn(context.peer, DEFAULT_MCP_LOG_LEVEL, principal));
+        }
+    }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<(), McpError> {
+        let principal = self.principal(&context);
+        let mut forwarder = self.log_forwarder.lock().unwrap_or_else(|e| e.into_inner());
+        match forwarder.as_ref() {
+            Some(existing) => existing.set_level(request.level),
+            None => *forwarder = Some(McpLogForwarder::spawn(context.peer, request.level, principal)),
+        }
+        Ok(())
+    }
+
     async fn list_tools(
         &self,
         _request: Option<PaginatedRequestParam>,
-        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
     ) -> Result<ListToolsResult, McpError> {
+        let config = self.client_manager.get_config();
+        let mut tools = ToolDefinitions::list_for_config(self.mode, config);
+
+        if self.recipe_tools_enabled() {
+            match self.recipe_manager.load_all_recipes().await {
+                Ok(recipes) => tools.extend(
+                    RecipeTools::published_names(&recipes)
+                        .into_iter()
+                        .map(|(name, recipe)| RecipeTools::recipe_tool(&name, recipe))
+                        .filter(|tool| config.is_tool_enabled(&tool.name)),
+                ),
+                Err(e) => tracing::warn!("Failed to load recipes for tool listing: {}", e),
+            }
+        }
+
+        if let Some(scopes) = self.granted_scopes(&context) {
+            tools.retain(|tool| scopes.allows(&tool.name));
+        }
+
         Ok(ListToolsResult {
-            tools: ToolDefinitions::list_for_mode(self.mode),
+            tools,
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let principal = self.principal(&context);
+        Ok(ListResourcesResult {
+            resources: self.client_manager.get_resource_store().list(principal.as_deref()),
             next_cursor: None,
         })
     }
 
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let principal = self.principal(&context);
+        match self.client_manager.get_resource_store().read(&request.uri, principal.as_deref()) {
+            Ok(contents) => Ok(ReadResourceResult {
+                contents: vec![contents],
+            }),
+            Err(WebDriverError::NotFound(msg)) => Err(McpError::resource_not_found(
+                msg,
+                Some(serde_json::json!({ "uri": request.uri })),
+            )),
+            Err(e) => Err(McpError::internal_error(e.to_string(), None)),
+        }
+    }
+
     async fn call_tool(
         &self,
         request: CallToolRequestParam,
-        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        match request.name.as_ref() {
-            // Navigation tools
-            "navigate" => navigation::handle_navigate(&self.client_manager, &request.arguments).await,
-            "get_current_url" => navigation::handle_get_current_url(&self.client_manager, &request.arguments).await,
-            "back" => navigation::handle_back(&self.client_manager, &request.arguments).await,
-            "forward" => navigation::handle_forward(&self.client_manager, &request.arguments).await,
-            "refresh" => navigation::handle_refresh(&self.client_manager, &request.arguments).await,
-            "get_page_load_status" => navigation::handle_get_page_load_status(&self.client_manager, &request.arguments).await,
+        let started = std::time::Instant::now();
 
-            // Element tools
-            "click" => elements::handle_click(&self.client_manager, &request.arguments).await,
-            "send_keys" => elements::handle_send_keys(&self.client_manager, &request.arguments).await,
-            "wait_for_element" => elements::handle_wait_for_element(&self.client_manager, &request.arguments).await,
-            "wait_for_condition" => elements::handle_wait_for_condition(&self.client_manager, &request.arguments).await,
-            "get_element_info" => elements::handle_get_element_info(&self.client_manager, &request.arguments).await,
-            "get_attribute" => elements::handle_get_element_attribute(&self.client_manager, &request.arguments).await,
-            "get_property" => elements::handle_get_element_property(&self.client_manager, &request.arguments).await,
-            "find_element" => elements::handle_find_element(&self.client_manager, &request.arguments).await,
-            "find_elements" => elements::handle_find_elements(&self.client_manager, &request.arguments).await,
-            "scroll_to_element" => elements::handle_scroll_to_element(&self.client_manager, &request.arguments).await,
-            "hover" => elements::handle_hover(&self.client_manager, &request.arguments).await,
-            "fill_and_submit_form" => elements::handle_fill_and_submit_form(&self.client_manager, &request.arguments).await,
-            "login_form" => elements::handle_login_form(&self.client_manager, &request.arguments).await,
+        // Held until the call returns so shutdown can wait for it
+        let in_flight = self.drain.enter();
 
-            // Page tools
-            "get_title" => page::handle_get_title(&self.client_manager, &request.arguments).await,
-            "get_text" => page::handle_get_text(&self.client_manager, &request.arguments).await,
-            "execute_script" => page::handle_execute_script(&self.client_manager, &request.arguments).await,
-            "screenshot" => page::handle_screenshot(&self.client_manager, &request.arguments).await,
-            "resize_window" => page::handle_resize_window(&self.client_manager, &request.arguments).await,
-            "get_page_source" => page::handle_get_page_source(&self.client_manager, &request.arguments).await,
-
-            // Performance tools
-            "get_console_logs" => performance::handle_get_console_logs(&self.client_manager, &request.arguments).await,
-            "get_performance_metrics" => performance::handle_get_performance_metrics(&self.client_manager, &request.arguments).await,
-            "monitor_memory_usage" => performance::handle_monitor_memory_usage(&self.client_manager, &request.arguments).await,
-            "run_performance_test" => performance::handle_run_performance_test(&self.client_manager, &request.arguments).await,
-            "monitor_resource_usage" => performance::handle_monitor_resource_usage(&self.client_manager, &request.arguments).await,
-
-            // Driver lifecycle tools (stdio mode only)
-            "get_healthy_endpoints" => {
-                if self.mode == ServerMode::Stdio {
-                    drivers::handle_get_healthy_endpoints(&self.client_manager, &request.arguments).await
-                } else {
-                    Err(McpError::method_not_found::<CallToolRequestMethod>())
-                }
-            }
-            "refresh_driver_health" => {
-                if self.mode == ServerMode::Stdio {
-                    drivers::handle_refresh_driver_health(&self.client_manager, &request.arguments).await
-                } else {
-                    Err(McpError::method_not_found::<CallToolRequestMethod>())
-                }
+        let enabled = self.client_manager.get_config().is_tool_enabled(&request.name);
+        let scopes = self.granted_scopes(&context);
+        let denied_scopes = scopes.as_ref().filter(|scopes| !scopes.allows(&request.name));
+        // Stdio has no client identity and is never limited
+        let permit = match self.client_identity(&context) {
+            Some(client) if in_flight.is_some() && enabled && denied_scopes.is_none() && self.rate_limiter.is_enabled() => {
+                Some(self.rate_limiter.acquire(&client))
             }
-            "list_managed_drivers" => {
-                if self.mode == ServerMode::Stdio {
-                    drivers::handle_list_managed_drivers(&self.client_manager, &request.arguments).await
-                } else {
-                    Err(McpError::method_not_found::<CallToolRequestMethod>())
-                }
-            }
-            "start_driver" => {
-                if self.mode == ServerMode::Stdio {
-                    drivers::handle_start_driver(&self.client_manager, &request.arguments).await
-                } else {
-                    Err(McpError::method_not_found::<CallToolRequestMethod>())
-                }
-            }
-            "stop_driver" => {
-                if self.mode == ServerMode::Stdio {
-                    drivers::handle_stop_driver(&self.client_manager, &request.arguments).await
-                } else {
-                    Err(McpError::method_not_found::<CallToolRequestMethod>())
-                }
-            }
-            "stop_all_drivers" => {
-                if self.mode == ServerMode::Stdio {
-                    drivers::handle_stop_all_drivers(&self.client_manager, &request.arguments).await
-                } else {
-                    Err(McpError::method_not_found::<CallToolRequestMethod>())
-                }
-            }
-            "force_cleanup_orphaned_processes" => {
-                if self.mode == ServerMode::Stdio {
-                    drivers::handle_force_cleanup_orphaned_processes(&self.client_manager, &request.arguments).await
-                } else {
-                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+            _ => None,
+        };
+        let limited = permit.as_ref().and_then(|permit| permit.as_ref().err());
+        let rejected = in_flight.is_none() || !enabled || denied_scopes.is_some() || limited.is_some();
+
+        let result = if in_flight.is_none() {
+            Err(McpError::new(
+                ErrorCode(SHUTTING_DOWN_ERROR_CODE),
+                "Server is shutting down and no longer accepts tool calls",
+                Some(serde_json::json!({
+                    "status": 503,
+                    "error": "shutting_down",
+                    "code": crate::ErrorCode::ShuttingDown.as_str(),
+                    "retryable": false,
+                })),
+            ))
+        } else if !enabled {
+            Err(McpError::invalid_params(
+                format!("Tool '{}' is disabled by server configuration", request.name),
+                Some(serde_json::json!({
+                    "code": crate::ErrorCode::PolicyBlocked.as_str(),
+                    "retryable": false,
+                    "tool": request.name,
+                })),
+            ))
+        } else if let Some(scopes) = denied_scopes {
+            Err(McpError::invalid_request(
+                format!(
+                    "Tool '{}' requires one of the scopes: {}",
+                    request.name,
+                    GrantedScopes::accepted_scopes(&request.name).join(", ")
+                ),
+                Some(scopes.denial(&request.name)),
+            ))
+        } else if let Some(exceeded) = limited {
+            Err(McpError::new(
+                ErrorCode(RATE_LIMITED_ERROR_CODE),
+                exceeded.message(),
+                Some(exceeded.to_json()),
+            ))
+        } else {
+            match self.requested_timeout(&request) {
+                Err(e) => Err(e),
+                Ok(timeout) => {
+                    // Sessions opened during this call are private to the calling user, and
+                    // tools run on their behalf, like recipe steps, need their scopes. Boxed
+                    // to keep the large dispatch future off the stack.
+                    let dispatch = Box::pin(self.dispatch_tool(&request, &context));
+                    let call = ClientManager::with_principal(
+                        self.principal(&context),
+                        GrantedScopes::with_scopes(scopes.clone(), dispatch),
+                    );
+                    match timeout {
+                        // Dropping the call on expiry abandons whatever command it was waiting on
+                        Some(limit) => tokio::time::timeout(limit, call).await.unwrap_or_else(|_| {
+                            Ok(coded_error_response(
+                                crate::ErrorCode::Timeout,
+                                format!("Tool '{}' did not finish within {} ms", request.name, limit.as_millis()),
+                                serde_json::json!({ "tool": request.name, "timeout_ms": limit.as_millis() as u64 }),
+                            ))
+                        }),
+                        None => call.await,
+                    }
                 }
             }
+        };
 
-            // Recipe management tools (available in both modes)
-            "create_recipe" => recipes::handle_create_recipe(&self.recipe_manager, &request.arguments).await,
-            "list_recipes" => recipes::handle_list_recipes(&self.recipe_manager, &request.arguments).await,
-            "get_recipe" => recipes::handle_get_recipe(&self.recipe_manager, &request.arguments).await,
-            "execute_recipe" => recipes::handle_execute_recipe(self, &self.recipe_manager, &request.arguments).await,
-            "delete_recipe" => recipes::handle_delete_recipe(&self.recipe_manager, &request.arguments).await,
-            "create_recipe_template" => recipes::handle_create_recipe_template(&self.recipe_manager, &request.arguments).await,
+        drop(permit);
+        drop(in_flight);
 
-            _ => Err(McpError::method_not_found::<CallToolRequestMethod>()),
-        }
+        let elapsed = started.elapsed();
+        let is_error = !matches!(&result, Ok(r) if r.is_error != Some(true));
+        self.client_manager
+            .get_stats()
+            .record_tool_call(&request.name, elapsed, is_error);
+        self.record_audit_entry(&request, &context, elapsed, &result, rejected);
+        result
     }
 }
@@ -1,6 +1,8 @@
 use anyhow::Result;
 use tokio::signal;
-use rust_browser_mcp::WebDriverServer;
+use rust_browser_mcp::{Config, WebDriverServer};
+
+use super::probes::probe_router;
 
 use rmcp::transport::streamable_http_server::{
     StreamableHttpService, session::local::LocalSessionManager,
@@ -9,15 +11,21 @@ use rmcp::transport::streamable_http_server::{
 use tokio::sync::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc as StdArc;
-use tower_http::cors::CorsLayer;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use rust_browser_mcp::auth::api_key::{ApiKeyStore, api_key_middleware};
+use rust_browser_mcp::auth::jwt::{JwtValidator, jwt_middleware};
 use rust_browser_mcp::auth::oauth::{OAuthConfig, OAuthStore, create_oauth_router};
-use axum::{middleware, extract::State, http::{Request, StatusCode}, response::Response, body::Body};
+use axum::{middleware, extract::State, http::{HeaderName, HeaderValue, Method, Request, StatusCode}, response::Response, body::Body};
 use axum::middleware::Next;
 use std::sync::Arc;
 
 pub async fn run_http_server(server: WebDriverServer, bind_addr: &str, no_auth: bool) -> Result<()> {
     // Convert server to HTTP mode
     let config = server.get_client_manager().get_config().clone();
+    let api_keys = if no_auth { None } else { ApiKeyStore::from_config(&config)? };
+    let jwt = if no_auth { None } else { JwtValidator::from_config(&config) };
+    let cors = cors_layer(&config);
+    let base_path = config.normalized_base_path();
     let mut server = rust_browser_mcp::WebDriverServer::with_config_and_mode(config, rust_browser_mcp::tools::ServerMode::Http)?;
     tracing::info!(
         "WebDriver MCP Server listening on HTTP at {} (Ctrl+C to stop)",
@@ -29,10 +37,12 @@ pub async fn run_http_server(server: WebDriverServer, bind_addr: &str, no_auth:
 
     // Create a clone of the server for cleanup before moving it into the closure
     let server_for_cleanup = server.clone();
+    // Probes stay reachable without credentials in every auth mode
+    let probes = probe_router(server.clone());
 
     // Create MCP service
     let service = StreamableHttpService::new(
-        move || Ok(server.clone()),
+        move || Ok(server.for_new_session()),
         LocalSessionManager::default().into(),
         Default::default(),
     );
@@ -41,7 +51,25 @@ pub async fn run_http_server(server: WebDriverServer, bind_addr: &str, no_auth:
         // Create unprotected MCP routes (no OAuth)
         axum::Router::new()
             .fallback_service(service)
-            .layer(CorsLayer::permissive())
+            .layer(cors.clone())
+    } else if let Some(api_keys) = api_keys.clone() {
+        // API keys replace the OAuth flow entirely
+        let protected_service = tower::ServiceBuilder::new()
+            .layer(middleware::from_fn_with_state(api_keys, api_key_middleware))
+            .service(service);
+
+        axum::Router::new()
+            .fallback_service(protected_service)
+            .layer(cors.clone())
+    } else if let Some(jwt) = jwt.clone() {
+        // Tokens come from an external identity provider; no local OAuth endpoints
+        let protected_service = tower::ServiceBuilder::new()
+            .layer(middleware::from_fn_with_state(jwt, jwt_middleware))
+            .service(service);
+
+        axum::Router::new()
+            .fallback_service(protected_service)
+            .layer(cors.clone())
     } else {
         // Create OAuth store with default configuration
         let oauth_config = OAuthConfig::default();
@@ -70,64 +98,174 @@ pub async fn run_http_server(server: WebDriverServer, bind_addr: &str, no_auth:
         axum::Router::new()
             .merge(oauth_router)
             .fallback_service(protected_service)
-            .layer(CorsLayer::permissive())
+            .layer(cors.clone())
     };
 
+    let router = mount_at_base_path(probes.merge(router), &base_path);
+
     let tcp_listener = tokio::net::TcpListener::bind(bind_addr).await?;
 
+    let root = format!("{bind_addr}{base_path}");
     if no_auth {
-        tracing::info!("MCP endpoint (no auth): http://{}/", bind_addr);
+        tracing::info!("MCP endpoint (no auth): http://{}/", root);
+    } else if api_keys.is_some() {
+        tracing::info!("Protected MCP endpoint (API key): http://{}/", root);
+    } else if jwt.is_some() {
+        tracing::info!("Protected MCP endpoint (external IdP JWT): http://{}/", root);
     } else {
         tracing::info!("OAuth endpoints available at:");
-        tracing::info!("  Authorization: http://{}/oauth/authorize", bind_addr);
-        tracing::info!("  Callback: http://{}/oauth/callback", bind_addr);
-        tracing::info!("Protected MCP endpoint: http://{}/", bind_addr);
+        tracing::info!("  Authorization: http://{}/oauth/authorize", root);
+        tracing::info!("  Callback: http://{}/oauth/callback", root);
+        tracing::info!("Protected MCP endpoint: http://{}/", root);
     }
+    tracing::info!("Health probes: http://{}/healthz, /readyz, /version", root);
 
     axum::serve(tcp_listener, router)
-        .with_graceful_shutdown(async move {
-            signal::ctrl_c()
-                .await
-                .expect("Failed to install Ctrl+C handler");
-            tracing::info!("Received shutdown signal (Ctrl+C), initiating graceful shutdown...");
-            
-            // Cleanup WebDriver processes before shutdown with timeout
-            let cleanup_timeout = std::time::Duration::from_secs(8);
-            tracing::info!("Starting WebDriver cleanup with {:?} timeout...", cleanup_timeout);
-            
-            match tokio::time::timeout(cleanup_timeout, server_for_cleanup.cleanup()).await {
-                Ok(Ok(())) => tracing::info!("WebDriver cleanup completed successfully"),
-                Ok(Err(e)) => tracing::warn!("Error during WebDriver cleanup: {}", e),
-                Err(_) => {
-                    tracing::warn!("WebDriver cleanup timed out after {:?}, forcing server shutdown", cleanup_timeout);
-                    tracing::warn!("Some WebDriver processes may still be running");
-                }
-            }
-            
-            tracing::info!("Graceful shutdown sequence completed");
-        })
+        .with_graceful_shutdown(shutdown_with_cleanup(server_for_cleanup))
         .await?;
 
     tracing::info!("WebDriver MCP HTTP Server stopped");
     Ok(())
 }
 
-/// OAuth session middleware that ensures sessions exist for OAuth-authenticated users
-async fn oauth_session_middleware(
-    State((oauth_store, session_manager, oauth_sessions)): State<(Arc<OAuthStore>, Arc<LocalSessionManager>, Arc<RwLock<HashMap<String, StdArc<str>>>>)>,
-    mut request: Request<Body>,
-    next: Next,
-) -> Response {
-    // First validate the OAuth token using the existing middleware logic
-    let token = if let Some(auth_header) = request.headers().get("Authorization") {
-        let header_str = auth_header.to_str().unwrap_or("");
-        header_str.strip_prefix("Bearer ").map(|token| token.to_string())
-    } else {
-        None
+/// Wait for Ctrl+C or SIGTERM, let in-flight tool calls drain, then stop
+/// browser sessions and WebDriver processes before the listener shuts down
+pub async fn shutdown_with_cleanup(server: WebDriverServer) {
+    wait_for_shutdown_signal().await;
+    tracing::info!("Received shutdown signal, initiating graceful shutdown...");
+
+    // New tool calls are refused from here on; running ones and recipe runs may finish
+    let drain_timeout = std::time::Duration::from_secs(
+        server.get_client_manager().get_config().shutdown_drain_timeout_secs,
+    );
+    if server.drain(drain_timeout).await {
+        tracing::info!("All in-flight tool calls finished");
+    }
+    
+    // Cleanup WebDriver processes before shutdown with timeout
+    let cleanup_timeout = std::time::Duration::from_secs(8);
+    tracing::info!("Starting WebDriver cleanup with {:?} timeout...", cleanup_timeout);
+    
+    match tokio::time::timeout(cleanup_timeout, server.cleanup()).await {
+        Ok(Ok(())) => tracing::info!("WebDriver cleanup completed successfully"),
+        Ok(Err(e)) => tracing::warn!("Error during WebDriver cleanup: {}", e),
+        Err(_) => {
+            tracing::warn!("WebDriver cleanup timed out after {:?}, forcing server shutdown", cleanup_timeout);
+            tracing::warn!("Some WebDriver processes may still be running");
+        }
+    }
+    
+    tracing::info!("Graceful shutdown sequence completed");
+}
+
+/// Resolve on Ctrl+C, or on SIGTERM from an orchestrator on Unix
+async fn wait_for_shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to install SIGTERM handler: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// CORS policy for browser-based MCP clients: any origin unless
+/// `cors_allowed_origins` lists specific ones
+pub fn cors_layer(config: &Config) -> CorsLayer {
+    let origins: Vec<HeaderValue> = config
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|origin| HeaderValue::from_str(origin.trim_end_matches('/')).ok())
+        .collect();
+    if origins.is_empty() || config.cors_allowed_origins.iter().any(|origin| origin == "*") {
+        return CorsLayer::permissive();
+    }
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
+        .allow_headers(Any)
+        // Streamable HTTP clients must read the session id assigned on initialize
+        .expose_headers([HeaderName::from_static("mcp-session-id")])
+}
+
+/// Serve `router` under `base_path` (normalized, empty for the root) so the
+/// server can sit behind a reverse proxy at a sub-path. Unlike `Router::nest`,
+/// both `/base` and `/base/` reach the inner root.
+pub fn mount_at_base_path(router: axum::Router, base_path: &str) -> axum::Router {
+    if base_path.is_empty() {
+        return router;
+    }
+
+    let base_path: Arc<str> = base_path.into();
+    axum::Router::new()
+        .fallback_service(router)
+        .layer(middleware::from_fn(move |request: Request<Body>, next: Next| {
+            let base_path = base_path.clone();
+            async move { strip_base_path(&base_path, request, next).await }
+        }))
+}
+
+/// Rewrite `/base/rest` to `/rest`; paths outside the base path are not found.
+/// The original URI stays available through axum's `OriginalUri`.
+async fn strip_base_path(base_path: &str, mut request: Request<Body>, next: Next) -> Response {
+    let uri = request.uri();
+    let rest = match uri.path().strip_prefix(base_path) {
+        Some("") => "/",
+        Some(rest) if rest.starts_with('/') => rest,
+        _ => {
+            return Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::empty())
+                .unwrap();
+        }
     };
 
-    let token = token.or_else(|| {
-        request.headers().get("cookie")
+    let path_and_query = match uri.query() {
+        Some(query) => format!("{rest}?{query}"),
+        None => rest.to_string(),
+    };
+    match path_and_query.parse() {
+        Ok(rewritten) => {
+            *request.uri_mut() = rewritten;
+            next.run(request).await
+        }
+        Err(_) => Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::empty())
+            .unwrap(),
+    }
+}
+
+/// Bearer token from the `Authorization` header or the `auth_token` cookie
+pub fn extract_auth_token(headers: &axum::http::HeaderMap) -> Option<String> {
+    let token = headers
+        .get("Authorization")
+        .and_then(|auth_header| auth_header.to_str().ok())
+        .and_then(|header_str| header_str.strip_prefix("Bearer "))
+        .map(|token| token.to_string());
+
+    token.or_else(|| {
+        headers.get("cookie")
             .and_then(|cookie_header| cookie_header.to_str().ok())
             .and_then(|cookies| {
                 for cookie in cookies.split(';') {
@@ -138,7 +276,17 @@ async fn oauth_session_middleware(
                 }
                 None
             })
-    });
+    })
+}
+
+/// OAuth session middleware that ensures sessions exist for OAuth-authenticated users
+async fn oauth_session_middleware(
+    State((oauth_store, session_manager, oauth_sessions)): State<(Arc<OAuthStore>, Arc<LocalSessionManager>, Arc<RwLock<HashMap<String, StdArc<str>>>>)>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    // First validate the OAuth token using the existing middleware logic
+    let token = extract_auth_token(request.headers());
 
     let token = match token {
         Some(token) => token,
@@ -221,4 +369,74 @@ async fn oauth_session_middleware(
     request.extensions_mut().insert(token_info);
     
     next.run(request).await
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use axum::http::header;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cors_allows_only_listed_origins() {
+        let config = Config {
+            cors_allowed_origins: vec!["https://app.example.com/".to_string()],
+            ..Config::from_env()
+        };
+        let router = axum::Router::new().route("/", get(|| async { "ok" })).layer(cors_layer(&config));
+        let preflight = |origin: &str| {
+            Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let allowed = router.clone().oneshot(preflight("https://app.example.com")).await.unwrap();
+        assert_eq!(allowed.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example.com");
+        let refused = router.clone().oneshot(preflight("https://evil.test")).await.unwrap();
+        assert!(refused.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+
+        let request = Request::get("/").header(header::ORIGIN, "https://app.example.com").body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_EXPOSE_HEADERS], "mcp-session-id");
+
+        let any = Config { cors_allowed_origins: vec!["*".to_string()], ..Config::from_env() };
+        let router = axum::Router::new().route("/", get(|| async { "ok" })).layer(cors_layer(&any));
+        let response = router.oneshot(preflight("https://evil.test")).await.unwrap();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+
+    #[tokio::test]
+    async fn test_base_path_is_stripped_before_routing() {
+        let router = axum::Router::new()
+            .route("/", get(|| async { "root" }))
+            .route("/healthz", get(|request: Request<Body>| async move { request.uri().to_string() }));
+        let router = mount_at_base_path(router, "/mcp");
+        let get_path = |path: &str| {
+            let router = router.clone();
+            let request = Request::get(path).body(Body::empty()).unwrap();
+            async move {
+                let response = router.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, String::from_utf8_lossy(&body).into_owned())
+            }
+        };
+
+        assert_eq!(get_path("/mcp").await, (StatusCode::OK, "root".to_string()));
+        assert_eq!(get_path("/mcp/").await, (StatusCode::OK, "root".to_string()));
+        assert_eq!(get_path("/mcp/healthz?verbose=1").await, (StatusCode::OK, "/healthz?verbose=1".to_string()));
+        assert_eq!(get_path("/healthz").await.0, StatusCode::NOT_FOUND);
+        assert_eq!(get_path("/mcpx/healthz").await.0, StatusCode::NOT_FOUND);
+
+        // Served at the root, the router is used as is
+        let root = axum::Router::new().route("/healthz", get(|| async { "ok" }));
+        let request = Request::get("/healthz").body(Body::empty()).unwrap();
+        assert_eq!(mount_at_base_path(root, "").oneshot(request).await.unwrap().status(), StatusCode::OK);
+    }
+}
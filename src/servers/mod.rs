@@ -1,5 +1,8 @@
 pub mod http;
+pub mod probes;
 pub mod stdio;
+pub mod ws;
 
 pub use http::run_http_server;
-pub use stdio::run_stdio_server;
\ No newline at end of file
+pub use stdio::run_stdio_server;
+pub use ws::run_ws_server;
\ No newline at end of file
@@ -0,0 +1,131 @@
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use rmcp::ServerHandler;
+use rust_browser_mcp::{DriverType, WebDriverServer};
+use serde_json::{Map, json};
+
+/// Unauthenticated `/healthz`, `/readyz` and `/version` endpoints so load
+/// balancers and orchestrators can probe the server without speaking MCP
+pub fn probe_router(server: WebDriverServer) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/version", get(version))
+        .with_state(server)
+}
+
+/// Liveness: the process is up and serving requests
+async fn healthz(State(server): State<WebDriverServer>) -> Response {
+    let uptime_secs = server.get_client_manager().get_stats().uptime().as_secs();
+    Json(json!({ "status": "ok", "uptime_secs": uptime_secs })).into_response()
+}
+
+/// Readiness: at least one configured driver is healthy and can take another
+/// session. With an explicit `WEBDRIVER_ENDPOINT` the driver is not managed
+/// here, so only session capacity is considered. Never ready while draining.
+async fn readyz(State(server): State<WebDriverServer>) -> Response {
+    if server.is_draining() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "draining" })),
+        )
+            .into_response();
+    }
+
+    let client_manager = server.get_client_manager();
+    let config = client_manager.get_config();
+    let managed = config.webdriver_endpoint == "auto" && config.auto_start_driver;
+    let healthy = client_manager.get_driver_manager().get_healthy_endpoints().await;
+
+    let mut drivers = Map::new();
+    let mut ready = false;
+    for name in &config.concurrent_drivers {
+        let Some(driver_type) = DriverType::from_string(name) else {
+            continue;
+        };
+        let is_healthy = !managed || healthy.contains_key(&driver_type);
+        let has_capacity = client_manager.get_pool().has_capacity(&driver_type).await;
+        ready |= is_healthy && has_capacity;
+        drivers.insert(
+            driver_type.browser_name().to_lowercase(),
+            json!({ "healthy": is_healthy, "capacity_available": has_capacity }),
+        );
+    }
+
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let body = json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "drivers": drivers,
+    });
+    (status, Json(body)).into_response()
+}
+
+async fn version(State(server): State<WebDriverServer>) -> Response {
+    let info = server.get_info();
+    Json(json!({
+        "name": info.server_info.name,
+        "version": info.server_info.version,
+        "protocol_version": info.protocol_version,
+    }))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use rust_browser_mcp::Config;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn probe(server: &WebDriverServer, path: &str) -> (StatusCode, Value) {
+        let request = Request::get(path).body(Body::empty()).unwrap();
+        let response = probe_router(server.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_probes_report_liveness_readiness_and_version() {
+        // An explicit endpoint is not managed here, so only capacity decides readiness
+        let external = Config {
+            webdriver_endpoint: "http://127.0.0.1:9515".to_string(),
+            auto_start_driver: false,
+            ..Config::from_env()
+        };
+        let server = WebDriverServer::with_config(external).unwrap();
+
+        let (status, health) = probe(&server, "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(health["status"], "ok");
+
+        let (status, version) = probe(&server, "/version").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+
+        let (status, ready) = probe(&server, "/readyz").await;
+        assert_eq!(status, StatusCode::OK, "{ready}");
+        assert_eq!(ready["status"], "ready");
+
+        server.drain(std::time::Duration::ZERO).await;
+        let (status, draining) = probe(&server, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(draining["status"], "draining");
+
+        // Managed drivers that were never started are not healthy
+        let managed = Config { webdriver_endpoint: "auto".to_string(), auto_start_driver: true, ..Config::from_env() };
+        let server = WebDriverServer::with_config(managed).unwrap();
+        let (status, ready) = probe(&server, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(ready["status"], "not_ready");
+        assert!(ready["drivers"].as_object().unwrap().values().all(|driver| driver["healthy"] == false));
+    }
+}
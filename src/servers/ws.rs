@@ -0,0 +1,240 @@
+use anyhow::Result;
+use axum::{
+    Router,
+    extract::{
+        Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use futures::{SinkExt, StreamExt};
+use rmcp::{
+    ServiceExt,
+    model::{ClientJsonRpcMessage, ServerJsonRpcMessage},
+};
+use rust_browser_mcp::{
+    WebDriverServer,
+    auth::{
+        api_key::ApiKeyStore,
+        jwt::JwtValidator,
+        oauth::{OAuthConfig, OAuthStore, create_oauth_router},
+    },
+};
+use std::{collections::HashMap, sync::Arc};
+
+use super::{
+    http::{cors_layer, extract_auth_token, mount_at_base_path, shutdown_with_cleanup},
+    probes::probe_router,
+};
+
+#[derive(Clone)]
+struct WsState {
+    server: WebDriverServer,
+    /// Set when API keys are configured; takes precedence over JWT and OAuth
+    api_keys: Option<ApiKeyStore>,
+    /// Set when an external identity provider is configured; takes precedence over OAuth
+    jwt: Option<JwtValidator>,
+    /// `None` when started with `--no-auth`, in API key mode or with an external IdP
+    oauth_store: Option<Arc<OAuthStore>>,
+    /// Origins whose pages may open a socket with the `auth_token` cookie
+    cors_allowed_origins: Arc<[String]>,
+}
+
+/// Serve MCP over WebSocket: one JSON-RPC message per text frame, one MCP
+/// session per connection. Uses the same multi-tenant server and OAuth
+/// tokens as the HTTP transport.
+pub async fn run_ws_server(server: WebDriverServer, bind_addr: &str, no_auth: bool) -> Result<()> {
+    // WebSocket clients are remote, like HTTP ones: use the multi-tenant mode
+    let config = server.get_client_manager().get_config().clone();
+    let api_keys = if no_auth { None } else { ApiKeyStore::from_config(&config)? };
+    let jwt = if no_auth || api_keys.is_some() { None } else { JwtValidator::from_config(&config) };
+    let cors = cors_layer(&config);
+    let cors_allowed_origins: Arc<[String]> = config.cors_allowed_origins.clone().into();
+    let base_path = config.normalized_base_path();
+    let mut server = WebDriverServer::with_config_and_mode(config, rust_browser_mcp::tools::ServerMode::Http)?;
+    tracing::info!(
+        "WebDriver MCP Server listening on WebSocket at {} (Ctrl+C to stop)",
+        bind_addr
+    );
+
+    server.ensure_drivers_started().await?;
+
+    let server_for_cleanup = server.clone();
+
+    let oauth_store = (!no_auth && api_keys.is_none() && jwt.is_none())
+        .then(|| Arc::new(OAuthStore::new(OAuthConfig::default())));
+    let probes = probe_router(server.clone());
+    let mut router = Router::new().route(
+        "/",
+        get(ws_handler).with_state(WsState {
+            server,
+            api_keys: api_keys.clone(),
+            jwt: jwt.clone(),
+            oauth_store: oauth_store.clone(),
+            cors_allowed_origins,
+        }),
+    );
+    if let Some(oauth_store) = oauth_store {
+        router = router.merge(create_oauth_router(oauth_store));
+    }
+    let router = mount_at_base_path(router.merge(probes).layer(cors), &base_path);
+
+    let tcp_listener = tokio::net::TcpListener::bind(bind_addr).await?;
+
+    let root = format!("{bind_addr}{base_path}");
+    if no_auth {
+        tracing::info!("MCP WebSocket endpoint (no auth): ws://{}/", root);
+    } else if api_keys.is_some() {
+        tracing::info!("Protected MCP WebSocket endpoint (API key): ws://{}/", root);
+    } else if jwt.is_some() {
+        tracing::info!("Protected MCP WebSocket endpoint (external IdP JWT): ws://{}/", root);
+    } else {
+        tracing::info!("OAuth endpoints available at:");
+        tracing::info!("  Authorization: http://{}/oauth/authorize", root);
+        tracing::info!("  Callback: http://{}/oauth/callback", root);
+        tracing::info!("Protected MCP WebSocket endpoint: ws://{}/", root);
+    }
+    tracing::info!("Health probes: http://{}/healthz, /readyz, /version", root);
+
+    axum::serve(tcp_listener, router)
+        .with_graceful_shutdown(shutdown_with_cleanup(server_for_cleanup))
+        .await?;
+
+    tracing::info!("WebDriver MCP WebSocket Server stopped");
+    Ok(())
+}
+
+/// Whether a page of the handshake's `Origin` may use the `auth_token` cookie.
+/// Browsers attach cookies to cross-site WebSocket handshakes and CORS does not
+/// apply to them, so only the server's own pages and `cors_allowed_origins` may.
+/// Handshakes without an `Origin` do not come from a page.
+fn cookie_origin_allowed(headers: &HeaderMap, allowed_origins: &[String]) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok()) else {
+        return true;
+    };
+    let origin = origin.trim_end_matches('/');
+    let same_origin = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .zip(origin.split_once("://"))
+        .is_some_and(|(host, (_, origin_host))| host.eq_ignore_ascii_case(origin_host));
+    same_origin
+        || allowed_origins
+            .iter()
+            .any(|allowed| allowed == "*" || allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+}
+
+/// Authenticate the upgrade request, then hand the socket to a fresh MCP session.
+/// Clients that cannot set headers on the handshake may pass `?access_token=`.
+/// The `auth_token` cookie is only accepted from allowed origins.
+async fn ws_handler(
+    State(state): State<WsState>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let token = if let Some(api_keys) = &state.api_keys {
+        match api_keys.authenticate(&headers) {
+            Some(token_info) => Some(token_info),
+            None => return (StatusCode::UNAUTHORIZED, "Missing or invalid API key").into_response(),
+        }
+    } else if state.jwt.is_some() || state.oauth_store.is_some() {
+        let from_cookie = !headers.contains_key(header::AUTHORIZATION) && !query.contains_key("access_token");
+        if from_cookie && !cookie_origin_allowed(&headers, &state.cors_allowed_origins) {
+            return (StatusCode::FORBIDDEN, "Origin not allowed to authenticate with the auth_token cookie")
+                .into_response();
+        }
+        let token = extract_auth_token(&headers).or_else(|| query.get("access_token").cloned());
+        let Some(token) = token else {
+            return (StatusCode::UNAUTHORIZED, "Missing authentication token").into_response();
+        };
+        let token_info = match (&state.jwt, &state.oauth_store) {
+            (Some(jwt), _) => jwt.validate(&token).await.ok(),
+            (None, Some(oauth_store)) => oauth_store.validate_token(&token).await,
+            (None, None) => None,
+        };
+        match token_info {
+            Some(token_info) => Some(token_info),
+            None => return (StatusCode::UNAUTHORIZED, "Invalid token").into_response(),
+        }
+    } else {
+        None
+    };
+
+    let user = token.as_ref().map(|token_info| token_info.user_id.clone());
+    let server = state.server.for_new_session().with_access_token(token);
+    upgrade.on_upgrade(move |socket| serve_socket(server, socket, user))
+}
+
+async fn serve_socket(server: WebDriverServer, socket: WebSocket, user: Option<String>) {
+    let client = user.unwrap_or_else(|| "anonymous".to_string());
+    tracing::debug!("WebSocket client connected: {}", client);
+
+    let (sender, receiver) = socket.split();
+
+    let sink = Box::pin(sender.sink_map_err(std::io::Error::other).with(
+        |message: ServerJsonRpcMessage| async move {
+            serde_json::to_string(&message)
+                .map(|text| Message::Text(text.into()))
+                .map_err(std::io::Error::other)
+        },
+    ));
+
+    // Ping/pong/binary frames carry no MCP traffic; the stream ends when the socket closes
+    let stream = receiver
+        .filter_map(|frame| async move {
+            match frame {
+                Ok(Message::Text(text)) => match serde_json::from_str::<ClientJsonRpcMessage>(text.as_str()) {
+                    Ok(message) => Some(message),
+                    Err(e) => {
+                        tracing::warn!("Ignoring malformed WebSocket message: {}", e);
+                        None
+                    }
+                },
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::debug!("WebSocket receive error: {}", e);
+                    None
+                }
+            }
+        })
+        .boxed();
+
+    match server.serve((sink, stream)).await {
+        Ok(service) => {
+            if let Err(e) = service.waiting().await {
+                tracing::warn!("WebSocket session for {} ended with error: {}", client, e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to start MCP session for WebSocket client {}: {}", client, e),
+    }
+
+    tracing::debug!("WebSocket client disconnected: {}", client);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cookie_origin_allowed() {
+        let handshake = |origin: Option<&str>| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::HOST, "mcp.example.com".parse().unwrap());
+            if let Some(origin) = origin {
+                headers.insert(header::ORIGIN, origin.parse().unwrap());
+            }
+            headers
+        };
+        let allowed = ["https://app.example.com/".to_string()];
+
+        assert!(cookie_origin_allowed(&handshake(None), &[]));
+        assert!(cookie_origin_allowed(&handshake(Some("https://mcp.example.com")), &[]));
+        assert!(cookie_origin_allowed(&handshake(Some("https://app.example.com")), &allowed));
+        assert!(!cookie_origin_allowed(&handshake(Some("https://evil.test")), &allowed));
+        assert!(!cookie_origin_allowed(&handshake(Some("https://evil.test")), &[]));
+        assert!(cookie_origin_allowed(&handshake(Some("https://evil.test")), &["*".to_string()]));
+    }
+}
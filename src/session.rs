@@ -0,0 +1,255 @@
+//! Typed browser automation for embedding
+//!
+//! The MCP tools take and return JSON. Rust services that want the same driver
+//! management without speaking MCP to themselves can drive a browser through
+//! [`BrowserSession`] instead: one named session on a [`ClientManager`], with
+//! async methods returning typed results and [`WebDriverError`]s.
+//!
+//! ```no_run
+//! use rust_browser_mcp::{BrowserSession, ClientManager, Config};
+//!
+//! # async fn run() -> rust_browser_mcp::Result<()> {
+//! let manager = ClientManager::new(Config::from_env())?;
+//! let session = BrowserSession::open(&manager, "chrome_embedded").await?;
+//! let page = session.navigate("https://example.com").await?;
+//! println!("{} at {}", page.title, page.url);
+//! println!("heading: {}", session.text("h1").await?);
+//! session.screenshot().await?.save("example.png")?;
+//! session.close().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{path::Path, time::Duration};
+
+use base64::{Engine as _, engine::general_purpose};
+use fantoccini::{Client, Locator, elements::Element, error::CmdError};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    client::{ClientManager, SessionOptions},
+    error::{Result, WebDriverError},
+};
+
+/// Where a navigation ended up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// URL after redirects
+    pub url: String,
+    pub title: String,
+}
+
+/// A PNG capture of the viewport or an element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshot {
+    pub png: Vec<u8>,
+}
+
+impl Screenshot {
+    /// Width and height from the PNG header, if it is a well-formed PNG
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+        if self.png.len() < 24 || !self.png.starts_with(SIGNATURE) || &self.png[12..16] != b"IHDR" {
+            return None;
+        }
+        let width = u32::from_be_bytes(self.png[16..20].try_into().ok()?);
+        let height = u32::from_be_bytes(self.png[20..24].try_into().ok()?);
+        Some((width, height))
+    }
+
+    pub fn to_base64(&self) -> String {
+        general_purpose::STANDARD.encode(&self.png)
+    }
+
+    /// Write the PNG to `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, &self.png)
+            .map_err(|e| WebDriverError::FileSystem(format!("Failed to write {}: {}", path.display(), e)))
+    }
+}
+
+/// One named browser session on a [`ClientManager`]
+///
+/// Element lookups wait up to `default_wait_timeout_secs` from the manager's
+/// config, and every command runs under its call timeout, just like the tools.
+#[derive(Clone)]
+pub struct BrowserSession {
+    manager: ClientManager,
+    id: String,
+}
+
+impl BrowserSession {
+    /// Start the session `id`, or attach to it when it is already running. As
+    /// with the tools, a `chrome_`/`firefox_`/`edge_` prefix picks the browser.
+    pub async fn open(manager: &ClientManager, id: impl Into<String>) -> Result<Self> {
+        Self::open_with(manager, id, &SessionOptions::default()).await
+    }
+
+    /// [`BrowserSession::open`] with per-session overrides such as headless mode
+    pub async fn open_with(manager: &ClientManager, id: impl Into<String>, options: &SessionOptions) -> Result<Self> {
+        let (id, _) = manager.create_session(Some(id.into()), options).await?;
+        Ok(Self { manager: manager.clone(), id })
+    }
+
+    /// Session id, as the MCP tools know it
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub async fn navigate(&self, url: &str) -> Result<Page> {
+        let client = self.client().await?;
+        self.manager.navigate(&self.id, &client, url).await?;
+        self.page(&client).await
+    }
+
+    /// URL and title of the current page
+    pub async fn current_page(&self) -> Result<Page> {
+        let client = self.client().await?;
+        self.page(&client).await
+    }
+
+    pub async fn back(&self) -> Result<()> {
+        let client = self.client().await?;
+        self.manager.run_command(&self.id, "back", client.back()).await
+    }
+
+    pub async fn refresh(&self) -> Result<()> {
+        let client = self.client().await?;
+        self.manager.run_command(&self.id, "refresh", client.refresh()).await
+    }
+
+    pub async fn click(&self, selector: &str) -> Result<()> {
+        let element = self.find(selector).await?;
+        self.manager.run_command(&self.id, "click", element.click()).await
+    }
+
+    /// Clear the field matching `selector` and type `text` into it
+    pub async fn fill(&self, selector: &str, text: &str) -> Result<()> {
+        let element = self.find(selector).await?;
+        self.manager.run_command(&self.id, "clear", element.clear()).await?;
+        self.manager.run_command(&self.id, "send_keys", element.send_keys(text)).await
+    }
+
+    /// Visible text of the first element matching `selector`
+    pub async fn text(&self, selector: &str) -> Result<String> {
+        let element = self.find(selector).await?;
+        self.manager.run_command(&self.id, "get_text", element.text()).await
+    }
+
+    /// Visible text of every element matching `selector`; empty when none match
+    pub async fn texts(&self, selector: &str) -> Result<Vec<String>> {
+        let client = self.client().await?;
+        let found = client.find_all(Locator::Css(selector));
+        let elements = self.manager.run_command(&self.id, "find_elements", found).await?;
+        let mut texts = Vec::with_capacity(elements.len());
+        for element in elements {
+            texts.push(self.manager.run_command(&self.id, "get_text", element.text()).await?);
+        }
+        Ok(texts)
+    }
+
+    /// Attribute of the first element matching `selector`, `None` when unset
+    pub async fn attribute(&self, selector: &str, name: &str) -> Result<Option<String>> {
+        let element = self.find(selector).await?;
+        self.manager.run_command(&self.id, "get_attribute", element.attr(name)).await
+    }
+
+    /// Whether an element matches `selector` right now, without waiting
+    pub async fn exists(&self, selector: &str) -> Result<bool> {
+        let client = self.client().await?;
+        let found = client.find_all(Locator::Css(selector));
+        let elements = self.manager.run_command(&self.id, "find_elements", found).await?;
+        Ok(!elements.is_empty())
+    }
+
+    /// Wait up to `timeout` for an element matching `selector`
+    pub async fn wait_for(&self, selector: &str, timeout: Duration) -> Result<()> {
+        let client = self.client().await?;
+        self.manager
+            .find_element_with_wait(&client, selector, Some(timeout.as_secs_f64()))
+            .await
+            .map(|_| ())
+            .map_err(|e| Self::element_error(e, selector))
+    }
+
+    /// Run `script` in the page and deserialize what it returns
+    pub async fn execute<T: DeserializeOwned>(&self, script: &str, arguments: Vec<Value>) -> Result<T> {
+        let client = self.client().await?;
+        let value = self.manager.run_command(&self.id, "execute_script", client.execute(script, arguments)).await?;
+        serde_json::from_value(value).map_err(|e| WebDriverError::Serialization(e.to_string()))
+    }
+
+    /// Capture the viewport
+    pub async fn screenshot(&self) -> Result<Screenshot> {
+        let client = self.client().await?;
+        let png = self.manager.run_command(&self.id, "screenshot", client.screenshot()).await?;
+        Ok(Screenshot { png })
+    }
+
+    /// Capture just the first element matching `selector`
+    pub async fn element_screenshot(&self, selector: &str) -> Result<Screenshot> {
+        let element = self.find(selector).await?;
+        let png = self.manager.run_command(&self.id, "screenshot", element.screenshot()).await?;
+        Ok(Screenshot { png })
+    }
+
+    /// Full HTML of the current page
+    pub async fn source(&self) -> Result<String> {
+        let client = self.client().await?;
+        self.manager.run_command(&self.id, "get_page_source", client.source()).await
+    }
+
+    /// Close the browser session
+    pub async fn close(self) -> Result<()> {
+        self.manager.close_session(&self.id).await
+    }
+
+    async fn client(&self) -> Result<Client> {
+        self.manager.get_existing_client(Some(self.id.clone())).await.map(|(_, client)| client)
+    }
+
+    async fn page(&self, client: &Client) -> Result<Page> {
+        let url = self.manager.run_command(&self.id, "get_current_url", client.current_url()).await?;
+        let title = self.manager.run_command(&self.id, "get_title", client.title()).await?;
+        Ok(Page { url: url.to_string(), title })
+    }
+
+    async fn find(&self, selector: &str) -> Result<Element> {
+        let client = self.client().await?;
+        let wait = self.manager.get_config().default_wait_timeout_secs;
+        self.manager
+            .find_element_with_wait(&client, selector, Some(wait))
+            .await
+            .map_err(|e| Self::element_error(e, selector))
+    }
+
+    /// Name the selector in lookup failures instead of passing on the raw driver error
+    fn element_error(error: WebDriverError, selector: &str) -> WebDriverError {
+        match error {
+            WebDriverError::Client(CmdError::WaitTimeout) => WebDriverError::Timeout { selector: selector.to_string() },
+            WebDriverError::Client(e) if e.is_no_such_element() => {
+                WebDriverError::ElementNotFound { selector: selector.to_string() }
+            }
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshot_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&1280u32.to_be_bytes());
+        png.extend_from_slice(&720u32.to_be_bytes());
+        let screenshot = Screenshot { png };
+        assert_eq!(screenshot.dimensions(), Some((1280, 720)));
+        assert!(screenshot.to_base64().starts_with("iVBORw0KGgo"));
+
+        assert_eq!(Screenshot { png: b"GIF89a".to_vec() }.dimensions(), None);
+    }
+}
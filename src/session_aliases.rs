@@ -0,0 +1,162 @@
+//! Sessions bound to a browser
+//!
+//! `create_session` and `navigate` take a `browser`, and optionally a WebDriver
+//! `endpoint` and extra `capabilities`, for the session they open. The session
+//! id then works as an alias for that binding: the browser is opened with it
+//! and later calls find the session's browser here instead of guessing it from
+//! a `firefox_*`/`chrome_*`/`edge_*` id prefix, which only sessions without a
+//! binding still rely on.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::driver::DriverType;
+
+/// Capability keys a session's own `capabilities` may not contain at any level:
+/// they pick the program the driver starts, its arguments and environment, or
+/// what it loads at startup
+pub const DENIED_CAPABILITY_KEYS: [&str; 7] =
+    ["args", "binary", "debuggerAddress", "env", "extensions", "prefs", "profile"];
+
+/// Browser, endpoint and capabilities a session is opened with
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionBinding {
+    #[serde(serialize_with = "serialize_browser")]
+    pub browser: DriverType,
+    /// WebDriver endpoint to open the session at instead of the resolved one
+    pub endpoint: Option<String>,
+    /// Merged into the capabilities the server builds for the browser
+    pub capabilities: Map<String, Value>,
+}
+
+fn serialize_browser<S: serde::Serializer>(browser: &DriverType, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&browser.browser_name().to_lowercase())
+}
+
+impl SessionBinding {
+    pub fn new(browser: DriverType) -> Self {
+        Self { browser, endpoint: None, capabilities: Map::new() }
+    }
+
+    /// Whether a pooled connection, opened with default settings, may serve the session
+    pub fn allows_pooling(&self) -> bool {
+        self.endpoint.is_none() && self.capabilities.is_empty()
+    }
+
+    /// Merge the binding's capabilities into `caps`, see [`merge_capabilities`]
+    pub fn apply_capabilities(&self, caps: &mut Map<String, Value>) {
+        merge_capabilities(caps, &self.capabilities);
+    }
+}
+
+/// Merge `extra` into `caps`. Objects such as `goog:chromeOptions` are merged
+/// key by key at every level and arrays such as `args` are extended, so what
+/// the server sets is replaced only where `extra` names the same scalar.
+pub fn merge_capabilities(caps: &mut Map<String, Value>, extra: &Map<String, Value>) {
+    for (name, value) in extra {
+        match (caps.get_mut(name), value) {
+            (Some(Value::Object(existing)), Value::Object(extra)) => merge_capabilities(existing, extra),
+            (Some(Value::Array(existing)), Value::Array(extra)) => {
+                for item in extra {
+                    if !existing.contains(item) {
+                        existing.push(item.clone());
+                    }
+                }
+            }
+            _ => {
+                caps.insert(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Dotted path of the first denied key in `capabilities`, e.g. `goog:chromeOptions.binary`
+pub fn denied_capability(capabilities: &Map<String, Value>) -> Option<String> {
+    capabilities.iter().find_map(|(name, value)| {
+        if DENIED_CAPABILITY_KEYS.contains(&name.as_str()) {
+            return Some(name.clone());
+        }
+        match value {
+            Value::Object(nested) => denied_capability(nested).map(|path| format!("{name}.{path}")),
+            _ => None,
+        }
+    })
+}
+
+/// Bindings by session key
+#[derive(Clone, Default)]
+pub struct SessionAliases {
+    bindings: Arc<Mutex<HashMap<String, SessionBinding>>>,
+}
+
+impl SessionAliases {
+    pub fn bind(&self, key: &str, binding: SessionBinding) {
+        let mut bindings = self.bindings.lock().unwrap_or_else(|e| e.into_inner());
+        bindings.insert(key.to_string(), binding);
+    }
+
+    pub fn get(&self, key: &str) -> Option<SessionBinding> {
+        let bindings = self.bindings.lock().unwrap_or_else(|e| e.into_inner());
+        bindings.get(key).cloned()
+    }
+
+    /// Forget a closed session
+    pub fn remove(&self, key: &str) {
+        let mut bindings = self.bindings.lock().unwrap_or_else(|e| e.into_inner());
+        bindings.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_binding_merges_capabilities() {
+        let mut binding = SessionBinding::new(DriverType::Firefox);
+        assert!(binding.allows_pooling());
+        binding.capabilities = json!({
+            "moz:firefoxOptions": { "prefs": { "intl.accept_languages": "de" }, "args": ["-devtools", "--headless"] },
+            "acceptInsecureCerts": true
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        assert!(!binding.allows_pooling());
+
+        let mut caps = json!({ "browserName": "firefox", "moz:firefoxOptions": { "args": ["--headless"] } })
+            .as_object()
+            .unwrap()
+            .clone();
+        binding.apply_capabilities(&mut caps);
+        assert_eq!(caps["moz:firefoxOptions"]["args"], json!(["--headless", "-devtools"]));
+        assert_eq!(caps["moz:firefoxOptions"]["prefs"]["intl.accept_languages"], "de");
+        assert_eq!(caps["acceptInsecureCerts"], true);
+
+        let aliases = SessionAliases::default();
+        aliases.bind("work", binding);
+        assert_eq!(serde_json::to_value(aliases.get("work")).unwrap()["browser"], "firefox");
+        aliases.remove("work");
+        assert_eq!(aliases.get("work"), None);
+    }
+
+    #[test]
+    fn test_denied_capabilities() {
+        let caps = |value: Value| value.as_object().unwrap().clone();
+        assert_eq!(denied_capability(&caps(json!({ "acceptInsecureCerts": true }))), None);
+        assert_eq!(
+            denied_capability(&caps(json!({ "goog:chromeOptions": { "binary": "/bin/sh" } }))).as_deref(),
+            Some("goog:chromeOptions.binary")
+        );
+        assert_eq!(
+            denied_capability(&caps(json!({ "moz:firefoxOptions": { "args": ["-profile", "/tmp"] } }))).as_deref(),
+            Some("moz:firefoxOptions.args")
+        );
+    }
+}
@@ -0,0 +1,410 @@
+//! Session health
+//!
+//! A session that stays open for hours collects windows, leaked DOM nodes and
+//! heap, and a page that never goes idle keeps a CPU core busy, slowing every
+//! other session on the machine. `get_session_health` samples each session
+//! over a short window and reports what it uses, with warnings and a
+//! recommendation to recycle once a number crosses its threshold.
+//!
+//! On Chrome and Edge the page numbers come from CDP `Performance.getMetrics`
+//! and the browser processes from `SystemInfo.getProcessInfo`; their resident
+//! memory is read from `/proc` when the browser runs on this machine. Other
+//! browsers report what the page itself can measure: heap where exposed, DOM
+//! nodes and how late timers fire, which tracks a busy main thread.
+
+use std::time::Duration;
+
+use fantoccini::Client;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use crate::{ClientManager, cdp};
+
+/// Longest sampling window a call may ask for
+pub const MAX_HEALTH_SAMPLE_MS: u64 = 10_000;
+
+/// Main thread busy this much of an idle sampling window
+const MAX_IDLE_BUSY_PERCENT: f64 = 50.0;
+
+/// Share of the heap limit the JS heap may use
+const MAX_HEAP_LIMIT_RATIO: f64 = 0.8;
+
+/// JS heap in use, in bytes
+const MAX_HEAP_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// DOM nodes alive in the renderer, including detached ones
+const MAX_DOM_NODES: u64 = 150_000;
+
+/// Open windows and tabs
+const MAX_WINDOWS: usize = 10;
+
+/// Resident memory of all browser processes, in bytes
+const MAX_PROCESS_RSS_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Reads heap and node counts, then measures for `arguments[0]` milliseconds
+/// how late 20ms timers fire; the lateness is time the main thread was busy
+const PAGE_HEALTH_SCRIPT: &str = r#"
+    const [windowMs] = arguments;
+    const done = arguments[arguments.length - 1];
+    const memory = performance.memory;
+    const started = performance.now();
+    let expected = started + 20;
+    let late = 0;
+    const tick = () => {
+        const now = performance.now();
+        late += Math.max(0, now - expected);
+        if (now - started < windowMs) {
+            expected = now + 20;
+            setTimeout(tick, 20);
+            return;
+        }
+        done({
+            busy_percent: Math.min(100, late * 100 / (now - started)),
+            heap_used: memory ? memory.usedJSHeapSize : null,
+            heap_total: memory ? memory.totalJSHeapSize : null,
+            heap_limit: memory ? memory.jsHeapSizeLimit : null,
+            nodes: document.getElementsByTagName('*').length,
+            documents: 1 + document.querySelectorAll('iframe, frame').length
+        });
+    };
+    setTimeout(tick, 20);
+"#;
+
+/// How busy the page's main thread was while sampled
+#[derive(Debug, Clone, Serialize)]
+pub struct CpuUsage {
+    pub busy_percent: f64,
+    /// "cdp" (main-thread task time) or "timer_lag" (estimated in the page)
+    pub source: &'static str,
+}
+
+/// JavaScript heap of the current page
+#[derive(Debug, Clone, Serialize)]
+pub struct JsHeap {
+    pub used_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub limit_bytes: Option<u64>,
+}
+
+/// Browser processes serving the session
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessUsage {
+    pub count: usize,
+    /// CPU time over the sampling window; above 100 when several cores are busy
+    pub cpu_percent: f64,
+    /// Resident memory, when the browser runs on this machine
+    pub rss_bytes: Option<u64>,
+}
+
+/// What one session uses, and whether it is due for recycling
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionHealth {
+    pub session: String,
+    pub browser: Option<String>,
+    pub url: Option<String>,
+    pub windows: Option<usize>,
+    pub cpu: Option<CpuUsage>,
+    pub js_heap: Option<JsHeap>,
+    pub dom_nodes: Option<u64>,
+    pub documents: Option<u64>,
+    pub event_listeners: Option<u64>,
+    pub processes: Option<ProcessUsage>,
+    pub warnings: Vec<String>,
+    pub recycle_recommended: bool,
+}
+
+impl SessionHealth {
+    fn new(session: &str, browser: Option<String>) -> Self {
+        Self {
+            session: session.to_string(),
+            browser,
+            url: None,
+            windows: None,
+            cpu: None,
+            js_heap: None,
+            dom_nodes: None,
+            documents: None,
+            event_listeners: None,
+            processes: None,
+            warnings: Vec::new(),
+            recycle_recommended: false,
+        }
+    }
+
+    /// Compare the numbers against the thresholds, filling in the warnings
+    fn assess(&mut self) {
+        let mut warnings = Vec::new();
+        if let Some(cpu) = &self.cpu
+            && cpu.busy_percent > MAX_IDLE_BUSY_PERCENT
+        {
+            warnings.push(format!("the idle page kept its main thread {:.0}% busy", cpu.busy_percent));
+        }
+        if let Some(heap) = &self.js_heap {
+            if heap.used_bytes > MAX_HEAP_BYTES {
+                warnings.push(format!("JS heap uses {}", megabytes(heap.used_bytes)));
+            } else if let Some(limit) = heap.limit_bytes.filter(|limit| *limit > 0)
+                && heap.used_bytes as f64 > limit as f64 * MAX_HEAP_LIMIT_RATIO
+            {
+                warnings.push(format!("JS heap at {:.0}% of its limit", heap.used_bytes as f64 * 100.0 / limit as f64));
+            }
+        }
+        if let Some(nodes) = self.dom_nodes.filter(|nodes| *nodes > MAX_DOM_NODES) {
+            warnings.push(format!("{nodes} DOM nodes alive"));
+        }
+        if let Some(windows) = self.windows.filter(|windows| *windows > MAX_WINDOWS) {
+            warnings.push(format!("{windows} windows open"));
+        }
+        if let Some(rss) = self.processes.as_ref().and_then(|processes| processes.rss_bytes)
+            && rss > MAX_PROCESS_RSS_BYTES
+        {
+            warnings.push(format!("browser processes use {}", megabytes(rss)));
+        }
+        self.recycle_recommended = !warnings.is_empty();
+        self.warnings = warnings;
+    }
+
+    /// One line for the text response
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(cpu) = &self.cpu {
+            parts.push(format!("main thread {:.1}% busy", cpu.busy_percent));
+        }
+        if let Some(heap) = &self.js_heap {
+            parts.push(format!("heap {}", megabytes(heap.used_bytes)));
+        }
+        if let Some(nodes) = self.dom_nodes {
+            parts.push(format!("{nodes} DOM nodes"));
+        }
+        if let Some(windows) = self.windows {
+            parts.push(format!("{windows} window(s)"));
+        }
+        if let Some(processes) = &self.processes {
+            let memory = processes.rss_bytes.map(|rss| format!(", {}", megabytes(rss))).unwrap_or_default();
+            parts.push(format!("{} process(es) at {:.1}% CPU{}", processes.count, processes.cpu_percent, memory));
+        }
+        let verdict = if self.recycle_recommended {
+            format!("recycle: {}", self.warnings.join("; "))
+        } else {
+            "healthy".to_string()
+        };
+        format!(
+            "{} ({}): {} - {}",
+            self.session,
+            self.browser.as_deref().unwrap_or("unknown browser"),
+            if parts.is_empty() { "no measurements".to_string() } else { parts.join(", ") },
+            verdict
+        )
+    }
+}
+
+/// Whether WebDriver `endpoint` runs on this machine
+pub fn is_local_endpoint(endpoint: &str) -> bool {
+    url::Url::parse(endpoint)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .is_some_and(|host| matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]"))
+}
+
+/// Sample `session` for `sample` and assess it. `local` says whether the
+/// browser runs on this machine, so its processes can be looked up by id.
+pub async fn check(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    browser: Option<String>,
+    local: bool,
+    sample: Duration,
+) -> SessionHealth {
+    let mut health = SessionHealth::new(session, browser);
+    health.url = client_manager
+        .run_command(session, "current_url", client.current_url())
+        .await
+        .ok()
+        .map(|url| url.to_string());
+    health.windows = client_manager.run_command(session, "windows", client.windows()).await.ok().map(|w| w.len());
+
+    let cdp_available = cdp::execute(client_manager, session, client, "Performance.enable", json!({})).await.is_ok();
+    if cdp_available {
+        sample_with_cdp(client_manager, session, client, local, sample, &mut health).await;
+    } else {
+        sample_in_page(client_manager, session, client, sample, &mut health).await;
+    }
+    health.assess();
+    health
+}
+
+/// Page and process numbers of a Chromium session from two CDP snapshots
+async fn sample_with_cdp(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    local: bool,
+    sample: Duration,
+    health: &mut SessionHealth,
+) {
+    let metrics = async || {
+        let result = cdp::execute(client_manager, session, client, "Performance.getMetrics", json!({})).await.ok()?;
+        Some(metric_values(&result))
+    };
+    // The process list is not available to every tab; the page numbers still are
+    let processes = async || {
+        let result = cdp::execute(client_manager, session, client, "SystemInfo.getProcessInfo", json!({})).await.ok()?;
+        Some(process_times(&result))
+    };
+
+    let (metrics_before, processes_before) = (metrics().await, processes().await);
+    let started = tokio::time::Instant::now();
+    tokio::time::sleep(sample).await;
+    let (metrics_after, processes_after) = (metrics().await, processes().await);
+    let window_secs = started.elapsed().as_secs_f64();
+
+    if let Some(after) = &metrics_after {
+        let value = |name: &str| after.get(name).and_then(Value::as_f64);
+        if let Some(before) = &metrics_before
+            && let (Some(start), Some(end)) = (before.get("TaskDuration").and_then(Value::as_f64), value("TaskDuration"))
+        {
+            health.cpu = Some(CpuUsage { busy_percent: busy_percent(end - start, window_secs), source: "cdp" });
+        }
+        health.js_heap = value("JSHeapUsedSize").map(|used| JsHeap {
+            used_bytes: used as u64,
+            total_bytes: value("JSHeapTotalSize").map(|total| total as u64),
+            limit_bytes: None,
+        });
+        health.dom_nodes = value("Nodes").map(|nodes| nodes as u64);
+        health.documents = value("Documents").map(|documents| documents as u64);
+        health.event_listeners = value("JSEventListeners").map(|listeners| listeners as u64);
+    }
+
+    if let (Some(before), Some(after)) = (processes_before, processes_after) {
+        let cpu_secs: f64 = after
+            .iter()
+            .map(|(pid, end)| end - before.iter().find(|(id, _)| id == pid).map(|(_, start)| *start).unwrap_or(*end))
+            .sum();
+        let rss_bytes = if local {
+            after.iter().map(|(pid, _)| resident_bytes(*pid)).sum::<Option<u64>>()
+        } else {
+            None
+        };
+        health.processes = Some(ProcessUsage {
+            count: after.len(),
+            cpu_percent: busy_percent(cpu_secs, window_secs),
+            rss_bytes,
+        });
+    }
+}
+
+/// Page numbers measured by a script in the page, for browsers without CDP
+async fn sample_in_page(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    sample: Duration,
+    health: &mut SessionHealth,
+) {
+    let window_ms = json!(sample.as_millis() as u64);
+    let result = client_manager
+        .run_command(session, "session_health", client.execute_async(PAGE_HEALTH_SCRIPT, vec![window_ms]))
+        .await;
+    let page = match result {
+        Ok(page) => page,
+        Err(e) => {
+            tracing::debug!("Failed to sample the page of session '{}': {}", session, e);
+            return;
+        }
+    };
+    let number = |name: &str| page.get(name).and_then(Value::as_f64);
+    health.cpu = number("busy_percent").map(|busy| CpuUsage { busy_percent: round(busy), source: "timer_lag" });
+    health.js_heap = number("heap_used").map(|used| JsHeap {
+        used_bytes: used as u64,
+        total_bytes: number("heap_total").map(|total| total as u64),
+        limit_bytes: number("heap_limit").map(|limit| limit as u64),
+    });
+    health.dom_nodes = number("nodes").map(|nodes| nodes as u64);
+    health.documents = number("documents").map(|documents| documents as u64);
+}
+
+/// `Performance.getMetrics` result as name -> value
+fn metric_values(result: &Value) -> Map<String, Value> {
+    result["metrics"]
+        .as_array()
+        .map(|metrics| {
+            metrics
+                .iter()
+                .filter_map(|metric| Some((metric["name"].as_str()?.to_string(), metric["value"].clone())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `SystemInfo.getProcessInfo` result as (process id, CPU seconds) pairs
+fn process_times(result: &Value) -> Vec<(u64, f64)> {
+    result["processInfo"]
+        .as_array()
+        .map(|processes| {
+            processes
+                .iter()
+                .filter_map(|process| Some((process["id"].as_u64()?, process["cpuTime"].as_f64().unwrap_or(0.0))))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Resident memory of process `pid` on this machine, from `/proc`
+fn resident_bytes(pid: u64) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    parse_resident_kb(&status).map(|kb| kb * 1024)
+}
+
+fn parse_resident_kb(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Share of `window_secs` that `busy_secs` took, in percent
+fn busy_percent(busy_secs: f64, window_secs: f64) -> f64 {
+    if window_secs <= 0.0 {
+        return 0.0;
+    }
+    round(busy_secs.max(0.0) * 100.0 / window_secs)
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.0} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assess_flags_sessions_to_recycle() {
+        let mut health = SessionHealth::new("work", Some("chrome".to_string()));
+        health.cpu = Some(CpuUsage { busy_percent: 4.0, source: "cdp" });
+        health.js_heap = Some(JsHeap { used_bytes: 50 * 1024 * 1024, total_bytes: None, limit_bytes: None });
+        health.windows = Some(2);
+        health.assess();
+        assert!(!health.recycle_recommended);
+        assert!(health.describe().ends_with("healthy"));
+
+        health.cpu = Some(CpuUsage { busy_percent: 92.0, source: "cdp" });
+        health.windows = Some(14);
+        health.js_heap = Some(JsHeap { used_bytes: 900, total_bytes: None, limit_bytes: Some(1000) });
+        health.assess();
+        assert!(health.recycle_recommended);
+        assert_eq!(health.warnings.len(), 3);
+        assert!(health.warnings[1].contains("90% of its limit"));
+
+        let metrics = json!({ "metrics": [{ "name": "Nodes", "value": 420 }, { "name": "TaskDuration", "value": 1.5 }] });
+        assert_eq!(metric_values(&metrics)["Nodes"], 420);
+        let processes = json!({ "processInfo": [{ "type": "browser", "id": 4242, "cpuTime": 3.25 }] });
+        assert_eq!(process_times(&processes), vec![(4242, 3.25)]);
+        assert_eq!(parse_resident_kb("Name:\tchrome\nVmRSS:\t  204800 kB\n"), Some(204800));
+        assert_eq!(busy_percent(0.25, 1.0), 25.0);
+        assert!(is_local_endpoint("http://localhost:9515"));
+        assert!(!is_local_endpoint("http://grid.internal:4444/wd/hub"));
+    }
+}
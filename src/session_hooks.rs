@@ -0,0 +1,198 @@
+//! Hooks run when a session is created or closed
+//!
+//! A hook is a small fixed recipe: open a page, set cookies, run a script
+//! snippet and dismiss consent banners, in that order, skipping whatever it
+//! leaves out. `session_hooks` in the config applies to every session;
+//! `create_session` can add hooks of its own for the session it opens.
+//! `ClientManager` runs the `create` hooks once a new session is registered
+//! and the `close` hooks before the browser is shut, e.g. to log out.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use fantoccini::{Client, cookies::Cookie};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Result, WebDriverError},
+    overlays::{self, ConsentChoice},
+};
+
+/// When a hook runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookEvent {
+    /// Once a new session is open, before any tool uses it
+    #[default]
+    Create,
+    /// Before the session's browser is shut
+    Close,
+}
+
+/// One hook; its actions run in field order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionHook {
+    pub on: HookEvent,
+    /// Page opened first, e.g. the site the cookies belong to or a logout URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub navigate: Option<String>,
+    /// Cookies set for the domain of the page the session is on
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub cookies: BTreeMap<String, String>,
+    /// Script snippet run in the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Answer consent banners and similar overlays on the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dismiss_overlays: Option<ConsentChoice>,
+}
+
+impl SessionHook {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        let acts = self.navigate.is_some() || !self.cookies.is_empty() || self.script.is_some();
+        if !acts && self.dismiss_overlays.is_none() {
+            return Err("hook does nothing; set navigate, cookies, script or dismiss_overlays".to_string());
+        }
+        if let Some(url) = &self.navigate
+            && url::Url::parse(url).is_err()
+        {
+            return Err(format!("invalid navigate URL '{url}'"));
+        }
+        if self.cookies.keys().any(|name| name.is_empty()) {
+            return Err("cookie names must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Refuse a `script` the caller could not run through execute_script
+    pub fn check_script_allowed(&self, config: &crate::Config) -> std::result::Result<(), String> {
+        match self.script {
+            Some(_) => crate::auth::check_tool_allowed(config, "execute_script")
+                .map_err(|e| format!("hook script refused: {e}")),
+            None => Ok(()),
+        }
+    }
+
+    /// Copy safe to print: cookie values and the script are masked
+    pub fn redacted(&self) -> Self {
+        let mut hook = self.clone();
+        hook.cookies.values_mut().for_each(|value| *value = "***".to_string());
+        hook.script = hook.script.map(|_| "***".to_string());
+        hook
+    }
+
+    /// Run the hook's actions in `client`, each under `timeout`
+    pub async fn run(&self, client: &Client, timeout: Duration) -> Result<()> {
+        if let Some(url) = &self.navigate {
+            within(timeout, "navigate", client.goto(url)).await?;
+        }
+        for (name, value) in &self.cookies {
+            let cookie = Cookie::new(name.clone(), value.clone());
+            within(timeout, "add_cookie", client.add_cookie(cookie)).await?;
+        }
+        if let Some(script) = &self.script {
+            within(timeout, "execute_script", client.execute(script, Vec::new())).await?;
+        }
+        if let Some(choice) = self.dismiss_overlays {
+            within(timeout, "dismiss_overlays", overlays::dismiss_command(client, choice)).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Hooks run with plain timeouts rather than `ClientManager::run_command`, whose
+/// recovery from a dead session closes it and would run the close hooks again
+async fn within<T, E>(
+    timeout: Duration,
+    operation: &str,
+    command: impl std::future::Future<Output = std::result::Result<T, E>>,
+) -> Result<T>
+where
+    WebDriverError: From<E>,
+{
+    match tokio::time::timeout(timeout, command).await {
+        Ok(result) => result.map_err(WebDriverError::from),
+        Err(_) => Err(WebDriverError::CommandTimeout {
+            operation: operation.to_string(),
+            timeout_secs: timeout.as_secs(),
+        }),
+    }
+}
+
+/// Hooks `create_session` added, by session key
+#[derive(Clone, Default)]
+pub struct SessionHooks {
+    hooks: Arc<Mutex<HashMap<String, Vec<SessionHook>>>>,
+}
+
+impl SessionHooks {
+    pub fn set(&self, key: &str, hooks: Vec<SessionHook>) {
+        let mut all = self.hooks.lock().unwrap_or_else(|e| e.into_inner());
+        all.insert(key.to_string(), hooks);
+    }
+
+    /// Hooks of `key` that run on `event`
+    pub fn for_event(&self, key: &str, event: HookEvent) -> Vec<SessionHook> {
+        let all = self.hooks.lock().unwrap_or_else(|e| e.into_inner());
+        all.get(key)
+            .map(|hooks| hooks.iter().filter(|hook| hook.on == event).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget a closed session
+    pub fn remove(&self, key: &str) {
+        let mut all = self.hooks.lock().unwrap_or_else(|e| e.into_inner());
+        all.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hooks_parse_and_validate() {
+        let hooks: Vec<SessionHook> = serde_json::from_value(json!([
+            { "navigate": "https://shop.example.com", "cookies": { "consent": "yes" } },
+            { "on": "close", "navigate": "https://shop.example.com/logout" },
+            { "script": "localStorage.clear()", "dismiss_overlays": "reject" }
+        ]))
+        .unwrap();
+        assert!(hooks.iter().all(|hook| hook.validate().is_ok()));
+        assert_eq!(hooks[0].on, HookEvent::Create);
+        assert_eq!(hooks[2].dismiss_overlays, Some(ConsentChoice::Reject));
+
+        let store = SessionHooks::default();
+        store.set("work", hooks);
+        assert_eq!(store.for_event("work", HookEvent::Create).len(), 2);
+        let close = store.for_event("work", HookEvent::Close);
+        assert_eq!(close[0].navigate.as_deref(), Some("https://shop.example.com/logout"));
+        store.remove("work");
+        assert!(store.for_event("work", HookEvent::Close).is_empty());
+
+        assert!(SessionHook::default().validate().is_err());
+        let relative = SessionHook { navigate: Some("/logout".to_string()), ..SessionHook::default() };
+        assert!(relative.validate().unwrap_err().contains("/logout"));
+        assert!(serde_json::from_value::<SessionHook>(json!({ "navigate": "https://a.test", "wait": 1 })).is_err());
+    }
+
+    #[test]
+    fn test_script_hooks_need_execute_script() {
+        let script = SessionHook { script: Some("localStorage.clear()".to_string()), ..SessionHook::default() };
+        let navigate = SessionHook { navigate: Some("https://a.test".to_string()), ..SessionHook::default() };
+        let mut config = crate::Config::default();
+        assert!(script.check_script_allowed(&config).is_ok());
+
+        config.disabled_tools.push("execute_script".to_string());
+        assert!(script.check_script_allowed(&config).unwrap_err().contains("disabled by server configuration"));
+        assert!(navigate.check_script_allowed(&config).is_ok());
+
+        config.session_hooks = vec![script];
+        assert!(config.validate().unwrap_err().contains("execute_script"));
+    }
+}
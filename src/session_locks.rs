@@ -0,0 +1,61 @@
+//! Per-session locks for opening sessions
+//!
+//! Starting a browser, or checking that an open one still answers, can take
+//! seconds. Done under the lock of the whole session table, it held up the tool
+//! calls of every other session. Each session gets a lock of its own instead:
+//! calls for one session wait until it is open, calls for others carry on.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{Mutex as SessionLock, OwnedMutexGuard};
+
+/// Locks by session key; clones share them
+#[derive(Clone, Default)]
+pub struct SessionLocks {
+    locks: Arc<Mutex<HashMap<String, Arc<SessionLock<()>>>>>,
+}
+
+impl SessionLocks {
+    /// Wait until no other call is opening or checking the session `key`.
+    /// It stays locked until the guard is dropped.
+    pub async fn lock(&self, key: &str) -> OwnedMutexGuard<()> {
+        let lock = self
+            .locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key.to_string())
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+
+    /// Forget the lock of a closed session, unless a call still holds or waits for it
+    pub fn remove(&self, key: &str) {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        if locks.get(key).is_some_and(|lock| Arc::strong_count(lock) == 1) {
+            locks.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sessions_lock_independently() {
+        let locks = SessionLocks::default();
+        let first = locks.lock("a").await;
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(50), locks.lock("b")).await.is_ok());
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(50), locks.lock("a")).await.is_err());
+
+        locks.remove("a");
+        assert!(locks.locks.lock().unwrap().contains_key("a"));
+        drop(first);
+        locks.remove("a");
+        assert!(!locks.locks.lock().unwrap().contains_key("a"));
+    }
+}
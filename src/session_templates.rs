@@ -0,0 +1,152 @@
+//! Named session templates
+//!
+//! `session_templates` in the config names sets of session settings that
+//! `create_session` applies with `template: "mobile-de"`: the browser, headless
+//! mode, viewport, a proxy, extra request headers and hooks. Arguments of the
+//! call take precedence over the template's, and the call's hooks run after
+//! the template's.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{SessionOptions, driver::DriverType, screenshots::Viewport, session_hooks::SessionHook};
+
+/// Session settings stored under a name in the config
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionTemplate {
+    /// "chrome", "firefox" or "edge"
+    pub browser: Option<String>,
+    pub headless: Option<bool>,
+    /// Viewport preset such as "iphone-14", or "WIDTHxHEIGHT"
+    pub viewport: Option<String>,
+    /// Proxy all traffic goes through: `host:port`, `http://host:port` or `socks5://host:port`
+    pub proxy: Option<String>,
+    /// Extra headers sent with every request of the session's first tab (Chrome and Edge)
+    pub headers: BTreeMap<String, String>,
+    /// Hooks run when the session is created or closed
+    pub hooks: Vec<SessionHook>,
+}
+
+impl SessionTemplate {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(browser) = &self.browser
+            && DriverType::from_string(browser).is_none()
+        {
+            return Err(format!("invalid browser '{browser}'. Must be one of: chrome, firefox, edge"));
+        }
+        if let Some(viewport) = &self.viewport {
+            Viewport::parse(&json!(viewport))?;
+        }
+        if let Some(proxy) = &self.proxy {
+            proxy_capability(proxy)?;
+        }
+        if self.headers.keys().any(|name| name.trim().is_empty()) {
+            return Err("header names must not be empty".to_string());
+        }
+        for hook in &self.hooks {
+            hook.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Copy safe to print: header values, proxy credentials and hook secrets are masked
+    pub fn redacted(&self) -> Self {
+        let mut template = self.clone();
+        template.headers.values_mut().for_each(|value| *value = "***".to_string());
+        template.proxy = template.proxy.as_deref().map(redacted_proxy);
+        template.hooks = template.hooks.iter().map(SessionHook::redacted).collect();
+        template
+    }
+
+    /// Fill in what `options` leaves unset. The template's headers give way to
+    /// ones already set, and its hooks run before the options' own.
+    pub fn apply(&self, options: &mut SessionOptions) -> Result<(), String> {
+        options.headless = options.headless.or(self.headless);
+        if options.viewport.is_none()
+            && let Some(viewport) = &self.viewport
+        {
+            let viewport = Viewport::parse(&json!(viewport))?;
+            options.viewport = Some((viewport.width, viewport.height));
+        }
+        if options.proxy.is_none() {
+            options.proxy = self.proxy.clone();
+        }
+        for (name, value) in &self.headers {
+            options.headers.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+        options.hooks.splice(0..0, self.hooks.iter().cloned());
+        Ok(())
+    }
+}
+
+/// `proxy` with any `user:password@` masked
+fn redacted_proxy(proxy: &str) -> String {
+    let (scheme, rest) = proxy.split_once("://").unwrap_or(("", proxy));
+    let separator = if scheme.is_empty() { "" } else { "://" };
+    match rest.rsplit_once('@') {
+        Some((_, address)) => format!("{scheme}{separator}***@{address}"),
+        None => proxy.to_string(),
+    }
+}
+
+/// W3C `proxy` capability sending HTTP(S) or SOCKS traffic through `proxy`
+pub fn proxy_capability(proxy: &str) -> Result<Value, String> {
+    let with_scheme = if proxy.contains("://") { proxy.to_string() } else { format!("http://{proxy}") };
+    let url = url::Url::parse(&with_scheme).map_err(|e| format!("invalid proxy '{proxy}': {e}"))?;
+    let host = url.host_str().ok_or_else(|| format!("proxy '{proxy}' names no host"))?;
+    let address = match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    };
+    match url.scheme() {
+        "http" | "https" => Ok(json!({ "proxyType": "manual", "httpProxy": address, "sslProxy": address })),
+        "socks4" => Ok(json!({ "proxyType": "manual", "socksProxy": address, "socksVersion": 4 })),
+        "socks5" => Ok(json!({ "proxyType": "manual", "socksProxy": address, "socksVersion": 5 })),
+        scheme => Err(format!("unsupported proxy scheme '{scheme}'. Must be one of: http, https, socks4, socks5")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_fills_unset_options() {
+        let template: SessionTemplate = toml::from_str(
+            r#"
+            browser = "chrome"
+            headless = true
+            viewport = "iphone-14"
+            proxy = "socks5://proxy.internal:1080"
+            headers = { "Accept-Language" = "de-DE", "X-Test" = "template" }
+            hooks = [{ navigate = "https://shop.example.com", cookies = { region = "de" } }]
+            "#,
+        )
+        .unwrap();
+        assert!(template.validate().is_ok());
+
+        let mut options = SessionOptions {
+            headless: Some(false),
+            headers: BTreeMap::from([("X-Test".to_string(), "call".to_string())]),
+            hooks: vec![SessionHook { script: Some("1".to_string()), ..SessionHook::default() }],
+            ..SessionOptions::default()
+        };
+        template.apply(&mut options).unwrap();
+        assert_eq!(options.headless, Some(false));
+        assert_eq!(options.viewport, Some((390, 844)));
+        assert_eq!(options.headers["X-Test"], "call");
+        assert_eq!(options.headers["Accept-Language"], "de-DE");
+        assert_eq!(options.hooks[0].navigate.as_deref(), Some("https://shop.example.com"));
+        assert_eq!(options.hooks[1].script.as_deref(), Some("1"));
+
+        let proxy = proxy_capability(options.proxy.as_deref().unwrap()).unwrap();
+        assert_eq!(proxy, json!({ "proxyType": "manual", "socksProxy": "proxy.internal:1080", "socksVersion": 5 }));
+        assert_eq!(proxy_capability("10.0.0.1:3128").unwrap()["sslProxy"], "10.0.0.1:3128");
+        assert!(proxy_capability("ftp://proxy:21").is_err());
+        let invalid = SessionTemplate { viewport: Some("watch".to_string()), ..SessionTemplate::default() };
+        assert!(invalid.validate().unwrap_err().contains("watch"));
+    }
+}
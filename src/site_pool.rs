@@ -0,0 +1,178 @@
+//! Per-site sessions for multi-site scraping
+//!
+//! With `site_pool_enabled`, a `navigate` that names no session goes to a
+//! session of its own per origin (`site_example.com`), so each site keeps its
+//! cookies warm between visits and sites never share browser state. Calls that
+//! follow without a session land in the session navigated last. Navigations to
+//! the same origin are spaced at least `site_min_interval_ms` apart, whichever
+//! session makes them. Each user keeps at most `site_pool_max_sessions` site
+//! sessions; the least recently used one is closed to make room for a new site.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::config::Config;
+
+/// Prefix of the sessions the pool opens
+pub const SITE_SESSION_PREFIX: &str = "site_";
+
+/// Origin of `url` (`https://example.com:8443`), or `None` for URLs without one
+/// such as `about:blank` and `data:`
+pub fn origin_of(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    let origin = url.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+/// Session the pool uses for `url`'s site: its host, plus the port when not the default
+pub fn site_session_name(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    let site = match url.port() {
+        Some(port) => format!("{host}_{port}"),
+        None => host.to_string(),
+    };
+    let site: String = site
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    Some(format!("{SITE_SESSION_PREFIX}{site}"))
+}
+
+struct SiteSession {
+    /// Client-visible session id
+    session: String,
+    principal: Option<String>,
+    last_used: Instant,
+}
+
+#[derive(Default)]
+struct PoolState {
+    /// Site sessions by session key
+    sessions: HashMap<String, SiteSession>,
+    /// Site session navigated last, per principal
+    current: HashMap<Option<String>, String>,
+    /// Earliest time the next navigation to each origin may start
+    next_navigation: HashMap<String, Instant>,
+}
+
+/// What a navigation has to do before it starts
+#[derive(Debug, Default, PartialEq)]
+pub struct Visit {
+    /// Politeness delay for the target origin
+    pub wait: Duration,
+    /// Site session to close to stay within `site_pool_max_sessions`
+    pub evict: Option<String>,
+}
+
+/// Site sessions and per-origin navigation slots
+#[derive(Clone)]
+pub struct SitePool {
+    state: Arc<Mutex<PoolState>>,
+    min_interval: Duration,
+    max_sessions: usize,
+}
+
+impl SitePool {
+    pub fn new(min_interval: Duration, max_sessions: usize) -> Self {
+        Self { state: Arc::default(), min_interval, max_sessions }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(Duration::from_millis(config.site_min_interval_ms), config.site_pool_max_sessions)
+    }
+
+    /// Site session `principal` navigated last
+    pub fn current(&self, principal: &Option<String>) -> Option<String> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.current.get(principal).cloned()
+    }
+
+    /// Take the next navigation slot of `origin` for `session` (internal key
+    /// `key`). A `site` session becomes the principal's current one and may push
+    /// the least recently used site session out of the pool.
+    pub fn visit(&self, principal: Option<String>, session: &str, key: &str, origin: &str, site: bool) -> Visit {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+
+        let slot = state.next_navigation.get(origin).copied().filter(|slot| *slot > now).unwrap_or(now);
+        state.next_navigation.insert(origin.to_string(), slot + self.min_interval);
+        state.next_navigation.retain(|_, next| *next > now);
+        let mut visit = Visit { wait: slot - now, evict: None };
+        if !site {
+            return visit;
+        }
+
+        state.sessions.insert(
+            key.to_string(),
+            SiteSession { session: session.to_string(), principal: principal.clone(), last_used: now },
+        );
+        state.current.insert(principal.clone(), session.to_string());
+
+        let owned = state.sessions.values().filter(|entry| entry.principal == principal).count();
+        if owned > self.max_sessions {
+            let oldest = state
+                .sessions
+                .iter()
+                .filter(|(other, entry)| entry.principal == principal && other.as_str() != key)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(other, _)| other.clone());
+            if let Some(oldest) = oldest.and_then(|oldest| state.sessions.remove(&oldest)) {
+                visit.evict = Some(oldest.session);
+            }
+        }
+        visit
+    }
+
+    /// Forget a closed session
+    pub fn remove(&self, key: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = state.sessions.remove(key)
+            && state.current.get(&entry.principal) == Some(&entry.session)
+        {
+            state.current.remove(&entry.principal);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_site_names_and_origins() {
+        assert_eq!(site_session_name("https://Shop.Example.com/a?b=1").as_deref(), Some("site_shop.example.com"));
+        assert_eq!(site_session_name("http://localhost:8080/").as_deref(), Some("site_localhost_8080"));
+        assert_eq!(site_session_name("http://[::1]:3000/").as_deref(), Some("site____1__3000"));
+        assert_eq!(site_session_name("about:blank"), None);
+        assert_eq!(origin_of("https://example.com/path").as_deref(), Some("https://example.com"));
+        assert_eq!(origin_of("data:text/html,hi"), None);
+    }
+
+    #[test]
+    fn test_pool_spaces_navigations_and_evicts_least_recent() {
+        let pool = SitePool::new(Duration::from_secs(60), 2);
+        let origin = "https://a.test";
+        assert_eq!(pool.visit(None, "site_a.test", "site_a.test", origin, true), Visit::default());
+        let again = pool.visit(None, "site_a.test", "site_a.test", origin, true);
+        assert!(again.wait > Duration::from_secs(59), "{again:?}");
+        // Explicit sessions wait their turn but do not join the pool
+        let explicit = pool.visit(None, "mine", "mine", origin, false);
+        assert!(explicit.wait > Duration::from_secs(119), "{explicit:?}");
+        assert_eq!(pool.current(&None).as_deref(), Some("site_a.test"));
+
+        pool.visit(None, "site_b.test", "site_b.test", "https://b.test", true);
+        // Other users' sessions count against their own limit
+        let alice = Some("alice".to_string());
+        pool.visit(alice.clone(), "site_c.test", "site_c.test@alice", "https://c.test", true);
+        let visit = pool.visit(None, "site_c.test", "site_c.test", "https://c.test", true);
+        assert_eq!(visit.evict.as_deref(), Some("site_a.test"));
+        assert_eq!(pool.current(&alice).as_deref(), Some("site_c.test"));
+
+        pool.remove("site_c.test");
+        assert_eq!(pool.current(&None), None);
+    }
+}
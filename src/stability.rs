@@ -0,0 +1,99 @@
+//! Waiting for an element to hold still
+//!
+//! A click aimed at an element that is still sliding, fading or being laid
+//! out again lands wherever WebDriver computed its centre, often on whatever
+//! moves in next. Before `click` and `send_keys` act, the element is checked
+//! once per animation frame until it is visible, enabled and has the same
+//! bounding box in two consecutive frames.
+
+use std::time::Duration;
+
+use fantoccini::{Client, elements::Element};
+use serde_json::{Value, json};
+
+use crate::{ClientManager, element_handles::element_reference};
+
+/// Resolves to `{stable: true}` once element `arguments[0]` is visible, enabled
+/// and unmoved between two frames, or `{stable: false, reason}` after
+/// `arguments[1]` milliseconds. Frames are backed by a timer so a throttled
+/// background tab still finishes.
+const STABILITY_SCRIPT: &str = r#"
+    const [element, timeoutMs] = arguments;
+    const done = arguments[arguments.length - 1];
+    const started = performance.now();
+    const problem = () => {
+        if (!element.isConnected) return 'it was removed from the page';
+        const style = window.getComputedStyle(element);
+        const rect = element.getBoundingClientRect();
+        if (style.display === 'none' || style.visibility === 'hidden' || (rect.width === 0 && rect.height === 0)) {
+            return 'it is not visible';
+        }
+        if (element.matches(':disabled') || element.getAttribute('aria-disabled') === 'true') {
+            return 'it is disabled';
+        }
+        return null;
+    };
+    let lastBox = null;
+    const frame = () => {
+        const found = problem();
+        const rect = element.getBoundingClientRect();
+        const box = [rect.x, rect.y, rect.width, rect.height].join(',');
+        if (!found && box === lastBox) {
+            return done({ stable: true, waited_ms: Math.round(performance.now() - started) });
+        }
+        if (performance.now() - started >= timeoutMs) {
+            return done({ stable: false, reason: found || 'it kept moving' });
+        }
+        lastBox = found ? null : box;
+        schedule();
+    };
+    const schedule = () => {
+        let fired = false;
+        const run = () => {
+            if (!fired) {
+                fired = true;
+                frame();
+            }
+        };
+        requestAnimationFrame(run);
+        setTimeout(run, 100);
+    };
+    schedule();
+"#;
+
+/// Wait up to `timeout` for `element` to be visible, enabled and stationary.
+/// Returns why it is not when time runs out.
+pub async fn wait_until_stable(
+    client_manager: &ClientManager,
+    session: &str,
+    client: &Client,
+    element: &Element,
+    timeout: Duration,
+) -> crate::Result<Option<String>> {
+    let arguments = vec![element_reference(&element.element_id()), json!(timeout.as_millis() as u64)];
+    let result = client_manager
+        .run_command(session, "wait_until_stable", client.execute_async(STABILITY_SCRIPT, arguments))
+        .await?;
+    Ok(unstable_reason(&result))
+}
+
+/// Reason a stability check failed, or `None` when the element held still
+fn unstable_reason(result: &Value) -> Option<String> {
+    if result.get("stable").and_then(Value::as_bool).unwrap_or(false) {
+        return None;
+    }
+    let reason = result.get("reason").and_then(Value::as_str).unwrap_or("it did not settle");
+    Some(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unstable_reason() {
+        assert_eq!(unstable_reason(&json!({ "stable": true, "waited_ms": 34 })), None);
+        assert_eq!(unstable_reason(&json!({ "stable": false, "reason": "it is disabled" })).unwrap(), "it is disabled");
+        assert_eq!(unstable_reason(&Value::Null).unwrap(), "it did not settle");
+    }
+}
@@ -0,0 +1,181 @@
+//! Lightweight runtime counters for `get_server_stats`
+//!
+//! Tool calls and recipe runs are counted here as they complete. Each tool keeps a
+//! bounded window of recent latencies from which percentiles are computed on demand,
+//! so memory use stays flat no matter how long the server runs. Names that are not
+//! built-in tools, such as published recipe tools or names a client made up, share
+//! one "unknown" entry so callers cannot grow the table.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::tools::ToolDefinitions;
+
+/// Latency samples kept per tool for percentile calculation
+const LATENCY_WINDOW: usize = 512;
+
+/// Entry counting calls to names that are not built-in tools
+const UNKNOWN_TOOL: &str = "unknown";
+
+#[derive(Default)]
+struct ToolCounters {
+    calls: u64,
+    errors: u64,
+    latencies_ms: VecDeque<u64>,
+}
+
+#[derive(Default)]
+struct Counters {
+    tools: HashMap<String, ToolCounters>,
+    recipe_runs: u64,
+    recipe_failures: u64,
+    page_crashes: u64,
+}
+
+/// Call count and latency percentiles for one tool
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolStats {
+    pub calls: u64,
+    pub errors: u64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Shared counters; clones update the same totals
+#[derive(Clone)]
+pub struct ServerStats {
+    started_at: Instant,
+    counters: Arc<Mutex<Counters>>,
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServerStats {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            counters: Arc::default(),
+        }
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    pub fn record_tool_call(&self, tool: &str, duration: Duration, is_error: bool) {
+        let name = if ToolDefinitions::is_defined(tool) { tool } else { UNKNOWN_TOOL };
+        let mut counters = self.lock();
+        let tool = counters.tools.entry(name.to_string()).or_default();
+        tool.calls += 1;
+        if is_error {
+            tool.errors += 1;
+        }
+        tool.latencies_ms.push_back(duration.as_millis() as u64);
+        if tool.latencies_ms.len() > LATENCY_WINDOW {
+            tool.latencies_ms.pop_front();
+        }
+    }
+
+    pub fn record_recipe_run(&self, success: bool) {
+        let mut counters = self.lock();
+        counters.recipe_runs += 1;
+        if !success {
+            counters.recipe_failures += 1;
+        }
+    }
+
+    pub fn record_page_crash(&self) {
+        self.lock().page_crashes += 1;
+    }
+
+    /// Renderer crashes seen since startup
+    pub fn page_crashes(&self) -> u64 {
+        self.lock().page_crashes
+    }
+
+    /// `(runs, failures)` since startup
+    pub fn recipe_runs(&self) -> (u64, u64) {
+        let counters = self.lock();
+        (counters.recipe_runs, counters.recipe_failures)
+    }
+
+    /// Per-tool statistics, sorted by tool name
+    pub fn tool_stats(&self) -> BTreeMap<String, ToolStats> {
+        self.lock()
+            .tools
+            .iter()
+            .map(|(name, counters)| {
+                let mut sorted: Vec<u64> = counters.latencies_ms.iter().copied().collect();
+                sorted.sort_unstable();
+                let stats = ToolStats {
+                    calls: counters.calls,
+                    errors: counters.errors,
+                    p50_ms: Self::percentile(&sorted, 50),
+                    p90_ms: Self::percentile(&sorted, 90),
+                    p99_ms: Self::percentile(&sorted, 99),
+                    max_ms: sorted.last().copied().unwrap_or(0),
+                };
+                (name.clone(), stats)
+            })
+            .collect()
+    }
+
+    /// Nearest-rank percentile of an ascending slice
+    fn percentile(sorted: &[u64], pct: usize) -> u64 {
+        if sorted.is_empty() {
+            return 0;
+        }
+        let rank = (pct * sorted.len()).div_ceil(100).max(1);
+        sorted[rank.min(sorted.len()) - 1]
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Counters> {
+        self.counters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_percentiles_and_recipe_counts() {
+        let stats = ServerStats::new();
+        for ms in 1..=100 {
+            stats.record_tool_call("navigate", Duration::from_millis(ms), ms % 10 == 0);
+        }
+        stats.record_tool_call("click", Duration::from_millis(7), false);
+        stats.record_recipe_run(true);
+        stats.record_recipe_run(false);
+
+        let tools = stats.tool_stats();
+        let navigate = &tools["navigate"];
+        assert_eq!(navigate.calls, 100);
+        assert_eq!(navigate.errors, 10);
+        assert_eq!(navigate.p50_ms, 50);
+        assert_eq!(navigate.p90_ms, 90);
+        assert_eq!(navigate.p99_ms, 99);
+        assert_eq!(navigate.max_ms, 100);
+        assert_eq!(tools["click"].p99_ms, 7);
+        assert_eq!(stats.recipe_runs(), (2, 1));
+
+        // Made-up names share one entry instead of adding their own
+        for n in 0..100 {
+            stats.record_tool_call(&format!("no_such_tool_{n}"), Duration::from_millis(1), true);
+        }
+        let tools = stats.tool_stats();
+        assert_eq!(tools.len(), 3);
+        assert_eq!(tools["unknown"].calls, 100);
+    }
+}
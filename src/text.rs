@@ -0,0 +1,70 @@
+//! String truncation that respects UTF-8 character boundaries, and whitespace cleanup
+//!
+//! Page text is arbitrary Unicode, so byte-offset slicing like `&text[..97]`
+//! panics as soon as the cut lands inside an emoji or a CJK character. Previews
+//! and size caps go through these helpers instead.
+
+/// Marker appended to text cut by [`preview`]
+pub const ELLIPSIS: &str = "...";
+
+/// Longest prefix of `text` that fits in `max_bytes`, cut at a character boundary
+pub fn truncate_bytes(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// `text` unchanged when it fits in `max_bytes`, otherwise its longest prefix that
+/// fits together with a trailing `...`
+pub fn preview(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let prefix = truncate_bytes(text, max_bytes.saturating_sub(ELLIPSIS.len()));
+    format!("{prefix}{ELLIPSIS}")
+}
+
+/// `text` with every run of whitespace, newlines included, collapsed to one space
+/// and the ends trimmed
+pub fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// First `max_chars` characters of `text`, and whether anything was cut
+pub fn truncate_chars(text: &str, max_chars: usize) -> (&str, bool) {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => (&text[..end], true),
+        None => (text, false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncation_never_splits_characters() {
+        assert_eq!(truncate_bytes("hello", 10), "hello");
+        assert_eq!(truncate_bytes("hello", 3), "hel");
+        // "é" is two bytes, "😀" four and "漢" three
+        assert_eq!(truncate_bytes("café", 4), "caf");
+        assert_eq!(truncate_bytes("😀😀", 5), "😀");
+        assert_eq!(truncate_bytes("漢字", 2), "");
+
+        assert_eq!(preview("short", 100), "short");
+        let long = "漢".repeat(50);
+        let cut = preview(&long, 100);
+        assert!(cut.len() <= 100);
+        assert!(cut.ends_with(ELLIPSIS));
+        assert_eq!(cut, format!("{}{ELLIPSIS}", "漢".repeat(32)));
+
+        assert_eq!(truncate_chars("漢字です", 2), ("漢字", true));
+        assert_eq!(truncate_chars("abc", 3), ("abc", false));
+        assert_eq!(normalize_whitespace("  Blue\n\t mug \u{a0} 250 ml "), "Blue mug 250 ml");
+    }
+}
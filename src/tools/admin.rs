@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use rmcp::model::Tool;
+use serde_json::json;
+
+use super::read_only_hints;
+
+pub struct AdminTools;
+
+impl AdminTools {
+    pub fn get_tools() -> Vec<Tool> {
+        vec![
+            Self::get_audit_log_tool(),
+            Self::get_server_stats_tool(),
+        ]
+    }
+
+    fn get_audit_log_tool() -> Tool {
+        Tool {
+            name: "get_audit_log".into(),
+            description: Some("Show recent tool calls recorded by the audit log (tool, redacted arguments, session, duration, outcome, client)".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "limit": {
+                            "type": "number",
+                            "description": "Maximum number of entries to return, newest first (default: 50)",
+                            "default": 50
+                        },
+                        "tool": {
+                            "type": "string",
+                            "description": "Only return calls to this tool"
+                        },
+                        "session_id": {
+                            "type": "string",
+                            "description": "Only return calls targeting this browser session"
+                        }
+                    }
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn get_server_stats_tool() -> Tool {
+        Tool {
+            name: "get_server_stats".into(),
+            description: Some("Report server uptime, active sessions per browser, tool-call counts with latency percentiles per tool, recipe runs and driver restarts".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {}
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: read_only_hints(),
+        }
+    }
+}
@@ -1,12 +1,25 @@
 use std::sync::Arc;
-use rmcp::model::Tool;
+use rmcp::model::{JsonObject, Tool};
 use serde_json::json;
 
+use super::{
+    destructive_hints, idempotent_hints, interaction_hints, read_only_hints,
+    params::{
+        self, CheckLocalizationParams, DismissOverlaysParams, GetPageEventsParams, GetPageTextParams, GetPopupEventsParams,
+        GetTextParams, LoginViaTokenParams, NavigateParams, RunBatchParams, ScreenshotOnChangeParams, SessionParams,
+        SetCacheDisabledParams, SetWindowPositionParams, SummarizePageParams, UnregisterServiceWorkersParams,
+        WaitForAttributeParams, WaitForDomStableParams, WaitForPropertyParams, WhoamiNetworkParams,
+    },
+};
+
 pub struct AutomationTools;
 
 impl AutomationTools {
     pub fn get_tools() -> Vec<Tool> {
         vec![
+            Self::create_session_tool(),
+            Self::list_sessions_tool(),
+            Self::run_batch_tool(),
             Self::navigation_tool(),
             Self::find_element_tool(),
             Self::click_tool(),
@@ -14,58 +27,191 @@ impl AutomationTools {
             Self::get_title_tool(),
             Self::get_text_tool(),
             Self::execute_script_tool(),
+            Self::execute_async_script_tool(),
             Self::get_current_url_tool(),
             Self::back_tool(),
             Self::forward_tool(),
             Self::refresh_tool(),
             Self::get_page_load_status_tool(),
             Self::screenshot_tool(),
+            Self::screenshot_on_change_tool(),
+            Self::responsive_screenshots_tool(),
+            Self::highlight_elements_tool(),
+            Self::list_artifacts_tool(),
             Self::resize_window_tool(),
+            Self::get_window_size_tool(),
+            Self::maximize_window_tool(),
+            Self::minimize_window_tool(),
+            Self::fullscreen_window_tool(),
+            Self::set_window_position_tool(),
             Self::wait_for_element_tool(),
             Self::wait_for_condition_tool(),
+            Self::wait_for_dom_stable_tool(),
+            Self::wait_for_attribute_tool(),
+            Self::wait_for_property_tool(),
             Self::get_element_info_tool(),
+            Self::get_computed_style_tool(),
+            Self::get_element_rects_tool(),
             Self::get_element_attribute_tool(),
             Self::get_page_source_tool(),
+            Self::summarize_page_tool(),
+            Self::get_page_text_tool(),
+            Self::snapshot_dom_tool(),
+            Self::diff_dom_tool(),
+            Self::audit_accessibility_tool(),
+            Self::crawl_tool(),
+            Self::compare_across_browsers_tool(),
+            Self::check_localization_tool(),
+            Self::whoami_network_tool(),
             Self::get_element_property_tool(),
             Self::find_elements_tool(),
+            Self::element_exists_tool(),
+            Self::count_elements_tool(),
             Self::scroll_to_element_tool(),
             Self::hover_tool(),
+            Self::dismiss_overlays_tool(),
+            Self::get_popup_events_tool(),
+            Self::get_page_events_tool(),
+            Self::list_service_workers_tool(),
+            Self::unregister_service_workers_tool(),
+            Self::set_cache_disabled_tool(),
+            Self::discover_forms_tool(),
             Self::fill_and_submit_form_tool(),
             Self::login_form_tool(),
+            Self::enter_otp_tool(),
+            Self::login_via_token_tool(),
         ]
     }
 
-    fn navigation_tool() -> Tool {
+    fn create_session_tool() -> Tool {
         Tool {
-            name: "navigate".into(),
-            description: Some("Navigate to a URL".into()),
+            name: "create_session".into(),
+            description: Some("Open a browser session. Read-only tools (get_title, find_elements, ...) never start a browser themselves, and when the server runs with auto_create_sessions off every tool needs a session created here first. Reports the running session if one with this id already exists.".into()),
             input_schema: Arc::new(
                 json!({
                     "type": "object",
                     "properties": {
-                        "url": {
+                        "session_id": {
                             "type": "string",
-                            "description": "URL to navigate to"
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
                         },
-                        "session_id": {
+                        "template": {
+                            "type": "string",
+                            "description": "Name of a session template from the server config (session_templates) supplying browser, headless, viewport, proxy, headers and hooks; other arguments of this call take precedence"
+                        },
+                        "browser": {
+                            "type": "string",
+                            "enum": ["chrome", "firefox", "edge"],
+                            "description": "Browser to open the session in. The session id becomes an alias for this browser, endpoint and capabilities"
+                        },
+                        "endpoint": {
+                            "type": "string",
+                            "description": "WebDriver endpoint to open the session at instead of the resolved one; must be the server's webdriver_endpoint or one of its failover_endpoints (requires browser)"
+                        },
+                        "capabilities": {
+                            "type": "object",
+                            "description": "Capabilities merged into the ones the server builds, e.g. {\"acceptInsecureCerts\": true}; browser option objects such as goog:chromeOptions are merged key by key and arrays such as args extended, after the server's extra_capabilities. Refused unless the server sets allow_session_capabilities; args, binary, debuggerAddress, env, extensions, prefs and profile are always refused (requires browser)"
+                        },
+                        "headless": {
+                            "type": "boolean",
+                            "description": "Override the server's headless setting for this session"
+                        },
+                        "private": {
+                            "type": "boolean",
+                            "description": "Start the browser in a private/incognito window (default: false)"
+                        },
+                        "auto_dismiss_banners": {
+                            "type": "boolean",
+                            "description": "Click away cookie/consent banners after every navigate in this session (default: the server's auto_dismiss_banners setting)"
+                        },
+                        "popup_policy": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "enum": ["allow", "block", "switch"],
+                            "description": "What to do with windows pages open: allow them, block them, or switch the session to the newest one and back to its opener when it closes (default: the server's popup_policy setting)"
+                        },
+                        "max_tool_calls": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Tool calls the session may serve before further calls fail with QUOTA_EXCEEDED (default: unlimited)"
+                        },
+                        "max_navigations": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "navigate, back, forward and refresh calls the session may serve (default: unlimited)"
+                        },
+                        "max_runtime_secs": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Seconds after creation the session may be used for (default: unlimited)"
+                        },
+                        "hooks": {
+                            "type": "array",
+                            "description": "Hooks run when this session is created or closed, after the server's session_hooks. Each one opens `navigate`, sets `cookies` on that page's domain, runs `script` and answers overlays with `dismiss_overlays`, in that order",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "on": { "type": "string", "enum": ["create", "close"], "description": "When the hook runs (default: create)" },
+                                    "navigate": { "type": "string", "description": "Absolute URL to open first, e.g. a logout page" },
+                                    "cookies": {
+                                        "type": "object",
+                                        "additionalProperties": { "type": "string" },
+                                        "description": "Cookies to set, by name"
+                                    },
+                                    "script": { "type": "string", "description": "JavaScript run in the page" },
+                                    "dismiss_overlays": { "type": "string", "enum": ["accept", "reject"], "description": "Answer consent banners and similar overlays" }
+                                },
+                                "additionalProperties": false
+                            }
                         }
-                    },
-                    "required": ["url"]
+                    }
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn list_sessions_tool() -> Tool {
+        Tool {
+            name: "list_sessions".into(),
+            description: Some("List open browser sessions with their browser and the WebDriver endpoint each one was started at, which differs from the primary one when session creation failed over".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {}
                 })
                 .as_object()
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn run_batch_tool() -> Tool {
+        Tool {
+            name: "run_batch".into(),
+            description: Some("Run several tool calls in order in one request, e.g. click -> wait_for_element -> get_text, and get every step's result back together. Steps use the batch's session_id unless they name their own; the batch stops at the first failed step unless stop_on_error is false, and is reported as an error when any step failed".into()),
+            input_schema: params::input_schema::<RunBatchParams>(),
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn navigation_tool() -> Tool {
+        Tool {
+            name: "navigate".into(),
+            description: Some("Navigate to a URL. Fails with BLOCKED_BY_CHALLENGE when the site answers with a CAPTCHA or bot-check page, and with POLICY_BLOCKED when the server respects robots.txt and it disallows the URL".into()),
+            input_schema: params::input_schema::<NavigateParams>(),
+            annotations: idempotent_hints(),
         }
     }
 
     fn find_element_tool() -> Tool {
         Tool {
             name: "find_element".into(),
-            description: Some("Find an element by CSS selector, optionally scoped within a parent element. This tool is ideal for finding nested elements in complex structures like charts, forms, or components without requiring complex CSS selectors.".into()),
+            description: Some("Find an element by CSS selector, optionally scoped within a parent element. This tool is ideal for finding nested elements in complex structures like charts, forms, or components without requiring complex CSS selectors. Returns a handle (e.g. el-3) that execute_script can take as an argument.".into()),
             input_schema: Arc::new(
                 json!({
                     "type": "object",
@@ -84,7 +230,7 @@ impl AutomationTools {
                         },
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
                         }
                     },
                     "required": ["selector"]
@@ -93,14 +239,14 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
     fn click_tool() -> Tool {
         Tool {
             name: "click".into(),
-            description: Some("Click an element by CSS selector".into()),
+            description: Some("Click an element by CSS selector, once it is visible, enabled and no longer moving. A click that another element would receive fails with CLICK_INTERCEPTED naming that element".into()),
             input_schema: Arc::new(json!({
                 "type": "object",
                 "properties": {
@@ -115,18 +261,26 @@ impl AutomationTools {
                     "wait_timeout": {
                         "type": "number",
                         "description": "Wait up to this many seconds for element to appear (default: 0 = no wait)"
+                    },
+                    "wait_for_stable": {
+                        "type": "boolean",
+                        "description": "Wait for the element to be visible, enabled and no longer moving before acting, so the action does not land on an animating element (default: true unless the server turns it off)"
+                    },
+                    "scroll_on_intercept": {
+                        "type": "boolean",
+                        "description": "When another element such as a sticky header would receive the click, scroll the element to the middle of the viewport and click once more (default: false)"
                     }
                 },
                 "required": ["selector"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: interaction_hints(),
         }
     }
 
     fn send_keys_tool() -> Tool {
         Tool {
             name: "send_keys".into(),
-            description: Some("Send keys/text to an element".into()),
+            description: Some("Send keys/text to an element. Set typing_delay_ms to type one character at a time with human-like pauses, for autocomplete or bot-detecting inputs that break on instant input".into()),
             input_schema: Arc::new(json!({
                 "type": "object",
                 "properties": {
@@ -138,6 +292,21 @@ impl AutomationTools {
                         "type": "string",
                         "description": "Text to send"
                     },
+                    "clear_first": {
+                        "type": "boolean",
+                        "description": "Clear the element's current value before typing (default: false)"
+                    },
+                    "typing_delay_ms": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "maximum": 2000,
+                        "description": "Average pause between keys in milliseconds; 0 sends the text at once (default: 0)"
+                    },
+                    "typing_jitter_ms": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Random variation of each pause, up to typing_delay_ms (default: half of typing_delay_ms)"
+                    },
                     "session_id": {
                         "type": "string",
                         "description": "Optional session ID (defaults to 'default')"
@@ -145,11 +314,15 @@ impl AutomationTools {
                     "wait_timeout": {
                         "type": "number",
                         "description": "Wait up to this many seconds for element to appear (default: 0 = no wait)"
+                    },
+                    "wait_for_stable": {
+                        "type": "boolean",
+                        "description": "Wait for the element to be visible, enabled and no longer moving before acting, so the action does not land on an animating element (default: true unless the server turns it off)"
                     }
                 },
                 "required": ["selector", "text"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: interaction_hints(),
         }
     }
 
@@ -163,7 +336,7 @@ impl AutomationTools {
                     "properties": {
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
                         }
                     }
                 })
@@ -171,83 +344,74 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
     fn get_text_tool() -> Tool {
         Tool {
             name: "get_text".into(),
-            description: Some("Get text content of an element".into()),
-            input_schema: Arc::new(
-                json!({
-                    "type": "object",
-                    "properties": {
-                        "selector": {
-                            "type": "string",
-                            "description": "CSS selector of element"
-                        },
-                        "session_id": {
-                            "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
-                        }
-                    },
-                    "required": ["selector"]
-                })
-                .as_object()
-                .unwrap()
-                .clone(),
-            ),
-            annotations: None,
+            description: Some("Get text content of an element, or with `all` of every matching element as a JSON array".into()),
+            input_schema: params::input_schema::<GetTextParams>(),
+            annotations: read_only_hints(),
         }
     }
 
     fn execute_script_tool() -> Tool {
         Tool {
             name: "execute_script".into(),
-            description: Some("Execute JavaScript code".into()),
-            input_schema: Arc::new(
-                json!({
-                    "type": "object",
-                    "properties": {
-                        "script": {
-                            "type": "string",
-                            "description": "JavaScript code to execute"
-                        },
-                        "session_id": {
-                            "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
-                        }
-                    },
-                    "required": ["script"]
-                })
-                .as_object()
-                .unwrap()
-                .clone(),
+            description: Some("Execute JavaScript code. Elements found by find_element/find_elements can be passed in `args` by handle, so the script works on exactly those nodes.".into()),
+            input_schema: Self::script_input_schema(
+                "JavaScript code to execute; `return` a value to get it back, and read `args` from `arguments`",
+            ),
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn execute_async_script_tool() -> Tool {
+        Tool {
+            name: "execute_async_script".into(),
+            description: Some("Execute asynchronous JavaScript that finishes by calling the callback passed as its last argument. Elements found by find_element/find_elements can be passed in `args` by handle.".into()),
+            input_schema: Self::script_input_schema(
+                "JavaScript code to execute; call `arguments[arguments.length - 1](value)` to finish with a result",
             ),
-            annotations: None,
+            annotations: interaction_hints(),
         }
     }
 
+    /// Arguments shared by `execute_script` and `execute_async_script`
+    fn script_input_schema(script_description: &str) -> Arc<JsonObject> {
+        Arc::new(
+            json!({
+                "type": "object",
+                "properties": {
+                    "script": {
+                        "type": "string",
+                        "description": script_description
+                    },
+                    "args": {
+                        "type": "array",
+                        "description": "Values passed to the script as `arguments`. `{\"element\": \"el-3\"}` (at any depth) stands for the element find_element/find_elements returned as handle el-3; a handle whose node has left the page fails as stale."
+                    },
+                    "session_id": {
+                        "type": "string",
+                        "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
+                    }
+                },
+                "required": ["script"]
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        )
+    }
+
     fn get_current_url_tool() -> Tool {
         Tool {
             name: "get_current_url".into(),
             description: Some("Get the current URL of the browser".into()),
-            input_schema: Arc::new(
-                json!({
-                    "type": "object",
-                    "properties": {
-                        "session_id": {
-                            "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
-                        }
-                    }
-                })
-                .as_object()
-                .unwrap()
-                .clone(),
-            ),
-            annotations: None,
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: read_only_hints(),
         }
     }
 
@@ -255,21 +419,8 @@ impl AutomationTools {
         Tool {
             name: "back".into(),
             description: Some("Navigate back to the previous page in browser history".into()),
-            input_schema: Arc::new(
-                json!({
-                    "type": "object",
-                    "properties": {
-                        "session_id": {
-                            "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
-                        }
-                    }
-                })
-                .as_object()
-                .unwrap()
-                .clone(),
-            ),
-            annotations: None,
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: interaction_hints(),
         }
     }
 
@@ -277,13 +428,67 @@ impl AutomationTools {
         Tool {
             name: "forward".into(),
             description: Some("Navigate forward to the next page in browser history".into()),
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn refresh_tool() -> Tool {
+        Tool {
+            name: "refresh".into(),
+            description: Some("Refresh/reload the current page".into()),
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn get_page_load_status_tool() -> Tool {
+        Tool {
+            name: "get_page_load_status".into(),
+            description: Some("Check if the page has finished loading".into()),
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn screenshot_tool() -> Tool {
+        Tool {
+            name: "screenshot".into(),
+            description: Some("Take a screenshot of the current page and optionally save to disk. The image is stored as a browser:// resource readable via resources/read; set inline=true to also embed it in the result. Pass highlight to outline elements with numbered or labeled boxes".into()),
             input_schema: Arc::new(
                 json!({
                     "type": "object",
                     "properties": {
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
+                        },
+                        "save_path": {
+                            "type": "string",
+                            "description": "Optional file name to save the screenshot under, relative to this server run's artifacts directory (e.g., 'checkout/step1.png')"
+                        },
+                        "inline": {
+                            "type": "boolean",
+                            "description": "Embed the base64 PNG in the tool result in addition to the resource URI (default: false)",
+                            "default": false
+                        },
+                        "highlight": {
+                            "type": "array",
+                            "items": {
+                                "oneOf": [
+                                    { "type": "string" },
+                                    {
+                                        "type": "object",
+                                        "properties": {
+                                            "selector": { "type": "string" },
+                                            "label": { "type": "string" },
+                                            "color": { "type": "string" }
+                                        },
+                                        "required": ["selector"]
+                                    }
+                                ]
+                            },
+                            "description": "Optional selectors (or {selector, label, color} objects) to outline with labeled boxes in this screenshot only; the boxes are removed after capture"
                         }
                     }
                 })
@@ -291,21 +496,63 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: idempotent_hints(),
         }
     }
 
-    fn refresh_tool() -> Tool {
+    fn responsive_screenshots_tool() -> Tool {
         Tool {
-            name: "refresh".into(),
-            description: Some("Refresh/reload the current page".into()),
+            name: "responsive_screenshots".into(),
+            description: Some("Capture the current page (or url) at several viewport sizes in one call, restoring the window size afterwards. Viewports are presets (mobile, iphone-14, pixel-7, tablet, ipad-pro, laptop, desktop), 'WIDTHxHEIGHT' strings or {width, height, label} objects. Each capture is stored as a labeled browser:// resource; set stitch=true to also get one side-by-side comparison image".into()),
             input_schema: Arc::new(
                 json!({
                     "type": "object",
                     "properties": {
+                        "viewports": {
+                            "type": "array",
+                            "items": {
+                                "oneOf": [
+                                    { "type": "string" },
+                                    {
+                                        "type": "object",
+                                        "properties": {
+                                            "width": { "type": "integer", "minimum": 1 },
+                                            "height": { "type": "integer", "minimum": 1 },
+                                            "label": { "type": "string" }
+                                        },
+                                        "required": ["width", "height"]
+                                    }
+                                ]
+                            },
+                            "maxItems": 10,
+                            "description": "Viewports to capture (default: [\"mobile\", \"tablet\", \"desktop\"])"
+                        },
+                        "url": {
+                            "type": "string",
+                            "description": "Optional URL to navigate to before capturing"
+                        },
+                        "settle_ms": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Wait after each resize before capturing, for layouts and lazy images to settle (default: 500)"
+                        },
+                        "stitch": {
+                            "type": "boolean",
+                            "description": "Also combine the captures into one labeled side-by-side comparison image (default: false)",
+                            "default": false
+                        },
+                        "inline": {
+                            "type": "boolean",
+                            "description": "Embed the images in the tool result; with stitch only the comparison image is embedded (default: false)",
+                            "default": false
+                        },
+                        "save_prefix": {
+                            "type": "string",
+                            "description": "Optional file name prefix; captures are saved as responsive/<prefix>-<label>.png in the artifacts directory"
+                        },
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default')"
                         }
                     }
                 })
@@ -313,21 +560,49 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: idempotent_hints(),
         }
     }
 
-    fn get_page_load_status_tool() -> Tool {
+    fn highlight_elements_tool() -> Tool {
         Tool {
-            name: "get_page_load_status".into(),
-            description: Some("Check if the page has finished loading".into()),
+            name: "highlight_elements".into(),
+            description: Some("Draw labeled bounding boxes around the elements matching each selector, so a following screenshot shows which element is which (e.g. \"box 3 is the third card\"). Boxes are numbered unless a label is given, stay on the page until clear=true or the next call, and do not intercept clicks. Returns each box's label, position and text".into()),
             input_schema: Arc::new(
                 json!({
                     "type": "object",
                     "properties": {
+                        "selectors": {
+                            "type": "array",
+                            "items": {
+                                "oneOf": [
+                                    { "type": "string" },
+                                    {
+                                        "type": "object",
+                                        "properties": {
+                                            "selector": { "type": "string" },
+                                            "label": { "type": "string" },
+                                            "color": { "type": "string" }
+                                        },
+                                        "required": ["selector"]
+                                    }
+                                ]
+                            },
+                            "description": "CSS selectors, or {selector, label, color} objects, to outline; every match of a selector gets its own box"
+                        },
+                        "max_per_selector": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 50,
+                            "description": "Boxes drawn per selector at most (default: 50)"
+                        },
+                        "clear": {
+                            "type": "boolean",
+                            "description": "Remove existing highlights instead of drawing new ones (default: false)"
+                        },
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default')"
                         }
                     }
                 })
@@ -335,25 +610,26 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: idempotent_hints(),
         }
     }
 
-    fn screenshot_tool() -> Tool {
+    fn list_artifacts_tool() -> Tool {
         Tool {
-            name: "screenshot".into(),
-            description: Some("Take a screenshot of the current page and optionally save to disk".into()),
+            name: "list_artifacts".into(),
+            description: Some("List files saved under the artifacts directory (screenshots, recipe outputs, ...), newest first, grouped by server run".into()),
             input_schema: Arc::new(
                 json!({
                     "type": "object",
                     "properties": {
-                        "session_id": {
+                        "run": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Only list artifacts of this run directory; use 'current' for this server run"
                         },
-                        "save_path": {
-                            "type": "string",
-                            "description": "Optional file path to save the screenshot (e.g., '/path/to/screenshot.png')"
+                        "limit": {
+                            "type": "number",
+                            "description": "Maximum number of artifacts to return (default: 100)",
+                            "default": 100
                         }
                     }
                 })
@@ -361,7 +637,7 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
@@ -384,7 +660,7 @@ impl AutomationTools {
                         },
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
                         }
                     }
                 })
@@ -392,7 +668,115 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn get_window_size_tool() -> Tool {
+        Tool {
+            name: "get_window_size".into(),
+            description: Some("Get the browser window's size and position and the page viewport (inner size, device pixel ratio, fullscreen and visibility state)".into()),
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn maximize_window_tool() -> Tool {
+        Tool {
+            name: "maximize_window".into(),
+            description: Some("Maximize the browser window, e.g. to restore it after resize_window, minimize_window or fullscreen_window. Reports the resulting window and viewport size.".into()),
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn minimize_window_tool() -> Tool {
+        Tool {
+            name: "minimize_window".into(),
+            description: Some("Minimize (iconify) the browser window; maximize_window or resize_window restores it".into()),
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn fullscreen_window_tool() -> Tool {
+        Tool {
+            name: "fullscreen_window".into(),
+            description: Some("Switch the browser window to fullscreen, as if the user pressed F11. Reports the resulting window and viewport size.".into()),
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn set_window_position_tool() -> Tool {
+        Tool {
+            name: "set_window_position".into(),
+            description: Some("Move the browser window to a screen position without resizing it".into()),
+            input_schema: params::input_schema::<SetWindowPositionParams>(),
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn screenshot_on_change_tool() -> Tool {
+        Tool {
+            name: "screenshot_on_change".into(),
+            description: Some("Watch an element with a MutationObserver and take a screenshot the moment it appears, is removed, or its content, attributes or text change; captures anyway when the wait runs out. Catches toasts, spinners and animation states a separate screenshot call misses".into()),
+            input_schema: params::input_schema::<ScreenshotOnChangeParams>(),
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn dismiss_overlays_tool() -> Tool {
+        Tool {
+            name: "dismiss_overlays".into(),
+            description: Some("Click away cookie/consent banners, paywall modals and newsletter popups that cover the page, using selectors for common consent platforms (OneTrust, Cookiebot, Didomi, ...) and a text-matching fallback. Reports what was dismissed".into()),
+            input_schema: params::input_schema::<DismissOverlaysParams>(),
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn get_popup_events_tool() -> Tool {
+        Tool {
+            name: "get_popup_events".into(),
+            description: Some("List popups and new windows pages opened in a session (window.open calls, new windows, and whether the session's popup policy blocked, allowed or switched to them), oldest first".into()),
+            input_schema: params::input_schema::<GetPopupEventsParams>(),
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn get_page_events_tool() -> Tool {
+        Tool {
+            name: "get_page_events".into(),
+            description: Some("List lifecycle events of a session's pages, oldest first: navigation committed (including single-page app history changes), DOMContentLoaded, load, dialogs opened, downloads started and frames attached. Events are recorded from each navigate and buffered per session; clients at log level info also receive them as notifications".into()),
+            input_schema: params::input_schema::<GetPageEventsParams>(),
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn list_service_workers_tool() -> Tool {
+        Tool {
+            name: "list_service_workers".into(),
+            description: Some("List the service worker registrations (scope, active/waiting/installing script and state) and Cache Storage caches of the current page's origin, and whether a worker controls the page".into()),
+            input_schema: params::input_schema::<SessionParams>(),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn unregister_service_workers_tool() -> Tool {
+        Tool {
+            name: "unregister_service_workers".into(),
+            description: Some("Unregister service workers of the current page's origin, optionally only those under a scope and together with their Cache Storage caches, so the next load comes from the network".into()),
+            input_schema: params::input_schema::<UnregisterServiceWorkersParams>(),
+            annotations: destructive_hints(),
+        }
+    }
+
+    fn set_cache_disabled_tool() -> Tool {
+        Tool {
+            name: "set_cache_disabled".into(),
+            description: Some("Bypass the browser's HTTP cache for the session's tab, and optionally its service workers, or turn them back on. Chrome and Edge only (Chrome DevTools Protocol)".into()),
+            input_schema: params::input_schema::<SetCacheDisabledParams>(),
+            annotations: idempotent_hints(),
         }
     }
 
@@ -412,11 +796,11 @@ impl AutomationTools {
                         },
                         "timeout_seconds": {
                             "type": "number",
-                            "description": "Maximum time to wait in seconds (default: 10)"
+                            "description": "Maximum time to wait in seconds (default: the server's default wait timeout, 10 unless configured)"
                         },
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
                         }
                     },
                     "required": ["selector"]
@@ -425,7 +809,34 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn wait_for_dom_stable_tool() -> Tool {
+        Tool {
+            name: "wait_for_dom_stable".into(),
+            description: Some("Wait until the page's DOM has not changed for quiet_ms (watched with a MutationObserver), optionally only inside selector. More reliable than readyState for client-rendered apps that keep rendering after load; fails with TIMEOUT if the DOM keeps changing".into()),
+            input_schema: params::input_schema::<WaitForDomStableParams>(),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn wait_for_attribute_tool() -> Tool {
+        Tool {
+            name: "wait_for_attribute".into(),
+            description: Some("Wait until an element's attribute has a value (e.g. aria-busy=\"false\", data-state=\"ready\"), matches a regular expression, is present, or is removed, without writing JavaScript. Fails with TIMEOUT reporting the last value seen".into()),
+            input_schema: params::input_schema::<WaitForAttributeParams>(),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn wait_for_property_tool() -> Tool {
+        Tool {
+            name: "wait_for_property".into(),
+            description: Some("Wait until a DOM property of an element (value, checked, naturalWidth, dataset.state, ...) equals a JSON value, matches a regular expression, or is truthy, without writing JavaScript. Fails with TIMEOUT reporting the last value seen".into()),
+            input_schema: params::input_schema::<WaitForPropertyParams>(),
+            annotations: read_only_hints(),
         }
     }
 
@@ -445,7 +856,7 @@ impl AutomationTools {
                         },
                         "timeout_seconds": {
                             "type": "number",
-                            "description": "Maximum time to wait in seconds (default: 10)"
+                            "description": "Maximum time to wait in seconds (default: the server's default wait timeout, 10 unless configured)"
                         },
                         "check_interval_ms": {
                             "type": "number",
@@ -453,7 +864,7 @@ impl AutomationTools {
                         },
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
                         }
                     },
                     "required": ["condition"]
@@ -462,7 +873,7 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
@@ -479,7 +890,7 @@ impl AutomationTools {
                     },
                     "include_computed_styles": {
                         "type": "boolean",
-                        "description": "Include computed CSS styles (default: false)"
+                        "description": "Include a fixed set of computed CSS styles (default: false). Use get_computed_style to read specific properties as JSON"
                     },
                     "wait_timeout": {
                         "type": "number",
@@ -492,37 +903,127 @@ impl AutomationTools {
                 },
                 "required": ["selector"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
-    fn get_element_attribute_tool() -> Tool {
+    fn get_computed_style_tool() -> Tool {
         Tool {
-            name: "get_attribute".into(),
-            description: Some("Get an HTML attribute value from an element (href, src, class, id, etc.)".into()),
-            input_schema: Arc::new(json!({
-                "type": "object",
-                "properties": {
-                    "selector": {
-                        "type": "string",
-                        "description": "CSS selector of element"
-                    },
-                    "attribute": {
-                        "type": "string", 
-                        "description": "HTML attribute name (e.g., href, src, class, id, alt)"
-                    },
-                    "wait_timeout": {
-                        "type": "number",
-                        "description": "Wait up to this many seconds for element to appear (default: 0 = no wait)"
-                    },
-                    "session_id": {
+            name: "get_computed_style".into(),
+            description: Some("Get computed CSS property values of the first element matching a selector as structured JSON, e.g. properties [\"color\", \"font-size\", \"display\"]. Omit properties to get every computed property".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "selector": {
+                            "type": "string",
+                            "description": "CSS selector of the element"
+                        },
+                        "properties": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "CSS properties to read, in kebab-case or camelCase; custom properties (--name) are allowed (default: all computed properties)"
+                        },
+                        "pseudo_element": {
+                            "type": "string",
+                            "description": "Optional pseudo-element to read instead, e.g. '::before' or '::placeholder'"
+                        },
+                        "wait_timeout": {
+                            "type": "number",
+                            "description": "Wait up to this many seconds for element to appear (default: 0 = no wait)"
+                        },
+                        "session_id": {
+                            "type": "string",
+                            "description": "Optional session ID (defaults to 'default')"
+                        }
+                    },
+                    "required": ["selector"]
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn get_element_rects_tool() -> Tool {
+        Tool {
+            name: "get_element_rects".into(),
+            description: Some("Get viewport-relative bounding boxes (x, y, width, height, center) and visibility of every element matching a selector in one call, plus which visible boxes overlap and whether a click at each center would hit the element. Useful for layout assertions (ordering, overlap, off-screen content) and coordinate-based interactions".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "selector": {
+                            "type": "string",
+                            "description": "CSS selector of the elements to measure"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Maximum number of elements to measure (default: 100)"
+                        },
+                        "session_id": {
+                            "type": "string",
+                            "description": "Optional session ID (defaults to 'default')"
+                        }
+                    },
+                    "required": ["selector"]
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn get_element_attribute_tool() -> Tool {
+        Tool {
+            name: "get_attribute".into(),
+            description: Some("Get an HTML attribute value from an element (href, src, class, id, etc.)".into()),
+            input_schema: Arc::new(json!({
+                "type": "object",
+                "properties": {
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector of element"
+                    },
+                    "attribute": {
+                        "type": "string", 
+                        "description": "HTML attribute name (e.g., href, src, class, id, alt)"
+                    },
+                    "wait_timeout": {
+                        "type": "number",
+                        "description": "Wait up to this many seconds for element to appear (default: 0 = no wait)"
+                    },
+                    "session_id": {
                         "type": "string",
                         "description": "Optional session ID (defaults to 'default')"
                     }
                 },
                 "required": ["selector", "attribute"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn summarize_page_tool() -> Tool {
+        Tool {
+            name: "summarize_page".into(),
+            description: Some("Summarize the current page as compact JSON: title, meta description, H1-H3 outline, an excerpt of the main content's text, form, link and image counts, and detected frameworks (React, Next.js, Vue, Angular, ...). A cheap first look before deciding what to read or click".into()),
+            input_schema: params::input_schema::<SummarizePageParams>(),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn get_page_text_tool() -> Tool {
+        Tool {
+            name: "get_page_text".into(),
+            description: Some("Get the rendered text of the whole page (document.body.innerText), or of the first element matching selector: what a reader sees, without markup, scripts or hidden elements. Much smaller than get_page_source and needs no selector, unlike get_text".into()),
+            input_schema: params::input_schema::<GetPageTextParams>(),
+            annotations: read_only_hints(),
         }
     }
 
@@ -536,7 +1037,7 @@ impl AutomationTools {
                     "properties": {
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
                         }
                     }
                 })
@@ -544,7 +1045,233 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn snapshot_dom_tool() -> Tool {
+        Tool {
+            name: "snapshot_dom".into(),
+            description: Some("Capture a normalized snapshot of the DOM (elements, attributes, own text and form state; scripts and styles left out) and keep it under a name for diff_dom. Typical use: snapshot_dom name=\"before\", interact, then diff_dom before=\"before\" to see what the interaction changed".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Name to save the snapshot under; an existing snapshot with this name is replaced (default: 'default')"
+                        },
+                        "selector": {
+                            "type": "string",
+                            "description": "Optional CSS selector to snapshot only one part of the page (default: the whole document)"
+                        },
+                        "session_id": {
+                            "type": "string",
+                            "description": "Optional session ID (defaults to 'default')"
+                        }
+                    }
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn diff_dom_tool() -> Tool {
+        Tool {
+            name: "diff_dom".into(),
+            description: Some("Report the nodes added, removed and changed (attributes, text, form values) between two DOM snapshots taken with snapshot_dom, or between one snapshot and the page as it is now. Siblings are matched by tag, id and class, so an inserted element is reported once instead of shifting everything after it".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "before": {
+                            "type": "string",
+                            "description": "Name of the earlier snapshot (default: 'default')"
+                        },
+                        "after": {
+                            "type": "string",
+                            "description": "Name of the later snapshot; omit to compare with the current page, using the same selector as the earlier snapshot"
+                        },
+                        "max_changes": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Changes to list at most (default: 200)"
+                        },
+                        "session_id": {
+                            "type": "string",
+                            "description": "Optional session ID (defaults to 'default')"
+                        }
+                    }
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn audit_accessibility_tool() -> Tool {
+        Tool {
+            name: "audit_accessibility".into(),
+            description: Some("Audit the current page for accessibility problems with a bundled, offline axe-style rule engine (image-alt, label, button-name, link-name, color-contrast, heading-order, ...). Returns violations grouped by impact with offending selectors and how to fix them. Set fail_on to turn violations at or above an impact level into an error.".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "selector": {
+                            "type": "string",
+                            "description": "Optional CSS selector to audit only one part of the page (page-level rules such as document-title are skipped)"
+                        },
+                        "rules": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Optional rule ids to run, e.g. [\"image-alt\", \"color-contrast\"] (default: all rules)"
+                        },
+                        "fail_on": {
+                            "type": "string",
+                            "enum": ["minor", "moderate", "serious", "critical"],
+                            "description": "Report the call as failed (CHECK_FAILED) when any violation has this impact or worse"
+                        },
+                        "session_id": {
+                            "type": "string",
+                            "description": "Optional session ID (defaults to 'default')"
+                        }
+                    }
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn crawl_tool() -> Tool {
+        Tool {
+            name: "crawl".into(),
+            description: Some("Crawl a site starting from a URL (or its sitemap.xml), following same-origin links breadth-first up to max_depth/max_pages with a politeness delay between page loads. Reports each page's title, HTTP status, broken images/resources and optional selector extraction; progress is streamed per page and the full report is saved as an artifact. When the server respects robots.txt, disallowed pages are skipped and listed.".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "url": {
+                            "type": "string",
+                            "description": "Start URL, or a sitemap URL (any URL ending in .xml is read as a sitemap)"
+                        },
+                        "sitemap": {
+                            "type": "boolean",
+                            "description": "Treat url as a sitemap and seed the crawl with its <loc> entries (default: true for .xml URLs)"
+                        },
+                        "max_depth": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "How many links away from the start pages to follow (default: 2)"
+                        },
+                        "max_pages": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 500,
+                            "description": "Maximum number of pages to load (default: 20)"
+                        },
+                        "delay_ms": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Pause between page loads in milliseconds (default: 1000)"
+                        },
+                        "extract_selector": {
+                            "type": "string",
+                            "description": "Optional CSS selector whose matching elements' text is collected on every page (up to 50 per page)"
+                        },
+                        "session_id": {
+                            "type": "string",
+                            "description": "Optional session ID (defaults to 'default')"
+                        }
+                    },
+                    "required": ["url"]
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn check_localization_tool() -> Tool {
+        Tool {
+            name: "check_localization".into(),
+            description: Some("Load a page once per locale (Accept-Language, navigator.language and Intl locale overridden; Chrome and Edge only), collect its visible strings and diff them against the base locale's: strings left untranslated, strings missing from a locale, raw translation keys or {{placeholders}} shown to users, and <html lang> not matching the locale. The session returns to its page afterwards. Also available as a recipe step.".into()),
+            input_schema: params::input_schema::<CheckLocalizationParams>(),
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn whoami_network_tool() -> Tool {
+        Tool {
+            name: "whoami_network".into(),
+            description: Some("Check what sites see of a session: loads an echo endpoint (the server's network_echo_url or echo_url) and reports the apparent egress IP, the User-Agent and Accept-Language it received, the browser's languages and timezone, and whether WebRTC leaks a public IP other than the egress IP or an unmasked local one. Flags a User-Agent, language or timezone that disagrees with the browser. Use it to verify proxy and emulation settings took effect. The session returns to its page afterwards.".into()),
+            input_schema: params::input_schema::<WhoamiNetworkParams>(),
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn compare_across_browsers_tool() -> Tool {
+        Tool {
+            name: "compare_across_browsers".into(),
+            description: Some("Load the same URL in several browsers (chrome, firefox, edge) concurrently, optionally running the same actions in each, and compare the results: per-browser screenshots plus a side-by-side image, titles, final URLs, console error/warning counts and load metrics (TTFB, DOMContentLoaded, load, first contentful paint, transfer size). Uses throwaway sessions that are closed afterwards.".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "url": {
+                            "type": "string",
+                            "description": "URL to load in every browser"
+                        },
+                        "browsers": {
+                            "type": "array",
+                            "items": { "type": "string", "enum": ["chrome", "firefox", "edge"] },
+                            "description": "Browsers to compare, at least two (default: the configured concurrent_drivers)"
+                        },
+                        "actions": {
+                            "type": "array",
+                            "maxItems": 50,
+                            "description": "Optional steps run in each browser after loading, in order. Each is an object with a type: {\"type\": \"click\", \"selector\"}, {\"type\": \"type\", \"selector\", \"text\"}, {\"type\": \"scroll\", \"selector\"}, {\"type\": \"wait\", \"duration_ms\"}, {\"type\": \"wait_for\", \"selector\", \"timeout\"} or {\"type\": \"navigate\", \"url\"}. A failing step skips the rest for that browser",
+                            "items": { "type": "object" }
+                        },
+                        "settle_ms": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "maximum": 10000,
+                            "description": "Pause before capturing, after the last action (default: 500)"
+                        },
+                        "stitch": {
+                            "type": "boolean",
+                            "description": "Also build one side-by-side image of all browsers (default: true)"
+                        },
+                        "inline": {
+                            "type": "boolean",
+                            "description": "Return the images in the result, not just as resources (default: false)"
+                        },
+                        "save_prefix": {
+                            "type": "string",
+                            "description": "Optional file name prefix; screenshots are then saved under compare/ in the artifacts directory"
+                        },
+                        "keep_sessions": {
+                            "type": "boolean",
+                            "description": "Leave the per-browser sessions open for further inspection (default: false)"
+                        }
+                    },
+                    "required": ["url"]
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: interaction_hints(),
         }
     }
 
@@ -574,7 +1301,7 @@ impl AutomationTools {
                 },
                 "required": ["selector", "property"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
@@ -582,7 +1309,7 @@ impl AutomationTools {
         Tool {
             name: "find_elements".into(),
             description: Some(
-                "Find all elements matching a CSS selector, optionally scoped within a parent element. Returns basic info about each element found. Perfect for finding multiple items like chart data points, form fields, list items, or menu options within a specific container.".into(),
+                "Find all elements matching a CSS selector, optionally scoped within a parent element. Returns basic info and a handle for execute_script about each element found. Perfect for finding multiple items like chart data points, form fields, list items, or menu options within a specific container.".into(),
             ),
             input_schema: Arc::new(
                 json!({
@@ -600,9 +1327,14 @@ impl AutomationTools {
                             "type": "number",
                             "description": "Wait up to this many seconds for parent element to appear (default: 0 = no wait). Child elements are found immediately once parent is located."
                         },
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Maximum number of elements to describe (default: 100). The total match count is always reported."
+                        },
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
                         }
                     },
                     "required": ["selector"]
@@ -611,7 +1343,7 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
@@ -629,7 +1361,7 @@ impl AutomationTools {
                         },
                         "session_id": {
                             "type": "string",
-                            "description": "Optional session ID (defaults to 'default'). Use 'firefox_*' or 'chrome_*' prefixes to specify browser preference."
+                            "description": "Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated."
                         }
                     },
                     "required": ["selector"]
@@ -638,7 +1370,7 @@ impl AutomationTools {
                 .unwrap()
                 .clone(),
             ),
-            annotations: None,
+            annotations: idempotent_hints(),
         }
     }
 
@@ -664,14 +1396,109 @@ impl AutomationTools {
                 },
                 "required": ["selector"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: idempotent_hints(),
+        }
+    }
+
+    fn element_exists_tool() -> Tool {
+        Tool {
+            name: "element_exists".into(),
+            description: Some("Check whether an element matching a CSS selector exists. Always succeeds with exists=true/false (plus match counts) instead of returning an error, so it is the tool to branch on presence; only an invalid selector is an error".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "selector": {
+                            "type": "string",
+                            "description": "CSS selector to look for"
+                        },
+                        "parent_selector": {
+                            "type": "string",
+                            "description": "Optional CSS selector of a container to search within"
+                        },
+                        "visible_only": {
+                            "type": "boolean",
+                            "description": "Count only rendered, non-hidden matches as existing (default: false)"
+                        },
+                        "wait_timeout": {
+                            "type": "number",
+                            "description": "Keep checking for up to this many seconds before answering false (default: 0 = check once)"
+                        },
+                        "session_id": {
+                            "type": "string",
+                            "description": "Optional session ID (defaults to 'default')"
+                        }
+                    },
+                    "required": ["selector"]
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn count_elements_tool() -> Tool {
+        Tool {
+            name: "count_elements".into(),
+            description: Some("Count the elements matching a CSS selector, and how many of them are visible. Zero matches is a normal result, not an error".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "selector": {
+                            "type": "string",
+                            "description": "CSS selector to count"
+                        },
+                        "parent_selector": {
+                            "type": "string",
+                            "description": "Optional CSS selector of a container to count within"
+                        },
+                        "session_id": {
+                            "type": "string",
+                            "description": "Optional session ID (defaults to 'default')"
+                        }
+                    },
+                    "required": ["selector"]
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            ),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn discover_forms_tool() -> Tool {
+        Tool {
+            name: "discover_forms".into(),
+            description: Some("List every form on the page with its fields (name, type, label, required, options, current value) and submit controls, each with a selector that matches exactly one element. Use the selectors with fill_and_submit_form instead of guessing them. Inputs outside any <form> are reported as a standalone group; password values are redacted.".into()),
+            input_schema: Arc::new(json!({
+                "type": "object",
+                "properties": {
+                    "form_selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector of one <form> to describe (default: all forms)"
+                    },
+                    "include_hidden": {
+                        "type": "boolean",
+                        "description": "Include type=hidden inputs such as CSRF tokens (default: false)"
+                    },
+                    "session_id": {
+                        "type": "string",
+                        "description": "Optional session ID (defaults to 'default')"
+                    }
+                }
+            }).as_object().unwrap().clone()),
+            annotations: read_only_hints(),
         }
     }
 
     fn fill_and_submit_form_tool() -> Tool {
         Tool {
             name: "fill_and_submit_form".into(),
-            description: Some("Fill out a form with multiple fields and submit it".into()),
+            description: Some("Fill out a form field by field, in order, and submit it. Each field waits for its element and is filled according to its type: text is typed, <select> options chosen by value or label, checkboxes set, radios picked by value, file inputs given paths. Set verify=true to read every field back and skip the submit on a mismatch. Call discover_forms first to get field and submit selectors.".into()),
             input_schema: Arc::new(json!({
                 "type": "object",
                 "properties": {
@@ -680,16 +1507,47 @@ impl AutomationTools {
                         "description": "CSS selector of the form element (optional, for validation)"
                     },
                     "fields": {
-                        "type": "object",
-                        "description": "Object mapping CSS selectors to values to fill",
-                        "additionalProperties": {
-                            "type": "string"
+                        "type": "array",
+                        "description": "Field actions, applied in order. (An object mapping selectors to text is still accepted; it is filled in key order.)",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "selector": {
+                                    "type": "string",
+                                    "description": "CSS selector of the field; for radios, the group (e.g. input[name='plan']) with value naming the option"
+                                },
+                                "value": {
+                                    "description": "Text to type, option value/label (array for multi-selects), true/false for checkboxes, radio value, or file path(s) on the server's machine"
+                                },
+                                "action": {
+                                    "type": "string",
+                                    "enum": ["auto", "type", "select", "checkbox", "radio", "file", "clear"],
+                                    "description": "How to apply the value (default: auto, from the element's tag and type)"
+                                },
+                                "wait_timeout": {
+                                    "type": "number",
+                                    "description": "Seconds to wait for this field to appear (default: the call's wait_timeout)"
+                                }
+                            },
+                            "required": ["selector"]
                         }
                     },
                     "submit_selector": {
                         "type": "string",
                         "description": "CSS selector of the submit button or element"
                     },
+                    "verify": {
+                        "type": "boolean",
+                        "description": "Read each field back after filling and do not submit if any holds something else (default: false)"
+                    },
+                    "wait_timeout": {
+                        "type": "number",
+                        "description": "Seconds to wait for each field and the submit button to appear (default: the server's default wait timeout)"
+                    },
+                    "wait_for_stable": {
+                        "type": "boolean",
+                        "description": "Wait for each field and the submit button to be visible, enabled and no longer moving before acting (default: true unless the server turns it off)"
+                    },
                     "session_id": {
                         "type": "string",
                         "description": "Optional session ID (defaults to 'default')"
@@ -697,14 +1555,14 @@ impl AutomationTools {
                 },
                 "required": ["fields", "submit_selector"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: interaction_hints(),
         }
     }
 
     fn login_form_tool() -> Tool {
         Tool {
             name: "login_form".into(),
-            description: Some("Automatically fill and submit a login form with username/email and password".into()),
+            description: Some("Automatically fill and submit a login form with username/email and password, then watch the page to report whether the login succeeded (URL change, error banners, password field still present). Pass otp_code or totp_secret to also complete a two-factor code step. A detected failure returns CHECK_FAILED with the resulting URL; a CAPTCHA or bot-check page returns BLOCKED_BY_CHALLENGE".into()),
             input_schema: Arc::new(json!({
                 "type": "object",
                 "properties": {
@@ -728,6 +1586,42 @@ impl AutomationTools {
                         "type": "string",
                         "description": "Optional custom CSS selector for submit button (will auto-detect if not provided)"
                     },
+                    "verify": {
+                        "type": "boolean",
+                        "description": "Wait after submitting and report whether the login worked (default: true). Set false to return as soon as the button is clicked"
+                    },
+                    "verify_timeout": {
+                        "type": "number",
+                        "description": "Seconds to watch the page after submitting (default: 10)"
+                    },
+                    "success_selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector of an element that only appears after a successful login, e.g. a user menu"
+                    },
+                    "failure_selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector of an element that only appears when the login failed"
+                    },
+                    "otp_code": {
+                        "type": "string",
+                        "description": "Optional one-time code for a two-factor step after the password"
+                    },
+                    "totp_secret": {
+                        "type": "string",
+                        "description": "Optional base32 TOTP secret or otpauth:// URI; the current code is computed and entered in a two-factor step after the password"
+                    },
+                    "otp_selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector for the one-time code field (will auto-detect if not provided)"
+                    },
+                    "otp_submit_selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector for the one-time code submit button (tries submit buttons, then Enter, if not provided)"
+                    },
+                    "otp_timeout": {
+                        "type": "number",
+                        "description": "Seconds to wait for the one-time code field to appear (default: 15)"
+                    },
                     "session_id": {
                         "type": "string",
                         "description": "Optional session ID (defaults to 'default')"
@@ -735,7 +1629,69 @@ impl AutomationTools {
                 },
                 "required": ["username", "password"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn enter_otp_tool() -> Tool {
+        Tool {
+            name: "enter_otp".into(),
+            description: Some("Enter the one-time code of a two-factor login: wait for the code field, type a given code or one computed from a TOTP secret, submit, and report whether the login completed. A detected failure returns CHECK_FAILED".into()),
+            input_schema: Arc::new(json!({
+                "type": "object",
+                "properties": {
+                    "code": {
+                        "type": "string",
+                        "description": "The one-time code to enter (or pass totp_secret)"
+                    },
+                    "totp_secret": {
+                        "type": "string",
+                        "description": "Base32 TOTP secret or otpauth:// URI to compute the current code from"
+                    },
+                    "otp_selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector for the code field (will auto-detect if not provided)"
+                    },
+                    "otp_submit_selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector for the submit button (tries submit buttons, then Enter, if not provided)"
+                    },
+                    "otp_timeout": {
+                        "type": "number",
+                        "description": "Seconds to wait for the code field to appear (default: 15)"
+                    },
+                    "verify": {
+                        "type": "boolean",
+                        "description": "Wait after submitting and report whether the login completed (default: true)"
+                    },
+                    "verify_timeout": {
+                        "type": "number",
+                        "description": "Seconds to watch the page after submitting (default: 10)"
+                    },
+                    "success_selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector of an element that only appears once logged in"
+                    },
+                    "failure_selector": {
+                        "type": "string",
+                        "description": "Optional CSS selector of an element that only appears when the code was rejected"
+                    },
+                    "session_id": {
+                        "type": "string",
+                        "description": "Optional session ID (defaults to 'default')"
+                    }
+                }
+            }).as_object().unwrap().clone()),
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn login_via_token_tool() -> Tool {
+        Tool {
+            name: "login_via_token".into(),
+            description: Some("Log in without the login form by setting known auth artifacts (session cookies, JWTs in localStorage or sessionStorage) on a page of the app, then opening a probe page to check the app recognizes the session: being sent to a login page or shown a password field means it did not, a success_selector appearing means it did. Values are never echoed back. A rejected token returns CHECK_FAILED".into()),
+            input_schema: params::input_schema::<LoginViaTokenParams>(),
+            annotations: interaction_hints(),
         }
     }
 }
\ No newline at end of file
@@ -2,12 +2,15 @@ use std::sync::Arc;
 use rmcp::model::Tool;
 use serde_json::json;
 
+use super::{destructive_hints, idempotent_hints, read_only_hints};
+
 pub struct DriverManagementTools;
 
 impl DriverManagementTools {
     pub fn get_tools() -> Vec<Tool> {
         vec![
             Self::get_healthy_endpoints_tool(),
+            Self::get_endpoint_status_tool(),
             Self::refresh_driver_health_tool(),
             Self::list_managed_drivers_tool(),
             Self::start_driver_tool(),
@@ -26,7 +29,25 @@ impl DriverManagementTools {
                     "type": "object",
                     "properties": {}
                 }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn get_endpoint_status_tool() -> Tool {
+        Tool {
+            name: "get_endpoint_status".into(),
+            description: Some("Get every known WebDriver endpoint with its browser, health status (healthy, unhealthy or stopped), whether this server manages it, last check time, driver version and last error, as structured JSON".into()),
+            input_schema: Arc::new(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "refresh": {
+                            "type": "boolean",
+                            "description": "Check every endpoint before reporting (default: false)"
+                        }
+                    }
+                }).as_object().unwrap().clone()),
+            annotations: read_only_hints(),
         }
     }
 
@@ -39,7 +60,7 @@ impl DriverManagementTools {
                     "type": "object",
                     "properties": {}
                 }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: idempotent_hints(),
         }
     }
 
@@ -52,7 +73,7 @@ impl DriverManagementTools {
                     "type": "object",
                     "properties": {}
                 }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
@@ -71,14 +92,14 @@ impl DriverManagementTools {
                     },
                     "required": ["driver_type"]
                 }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: idempotent_hints(),
         }
     }
 
     fn stop_driver_tool() -> Tool {
         Tool {
             name: "stop_driver".into(),
-            description: Some("Stop a specific WebDriver process".into()),
+            description: Some("Stop a specific WebDriver process. Refused with SESSIONS_ACTIVE, listing the sessions, while the driver still serves open sessions unless force is set".into()),
             input_schema: Arc::new(
                 json!({
                     "type": "object",
@@ -86,24 +107,33 @@ impl DriverManagementTools {
                         "driver_type": {
                             "type": "string",
                             "description": "Type of driver to stop (chrome, firefox, edge)"
+                        },
+                        "force": {
+                            "type": "boolean",
+                            "description": "Close the driver's open sessions, then stop it (default: false)"
                         }
                     },
                     "required": ["driver_type"]
                 }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: destructive_hints(),
         }
     }
 
     fn stop_all_drivers_tool() -> Tool {
         Tool {
             name: "stop_all_drivers".into(),
-            description: Some("Stop all managed WebDriver processes".into()),
+            description: Some("Stop all managed WebDriver processes. Refused with SESSIONS_ACTIVE, listing the sessions, while any of them still serves open sessions unless force is set".into()),
             input_schema: Arc::new(
                 json!({
                     "type": "object",
-                    "properties": {}
+                    "properties": {
+                        "force": {
+                            "type": "boolean",
+                            "description": "Close the open sessions, then stop the drivers (default: false)"
+                        }
+                    }
                 }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: destructive_hints(),
         }
     }
 
@@ -116,7 +146,7 @@ impl DriverManagementTools {
                     "type": "object",
                     "properties": {}
                 }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: destructive_hints(),
         }
     }
 }
\ No newline at end of file
@@ -1,15 +1,103 @@
+mod admin;
 mod automation;
 mod driver_management;
 mod performance;
 mod recipes;
+pub mod params;
 
+pub use admin::*;
 pub use automation::*;
 pub use driver_management::*;
 pub use performance::*;
 pub use recipes::*;
 
+use std::sync::Arc;
+
 use once_cell::sync::Lazy;
-use rmcp::model::{Content, Tool};
+
+use crate::{config::Config, error::ErrorCode};
+use rmcp::model::{Content, Tool, ToolAnnotations};
+
+/// Argument every tool accepts to bound the whole call, in milliseconds
+/// (clamped to `Config::max_tool_timeout_ms`)
+pub const TIMEOUT_ARGUMENT: &str = "timeout_ms";
+
+/// Advertise [`TIMEOUT_ARGUMENT`] on `tool`, unless it already takes an argument of that name
+pub fn with_timeout_argument(mut tool: Tool) -> Tool {
+    let schema = Arc::make_mut(&mut tool.input_schema);
+    let properties = schema.entry("properties").or_insert_with(|| serde_json::json!({}));
+    if let serde_json::Value::Object(properties) = properties {
+        properties.entry(TIMEOUT_ARGUMENT).or_insert_with(|| {
+            serde_json::json!({
+                "type": "integer",
+                "minimum": 1,
+                "description": "Optional limit on the whole call in milliseconds; the call fails with TIMEOUT when it runs longer. Capped by the server's maximum."
+            })
+        });
+    }
+    tool
+}
+
+/// Groups of tools that operators can switch off as a whole
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolCategory {
+    /// Navigation, element interaction and page inspection
+    Automation,
+    /// Tools that evaluate caller-supplied JavaScript
+    Scripting,
+    Performance,
+    /// Recipe management, execution and published `recipe_<name>` tools
+    Recipes,
+    /// Driver lifecycle tools (stdio mode only)
+    DriverManagement,
+    /// Operator tools such as `get_audit_log`
+    Admin,
+}
+
+impl ToolCategory {
+    pub const ALL: [ToolCategory; 6] = [
+        Self::Automation,
+        Self::Scripting,
+        Self::Performance,
+        Self::Recipes,
+        Self::DriverManagement,
+        Self::Admin,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Automation => "automation",
+            Self::Scripting => "scripting",
+            Self::Performance => "performance",
+            Self::Recipes => "recipes",
+            Self::DriverManagement => "driver_management",
+            Self::Admin => "admin",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase().replace('-', "_");
+        Self::ALL.into_iter().find(|category| category.as_str() == name)
+    }
+
+    /// Category a tool belongs to; tools not listed explicitly count as automation
+    pub fn of_tool(tool_name: &str) -> Self {
+        match tool_name {
+            "execute_script" | "execute_async_script" | "wait_for_condition" => Self::Scripting,
+            "get_console_logs" | "get_performance_metrics" | "monitor_memory_usage"
+            | "run_performance_test" | "monitor_resource_usage" | "get_page_weight"
+            | "analyze_third_parties" => Self::Performance,
+            "create_recipe" | "list_recipes" | "get_recipe" | "execute_recipe" | "delete_recipe"
+            | "create_recipe_template" => Self::Recipes,
+            "get_healthy_endpoints" | "get_endpoint_status" | "refresh_driver_health" | "list_managed_drivers"
+            | "start_driver" | "stop_driver" | "stop_all_drivers"
+            | "force_cleanup_orphaned_processes" => Self::DriverManagement,
+            "get_audit_log" | "get_server_stats" => Self::Admin,
+            name if name.starts_with(RECIPE_TOOL_PREFIX) => Self::Recipes,
+            _ => Self::Automation,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ServerMode {
@@ -24,7 +112,8 @@ static STDIO_TOOLS: Lazy<Vec<Tool>> = Lazy::new(|| {
     tools.extend(PerformanceTools::get_tools());
     tools.extend(RecipeTools::get_tools());
     tools.extend(DriverManagementTools::get_tools());
-    tools
+    tools.extend(AdminTools::get_tools());
+    tools.into_iter().map(with_timeout_argument).collect()
 });
 
 /// Cached tool definitions for http mode (excludes driver management tools)
@@ -33,9 +122,32 @@ static HTTP_TOOLS: Lazy<Vec<Tool>> = Lazy::new(|| {
     tools.extend(AutomationTools::get_tools());
     tools.extend(PerformanceTools::get_tools());
     tools.extend(RecipeTools::get_tools());
-    tools
+    tools.extend(AdminTools::get_tools());
+    tools.into_iter().map(with_timeout_argument).collect()
 });
 
+/// Hints for tools that only observe page, driver or recipe state
+fn read_only_hints() -> Option<ToolAnnotations> {
+    Some(ToolAnnotations::new().read_only(true).destructive(false).idempotent(true))
+}
+
+/// Hints for tools that change state but leave it the same when repeated
+/// (navigate, resize_window, start_driver, ...)
+fn idempotent_hints() -> Option<ToolAnnotations> {
+    Some(ToolAnnotations::new().read_only(false).destructive(false).idempotent(true))
+}
+
+/// Hints for tools whose effect depends on page state and may differ per call
+/// (click, send_keys, execute_script, ...)
+fn interaction_hints() -> Option<ToolAnnotations> {
+    Some(ToolAnnotations::new().read_only(false).destructive(false).idempotent(false))
+}
+
+/// Hints for tools that tear down drivers, sessions or saved data
+fn destructive_hints() -> Option<ToolAnnotations> {
+    Some(ToolAnnotations::new().read_only(false).destructive(true).idempotent(true))
+}
+
 pub struct ToolDefinitions;
 
 impl ToolDefinitions {
@@ -52,6 +164,28 @@ impl ToolDefinitions {
             ServerMode::Http => HTTP_TOOLS.clone(),
         }
     }
+
+    /// Whether `tool_name` is one of the built-in tools of any mode
+    pub fn is_defined(tool_name: &str) -> bool {
+        STDIO_TOOLS.iter().any(|tool| tool.name == tool_name)
+    }
+
+    /// Whether a tool is annotated as read-only; unknown tools are not
+    pub fn is_read_only(tool_name: &str) -> bool {
+        STDIO_TOOLS
+            .iter()
+            .find(|tool| tool.name == tool_name)
+            .and_then(|tool| tool.annotations.as_ref())
+            .and_then(|annotations| annotations.read_only_hint)
+            .unwrap_or(false)
+    }
+
+    /// Returns the tools for the given mode that the configuration leaves enabled
+    pub fn list_for_config(mode: ServerMode, config: &Config) -> Vec<Tool> {
+        let mut tools = Self::list_for_mode(mode);
+        tools.retain(|tool| config.is_tool_enabled(&tool.name));
+        tools
+    }
 }
 
 pub fn success_response(message: String) -> rmcp::model::CallToolResult {
@@ -61,9 +195,65 @@ pub fn success_response(message: String) -> rmcp::model::CallToolResult {
     }
 }
 
+/// Success response carrying the human-readable message plus a JSON content block
+/// with the same data, so clients don't have to parse the prose
+pub fn structured_response(message: String, data: serde_json::Value) -> rmcp::model::CallToolResult {
+    let mut result = success_response(message);
+    if let Ok(json) = Content::json(data) {
+        result.content.push(json);
+    }
+    result
+}
+
+/// Error response whose code is inferred from the message; prefer
+/// [`coded_error_response`] when the handler knows the cause
 pub fn error_response(message: String) -> rmcp::model::CallToolResult {
+    let code = ErrorCode::classify(&message);
+    coded_error_response(code, message, serde_json::json!({}))
+}
+
+/// Error response whose code is inferred from the message, with context fields
+/// such as the selector or session involved
+pub fn error_response_with(message: String, context: serde_json::Value) -> rmcp::model::CallToolResult {
+    let code = ErrorCode::classify(&message);
+    coded_error_response(code, message, context)
+}
+
+/// Error response carrying the message plus a JSON content block with a
+/// machine-readable code, whether retrying may help, and context such as the
+/// selector or session involved
+pub fn coded_error_response(
+    code: ErrorCode,
+    message: String,
+    context: serde_json::Value,
+) -> rmcp::model::CallToolResult {
+    let data = serde_json::json!({
+        "error": {
+            "code": code.as_str(),
+            "retryable": code.is_retryable(),
+            "message": message,
+            "context": context,
+        }
+    });
+    let mut content = vec![Content::text(message)];
+    if let Ok(json) = Content::json(data) {
+        content.push(json);
+    }
     rmcp::model::CallToolResult {
-        content: vec![Content::text(message)],
+        content,
         is_error: Some(true),
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_tool_accepts_timeout() {
+        for tool in ToolDefinitions::list_all() {
+            let property = &tool.input_schema["properties"][TIMEOUT_ARGUMENT];
+            assert_eq!(property["type"], "integer", "{} does not advertise {}", tool.name, TIMEOUT_ARGUMENT);
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! Typed tool arguments
+//!
+//! A tool's arguments are a struct deriving both `Deserialize` and
+//! `JsonSchema`: handlers parse them with `handlers::parse_params`, and the tool
+//! definition advertises [`input_schema`] of the same struct, so what a tool
+//! accepts and what it claims to accept come from one place. Field doc comments
+//! become the property descriptions.
+
+use std::sync::Arc;
+
+use rmcp::model::JsonObject;
+use schemars::{JsonSchema, generate::SchemaSettings};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{ConsentChoice, login::TokenCookie};
+
+/// Input schema of a tool taking `T` as its arguments
+pub fn input_schema<T: JsonSchema>() -> Arc<JsonObject> {
+    let generator = SchemaSettings::draft07()
+        .with(|settings| {
+            settings.meta_schema = None;
+            settings.inline_subschemas = true;
+        })
+        .into_generator();
+    let mut schema = match generator.into_root_schema_for::<T>().to_value() {
+        Value::Object(schema) => schema,
+        _ => JsonObject::new(),
+    };
+    // Struct docs are written for Rust readers; the tool has its own description
+    schema.remove("title");
+    schema.remove("description");
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        for property in properties.values_mut() {
+            drop_null_type(property);
+        }
+    }
+    Arc::new(schema)
+}
+
+/// Optional fields come out as `"type": ["string", "null"]`; an omitted
+/// argument already means "not set", so advertise the plain type
+fn drop_null_type(property: &mut Value) {
+    let Some(Value::Array(types)) = property.get_mut("type") else {
+        return;
+    };
+    types.retain(|kind| kind != "null");
+    if types.len() == 1 {
+        let kind = types.remove(0);
+        property["type"] = kind;
+    }
+}
+
+/// The session a tool acts on
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct SessionParams {
+    /// Optional session ID (defaults to 'default'). Pick a new session's browser with `browser` on create_session or navigate; 'firefox_*' and 'chrome_*' prefixes still do so but are deprecated.
+    pub session_id: Option<String>,
+}
+
+/// Arguments of `navigate`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct NavigateParams {
+    /// URL to navigate to
+    pub url: String,
+    /// Seconds to wait for a bot-check interstitial (e.g. Cloudflare's "Just a moment...") to pass on its own before reporting it (default: 0)
+    #[serde(default)]
+    pub challenge_wait: f64,
+    /// Browser to open the session in when it does not exist yet: chrome, firefox or edge
+    pub browser: Option<String>,
+    /// Load the page as a first visit: clear the HTTP cache, the origin's service workers and Cache Storage, and
+    /// bypass the HTTP cache for this load. Cookies stay. Chrome and Edge only (default: false)
+    #[serde(default)]
+    pub cold_load: bool,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `login_via_token`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct LoginViaTokenParams {
+    /// Page of the app to open first; cookies and storage entries belong to its origin (default: the page the session is on)
+    pub url: Option<String>,
+    /// Cookies to set, e.g. a session ID or a JWT
+    #[serde(default)]
+    pub cookies: Vec<TokenCookie>,
+    /// localStorage entries to set; values that are not strings are stored as JSON
+    #[serde(default)]
+    pub local_storage: serde_json::Map<String, Value>,
+    /// sessionStorage entries to set; values that are not strings are stored as JSON
+    #[serde(default)]
+    pub session_storage: serde_json::Map<String, Value>,
+    /// Page opened afterwards to check that the app recognizes the session, e.g. an account page (default: the page the token was set on)
+    pub probe_url: Option<String>,
+    /// CSS selector of an element that only appears when logged in, e.g. a user menu
+    pub success_selector: Option<String>,
+    /// CSS selector of an element that only appears when the app did not take the token
+    pub failure_selector: Option<String>,
+    /// Open the probe page and report whether the app recognizes the session (default: true)
+    pub verify: Option<bool>,
+    /// Seconds to watch the probe page (default: 10)
+    pub verify_timeout: Option<f64>,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `get_text`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct GetTextParams {
+    /// CSS selector of element
+    pub selector: String,
+    /// Optional CSS selector of a container; only elements inside its first match are read
+    pub parent_selector: Option<String>,
+    /// Return the text of every match as a JSON array instead of only the first match's (default: false)
+    #[serde(default)]
+    pub all: bool,
+    /// Collapse runs of whitespace, newlines included, into single spaces and trim the ends (default: false)
+    #[serde(default)]
+    pub normalize_whitespace: bool,
+    /// Cut each text to at most this many characters
+    pub max_length: Option<usize>,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `set_window_position`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct SetWindowPositionParams {
+    /// Distance of the window's left edge from the left of the screen, in pixels
+    pub x: u32,
+    /// Distance of the window's top edge from the top of the screen, in pixels
+    pub y: u32,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `dismiss_overlays`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct DismissOverlaysParams {
+    /// How to answer consent dialogs: "accept" (default) or "reject". Dialogs without a reject button are removed
+    #[serde(default)]
+    pub choice: ConsentChoice,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `wait_for_dom_stable`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct WaitForDomStableParams {
+    /// Milliseconds without any DOM mutation after which the page counts as settled (default: 500)
+    #[serde(default = "default_quiet_ms")]
+    pub quiet_ms: u64,
+    /// Maximum time to wait in seconds (default: the server's default wait timeout, 10 unless configured)
+    pub timeout_seconds: Option<f64>,
+    /// Optional CSS selector; only mutations inside its first match count (default: the whole document)
+    pub selector: Option<String>,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+fn default_quiet_ms() -> u64 {
+    500
+}
+
+/// Arguments of `wait_for_attribute`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct WaitForAttributeParams {
+    /// CSS selector of the element; its first match is checked
+    pub selector: String,
+    /// HTML attribute to watch, e.g. aria-busy, data-state or disabled
+    pub attribute: String,
+    /// Value the attribute should have, e.g. "false" or "ready"
+    pub value: Option<String>,
+    /// Regular expression (JavaScript syntax) the attribute's value should match, instead of value
+    pub pattern: Option<String>,
+    /// Wait for the attribute to be removed instead (default: false). Without value, pattern or absent, the wait ends
+    /// once the attribute is present
+    #[serde(default)]
+    pub absent: bool,
+    /// Maximum time to wait in seconds (default: the server's default wait timeout, 10 unless configured)
+    pub timeout_seconds: Option<f64>,
+    /// How often to check in milliseconds (default: 100)
+    pub check_interval_ms: Option<u64>,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `wait_for_property`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct WaitForPropertyParams {
+    /// CSS selector of the element; its first match is checked
+    pub selector: String,
+    /// DOM property to watch, dotted for nested ones, e.g. value, checked, naturalWidth or dataset.state
+    pub property: String,
+    /// Value the property should have, compared as JSON, e.g. true, 0 or "done". Without value or pattern, the wait
+    /// ends once the property is truthy
+    pub value: Option<Value>,
+    /// Regular expression (JavaScript syntax) the property, as a string, should match, instead of value
+    pub pattern: Option<String>,
+    /// Maximum time to wait in seconds (default: the server's default wait timeout, 10 unless configured)
+    pub timeout_seconds: Option<f64>,
+    /// How often to check in milliseconds (default: 100)
+    pub check_interval_ms: Option<u64>,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `get_page_text`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct GetPageTextParams {
+    /// Optional CSS selector; only the text of its first match is returned (default: the whole body)
+    pub selector: Option<String>,
+    /// Cut the text to at most this many characters (default: 20000)
+    pub max_length: Option<usize>,
+    /// Collapse runs of whitespace, newlines included, into single spaces and trim the ends (default: false)
+    #[serde(default)]
+    pub normalize_whitespace: bool,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `summarize_page`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct SummarizePageParams {
+    /// Characters of the main content's text to include (default: 500)
+    #[serde(default = "default_excerpt_length")]
+    pub excerpt_length: usize,
+    /// H1-H3 headings to list in the outline; the total is always reported (default: 30)
+    #[serde(default = "default_max_headings")]
+    pub max_headings: usize,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+fn default_excerpt_length() -> usize {
+    500
+}
+
+fn default_max_headings() -> usize {
+    30
+}
+
+/// Arguments of `get_popup_events`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct GetPopupEventsParams {
+    /// Forget the returned events, so the next call only reports new ones (default: false)
+    #[serde(default)]
+    pub clear: bool,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `get_page_events`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct GetPageEventsParams {
+    /// Only report events of these types: navigation_committed, dom_content_loaded, load, dialog_opened,
+    /// download_started, frame_attached (default: all)
+    #[serde(default)]
+    pub types: Vec<String>,
+    /// Forget the buffered events, so the next call only reports new ones (default: false)
+    #[serde(default)]
+    pub clear: bool,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `unregister_service_workers`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct UnregisterServiceWorkersParams {
+    /// Only unregister workers whose scope starts with this URL (default: every worker of the page's origin)
+    pub scope: Option<String>,
+    /// Also delete the origin's Cache Storage caches, which workers usually serve from (default: false)
+    #[serde(default)]
+    pub clear_caches: bool,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `set_cache_disabled`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct SetCacheDisabledParams {
+    /// true to bypass the HTTP cache for every request of the session, false to use it again
+    pub disabled: bool,
+    /// Also send requests to the network instead of through service workers
+    pub bypass_service_worker: Option<bool>,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `screenshot_on_change`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ScreenshotOnChangeParams {
+    /// CSS selector of the element to watch; it may also not exist yet, or be about to be removed
+    pub selector: String,
+    /// Seconds to wait for a change before capturing anyway (default: 10)
+    pub wait_timeout: Option<f64>,
+    /// Optional file name to save the screenshot under, relative to this server run's artifacts directory
+    pub save_path: Option<String>,
+    /// Embed the base64 PNG in the tool result in addition to the resource URI (default: false)
+    #[serde(default)]
+    pub inline: bool,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `get_page_weight`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct GetPageWeightParams {
+    /// How many of the heaviest resources to list (default: 10)
+    pub top: Option<usize>,
+    /// Reload the current page as a first visit before measuring, with caches and service workers cleared
+    /// (Chrome and Edge only, default: false)
+    #[serde(default)]
+    pub cold_load: bool,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `get_session_health`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct GetSessionHealthParams {
+    /// Report every open session of the caller instead of one (default: false)
+    #[serde(default)]
+    pub all_sessions: bool,
+    /// Milliseconds to sample CPU use over (default: 1000, at most 10000)
+    pub sample_ms: Option<u64>,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `whoami_network`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct WhoamiNetworkParams {
+    /// Echo endpoint to load instead of the server's network_echo_url; it should answer JSON with the caller's IP and
+    /// ideally its request headers (httpbin /anything) or location (ip-api /json)
+    pub echo_url: Option<String>,
+    /// STUN server for the WebRTC check (default: stun:stun.l.google.com:19302); an empty string gathers local
+    /// candidates only
+    pub stun_server: Option<String>,
+    /// Milliseconds to gather WebRTC candidates (default: 3000, at most 15000)
+    pub webrtc_wait_ms: Option<u64>,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `analyze_third_parties`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct AnalyzeThirdPartiesParams {
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// One tool call of a `run_batch`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct BatchStep {
+    /// Name of the tool to call, e.g. "click"
+    pub tool: String,
+    /// The tool's arguments; session_id defaults to the batch's
+    #[serde(default)]
+    pub arguments: serde_json::Map<String, Value>,
+}
+
+/// Arguments of `run_batch`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct RunBatchParams {
+    /// Tool calls to run in order
+    pub steps: Vec<BatchStep>,
+    /// Skip the remaining steps after the first one that fails (default: true)
+    pub stop_on_error: Option<bool>,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+/// Arguments of `check_localization`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct CheckLocalizationParams {
+    /// Page to load under each locale
+    pub url: String,
+    /// Locales to load the page under, e.g. ["en-US", "de-DE", "fr-FR"]
+    pub locales: Vec<String>,
+    /// Locale the others are compared against (default: the first of locales)
+    pub base_locale: Option<String>,
+    /// Only compare strings inside the first element matching this CSS selector (default: the whole page)
+    pub selector: Option<String>,
+    /// Strings that are the same in every language, such as brand or product names, never reported as untranslated
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Wait this many milliseconds after each load for late-rendered strings (default: 500, at most 10000)
+    pub settle_ms: Option<u64>,
+    /// List at most this many strings per finding and locale (default: 50)
+    pub max_strings: Option<usize>,
+    /// Fail with CHECK_FAILED when any locale has findings (default: false)
+    #[serde(default)]
+    pub fail_on_issues: bool,
+    #[serde(flatten)]
+    pub session: SessionParams,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_matches_params() {
+        let schema = input_schema::<NavigateParams>();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["url"]));
+        assert_eq!(schema["properties"]["url"]["type"], "string");
+        assert_eq!(schema["properties"]["session_id"]["type"], "string");
+        assert_eq!(schema["properties"]["challenge_wait"]["type"], "number");
+        assert_eq!(schema["properties"]["url"]["description"], "URL to navigate to");
+        assert!(["title", "description", "$schema"].iter().all(|key| schema.get(*key).is_none()));
+
+        let session = input_schema::<SessionParams>();
+        assert!(session.get("required").is_none_or(|required| required == &json!([])));
+
+        let parsed: NavigateParams =
+            serde_json::from_value(json!({ "url": "https://example.com", "session_id": "firefox_1" })).unwrap();
+        assert_eq!(parsed.challenge_wait, 0.0);
+        assert_eq!(parsed.session.session_id.as_deref(), Some("firefox_1"));
+
+        let get_text = input_schema::<GetTextParams>();
+        assert_eq!(get_text["required"], json!(["selector"]));
+        assert_eq!(get_text["properties"]["all"]["type"], "boolean");
+        assert_eq!(get_text["properties"]["max_length"]["type"], "integer");
+    }
+}
@@ -2,6 +2,11 @@ use std::sync::Arc;
 use rmcp::model::Tool;
 use serde_json::json;
 
+use super::{
+    interaction_hints, read_only_hints,
+    params::{self, AnalyzeThirdPartiesParams, GetPageWeightParams, GetSessionHealthParams},
+};
+
 pub struct PerformanceTools;
 
 impl PerformanceTools {
@@ -12,28 +17,64 @@ impl PerformanceTools {
             Self::monitor_memory_usage_tool(),
             Self::run_performance_test_tool(),
             Self::monitor_resource_usage_tool(),
+            Self::get_page_weight_tool(),
+            Self::analyze_third_parties_tool(),
+            Self::get_session_health_tool(),
         ]
     }
 
     fn get_console_logs_tool() -> Tool {
         Tool {
             name: "get_console_logs".into(),
-            description: Some("Capture browser console logs, errors, and warnings for debugging".into()),
+            description: Some("Capture browser console logs, errors, and warnings for debugging, with the source file and line when known. Logs are buffered on the server and survive navigation; repeated messages are collapsed with a count, and results are paged with offset/limit. Returns immediately unless asked to wait for entries or a quiet period.".into()),
             input_schema: Arc::new(json!({
                 "type": "object", 
                 "properties": {
                     "level": {
                         "type": "string",
                         "enum": ["all", "error", "warn", "info", "debug"],
-                        "description": "Filter logs by level (default: 'all')"
+                        "description": "Filter logs by level (default: 'all'). Levels are normalized across browsers; console.log counts as 'info'"
                     },
                     "since_timestamp": {
                         "type": "number",
                         "description": "Optional: Only return logs since this timestamp (milliseconds)"
                     },
+                    "since_watermark": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Optional: Only return entries buffered after this cursor; pass the high_watermark of the previous call to fetch only new entries"
+                    },
+                    "clear": {
+                        "type": "boolean",
+                        "description": "Remove every buffered entry up to the returned high_watermark after reading, whatever the filters (default: false)"
+                    },
+                    "min_entries": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Optional: Wait until at least this many entries match the filters"
+                    },
+                    "quiet_period_ms": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Optional: Wait until the page has logged nothing new for this many milliseconds"
+                    },
                     "wait_timeout": {
                         "type": "number",
-                        "description": "Wait up to this many seconds before capturing logs to allow JavaScript execution (default: 2.0 seconds)"
+                        "description": "Longest time in seconds to wait for min_entries or quiet_period_ms (default: the server's console log wait, 2.0 seconds unless configured). Without either, logs are returned immediately."
+                    },
+                    "dedup": {
+                        "type": "boolean",
+                        "description": "Collapse repeats of the same message from the same source line into one entry with a count (default: true)"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Maximum entries to return (default: 100)"
+                    },
+                    "offset": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Entries to skip, oldest first; use next_offset from the previous call to page through (default: 0)"
                     },
                     "session_id": {
                         "type": "string",
@@ -41,7 +82,7 @@ impl PerformanceTools {
                     }
                 }
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
@@ -64,13 +105,17 @@ impl PerformanceTools {
                         "type": "boolean",
                         "description": "Include paint timing data (default: true)"
                     },
+                    "cold_load": {
+                        "type": "boolean",
+                        "description": "Reload the current page as a first visit before measuring: clear the HTTP cache, the origin's service workers and Cache Storage, and bypass the HTTP cache for the load. Chrome and Edge only (default: false)"
+                    },
                     "session_id": {
                         "type": "string",
                         "description": "Optional session ID (defaults to 'default')"
                     }
                 }
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
@@ -99,7 +144,7 @@ impl PerformanceTools {
                     }
                 }
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
@@ -145,6 +190,10 @@ impl PerformanceTools {
                         "type": "boolean",
                         "description": "Take screenshots during test (default: false)"
                     },
+                    "cold_load": {
+                        "type": "boolean",
+                        "description": "Make every navigate action a first visit, with the HTTP cache, service workers and Cache Storage cleared first, so each iteration measures a cold load. Chrome and Edge only (default: false)"
+                    },
                     "session_id": {
                         "type": "string",
                         "description": "Optional session ID (defaults to 'default')"
@@ -152,14 +201,14 @@ impl PerformanceTools {
                 },
                 "required": ["test_actions"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: interaction_hints(),
         }
     }
 
     fn monitor_resource_usage_tool() -> Tool {
         Tool {
             name: "monitor_resource_usage".into(),
-            description: Some("Monitor network requests, CPU usage, and rendering performance metrics".into()),
+            description: Some("Monitor network requests, CPU usage, and frame timing over a time window: frame rate, dropped frames and janky frames, optionally while an interaction script runs. On Chrome/Edge, main-thread busy time comes from the DevTools Protocol and slow frames are attributed with Long Animation Frames; other browsers fall back to requestAnimationFrame sampling".into()),
             input_schema: Arc::new(json!({
                 "type": "object",
                 "properties": {
@@ -177,7 +226,11 @@ impl PerformanceTools {
                     },
                     "include_fps": {
                         "type": "boolean",
-                        "description": "Monitor frame rate performance (default: true)"
+                        "description": "Monitor frame rate, dropped frames and jank (default: true)"
+                    },
+                    "interaction_script": {
+                        "type": "string",
+                        "description": "JavaScript to run when monitoring starts, e.g. a scroll or an animation, so frame timing covers it. Requires execute_script to be enabled"
                     },
                     "network_filter": {
                         "type": "string",
@@ -189,7 +242,34 @@ impl PerformanceTools {
                     }
                 }
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: interaction_hints(),
+        }
+    }
+
+    fn get_page_weight_tool() -> Tool {
+        Tool {
+            name: "get_page_weight".into(),
+            description: Some("Summarize what the current page loaded: total transfer size and request count, broken down by type (document, js, css, img, font, xhr, media), with the heaviest resources listed. Read from resource timing, so cross-origin resources without Timing-Allow-Origin count as requests of unknown size".into()),
+            input_schema: params::input_schema::<GetPageWeightParams>(),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn analyze_third_parties_tool() -> Tool {
+        Tool {
+            name: "analyze_third_parties".into(),
+            description: Some("Group the current page's requests to other sites by registrable domain, with request count, bytes and resource types per domain; flag known trackers (analytics, advertising, session replay) and report render-blocking time and main-thread blocking time of third-party scripts. Blocking time needs the Long Animation Frames API (Chrome/Edge 123+)".into()),
+            input_schema: params::input_schema::<AnalyzeThirdPartiesParams>(),
+            annotations: read_only_hints(),
+        }
+    }
+
+    fn get_session_health_tool() -> Tool {
+        Tool {
+            name: "get_session_health".into(),
+            description: Some("Check whether a long-lived session should be recycled: samples how busy the idle page keeps its main thread, and reports JS heap, DOM nodes, open windows and, on Chrome and Edge, CPU and memory of the browser processes. Sessions over a threshold get warnings and recycle_recommended; close and recreate those before they slow down everything else. all_sessions checks every open session at once".into()),
+            input_schema: params::input_schema::<GetSessionHealthParams>(),
+            annotations: read_only_hints(),
         }
     }
-}
\ No newline at end of file
+}
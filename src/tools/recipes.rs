@@ -1,6 +1,14 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 use rmcp::model::Tool;
-use serde_json::json;
+use serde_json::{json, Value};
+
+use super::{
+    ToolDefinitions, destructive_hints, idempotent_hints, interaction_hints, read_only_hints, with_timeout_argument,
+};
+use crate::recipes::Recipe;
+
+/// Prefix of the tools generated from saved recipes
+pub const RECIPE_TOOL_PREFIX: &str = "recipe_";
 
 pub struct RecipeTools;
 
@@ -30,7 +38,7 @@ impl RecipeTools {
                 },
                 "required": ["recipe_json"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: idempotent_hints(),
         }
     }
 
@@ -42,14 +50,14 @@ impl RecipeTools {
                 "type": "object",
                 "properties": {}
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
     fn get_recipe_tool() -> Tool {
         Tool {
             name: "get_recipe".into(),
-            description: Some("Get the JSON definition of a specific recipe".into()),
+            description: Some("Get the JSON definition of a specific recipe, along with a description and JSON Schema of the parameters it accepts".into()),
             input_schema: Arc::new(json!({
                 "type": "object",
                 "properties": {
@@ -60,7 +68,7 @@ impl RecipeTools {
                 },
                 "required": ["name"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: read_only_hints(),
         }
     }
 
@@ -77,9 +85,16 @@ impl RecipeTools {
                     },
                     "parameters": {
                         "type": "object",
-                        "description": "Optional parameters to substitute in the recipe",
+                        "description": "Parameters to substitute in the recipe. Declared defaults are applied and values are checked against the recipe's parameter schema (see get_recipe)",
+                        "additionalProperties": {
+                            "type": ["string", "number", "boolean"]
+                        }
+                    },
+                    "variables": {
+                        "type": "object",
+                        "description": "Run-time variables for this execution only, referenced in steps as {{name}} (e.g. a base URL or tenant ID). Cannot be browser, step, timestamp or a declared parameter; a step's store_as replaces an injected value from that step on",
                         "additionalProperties": {
-                            "type": "string"
+                            "type": ["string", "number", "boolean"]
                         }
                     },
                     "session_id": {
@@ -89,11 +104,16 @@ impl RecipeTools {
                     "continue_on_error": {
                         "type": "boolean",
                         "description": "Whether to continue execution when individual steps fail (default: false)"
+                    },
+                    "report": {
+                        "type": "string",
+                        "enum": ["html", "markdown", "none"],
+                        "description": "Write a human-readable report of the run (steps, timings, screenshots, console errors, environment) to the artifacts directory; the result names its path. Defaults to the server's recipe_report setting"
                     }
                 },
                 "required": ["name"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: interaction_hints(),
         }
     }
 
@@ -111,7 +131,7 @@ impl RecipeTools {
                 },
                 "required": ["name"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: destructive_hints(),
         }
     }
 
@@ -165,7 +185,103 @@ impl RecipeTools {
                 },
                 "required": ["template"]
             }).as_object().unwrap().clone()),
-            annotations: None,
+            annotations: idempotent_hints(),
+        }
+    }
+
+    /// Name under which a saved recipe is published as a tool
+    pub fn recipe_tool_name(recipe_name: &str) -> String {
+        let mut name: String = format!("{}{}", RECIPE_TOOL_PREFIX, recipe_name)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c.to_ascii_lowercase() } else { '_' })
+            .collect();
+        name.truncate(64);
+        name
+    }
+
+    /// Tool names of saved recipes, in recipe name order. A name a built-in tool or an
+    /// earlier recipe already has, e.g. "Log in" and "log_in" both becoming
+    /// `recipe_log_in`, gets a `_2`, `_3`, ... suffix and a warning instead of
+    /// shadowing the other.
+    pub fn published_names(recipes: &[Recipe]) -> Vec<(String, &Recipe)> {
+        let mut sorted: Vec<&Recipe> = recipes.iter().collect();
+        sorted.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut taken = HashSet::new();
+        let mut published = Vec::with_capacity(sorted.len());
+        for recipe in sorted {
+            let base = Self::recipe_tool_name(&recipe.name);
+            let mut name = base.clone();
+            let mut suffix = 1;
+            while ToolDefinitions::is_defined(&name) || taken.contains(&name) {
+                suffix += 1;
+                let end = format!("_{suffix}");
+                name = format!("{}{end}", &base[..base.len().min(64 - end.len())]);
+            }
+            if name != base {
+                tracing::warn!("Recipe '{}' is published as tool '{}'; '{}' is taken", recipe.name, name, base);
+            }
+            taken.insert(name.clone());
+            published.push((name, recipe));
+        }
+        published
+    }
+
+    /// Tool definition for a saved recipe published as `name`, using its declared
+    /// parameters as the input schema
+    pub fn recipe_tool(name: &str, recipe: &Recipe) -> Tool {
+        let mut schema = recipe.parameters_schema();
+        if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+            properties.entry("session_id").or_insert_with(|| json!({
+                "type": "string",
+                "description": "Optional session ID for browser operations"
+            }));
+            properties.entry("continue_on_error").or_insert_with(|| json!({
+                "type": "boolean",
+                "description": "Whether to continue execution when individual steps fail (default: false)"
+            }));
+            properties.entry("report").or_insert_with(|| json!({
+                "type": "string",
+                "enum": ["html", "markdown", "none"],
+                "description": "Write a human-readable report of the run to the artifacts directory"
+            }));
+            properties.entry("variables").or_insert_with(|| json!({
+                "type": "object",
+                "description": "Run-time variables for this execution only, referenced in steps as {{name}}",
+                "additionalProperties": { "type": ["string", "number", "boolean"] }
+            }));
+        }
+
+        with_timeout_argument(Tool {
+            name: name.to_string().into(),
+            description: Some(recipe.tool_description().into()),
+            input_schema: Arc::new(schema.as_object().cloned().unwrap_or_default()),
+            annotations: interaction_hints(),
+        })
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colliding_recipe_tool_names_get_suffixes() {
+        let recipe = |name: &str| Recipe::builder(name).navigate("https://example.com").build().unwrap();
+        let recipes = vec![recipe("log_in"), recipe("Log In"), recipe("checkout"), recipe("LOG IN")];
+
+        let names: Vec<(String, String)> = RecipeTools::published_names(&recipes)
+            .into_iter()
+            .map(|(tool, recipe)| (tool, recipe.name.clone()))
+            .collect();
+        let expected = [
+            ("recipe_log_in", "LOG IN"),
+            ("recipe_log_in_2", "Log In"),
+            ("recipe_checkout", "checkout"),
+            ("recipe_log_in_3", "log_in"),
+        ];
+        for (tool, recipe) in expected {
+            assert!(names.contains(&(tool.to_string(), recipe.to_string())), "{tool} is not {recipe}");
         }
+        assert_eq!(names.len(), 4);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,109 @@
+//! Typing text one character at a time
+//!
+//! `send_keys` normally hands the whole string to the driver, which injects it
+//! in one go. Autocomplete widgets and bot checks that watch key timing can miss
+//! or reject that, so text can instead be typed character by character with a
+//! pause between keys that varies by a random jitter.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use fantoccini::{elements::Element, error::CmdError};
+use serde_json::{Map, Value};
+
+/// Longest pause allowed between two keys
+pub const MAX_TYPING_DELAY_MS: u64 = 2000;
+
+/// How text is typed into an element
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TypingOptions {
+    /// Average pause between keys; 0 sends the text at once
+    pub delay_ms: u64,
+    /// Each pause is `delay_ms` plus or minus up to this much
+    pub jitter_ms: u64,
+}
+
+impl TypingOptions {
+    /// Read `typing_delay_ms` and `typing_jitter_ms`. The jitter defaults to
+    /// half the delay, so pauses are never perfectly regular.
+    pub fn from_arguments(arguments: &Map<String, Value>) -> Self {
+        let delay_ms = arguments
+            .get("typing_delay_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+            .min(MAX_TYPING_DELAY_MS);
+        let jitter_ms = arguments
+            .get("typing_jitter_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(delay_ms / 2)
+            .min(delay_ms);
+        Self { delay_ms, jitter_ms }
+    }
+
+    /// Whether the text goes to the driver in a single command
+    pub fn is_instant(&self) -> bool {
+        self.delay_ms == 0
+    }
+
+    /// Longest time the pauses between the keys of `text` can add up to
+    pub fn max_pauses(&self, text: &str) -> Duration {
+        let pauses = text.chars().count().saturating_sub(1) as u64;
+        Duration::from_millis(pauses.saturating_mul(self.delay_ms + self.jitter_ms))
+    }
+
+    /// A pause between `delay_ms - jitter_ms` and `delay_ms + jitter_ms`
+    fn pause(&self) -> Duration {
+        if self.jitter_ms == 0 {
+            return Duration::from_millis(self.delay_ms);
+        }
+        let random = RandomState::new().build_hasher().finish();
+        let offset = random % (2 * self.jitter_ms + 1);
+        Duration::from_millis(self.delay_ms - self.jitter_ms + offset)
+    }
+}
+
+/// Type `text` into `element`, key by key unless `options` is instant
+pub async fn type_into(element: &Element, text: &str, options: TypingOptions) -> Result<(), CmdError> {
+    if options.is_instant() {
+        return element.send_keys(text).await;
+    }
+    let mut buffer = [0u8; 4];
+    for (i, character) in text.chars().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(options.pause()).await;
+        }
+        element.send_keys(character.encode_utf8(&mut buffer)).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_typing_options_and_pauses() {
+        let options = |value: Value| TypingOptions::from_arguments(value.as_object().unwrap());
+
+        assert!(options(json!({})).is_instant());
+        assert_eq!(options(json!({ "typing_delay_ms": 100 })), TypingOptions { delay_ms: 100, jitter_ms: 50 });
+        assert_eq!(
+            options(json!({ "typing_delay_ms": 99999, "typing_jitter_ms": 99999 })),
+            TypingOptions { delay_ms: MAX_TYPING_DELAY_MS, jitter_ms: MAX_TYPING_DELAY_MS }
+        );
+
+        let steady = options(json!({ "typing_delay_ms": 80, "typing_jitter_ms": 0 }));
+        assert_eq!(steady.pause(), Duration::from_millis(80));
+        let jittered = options(json!({ "typing_delay_ms": 100, "typing_jitter_ms": 30 }));
+        for _ in 0..50 {
+            let pause = jittered.pause().as_millis();
+            assert!((70..=130).contains(&pause), "{pause}");
+        }
+        assert_eq!(jittered.max_pauses("abcd"), Duration::from_millis(3 * 130));
+        assert_eq!(options(json!({})).max_pauses("abcd"), Duration::ZERO);
+    }
+}
@@ -0,0 +1,42 @@
+//! Waits in seconds from tool arguments
+//!
+//! `Duration::from_secs_f64` panics on negative, non-finite and overflowing
+//! values, and callers can send any of them, e.g. `wait_timeout: 1e300`.
+//! Waits go through these helpers, which cap them at [`MAX_WAIT_SECS`].
+
+use std::time::Duration;
+
+/// Longest wait a tool call can ask for
+pub const MAX_WAIT_SECS: f64 = 3600.0;
+
+/// `secs` as a duration, with negative values (and NaN) read as 0 and large ones capped
+pub fn clamped_wait(secs: f64) -> Duration {
+    Duration::try_from_secs_f64(secs.clamp(0.0, MAX_WAIT_SECS)).unwrap_or_default()
+}
+
+/// `secs` from the argument `name` as a duration, capped at [`MAX_WAIT_SECS`];
+/// negative values are refused
+pub fn wait_duration(name: &str, secs: f64) -> Result<Duration, String> {
+    if secs.is_nan() || secs < 0.0 {
+        return Err(format!("{name} must be a non-negative number of seconds, got {secs}"));
+    }
+    Ok(clamped_wait(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waits_never_panic() {
+        assert_eq!(clamped_wait(1.5), Duration::from_millis(1500));
+        assert_eq!(clamped_wait(-3.0), Duration::ZERO);
+        assert_eq!(clamped_wait(f64::NAN), Duration::ZERO);
+        assert_eq!(clamped_wait(1e300), Duration::from_secs(3600));
+        assert_eq!(clamped_wait(f64::INFINITY), Duration::from_secs(3600));
+
+        assert!(wait_duration("timeout_seconds", -1.0).unwrap_err().contains("timeout_seconds"));
+        assert!(wait_duration("timeout_seconds", f64::NAN).is_err());
+        assert_eq!(wait_duration("timeout_seconds", 1e300), Ok(Duration::from_secs(3600)));
+    }
+}
@@ -0,0 +1,54 @@
+//! Bot challenge detection against the fixture pages, in a real browser:
+//!
+//! `cargo test --features test-fixtures --test challenge_detection`
+
+use rmcp::{ServiceExt, model::CallToolRequestParam, object};
+use rust_browser_mcp::{FixtureServer, WebDriverServer};
+
+fn text_of(result: &rmcp::model::CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|content| content.raw.as_text().map(|text| text.text.clone()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tokio::test]
+async fn test_navigate_reports_challenges() -> Result<(), Box<dyn std::error::Error>> {
+    let fixtures = FixtureServer::start().await?;
+    let server = WebDriverServer::new()?;
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(async move {
+        if let Ok(running) = server.serve(server_io).await {
+            let _ = running.waiting().await;
+        }
+    });
+    let client = ().serve(client_io).await?;
+    let navigate = |path: &str| {
+        let arguments = object!({ "url": fixtures.url(path) });
+        client.call_tool(CallToolRequestParam { name: "navigate".into(), arguments: Some(arguments) })
+    };
+
+    // A page protected by invisible reCAPTCHA only shows its badge and must load normally
+    let invisible = navigate("/challenge/invisible").await?;
+    if invisible.is_error == Some(true) && !text_of(&invisible).contains("BLOCKED_BY_CHALLENGE") {
+        eprintln!("Skipping: no browser available ({})", text_of(&invisible));
+        client.cancel().await?;
+        return Ok(());
+    }
+    assert_ne!(invisible.is_error, Some(true), "{}", text_of(&invisible));
+
+    let captcha = navigate("/challenge/captcha").await?;
+    assert_eq!(captcha.is_error, Some(true));
+    assert!(text_of(&captcha).contains("BLOCKED_BY_CHALLENGE"), "{}", text_of(&captcha));
+    assert!(text_of(&captcha).contains("reCAPTCHA"), "{}", text_of(&captcha));
+
+    let interstitial = navigate("/challenge/interstitial").await?;
+    assert_eq!(interstitial.is_error, Some(true));
+    assert!(text_of(&interstitial).contains("BLOCKED_BY_CHALLENGE"), "{}", text_of(&interstitial));
+    assert!(text_of(&interstitial).contains("Cloudflare"), "{}", text_of(&interstitial));
+
+    client.cancel().await?;
+    Ok(())
+}
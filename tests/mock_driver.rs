@@ -0,0 +1,640 @@
+//! Runs MCP tool calls end to end against the in-process mock driver, so it
+//! needs neither a browser nor a driver binary:
+//!
+//! `cargo test --features mock-driver --test mock_driver`
+#![cfg(feature = "mock-driver")]
+
+use rmcp::{
+    ServiceExt,
+    model::{CallToolRequestParam, CallToolResult, JsonObject},
+    object,
+};
+use rust_browser_mcp::{ExecutionContext, MockDriver, MockElement, MockPage, Recipe, RecipeExecutor, WebDriverServer};
+use serde_json::json;
+
+type Client = rmcp::service::RunningService<rmcp::RoleClient, ()>;
+
+fn text_of(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|content| content.raw.as_text().map(|text| text.text.clone()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// MCP client talking to `server` over an in-memory pipe
+async fn connect(server: WebDriverServer) -> Result<Client, Box<dyn std::error::Error>> {
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(async move {
+        if let Ok(running) = server.serve(server_io).await {
+            let _ = running.waiting().await;
+        }
+    });
+    Ok(().serve(client_io).await?)
+}
+
+/// Call tool `name` with `arguments`
+async fn call(client: &Client, name: &str, arguments: JsonObject) -> Result<CallToolResult, rmcp::ServiceError> {
+    client.call_tool(CallToolRequestParam { name: name.to_string().into(), arguments: Some(arguments) }).await
+}
+
+/// Load `url` in the default session, failing the test if that does not work
+async fn navigate(client: &Client, url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let navigated = call(client, "navigate", object!({ "url": url })).await?;
+    assert_ne!(navigated.is_error, Some(true), "{}", text_of(&navigated));
+    Ok(())
+}
+
+/// Client of a server with `driver`'s default config, showing `url`
+async fn open(driver: &MockDriver, url: &str) -> Result<Client, Box<dyn std::error::Error>> {
+    driver.add_page(url, MockPage::new("Shop"));
+    let client = connect(WebDriverServer::with_config(driver.config())?).await?;
+    navigate(&client, url).await?;
+    Ok(client)
+}
+
+/// Number of scripts the driver has run so far
+fn scripts_run(driver: &MockDriver) -> usize {
+    driver.commands().iter().filter(|command| *command == "execute_script").count()
+}
+
+#[tokio::test]
+async fn test_tools_against_mock_driver() -> Result<(), Box<dyn std::error::Error>> {
+    let driver = MockDriver::start().await?;
+    driver.add_page(
+        "https://shop.test/",
+        MockPage::new("Shop")
+            .element(MockElement::new("h1").tag("h1").text("Welcome to the shop"))
+            .element(MockElement::new("#buy").tag("button").text("Buy").navigates_to("https://shop.test/thanks")),
+    );
+    driver.add_page("https://shop.test/thanks", MockPage::new("Thank you"));
+
+    let mut config = driver.config();
+    config.default_wait_timeout_secs = 0.5;
+    let client = connect(WebDriverServer::with_config(config)?).await?;
+    let call = |name: &'static str, arguments| call(&client, name, arguments);
+
+    let navigated = call("navigate", object!({ "url": "https://shop.test/" })).await?;
+    assert_ne!(navigated.is_error, Some(true), "{}", text_of(&navigated));
+
+    let heading = call("get_text", object!({ "selector": "h1" })).await?;
+    assert!(text_of(&heading).contains("Welcome to the shop"));
+
+    let batch = call(
+        "run_batch",
+        object!({ "steps": [
+            { "tool": "get_text", "arguments": { "selector": "h1" } },
+            { "tool": "get_title" }
+        ] }),
+    )
+    .await?;
+    assert!(text_of(&batch).contains("Ran 2 of 2 step(s), 0 failed"), "{}", text_of(&batch));
+    assert!(text_of(&batch).contains("Welcome to the shop"));
+
+    call("click", object!({ "selector": "#buy" })).await?;
+    let title = call("get_title", object!({})).await?;
+    assert!(text_of(&title).contains("Thank you"));
+
+    let missing = call("click", object!({ "selector": "#sold-out" })).await?;
+    assert_eq!(missing.is_error, Some(true));
+
+    let popups = call("get_popup_events", object!({})).await?;
+    assert!(text_of(&popups).contains("0 popup event(s), policy 'allow'"), "{}", text_of(&popups));
+
+    let maximized = call("maximize_window", object!({})).await?;
+    assert!(text_of(&maximized).contains("Window 1280x720"), "{}", text_of(&maximized));
+
+    driver.crash_page();
+    let crashed = call("get_title", object!({})).await?;
+    assert_eq!(crashed.is_error, Some(true));
+    assert!(text_of(&crashed).contains("SESSION_CRASHED"), "{}", text_of(&crashed));
+    let recovered = call("navigate", object!({ "url": "https://shop.test/" })).await?;
+    assert_ne!(recovered.is_error, Some(true), "{}", text_of(&recovered));
+
+    assert!(driver.commands().iter().any(|command| command == "click #buy"));
+    assert!(driver.commands().iter().any(|command| command == "new_window"));
+    assert!(driver.commands().iter().any(|command| command == "maximize_window"));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancelled_wait_stops_polling() -> Result<(), Box<dyn std::error::Error>> {
+    let driver = MockDriver::start().await?;
+    let client = open(&driver, "https://shop.test/").await?;
+
+    // The condition never holds, so only the cancellation can end the wait before its 30s
+    let wait = CallToolRequestParam {
+        name: "wait_for_condition".into(),
+        arguments: Some(object!({ "condition": "return window.__neverReady", "timeout_seconds": 30 })),
+    };
+    let request = rmcp::model::ClientRequest::CallToolRequest(rmcp::model::Request::new(wait));
+    let handle = client
+        .peer()
+        .send_cancellable_request(request, rmcp::service::PeerRequestOptions::no_options())
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert!(scripts_run(&driver) > 0, "the condition was polled");
+    handle.cancel(Some("test".to_string())).await?;
+
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    let polled = scripts_run(&driver);
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(scripts_run(&driver), polled, "polling stopped once the request was cancelled");
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancelled_recipe_stops_between_and_during_steps() -> Result<(), Box<dyn std::error::Error>> {
+    let driver = MockDriver::start().await?;
+    driver.add_page("https://shop.test/", MockPage::new("Shop"));
+    let server = WebDriverServer::with_config(driver.config())?;
+    // A `true` condition makes the step wait out its whole timeout
+    let recipe = Recipe::from_json(
+        r#"{
+            "name": "slow_checkout",
+            "version": "1.0.0",
+            "browsers": ["chrome"],
+            "steps": [
+                { "action": "navigate", "arguments": { "url": "https://shop.test/" } },
+                { "action": "wait_for_condition", "arguments": { "condition": "true", "timeout_seconds": 30 } }
+            ]
+        }"#,
+    )?;
+    let context = || ExecutionContext { session_id: None, variables: Default::default(), continue_on_error: false };
+
+    // Cancelled before it starts: nothing reaches the driver
+    let cancelled = tokio_util::sync::CancellationToken::new();
+    cancelled.cancel();
+    let result = RecipeExecutor::new(&server)
+        .with_cancellation(cancelled)
+        .execute_recipe(&recipe, None, context())
+        .await?;
+    assert!(!result.success);
+    assert_eq!(result.error_message.as_deref(), Some("Recipe execution cancelled"));
+    assert!(!driver.commands().iter().any(|command| command.starts_with("navigate")));
+
+    // Cancelled mid-wait: the run ends well before the step's 30s timeout
+    let cancellation = tokio_util::sync::CancellationToken::new();
+    let cancel = cancellation.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        cancel.cancel();
+    });
+    let started = std::time::Instant::now();
+    let result = RecipeExecutor::new(&server)
+        .with_cancellation(cancellation)
+        .execute_recipe(&recipe, None, context())
+        .await?;
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    assert!(!result.success);
+    assert!(driver.commands().iter().any(|command| command == "navigate https://shop.test/"));
+    let waited = result.step_results.iter().find(|step| step.action == "wait_for_condition").expect("wait step ran");
+    assert!(!waited.success);
+    assert!(waited.error_message.as_deref().is_some_and(|error| error.contains("cancelled")), "{waited:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_elements_collects_metadata_in_one_script() -> Result<(), Box<dyn std::error::Error>> {
+    const FIND_SCRIPT: &str = "return { total: matches.length, elements }";
+
+    let driver = MockDriver::start().await?;
+    driver.on_script(
+        FIND_SCRIPT,
+        json!({
+            "total": 3,
+            "elements": [
+                { "tag_name": "li", "text": "Mug" },
+                { "tag_name": "li", "text": "Plate", "element": { "element-6066-11e4-a52e-4f735466cecf": "node-2" } },
+            ],
+        }),
+    );
+    let client = open(&driver, "https://shop.test/cart").await?;
+
+    let before = driver.commands().len();
+    let found = call(&client, "find_elements", object!({ "selector": "li", "parent_selector": "#cart", "limit": 2 }))
+        .await?;
+    let text = text_of(&found);
+    assert!(text.contains("Found 3 element(s) matching 'li' within parent '#cart'"), "{text}");
+    assert!(text.contains("2. <li> - Text: \"Plate\" [handle: el-"), "{text}");
+    assert!(text.contains("showing the first 2 of 3; raise `limit` to see more"), "{text}");
+    let commands = &driver.commands()[before..];
+    assert_eq!(commands.iter().filter(|command| *command == "execute_script").count(), 1, "{commands:?}");
+    assert!(!commands.iter().any(|command| command.starts_with("find ")), "no per-element lookups: {commands:?}");
+
+    // Without a limit the script reads at most 100 elements
+    call(&client, "find_elements", object!({ "selector": "li" })).await?;
+    assert_eq!(driver.script_args(FIND_SCRIPT), [json!(["li", "#cart", 2]), json!(["li", null, 100])]);
+
+    // Only a parent is waited for, through ordinary element lookups
+    let wait = object!({ "selector": "li", "parent_selector": "#wishlist", "wait_timeout": 0.2 });
+    let waited = call(&client, "find_elements", wait).await?;
+    assert_eq!(waited.is_error, Some(true));
+    assert!(text_of(&waited).contains("Parent element '#wishlist' not found within 0.2s"), "{}", text_of(&waited));
+    assert!(driver.commands().iter().any(|command| command == "find #wishlist"));
+    assert_eq!(driver.script_args(FIND_SCRIPT).len(), 2, "no script once the parent is missing");
+    client.cancel().await?;
+
+    let driver = MockDriver::start().await?;
+    driver.on_script(FIND_SCRIPT, json!({ "error": "no such element" }));
+    let client = open(&driver, "https://shop.test/cart").await?;
+    let missing = call(&client, "find_elements", object!({ "selector": "li", "parent_selector": "#wishlist" })).await?;
+    assert_eq!(missing.is_error, Some(true));
+    let text = text_of(&missing);
+    assert!(text.contains("Parent element '#wishlist' not found: no such element"), "{text}");
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_text_scoping_and_all_matches() -> Result<(), Box<dyn std::error::Error>> {
+    const ALL_TEXTS_SCRIPT: &str = "return { total: matches.length, texts }";
+
+    let driver = MockDriver::start().await?;
+    driver.add_page(
+        "https://shop.test/cart",
+        MockPage::new("Cart")
+            .element(MockElement::new("#cart").tag("ul"))
+            .element(MockElement::new(".item").tag("li").text("  Blue\n   mug \t 250 ml  ")),
+    );
+    driver.on_script(ALL_TEXTS_SCRIPT, json!({ "total": 1200, "texts": ["  Blue\n mug ", "Plate"] }));
+    let client = connect(WebDriverServer::with_config(driver.config())?).await?;
+    navigate(&client, "https://shop.test/cart").await?;
+
+    // A single match is looked up inside its parent
+    let raw = call(&client, "get_text", object!({ "selector": ".item", "parent_selector": "#cart" })).await?;
+    assert!(text_of(&raw).contains("Blue\n   mug"), "{}", text_of(&raw));
+    let commands = driver.commands();
+    let parent = commands.iter().position(|command| command == "find #cart").expect("parent looked up");
+    assert_eq!(commands[parent + 1], "find .item");
+
+    let outside = call(&client, "get_text", object!({ "selector": ".item", "parent_selector": "#wishlist" })).await?;
+    assert_eq!(outside.is_error, Some(true));
+    assert!(text_of(&outside).contains("Failed to find parent element #wishlist"), "{}", text_of(&outside));
+
+    // Cut by characters after collapsing whitespace
+    let short = object!({ "selector": ".item", "normalize_whitespace": true, "max_length": 8 });
+    let tidy = call(&client, "get_text", short).await?;
+    assert!(text_of(&tidy).contains("Element text: Blue mug (session: "), "{}", text_of(&tidy));
+    assert!(text_of(&tidy).contains("\"truncated\":true"), "{}", text_of(&tidy));
+
+    // Every match is read by one script, capped at 1000
+    let all = object!({ "selector": ".item", "all": true, "parent_selector": "#cart" });
+    let all = call(&client, "get_text", all).await?;
+    assert_eq!(driver.script_args(ALL_TEXTS_SCRIPT), [json!([".item", "#cart", 1000])]);
+    let text = text_of(&all);
+    assert!(text.contains("Text of 2 element(s) matching '.item' within '#cart'"), "{text}");
+    assert!(text.contains("... only the first 2 of 1200 matches are read"), "{text}");
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_workers_and_cache_settings() -> Result<(), Box<dyn std::error::Error>> {
+    const UNREGISTER_SCRIPT: &str = "const [scope, clearCaches, done] = arguments";
+
+    let driver = MockDriver::start().await?;
+    driver.on_script(UNREGISTER_SCRIPT, json!({ "unregistered": [], "cleared_caches": [] }));
+    let client = open(&driver, "https://shop.test/").await?;
+
+    let removed = call(&client, "unregister_service_workers", object!({ "scope": "https://shop.test/app/" })).await?;
+    assert_ne!(removed.is_error, Some(true), "{}", text_of(&removed));
+    call(&client, "unregister_service_workers", object!({ "clear_caches": true })).await?;
+    assert_eq!(driver.script_args(UNREGISTER_SCRIPT), [json!(["https://shop.test/app/", false]), json!([null, true])]);
+
+    // Like geckodriver, the mock has no DevTools Protocol until it is enabled
+    let refused = call(&client, "set_cache_disabled", object!({ "disabled": true })).await?;
+    assert_eq!(refused.is_error, Some(true));
+    assert!(text_of(&refused).contains("needs a Chrome or Edge session"), "{}", text_of(&refused));
+
+    driver.enable_cdp();
+    let cdp_commands = || -> Vec<String> {
+        driver.commands().into_iter().filter(|command| command.starts_with("cdp ")).collect()
+    };
+    call(&client, "set_cache_disabled", object!({ "disabled": true })).await?;
+    assert_eq!(cdp_commands(), ["cdp Network.enable", "cdp Network.setCacheDisabled"]);
+    let bypassed = call(&client, "set_cache_disabled", object!({ "disabled": false, "bypass_service_worker": true }))
+        .await?;
+    assert_ne!(bypassed.is_error, Some(true), "{}", text_of(&bypassed));
+    assert_eq!(
+        cdp_commands()[2..],
+        ["cdp Network.enable", "cdp Network.setCacheDisabled", "cdp Network.setBypassServiceWorker"]
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_screenshot_on_change_without_a_change() -> Result<(), Box<dyn std::error::Error>> {
+    const WATCH_SCRIPT: &str = "const [selector, waitMs, reset, done] = arguments";
+
+    let driver = MockDriver::start().await?;
+    let client = open(&driver, "https://shop.test/cart").await?;
+
+    // Nothing changes, so the watch polls until the timeout and captures anyway
+    let watch = object!({ "selector": ".toast", "wait_timeout": 0.2 });
+    let unchanged = call(&client, "screenshot_on_change", watch).await?;
+    let text = text_of(&unchanged);
+    assert!(text.contains("No change to .toast within 0.2s; captured anyway"), "{text}");
+    assert!(text.contains("\"changed\":false"), "{text}");
+    assert!(driver.commands().iter().any(|command| command == "screenshot"));
+    let watches = driver.script_args(WATCH_SCRIPT);
+    assert!(watches.len() > 1, "{watches:?}");
+    assert_eq!(watches[0][0], ".toast");
+    assert!(watches[0][1].as_u64().is_some_and(|wait_ms| wait_ms <= 200), "{watches:?}");
+    // Only the first call replaces an observer left by an earlier watch
+    assert_eq!(watches[0][2], true);
+    assert!(watches[1..].iter().all(|args| args[2] == false), "{watches:?}");
+    client.cancel().await?;
+
+    let driver = MockDriver::start().await?;
+    driver.on_script(WATCH_SCRIPT, json!({ "error": "Invalid selector: .toast[" }));
+    let client = open(&driver, "https://shop.test/cart").await?;
+    let invalid = call(&client, "screenshot_on_change", object!({ "selector": ".toast[" })).await?;
+    assert_eq!(invalid.is_error, Some(true));
+    let text = text_of(&invalid);
+    assert!(text.contains("INVALID_ARGUMENT"), "{text}");
+    assert!(text.contains("Failed to watch .toast[: Execution error: Invalid selector"), "{text}");
+    assert!(!driver.commands().iter().any(|command| command == "screenshot"));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_summarize_page_limits() -> Result<(), Box<dyn std::error::Error>> {
+    const SUMMARY_SCRIPT: &str = "const [excerptLength, maxHeadings] = arguments";
+
+    let driver = MockDriver::start().await?;
+    let summary = json!({ "title": "Shop", "headings_total": 3, "forms": 1, "links": { "total": 8 } });
+    driver.on_script(SUMMARY_SCRIPT, summary);
+    let client = open(&driver, "https://shop.test/").await?;
+
+    let summary = call(&client, "summarize_page", object!({})).await?;
+    assert!(text_of(&summary).contains("\"session\":\"default\""), "{}", text_of(&summary));
+    call(&client, "summarize_page", object!({ "excerpt_length": 100, "max_headings": 5 })).await?;
+    assert_eq!(driver.script_args(SUMMARY_SCRIPT), [json!([500, 30]), json!([100, 5])]);
+
+    let negative = call(&client, "summarize_page", object!({ "max_headings": -1 })).await;
+    assert!(negative.is_err(), "a negative limit is rejected");
+    let elsewhere = call(&client, "summarize_page", object!({ "session_id": "checkout" })).await?;
+    assert_eq!(elsewhere.is_error, Some(true));
+    assert_eq!(driver.script_args(SUMMARY_SCRIPT).len(), 2);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_page_text_limits() -> Result<(), Box<dyn std::error::Error>> {
+    const PAGE_TEXT_SCRIPT: &str = "return root.innerText ?? root.textContent";
+
+    let driver = MockDriver::start().await?;
+    let client = open(&driver, "https://shop.test/").await?;
+
+    // The script yields null when the selector matches nothing
+    let missing = call(&client, "get_page_text", object!({ "selector": "#missing" })).await?;
+    assert_eq!(missing.is_error, Some(true));
+    assert!(text_of(&missing).contains("Failed to find element #missing"), "{}", text_of(&missing));
+    let no_body = call(&client, "get_page_text", object!({})).await?;
+    assert!(text_of(&no_body).contains("Failed to find element body"), "{}", text_of(&no_body));
+    assert_eq!(driver.script_args(PAGE_TEXT_SCRIPT), [json!(["#missing"]), json!([null])]);
+    client.cancel().await?;
+
+    // Without max_length the text is cut at 20000 characters, counted rather than bytes
+    let driver = MockDriver::start().await?;
+    driver.on_script(PAGE_TEXT_SCRIPT, json!(format!("{}\n\n  tail", "é".repeat(20_005))));
+    let client = open(&driver, "https://shop.test/").await?;
+    let long = call(&client, "get_page_text", object!({})).await?;
+    let text = text_of(&long);
+    assert!(text.contains("Page text, 20013 character(s) (session: default), cut to the first 20000:"));
+    assert!(!text.contains("tail"));
+
+    let tidy = call(&client, "get_page_text", object!({ "normalize_whitespace": true, "max_length": 30_000 })).await?;
+    let text = text_of(&tidy);
+    assert!(text.contains("Page text, 20010 character(s) (session: default):"));
+    assert!(text.contains("é tail"));
+    assert!(text.contains("\"truncated\":false"));
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_wait_for_dom_stable_observes_and_stops() -> Result<(), Box<dyn std::error::Error>> {
+    const OBSERVE: &str = "const state = { last: performance.now(), mutations: 0 }";
+    const IDLE: &str = "return state ? { idle_ms";
+    const STOP: &str = "delete window.__mcpDomStable";
+
+    let driver = MockDriver::start().await?;
+    let client = open(&driver, "https://shop.test/feed").await?;
+    let missing = call(&client, "wait_for_dom_stable", object!({ "selector": "#feed" })).await?;
+    assert_eq!(missing.is_error, Some(true));
+    assert!(text_of(&missing).contains("Failed to find element #feed"), "{}", text_of(&missing));
+    assert_eq!(driver.script_args(OBSERVE), [json!(["#feed"])]);
+    assert!(driver.script_args(IDLE).is_empty(), "no polling without an observer");
+    client.cancel().await?;
+
+    // The idle script answers null, as after a navigation dropped the observer,
+    // so the observer is put back on each poll until the wait times out
+    let driver = MockDriver::start().await?;
+    driver.on_script(OBSERVE, json!(true));
+    let client = open(&driver, "https://shop.test/feed").await?;
+    let timed_out = call(&client, "wait_for_dom_stable", object!({ "quiet_ms": 500, "timeout_seconds": 0.3 })).await?;
+    assert_eq!(timed_out.is_error, Some(true));
+    let text = text_of(&timed_out);
+    assert!(text.contains("TIMEOUT"), "{text}");
+    assert!(text.contains("DOM did not stay unchanged for 500ms within 0.3s; 0 mutation(s) seen"), "{text}");
+    let polls = driver.script_args(IDLE).len();
+    assert!(polls >= 2, "{polls} poll(s)");
+    assert_eq!(driver.script_args(OBSERVE).len(), polls + 1);
+    assert_eq!(driver.script_args(STOP).len(), 1, "the observer is removed after a timeout");
+    client.cancel().await?;
+
+    let driver = MockDriver::start().await?;
+    driver.on_script(OBSERVE, json!(true));
+    driver.on_script(IDLE, json!({ "idle_ms": 800, "mutations": 2 }));
+    let client = open(&driver, "https://shop.test/feed").await?;
+    let stable = call(&client, "wait_for_dom_stable", object!({ "quiet_ms": 500 })).await?;
+    let text = text_of(&stable);
+    assert!(text.contains("DOM stable for 500ms after "), "{text}");
+    assert!(text.contains("2 mutation(s) seen"), "{text}");
+    assert_eq!(driver.script_args(IDLE).len(), 1, "an already quiet page needs one poll");
+    assert_eq!(driver.script_args(STOP).len(), 1);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cold_load_clears_caches_around_the_navigation() -> Result<(), Box<dyn std::error::Error>> {
+    let driver = MockDriver::start().await?;
+    driver.add_page("https://shop.test/", MockPage::new("Shop"));
+    let client = connect(WebDriverServer::with_config(driver.config())?).await?;
+    let cold_load = object!({ "url": "https://shop.test/", "cold_load": true });
+
+    // Without the DevTools Protocol a cold load is refused rather than quietly warm
+    let refused = call(&client, "navigate", cold_load.clone()).await?;
+    assert_eq!(refused.is_error, Some(true));
+    assert!(text_of(&refused).contains("needs a Chrome or Edge session"), "{}", text_of(&refused));
+    assert!(!driver.commands().iter().any(|command| command.starts_with("navigate")));
+
+    driver.enable_cdp();
+    let loaded = call(&client, "navigate", cold_load).await?;
+    let text = text_of(&loaded);
+    assert!(text.contains("Cold load with http_cache, service_workers, cache_storage cleared"), "{text}");
+    let sequence: Vec<String> = driver
+        .commands()
+        .into_iter()
+        .filter(|command| command.starts_with("cdp ") || command.starts_with("navigate "))
+        .collect();
+    assert_eq!(
+        sequence,
+        [
+            "cdp Network.enable",
+            "cdp Network.clearBrowserCache",
+            "cdp Storage.clearDataForOrigin",
+            "cdp Network.setCacheDisabled",
+            "cdp Network.setBypassServiceWorker",
+            "navigate https://shop.test/",
+            "cdp Network.setCacheDisabled",
+            "cdp Network.setBypassServiceWorker",
+        ]
+    );
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_wait_for_attribute_and_property() -> Result<(), Box<dyn std::error::Error>> {
+    const VALUE_SCRIPT: &str = "const [selector, kind, name, expected, pattern, absent] = arguments";
+
+    let driver = MockDriver::start().await?;
+    driver.on_script(VALUE_SCRIPT, json!({ "found": true, "matched": true, "value": "false" }));
+    let client = open(&driver, "https://shop.test/list").await?;
+    let idle = object!({ "selector": "#list", "attribute": "aria-busy", "value": "false" });
+    let done = call(&client, "wait_for_attribute", idle).await?;
+    assert!(text_of(&done).contains("Attribute 'aria-busy' of '#list' is \"false\" after "), "{}", text_of(&done));
+    let removed = object!({ "selector": "#spinner", "attribute": "hidden", "absent": true });
+    call(&client, "wait_for_attribute", removed).await?;
+    let counted = object!({ "selector": "#list", "property": "dataset.count", "value": 3 });
+    call(&client, "wait_for_property", counted).await?;
+    assert_eq!(
+        driver.script_args(VALUE_SCRIPT),
+        [
+            json!(["#list", "attribute", "aria-busy", "false", null, false]),
+            json!(["#spinner", "attribute", "hidden", null, null, true]),
+            json!(["#list", "property", "dataset.count", 3, null, false]),
+        ]
+    );
+
+    let both = object!({ "selector": "#list", "attribute": "aria-busy", "value": "false", "absent": true });
+    assert!(call(&client, "wait_for_attribute", both).await.is_err(), "value and absent together are rejected");
+    let both = object!({ "selector": "#list", "property": "value", "value": "a", "pattern": "^a" });
+    assert!(call(&client, "wait_for_property", both).await.is_err(), "value and pattern together are rejected");
+    assert_eq!(driver.script_args(VALUE_SCRIPT).len(), 3);
+    client.cancel().await?;
+
+    let driver = MockDriver::start().await?;
+    driver.on_script(VALUE_SCRIPT, json!({ "found": false, "matched": false, "value": null }));
+    let client = open(&driver, "https://shop.test/list").await?;
+    let hidden = object!({ "selector": "#spinner", "attribute": "hidden", "timeout_seconds": 0.2 });
+    let timed_out = call(&client, "wait_for_attribute", hidden).await?;
+    assert_eq!(timed_out.is_error, Some(true));
+    let text = text_of(&timed_out);
+    assert!(text.contains("Attribute 'hidden' of '#spinner' did not appear within 0.2s"), "{text}");
+    assert!(text.contains("no element matches the selector"), "{text}");
+    client.cancel().await?;
+
+    let driver = MockDriver::start().await?;
+    driver.on_script(VALUE_SCRIPT, json!({ "error": "SyntaxError: Invalid regular expression" }));
+    let client = open(&driver, "https://shop.test/list").await?;
+    let pattern = object!({ "selector": "#list", "attribute": "class", "pattern": "(" });
+    let invalid = call(&client, "wait_for_attribute", pattern).await?;
+    assert_eq!(invalid.is_error, Some(true));
+    let text = text_of(&invalid);
+    assert!(text.contains("INVALID_ARGUMENT"), "{text}");
+    assert!(text.contains("Cannot wait for Attribute 'class' of '#list': SyntaxError"), "{text}");
+    assert_eq!(driver.script_args(VALUE_SCRIPT).len(), 1, "an invalid pattern is not retried");
+    client.cancel().await?;
+    Ok(())
+}
+
+/// Tool calls for different sessions run side by side
+#[tokio::test]
+async fn test_sessions_run_in_parallel() -> Result<(), Box<dyn std::error::Error>> {
+    const SESSIONS: [&str; 4] = ["parallel_1", "parallel_2", "parallel_3", "parallel_4"];
+    let load_time = std::time::Duration::from_millis(500);
+
+    let driver = MockDriver::start().await?;
+    driver.add_page("https://slow.test/", MockPage::new("Slow").load_time(load_time));
+    let client = connect(WebDriverServer::with_config(driver.config())?).await?;
+    let navigate = |session: &'static str| {
+        call(&client, "navigate", object!({ "url": "https://slow.test/", "session_id": session }))
+    };
+    // Open the sessions first, so only page loads are timed
+    for session in SESSIONS {
+        call(&client, "create_session", object!({ "session_id": session })).await?;
+    }
+
+    let started = std::time::Instant::now();
+    for session in SESSIONS {
+        let navigated = navigate(session).await?;
+        assert_ne!(navigated.is_error, Some(true), "{}", text_of(&navigated));
+    }
+    let serial = started.elapsed();
+
+    let started = std::time::Instant::now();
+    for navigated in futures::future::join_all(SESSIONS.map(navigate)).await {
+        let navigated = navigated?;
+        assert_ne!(navigated.is_error, Some(true), "{}", text_of(&navigated));
+    }
+    let parallel = started.elapsed();
+
+    let sessions = SESSIONS.len();
+    assert!(serial >= load_time * sessions as u32, "one by one took {serial:?}");
+    assert!(
+        parallel < load_time * 2,
+        "{sessions} sessions loading a {load_time:?} page took {parallel:?} at once, {serial:?} one by one"
+    );
+    assert_eq!(driver.session_count(), sessions);
+    client.cancel().await?;
+    Ok(())
+}
+
+/// `stop_driver` refuses while its driver serves sessions, naming only the
+/// caller's own, and with `force` closes every one of them before stopping
+#[tokio::test]
+async fn test_stop_driver_refuses_then_forces() -> Result<(), Box<dyn std::error::Error>> {
+    use rust_browser_mcp::{ClientManager, DriverType};
+
+    let driver = MockDriver::start().await?;
+    let mut config = driver.config();
+    // Managed drivers are reached at localhost, like the ones the server starts
+    config.webdriver_endpoint = format!("http://localhost:{}", driver.port());
+    let server = WebDriverServer::with_config(config)?;
+    let manager = server.get_client_manager().clone();
+    let stand_in = tokio::process::Command::new("sleep").arg("600").kill_on_drop(true).spawn()?;
+    manager.get_driver_manager().adopt_process(DriverType::Chrome, stand_in, driver.port()).await;
+    let client = connect(server).await?;
+
+    call(&client, "create_session", object!({ "session_id": "mine" })).await?;
+    ClientManager::with_principal(Some("bob".to_string()), manager.get_or_create_client(Some("theirs".to_string())))
+        .await?;
+    assert_eq!(driver.session_count(), 2);
+
+    let refused = call(&client, "stop_driver", object!({ "driver_type": "chrome" })).await?;
+    assert_eq!(refused.is_error, Some(true));
+    let text = text_of(&refused);
+    assert!(text.contains("SESSIONS_ACTIVE"), "{text}");
+    assert!(text.contains("mine, 1 session(s) of other users"), "{text}");
+    assert!(!text.contains("theirs"), "{text}");
+    assert_eq!(driver.session_count(), 2);
+
+    let forced = call(&client, "stop_driver", object!({ "driver_type": "chrome", "force": true })).await?;
+    assert_ne!(forced.is_error, Some(true), "{}", text_of(&forced));
+    assert!(text_of(&forced).contains("after closing mine, 1 session(s) of other users"), "{}", text_of(&forced));
+    assert_eq!(driver.session_count(), 0);
+    assert!(manager.list_sessions().await.is_empty());
+    client.cancel().await?;
+    Ok(())
+}
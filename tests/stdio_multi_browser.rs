@@ -1,5 +1,6 @@
 mod common;
 use common::{TestClient, TestTimer};
+use rust_browser_mcp::FixtureServer;
 
 /// Integration test for using multiple browser drivers via stdio
 /// Tests that we can start both Chrome and Firefox and switch between them
@@ -7,6 +8,7 @@ use common::{TestClient, TestTimer};
 async fn test_multi_browser_stdio_integration() {
     println!("🚀 Testing multi-browser stdio integration (Chrome + Firefox)");
     
+    let fixtures = FixtureServer::start().await.expect("Failed to start fixture server");
     let client = TestClient::new().await.expect("Failed to create test client");
     
     if let Some(server_info) = client.server_info() {
@@ -43,7 +45,7 @@ async fn test_multi_browser_stdio_integration() {
         println!("🌐 Test 3: Navigate with Chrome");
         let nav_timer = TestTimer::new();
         
-        match client.navigate("https://example.com", Some("chrome_session")).await {
+        match client.navigate(&fixtures.url("/"), Some("chrome_session")).await {
             Ok(result) => {
                 println!("  Chrome navigation time: {:.2}ms", nav_timer.elapsed_ms());
                 
@@ -67,7 +69,7 @@ async fn test_multi_browser_stdio_integration() {
         println!("🦊 Test 4: Navigate with Firefox");
         let nav_timer = TestTimer::new();
         
-        match client.navigate("https://httpbin.org/html", Some("firefox_session")).await {
+        match client.navigate(&fixtures.url("/form"), Some("firefox_session")).await {
             Ok(result) => {
                 println!("  Firefox navigation time: {:.2}ms", nav_timer.elapsed_ms());
                 
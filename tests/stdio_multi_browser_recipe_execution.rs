@@ -40,10 +40,11 @@ async fn test_multi_browser_recipe_execution() {
     let screenshot_files = ["chrome_test.png", "firefox_test.png"];
     let mut artifacts_found = 0;
     
+    // Recipe screenshots are saved below the server's artifacts directory, not the CWD
     for filename in &screenshot_files {
-        if std::path::Path::new(filename).exists() {
-            let metadata = std::fs::metadata(filename).unwrap();
-            println!("  ✅ {} exists ({} bytes)", filename, metadata.len());
+        if let Some(path) = find_artifact(std::path::Path::new("artifacts"), filename) {
+            let metadata = std::fs::metadata(&path).unwrap();
+            println!("  ✅ {} exists ({} bytes)", path.display(), metadata.len());
             artifacts_found += 1;
         } else {
             println!("  ❌ {} not found", filename);
@@ -205,4 +206,24 @@ async fn test_force_cleanup_functionality() {
     
     assert!(cleanup_result.is_ok(), "Force cleanup should complete without errors");
     assert!(cleanup_effective, "Cleanup should be effective at reducing process count");
-}
\ No newline at end of file
+}
+
+/// Newest file named `filename` anywhere below `dir`
+fn find_artifact(dir: &std::path::Path, filename: &str) -> Option<std::path::PathBuf> {
+    let mut newest: Option<(std::time::SystemTime, std::path::PathBuf)> = None;
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        let found = if path.is_dir() {
+            find_artifact(&path, filename)
+        } else {
+            (entry.file_name() == filename).then_some(path)
+        };
+        if let Some(found) = found {
+            let modified = std::fs::metadata(&found).and_then(|m| m.modified()).ok()?;
+            if newest.as_ref().is_none_or(|(time, _)| modified > *time) {
+                newest = Some((modified, found));
+            }
+        }
+    }
+    newest.map(|(_, path)| path)
+}
@@ -3,6 +3,7 @@ use tokio::time::sleep;
 
 mod common;
 use common::{TestClient, TestTimer};
+use rust_browser_mcp::FixtureServer;
 
 /// Test sequential multi-browser screenshot capability
 /// This demonstrates the practical use case you described:
@@ -18,8 +19,10 @@ async fn test_sequential_multi_browser_screenshots() {
         println!("✅ Connected to server: {}", server_info.server_info.name);
     }
     
-    // Test website URL (using a simple, fast-loading site for testing)
-    let test_url = "https://example.com";
+    // Local fixture page, so the test does not depend on an external site
+    let fixtures = FixtureServer::start().await.expect("Failed to start fixture server");
+    let test_url = fixtures.url("/form");
+    let test_url = test_url.as_str();
     
     // Sequential browser testing
     let browsers = vec!["chrome", "firefox"];
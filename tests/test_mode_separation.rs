@@ -166,4 +166,32 @@ async fn test_tool_count_by_mode() -> Result<()> {
              stdio_tools.len(), http_tools.len(), expected_difference);
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_tool_category_filtering() -> Result<()> {
+    use rust_browser_mcp::tools::ToolDefinitions;
+
+    let mut config = Config::from_env();
+    config.disabled_tool_categories = vec!["scripting".to_string(), "performance".to_string()];
+    config.disabled_tools = vec!["hover".to_string()];
+    config.enabled_tools = vec!["wait_for_condition".to_string()];
+
+    let tools = ToolDefinitions::list_for_config(ServerMode::Stdio, &config);
+    let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
+
+    assert!(!names.contains(&"execute_script"), "scripting category should be disabled");
+    assert!(!names.contains(&"get_console_logs"), "performance category should be disabled");
+    assert!(!names.contains(&"hover"), "individually disabled tool should be hidden");
+    assert!(names.contains(&"wait_for_condition"), "enabled_tools should override the category");
+    assert!(names.contains(&"navigate"), "other categories should stay enabled");
+
+    assert!(!config.is_tool_enabled("execute_script"));
+    assert!(config.is_tool_enabled("click"));
+
+    config.disabled_tool_categories.push("not_a_category".to_string());
+    assert!(config.validate().is_err(), "unknown categories should be rejected");
+
+    println!("✅ Tool filtering: {} of {} tools enabled", tools.len(), ToolDefinitions::list_for_mode(ServerMode::Stdio).len());
+    Ok(())
+}
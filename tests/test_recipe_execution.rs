@@ -2,10 +2,12 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::Instant;
 use tokio;
-use rust_browser_mcp::{WebDriverServer, Recipe, RecipeStep, RecipeExecutor, ExecutionContext};
+use rust_browser_mcp::{WebDriverServer, Recipe, RecipeStep, RecipeExecutor, ExecutionContext, FixtureServer};
 
 #[tokio::test]
 async fn test_recipe_navigation_and_screenshot() {
+    let fixtures = FixtureServer::start().await.expect("Failed to start fixture server");
+
     // Create WebDriver server
     let server = match WebDriverServer::new() {
         Ok(server) => server,
@@ -24,14 +26,17 @@ async fn test_recipe_navigation_and_screenshot() {
         created_at: None,
         parameters: None,
         browsers: vec!["chrome".to_string()],
+        retry: None,
+        on_failure: Vec::new(),
+        isolation: None,
         steps: vec![
             RecipeStep {
-                name: Some("Navigate to example.com".to_string()),
+                name: Some("Navigate to the fixture index".to_string()),
                 description: None,
                 action: "navigate".to_string(),
                 arguments: {
                     let mut args = serde_json::Map::new();
-                    args.insert("url".to_string(), serde_json::Value::String("https://example.com".to_string()));
+                    args.insert("url".to_string(), serde_json::Value::String(fixtures.url("/")));
                     args
                 },
                 continue_on_error: None,
@@ -40,6 +45,7 @@ async fn test_recipe_navigation_and_screenshot() {
                 condition: None,
                 session_id: Some("test_session".to_string()),
                 browser: Some("chrome".to_string()),
+                browser_overrides: HashMap::new(),
             },
             RecipeStep {
                 name: Some("Take screenshot".to_string()),
@@ -56,6 +62,7 @@ async fn test_recipe_navigation_and_screenshot() {
                 condition: None,
                 session_id: Some("test_session".to_string()),
                 browser: Some("chrome".to_string()),
+                browser_overrides: HashMap::new(),
             },
         ],
     };
@@ -148,6 +155,7 @@ async fn test_recipe_navigation_and_screenshot() {
 #[tokio::test] 
 async fn test_direct_vs_recipe_comparison() {
     println!("\n🔬 DIRECT VS RECIPE COMPARISON TEST");
+    let fixtures = FixtureServer::start().await.expect("Failed to start fixture server");
     
     let server = match WebDriverServer::new() {
         Ok(server) => server,
@@ -165,8 +173,8 @@ async fn test_direct_vs_recipe_comparison() {
     let (_session, client) = client_manager.get_or_create_client(Some("direct_test".to_string())).await
         .expect("Should be able to create client");
     
-    println!("🌐 Navigating directly to example.com...");
-    client.goto("https://example.com").await
+    println!("🌐 Navigating directly to the fixture index...");
+    client.goto(&fixtures.url("/")).await
         .expect("Direct navigation should work");
     
     println!("📸 Taking direct screenshot...");
@@ -192,6 +200,9 @@ async fn test_direct_vs_recipe_comparison() {
         created_at: None,
         parameters: None,
         browsers: vec!["chrome".to_string()],
+        retry: None,
+        on_failure: Vec::new(),
+        isolation: None,
         steps: vec![
             RecipeStep {
                 name: Some("Navigate via recipe".to_string()),
@@ -199,7 +210,7 @@ async fn test_direct_vs_recipe_comparison() {
                 action: "navigate".to_string(),
                 arguments: {
                     let mut args = serde_json::Map::new();
-                    args.insert("url".to_string(), serde_json::Value::String("https://example.com".to_string()));
+                    args.insert("url".to_string(), serde_json::Value::String(fixtures.url("/")));
                     args
                 },
                 continue_on_error: None,
@@ -208,6 +219,7 @@ async fn test_direct_vs_recipe_comparison() {
                 condition: None,
                 session_id: Some("recipe_test".to_string()),
                 browser: Some("chrome".to_string()),
+                browser_overrides: HashMap::new(),
             },
             RecipeStep {
                 name: Some("Screenshot via recipe".to_string()),
@@ -224,6 +236,7 @@ async fn test_direct_vs_recipe_comparison() {
                 condition: None,
                 session_id: Some("recipe_test".to_string()),
                 browser: Some("chrome".to_string()),
+                browser_overrides: HashMap::new(),
             },
         ],
     };